@@ -73,6 +73,19 @@ export interface SimulationState {
   metrics: MetricsSnapshot
 }
 
+/** Changes since the last StateSync or StateDelta */
+export interface StateDelta {
+  time_s: number
+  events_processed: number
+  orders_completed: number
+  status?: string
+  speed?: number
+  robots?: RobotState[]
+  removed_robots?: number[]
+  stations?: StationState[]
+  metrics?: MetricsSnapshot
+}
+
 export interface PresetInfo {
   name: string
   description: string
@@ -157,12 +170,15 @@ export const useSimulationStore = defineStore('simulation', () => {
   }
 
   async function createSession(config: {
-    preset: string
+    preset?: string
+    scenario?: string | Record<string, unknown>
+    scenario_name?: string
     robot_count?: number
     order_rate?: number
     duration_minutes?: number
     grid_rows?: number
     grid_cols?: number
+    record_events?: boolean
   }) {
     const previousStatus = appStatus.value
     try {
@@ -174,6 +190,9 @@ export const useSimulationStore = defineStore('simulation', () => {
       })
       if (!res.ok) {
         const err = await res.json().catch(() => ({}))
+        for (const v of err.validation_errors ?? []) {
+          addLog(`  ${v.path}: ${v.message}${v.suggestion ? ` (${v.suggestion})` : ''}`)
+        }
         throw new Error(err.error || `HTTP ${res.status}`)
       }
       const data = await res.json()
@@ -203,6 +222,15 @@ export const useSimulationStore = defineStore('simulation', () => {
     const wsUrl = `${WS_BASE}/ws/${sessionId.value}`
     ws = new WebSocket(wsUrl)
 
+    // The map only needs robots' nodes: skip edge progress and ticks, and
+    // take state as deltas at a rate the browser keeps up with
+    ws.onopen = () => {
+      ws?.send(JSON.stringify({
+        type: 'Subscribe',
+        payload: { deltas: true, robot_positions: false, ticks: false, max_messages_per_second: 30 },
+      }))
+    }
+
     ws.onmessage = (event) => {
       const msg = JSON.parse(event.data)
       handleWsMessage(msg)
@@ -236,6 +264,10 @@ export const useSimulationStore = defineStore('simulation', () => {
         updateState(msg.state)
         break
 
+      case 'StateDelta':
+        applyDelta(msg.delta)
+        break
+
       case 'RobotMoved': {
         const robot = robots.value.find(r => r.id === msg.robot_id)
         if (robot) {
@@ -256,6 +288,10 @@ export const useSimulationStore = defineStore('simulation', () => {
         addLog(`Order #${msg.order_id} completed (${msg.cycle_time_s.toFixed(1)}s${msg.on_time ? '' : ', LATE'})`)
         break
 
+      case 'Intervention':
+        addLog(`Intervention: ${msg.record.intervention.type} at ${msg.record.time_s.toFixed(1)}s`)
+        break
+
       case 'Finished':
         appStatus.value = 'finished'
         metrics.value = msg.final_metrics
@@ -276,8 +312,30 @@ export const useSimulationStore = defineStore('simulation', () => {
     robots.value = state.robots
     stations.value = state.stations
     metrics.value = state.metrics
+    updateStatus(state.status)
+  }
+
+  function applyDelta(delta: StateDelta) {
+    simTime.value = delta.time_s
+    eventsProcessed.value = delta.events_processed
+    if (delta.speed !== undefined) speed.value = delta.speed
+    if (delta.metrics) metrics.value = delta.metrics
+    if (delta.robots?.length || delta.removed_robots?.length) {
+      const removed = new Set(delta.removed_robots ?? [])
+      const byId = new Map(robots.value.filter(r => !removed.has(r.id)).map(r => [r.id, r]))
+      for (const r of delta.robots ?? []) byId.set(r.id, r)
+      robots.value = [...byId.values()]
+    }
+    if (delta.stations?.length) {
+      const byId = new Map(stations.value.map(s => [s.id, s]))
+      for (const s of delta.stations) byId.set(s.id, s)
+      stations.value = [...byId.values()]
+    }
+    if (delta.status) updateStatus(delta.status)
+  }
 
-    switch (state.status) {
+  function updateStatus(status: string) {
+    switch (status) {
       case 'running': appStatus.value = 'running'; break
       case 'paused': appStatus.value = 'paused'; break
       case 'finished': appStatus.value = 'finished'; break
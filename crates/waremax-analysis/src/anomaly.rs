@@ -559,7 +559,7 @@ mod tests {
 
         // Should detect the slow orders
         let anomalies = detector.anomalies();
-        assert!(anomalies.len() >= 1);
+        assert!(!anomalies.is_empty());
 
         // Check that slow orders were flagged
         let slow_orders: Vec<_> = anomalies
@@ -585,7 +585,7 @@ mod tests {
         detector.detect_queue_spikes(StationId(1), "S1", &samples);
 
         let anomalies = detector.anomalies();
-        assert!(anomalies.len() >= 1);
+        assert!(!anomalies.is_empty());
 
         // Verify queue spike detected
         assert!(anomalies
@@ -4,7 +4,8 @@
 //! to a configurable address and serves the API.
 
 use std::net::SocketAddr;
-use waremax_api::ApiConfig;
+use std::path::PathBuf;
+use waremax_api::{ApiConfig, ApiKey};
 
 #[tokio::main]
 async fn main() {
@@ -32,6 +33,23 @@ async fn main() {
     } else {
         tracing::info!("CORS origins: {:?}", config.cors_origins);
     }
+    if let Some(dir) = &config.scenario_dir {
+        tracing::info!("Scenario library: {}", dir.display());
+    }
+    if let Some(dir) = &config.data_dir {
+        tracing::info!("Run history: {}", dir.display());
+    }
+    if let Some(dir) = &config.job_dir {
+        tracing::info!("Job directory: {}", dir.display());
+    }
+    if config.api_keys.is_empty() {
+        tracing::warn!("No API keys configured; the API is open to anyone");
+    } else {
+        tracing::info!("API key authentication: {} keys", config.api_keys.len());
+    }
+    if let Some(path) = &config.audit_log {
+        tracing::info!("Audit log: {}", path.display());
+    }
 
     let listener = tokio::net::TcpListener::bind(addr).await.expect("Failed to bind");
     axum::serve(listener, app).await.expect("Server error");
@@ -53,10 +71,52 @@ fn load_config_from_env() -> ApiConfig {
         .map(|s| s.split(',').map(|o| o.trim().to_string()).collect())
         .unwrap_or_default();
 
+    let scenario_dir = std::env::var("WAREMAX_SCENARIO_DIR")
+        .ok()
+        .map(PathBuf::from);
+
+    let job_dir = std::env::var("WAREMAX_JOB_DIR").ok().map(PathBuf::from);
+
+    let data_dir = std::env::var("WAREMAX_DATA_DIR").ok().map(PathBuf::from);
+
+    let job_workers = std::env::var("WAREMAX_JOB_WORKERS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(2);
+
+    let api_keys = load_api_keys_from_env().unwrap_or_else(|e| {
+        tracing::error!("Invalid API keys: {}", e);
+        std::process::exit(1);
+    });
+
+    let audit_log = std::env::var("WAREMAX_AUDIT_LOG").ok().map(PathBuf::from);
+
     ApiConfig {
         session_timeout_secs,
         max_sessions,
         cors_origins,
         request_id_header: true,
+        scenario_dir,
+        job_dir,
+        job_workers,
+        data_dir,
+        api_keys,
+        audit_log,
+    }
+}
+
+/// Keys from `WAREMAX_API_KEYS_FILE` (a JSON array) and `WAREMAX_API_KEYS`
+/// (`name:key` pairs, comma-separated)
+fn load_api_keys_from_env() -> Result<Vec<ApiKey>, String> {
+    let mut keys = Vec::new();
+    if let Ok(path) = std::env::var("WAREMAX_API_KEYS_FILE") {
+        let loaded = ApiKey::load_file(path.as_ref()).map_err(|e| format!("{}: {}", path, e))?;
+        keys.extend(loaded);
+    }
+    if let Ok(list) = std::env::var("WAREMAX_API_KEYS") {
+        keys.extend(ApiKey::parse_list(&list)?);
     }
+    // Duplicates are caught here rather than by create_router's panic
+    waremax_api::auth::ApiKeys::new(keys.clone())?;
+    Ok(keys)
 }
@@ -0,0 +1,58 @@
+//! Audit log of who ran what
+//!
+//! Every change made through the API, every WebSocket opened, and every
+//! session, replay and job created is logged with the tenant that did it:
+//! as a `tracing` event with target `audit`, and as a JSON line appended to
+//! the audit file if one is configured.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+use chrono::Utc;
+
+use crate::auth::Caller;
+
+/// Where audit entries go
+#[derive(Debug, Default)]
+pub struct AuditLog {
+    file: Option<Mutex<File>>,
+}
+
+impl AuditLog {
+    /// Log to `tracing` only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also append entries to the file at `path`
+    pub fn with_file(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            file: Some(Mutex::new(file)),
+        })
+    }
+
+    /// Record that `caller` did `action`, with fields describing it
+    pub fn record(&self, caller: &Caller, action: &str, fields: serde_json::Value) {
+        let key = caller.name().unwrap_or("-");
+        tracing::info!(target: "audit", key, action, %fields);
+
+        let Some(file) = &self.file else {
+            return;
+        };
+        let mut entry = serde_json::json!({
+            "at": Utc::now().to_rfc3339(),
+            "key": caller.name(),
+            "action": action,
+        });
+        if let (Some(entry), serde_json::Value::Object(fields)) = (entry.as_object_mut(), fields) {
+            entry.extend(fields);
+        }
+        let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = writeln!(file, "{}", entry) {
+            tracing::warn!(error = %e, "Failed to write audit log");
+        }
+    }
+}
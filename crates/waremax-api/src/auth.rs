@@ -0,0 +1,602 @@
+//! API keys, per-key quotas and ownership checks
+//!
+//! Without keys configured the API is open, as before. With keys, every
+//! request except the health check and the API schemas must present one, as
+//! `Authorization: Bearer <key>`, `X-API-Key: <key>`, or an `api_key` query
+//! parameter for browsers opening a WebSocket. Each key names its tenant.
+//!
+//! Sessions, runs, replays and jobs record the tenant that created them and
+//! are only visible to that tenant and to admin keys; a tenant asking for
+//! someone else's gets the same 404 as for an unknown id. A key may cap its
+//! concurrent sessions and the simulated minutes it may ask for per hour.
+//! Sessions and jobs are charged their full simulated length when created.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{Path as UrlPath, Query, Request, State},
+    http::{header, HeaderMap, StatusCode, Uri},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::routes;
+use crate::state::AppState;
+use crate::types::ErrorResponse;
+
+/// Window the simulated-minute quota is counted over
+const QUOTA_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// An API key and what it may do
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ApiKey {
+    /// Tenant name, recorded as the owner of what the key creates
+    pub name: String,
+    pub key: String,
+    /// Admin keys see and control every tenant's sessions, runs and jobs
+    #[serde(default)]
+    pub admin: bool,
+    /// Most sessions the key may have at once
+    #[serde(default)]
+    pub max_sessions: Option<usize>,
+    /// Most simulated minutes the key may start per hour
+    #[serde(default)]
+    pub sim_minutes_per_hour: Option<f64>,
+}
+
+impl ApiKey {
+    /// Load keys from a JSON file holding an array of keys
+    pub fn load_file(path: &Path) -> io::Result<Vec<ApiKey>> {
+        let json = std::fs::read_to_string(path)?;
+        serde_json::from_str(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Parse a comma-separated list of `name:key` pairs
+    pub fn parse_list(list: &str) -> Result<Vec<ApiKey>, String> {
+        list.split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| match entry.split_once(':') {
+                Some((name, key)) if !name.is_empty() && !key.is_empty() => Ok(ApiKey {
+                    name: name.to_string(),
+                    key: key.to_string(),
+                    admin: false,
+                    max_sessions: None,
+                    sim_minutes_per_hour: None,
+                }),
+                _ => Err(format!("Expected 'name:key', got '{}'", entry)),
+            })
+            .collect()
+    }
+}
+
+/// Why a tenant may not start more simulation
+#[derive(Error, Debug)]
+pub enum QuotaError {
+    #[error("API key '{name}' has reached its limit of {limit} concurrent sessions")]
+    Sessions { name: String, limit: usize },
+
+    #[error(
+        "API key '{name}' has started {used:.0} of its {limit:.0} simulated minutes \
+         this hour and asked for {requested:.0} more"
+    )]
+    SimMinutes {
+        name: String,
+        used: f64,
+        limit: f64,
+        requested: f64,
+    },
+}
+
+#[derive(Debug)]
+struct Tenant {
+    name: String,
+    admin: bool,
+    max_sessions: Option<usize>,
+    sim_minutes_per_hour: Option<f64>,
+    /// Simulated minutes started in the last hour, oldest first
+    usage: Mutex<VecDeque<(Instant, f64)>>,
+}
+
+/// Who is making a request: a tenant, or anyone when the API is open
+#[derive(Clone, Debug)]
+pub struct Caller(Option<Arc<Tenant>>);
+
+impl Caller {
+    /// The caller when no keys are configured: unrestricted and unnamed
+    pub fn anonymous() -> Self {
+        Self(None)
+    }
+
+    /// Tenant name, recorded as the owner of what the caller creates
+    pub fn name(&self) -> Option<&str> {
+        self.0.as_ref().map(|t| t.name.as_str())
+    }
+
+    /// Whether the caller may see something owned by `owner`
+    pub fn can_access(&self, owner: Option<&str>) -> bool {
+        match &self.0 {
+            None => true,
+            Some(tenant) => tenant.admin || owner == Some(tenant.name.as_str()),
+        }
+    }
+
+    /// Check the caller may have one more session when it has `running`
+    pub fn check_sessions(&self, running: usize) -> Result<(), QuotaError> {
+        match self.0.as_ref() {
+            Some(tenant) => match tenant.max_sessions {
+                Some(limit) if running >= limit => Err(QuotaError::Sessions {
+                    name: tenant.name.clone(),
+                    limit,
+                }),
+                _ => Ok(()),
+            },
+            None => Ok(()),
+        }
+    }
+
+    /// Charge `minutes` of simulated time to the caller's hourly quota
+    pub fn charge(&self, minutes: f64) -> Result<(), QuotaError> {
+        let Some(tenant) = &self.0 else {
+            return Ok(());
+        };
+        let Some(limit) = tenant.sim_minutes_per_hour else {
+            return Ok(());
+        };
+
+        let mut usage = tenant.usage.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        while usage
+            .front()
+            .is_some_and(|(at, _)| now.duration_since(*at) > QUOTA_WINDOW)
+        {
+            usage.pop_front();
+        }
+        let used: f64 = usage.iter().map(|(_, minutes)| minutes).sum();
+        if used + minutes > limit {
+            return Err(QuotaError::SimMinutes {
+                name: tenant.name.clone(),
+                used,
+                limit,
+                requested: minutes,
+            });
+        }
+        usage.push_back((now, minutes));
+        Ok(())
+    }
+}
+
+/// The `api_key` query parameter
+#[derive(Deserialize)]
+struct KeyQuery {
+    api_key: Option<String>,
+}
+
+/// The configured API keys
+#[derive(Debug)]
+pub struct ApiKeys {
+    by_key: HashMap<String, Caller>,
+}
+
+impl ApiKeys {
+    /// Index `keys`, rejecting duplicate names or keys
+    pub fn new(keys: Vec<ApiKey>) -> Result<Self, String> {
+        let mut by_key = HashMap::new();
+        let mut names = HashSet::new();
+        for key in keys {
+            if !names.insert(key.name.clone()) {
+                return Err(format!("Duplicate API key name '{}'", key.name));
+            }
+            let caller = Caller(Some(Arc::new(Tenant {
+                name: key.name.clone(),
+                admin: key.admin,
+                max_sessions: key.max_sessions,
+                sim_minutes_per_hour: key.sim_minutes_per_hour,
+                usage: Mutex::new(VecDeque::new()),
+            })));
+            if by_key.insert(key.key, caller).is_some() {
+                return Err(format!("API key of '{}' is used twice", key.name));
+            }
+        }
+        Ok(Self { by_key })
+    }
+
+    /// The caller presenting the request's key, if it is valid
+    fn caller(&self, headers: &HeaderMap, uri: &Uri) -> Option<Caller> {
+        let from_header = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .or_else(|| headers.get("x-api-key").and_then(|v| v.to_str().ok()));
+        let key = match from_header {
+            Some(key) => key.to_string(),
+            // Query values arrive percent-encoded
+            None => Query::<KeyQuery>::try_from_uri(uri).ok()?.0.api_key?,
+        };
+        self.by_key.get(key.trim()).cloned()
+    }
+}
+
+/// Identify the caller of every request and audit what it changes
+///
+/// Rejects requests without a valid key with 401 when keys are configured.
+pub async fn authenticate(
+    State(state): State<Arc<AppState>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let caller = match &state.api_keys {
+        None => Caller::anonymous(),
+        Some(_) if routes::is_public(request.uri().path()) => return next.run(request).await,
+        Some(keys) => match keys.caller(request.headers(), request.uri()) {
+            Some(caller) => caller,
+            None => {
+                return (
+                    StatusCode::UNAUTHORIZED,
+                    [(header::WWW_AUTHENTICATE, "Bearer")],
+                    Json(ErrorResponse::new("Missing or invalid API key")),
+                )
+                    .into_response()
+            }
+        },
+    };
+
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    request.extensions_mut().insert(caller.clone());
+    let response = next.run(request).await;
+
+    // Reads are not audited, but opening a WebSocket is
+    if method != axum::http::Method::GET || path.starts_with("/ws/") {
+        state.audit.record(
+            &caller,
+            "request",
+            serde_json::json!({
+                "method": method.as_str(),
+                "path": path,
+                "status": response.status().as_u16(),
+            }),
+        );
+    }
+    response
+}
+
+fn not_found(what: &str) -> Response {
+    (StatusCode::NOT_FOUND, Json(ErrorResponse::new(what))).into_response()
+}
+
+/// Hide sessions of other tenants on `/session/:id` and `/ws/:id` routes
+pub async fn require_session_owner(
+    State(state): State<Arc<AppState>>,
+    UrlPath(id): UrlPath<String>,
+    Extension(caller): Extension<Caller>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if let Some(owner) = state.session_manager.session_owner(&id).await {
+        if !caller.can_access(owner.as_deref()) {
+            return not_found("Session not found");
+        }
+    }
+    next.run(request).await
+}
+
+/// Hide runs of other tenants on `/runs/:id` routes
+pub async fn require_run_owner(
+    State(state): State<Arc<AppState>>,
+    UrlPath(id): UrlPath<String>,
+    Extension(caller): Extension<Caller>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if let Some(history) = state.session_manager.history() {
+        if let Ok(Some(run)) = history.record(&id) {
+            if !caller.can_access(run.owner.as_deref()) {
+                return not_found("Run not found");
+            }
+        }
+    }
+    next.run(request).await
+}
+
+/// Hide replays of other tenants on `/replays/:id` and `/ws/replays/:id`
+pub async fn require_replay_owner(
+    State(state): State<Arc<AppState>>,
+    UrlPath(id): UrlPath<String>,
+    Extension(caller): Extension<Caller>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if let Some(replay) = state.replay_manager.get(&id).await {
+        if !caller.can_access(replay.owner.as_deref()) {
+            return not_found("Replay not found");
+        }
+    }
+    next.run(request).await
+}
+
+/// Hide jobs of other tenants on `/jobs/:id` routes
+pub async fn require_job_owner(
+    State(state): State<Arc<AppState>>,
+    UrlPath(id): UrlPath<String>,
+    Extension(caller): Extension<Caller>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if let Some(job) = state.job_manager.get(&id) {
+        if !caller.can_access(job.owner.as_deref()) {
+            return not_found("Job not found");
+        }
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{create_router, ApiConfig};
+    use axum::body::{to_bytes, Body};
+    use axum::http::Method;
+    use axum::Router;
+    use tower::ServiceExt;
+
+    fn key(name: &str, admin: bool) -> ApiKey {
+        ApiKey {
+            name: name.to_string(),
+            key: format!("key-{}", name),
+            admin,
+            max_sessions: None,
+            sim_minutes_per_hour: None,
+        }
+    }
+
+    async fn call(
+        app: &Router,
+        method: Method,
+        uri: &str,
+        key: Option<&str>,
+        body: Option<serde_json::Value>,
+    ) -> (StatusCode, serde_json::Value) {
+        let mut request = Request::builder().method(method).uri(uri);
+        if let Some(key) = key {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", key));
+        }
+        let body = match body {
+            Some(json) => {
+                request = request.header(header::CONTENT_TYPE, "application/json");
+                Body::from(json.to_string())
+            }
+            None => Body::empty(),
+        };
+        let response = app
+            .clone()
+            .oneshot(request.body(body).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or_default())
+    }
+
+    fn small_session(duration_minutes: f64) -> Option<serde_json::Value> {
+        Some(serde_json::json!({ "preset": "small", "duration_minutes": duration_minutes }))
+    }
+
+    #[tokio::test]
+    async fn test_requests_need_a_valid_key() {
+        let mut tenant = key("a", false);
+        tenant.key = "a+b/c".to_string();
+        let app = create_router(ApiConfig {
+            api_keys: vec![tenant],
+            ..ApiConfig::default()
+        });
+
+        let (status, _) = call(&app, Method::GET, "/api/runs", None, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = call(&app, Method::GET, "/api/runs", Some("key-a"), None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = call(&app, Method::GET, "/api/health", None, None).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // Query keys are percent-decoded
+        let (status, _) = call(&app, Method::GET, "/api/jobs?api_key=a+b/c", None, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let uri = "/api/jobs?api_key=a%2Bb%2Fc";
+        let (status, _) = call(&app, Method::GET, uri, None, None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_tenants_only_see_their_own() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = create_router(ApiConfig {
+            data_dir: Some(dir.path().to_path_buf()),
+            api_keys: vec![key("a", false), key("b", false), key("ops", true)],
+            ..ApiConfig::default()
+        });
+
+        let (status, created) = call(
+            &app,
+            Method::POST,
+            "/api/session",
+            Some("key-a"),
+            small_session(5.0),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        let session_id = created["session_id"].as_str().unwrap().to_string();
+
+        let job = serde_json::json!({
+            "kind": "sweep",
+            "base": { "preset": "small" },
+            "sweep": "robots:2",
+            "replications": 1,
+        });
+        let (status, job) = call(&app, Method::POST, "/api/jobs", Some("key-a"), Some(job)).await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        let job_id = job["id"].as_str().unwrap().to_string();
+
+        let replay = serde_json::json!({
+            "replays": [{ "name": "log", "events": [], "duration_s": 10.0 }],
+        });
+        let (status, replay) = call(
+            &app,
+            Method::POST,
+            "/api/replays",
+            Some("key-a"),
+            Some(replay),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        let replay_id = replay["replay_id"].as_str().unwrap().to_string();
+
+        let reads = [
+            format!("/api/session/{}/map", session_id),
+            format!("/api/runs/{}", session_id),
+            format!("/api/jobs/{}", job_id),
+            format!("/api/replays/{}", replay_id),
+        ];
+        for uri in &reads {
+            let (status, _) = call(&app, Method::GET, uri, Some("key-b"), None).await;
+            assert_eq!(status, StatusCode::NOT_FOUND, "{}", uri);
+            for owner in ["key-a", "key-ops"] {
+                let (status, _) = call(&app, Method::GET, uri, Some(owner), None).await;
+                assert_eq!(status, StatusCode::OK, "{} as {}", uri, owner);
+            }
+        }
+
+        let changes = [
+            format!("/api/session/{}/pause", session_id),
+            format!("/api/replays/{}/pause", replay_id),
+            format!("/api/runs/{}/reopen", session_id),
+        ];
+        for uri in &changes {
+            let (status, _) = call(&app, Method::POST, uri, Some("key-b"), None).await;
+            assert_eq!(status, StatusCode::NOT_FOUND, "{}", uri);
+        }
+        let removals = [
+            format!("/api/session/{}", session_id),
+            format!("/api/runs/{}", session_id),
+            format!("/api/jobs/{}", job_id),
+            format!("/api/replays/{}", replay_id),
+        ];
+        for uri in &removals {
+            let (status, _) = call(&app, Method::DELETE, uri, Some("key-b"), None).await;
+            assert_eq!(status, StatusCode::NOT_FOUND, "{}", uri);
+        }
+
+        // Nothing was touched by the other tenant, and lists leave it out
+        let (_, runs) = call(&app, Method::GET, "/api/runs", Some("key-b"), None).await;
+        assert_eq!(runs.as_array().map(Vec::len), Some(0));
+        let (_, jobs) = call(&app, Method::GET, "/api/jobs", Some("key-b"), None).await;
+        assert_eq!(jobs.as_array().map(Vec::len), Some(0));
+        for uri in &reads {
+            let (status, _) = call(&app, Method::GET, uri, Some("key-a"), None).await;
+            assert_eq!(status, StatusCode::OK, "{}", uri);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_quotas() {
+        let mut limited = key("limited", false);
+        limited.max_sessions = Some(1);
+        let mut metered = key("metered", false);
+        metered.sim_minutes_per_hour = Some(100.0);
+        let app = create_router(ApiConfig {
+            api_keys: vec![limited, metered, key("other", false)],
+            ..ApiConfig::default()
+        });
+
+        let create = |key, minutes| {
+            let app = app.clone();
+            async move {
+                let session = small_session(minutes);
+                call(&app, Method::POST, "/api/session", Some(key), session)
+                    .await
+                    .0
+            }
+        };
+        assert_eq!(create("key-limited", 5.0).await, StatusCode::CREATED);
+        assert_eq!(
+            create("key-limited", 5.0).await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        // Other tenants' sessions don't count against the limit
+        assert_eq!(create("key-other", 5.0).await, StatusCode::CREATED);
+
+        assert_eq!(
+            create("key-metered", 500.0).await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(create("key-metered", 5.0).await, StatusCode::CREATED);
+    }
+
+    #[tokio::test]
+    async fn test_changes_are_audited() {
+        let dir = tempfile::tempdir().unwrap();
+        let audit_log = dir.path().join("audit.jsonl");
+        let app = create_router(ApiConfig {
+            api_keys: vec![key("a", false), key("b", false)],
+            audit_log: Some(audit_log.clone()),
+            ..ApiConfig::default()
+        });
+
+        let (_, created) = call(
+            &app,
+            Method::POST,
+            "/api/session",
+            Some("key-a"),
+            small_session(5.0),
+        )
+        .await;
+        let session_id = created["session_id"].as_str().unwrap();
+        let session = format!("/api/session/{}", session_id);
+        let pause = format!("{}/pause", session);
+        call(
+            &app,
+            Method::GET,
+            &format!("{}/map", session),
+            Some("key-a"),
+            None,
+        )
+        .await;
+        call(&app, Method::POST, &pause, Some("key-b"), None).await;
+        call(&app, Method::DELETE, &session, Some("key-a"), None).await;
+
+        let entries: Vec<serde_json::Value> = std::fs::read_to_string(&audit_log)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let requests: Vec<_> = entries
+            .iter()
+            .filter(|e| e["action"] == "request")
+            .map(|e| {
+                (
+                    e["key"].as_str().unwrap(),
+                    e["method"].as_str().unwrap(),
+                    e["path"].as_str().unwrap(),
+                    e["status"].as_u64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            requests,
+            [
+                ("a", "POST", "/api/session", 201),
+                ("b", "POST", pause.as_str(), 404),
+                ("a", "DELETE", session.as_str(), 200),
+            ]
+        );
+        assert!(entries
+            .iter()
+            .any(|e| e["action"] == "session.create" && e["session_id"] == session_id));
+    }
+}
@@ -0,0 +1,549 @@
+//! External controller for a running session
+//!
+//! A remote process connects to `/ws/:id/controller`, registers for one or
+//! more [`ControllerRole`]s and from then on receives a decision request with
+//! the candidates whenever the simulation needs that decision. The session's
+//! policies are wrapped in [`RemoteAllocationPolicy`] / [`RemoteStationPolicy`],
+//! which block the simulation for the reply and fall back to a heuristic on
+//! timeout, disconnect, or an answer that is not one of the candidates.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc as std_mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::runtime::RuntimeFlavor;
+use tokio::sync::mpsc;
+use waremax_core::{RobotId, StationId, TaskId};
+use waremax_entities::{StationType, Task};
+use waremax_policies::{
+    candidate_stations, PolicyContext, StationAssignmentPolicy, TaskAllocationPolicy,
+};
+
+use crate::types::{
+    ControllerRole, ControllerServerMessage, ControllerStats, ControllerStatus, RobotCandidate,
+    StationCandidate, TaskView,
+};
+
+/// Reply timeout when the controller does not ask for one
+pub const DEFAULT_TIMEOUT_MS: u64 = 1000;
+
+/// Longest reply timeout a controller may ask for
+pub const MAX_TIMEOUT_MS: u64 = 60_000;
+
+/// A controller's answer to request `request_id`
+pub type ControllerReply = (u64, Option<u32>);
+
+/// The simulation's side of a controller connection
+#[derive(Debug)]
+pub struct ControllerLink {
+    roles: Vec<ControllerRole>,
+    timeout: Duration,
+    requests: mpsc::UnboundedSender<ControllerServerMessage>,
+    replies: Mutex<std_mpsc::Receiver<ControllerReply>>,
+    next_request: AtomicU64,
+    connected: AtomicBool,
+    stats: Mutex<ControllerStats>,
+}
+
+impl ControllerLink {
+    pub fn new(
+        roles: Vec<ControllerRole>,
+        timeout_ms: u64,
+        requests: mpsc::UnboundedSender<ControllerServerMessage>,
+        replies: std_mpsc::Receiver<ControllerReply>,
+    ) -> Self {
+        Self {
+            roles,
+            timeout: Duration::from_millis(timeout_ms.clamp(1, MAX_TIMEOUT_MS)),
+            requests,
+            replies: Mutex::new(replies),
+            next_request: AtomicU64::new(1),
+            connected: AtomicBool::new(true),
+            stats: Mutex::new(ControllerStats::default()),
+        }
+    }
+
+    pub fn roles(&self) -> &[ControllerRole] {
+        &self.roles
+    }
+
+    pub fn has_role(&self, role: ControllerRole) -> bool {
+        self.roles.contains(&role)
+    }
+
+    pub fn timeout_ms(&self) -> u64 {
+        self.timeout.as_millis() as u64
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    /// Stop consulting the controller; every decision uses the fallback
+    pub fn disconnect(&self) {
+        self.connected.store(false, Ordering::Relaxed);
+    }
+
+    pub fn status(&self) -> ControllerStatus {
+        ControllerStatus {
+            connected: self.is_connected(),
+            roles: self.roles.clone(),
+            timeout_ms: self.timeout_ms(),
+            stats: self.stats.lock().map(|s| s.clone()).unwrap_or_default(),
+        }
+    }
+
+    /// Send a message to the controller; a closed connection disconnects
+    pub fn send(&self, message: ControllerServerMessage) {
+        if self.requests.send(message).is_err() {
+            self.disconnect();
+        }
+    }
+
+    /// Ask the controller and block for its answer. `None` means no usable
+    /// answer arrived in time and the caller should use its fallback.
+    ///
+    /// On a multi-threaded tokio worker the wait runs under `block_in_place`
+    /// so the connection's tasks keep running. A current-thread runtime
+    /// could never deliver the reply while this thread waits, so the
+    /// controller isn't asked at all.
+    fn ask(&self, request: impl FnOnce(u64) -> ControllerServerMessage) -> Option<Option<u32>> {
+        if !self.is_connected() {
+            return None;
+        }
+        let on_worker = match tokio::runtime::Handle::try_current() {
+            Ok(rt) if rt.runtime_flavor() == RuntimeFlavor::MultiThread => true,
+            Ok(_) => return None,
+            Err(_) => false,
+        };
+        let request_id = self.next_request.fetch_add(1, Ordering::Relaxed);
+        let replies = self.replies.lock().ok()?;
+        // Late answers to requests that already timed out are stale
+        while replies.try_recv().is_ok() {}
+
+        self.send(request(request_id));
+        if on_worker {
+            tokio::task::block_in_place(|| self.wait_for(&replies, request_id))
+        } else {
+            self.wait_for(&replies, request_id)
+        }
+    }
+
+    /// Wait out the timeout for the reply to `request_id`
+    fn wait_for(
+        &self,
+        replies: &std_mpsc::Receiver<ControllerReply>,
+        request_id: u64,
+    ) -> Option<Option<u32>> {
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match replies.recv_timeout(remaining) {
+                Ok((id, choice)) if id == request_id => return Some(choice),
+                Ok(_) => continue,
+                Err(std_mpsc::RecvTimeoutError::Timeout) => {
+                    self.record(|s| s.timeouts += 1);
+                    return None;
+                }
+                Err(std_mpsc::RecvTimeoutError::Disconnected) => {
+                    self.disconnect();
+                    return None;
+                }
+            }
+        }
+    }
+
+    fn record(&self, update: impl FnOnce(&mut ControllerStats)) {
+        if let Ok(mut stats) = self.stats.lock() {
+            update(&mut stats);
+        }
+    }
+}
+
+fn task_view(task: &Task) -> TaskView {
+    TaskView {
+        task_id: task.id.0,
+        task_type: format!("{:?}", task.task_type),
+        order_id: task.order_id.map(|o| o.0),
+        sku_id: task.sku_id.0,
+        quantity: task.quantity,
+        source_node: task.source.access_node.0,
+        destination_station: task.destination_station.0,
+        created_at_s: task.created_at.as_seconds(),
+    }
+}
+
+/// Task allocation decided by the external controller
+#[derive(Clone)]
+pub struct RemoteAllocationPolicy {
+    link: Arc<ControllerLink>,
+    fallback: Box<dyn TaskAllocationPolicy>,
+}
+
+impl RemoteAllocationPolicy {
+    pub fn new(link: Arc<ControllerLink>, fallback: Box<dyn TaskAllocationPolicy>) -> Self {
+        Self { link, fallback }
+    }
+}
+
+impl TaskAllocationPolicy for RemoteAllocationPolicy {
+    fn allocate(&self, ctx: &PolicyContext, task_id: TaskId) -> Option<RobotId> {
+        let task = ctx.tasks.get(&task_id)?;
+        let pickup = task.source.access_node;
+
+        let mut robots: Vec<_> = ctx.robots.values().filter(|r| r.is_available()).collect();
+        // No candidates => no decision; never consult the controller
+        if robots.is_empty() {
+            return None;
+        }
+        robots.sort_by_key(|r| r.id.0);
+        let candidates: Vec<RobotCandidate> = robots
+            .iter()
+            .map(|r| RobotCandidate {
+                robot_id: r.id.0,
+                node_id: r.current_node.0,
+                state: format!("{:?}", r.state)
+                    .split_whitespace()
+                    .next()
+                    .unwrap_or("Unknown")
+                    .to_string(),
+                queued_tasks: r.task_queue.len(),
+                soc: r.has_battery().then(|| r.soc()),
+                distance_m: ctx.map.euclidean_distance(r.current_node, pickup),
+            })
+            .collect();
+
+        let answer = self
+            .link
+            .ask(|request_id| ControllerServerMessage::AllocationRequest {
+                request_id,
+                time_s: ctx.current_time.as_seconds(),
+                task: task_view(task),
+                candidates,
+            });
+        match answer {
+            Some(None) => {
+                self.link.record(|s| s.decisions += 1);
+                None
+            }
+            Some(Some(id)) if robots.iter().any(|r| r.id.0 == id) => {
+                self.link.record(|s| s.decisions += 1);
+                Some(RobotId(id))
+            }
+            Some(Some(_)) => {
+                self.link.record(|s| s.invalid += 1);
+                self.fallback.allocate(ctx, task_id)
+            }
+            None => self.fallback.allocate(ctx, task_id),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "remote_controller"
+    }
+}
+
+/// Station assignment decided by the external controller. A `null` answer
+/// defers to the fallback so a task is never left without a station.
+#[derive(Clone)]
+pub struct RemoteStationPolicy {
+    link: Arc<ControllerLink>,
+    fallback: Box<dyn StationAssignmentPolicy>,
+}
+
+impl RemoteStationPolicy {
+    pub fn new(link: Arc<ControllerLink>, fallback: Box<dyn StationAssignmentPolicy>) -> Self {
+        Self { link, fallback }
+    }
+}
+
+impl StationAssignmentPolicy for RemoteStationPolicy {
+    fn assign(&self, ctx: &PolicyContext, task: &Task) -> Option<StationId> {
+        let mut stations = candidate_stations(ctx, &StationType::Pick);
+        if stations.is_empty() {
+            return self.fallback.assign(ctx, task);
+        }
+        stations.sort_by_key(|s| s.id.0);
+        let candidates: Vec<StationCandidate> = stations
+            .iter()
+            .map(|s| StationCandidate {
+                station_id: s.id.0,
+                name: s.string_id.clone(),
+                station_type: format!("{:?}", s.station_type),
+                queue_length: s.queue_length(),
+                serving: s.serving_count(),
+                distance_m: ctx.map.euclidean_distance(task.source.access_node, s.node),
+            })
+            .collect();
+
+        let answer = self
+            .link
+            .ask(|request_id| ControllerServerMessage::StationRequest {
+                request_id,
+                time_s: ctx.current_time.as_seconds(),
+                task: task_view(task),
+                candidates,
+            });
+        match answer {
+            Some(Some(id)) if stations.iter().any(|s| s.id.0 == id) => {
+                self.link.record(|s| s.decisions += 1);
+                Some(StationId(id))
+            }
+            Some(Some(_)) => {
+                self.link.record(|s| s.invalid += 1);
+                self.fallback.assign(ctx, task)
+            }
+            Some(None) => {
+                self.link.record(|s| s.decisions += 1);
+                self.fallback.assign(ctx, task)
+            }
+            None => self.fallback.assign(ctx, task),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "remote_controller"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::thread;
+    use waremax_core::{NodeId, OrderId, RackId, SimTime, SkuId};
+    use waremax_entities::{BinLocation, Order, Robot, ServiceTimeModel, Station};
+    use waremax_map::{Node, NodeType, WarehouseMap};
+    use waremax_policies::{LeastQueuePolicy, NearestRobotPolicy};
+    use waremax_storage::BinAddress;
+
+    /// Three nodes in a row, robots at each end and pick stations at the
+    /// middle and far end, with one task picking at node 0
+    struct Fixture {
+        map: WarehouseMap,
+        robots: HashMap<RobotId, Robot>,
+        tasks: HashMap<TaskId, Task>,
+        stations: HashMap<StationId, Station>,
+        orders: HashMap<OrderId, Order>,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let mut map = WarehouseMap::new();
+            for id in 0..3 {
+                map.add_node(Node::new(
+                    NodeId(id),
+                    format!("N{}", id),
+                    id as f64 * 10.0,
+                    0.0,
+                    NodeType::Aisle,
+                ));
+            }
+            let robots = [(0, 0), (1, 2)]
+                .into_iter()
+                .map(|(id, node)| {
+                    (
+                        RobotId(id),
+                        Robot::new(RobotId(id), NodeId(node), 1.5, 25.0),
+                    )
+                })
+                .collect();
+            let stations = [(0, 1), (1, 2)]
+                .into_iter()
+                .map(|(id, node)| {
+                    let station = Station::new(
+                        StationId(id),
+                        format!("S{}", id),
+                        NodeId(node),
+                        StationType::Pick,
+                        1,
+                        None,
+                        ServiceTimeModel::default(),
+                    );
+                    (StationId(id), station)
+                })
+                .collect();
+            let task = Task::new_pick(
+                TaskId(0),
+                OrderId(0),
+                SkuId(0),
+                1,
+                BinLocation::new(BinAddress::new(RackId(0), 0, 0), NodeId(0)),
+                StationId(0),
+                SimTime::ZERO,
+            );
+            Self {
+                map,
+                robots,
+                tasks: HashMap::from([(TaskId(0), task)]),
+                stations,
+                orders: HashMap::new(),
+            }
+        }
+
+        fn ctx(&self) -> PolicyContext<'_> {
+            PolicyContext {
+                current_time: SimTime::ZERO,
+                map: &self.map,
+                robots: &self.robots,
+                tasks: &self.tasks,
+                stations: &self.stations,
+                orders: &self.orders,
+                attribution: None,
+                traffic: None,
+            }
+        }
+    }
+
+    /// A link whose controller answers every request with `choose(request)`
+    /// from its own thread; `None` never answers.
+    fn link(
+        timeout_ms: u64,
+        choose: impl Fn(&ControllerServerMessage) -> Option<Option<u32>> + Send + 'static,
+    ) -> Arc<ControllerLink> {
+        let (request_tx, mut request_rx) = mpsc::unbounded_channel();
+        let (reply_tx, reply_rx) = std_mpsc::channel();
+        thread::spawn(move || {
+            while let Some(request) = request_rx.blocking_recv() {
+                let request_id = match &request {
+                    ControllerServerMessage::AllocationRequest { request_id, .. }
+                    | ControllerServerMessage::StationRequest { request_id, .. } => *request_id,
+                    _ => continue,
+                };
+                if let Some(choice) = choose(&request) {
+                    let _ = reply_tx.send((request_id, choice));
+                }
+            }
+        });
+        Arc::new(ControllerLink::new(
+            vec![
+                ControllerRole::TaskAllocation,
+                ControllerRole::StationAssignment,
+            ],
+            timeout_ms,
+            request_tx,
+            reply_rx,
+        ))
+    }
+
+    fn allocation(link: &Arc<ControllerLink>) -> RemoteAllocationPolicy {
+        RemoteAllocationPolicy::new(link.clone(), Box::new(NearestRobotPolicy::new()))
+    }
+
+    fn station(link: &Arc<ControllerLink>) -> RemoteStationPolicy {
+        RemoteStationPolicy::new(link.clone(), Box::new(LeastQueuePolicy::default()))
+    }
+
+    #[test]
+    fn test_reply_is_used() {
+        let fixture = Fixture::new();
+        let link = link(1000, |request| match request {
+            ControllerServerMessage::AllocationRequest { candidates, .. } => {
+                assert_eq!(candidates.len(), 2);
+                Some(Some(1))
+            }
+            _ => Some(Some(1)),
+        });
+
+        // Nearest would be robot 0
+        let robot = allocation(&link).allocate(&fixture.ctx(), TaskId(0));
+        assert_eq!(robot, Some(RobotId(1)));
+        let task = &fixture.tasks[&TaskId(0)];
+        assert_eq!(
+            station(&link).assign(&fixture.ctx(), task),
+            Some(StationId(1))
+        );
+        assert_eq!(link.status().stats.decisions, 2);
+    }
+
+    #[test]
+    fn test_timeout_and_disconnect_fall_back() {
+        let fixture = Fixture::new();
+        let task = &fixture.tasks[&TaskId(0)];
+        let fallback_station = LeastQueuePolicy::default().assign(&fixture.ctx(), task);
+
+        let silent = link(20, |_| None);
+        let robot = allocation(&silent).allocate(&fixture.ctx(), TaskId(0));
+        assert_eq!(robot, Some(RobotId(0)));
+        assert_eq!(
+            station(&silent).assign(&fixture.ctx(), task),
+            fallback_station
+        );
+        assert_eq!(silent.status().stats.timeouts, 2);
+        assert!(silent.is_connected());
+
+        // Closing the reply channel disconnects; later asks go straight to
+        // the fallback
+        let (request_tx, _request_rx) = mpsc::unbounded_channel();
+        let (reply_tx, reply_rx) = std_mpsc::channel();
+        drop(reply_tx);
+        let gone = Arc::new(ControllerLink::new(
+            vec![ControllerRole::TaskAllocation],
+            1000,
+            request_tx,
+            reply_rx,
+        ));
+        let robot = allocation(&gone).allocate(&fixture.ctx(), TaskId(0));
+        assert_eq!(robot, Some(RobotId(0)));
+        assert!(!gone.is_connected());
+        assert_eq!(gone.status().stats.timeouts, 0);
+    }
+
+    #[test]
+    fn test_out_of_range_answers_are_rejected() {
+        let fixture = Fixture::new();
+        let task = &fixture.tasks[&TaskId(0)];
+        let fallback_station = LeastQueuePolicy::default().assign(&fixture.ctx(), task);
+        let link = link(1000, |_| Some(Some(99)));
+
+        let robot = allocation(&link).allocate(&fixture.ctx(), TaskId(0));
+        assert_eq!(robot, Some(RobotId(0)));
+        assert_eq!(
+            station(&link).assign(&fixture.ctx(), task),
+            fallback_station
+        );
+        let stats = link.status().stats;
+        assert_eq!((stats.invalid, stats.decisions), (2, 0));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_reply_arrives_while_a_worker_waits() {
+        let (request_tx, mut request_rx) = mpsc::unbounded_channel();
+        let (reply_tx, reply_rx) = std_mpsc::channel();
+        let link = Arc::new(ControllerLink::new(
+            vec![ControllerRole::TaskAllocation],
+            5000,
+            request_tx,
+            reply_rx,
+        ));
+        // The controller side is an ordinary task, as the socket handler is
+        tokio::spawn(async move {
+            while let Some(ControllerServerMessage::AllocationRequest { request_id, .. }) =
+                request_rx.recv().await
+            {
+                let _ = reply_tx.send((request_id, Some(1)));
+            }
+        });
+
+        let fixture = Fixture::new();
+        let robot = allocation(&link).allocate(&fixture.ctx(), TaskId(0));
+        assert_eq!(robot, Some(RobotId(1)));
+    }
+
+    #[tokio::test]
+    async fn test_current_thread_runtime_uses_fallback() {
+        let (request_tx, mut request_rx) = mpsc::unbounded_channel();
+        let (_reply_tx, reply_rx) = std_mpsc::channel();
+        let link = Arc::new(ControllerLink::new(
+            vec![ControllerRole::TaskAllocation],
+            5000,
+            request_tx,
+            reply_rx,
+        ));
+
+        let fixture = Fixture::new();
+        let robot = allocation(&link).allocate(&fixture.ctx(), TaskId(0));
+        assert_eq!(robot, Some(RobotId(0)));
+        assert!(request_rx.try_recv().is_err());
+    }
+}
@@ -1,36 +1,106 @@
 //! REST API handlers for simulation control
 
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
     response::IntoResponse,
-    Json,
+    Extension, Json,
 };
 use std::sync::Arc;
 
+use crate::auth::Caller;
+use crate::jobs::{runs_csv, JobError};
+use crate::replay::ReplayError;
+use crate::scenario::{list_library, resolve_scenario, ScenarioError};
+use crate::session::SessionError;
 use crate::state::AppState;
 use crate::types::{
-    AddRobotRequest, ErrorResponse, PresetInfo, SessionConfig, SessionResponse, SpeedRequest,
+    AddRobotRequest, ErrorResponse, EventRangeQuery, HealthResponse, Intervention, JobRequest,
+    JobResultsQuery, JobStatus, PlayRequest, PresetInfo, ReplayMapQuery, ReplayRequest, RunStatus,
+    SeekRequest, SessionConfig, SessionResponse, SpeedRequest, SpeedResponse, StatusResponse,
+    ValidationIssue,
 };
 
 /// Create a new simulation session
+///
+/// The body may carry an inline `scenario` or a library `scenario_name`
+/// instead of a preset. Scenarios failing validation are rejected with 422
+/// and the list of validation errors.
 pub async fn create_session(
     State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<Caller>,
     Json(config): Json<SessionConfig>,
 ) -> impl IntoResponse {
-    match state.session_manager.create_session(config).await {
-        Ok(session_id) => (
-            StatusCode::CREATED,
-            Json(SessionResponse {
-                session_id,
-                status: "created".to_string(),
-            }),
-        )
-            .into_response(),
-        Err(e) => (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(e))).into_response(),
+    let resolved = match resolve_scenario(&config, state.scenario_dir.as_deref()) {
+        Ok(resolved) => resolved,
+        Err(ScenarioError::Invalid(errors)) => {
+            return (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(ErrorResponse::with_validation_errors(
+                    "Scenario validation failed",
+                    &errors,
+                )),
+            )
+                .into_response()
+        }
+        Err(e @ ScenarioError::NotFound(_)) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse::new(e.to_string())),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e.to_string())),
+            )
+                .into_response()
+        }
+    };
+    let (scenario, warnings) = match resolved {
+        Some(r) => (Some(r.scenario), r.warnings),
+        None => (None, Vec::new()),
+    };
+
+    let source = match (&config.scenario_name, &config.scenario) {
+        (Some(name), _) => name.clone(),
+        (None, Some(_)) => "inline".to_string(),
+        (None, None) => config.preset.clone(),
+    };
+    match state
+        .session_manager
+        .create_session(config, scenario, &caller)
+        .await
+    {
+        Ok(session_id) => {
+            state.audit.record(
+                &caller,
+                "session.create",
+                serde_json::json!({ "session_id": session_id, "scenario": source }),
+            );
+            (
+                StatusCode::CREATED,
+                Json(SessionResponse {
+                    session_id,
+                    status: "created".to_string(),
+                    warnings: warnings.iter().map(ValidationIssue::from).collect(),
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => session_error(e),
     }
 }
 
+fn session_error(e: SessionError) -> axum::response::Response {
+    let status = match e {
+        SessionError::Full => StatusCode::BAD_REQUEST,
+        SessionError::Quota(_) => StatusCode::TOO_MANY_REQUESTS,
+    };
+    (status, Json(ErrorResponse::new(e.to_string()))).into_response()
+}
+
 /// Get the warehouse map for a session
 pub async fn get_session_map(
     State(state): State<Arc<AppState>>,
@@ -60,11 +130,7 @@ pub async fn start_session(
             let mut session = session.lock().await;
             session.touch();
             match session.start().await {
-                Ok(()) => (
-                    StatusCode::OK,
-                    Json(serde_json::json!({"status": "started"})),
-                )
-                    .into_response(),
+                Ok(()) => (StatusCode::OK, Json(StatusResponse::new("started"))).into_response(),
                 Err(e) => (
                     StatusCode::INTERNAL_SERVER_ERROR,
                     Json(ErrorResponse::new(e)),
@@ -90,11 +156,7 @@ pub async fn pause_session(
             let mut session = session.lock().await;
             session.touch();
             match session.pause().await {
-                Ok(()) => (
-                    StatusCode::OK,
-                    Json(serde_json::json!({"status": "paused"})),
-                )
-                    .into_response(),
+                Ok(()) => (StatusCode::OK, Json(StatusResponse::new("paused"))).into_response(),
                 Err(e) => (
                     StatusCode::INTERNAL_SERVER_ERROR,
                     Json(ErrorResponse::new(e)),
@@ -120,11 +182,7 @@ pub async fn resume_session(
             let mut session = session.lock().await;
             session.touch();
             match session.resume().await {
-                Ok(()) => (
-                    StatusCode::OK,
-                    Json(serde_json::json!({"status": "running"})),
-                )
-                    .into_response(),
+                Ok(()) => (StatusCode::OK, Json(StatusResponse::new("running"))).into_response(),
                 Err(e) => (
                     StatusCode::INTERNAL_SERVER_ERROR,
                     Json(ErrorResponse::new(e)),
@@ -154,7 +212,9 @@ pub async fn set_speed(
             match session.set_speed(clamped_speed).await {
                 Ok(()) => (
                     StatusCode::OK,
-                    Json(serde_json::json!({"speed": clamped_speed})),
+                    Json(SpeedResponse {
+                        speed: clamped_speed,
+                    }),
                 )
                     .into_response(),
                 Err(e) => (
@@ -182,11 +242,7 @@ pub async fn step_session(
             let mut session = session.lock().await;
             session.touch();
             match session.step().await {
-                Ok(()) => (
-                    StatusCode::OK,
-                    Json(serde_json::json!({"status": "stepped"})),
-                )
-                    .into_response(),
+                Ok(()) => (StatusCode::OK, Json(StatusResponse::new("stepped"))).into_response(),
                 Err(e) => (
                     StatusCode::INTERNAL_SERVER_ERROR,
                     Json(ErrorResponse::new(e)),
@@ -212,12 +268,48 @@ pub async fn add_robot(
         Some(session) => {
             let mut session = session.lock().await;
             session.touch();
+            if session.replay_of.is_some() {
+                return replay_conflict();
+            }
             match session.add_robot(req.node_id).await {
-                Ok(()) => (
-                    StatusCode::OK,
-                    Json(serde_json::json!({"status": "robot_added"})),
+                Ok(()) => {
+                    (StatusCode::OK, Json(StatusResponse::new("robot_added"))).into_response()
+                }
+                Err(e) => (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ErrorResponse::new(e)),
                 )
                     .into_response(),
+            }
+        }
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("Session not found")),
+        )
+            .into_response(),
+    }
+}
+
+/// Apply a live intervention at the session's next event boundary
+///
+/// The outcome arrives on the WebSocket stream as an `Intervention` message,
+/// or an `Error` if it could not be applied.
+pub async fn intervene(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Json(intervention): Json<Intervention>,
+) -> impl IntoResponse {
+    match state.session_manager.get_session(&session_id).await {
+        Some(session) => {
+            let mut session = session.lock().await;
+            session.touch();
+            if session.replay_of.is_some() {
+                return replay_conflict();
+            }
+            match session.intervene(intervention).await {
+                Ok(()) => {
+                    (StatusCode::ACCEPTED, Json(StatusResponse::new("queued"))).into_response()
+                }
                 Err(e) => (
                     StatusCode::INTERNAL_SERVER_ERROR,
                     Json(ErrorResponse::new(e)),
@@ -233,17 +325,49 @@ pub async fn add_robot(
     }
 }
 
+/// Get the session's intervention log (replayable via `SessionConfig::interventions`)
+pub async fn get_interventions(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+) -> impl IntoResponse {
+    match state.session_manager.get_session(&session_id).await {
+        Some(session) => {
+            let session = session.lock().await;
+            (StatusCode::OK, Json(session.interventions())).into_response()
+        }
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("Session not found")),
+        )
+            .into_response(),
+    }
+}
+
+/// Get the status of the session's external controller (`null` if none)
+pub async fn get_controller(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+) -> impl IntoResponse {
+    match state.session_manager.get_session(&session_id).await {
+        Some(session) => {
+            let session = session.lock().await;
+            (StatusCode::OK, Json(session.controller_status())).into_response()
+        }
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("Session not found")),
+        )
+            .into_response(),
+    }
+}
+
 /// Delete a session
 pub async fn delete_session(
     State(state): State<Arc<AppState>>,
     Path(session_id): Path<String>,
 ) -> impl IntoResponse {
     if state.session_manager.remove_session(&session_id).await {
-        (
-            StatusCode::OK,
-            Json(serde_json::json!({"status": "deleted"})),
-        )
-            .into_response()
+        (StatusCode::OK, Json(StatusResponse::new("deleted"))).into_response()
     } else {
         (
             StatusCode::NOT_FOUND,
@@ -253,6 +377,475 @@ pub async fn delete_session(
     }
 }
 
+fn replay_conflict() -> axum::response::Response {
+    (
+        StatusCode::CONFLICT,
+        Json(ErrorResponse::new("Replay sessions cannot be changed")),
+    )
+        .into_response()
+}
+
+fn run_not_found() -> axum::response::Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(ErrorResponse::new("Run not found")),
+    )
+        .into_response()
+}
+
+fn storage_error(e: std::io::Error) -> axum::response::Response {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ErrorResponse::with_details(
+            "Run history error",
+            e.to_string(),
+        )),
+    )
+        .into_response()
+}
+
+/// List the caller's recorded runs, oldest first (empty without a data
+/// directory)
+pub async fn list_runs(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<Caller>,
+) -> impl IntoResponse {
+    let Some(history) = state.session_manager.history() else {
+        return Json(Vec::<crate::types::RunRecord>::new()).into_response();
+    };
+    match history.list() {
+        Ok(mut runs) => {
+            runs.retain(|run| caller.can_access(run.owner.as_deref()));
+            Json(runs).into_response()
+        }
+        Err(e) => storage_error(e),
+    }
+}
+
+/// Get a recorded run with its scenario, intervention log and final report
+pub async fn get_run(
+    State(state): State<Arc<AppState>>,
+    Path(run_id): Path<String>,
+) -> impl IntoResponse {
+    let Some(history) = state.session_manager.history() else {
+        return run_not_found();
+    };
+    match history.detail(&run_id) {
+        Ok(Some(run)) => (StatusCode::OK, Json(run)).into_response(),
+        Ok(None) => run_not_found(),
+        Err(e) => storage_error(e),
+    }
+}
+
+/// Get a recorded run's event log, optionally limited to `start_s..=end_s`
+pub async fn get_run_events(
+    State(state): State<Arc<AppState>>,
+    Path(run_id): Path<String>,
+    Query(query): Query<EventRangeQuery>,
+) -> impl IntoResponse {
+    let Some(history) = state.session_manager.history() else {
+        return run_not_found();
+    };
+    match history.record(&run_id) {
+        Ok(Some(run)) if run.status == RunStatus::Running => {
+            return (
+                StatusCode::CONFLICT,
+                Json(ErrorResponse::new("Run is still in progress")),
+            )
+                .into_response()
+        }
+        Ok(Some(_)) => {}
+        Ok(None) => return run_not_found(),
+        Err(e) => return storage_error(e),
+    }
+
+    let start_s = query.start_s.unwrap_or(0.0);
+    let end_s = query.end_s.unwrap_or(f64::MAX);
+    match history.events(&run_id, start_s, end_s) {
+        Ok(Some(events)) => (StatusCode::OK, Json(events)).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("Run has no event log")),
+        )
+            .into_response(),
+        Err(e) => storage_error(e),
+    }
+}
+
+/// Delete a recorded run and its event log
+pub async fn delete_run(
+    State(state): State<Arc<AppState>>,
+    Path(run_id): Path<String>,
+) -> impl IntoResponse {
+    let Some(history) = state.session_manager.history() else {
+        return run_not_found();
+    };
+    match history.record(&run_id) {
+        Ok(Some(run)) if run.status == RunStatus::Running => {
+            return (
+                StatusCode::CONFLICT,
+                Json(ErrorResponse::new(
+                    "Run is still in progress; delete its session first",
+                )),
+            )
+                .into_response()
+        }
+        Ok(Some(_)) => {}
+        Ok(None) => return run_not_found(),
+        Err(e) => return storage_error(e),
+    }
+
+    match history.delete(&run_id) {
+        Ok(_) => (StatusCode::OK, Json(StatusResponse::new("deleted"))).into_response(),
+        Err(e) => storage_error(e),
+    }
+}
+
+/// Re-open a recorded run as a new, paused replay session
+///
+/// The replay runs the recorded scenario and seed and re-applies the
+/// intervention log, so it reproduces the run. Its interventions and
+/// controller can't be changed.
+pub async fn reopen_run(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<Caller>,
+    Path(run_id): Path<String>,
+) -> impl IntoResponse {
+    let Some(history) = state.session_manager.history() else {
+        return run_not_found();
+    };
+    let run = match history.detail(&run_id) {
+        Ok(Some(run)) => run,
+        Ok(None) => return run_not_found(),
+        Err(e) => return storage_error(e),
+    };
+
+    match state
+        .session_manager
+        .create_replay_session(&run, &caller)
+        .await
+    {
+        Ok(session_id) => {
+            state.audit.record(
+                &caller,
+                "session.create",
+                serde_json::json!({ "session_id": session_id, "run_id": run_id }),
+            );
+            (
+                StatusCode::CREATED,
+                Json(SessionResponse {
+                    session_id,
+                    status: "replay".to_string(),
+                    warnings: Vec::new(),
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => session_error(e),
+    }
+}
+
+/// Submit a batch job (sweep, comparison or A/B test)
+///
+/// The job is queued and runs in the background; poll `GET /jobs/:id` for
+/// progress. Scenarios failing validation are rejected with 422.
+pub async fn submit_job(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<Caller>,
+    Json(request): Json<JobRequest>,
+) -> impl IntoResponse {
+    match state.job_manager.submit(request, &caller).await {
+        Ok(info) => {
+            state.audit.record(
+                &caller,
+                "job.submit",
+                serde_json::json!({
+                    "job_id": info.id,
+                    "kind": info.kind,
+                    "runs": info.total_runs,
+                }),
+            );
+            (StatusCode::ACCEPTED, Json(info)).into_response()
+        }
+        Err(JobError::Scenario(ScenarioError::Invalid(errors))) => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ErrorResponse::with_validation_errors(
+                "Scenario validation failed",
+                &errors,
+            )),
+        )
+            .into_response(),
+        Err(e @ JobError::Scenario(ScenarioError::NotFound(_))) => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(e.to_string())),
+        )
+            .into_response(),
+        Err(e @ JobError::Quota(_)) => (
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(ErrorResponse::new(e.to_string())),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.to_string())),
+        )
+            .into_response(),
+    }
+}
+
+/// List the caller's batch jobs, oldest first
+pub async fn list_jobs(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<Caller>,
+) -> impl IntoResponse {
+    let mut jobs = state.job_manager.list();
+    jobs.retain(|job| caller.can_access(job.owner.as_deref()));
+    Json(jobs)
+}
+
+/// Get a batch job's status and progress
+pub async fn get_job(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<String>,
+) -> impl IntoResponse {
+    match state.job_manager.get(&job_id) {
+        Some(info) => (StatusCode::OK, Json(info)).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("Job not found")),
+        )
+            .into_response(),
+    }
+}
+
+/// Cancel a batch job; runs already finished are discarded
+pub async fn cancel_job(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<String>,
+) -> impl IntoResponse {
+    match state.job_manager.cancel(&job_id).await {
+        Some(info) => (StatusCode::OK, Json(info)).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("Job not found")),
+        )
+            .into_response(),
+    }
+}
+
+/// Get a completed job's results as JSON, or its runs as CSV with `?format=csv`
+pub async fn get_job_results(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<String>,
+    Query(query): Query<JobResultsQuery>,
+) -> impl IntoResponse {
+    let Some((info, results)) = state.job_manager.results(&job_id) else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("Job not found")),
+        )
+            .into_response();
+    };
+    let Some(results) = results.filter(|_| info.status == JobStatus::Completed) else {
+        return (
+            StatusCode::CONFLICT,
+            Json(ErrorResponse::with_details(
+                "Job has no results",
+                format!("Job status is {:?}", info.status),
+            )),
+        )
+            .into_response();
+    };
+
+    match query.format.as_deref() {
+        None | Some("json") => (StatusCode::OK, Json(results)).into_response(),
+        Some("csv") => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, "text/csv")],
+            runs_csv(results.runs()),
+        )
+            .into_response(),
+        Some(other) => (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(format!(
+                "Unknown format '{}', expected 'json' or 'csv'",
+                other
+            ))),
+        )
+            .into_response(),
+    }
+}
+
+fn replay_not_found() -> axum::response::Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(ErrorResponse::new("Replay not found")),
+    )
+        .into_response()
+}
+
+fn replay_error(e: ReplayError) -> axum::response::Response {
+    match e {
+        ReplayError::Scenario(ScenarioError::Invalid(errors)) => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ErrorResponse::with_validation_errors(
+                "Scenario validation failed",
+                &errors,
+            )),
+        )
+            .into_response(),
+        e @ (ReplayError::RunNotFound(_)
+        | ReplayError::NoEventLog(_)
+        | ReplayError::Scenario(ScenarioError::NotFound(_))) => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(e.to_string())),
+        )
+            .into_response(),
+        e @ ReplayError::RunInProgress(_) => (
+            StatusCode::CONFLICT,
+            Json(ErrorResponse::new(e.to_string())),
+        )
+            .into_response(),
+        ReplayError::Io(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse::with_details(
+                "Replay storage error",
+                e.to_string(),
+            )),
+        )
+            .into_response(),
+        e => (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.to_string())),
+        )
+            .into_response(),
+    }
+}
+
+/// Create a paused replay of recorded runs and/or uploaded event logs
+///
+/// All of its event logs play at the same simulated time; stream them from
+/// `/ws/replays/:id`.
+pub async fn create_replay(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<Caller>,
+    Json(request): Json<ReplayRequest>,
+) -> impl IntoResponse {
+    match state.replay_manager.create(request, &caller).await {
+        Ok(info) => {
+            let names: Vec<_> = info.replays.iter().map(|track| &track.name).collect();
+            state.audit.record(
+                &caller,
+                "replay.create",
+                serde_json::json!({ "replay_id": info.replay_id, "replays": names }),
+            );
+            (StatusCode::CREATED, Json(info)).into_response()
+        }
+        Err(e) => replay_error(e),
+    }
+}
+
+/// List the caller's replays
+pub async fn list_replays(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<Caller>,
+) -> impl IntoResponse {
+    let mut replays = state.replay_manager.list().await;
+    replays.retain(|replay| caller.can_access(replay.owner.as_deref()));
+    Json(replays)
+}
+
+/// Get a replay's playback position and event logs
+pub async fn get_replay(
+    State(state): State<Arc<AppState>>,
+    Path(replay_id): Path<String>,
+) -> impl IntoResponse {
+    match state.replay_manager.get(&replay_id).await {
+        Some(replay) => (StatusCode::OK, Json(replay.info().await)).into_response(),
+        None => replay_not_found(),
+    }
+}
+
+/// Play a replay, optionally at a new speed
+pub async fn play_replay(
+    State(state): State<Arc<AppState>>,
+    Path(replay_id): Path<String>,
+    request: Option<Json<PlayRequest>>,
+) -> impl IntoResponse {
+    let Some(replay) = state.replay_manager.get(&replay_id).await else {
+        return replay_not_found();
+    };
+    let speed = request.and_then(|Json(r)| r.speed);
+    match replay.play(speed).await {
+        Ok(info) => (StatusCode::OK, Json(info)).into_response(),
+        Err(e) => replay_error(e),
+    }
+}
+
+/// Pause a replay
+pub async fn pause_replay(
+    State(state): State<Arc<AppState>>,
+    Path(replay_id): Path<String>,
+) -> impl IntoResponse {
+    match state.replay_manager.get(&replay_id).await {
+        Some(replay) => (StatusCode::OK, Json(replay.pause().await)).into_response(),
+        None => replay_not_found(),
+    }
+}
+
+/// Move all of a replay's event logs to the same time
+pub async fn seek_replay(
+    State(state): State<Arc<AppState>>,
+    Path(replay_id): Path<String>,
+    Json(request): Json<SeekRequest>,
+) -> impl IntoResponse {
+    let Some(replay) = state.replay_manager.get(&replay_id).await else {
+        return replay_not_found();
+    };
+    match replay.seek(request.time_s).await {
+        Ok(info) => (StatusCode::OK, Json(info)).into_response(),
+        Err(e) => replay_error(e),
+    }
+}
+
+/// Get the warehouse map of one of a replay's event logs
+pub async fn get_replay_map(
+    State(state): State<Arc<AppState>>,
+    Path(replay_id): Path<String>,
+    Query(query): Query<ReplayMapQuery>,
+) -> impl IntoResponse {
+    let Some(replay) = state.replay_manager.get(&replay_id).await else {
+        return replay_not_found();
+    };
+    match replay.map(query.replay.as_deref()).await {
+        Some(Some(map)) => (StatusCode::OK, Json(map)).into_response(),
+        Some(None) => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(
+                "Event log has no scenario to build a map from",
+            )),
+        )
+            .into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("No such event log in this replay")),
+        )
+            .into_response(),
+    }
+}
+
+/// Delete a replay
+pub async fn delete_replay(
+    State(state): State<Arc<AppState>>,
+    Path(replay_id): Path<String>,
+) -> impl IntoResponse {
+    if state.replay_manager.remove(&replay_id).await {
+        (StatusCode::OK, Json(StatusResponse::new("deleted"))).into_response()
+    } else {
+        replay_not_found()
+    }
+}
+
 /// Get available presets
 pub async fn get_presets() -> impl IntoResponse {
     let presets = vec![
@@ -288,10 +881,20 @@ pub async fn get_presets() -> impl IntoResponse {
     Json(presets)
 }
 
+/// List the scenarios in the server's scenario library
+pub async fn get_scenarios(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let names = state
+        .scenario_dir
+        .as_deref()
+        .map(list_library)
+        .unwrap_or_default();
+    Json(names)
+}
+
 /// Health check endpoint
 pub async fn health_check() -> impl IntoResponse {
-    Json(serde_json::json!({
-        "status": "healthy",
-        "version": env!("CARGO_PKG_VERSION"),
-    }))
+    Json(HealthResponse {
+        status: "healthy".to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+    })
 }
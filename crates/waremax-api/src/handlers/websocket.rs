@@ -7,14 +7,20 @@ use axum::{
     },
     response::IntoResponse,
 };
+use futures::stream::SplitSink;
 use futures::{SinkExt, StreamExt};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::sync::{mpsc, Mutex};
+use tokio::sync::{broadcast, mpsc, Mutex};
 
-use crate::simulation::SimUpdate;
+use crate::controller::{ControllerLink, DEFAULT_TIMEOUT_MS};
+use crate::metrics::SocketKind;
+use crate::simulation::{SimCommand, SimUpdate};
 use crate::state::AppState;
-use crate::types::WebSocketMessage;
+use crate::stream::ClientStream;
+use crate::types::{
+    ControllerClientMessage, ControllerServerMessage, StreamSubscription, WebSocketMessage,
+};
 
 /// WebSocket upgrade handler
 pub async fn websocket_handler(
@@ -28,8 +34,10 @@ pub async fn websocket_handler(
 /// Handle a WebSocket connection with heartbeat support.
 ///
 /// Sends `Ping` frames every 15s and closes the connection if no `Pong`
-/// is received within 30s.
+/// is received within 30s. Updates are filtered, throttled and turned
+/// into deltas as the client subscribed (see [`ClientStream`]).
 async fn handle_socket(socket: WebSocket, state: Arc<AppState>, session_id: String) {
+    let _client = state.metrics.socket_opened(SocketKind::Session);
     let (mut sender, mut receiver) = socket.split();
 
     // Get the session
@@ -59,23 +67,31 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>, session_id: Stri
         .await;
 
     // Subscribe to simulation updates
-    let mut update_rx = {
+    let (mut update_rx, zones) = {
         let session = session.lock().await;
-        session.subscribe()
+        (session.subscribe(), session.zones().clone())
     };
 
     // Channel for forwarding command errors from the receiver task to the sender task
     let (error_tx, mut error_rx) = mpsc::unbounded_channel::<String>();
+    // and one for the client's subscriptions
+    let (subscribe_tx, mut subscribe_rx) = mpsc::unbounded_channel::<StreamSubscription>();
 
     // Shared last-pong timestamp
     let last_pong = Arc::new(Mutex::new(Instant::now()));
     let last_pong_rx = last_pong.clone();
 
-    // Spawn a task to forward simulation updates to the WebSocket
+    // Spawn a task to forward simulation updates to the WebSocket, as the
+    // client subscribed to them
+    let send_session = session.clone();
     let send_task = tokio::spawn(async move {
+        let mut stream = ClientStream::new();
+        let mut updates_open = true;
         let mut ping_interval = tokio::time::interval(Duration::from_secs(15));
+        // Sends state held back by the client's message budget
+        let mut flush_interval = tokio::time::interval(Duration::from_millis(50));
         loop {
-            tokio::select! {
+            let messages = tokio::select! {
                 _ = ping_interval.tick() => {
                     if sender.send(Message::Ping(vec![])).await.is_err() {
                         break;
@@ -85,83 +101,52 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>, session_id: Stri
                         let _ = sender.close().await;
                         break;
                     }
+                    continue;
                 }
-                Ok(update) = update_rx.recv() => {
-                    let msg = match update {
-                        SimUpdate::StateChanged(state) => WebSocketMessage::StateSync { state },
-                        SimUpdate::Tick {
-                            time_s,
-                            events_processed,
-                        } => WebSocketMessage::Tick {
-                            time_s,
-                            events_processed,
-                        },
-                        SimUpdate::RobotMoved {
-                            robot_id,
-                            from_node,
-                            to_node,
-                            time_s,
-                        } => WebSocketMessage::RobotMoved {
-                            robot_id,
-                            from_node,
-                            to_node,
-                            time_s,
-                        },
-                        SimUpdate::RobotStateChanged {
-                            robot_id,
-                            old_state,
-                            new_state,
-                            time_s,
-                        } => WebSocketMessage::RobotStateChanged {
-                            robot_id,
-                            old_state,
-                            new_state,
-                            time_s,
-                        },
-                        SimUpdate::RobotPositionUpdate {
-                            robot_id,
-                            edge_id,
-                            from_node,
-                            to_node,
-                            progress,
-                            time_s,
-                        } => WebSocketMessage::RobotPositionUpdate {
-                            robot_id,
-                            edge_id,
-                            from_node,
-                            to_node,
-                            progress,
-                            time_s,
-                        },
-                        SimUpdate::OrderCompleted {
-                            order_id,
-                            cycle_time_s,
-                            on_time,
-                        } => WebSocketMessage::OrderCompleted {
-                            order_id,
-                            cycle_time_s,
-                            on_time,
-                        },
-                        SimUpdate::MetricsUpdate(metrics) => WebSocketMessage::MetricsUpdate { metrics },
-                        SimUpdate::Finished(final_metrics) => WebSocketMessage::Finished { final_metrics },
-                        SimUpdate::Error(message) => WebSocketMessage::Error { message },
-                    };
-
-                    let json = match serde_json::to_string(&msg) {
-                        Ok(j) => j,
-                        Err(_) => continue,
-                    };
-
-                    if sender.send(Message::Text(json)).await.is_err() {
-                        break;
+                _ = flush_interval.tick() => stream.flush(Instant::now()).into_iter().collect(),
+                update = update_rx.recv(), if updates_open => match update {
+                    Ok(update) => stream.update(update, Instant::now()),
+                    // Fell behind the broadcast: skip to the current state
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::debug!(session_id = %session_id, skipped, "WebSocket client lagged");
+                        let state = send_session.lock().await.latest_state();
+                        state
+                            .map(|state| stream.keyframe(state, Instant::now()))
+                            .into_iter()
+                            .collect()
+                    }
+                    Err(broadcast::error::RecvError::Closed) => {
+                        updates_open = false;
+                        continue;
+                    }
+                },
+                Some(subscription) = subscribe_rx.recv() => {
+                    match stream.subscribe(subscription, &zones) {
+                        Ok(()) => {
+                            let state = send_session.lock().await.latest_state();
+                            state
+                                .map(|state| stream.keyframe(state, Instant::now()))
+                                .into_iter()
+                                .collect()
+                        }
+                        Err(message) => vec![WebSocketMessage::Error { message }],
                     }
                 }
                 Some(err_json) = error_rx.recv() => {
                     if sender.send(Message::Text(err_json)).await.is_err() {
                         break;
                     }
+                    continue;
+                }
+            };
+
+            for message in messages {
+                let Ok(json) = serde_json::to_string(&message) else {
+                    continue;
+                };
+                if sender.send(Message::Text(json)).await.is_err() {
+                    return;
                 }
-                else => break,
             }
         }
     });
@@ -196,6 +181,10 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>, session_id: Stri
                             continue;
                         }
                         crate::types::ControlCommand::Stop => crate::simulation::SimCommand::Stop,
+                        crate::types::ControlCommand::Subscribe(subscription) => {
+                            let _ = subscribe_tx.send(subscription);
+                            continue;
+                        }
                     };
                     if let Err(e) = session.send_command(sim_cmd).await {
                         let err_msg = serde_json::to_string(&WebSocketMessage::Error {
@@ -215,3 +204,291 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>, session_id: Stri
     // Cancel the send task when the connection closes
     send_task.abort();
 }
+
+/// External controller upgrade handler
+pub async fn controller_handler(
+    ws: WebSocketUpgrade,
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| handle_controller_socket(socket, state, session_id))
+}
+
+/// Serve an external controller.
+///
+/// The first message must be a `Register`. Its roles are then handed to the
+/// simulation, and decision requests and replies are relayed until either
+/// side ends, when the session's own policies take over again.
+async fn handle_controller_socket(socket: WebSocket, state: Arc<AppState>, session_id: String) {
+    let _client = state.metrics.socket_opened(SocketKind::Controller);
+    let (mut sender, mut receiver) = socket.split();
+
+    let Some(session) = state.session_manager.get_session(&session_id).await else {
+        let error = ControllerServerMessage::Error {
+            message: "Session not found".to_string(),
+        };
+        send_controller_message(&mut sender, &error).await;
+        return;
+    };
+    if session.lock().await.replay_of.is_some() {
+        let error = ControllerServerMessage::Error {
+            message: "Replay sessions cannot be changed".to_string(),
+        };
+        send_controller_message(&mut sender, &error).await;
+        return;
+    }
+
+    // Wait for the registration, skipping control frames
+    let register = tokio::time::timeout(Duration::from_secs(30), async {
+        while let Some(Ok(msg)) = receiver.next().await {
+            match msg {
+                Message::Text(text) => {
+                    return serde_json::from_str::<ControllerClientMessage>(&text).ok()
+                }
+                Message::Close(_) => return None,
+                _ => {}
+            }
+        }
+        None
+    })
+    .await;
+    let Ok(Some(ControllerClientMessage::Register {
+        roles,
+        timeout_ms,
+        fallback_allocation,
+        fallback_station,
+    })) = register
+    else {
+        let error = ControllerServerMessage::Error {
+            message: "Expected a Register message".to_string(),
+        };
+        send_controller_message(&mut sender, &error).await;
+        return;
+    };
+    if roles.is_empty() {
+        let error = ControllerServerMessage::Error {
+            message: "Register at least one role".to_string(),
+        };
+        send_controller_message(&mut sender, &error).await;
+        return;
+    }
+
+    let (request_tx, mut request_rx) = mpsc::unbounded_channel();
+    let (reply_tx, reply_rx) = std::sync::mpsc::channel();
+    let link = Arc::new(ControllerLink::new(
+        roles,
+        timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS),
+        request_tx,
+        reply_rx,
+    ));
+
+    // Subscribe before attaching so the end of the run is never missed
+    let mut update_rx = {
+        let session = session.lock().await;
+        let update_rx = session.subscribe();
+        let attach = SimCommand::AttachController {
+            link: link.clone(),
+            fallback_allocation,
+            fallback_station,
+        };
+        if let Err(e) = session.send_command(attach).await {
+            let error = ControllerServerMessage::Error {
+                message: format!("Command failed: {}", e),
+            };
+            send_controller_message(&mut sender, &error).await;
+            return;
+        }
+        update_rx
+    };
+
+    loop {
+        tokio::select! {
+            Some(request) = request_rx.recv() => {
+                let rejected = matches!(request, ControllerServerMessage::Error { .. });
+                if !send_controller_message(&mut sender, &request).await || rejected {
+                    break;
+                }
+            }
+            update = update_rx.recv() => match update {
+                Ok(SimUpdate::Finished(_)) => {
+                    send_controller_message(&mut sender, &ControllerServerMessage::Finished).await;
+                    break;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+                _ => {}
+            },
+            msg = receiver.next() => match msg {
+                Some(Ok(Message::Text(text))) => {
+                    match serde_json::from_str::<ControllerClientMessage>(&text) {
+                        Ok(ControllerClientMessage::Decide { request_id, choice }) => {
+                            let _ = reply_tx.send((request_id, choice));
+                        }
+                        Ok(ControllerClientMessage::Register { .. }) => {
+                            let error = ControllerServerMessage::Error {
+                                message: "Already registered".to_string(),
+                            };
+                            send_controller_message(&mut sender, &error).await;
+                        }
+                        Err(e) => {
+                            let error = ControllerServerMessage::Error {
+                                message: format!("Invalid message: {}", e),
+                            };
+                            send_controller_message(&mut sender, &error).await;
+                        }
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                _ => {}
+            },
+        }
+    }
+
+    // Stop waiting on this controller and restore the session's policies
+    link.disconnect();
+    let session = session.lock().await;
+    let _ = session
+        .send_command(SimCommand::DetachController(link))
+        .await;
+}
+
+/// Send one controller message; false when the connection is gone
+async fn send_controller_message(
+    sender: &mut SplitSink<WebSocket, Message>,
+    message: &ControllerServerMessage,
+) -> bool {
+    match serde_json::to_string(message) {
+        Ok(json) => sender.send(Message::Text(json)).await.is_ok(),
+        Err(_) => true,
+    }
+}
+
+/// Replay upgrade handler
+pub async fn replay_handler(
+    ws: WebSocketUpgrade,
+    State(state): State<Arc<AppState>>,
+    Path(replay_id): Path<String>,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| handle_replay_socket(socket, state, replay_id))
+}
+
+/// Stream a replay's messages, starting with a keyframe per event log.
+///
+/// Accepts the `Start`/`Resume`, `Pause` and `SetSpeed` control commands;
+/// seeking is done over REST. A client that falls behind the broadcast
+/// skips ahead to fresh keyframes.
+async fn handle_replay_socket(socket: WebSocket, state: Arc<AppState>, replay_id: String) {
+    let _client = state.metrics.socket_opened(SocketKind::Replay);
+    let (mut sender, mut receiver) = socket.split();
+
+    let Some(replay) = state.replay_manager.get(&replay_id).await else {
+        let _ = sender
+            .send(Message::Text(
+                serde_json::to_string(&WebSocketMessage::Error {
+                    message: "Replay not found".to_string(),
+                })
+                .unwrap(),
+            ))
+            .await;
+        return;
+    };
+
+    let _ = sender
+        .send(Message::Text(
+            serde_json::to_string(&WebSocketMessage::Connected {
+                session_id: replay_id.clone(),
+            })
+            .unwrap(),
+        ))
+        .await;
+
+    let (keyframes, mut update_rx) = replay.subscribe().await;
+    for keyframe in &keyframes {
+        if let Ok(json) = serde_json::to_string(keyframe) {
+            let _ = sender.send(Message::Text(json)).await;
+        }
+    }
+
+    let (error_tx, mut error_rx) = mpsc::unbounded_channel::<String>();
+    let last_pong = Arc::new(Mutex::new(Instant::now()));
+    let last_pong_rx = last_pong.clone();
+
+    let send_replay = replay.clone();
+    let send_task = tokio::spawn(async move {
+        let mut ping_interval = tokio::time::interval(Duration::from_secs(15));
+        loop {
+            let messages = tokio::select! {
+                _ = ping_interval.tick() => {
+                    if sender.send(Message::Ping(vec![])).await.is_err() {
+                        break;
+                    }
+                    if last_pong.lock().await.elapsed() > Duration::from_secs(30) {
+                        tracing::warn!(replay_id = %replay_id, "WebSocket pong timeout");
+                        let _ = sender.close().await;
+                        break;
+                    }
+                    continue;
+                }
+                update = update_rx.recv() => match update {
+                    Ok(message) => vec![message],
+                    Err(broadcast::error::RecvError::Lagged(_)) => send_replay.keyframes().await,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                Some(err_json) = error_rx.recv() => {
+                    if sender.send(Message::Text(err_json)).await.is_err() {
+                        break;
+                    }
+                    continue;
+                }
+            };
+
+            for message in messages {
+                let Ok(json) = serde_json::to_string(&message) else {
+                    continue;
+                };
+                if sender.send(Message::Text(json)).await.is_err() {
+                    return;
+                }
+            }
+        }
+    });
+
+    while let Some(msg) = receiver.next().await {
+        match msg {
+            Ok(Message::Pong(_)) => {
+                *last_pong_rx.lock().await = Instant::now();
+            }
+            Ok(Message::Text(text)) => {
+                let Ok(cmd) = serde_json::from_str::<crate::types::ControlCommand>(&text) else {
+                    continue;
+                };
+                let result = match cmd {
+                    crate::types::ControlCommand::Start | crate::types::ControlCommand::Resume => {
+                        replay.play(None).await.map(|_| ())
+                    }
+                    crate::types::ControlCommand::Pause => {
+                        replay.pause().await;
+                        Ok(())
+                    }
+                    crate::types::ControlCommand::SetSpeed { speed } => {
+                        replay.set_speed(speed).await.map(|_| ())
+                    }
+                    _ => Err(crate::replay::ReplayError::Spec(
+                        "Replays only accept Start, Resume, Pause and SetSpeed".to_string(),
+                    )),
+                };
+                if let Err(e) = result {
+                    if let Ok(err_json) = serde_json::to_string(&WebSocketMessage::Error {
+                        message: format!("Command failed: {}", e),
+                    }) {
+                        let _ = error_tx.send(err_json);
+                    }
+                }
+            }
+            Ok(Message::Close(_)) => break,
+            Err(_) => break,
+            _ => {}
+        }
+    }
+
+    send_task.abort();
+}
@@ -0,0 +1,362 @@
+//! Run history persisted in sled
+//!
+//! With a data directory configured, every session is recorded as a run: its
+//! scenario (which carries the seed), intervention log, final
+//! `SimulationReport` and, if asked for, its event log. Runs outlive their
+//! session, so they can be listed, inspected, deleted, or re-opened as a
+//! replay session after the session has expired or the server restarted.
+//!
+//! Layout under the data directory:
+//! - `history/`: sled database with one tree each for run records,
+//!   scenarios, intervention logs and reports, keyed by run id
+//! - `events/<run id>/`: the run's `EventLog`
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::Utc;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sled::{Db, Tree};
+use waremax_config::ScenarioConfig;
+use waremax_metrics::{EventLogReader, SimulationReport, TraceEntry};
+
+use crate::types::{InterventionRecord, MetricsSnapshot, RunDetail, RunRecord, RunStatus};
+
+fn sled_error(e: sled::Error) -> io::Error {
+    io::Error::other(format!("sled error: {:?}", e))
+}
+
+/// Persistent store of recorded runs
+#[derive(Debug)]
+pub struct RunHistory {
+    db: Db,
+    runs: Tree,
+    scenarios: Tree,
+    interventions: Tree,
+    reports: Tree,
+    events_dir: PathBuf,
+    /// An event log can only be opened once at a time
+    reading_events: Mutex<()>,
+}
+
+impl RunHistory {
+    /// Open or create the history in `data_dir`. Runs left `running` by a
+    /// previous server process are marked stopped.
+    pub fn open(data_dir: &Path) -> io::Result<Self> {
+        let db = sled::open(data_dir.join("history")).map_err(sled_error)?;
+        let history = Self {
+            runs: db.open_tree("runs").map_err(sled_error)?,
+            scenarios: db.open_tree("scenarios").map_err(sled_error)?,
+            interventions: db.open_tree("interventions").map_err(sled_error)?,
+            reports: db.open_tree("reports").map_err(sled_error)?,
+            events_dir: data_dir.join("events"),
+            reading_events: Mutex::new(()),
+            db,
+        };
+
+        for mut record in history.list()? {
+            if record.status == RunStatus::Running {
+                record.status = RunStatus::Stopped;
+                history.put(&history.runs, &record.id, &record)?;
+            }
+        }
+        history.db.flush().map_err(sled_error)?;
+
+        Ok(history)
+    }
+
+    /// Record a new run for session `id`, owned by the API key `owner`
+    pub fn begin(
+        &self,
+        id: &str,
+        source: &str,
+        scenario: &ScenarioConfig,
+        event_log: bool,
+        owner: Option<&str>,
+    ) -> io::Result<()> {
+        let record = RunRecord {
+            id: id.to_string(),
+            status: RunStatus::Running,
+            source: source.to_string(),
+            owner: owner.map(str::to_string),
+            seed: scenario.seed,
+            created_at: Utc::now().to_rfc3339(),
+            finished_at: None,
+            event_log,
+            interventions: 0,
+            final_metrics: None,
+        };
+        self.put(&self.scenarios, id, scenario)?;
+        self.put(&self.runs, id, &record)?;
+        self.db.flush().map_err(sled_error)?;
+        Ok(())
+    }
+
+    /// Where the event log of run `id` is kept
+    pub fn event_log_path(&self, id: &str) -> PathBuf {
+        self.events_dir.join(id)
+    }
+
+    /// Save the outcome of a run that simulated to the end
+    pub fn finish(
+        &self,
+        id: &str,
+        interventions: &[InterventionRecord],
+        final_metrics: MetricsSnapshot,
+        report: &SimulationReport,
+    ) -> io::Result<()> {
+        // A run deleted while its session was live stays deleted
+        let Some(mut record) = self.record(id)? else {
+            return Ok(());
+        };
+        record.status = RunStatus::Finished;
+        record.finished_at = Some(Utc::now().to_rfc3339());
+        record.interventions = interventions.len();
+        record.final_metrics = Some(final_metrics);
+
+        self.put(&self.interventions, id, &interventions)?;
+        self.put(&self.reports, id, report)?;
+        self.put(&self.runs, id, &record)?;
+        self.db.flush().map_err(sled_error)?;
+        Ok(())
+    }
+
+    /// Mark a run whose session ended early as stopped, keeping the
+    /// interventions applied so far. Finished runs are left as they are.
+    pub fn stop(&self, id: &str, interventions: &[InterventionRecord]) -> io::Result<()> {
+        let Some(mut record) = self.record(id)? else {
+            return Ok(());
+        };
+        if record.status != RunStatus::Running {
+            return Ok(());
+        }
+        record.status = RunStatus::Stopped;
+        record.finished_at = Some(Utc::now().to_rfc3339());
+        record.interventions = interventions.len();
+
+        self.put(&self.interventions, id, &interventions)?;
+        self.put(&self.runs, id, &record)?;
+        self.db.flush().map_err(sled_error)?;
+        Ok(())
+    }
+
+    /// All runs, oldest first
+    pub fn list(&self) -> io::Result<Vec<RunRecord>> {
+        let mut runs = self
+            .runs
+            .iter()
+            .values()
+            .map(|value| decode(&value.map_err(sled_error)?))
+            .collect::<io::Result<Vec<RunRecord>>>()?;
+        runs.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        Ok(runs)
+    }
+
+    pub fn record(&self, id: &str) -> io::Result<Option<RunRecord>> {
+        self.get(&self.runs, id)
+    }
+
+    /// A run with its scenario, intervention log and report
+    pub fn detail(&self, id: &str) -> io::Result<Option<RunDetail>> {
+        let Some(record) = self.record(id)? else {
+            return Ok(None);
+        };
+        let Some(scenario) = self.get(&self.scenarios, id)? else {
+            return Ok(None);
+        };
+        Ok(Some(RunDetail {
+            record,
+            scenario,
+            intervention_log: self.get(&self.interventions, id)?.unwrap_or_default(),
+            report: self.get(&self.reports, id)?,
+        }))
+    }
+
+    /// Events of a run's event log between `start_s` and `end_s`. `None` if
+    /// the run has no event log.
+    pub fn events(
+        &self,
+        id: &str,
+        start_s: f64,
+        end_s: f64,
+    ) -> io::Result<Option<Vec<TraceEntry>>> {
+        let path = self.event_log_path(id);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let _reading = self
+            .reading_events
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let reader = EventLogReader::open(&path)?;
+        Ok(Some(reader.get_events(start_s, end_s)))
+    }
+
+    /// Delete a run and its event log. Returns false if there was no such run.
+    pub fn delete(&self, id: &str) -> io::Result<bool> {
+        let existed = self.runs.remove(id).map_err(sled_error)?.is_some();
+        for tree in [&self.scenarios, &self.interventions, &self.reports] {
+            tree.remove(id).map_err(sled_error)?;
+        }
+        self.db.flush().map_err(sled_error)?;
+
+        let path = self.event_log_path(id);
+        if path.exists() {
+            std::fs::remove_dir_all(path)?;
+        }
+        Ok(existed)
+    }
+
+    fn put<T: Serialize + ?Sized>(&self, tree: &Tree, id: &str, value: &T) -> io::Result<()> {
+        let bytes =
+            serde_json::to_vec(value).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        tree.insert(id, bytes).map_err(sled_error)?;
+        Ok(())
+    }
+
+    fn get<T: DeserializeOwned>(&self, tree: &Tree, id: &str) -> io::Result<Option<T>> {
+        tree.get(id)
+            .map_err(sled_error)?
+            .map(|value| decode(&value))
+            .transpose()
+    }
+}
+
+fn decode<T: DeserializeOwned>(bytes: &[u8]) -> io::Result<T> {
+    serde_json::from_slice(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::{ControllableSimulation, SimulationConfig};
+    use crate::types::Intervention;
+
+    fn scenario() -> ScenarioConfig {
+        ControllableSimulation::build_scenario(&SimulationConfig::default())
+    }
+
+    /// Open the history again after dropping it. sled's flusher thread can
+    /// hold the database lock for a moment after the last handle goes away.
+    fn reopen(dir: &Path) -> RunHistory {
+        for _ in 0..100 {
+            if let Ok(history) = RunHistory::open(dir) {
+                return history;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        RunHistory::open(dir).unwrap()
+    }
+
+    fn interventions() -> Vec<InterventionRecord> {
+        vec![InterventionRecord {
+            at_event: 12,
+            time_s: 3.5,
+            intervention: Intervention::CloseStation { station_id: 0 },
+        }]
+    }
+
+    #[test]
+    fn test_finished_run_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = RunHistory::open(dir.path()).unwrap();
+        let scenario = scenario();
+        history
+            .begin("a", "standard", &scenario, false, Some("tenant"))
+            .unwrap();
+
+        let running = history.record("a").unwrap().unwrap();
+        assert_eq!(running.status, RunStatus::Running);
+        assert_eq!(running.seed, scenario.seed);
+        assert_eq!(running.owner.as_deref(), Some("tenant"));
+
+        let metrics = MetricsSnapshot {
+            orders_completed: 7,
+            ..Default::default()
+        };
+        let report = SimulationReport::new(3600.0, 500, 7, 1, 60.0, 90.0, 0.5, 0.4);
+        history
+            .finish("a", &interventions(), metrics.clone(), &report)
+            .unwrap();
+
+        let detail = history.detail("a").unwrap().unwrap();
+        assert_eq!(detail.record.status, RunStatus::Finished);
+        assert!(detail.record.finished_at.is_some());
+        assert_eq!(detail.record.interventions, 1);
+        assert_eq!(detail.record.final_metrics, Some(metrics));
+        assert_eq!(detail.intervention_log, interventions());
+        assert_eq!(detail.scenario.seed, scenario.seed);
+        assert_eq!(detail.report.unwrap().orders_completed, 7);
+        assert!(history.events("a", 0.0, f64::MAX).unwrap().is_none());
+
+        // Stopping a finished run changes nothing
+        history.stop("a", &[]).unwrap();
+        assert_eq!(
+            history.record("a").unwrap().unwrap().status,
+            RunStatus::Finished
+        );
+
+        std::fs::create_dir_all(history.event_log_path("a")).unwrap();
+        assert!(history.delete("a").unwrap());
+        assert!(!history.event_log_path("a").exists());
+        assert!(history.detail("a").unwrap().is_none());
+        assert!(history.list().unwrap().is_empty());
+        assert!(!history.delete("a").unwrap());
+    }
+
+    #[test]
+    fn test_stopped_run_keeps_its_interventions() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = RunHistory::open(dir.path()).unwrap();
+        history
+            .begin("a", "standard", &scenario(), false, None)
+            .unwrap();
+        history
+            .begin("b", "inline", &scenario(), false, None)
+            .unwrap();
+        history.stop("a", &interventions()).unwrap();
+
+        let detail = history.detail("a").unwrap().unwrap();
+        assert_eq!(detail.record.status, RunStatus::Stopped);
+        assert_eq!(detail.intervention_log, interventions());
+        assert!(detail.report.is_none());
+
+        let ids: Vec<_> = history.list().unwrap().into_iter().map(|r| r.id).collect();
+        assert_eq!(ids, ["a", "b"]);
+
+        // A run deleted while its session is live stays deleted
+        history.delete("b").unwrap();
+        let report = SimulationReport::new(60.0, 1, 0, 0, 0.0, 0.0, 0.0, 0.0);
+        history
+            .finish("b", &[], MetricsSnapshot::default(), &report)
+            .unwrap();
+        assert!(history.record("b").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_reopening_stops_interrupted_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let history = RunHistory::open(dir.path()).unwrap();
+            history
+                .begin("done", "standard", &scenario(), false, None)
+                .unwrap();
+            let report = SimulationReport::new(60.0, 1, 0, 0, 0.0, 0.0, 0.0, 0.0);
+            history
+                .finish("done", &[], MetricsSnapshot::default(), &report)
+                .unwrap();
+            history
+                .begin("live", "standard", &scenario(), true, None)
+                .unwrap();
+        }
+
+        // The server went away with "live" still running
+        let history = reopen(dir.path());
+        let status = |id: &str| history.record(id).unwrap().unwrap().status;
+        assert_eq!(status("done"), RunStatus::Finished);
+        assert_eq!(status("live"), RunStatus::Stopped);
+        assert!(history.detail("live").unwrap().unwrap().record.event_log);
+    }
+}
@@ -0,0 +1,825 @@
+//! Batch jobs: parameter sweeps, comparisons and A/B tests
+//!
+//! Jobs run in the background on a bounded pool of workers, each running its
+//! simulations in parallel with a `BatchRunner`. Progress is counted per
+//! finished run and a cancelled job stops before its next run. With a job
+//! directory configured, every job is saved as JSON so it survives a server
+//! restart: finished jobs keep their results and unfinished ones are queued
+//! again from the start.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::Semaphore;
+use tokio::task::JoinError;
+use uuid::Uuid;
+use waremax_config::ScenarioConfig;
+use waremax_testing::{
+    ABTestConfig, ABTestRunner, BatchRunner, LabeledScenarios, RunResult, ScenarioComparator,
+    SweepGenerator,
+};
+
+use crate::auth::{Caller, QuotaError};
+use crate::scenario::{resolve_source, ScenarioError};
+use crate::types::{JobInfo, JobRequest, JobResults, JobStatus, RankedConfig, RunSummary};
+
+/// Most simulation runs a single job may ask for
+pub const MAX_JOB_RUNS: usize = 1000;
+
+/// Why a job could not be submitted
+#[derive(Error, Debug)]
+pub enum JobError {
+    #[error(transparent)]
+    Scenario(#[from] ScenarioError),
+
+    #[error("{0}")]
+    Spec(String),
+
+    #[error(transparent)]
+    Quota(#[from] QuotaError),
+}
+
+/// The concrete runs of a job, fixed at submission so a restarted job runs
+/// exactly what was asked for even if the scenario library has changed
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum JobPlan {
+    Sweep {
+        runs: LabeledScenarios,
+    },
+    Compare {
+        baseline: LabeledScenarios,
+        variant: LabeledScenarios,
+    },
+    AbTest {
+        baseline: Box<ScenarioConfig>,
+        variant: Box<ScenarioConfig>,
+        replications: u32,
+        alpha: f64,
+    },
+}
+
+impl JobPlan {
+    fn from_request(request: &JobRequest, library: Option<&Path>) -> Result<Self, JobError> {
+        let replications = match request {
+            JobRequest::Sweep { replications, .. }
+            | JobRequest::Compare { replications, .. }
+            | JobRequest::AbTest { replications, .. } => *replications,
+        };
+        if replications == 0 {
+            return Err(JobError::Spec(
+                "replications must be at least 1".to_string(),
+            ));
+        }
+
+        let plan = match request {
+            JobRequest::Sweep { base, sweep, .. } => {
+                let base = resolve_source(base, library)?.scenario;
+                let runs = SweepGenerator::from_config(base)
+                    .with_spec(sweep)
+                    .map_err(JobError::Spec)?
+                    .sweep_seeds(replications)
+                    .generate();
+                JobPlan::Sweep { runs }
+            }
+            JobRequest::Compare {
+                baseline, variant, ..
+            } => {
+                // Same seeds as `waremax compare`
+                let with_seeds = |label: &str, config: ScenarioConfig| {
+                    (0..replications as u64)
+                        .map(|i| {
+                            let mut config = config.clone();
+                            config.seed = 1000 + i;
+                            (format!("{}_seed={}", label, config.seed), config)
+                        })
+                        .collect::<LabeledScenarios>()
+                };
+                JobPlan::Compare {
+                    baseline: with_seeds("baseline", resolve_source(baseline, library)?.scenario),
+                    variant: with_seeds("variant", resolve_source(variant, library)?.scenario),
+                }
+            }
+            JobRequest::AbTest {
+                baseline,
+                variant,
+                alpha,
+                ..
+            } => {
+                if !(*alpha > 0.0 && *alpha < 1.0) {
+                    return Err(JobError::Spec(format!(
+                        "alpha must be between 0 and 1, got {}",
+                        alpha
+                    )));
+                }
+                JobPlan::AbTest {
+                    baseline: Box::new(resolve_source(baseline, library)?.scenario),
+                    variant: Box::new(resolve_source(variant, library)?.scenario),
+                    replications,
+                    alpha: *alpha,
+                }
+            }
+        };
+
+        let total = plan.total_runs();
+        if total > MAX_JOB_RUNS {
+            return Err(JobError::Spec(format!(
+                "Job would run {} simulations; the limit is {}",
+                total, MAX_JOB_RUNS
+            )));
+        }
+        Ok(plan)
+    }
+
+    fn total_runs(&self) -> usize {
+        match self {
+            JobPlan::Sweep { runs } => runs.len(),
+            JobPlan::Compare { baseline, variant } => baseline.len() + variant.len(),
+            JobPlan::AbTest { replications, .. } => 2 * *replications as usize,
+        }
+    }
+
+    /// Simulated minutes, warmup included, of all the job's runs
+    fn sim_minutes(&self) -> f64 {
+        let minutes = |config: &ScenarioConfig| {
+            config.simulation.warmup_minutes + config.simulation.duration_minutes
+        };
+        match self {
+            JobPlan::Sweep { runs } => runs.iter().map(|(_, config)| minutes(config)).sum(),
+            JobPlan::Compare { baseline, variant } => baseline
+                .iter()
+                .chain(variant)
+                .map(|(_, config)| minutes(config))
+                .sum(),
+            JobPlan::AbTest {
+                baseline,
+                variant,
+                replications,
+                ..
+            } => (minutes(baseline) + minutes(variant)) * *replications as f64,
+        }
+    }
+}
+
+/// A job as saved in the job directory
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct JobRecord {
+    info: JobInfo,
+    plan: JobPlan,
+    results: Option<JobResults>,
+}
+
+impl JobRecord {
+    /// Record how the job's run ended
+    fn finish(&mut self, outcome: Result<Result<Option<JobResults>, String>, JoinError>) {
+        match outcome {
+            Ok(Ok(Some(results))) => {
+                self.info.status = JobStatus::Completed;
+                self.results = Some(results);
+            }
+            Ok(Ok(None)) => self.info.status = JobStatus::Cancelled,
+            Ok(Err(message)) => {
+                tracing::error!(job_id = %self.info.id, error = %message, "Job analysis failed");
+                self.info.status = JobStatus::Failed;
+                self.info.error = Some(message);
+            }
+            Err(e) => {
+                tracing::error!(job_id = %self.info.id, error = %e, "Job failed");
+                self.info.status = JobStatus::Failed;
+                self.info.error = Some(format!("Job failed: {}", e));
+            }
+        }
+        self.info.finished_at = Some(Utc::now().to_rfc3339());
+    }
+}
+
+/// Save a job as `<id>.json` in `dir`
+fn write_record(dir: &Path, record: &JobRecord) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let json = serde_json::to_vec(record).map_err(|e| std::io::Error::other(e.to_string()))?;
+    // Write then rename so a crash never leaves a partial file
+    let tmp = dir.join(format!("{}.json.tmp", record.info.id));
+    std::fs::write(&tmp, json)?;
+    std::fs::rename(&tmp, dir.join(format!("{}.json", record.info.id)))
+}
+
+struct JobEntry {
+    record: Mutex<JobRecord>,
+    cancelled: AtomicBool,
+}
+
+impl JobEntry {
+    fn info(&self) -> JobInfo {
+        self.record.lock().unwrap().info.clone()
+    }
+}
+
+/// Runs batch jobs on a bounded pool of workers
+pub struct JobManager {
+    jobs: RwLock<HashMap<String, Arc<JobEntry>>>,
+    workers: Arc<Semaphore>,
+    job_dir: Option<PathBuf>,
+    scenario_dir: Option<PathBuf>,
+}
+
+impl JobManager {
+    /// Create a job manager running at most `workers` jobs at a time.
+    ///
+    /// Jobs saved in `job_dir` are loaded, and the unfinished ones queued
+    /// again. Must be called inside a Tokio runtime.
+    pub fn new(
+        workers: usize,
+        job_dir: Option<PathBuf>,
+        scenario_dir: Option<PathBuf>,
+    ) -> Arc<Self> {
+        let manager = Arc::new(Self {
+            jobs: RwLock::new(HashMap::new()),
+            workers: Arc::new(Semaphore::new(workers.max(1))),
+            job_dir,
+            scenario_dir,
+        });
+
+        for mut record in manager.load_saved() {
+            let resume = !record.info.status.is_finished();
+            if resume {
+                record.info.status = JobStatus::Queued;
+                record.info.completed_runs = 0;
+                record.info.started_at = None;
+                tracing::info!(job_id = %record.info.id, "Requeued unfinished job");
+            }
+            let entry = Arc::new(JobEntry {
+                record: Mutex::new(record),
+                cancelled: AtomicBool::new(false),
+            });
+            let id = entry.info().id;
+            manager.jobs.write().unwrap().insert(id, entry.clone());
+            if resume {
+                manager.schedule(entry);
+            }
+        }
+
+        manager
+    }
+
+    /// Validate and queue a job for `caller`, charging its simulated
+    /// minutes to the caller's quota
+    pub async fn submit(
+        self: &Arc<Self>,
+        request: JobRequest,
+        caller: &Caller,
+    ) -> Result<JobInfo, JobError> {
+        let plan = JobPlan::from_request(&request, self.scenario_dir.as_deref())?;
+        caller.charge(plan.sim_minutes())?;
+        let info = JobInfo {
+            id: Uuid::new_v4().to_string(),
+            kind: request.kind().to_string(),
+            owner: caller.name().map(str::to_string),
+            status: JobStatus::Queued,
+            total_runs: plan.total_runs(),
+            completed_runs: 0,
+            created_at: Utc::now().to_rfc3339(),
+            started_at: None,
+            finished_at: None,
+            error: None,
+        };
+        let record = JobRecord {
+            info: info.clone(),
+            plan,
+            results: None,
+        };
+        self.save(record.clone()).await;
+
+        let entry = Arc::new(JobEntry {
+            record: Mutex::new(record),
+            cancelled: AtomicBool::new(false),
+        });
+        self.jobs
+            .write()
+            .unwrap()
+            .insert(info.id.clone(), entry.clone());
+        self.schedule(entry);
+
+        Ok(info)
+    }
+
+    /// All jobs, oldest first
+    pub fn list(&self) -> Vec<JobInfo> {
+        let mut jobs: Vec<JobInfo> = self
+            .jobs
+            .read()
+            .unwrap()
+            .values()
+            .map(|entry| entry.info())
+            .collect();
+        jobs.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        jobs
+    }
+
+    pub fn get(&self, id: &str) -> Option<JobInfo> {
+        self.entry(id).map(|entry| entry.info())
+    }
+
+    /// A job with its results, which are present once it has completed
+    pub fn results(&self, id: &str) -> Option<(JobInfo, Option<JobResults>)> {
+        let entry = self.entry(id)?;
+        let record = entry.record.lock().unwrap();
+        Some((record.info.clone(), record.results.clone()))
+    }
+
+    /// Cancel a job. A queued job never starts; a running one stops before
+    /// its next simulation. Finished jobs are left as they are.
+    pub async fn cancel(&self, id: &str) -> Option<JobInfo> {
+        let entry = self.entry(id)?;
+        entry.cancelled.store(true, Ordering::Relaxed);
+
+        let cancelled = {
+            let mut record = entry.record.lock().unwrap();
+            if record.info.status != JobStatus::Queued {
+                return Some(record.info.clone());
+            }
+            record.info.status = JobStatus::Cancelled;
+            record.info.finished_at = Some(Utc::now().to_rfc3339());
+            record.clone()
+        };
+        let info = cancelled.info.clone();
+        self.save(cancelled).await;
+        Some(info)
+    }
+
+    fn entry(&self, id: &str) -> Option<Arc<JobEntry>> {
+        self.jobs.read().unwrap().get(id).cloned()
+    }
+
+    /// Run the job once a worker is free
+    fn schedule(self: &Arc<Self>, entry: Arc<JobEntry>) {
+        let manager = self.clone();
+        tokio::spawn(async move {
+            let Ok(_permit) = manager.workers.clone().acquire_owned().await else {
+                return;
+            };
+
+            let started = {
+                let mut record = entry.record.lock().unwrap();
+                if entry.cancelled.load(Ordering::Relaxed) {
+                    return;
+                }
+                record.info.status = JobStatus::Running;
+                record.info.started_at = Some(Utc::now().to_rfc3339());
+                record.clone()
+            };
+            let plan = started.plan.clone();
+            manager.save(started).await;
+
+            let worker_entry = entry.clone();
+            let outcome = tokio::task::spawn_blocking(move || execute(&worker_entry, &plan)).await;
+
+            let finished = {
+                let mut record = entry.record.lock().unwrap();
+                record.finish(outcome);
+                record.clone()
+            };
+            manager.save(finished).await;
+        });
+    }
+
+    /// Write a job to the job directory, off the async runtime. Callers
+    /// save a copy taken under the job's lock, never while holding it.
+    async fn save(&self, record: JobRecord) {
+        let Some(dir) = self.job_dir.clone() else {
+            return;
+        };
+        let id = record.info.id.clone();
+        let result = tokio::task::spawn_blocking(move || write_record(&dir, &record))
+            .await
+            .unwrap_or_else(|e| Err(std::io::Error::other(e.to_string())));
+        if let Err(e) = result {
+            tracing::warn!(job_id = %id, error = %e, "Failed to save job");
+        }
+    }
+
+    fn load_saved(&self) -> Vec<JobRecord> {
+        let Some(dir) = &self.job_dir else {
+            return Vec::new();
+        };
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        entries
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                if path.extension()? != "json" {
+                    return None;
+                }
+                let text = std::fs::read_to_string(&path).ok()?;
+                match serde_json::from_str(&text) {
+                    Ok(record) => Some(record),
+                    Err(e) => {
+                        tracing::warn!(path = %path.display(), error = %e, "Skipping unreadable job");
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+/// Run every simulation of the job and analyze them. `None` if the job was
+/// cancelled, an error if the runs could not be analyzed.
+fn execute(entry: &JobEntry, plan: &JobPlan) -> Result<Option<JobResults>, String> {
+    let run_all = |scenarios: LabeledScenarios| {
+        BatchRunner::new(scenarios).run_with_progress(&entry.cancelled, |_| {
+            entry.record.lock().unwrap().info.completed_runs += 1;
+        })
+    };
+    let summaries = |results: &[RunResult]| results.iter().map(RunSummary::from).collect();
+
+    let results = match plan {
+        JobPlan::Sweep { runs } => {
+            let results = run_all(runs.clone());
+            if entry.cancelled.load(Ordering::Relaxed) {
+                return Ok(None);
+            }
+            // Group replications by configuration, as `waremax sweep` does
+            let mut groups: HashMap<&str, Vec<RunResult>> = HashMap::new();
+            for result in &results {
+                let config = result.label.split("_seed=").next().unwrap_or(&result.label);
+                groups.entry(config).or_default().push(result.clone());
+            }
+            let mut comparator = ScenarioComparator::new();
+            for (config, runs) in groups {
+                comparator.add_results(config, runs);
+            }
+            let ranking = comparator
+                .rank_by_throughput()
+                .into_iter()
+                .map(|(label, throughput)| RankedConfig { label, throughput })
+                .collect();
+            JobResults::Sweep {
+                runs: summaries(&results),
+                ranking,
+            }
+        }
+        JobPlan::Compare { baseline, variant } => {
+            let results = run_all([baseline.clone(), variant.clone()].concat());
+            if entry.cancelled.load(Ordering::Relaxed) {
+                return Ok(None);
+            }
+            let (baseline_results, variant_results) = results.split_at(baseline.len());
+            if baseline_results.is_empty() || variant_results.is_empty() {
+                return Err(
+                    "Comparison needs at least one baseline and one variant run".to_string()
+                );
+            }
+            let mut comparator = ScenarioComparator::new();
+            comparator.add_results("baseline", baseline_results.to_vec());
+            comparator.add_results("variant", variant_results.to_vec());
+            let report = comparator
+                .compare("baseline", "variant")
+                .ok_or("Comparison of baseline and variant failed")?;
+            JobResults::Compare {
+                runs: summaries(&results),
+                report,
+            }
+        }
+        JobPlan::AbTest {
+            baseline,
+            variant,
+            replications,
+            alpha,
+        } => {
+            let runner = ABTestRunner::new(
+                ABTestConfig::new(*baseline.clone(), *variant.clone())
+                    .replications(*replications)
+                    .alpha(*alpha),
+            );
+            let (baseline_runs, variant_runs) = runner.scenarios();
+            let baseline_count = baseline_runs.len();
+            let results = run_all([baseline_runs, variant_runs].concat());
+            if entry.cancelled.load(Ordering::Relaxed) {
+                return Ok(None);
+            }
+            let (baseline_results, variant_results) = results.split_at(baseline_count);
+            JobResults::AbTest {
+                runs: summaries(&results),
+                result: runner.analyze(baseline_results, variant_results),
+            }
+        }
+    };
+    Ok(Some(results))
+}
+
+/// The runs of a job as CSV, one row per simulation
+pub fn runs_csv(runs: &[RunSummary]) -> String {
+    let mut csv = String::from(
+        "label,seed,throughput_per_hour,p95_cycle_time_s,robot_utilization,station_utilization,orders_completed,orders_late,duration_ms\n",
+    );
+    for run in runs {
+        let _ = writeln!(
+            csv,
+            "{},{},{:.2},{:.2},{:.4},{:.4},{},{},{}",
+            run.label,
+            run.seed,
+            run.throughput_per_hour,
+            run.p95_cycle_time_s,
+            run.robot_utilization,
+            run.station_utilization,
+            run.orders_completed,
+            run.orders_late,
+            run.duration_ms
+        );
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ScenarioSource;
+    use std::time::Duration;
+
+    /// A sweep of the small preset over `robots`
+    fn sweep(robots: &str, replications: u32) -> JobRequest {
+        JobRequest::Sweep {
+            base: ScenarioSource {
+                preset: Some("small".to_string()),
+                ..Default::default()
+            },
+            sweep: format!("robots:{}", robots),
+            replications,
+        }
+    }
+
+    /// Poll the job until `done` holds, recording the progress seen
+    async fn wait_for(
+        manager: &JobManager,
+        id: &str,
+        done: impl Fn(&JobInfo) -> bool,
+    ) -> (JobInfo, Vec<usize>) {
+        let mut progress = Vec::new();
+        for _ in 0..6000 {
+            let info = manager.get(id).unwrap();
+            progress.push(info.completed_runs);
+            if done(&info) {
+                return (info, progress);
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("job {} never got there", id);
+    }
+
+    /// Poll the job's file until it is saved with `status`
+    async fn wait_saved(dir: &Path, id: &str, status: JobStatus) -> JobRecord {
+        let path = dir.join(format!("{}.json", id));
+        for _ in 0..500 {
+            if let Ok(text) = std::fs::read_to_string(&path) {
+                let record: JobRecord = serde_json::from_str(&text).unwrap();
+                if record.info.status == status {
+                    return record;
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("job {} was never saved as {:?}", id, status);
+    }
+
+    #[tokio::test]
+    async fn test_job_runs_to_completion() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = JobManager::new(1, Some(dir.path().to_path_buf()), None);
+
+        let info = manager
+            .submit(sweep("1,2", 1), &Caller::anonymous())
+            .await
+            .unwrap();
+        assert_eq!(info.status, JobStatus::Queued);
+        assert_eq!(info.total_runs, 2);
+        wait_saved(dir.path(), &info.id, JobStatus::Queued).await;
+
+        let (done, progress) = wait_for(&manager, &info.id, |i| i.status.is_finished()).await;
+        assert_eq!(done.status, JobStatus::Completed);
+        assert_eq!(done.completed_runs, 2);
+        assert!(done.started_at.is_some() && done.finished_at.is_some());
+        assert!(progress.windows(2).all(|w| w[0] <= w[1]));
+
+        let Some(JobResults::Sweep { runs, ranking }) = manager.results(&info.id).unwrap().1 else {
+            panic!("expected sweep results");
+        };
+        assert_eq!(runs.len(), 2);
+        assert_eq!(ranking.len(), 2);
+
+        let saved = wait_saved(dir.path(), &info.id, JobStatus::Completed).await;
+        assert_eq!(saved.info.completed_runs, 2);
+        assert!(saved.results.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_queued_and_running_jobs() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = JobManager::new(1, Some(dir.path().to_path_buf()), None);
+        let caller = Caller::anonymous();
+
+        // The only worker is busy with a long job, so the second one queues
+        let long = manager.submit(sweep("1,2,3,4", 50), &caller).await.unwrap();
+        wait_for(&manager, &long.id, |i| i.status == JobStatus::Running).await;
+        let queued = manager.submit(sweep("1", 1), &caller).await.unwrap();
+
+        let cancelled = manager.cancel(&queued.id).await.unwrap();
+        assert_eq!(cancelled.status, JobStatus::Cancelled);
+        assert!(cancelled.finished_at.is_some());
+        wait_saved(dir.path(), &queued.id, JobStatus::Cancelled).await;
+
+        // A running job reports itself running until its runs stop
+        assert_eq!(
+            manager.cancel(&long.id).await.unwrap().status,
+            JobStatus::Running
+        );
+        let (stopped, _) = wait_for(&manager, &long.id, |i| i.status.is_finished()).await;
+        assert_eq!(stopped.status, JobStatus::Cancelled);
+        assert!(stopped.completed_runs < stopped.total_runs);
+        assert!(manager.results(&long.id).unwrap().1.is_none());
+        wait_saved(dir.path(), &long.id, JobStatus::Cancelled).await;
+
+        // The queued job never started, even once the worker was free
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let never_ran = manager.get(&queued.id).unwrap();
+        assert_eq!(never_ran.status, JobStatus::Cancelled);
+        assert_eq!(never_ran.started_at, None);
+        assert_eq!(never_ran.completed_runs, 0);
+    }
+
+    #[tokio::test]
+    async fn test_results_as_json_and_csv() {
+        use crate::{create_router, ApiConfig};
+        use axum::body::{to_bytes, Body};
+        use axum::http::{header, Request, StatusCode};
+        use tower::ServiceExt;
+
+        let app = create_router(ApiConfig::default());
+        let call = |request: Request<Body>| {
+            let app = app.clone();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let content_type = response
+                    .headers()
+                    .get(header::CONTENT_TYPE)
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or_default()
+                    .to_string();
+                let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (
+                    status,
+                    content_type,
+                    String::from_utf8(body.to_vec()).unwrap(),
+                )
+            }
+        };
+        let get = |uri: String| Request::get(uri).body(Body::empty()).unwrap();
+
+        let submit = Request::post("/api/jobs")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::to_string(&sweep("1,2", 1)).unwrap()))
+            .unwrap();
+        let (status, _, body) = call(submit).await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        let info: JobInfo = serde_json::from_str(&body).unwrap();
+
+        let mut finished = false;
+        for _ in 0..6000 {
+            let (_, _, body) = call(get(format!("/api/jobs/{}", info.id))).await;
+            let info: JobInfo = serde_json::from_str(&body).unwrap();
+            if info.status.is_finished() {
+                assert_eq!(info.status, JobStatus::Completed);
+                finished = true;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(finished);
+
+        let results = format!("/api/jobs/{}/results", info.id);
+        let (status, content_type, body) = call(get(results.clone())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, "application/json");
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["kind"], "sweep");
+        let runs = json["runs"].as_array().unwrap();
+        assert_eq!(runs.len(), 2);
+
+        let (status, content_type, csv) = call(get(format!("{}?format=csv", results))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, "text/csv");
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("label,seed,throughput_per_hour,"));
+        for (line, run) in lines[1..].iter().zip(runs) {
+            let label = run["label"].as_str().unwrap();
+            assert!(line.starts_with(&format!("{},{},", label, run["seed"])));
+            assert_eq!(line.split(',').count(), 9);
+        }
+
+        let (status, _, _) = call(get(format!("{}?format=xml", results))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_restart_requeues_unfinished_jobs() {
+        let dir = tempfile::tempdir().unwrap();
+        let plan = JobPlan::from_request(&sweep("1,2", 1), None).unwrap();
+        let info = |id: &str, status| JobInfo {
+            id: id.to_string(),
+            kind: "sweep".to_string(),
+            owner: Some("team".to_string()),
+            status,
+            total_runs: 2,
+            completed_runs: 1,
+            created_at: Utc::now().to_rfc3339(),
+            started_at: Some(Utc::now().to_rfc3339()),
+            finished_at: None,
+            error: None,
+        };
+        // A job the server was running when it went down, and a finished one
+        for (id, status) in [
+            ("interrupted", JobStatus::Running),
+            ("done", JobStatus::Failed),
+        ] {
+            let record = JobRecord {
+                info: info(id, status),
+                plan: plan.clone(),
+                results: None,
+            };
+            write_record(dir.path(), &record).unwrap();
+        }
+
+        let manager = JobManager::new(1, Some(dir.path().to_path_buf()), None);
+        let requeued = manager.get("interrupted").unwrap();
+        assert_eq!(requeued.status, JobStatus::Queued);
+        assert_eq!(requeued.completed_runs, 0);
+        assert_eq!(requeued.started_at, None);
+        assert_eq!(requeued.owner.as_deref(), Some("team"));
+
+        let (rerun, _) = wait_for(&manager, "interrupted", |i| i.status.is_finished()).await;
+        assert_eq!(rerun.status, JobStatus::Completed);
+        assert_eq!(rerun.completed_runs, 2);
+        wait_saved(dir.path(), "interrupted", JobStatus::Completed).await;
+
+        // Finished jobs are left as they were
+        let done = manager.get("done").unwrap();
+        assert_eq!(done.status, JobStatus::Failed);
+        assert_eq!(done.completed_runs, 1);
+    }
+
+    /// A saved comparison with no runs on either side, as a hand-edited or
+    /// truncated job file might hold
+    fn save_empty_comparison(dir: &Path) -> String {
+        let record = JobRecord {
+            info: JobInfo {
+                id: "empty-compare".to_string(),
+                kind: "compare".to_string(),
+                owner: None,
+                status: JobStatus::Running,
+                total_runs: 0,
+                completed_runs: 0,
+                created_at: Utc::now().to_rfc3339(),
+                started_at: None,
+                finished_at: None,
+                error: None,
+            },
+            plan: JobPlan::Compare {
+                baseline: Vec::new(),
+                variant: Vec::new(),
+            },
+            results: None,
+        };
+        std::fs::write(
+            dir.join("empty-compare.json"),
+            serde_json::to_vec(&record).unwrap(),
+        )
+        .unwrap();
+        record.info.id
+    }
+
+    #[tokio::test]
+    async fn test_failed_comparison_fails_the_job() {
+        let dir = tempfile::tempdir().unwrap();
+        let id = save_empty_comparison(dir.path());
+
+        let manager = JobManager::new(1, Some(dir.path().to_path_buf()), None);
+        let (info, _) = wait_for(&manager, &id, |i| i.status.is_finished()).await;
+
+        assert_eq!(info.status, JobStatus::Failed);
+        assert!(info.error.unwrap().contains("baseline"));
+        assert!(manager.results(&id).unwrap().1.is_none());
+
+        // The failure is saved, so a restart doesn't run the job again
+        wait_saved(dir.path(), &id, JobStatus::Failed).await;
+        let reloaded = JobManager::new(1, Some(dir.path().to_path_buf()), None);
+        assert_eq!(reloaded.get(&id).unwrap().status, JobStatus::Failed);
+    }
+}
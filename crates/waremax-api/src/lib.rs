@@ -4,12 +4,25 @@
 //! API. Consumers can embed this router in their own server or use the
 //! `waremax-api-server` binary crate.
 
+pub mod audit;
+pub mod auth;
+pub mod controller;
 pub mod handlers;
+pub mod history;
+pub mod jobs;
+pub mod metrics;
+pub mod openapi;
+pub mod replay;
+pub mod routes;
+pub mod scenario;
 pub mod server;
 pub mod session;
 pub mod simulation;
 pub mod state;
+pub mod stream;
 pub mod types;
 
+pub use auth::ApiKey;
+pub use scenario::{resolve_scenario, ResolvedScenario, ScenarioError};
 pub use server::{create_router, ApiConfig};
 pub use types::SessionConfig;
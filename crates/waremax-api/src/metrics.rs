@@ -0,0 +1,561 @@
+//! Prometheus/OpenMetrics endpoint
+//!
+//! `GET /metrics` reports the server (sessions, WebSocket clients, events
+//! per second, replays, jobs) and a set of gauges per live session taken
+//! from the state it last streamed, in the OpenMetrics text format. When API
+//! keys are configured it needs an admin key, since it covers every tenant.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+
+use crate::auth::Caller;
+use crate::simulation::events_processed_total;
+use crate::state::AppState;
+use crate::types::{ErrorResponse, JobStatus, SimulationState, SimulationStatus};
+
+/// Content type of the OpenMetrics text format
+pub const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// How often the events-per-second rate is sampled
+const RATE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Kind of WebSocket a client is connected to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SocketKind {
+    /// `/ws/:id`
+    Session,
+    /// `/ws/:id/controller`
+    Controller,
+    /// `/ws/replays/:id`
+    Replay,
+}
+
+impl SocketKind {
+    const ALL: [SocketKind; 3] = [
+        SocketKind::Session,
+        SocketKind::Controller,
+        SocketKind::Replay,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            SocketKind::Session => "session",
+            SocketKind::Controller => "controller",
+            SocketKind::Replay => "replay",
+        }
+    }
+}
+
+/// Server statistics that aren't kept anywhere else
+#[derive(Debug, Default)]
+pub struct ServerMetrics {
+    sockets: [AtomicUsize; 3],
+    /// Events processed per second by all sessions, as `f64` bits
+    events_per_second: AtomicU64,
+}
+
+impl ServerMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a WebSocket client until the returned guard is dropped
+    pub fn socket_opened(self: &Arc<Self>, kind: SocketKind) -> SocketGuard {
+        self.sockets[kind as usize].fetch_add(1, Ordering::Relaxed);
+        SocketGuard {
+            metrics: self.clone(),
+            kind,
+        }
+    }
+
+    fn sockets(&self, kind: SocketKind) -> usize {
+        self.sockets[kind as usize].load(Ordering::Relaxed)
+    }
+
+    fn events_per_second(&self) -> f64 {
+        f64::from_bits(self.events_per_second.load(Ordering::Relaxed))
+    }
+}
+
+/// A connected WebSocket client, counted while it lives
+pub struct SocketGuard {
+    metrics: Arc<ServerMetrics>,
+    kind: SocketKind,
+}
+
+impl Drop for SocketGuard {
+    fn drop(&mut self) {
+        self.metrics.sockets[self.kind as usize].fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Start a background task sampling the events-per-second rate
+pub fn start_metrics_task(metrics: Arc<ServerMetrics>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(RATE_INTERVAL);
+        let mut last = (Instant::now(), events_processed_total());
+        loop {
+            interval.tick().await;
+            let now = (Instant::now(), events_processed_total());
+            let elapsed = now.0.duration_since(last.0).as_secs_f64();
+            if elapsed > 0.0 {
+                let rate = now.1.saturating_sub(last.1) as f64 / elapsed;
+                metrics
+                    .events_per_second
+                    .store(rate.to_bits(), Ordering::Relaxed);
+            }
+            last = now;
+        }
+    })
+}
+
+/// Writer of the OpenMetrics text format
+#[derive(Default)]
+struct Exposition {
+    text: String,
+}
+
+impl Exposition {
+    /// Start a metric family; its samples must follow before the next one
+    fn family(&mut self, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(self.text, "# TYPE {} {}", name, kind);
+        let _ = writeln!(self.text, "# HELP {} {}", name, help);
+    }
+
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) {
+        self.text.push_str(name);
+        if !labels.is_empty() {
+            self.text.push('{');
+            for (i, (label, value)) in labels.iter().enumerate() {
+                if i > 0 {
+                    self.text.push(',');
+                }
+                let _ = write!(self.text, "{}=\"{}\"", label, escape(value));
+            }
+            self.text.push('}');
+        }
+        let _ = match value {
+            v if v.is_nan() => writeln!(self.text, " NaN"),
+            v if v.is_infinite() => writeln!(self.text, " {}Inf", if v > 0.0 { "+" } else { "-" }),
+            v => writeln!(self.text, " {}", v),
+        };
+    }
+
+    /// A family with a single unlabelled sample
+    fn single(&mut self, name: &str, kind: &str, help: &str, value: f64) {
+        self.family(name, kind, help);
+        let sample = match kind {
+            "counter" => format!("{}_total", name),
+            _ => name.to_string(),
+        };
+        self.sample(&sample, &[], value);
+    }
+
+    /// A family with one sample per session
+    fn per_session(
+        &mut self,
+        name: &str,
+        kind: &str,
+        help: &str,
+        states: &[(String, SimulationState)],
+        value: impl Fn(&SimulationState) -> Option<f64>,
+    ) {
+        self.family(name, kind, help);
+        let sample = match kind {
+            "counter" => format!("{}_total", name),
+            _ => name.to_string(),
+        };
+        for (id, state) in states {
+            if let Some(v) = value(state) {
+                self.sample(&sample, &[("session_id", id)], v);
+            }
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.text.push_str("# EOF\n");
+        self.text
+    }
+}
+
+/// Escape a label value
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn job_status_label(status: JobStatus) -> &'static str {
+    match status {
+        JobStatus::Queued => "queued",
+        JobStatus::Running => "running",
+        JobStatus::Completed => "completed",
+        JobStatus::Failed => "failed",
+        JobStatus::Cancelled => "cancelled",
+    }
+}
+
+/// Render every metric in the OpenMetrics text format
+pub async fn render(state: &AppState) -> String {
+    let mut out = Exposition::default();
+    let sessions = state.session_manager.session_states().await;
+
+    // Server
+    out.family("waremax_build", "info", "Version of the server");
+    out.sample(
+        "waremax_build_info",
+        &[("version", env!("CARGO_PKG_VERSION"))],
+        1.0,
+    );
+    out.single(
+        "waremax_sessions_active",
+        "gauge",
+        "Live simulation sessions",
+        state.session_manager.session_count().await as f64,
+    );
+    out.family(
+        "waremax_websocket_clients",
+        "gauge",
+        "Connected WebSocket clients by socket kind",
+    );
+    for kind in SocketKind::ALL {
+        out.sample(
+            "waremax_websocket_clients",
+            &[("kind", kind.label())],
+            state.metrics.sockets(kind) as f64,
+        );
+    }
+    out.single(
+        "waremax_events_processed",
+        "counter",
+        "Simulation events processed by all sessions",
+        events_processed_total() as f64,
+    );
+    out.single(
+        "waremax_events_per_second",
+        "gauge",
+        "Simulation events processed per second by all sessions, over the last 5 s",
+        state.metrics.events_per_second(),
+    );
+    out.single(
+        "waremax_replays_active",
+        "gauge",
+        "Live event-log replays",
+        state.replay_manager.list().await.len() as f64,
+    );
+    let mut jobs: BTreeMap<&str, usize> = [
+        JobStatus::Queued,
+        JobStatus::Running,
+        JobStatus::Completed,
+        JobStatus::Failed,
+        JobStatus::Cancelled,
+    ]
+    .into_iter()
+    .map(|status| (job_status_label(status), 0))
+    .collect();
+    for job in state.job_manager.list() {
+        *jobs.entry(job_status_label(job.status)).or_default() += 1;
+    }
+    out.family("waremax_jobs", "gauge", "Batch jobs by status");
+    for (status, count) in jobs {
+        out.sample("waremax_jobs", &[("status", status)], count as f64);
+    }
+
+    // Sessions
+    out.per_session(
+        "waremax_session_running",
+        "gauge",
+        "Whether the session is simulating (1) or idle, paused or finished (0)",
+        &sessions,
+        |s| Some(f64::from(u8::from(s.status == SimulationStatus::Running))),
+    );
+    out.per_session(
+        "waremax_session_sim_time_seconds",
+        "gauge",
+        "Simulated time",
+        &sessions,
+        |s| Some(s.time_s),
+    );
+    out.per_session(
+        "waremax_session_speed",
+        "gauge",
+        "Simulated seconds per wall-clock second",
+        &sessions,
+        |s| Some(s.speed),
+    );
+    out.per_session(
+        "waremax_session_events_processed",
+        "counter",
+        "Simulation events processed",
+        &sessions,
+        |s| Some(s.events_processed as f64),
+    );
+    out.per_session(
+        "waremax_session_orders_completed",
+        "counter",
+        "Orders completed",
+        &sessions,
+        |s| Some(s.metrics.orders_completed as f64),
+    );
+    out.per_session(
+        "waremax_session_orders_late",
+        "counter",
+        "Orders completed after their due time",
+        &sessions,
+        |s| Some(s.metrics.late_orders as f64),
+    );
+    out.per_session(
+        "waremax_session_orders_pending",
+        "gauge",
+        "Orders not yet completed",
+        &sessions,
+        |s| Some(s.metrics.orders_pending as f64),
+    );
+    out.per_session(
+        "waremax_session_throughput_per_hour",
+        "gauge",
+        "Orders completed per simulated hour since warmup",
+        &sessions,
+        |s| Some(s.metrics.throughput_per_hour),
+    );
+    out.per_session(
+        "waremax_session_sla_rate",
+        "gauge",
+        "Share of completed orders finished on time",
+        &sessions,
+        |s| {
+            let completed = s.metrics.orders_completed as f64;
+            (completed > 0.0)
+                .then(|| (1.0 - s.metrics.late_orders as f64 / completed).clamp(0.0, 1.0))
+        },
+    );
+    out.per_session(
+        "waremax_session_avg_cycle_time_seconds",
+        "gauge",
+        "Mean order cycle time",
+        &sessions,
+        |s| Some(s.metrics.avg_cycle_time_s),
+    );
+    out.per_session(
+        "waremax_session_robot_utilization",
+        "gauge",
+        "Share of robot time spent moving or being served",
+        &sessions,
+        |s| Some(s.metrics.robot_utilization),
+    );
+    out.per_session(
+        "waremax_session_station_utilization",
+        "gauge",
+        "Share of station capacity spent serving",
+        &sessions,
+        |s| Some(s.metrics.station_utilization),
+    );
+
+    out.family("waremax_session_robots", "gauge", "Robots by state");
+    for (id, session) in &sessions {
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for robot in &session.robots {
+            *counts.entry(robot.state.as_str()).or_default() += 1;
+        }
+        for (robot_state, count) in counts {
+            out.sample(
+                "waremax_session_robots",
+                &[("session_id", id), ("state", robot_state)],
+                count as f64,
+            );
+        }
+    }
+    out.family(
+        "waremax_session_station_queue_length",
+        "gauge",
+        "Robots queued at a station",
+    );
+    for (id, session) in &sessions {
+        for station in &session.stations {
+            out.sample(
+                "waremax_session_station_queue_length",
+                &[("session_id", id), ("station", &station.name)],
+                station.queue_length as f64,
+            );
+        }
+    }
+    out.family(
+        "waremax_session_station_serving",
+        "gauge",
+        "Robots being served at a station",
+    );
+    for (id, session) in &sessions {
+        for station in &session.stations {
+            out.sample(
+                "waremax_session_station_serving",
+                &[("session_id", id), ("station", &station.name)],
+                station.serving_count as f64,
+            );
+        }
+    }
+
+    out.finish()
+}
+
+/// Serve the metrics; admin keys only when API keys are configured
+pub async fn scrape(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<Caller>,
+) -> Response {
+    if !caller.can_access(None) {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(ErrorResponse::new("Metrics need an admin API key")),
+        )
+            .into_response();
+    }
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, CONTENT_TYPE)],
+        render(&state).await,
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{create_router, ApiConfig, ApiKey};
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use axum::Router;
+    use tower::ServiceExt;
+
+    async fn get(app: &Router, uri: &str, key: Option<&str>) -> (StatusCode, String, String) {
+        let mut request = Request::get(uri);
+        if let Some(key) = key {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", key));
+        }
+        let response = app
+            .clone()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (
+            status,
+            content_type,
+            String::from_utf8(body.to_vec()).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_scrape_metrics() {
+        let app = create_router(ApiConfig::default());
+        let response = app
+            .clone()
+            .oneshot(
+                Request::post("/api/session")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(r#"{"preset": "small", "duration_minutes": 5}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let session_id = created["session_id"].as_str().unwrap().to_string();
+
+        // The session's gauges appear once it has streamed its first state
+        let throughput = format!(
+            "waremax_session_throughput_per_hour{{session_id=\"{}\"}} ",
+            session_id
+        );
+        let mut text = String::new();
+        for _ in 0..50 {
+            let (status, content_type, body) = get(&app, "/metrics", None).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(content_type, CONTENT_TYPE);
+            text = body;
+            if text.contains(&throughput) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+
+        assert!(text.contains(&throughput), "{}", text);
+        assert!(text.contains("waremax_sessions_active 1\n"));
+        assert!(text.contains("waremax_websocket_clients{kind=\"session\"} 0\n"));
+        assert!(text.contains("waremax_jobs{status=\"queued\"} 0\n"));
+        assert!(text.contains(&format!(
+            "waremax_session_running{{session_id=\"{}\"}} 0\n",
+            session_id
+        )));
+        assert!(text.contains("waremax_session_robots{session_id="));
+        assert!(text.contains("waremax_session_station_queue_length{session_id="));
+        assert!(text.ends_with("# EOF\n"));
+
+        // Every sample belongs to the family declared last
+        let mut family = String::new();
+        for line in text.lines() {
+            if let Some(declared) = line.strip_prefix("# TYPE ") {
+                family = declared.split(' ').next().unwrap().to_string();
+            } else if !line.starts_with('#') {
+                assert!(line.starts_with(&family), "{} outside {}", line, family);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scrape_needs_admin_key() {
+        let key = |name: &str, admin| ApiKey {
+            name: name.to_string(),
+            key: format!("key-{}", name),
+            admin,
+            max_sessions: None,
+            sim_minutes_per_hour: None,
+        };
+        let app = create_router(ApiConfig {
+            api_keys: vec![key("ops", true), key("team", false)],
+            ..ApiConfig::default()
+        });
+
+        assert_eq!(
+            get(&app, "/metrics", None).await.0,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            get(&app, "/metrics", Some("key-team")).await.0,
+            StatusCode::FORBIDDEN
+        );
+        let (status, _, text) = get(&app, "/metrics", Some("key-ops")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(text.contains("waremax_sessions_active 0\n"));
+    }
+
+    #[test]
+    fn test_label_escaping() {
+        let mut out = Exposition::default();
+        out.family("m", "gauge", "help");
+        out.sample("m", &[("station", "a\"b\\c\nd")], 1.5);
+        out.sample("m", &[], f64::INFINITY);
+        assert_eq!(
+            out.finish(),
+            "# TYPE m gauge\n# HELP m help\nm{station=\"a\\\"b\\\\c\\nd\"} 1.5\nm +Inf\n# EOF\n"
+        );
+    }
+}
@@ -0,0 +1,327 @@
+//! OpenAPI document and WebSocket message schema
+//!
+//! Both are generated from the request and response types in [`crate::types`],
+//! so they can't drift from what the handlers accept and return. The
+//! OpenAPI 3.1 document is served at `/openapi.json` and the JSON Schema of
+//! every WebSocket message at `/websocket.schema.json`; clients can generate
+//! typed bindings from either.
+//!
+//! Scenario configurations, simulation reports, event log entries and the
+//! statistics of job results are described as free-form objects.
+
+use axum::{response::IntoResponse, Json};
+use schemars::{generate::SchemaSettings, JsonSchema, Schema, SchemaGenerator};
+use serde_json::{json, Map, Value};
+
+use crate::routes::{Reply, Route, ROUTES};
+use crate::types::{
+    ControlCommand, ControllerClientMessage, ControllerServerMessage, ErrorResponse, ReplayMessage,
+    WebSocketMessage,
+};
+
+pub(crate) type SchemaFn = fn(&mut SchemaGenerator) -> Schema;
+
+pub(crate) fn schema<T: JsonSchema>(generator: &mut SchemaGenerator) -> Schema {
+    generator.subschema_for::<T>()
+}
+
+fn error_description(status: u16) -> &'static str {
+    match status {
+        400 => "Invalid request",
+        401 => "Missing or invalid API key",
+        403 => "Needs an admin API key",
+        404 => "Not found",
+        409 => "Conflicts with the resource's state",
+        422 => "Scenario validation failed",
+        429 => "Session or simulated-minute quota exceeded",
+        _ => "Server error",
+    }
+}
+
+/// `/api/session/:id` in OpenAPI's syntax, `/api/session/{id}`
+fn openapi_path(path: &str) -> String {
+    path.split('/')
+        .map(|segment| match segment.strip_prefix(':') {
+            Some(param) => format!("{{{}}}", param),
+            None => segment.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn json_content(schema: Schema) -> Value {
+    json!({ "application/json": { "schema": schema } })
+}
+
+fn operation(route: &Route, generator: &mut SchemaGenerator) -> Value {
+    let mut parameters: Vec<Value> = route
+        .path
+        .split('/')
+        .filter_map(|segment| segment.strip_prefix(':'))
+        .map(|param| {
+            json!({
+                "name": param,
+                "in": "path",
+                "required": true,
+                "schema": { "type": "string" },
+            })
+        })
+        .collect();
+    if let Some(query) = route.query {
+        // Query structs are flat, so each property is one parameter
+        let query = query(generator);
+        let name = query
+            .get("$ref")
+            .and_then(Value::as_str)
+            .and_then(|r| r.rsplit('/').next())
+            .unwrap_or_default()
+            .to_string();
+        let object = generator
+            .definitions_mut()
+            .remove(&name)
+            .unwrap_or_else(|| query.to_value());
+        let required = object["required"].as_array().cloned().unwrap_or_default();
+        if let Some(properties) = object["properties"].as_object() {
+            for (field, schema) in properties {
+                parameters.push(json!({
+                    "name": field,
+                    "in": "query",
+                    "required": required.contains(&Value::from(field.as_str())),
+                    "schema": schema,
+                }));
+            }
+        }
+    }
+
+    let mut op = Map::new();
+    op.insert("tags".into(), json!([route.tag]));
+    op.insert("summary".into(), json!(route.summary));
+    op.insert(
+        "operationId".into(),
+        json!(format!("{} {}", route.method, route.path)),
+    );
+    if !parameters.is_empty() {
+        op.insert("parameters".into(), Value::Array(parameters));
+    }
+    if let Some(body) = route.body {
+        op.insert(
+            "requestBody".into(),
+            json!({
+                "required": route.body_required,
+                "content": json_content(body(generator)),
+            }),
+        );
+    }
+
+    let mut responses = Map::new();
+    let success = match route.reply {
+        Reply::Json(reply) => json!({
+            "description": "Success",
+            "content": json_content(reply(generator)),
+        }),
+        Reply::JsonOrCsv(reply) => json!({
+            "description": "Success; CSV of the runs with `format=csv`",
+            "content": {
+                "application/json": { "schema": reply(generator) },
+                "text/csv": { "schema": { "type": "string" } },
+            },
+        }),
+        Reply::Text(content_type) => json!({
+            "description": "Success",
+            "content": { content_type: { "schema": { "type": "string" } } },
+        }),
+        Reply::WebSocket { receive, send } => json!({
+            "description": "Switching to the WebSocket protocol",
+            "x-websocket": {
+                "receive": receive(generator),
+                "send": send(generator),
+            },
+        }),
+    };
+    responses.insert(route.status.to_string(), success);
+    let unauthorized = (!route.public).then_some(401);
+    for status in route.errors.iter().copied().chain(unauthorized) {
+        responses.insert(
+            status.to_string(),
+            json!({
+                "description": error_description(status),
+                "content": json_content(schema::<ErrorResponse>(generator)),
+            }),
+        );
+    }
+    op.insert("responses".into(), Value::Object(responses));
+    if route.public {
+        op.insert("security".into(), json!([]));
+    }
+    Value::Object(op)
+}
+
+/// The OpenAPI 3.1 document of the API
+pub fn openapi() -> Value {
+    let mut generator = SchemaSettings::draft2020_12()
+        .with(|s| {
+            s.definitions_path = "/components/schemas".into();
+            s.meta_schema = None;
+        })
+        .into_generator();
+
+    let mut paths = Map::new();
+    for route in ROUTES {
+        let op = operation(route, &mut generator);
+        let path = paths
+            .entry(openapi_path(route.path))
+            .or_insert_with(|| json!({}));
+        path[route.method] = op;
+    }
+
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "Waremax API",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Run, stream and analyse Waremax warehouse simulations. \
+                WebSocket routes list the messages they send and receive under \
+                `x-websocket`; `/websocket.schema.json` describes them all.",
+        },
+        "tags": [
+            { "name": "meta" },
+            { "name": "sessions", "description": "Live simulation sessions" },
+            { "name": "runs", "description": "Recorded run history" },
+            { "name": "jobs", "description": "Batch sweeps, comparisons and A/B tests" },
+            { "name": "replays", "description": "Event-log replays" },
+            { "name": "websocket", "description": "Streaming and control over WebSockets" },
+        ],
+        "paths": paths,
+        "components": {
+            "schemas": generator.take_definitions(true),
+            "securitySchemes": {
+                "bearer": { "type": "http", "scheme": "bearer" },
+                "apiKeyHeader": { "type": "apiKey", "in": "header", "name": "X-API-Key" },
+                "apiKeyQuery": { "type": "apiKey", "in": "query", "name": "api_key" },
+            },
+        },
+        // Only enforced by servers with API keys configured
+        "security": [{ "bearer": [] }, { "apiKeyHeader": [] }, { "apiKeyQuery": [] }],
+    })
+}
+
+/// JSON Schema (2020-12) matching any message sent over a WebSocket, either way
+pub fn websocket_schema() -> Value {
+    let mut generator = SchemaSettings::draft2020_12().into_generator();
+    let messages = [
+        schema::<WebSocketMessage>(&mut generator),
+        schema::<ReplayMessage>(&mut generator),
+        schema::<ControlCommand>(&mut generator),
+        schema::<ControllerServerMessage>(&mut generator),
+        schema::<ControllerClientMessage>(&mut generator),
+    ];
+
+    json!({
+        "$schema": schemars::consts::meta_schemas::DRAFT2020_12,
+        "title": "WaremaxWebSocketMessage",
+        "description": "A message on /ws/:id (WebSocketMessage from the server, \
+            ControlCommand from the client), /ws/replays/:id (ReplayMessage, \
+            ControlCommand) or /ws/:id/controller (ControllerServerMessage, \
+            ControllerClientMessage)",
+        "oneOf": messages,
+        "$defs": generator.take_definitions(true),
+    })
+}
+
+/// Serve the OpenAPI document
+pub async fn openapi_json() -> impl IntoResponse {
+    Json(openapi())
+}
+
+/// Serve the WebSocket message schema
+pub async fn websocket_schema_json() -> impl IntoResponse {
+    Json(websocket_schema())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_router_serves_every_documented_route() {
+        use axum::body::{to_bytes, Body};
+        use axum::http::{Method, Request, StatusCode};
+        use tower::ServiceExt;
+
+        let spec = openapi();
+        let app = crate::create_router(crate::ApiConfig::default());
+        for route in ROUTES {
+            assert!(
+                spec["paths"][openapi_path(route.path)][route.method].is_object(),
+                "{} {} is not in the OpenAPI document",
+                route.method,
+                route.path
+            );
+
+            // Unknown ids are answered by the handler; unrouted requests
+            // get an empty 404 or a 405
+            let method = Method::from_bytes(route.method.to_uppercase().as_bytes()).unwrap();
+            let request = Request::builder()
+                .method(method)
+                .uri(route.path.replace(":id", "missing"))
+                .body(Body::empty())
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            let status = response.status();
+            let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            let unrouted = status == StatusCode::METHOD_NOT_ALLOWED
+                || (status == StatusCode::NOT_FOUND && body.is_empty());
+            assert!(!unrouted, "{} {} is not routed", route.method, route.path);
+        }
+    }
+
+    fn collect_refs(value: &Value, refs: &mut Vec<String>) {
+        match value {
+            Value::Object(map) => {
+                if let Some(Value::String(r)) = map.get("$ref") {
+                    refs.push(r.clone());
+                }
+                map.values().for_each(|v| collect_refs(v, refs));
+            }
+            Value::Array(items) => items.iter().for_each(|v| collect_refs(v, refs)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn test_schema_refs_resolve() {
+        for (doc, prefix, defs) in [
+            (openapi(), "#/components/schemas/", "/components/schemas"),
+            (websocket_schema(), "#/$defs/", "/$defs"),
+        ] {
+            let mut refs = Vec::new();
+            collect_refs(&doc, &mut refs);
+            assert!(!refs.is_empty());
+            for r in refs {
+                let name = r.strip_prefix(prefix).unwrap_or_else(|| panic!("{}", r));
+                assert!(
+                    doc.pointer(defs).unwrap().get(name).is_some(),
+                    "dangling {}",
+                    r
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_websocket_schema_covers_message_types() {
+        let schema = websocket_schema();
+        let message = serde_json::to_value(WebSocketMessage::Finished {
+            final_metrics: Default::default(),
+        })
+        .unwrap();
+        assert_eq!(message["type"], "Finished");
+        let variants = schema["$defs"]["WebSocketMessage"]["oneOf"]
+            .as_array()
+            .unwrap();
+        assert!(variants
+            .iter()
+            .any(|v| v.to_string().contains("\"Finished\"")));
+        assert_eq!(schema["oneOf"].as_array().unwrap().len(), 5);
+    }
+}
@@ -0,0 +1,1318 @@
+//! Replays of recorded event logs
+//!
+//! A replay plays one or more event logs side by side, kept at the same
+//! simulated time: seeking moves all of them with `ReplaySession::sync_time`.
+//! Logs come from the run history (`run_id`) or are uploaded with the
+//! request. Each is copied into a private `EventLog` under the replay
+//! directory, because an event log can only be opened once at a time, and
+//! the copies are removed with the replay.
+//!
+//! Playback turns the logged events into the `WebSocketMessage`s a live
+//! session sends (`RobotMoved`, `RobotStateChanged`, `OrderCompleted`,
+//! `Tick`, `MetricsUpdate`, `StateSync`, `Finished`), tagged with the name
+//! of their log, so the frontend renders a replay like a live session. The
+//! state a keyframe describes is folded from the log, seeded with the robots
+//! and stations of the run's scenario when known. Folding checkpoints the
+//! state every simulated minute, so a seek restores the nearest checkpoint
+//! before its time and only folds the events after it.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+
+use thiserror::Error;
+use tokio::sync::{broadcast, Mutex, RwLock};
+use uuid::Uuid;
+use waremax_config::ScenarioConfig;
+use waremax_metrics::{EventLog, EventLogConfig, TraceDetails};
+use waremax_sim::{PlaybackState, ReplayEngine, ReplaySession, Snapshot, SnapshotManager};
+
+use crate::auth::Caller;
+use crate::history::RunHistory;
+use crate::scenario::{resolve_source, ScenarioError};
+use crate::simulation::ScenarioLayout;
+use crate::types::{
+    MapData, MetricsSnapshot, ReplayInfo, ReplayMessage, ReplayRequest, ReplaySourceRequest,
+    ReplayStatus, ReplayTrackInfo, RobotState, RunStatus, SimulationState, SimulationStatus,
+    StationState, WebSocketMessage,
+};
+
+/// Wall-clock time between playback frames
+const FRAME_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Why a replay could not be created or controlled
+#[derive(Error, Debug)]
+pub enum ReplayError {
+    #[error("Run '{0}' not found")]
+    RunNotFound(String),
+
+    #[error("Run '{0}' has no event log")]
+    NoEventLog(String),
+
+    #[error("Run '{0}' is still in progress")]
+    RunInProgress(String),
+
+    #[error(transparent)]
+    Scenario(#[from] ScenarioError),
+
+    #[error("{0}")]
+    Spec(String),
+
+    #[error("Maximum number of replays reached")]
+    Limit,
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// State of one event log's world at the replay's current time, folded
+/// from its events
+#[derive(Clone, Debug, Default)]
+struct ReplayView {
+    initial_robots: Vec<RobotState>,
+    stations: Vec<StationState>,
+    robots: BTreeMap<u32, RobotState>,
+    /// When each robot with a task took it
+    busy_since: HashMap<u32, f64>,
+    robot_busy_s: f64,
+    station_busy_s: f64,
+    events_processed: u64,
+    /// Time of the last event applied
+    last_event_s: f64,
+    orders_completed: u64,
+    late_orders: u64,
+    total_cycle_time_s: f64,
+}
+
+impl ReplayView {
+    fn new(layout: Option<&ScenarioLayout>) -> Self {
+        let mut view = Self {
+            initial_robots: layout.map(|l| l.robots.clone()).unwrap_or_default(),
+            stations: layout.map(|l| l.stations.clone()).unwrap_or_default(),
+            ..Default::default()
+        };
+        view.reset();
+        view
+    }
+
+    /// Back to the state before the first event
+    fn reset(&mut self) {
+        self.robots = self
+            .initial_robots
+            .iter()
+            .map(|r| (r.id, r.clone()))
+            .collect();
+        self.busy_since.clear();
+        self.robot_busy_s = 0.0;
+        self.station_busy_s = 0.0;
+        self.events_processed = 0;
+        self.last_event_s = 0.0;
+        self.orders_completed = 0;
+        self.late_orders = 0;
+        self.total_cycle_time_s = 0.0;
+    }
+
+    fn robot(&mut self, robot_id: u32, node_id: u32) -> &mut RobotState {
+        self.robots.entry(robot_id).or_insert_with(|| RobotState {
+            id: robot_id,
+            node_id,
+            state: "Idle".to_string(),
+            battery_soc: None,
+            current_task: None,
+            is_failed: false,
+        })
+    }
+
+    fn set_state(&mut self, robot_id: u32, state: &str, time_s: f64) -> Option<WebSocketMessage> {
+        let robot = self.robot(robot_id, 0);
+        if robot.state == state {
+            return None;
+        }
+        let old_state = std::mem::replace(&mut robot.state, state.to_string());
+        Some(WebSocketMessage::RobotStateChanged {
+            robot_id,
+            old_state,
+            new_state: state.to_string(),
+            time_s,
+        })
+    }
+
+    /// Apply one logged event, returning the messages a live session would
+    /// have sent for it
+    fn apply(&mut self, time_s: f64, details: TraceDetails) -> Vec<WebSocketMessage> {
+        self.events_processed += 1;
+        self.last_event_s = time_s;
+        match details {
+            TraceDetails::RobotMove {
+                robot_id,
+                from_node,
+                to_node,
+            } => {
+                self.robot(robot_id, from_node).node_id = to_node;
+                vec![WebSocketMessage::RobotMoved {
+                    robot_id,
+                    from_node,
+                    to_node,
+                    time_s,
+                }]
+            }
+            TraceDetails::TaskAssign { task_id, robot_id } => {
+                self.robot(robot_id, 0).current_task = Some(task_id);
+                self.busy_since.entry(robot_id).or_insert(time_s);
+                self.set_state(robot_id, "Moving", time_s)
+                    .into_iter()
+                    .collect()
+            }
+            TraceDetails::TaskComplete { robot_id, .. } => {
+                self.robot(robot_id, 0).current_task = None;
+                if let Some(since) = self.busy_since.remove(&robot_id) {
+                    self.robot_busy_s += time_s - since;
+                }
+                self.set_state(robot_id, "Idle", time_s)
+                    .into_iter()
+                    .collect()
+            }
+            TraceDetails::StationService {
+                robot_id,
+                duration_s,
+                ..
+            } => {
+                self.station_busy_s += duration_s;
+                self.set_state(robot_id, "Idle", time_s)
+                    .into_iter()
+                    .collect()
+            }
+            TraceDetails::OrderComplete {
+                order_id,
+                cycle_time_s,
+                is_late,
+            } => {
+                self.orders_completed += 1;
+                self.total_cycle_time_s += cycle_time_s;
+                if is_late {
+                    self.late_orders += 1;
+                }
+                vec![WebSocketMessage::OrderCompleted {
+                    order_id,
+                    cycle_time_s,
+                    on_time: !is_late,
+                }]
+            }
+            TraceDetails::RobotFailure { robot_id } => {
+                self.robot(robot_id, 0).is_failed = true;
+                self.set_state(robot_id, "Failed", time_s)
+                    .into_iter()
+                    .collect()
+            }
+            TraceDetails::RobotMaintenance { robot_id, .. } => {
+                self.robot(robot_id, 0).is_failed = false;
+                self.set_state(robot_id, "InMaintenance", time_s)
+                    .into_iter()
+                    .collect()
+            }
+            TraceDetails::ChargingStart { robot_id, soc, .. } => {
+                self.robot(robot_id, 0).battery_soc = Some(soc);
+                self.set_state(robot_id, "Charging", time_s)
+                    .into_iter()
+                    .collect()
+            }
+            TraceDetails::ChargingEnd { robot_id, .. } => self
+                .set_state(robot_id, "Idle", time_s)
+                .into_iter()
+                .collect(),
+            TraceDetails::Generic { .. } => Vec::new(),
+        }
+    }
+
+    fn metrics(&self, time_s: f64) -> MetricsSnapshot {
+        let hours = time_s / 3600.0;
+        let open_busy_s: f64 = self
+            .busy_since
+            .values()
+            .map(|since| (time_s - since).max(0.0))
+            .sum();
+        let robot_time_s = time_s * self.robots.len() as f64;
+        let station_capacity: f64 = self.stations.iter().map(|s| s.concurrency as f64).sum();
+        let station_time_s = time_s * station_capacity;
+
+        MetricsSnapshot {
+            throughput_per_hour: if hours > 0.0 {
+                self.orders_completed as f64 / hours
+            } else {
+                0.0
+            },
+            orders_completed: self.orders_completed,
+            orders_pending: 0,
+            robot_utilization: if robot_time_s > 0.0 {
+                ((self.robot_busy_s + open_busy_s) / robot_time_s).min(1.0)
+            } else {
+                0.0
+            },
+            station_utilization: if station_time_s > 0.0 {
+                (self.station_busy_s / station_time_s).min(1.0)
+            } else {
+                0.0
+            },
+            avg_cycle_time_s: if self.orders_completed > 0 {
+                self.total_cycle_time_s / self.orders_completed as f64
+            } else {
+                0.0
+            },
+            late_orders: self.late_orders,
+        }
+    }
+
+    fn state(&self, status: SimulationStatus, time_s: f64, speed: f64) -> SimulationState {
+        SimulationState {
+            status,
+            time_s,
+            speed,
+            events_processed: self.events_processed,
+            orders_completed: self.orders_completed,
+            robots: self.robots.values().cloned().collect(),
+            stations: self.stations.clone(),
+            metrics: self.metrics(time_s),
+        }
+    }
+}
+
+/// A view as it was once every event up to `time_s` was applied
+struct Checkpoint {
+    time_s: f64,
+    view: ReplayView,
+}
+
+impl Snapshot for Checkpoint {
+    fn timestamp_s(&self) -> f64 {
+        self.time_s
+    }
+}
+
+/// One event log of a replay
+struct Track {
+    name: String,
+    run_id: Option<String>,
+    map: Option<MapData>,
+    view: ReplayView,
+    checkpoints: SnapshotManager<Checkpoint>,
+}
+
+impl Track {
+    fn new(name: String, run_id: Option<String>, layout: Option<ScenarioLayout>) -> Self {
+        Self {
+            name,
+            run_id,
+            view: ReplayView::new(layout.as_ref()),
+            map: layout.map(|l| l.map),
+            checkpoints: SnapshotManager::default(),
+        }
+    }
+
+    /// Apply one logged event to the view. Every interval, the view is
+    /// checkpointed first, once no more events share the last one's time.
+    fn apply(&mut self, time_s: f64, details: TraceDetails) -> Vec<WebSocketMessage> {
+        let last_s = self.view.last_event_s;
+        if time_s > last_s && self.checkpoints.should_snapshot(last_s) {
+            self.checkpoints.store(Checkpoint {
+                time_s: last_s,
+                view: self.view.clone(),
+            });
+        }
+        self.view.apply(time_s, details)
+    }
+
+    /// Fold the view to `time_s`, from the last checkpoint before it
+    fn rebuild(&mut self, engine: &ReplayEngine, time_s: f64) {
+        let from_s = match self.checkpoints.find_at_or_before(time_s) {
+            Some(checkpoint) => {
+                self.view = checkpoint.view.clone();
+                Some(checkpoint.time_s)
+            }
+            None => {
+                self.view.reset();
+                None
+            }
+        };
+        // The range is inclusive; events at the checkpoint's time are in it
+        for event in engine.get_events_in_range(from_s.unwrap_or(0.0), time_s) {
+            if from_s.is_some_and(|from_s| event.time_s <= from_s) {
+                continue;
+            }
+            if let Ok(details) = serde_json::from_str(&event.details_json) {
+                self.apply(event.time_s, details);
+            }
+        }
+    }
+}
+
+/// The engines of a replay and their playback settings
+struct Playback {
+    session: ReplaySession,
+    tracks: Vec<Track>,
+    speed: f64,
+    last_activity: Instant,
+}
+
+impl Playback {
+    fn engine(&self, name: &str) -> &ReplayEngine {
+        self.session
+            .get(name)
+            .expect("every track has a replay engine")
+    }
+
+    fn status(&self) -> ReplayStatus {
+        let states: Vec<&PlaybackState> = self
+            .tracks
+            .iter()
+            .map(|t| self.engine(&t.name).state())
+            .collect();
+        if states
+            .iter()
+            .any(|s| matches!(s, PlaybackState::Playing { .. }))
+        {
+            ReplayStatus::Playing
+        } else if states.iter().all(|s| **s == PlaybackState::Finished) {
+            ReplayStatus::Finished
+        } else {
+            ReplayStatus::Paused
+        }
+    }
+
+    /// The replay's time: logs shorter than the others stop at their end
+    fn time_s(&self) -> f64 {
+        self.tracks
+            .iter()
+            .map(|t| self.engine(&t.name).current_time())
+            .fold(0.0, f64::max)
+    }
+
+    fn info(&self, id: &str, owner: Option<&str>) -> ReplayInfo {
+        ReplayInfo {
+            replay_id: id.to_string(),
+            owner: owner.map(str::to_string),
+            status: self.status(),
+            time_s: self.time_s(),
+            speed: self.speed,
+            replays: self
+                .tracks
+                .iter()
+                .map(|t| {
+                    let engine = self.engine(&t.name);
+                    let metadata = engine.metadata();
+                    ReplayTrackInfo {
+                        name: t.name.clone(),
+                        run_id: t.run_id.clone(),
+                        duration_s: metadata.duration_s,
+                        event_count: metadata.event_count,
+                        seed: metadata.seed,
+                        finished: *engine.state() == PlaybackState::Finished,
+                        has_map: t.map.is_some(),
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// A `StateSync` per log describing its state at the current time
+    fn keyframes(&self) -> Vec<ReplayMessage> {
+        self.tracks
+            .iter()
+            .map(|t| {
+                let engine = self.engine(&t.name);
+                let status = match engine.state() {
+                    PlaybackState::Playing { .. } => SimulationStatus::Running,
+                    PlaybackState::Paused => SimulationStatus::Paused,
+                    PlaybackState::Finished => SimulationStatus::Finished,
+                };
+                ReplayMessage {
+                    replay: t.name.clone(),
+                    message: WebSocketMessage::StateSync {
+                        state: t.view.state(status, engine.current_time(), self.speed),
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// Rebuild every view at its log's current time
+    fn rebuild_views(&mut self) {
+        for track in &mut self.tracks {
+            let engine = self
+                .session
+                .get(&track.name)
+                .expect("every track has a replay engine");
+            track.rebuild(engine, engine.current_time());
+        }
+    }
+
+    fn play(&mut self) {
+        for track in &self.tracks {
+            if let Some(engine) = self.session.get_mut(&track.name) {
+                engine.play(self.speed);
+            }
+        }
+    }
+
+    fn pause(&mut self) {
+        for track in &self.tracks {
+            if let Some(engine) = self.session.get_mut(&track.name) {
+                if *engine.state() != PlaybackState::Finished {
+                    engine.pause();
+                }
+            }
+        }
+    }
+
+    /// Move every log to `time_s`, keeping on playing if the replay was
+    /// playing; logs seeked back from their end become playable again
+    fn seek(&mut self, time_s: f64) {
+        let playing = self.status() == ReplayStatus::Playing;
+        self.session.sync_time(time_s);
+        for track in &self.tracks {
+            if let Some(engine) = self.session.get_mut(&track.name) {
+                if engine.current_time() < engine.duration() {
+                    if playing {
+                        engine.play(self.speed);
+                    } else {
+                        engine.pause();
+                    }
+                }
+            }
+        }
+        self.rebuild_views();
+    }
+
+    /// Advance every playing log by `wall_delta_s` of wall-clock time
+    fn frame(&mut self, wall_delta_s: f64) -> Vec<ReplayMessage> {
+        let mut messages = Vec::new();
+        for track in &mut self.tracks {
+            let Some(engine) = self.session.get_mut(&track.name) else {
+                continue;
+            };
+            if !matches!(engine.state(), PlaybackState::Playing { .. }) {
+                continue;
+            }
+
+            // The range is inclusive; events at the old time were applied
+            // by the previous frame or the keyframe
+            let old_time = engine.current_time();
+            let orders_before = track.view.orders_completed;
+            let events = engine.step(wall_delta_s);
+            let time_s = engine.current_time();
+
+            let mut frame = Vec::new();
+            for event in events.into_iter().filter(|e| e.time_s > old_time) {
+                if let Ok(details) = serde_json::from_str(&event.details_json) {
+                    frame.extend(track.apply(event.time_s, details));
+                }
+            }
+            frame.push(WebSocketMessage::Tick {
+                time_s,
+                events_processed: track.view.events_processed,
+            });
+            if track.view.orders_completed != orders_before {
+                frame.push(WebSocketMessage::MetricsUpdate {
+                    metrics: track.view.metrics(time_s),
+                });
+            }
+            if *engine.state() == PlaybackState::Finished {
+                frame.push(WebSocketMessage::Finished {
+                    final_metrics: track.view.metrics(time_s),
+                });
+            }
+            messages.extend(frame.into_iter().map(|message| ReplayMessage {
+                replay: track.name.clone(),
+                message,
+            }));
+        }
+        messages
+    }
+}
+
+/// A replay of one or more event logs
+pub struct Replay {
+    pub id: String,
+    /// API key name of the tenant that created it
+    pub owner: Option<String>,
+    dir: PathBuf,
+    playback: Mutex<Playback>,
+    updates: broadcast::Sender<ReplayMessage>,
+}
+
+impl Replay {
+    /// Current playback position and the replay's event logs
+    pub async fn info(&self) -> ReplayInfo {
+        let mut playback = self.playback.lock().await;
+        playback.last_activity = Instant::now();
+        playback.info(&self.id, self.owner.as_deref())
+    }
+
+    /// Keyframes describing the current state, and the messages that
+    /// follow them
+    pub async fn subscribe(&self) -> (Vec<ReplayMessage>, broadcast::Receiver<ReplayMessage>) {
+        let mut playback = self.playback.lock().await;
+        playback.last_activity = Instant::now();
+        (playback.keyframes(), self.updates.subscribe())
+    }
+
+    /// Keyframes describing the current state
+    pub async fn keyframes(&self) -> Vec<ReplayMessage> {
+        self.playback.lock().await.keyframes()
+    }
+
+    /// Start playing, at `speed` if given
+    pub async fn play(&self, speed: Option<f64>) -> Result<ReplayInfo, ReplayError> {
+        if let Some(speed) = speed {
+            check_speed(speed)?;
+        }
+        Ok(self
+            .control(|playback| {
+                if let Some(speed) = speed {
+                    playback.speed = speed;
+                }
+                playback.play();
+            })
+            .await)
+    }
+
+    /// Change the playback speed, keeping on playing if playing
+    pub async fn set_speed(&self, speed: f64) -> Result<ReplayInfo, ReplayError> {
+        check_speed(speed)?;
+        Ok(self
+            .control(|playback| {
+                playback.speed = speed;
+                if playback.status() == ReplayStatus::Playing {
+                    playback.play();
+                }
+            })
+            .await)
+    }
+
+    pub async fn pause(&self) -> ReplayInfo {
+        self.control(Playback::pause).await
+    }
+
+    /// Move every event log to `time_s`
+    pub async fn seek(&self, time_s: f64) -> Result<ReplayInfo, ReplayError> {
+        if !time_s.is_finite() || time_s < 0.0 {
+            return Err(ReplayError::Spec(format!(
+                "time_s must be a non-negative number, got {}",
+                time_s
+            )));
+        }
+        Ok(self.control(|playback| playback.seek(time_s)).await)
+    }
+
+    /// Map of the named event log (the first if `None`). The outer `None`
+    /// means there is no such log.
+    pub async fn map(&self, name: Option<&str>) -> Option<Option<MapData>> {
+        let playback = self.playback.lock().await;
+        let track = match name {
+            Some(name) => playback.tracks.iter().find(|t| t.name == name)?,
+            None => playback.tracks.first()?,
+        };
+        Some(track.map.clone())
+    }
+
+    /// Apply a change to the playback and send everyone fresh keyframes
+    async fn control(&self, change: impl FnOnce(&mut Playback)) -> ReplayInfo {
+        let mut playback = self.playback.lock().await;
+        playback.last_activity = Instant::now();
+        change(&mut playback);
+        for message in playback.keyframes() {
+            let _ = self.updates.send(message);
+        }
+        playback.info(&self.id, self.owner.as_deref())
+    }
+
+    /// Close the event logs and delete their copies
+    async fn discard(&self) {
+        let mut playback = self.playback.lock().await;
+        playback.session = ReplaySession::new();
+        playback.tracks.clear();
+        if let Err(e) = std::fs::remove_dir_all(&self.dir) {
+            tracing::warn!(replay_id = %self.id, error = %e, "Failed to remove replay event logs");
+        }
+    }
+}
+
+/// Open a just-written event log. sled's background flusher can hold the
+/// file lock for a moment after the writer is dropped, so retry briefly.
+fn open_engine(path: &Path) -> io::Result<ReplayEngine> {
+    let mut attempts = 0;
+    loop {
+        match ReplayEngine::from_event_log(path) {
+            Err(_) if attempts < 20 => {
+                attempts += 1;
+                std::thread::sleep(Duration::from_millis(25));
+            }
+            result => return result,
+        }
+    }
+}
+
+fn check_speed(speed: f64) -> Result<(), ReplayError> {
+    if speed.is_finite() && speed > 0.0 {
+        Ok(())
+    } else {
+        Err(ReplayError::Spec(format!(
+            "speed must be a positive number, got {}",
+            speed
+        )))
+    }
+}
+
+/// Manager of the live replays
+pub struct ReplayManager {
+    replays: RwLock<HashMap<String, Arc<Replay>>>,
+    storage_dir: PathBuf,
+    replay_timeout: Duration,
+    max_replays: usize,
+    history: Option<Arc<RunHistory>>,
+    library: Option<PathBuf>,
+}
+
+impl ReplayManager {
+    /// Create a replay manager keeping its event log copies in
+    /// `storage_dir`. Copies left there by a previous process are removed.
+    pub fn new(
+        storage_dir: PathBuf,
+        replay_timeout: Duration,
+        max_replays: usize,
+        history: Option<Arc<RunHistory>>,
+        library: Option<PathBuf>,
+    ) -> Self {
+        if storage_dir.exists() {
+            if let Err(e) = std::fs::remove_dir_all(&storage_dir) {
+                tracing::warn!(
+                    dir = %storage_dir.display(),
+                    error = %e,
+                    "Failed to remove old replay event logs"
+                );
+            }
+        }
+        Self {
+            replays: RwLock::new(HashMap::new()),
+            storage_dir,
+            replay_timeout,
+            max_replays,
+            history,
+            library,
+        }
+    }
+
+    /// Create a paused replay of the requested event logs for `caller`,
+    /// who may only reference its own runs
+    pub async fn create(
+        &self,
+        request: ReplayRequest,
+        caller: &Caller,
+    ) -> Result<ReplayInfo, ReplayError> {
+        if request.replays.is_empty() {
+            return Err(ReplayError::Spec(
+                "A replay needs at least one event log".to_string(),
+            ));
+        }
+        let mut names = HashSet::new();
+        for source in &request.replays {
+            if source.name.is_empty() || !names.insert(source.name.as_str()) {
+                return Err(ReplayError::Spec(format!(
+                    "Replay names must be non-empty and unique, got '{}'",
+                    source.name
+                )));
+            }
+        }
+        let speed = request.speed.unwrap_or(1.0);
+        check_speed(speed)?;
+        if self.replays.read().await.len() >= self.max_replays {
+            return Err(ReplayError::Limit);
+        }
+
+        let id = Uuid::new_v4().to_string();
+        let dir = self.storage_dir.join(&id);
+        let mut session = ReplaySession::new();
+        let mut tracks = Vec::new();
+        for (index, source) in request.replays.iter().enumerate() {
+            match self.load_track(source, &dir.join(index.to_string()), caller) {
+                Ok((engine, track)) => {
+                    session.add(&track.name, engine);
+                    tracks.push(track);
+                }
+                Err(e) => {
+                    drop(session);
+                    let _ = std::fs::remove_dir_all(&dir);
+                    return Err(e);
+                }
+            }
+        }
+
+        let mut playback = Playback {
+            session,
+            tracks,
+            speed,
+            last_activity: Instant::now(),
+        };
+        playback.rebuild_views();
+        let info = playback.info(&id, caller.name());
+
+        let (updates, _) = broadcast::channel(1024);
+        let replay = Arc::new(Replay {
+            id: id.clone(),
+            owner: caller.name().map(str::to_string),
+            dir,
+            playback: Mutex::new(playback),
+            updates,
+        });
+
+        let mut replays = self.replays.write().await;
+        if replays.len() >= self.max_replays {
+            drop(replays);
+            replay.discard().await;
+            return Err(ReplayError::Limit);
+        }
+        spawn_playback(Arc::downgrade(&replay));
+        replays.insert(id, replay);
+
+        Ok(info)
+    }
+
+    /// Copy one event log to `path` and open it for playback
+    fn load_track(
+        &self,
+        source: &ReplaySourceRequest,
+        path: &Path,
+        caller: &Caller,
+    ) -> Result<(ReplayEngine, Track), ReplayError> {
+        let (events, duration_s, seed, scenario) = match (&source.run_id, &source.events) {
+            (Some(run_id), None) => {
+                let not_found = || ReplayError::RunNotFound(run_id.clone());
+                let history = self.history.as_ref().ok_or_else(not_found)?;
+                let run = history
+                    .detail(run_id)?
+                    .filter(|run| caller.can_access(run.record.owner.as_deref()))
+                    .ok_or_else(not_found)?;
+                if run.record.status == RunStatus::Running {
+                    return Err(ReplayError::RunInProgress(run_id.clone()));
+                }
+                let events = history
+                    .events(run_id, 0.0, f64::MAX)?
+                    .ok_or_else(|| ReplayError::NoEventLog(run_id.clone()))?;
+                let simulation = &run.scenario.simulation;
+                let duration_s = (simulation.warmup_minutes + simulation.duration_minutes) * 60.0;
+                (
+                    events,
+                    duration_s,
+                    Some(run.scenario.seed),
+                    Some(run.scenario),
+                )
+            }
+            (None, Some(events)) => {
+                let scenario: Option<ScenarioConfig> = source
+                    .scenario
+                    .as_ref()
+                    .map(|s| resolve_source(s, self.library.as_deref()))
+                    .transpose()?
+                    .map(|resolved| resolved.scenario);
+                let duration_s = match source.duration_s {
+                    Some(d) if d.is_finite() && d >= 0.0 => d,
+                    Some(d) => {
+                        return Err(ReplayError::Spec(format!(
+                            "duration_s must be a non-negative number, got {}",
+                            d
+                        )))
+                    }
+                    None => events.iter().map(|e| e.timestamp).fold(0.0, f64::max),
+                };
+                (events.clone(), duration_s, source.seed, scenario)
+            }
+            _ => {
+                return Err(ReplayError::Spec(format!(
+                    "Replay '{}' needs exactly one of 'run_id' and 'events'",
+                    source.name
+                )))
+            }
+        };
+
+        {
+            let mut log = EventLog::open(path, EventLogConfig::default())?;
+            for event in &events {
+                log.append(event)?;
+            }
+            log.set_metadata("duration_s", &duration_s.to_string())?;
+            if let Some(seed) = seed {
+                log.set_metadata("seed", &seed.to_string())?;
+            }
+            log.flush()?;
+        }
+        let engine = open_engine(path)?;
+
+        let layout = scenario.as_ref().map(ScenarioLayout::new);
+        let track = Track::new(source.name.clone(), source.run_id.clone(), layout);
+        Ok((engine, track))
+    }
+
+    /// Get a replay by ID
+    pub async fn get(&self, id: &str) -> Option<Arc<Replay>> {
+        self.replays.read().await.get(id).cloned()
+    }
+
+    /// All replays
+    pub async fn list(&self) -> Vec<ReplayInfo> {
+        let replays: Vec<Arc<Replay>> = self.replays.read().await.values().cloned().collect();
+        let mut infos = Vec::with_capacity(replays.len());
+        for replay in replays {
+            infos.push(
+                replay
+                    .playback
+                    .lock()
+                    .await
+                    .info(&replay.id, replay.owner.as_deref()),
+            );
+        }
+        infos
+    }
+
+    /// Remove a replay and delete its event log copies
+    pub async fn remove(&self, id: &str) -> bool {
+        let replay = self.replays.write().await.remove(id);
+        match replay {
+            Some(replay) => {
+                replay.discard().await;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remove replays nobody has used or watched for the timeout
+    pub async fn cleanup_expired(&self) {
+        let mut expired = Vec::new();
+        {
+            let replays = self.replays.read().await;
+            for (id, replay) in replays.iter() {
+                let idle = replay.playback.lock().await.last_activity.elapsed();
+                if idle > self.replay_timeout && replay.updates.receiver_count() == 0 {
+                    expired.push(id.clone());
+                }
+            }
+        }
+        for id in expired {
+            self.remove(&id).await;
+        }
+    }
+}
+
+/// Advance a replay every frame until it is removed
+fn spawn_playback(replay: Weak<Replay>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(FRAME_INTERVAL);
+        let mut last_frame = Instant::now();
+        loop {
+            interval.tick().await;
+            let Some(replay) = replay.upgrade() else {
+                break;
+            };
+            let now = Instant::now();
+            let wall_delta_s = now.duration_since(last_frame).as_secs_f64();
+            last_frame = now;
+
+            let messages = {
+                let mut playback = replay.playback.lock().await;
+                if replay.updates.receiver_count() > 0 {
+                    playback.last_activity = now;
+                }
+                playback.frame(wall_delta_s)
+            };
+            for message in messages {
+                let _ = replay.updates.send(message);
+            }
+        }
+    });
+}
+
+/// Start a background task to periodically clean up expired replays
+pub fn start_replay_cleanup_task(manager: Arc<ReplayManager>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            manager.cleanup_expired().await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use waremax_metrics::TraceEntry;
+
+    /// `seconds` of events: a robot moves every second, one takes a task at
+    /// 5 s past every ten, and every ten seconds a task and its order
+    /// complete in the same instant
+    fn log(seconds: u32) -> Vec<TraceEntry> {
+        let entry = |timestamp: u32, details| TraceEntry {
+            timestamp: timestamp as f64,
+            event_type: "test".to_string(),
+            details,
+        };
+        let mut events = Vec::new();
+        for t in 1..=seconds {
+            events.push(entry(
+                t,
+                TraceDetails::RobotMove {
+                    robot_id: t % 3,
+                    from_node: t - 1,
+                    to_node: t,
+                },
+            ));
+            if t % 10 == 5 {
+                events.push(entry(
+                    t,
+                    TraceDetails::TaskAssign {
+                        task_id: t,
+                        robot_id: t % 3,
+                    },
+                ));
+            }
+            if t % 10 == 0 {
+                events.push(entry(
+                    t,
+                    TraceDetails::TaskComplete {
+                        task_id: t - 5,
+                        robot_id: (t - 5) % 3,
+                    },
+                ));
+                events.push(entry(
+                    t,
+                    TraceDetails::OrderComplete {
+                        order_id: t,
+                        cycle_time_s: 5.0,
+                        is_late: t % 30 == 0,
+                    },
+                ));
+            }
+        }
+        events
+    }
+
+    fn source(name: &str, seconds: u32) -> ReplaySourceRequest {
+        ReplaySourceRequest {
+            name: name.to_string(),
+            run_id: None,
+            events: Some(log(seconds)),
+            duration_s: Some(seconds as f64),
+            seed: None,
+            scenario: None,
+        }
+    }
+
+    /// A paused playback of one log per `(name, seconds)`, with no task
+    /// advancing it behind the test's back
+    fn playback(dir: &Path, logs: &[(&str, u32)]) -> Playback {
+        let manager =
+            ReplayManager::new(dir.to_path_buf(), Duration::from_secs(60), 10, None, None);
+        let mut session = ReplaySession::new();
+        let mut tracks = Vec::new();
+        for (index, (name, seconds)) in logs.iter().enumerate() {
+            let (engine, track) = manager
+                .load_track(
+                    &source(name, *seconds),
+                    &dir.join(index.to_string()),
+                    &Caller::anonymous(),
+                )
+                .unwrap();
+            session.add(name, engine);
+            tracks.push(track);
+        }
+        let mut playback = Playback {
+            session,
+            tracks,
+            speed: 1.0,
+            last_activity: Instant::now(),
+        };
+        playback.rebuild_views();
+        playback
+    }
+
+    fn replay(playback: Playback) -> Replay {
+        Replay {
+            id: "replay".to_string(),
+            owner: None,
+            dir: PathBuf::new(),
+            playback: Mutex::new(playback),
+            updates: broadcast::channel(1024).0,
+        }
+    }
+
+    /// Each log's keyframe state, leaving out whether it is playing
+    fn states(playback: &Playback) -> Vec<serde_json::Value> {
+        playback
+            .keyframes()
+            .into_iter()
+            .map(|keyframe| {
+                let WebSocketMessage::StateSync { state } = keyframe.message else {
+                    panic!("keyframes are StateSyncs");
+                };
+                let mut state = serde_json::to_value(state).unwrap();
+                state["status"] = serde_json::Value::Null;
+                state
+            })
+            .collect()
+    }
+
+    /// Times of the ticks in `messages`, by log
+    fn ticks(messages: &[ReplayMessage]) -> Vec<(String, f64)> {
+        messages
+            .iter()
+            .filter_map(|m| match m.message {
+                WebSocketMessage::Tick { time_s, .. } => Some((m.replay.clone(), time_s)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_seeking_matches_a_straight_play() {
+        let dir = tempfile::tempdir().unwrap();
+        let logs = [("a", 300), ("b", 150)];
+
+        let mut straight = playback(&dir.path().join("straight"), &logs);
+        straight.play();
+        for _ in 0..10 {
+            straight.frame(20.0);
+        }
+        straight.pause();
+        assert_eq!(straight.time_s(), 200.0);
+        let expected = states(&straight);
+        assert_eq!(expected[0]["orders_completed"], 20);
+        assert_eq!(expected[0]["events_processed"], 200 + 20 + 20 + 20);
+        assert_eq!(expected[1]["time_s"], 150.0);
+
+        // Back and forth, each seek starting from a checkpoint
+        let mut seeking = playback(&dir.path().join("seeking"), &logs);
+        for time_s in [250.0, 30.0, 290.0, 200.0] {
+            seeking.seek(time_s);
+        }
+        assert_eq!(states(&seeking), expected);
+        let checkpoints = &seeking.tracks[0].checkpoints;
+        assert_eq!(checkpoints.count(), 5);
+        assert_eq!(checkpoints.find_at_or_before(200.0).unwrap().time_s, 180.0);
+
+        // The checkpoints a straight play takes serve seeks just the same
+        assert_eq!(straight.tracks[0].checkpoints.count(), 4);
+        straight.seek(10.0);
+        assert_eq!(states(&straight)[0]["orders_completed"], 1);
+        straight.seek(200.0);
+        assert_eq!(states(&straight), expected);
+    }
+
+    #[tokio::test]
+    async fn test_play_pause_and_speed() {
+        let dir = tempfile::tempdir().unwrap();
+        let replay = replay(playback(dir.path(), &[("a", 300)]));
+        let (keyframes, mut updates) = replay.subscribe().await;
+        assert_eq!(keyframes.len(), 1);
+        assert!(replay.playback.lock().await.frame(1.0).is_empty());
+
+        let info = replay.play(Some(5.0)).await.unwrap();
+        assert_eq!((info.status, info.speed), (ReplayStatus::Playing, 5.0));
+        // Control changes send everyone a fresh keyframe
+        let keyframe = updates.recv().await.unwrap();
+        assert!(matches!(
+            keyframe.message,
+            WebSocketMessage::StateSync { ref state } if state.status == SimulationStatus::Running
+        ));
+
+        // Frames carry what happened since the last one, never a keyframe
+        let frame = replay.playback.lock().await.frame(2.0);
+        assert!(frame.iter().all(|m| m.replay == "a"));
+        let moves = frame
+            .iter()
+            .filter(|m| matches!(m.message, WebSocketMessage::RobotMoved { .. }))
+            .count();
+        assert_eq!(moves, 10);
+        assert!(frame
+            .iter()
+            .any(|m| matches!(m.message, WebSocketMessage::OrderCompleted { .. })));
+        assert!(!frame
+            .iter()
+            .any(|m| matches!(m.message, WebSocketMessage::StateSync { .. })));
+        assert_eq!(ticks(&frame), [("a".to_string(), 10.0)]);
+        assert!(matches!(
+            frame.last().unwrap().message,
+            WebSocketMessage::MetricsUpdate { .. }
+        ));
+
+        // A new speed applies from the next frame on, still playing
+        assert!(replay.set_speed(0.0).await.is_err());
+        let info = replay.set_speed(20.0).await.unwrap();
+        assert_eq!((info.status, info.speed), (ReplayStatus::Playing, 20.0));
+        let frame = replay.playback.lock().await.frame(0.5);
+        assert_eq!(ticks(&frame), [("a".to_string(), 20.0)]);
+
+        let info = replay.pause().await;
+        assert_eq!((info.status, info.time_s), (ReplayStatus::Paused, 20.0));
+        assert!(replay.playback.lock().await.frame(1.0).is_empty());
+        assert!(replay.seek(-1.0).await.is_err());
+
+        // Playing to the end finishes exactly once
+        replay.play(Some(100.0)).await.unwrap();
+        let frame = replay.playback.lock().await.frame(10.0);
+        assert_eq!(ticks(&frame), [("a".to_string(), 300.0)]);
+        assert!(matches!(
+            frame.last().unwrap().message,
+            WebSocketMessage::Finished { .. }
+        ));
+        assert_eq!(replay.info().await.status, ReplayStatus::Finished);
+        assert!(replay.playback.lock().await.frame(1.0).is_empty());
+    }
+
+    #[test]
+    fn test_logs_play_and_seek_in_sync() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut playback = playback(dir.path(), &[("a", 300), ("b", 120)]);
+        playback.speed = 10.0;
+        playback.play();
+
+        // Both advance together until the shorter log ends
+        for step in 1..=10 {
+            let frame = playback.frame(1.0);
+            let time_s = (step * 10) as f64;
+            assert_eq!(
+                ticks(&frame),
+                [("a".to_string(), time_s), ("b".to_string(), time_s)]
+            );
+        }
+        let frame = playback.frame(3.0);
+        assert_eq!(
+            ticks(&frame),
+            [("a".to_string(), 130.0), ("b".to_string(), 120.0)]
+        );
+        assert!(frame
+            .iter()
+            .any(|m| m.replay == "b" && matches!(m.message, WebSocketMessage::Finished { .. })));
+        assert_eq!(ticks(&playback.frame(1.0)), [("a".to_string(), 140.0)]);
+
+        // A seek moves every log to the same time, or to its end
+        playback.pause();
+        playback.seek(200.0);
+        let info = playback.info("replay", None);
+        assert_eq!((info.status, info.time_s), (ReplayStatus::Paused, 200.0));
+        assert!(!info.replays[0].finished && info.replays[1].finished);
+        assert_eq!(playback.engine("b").current_time(), 120.0);
+
+        // Seeking back makes a finished log playable again, in step
+        playback.play();
+        playback.seek(60.0);
+        assert_eq!(playback.status(), ReplayStatus::Playing);
+        assert_eq!(
+            ticks(&playback.frame(0.1)),
+            [("a".to_string(), 61.0), ("b".to_string(), 61.0)]
+        );
+        let states = states(&playback);
+        assert_eq!(states[0]["orders_completed"], states[1]["orders_completed"]);
+
+        playback.seek(1000.0);
+        assert_eq!(playback.status(), ReplayStatus::Finished);
+    }
+
+    #[tokio::test]
+    async fn test_replay_websocket() {
+        use crate::{create_router, ApiConfig};
+        use axum::body::{to_bytes, Body};
+        use axum::http::{header, Request, StatusCode};
+        use futures::{SinkExt, StreamExt};
+        use tokio_tungstenite::tungstenite::{Error as WsError, Message as WsMessage};
+        use tower::ServiceExt;
+
+        let data = tempfile::tempdir().unwrap();
+        let app = create_router(ApiConfig {
+            data_dir: Some(data.path().to_path_buf()),
+            ..Default::default()
+        });
+        let request = ReplayRequest {
+            replays: vec![source("a", 300)],
+            speed: Some(50.0),
+        };
+        let response = app
+            .clone()
+            .oneshot(
+                Request::post("/api/replays")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(serde_json::to_vec(&request).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let info: ReplayInfo = serde_json::from_slice(&body).unwrap();
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let url = |id: &str| format!("ws://{}/ws/replays/{}", addr, id);
+        let (mut socket, _) = tokio_tungstenite::connect_async(url(&info.replay_id))
+            .await
+            .unwrap();
+        /// The next text message, as JSON
+        async fn next<S>(socket: &mut S) -> serde_json::Value
+        where
+            S: futures::Stream<Item = Result<WsMessage, WsError>> + Unpin,
+        {
+            loop {
+                let message = tokio::time::timeout(Duration::from_secs(5), socket.next())
+                    .await
+                    .expect("a message within 5 s")
+                    .unwrap()
+                    .unwrap();
+                if let WsMessage::Text(text) = message {
+                    return serde_json::from_str(&text).unwrap();
+                }
+            }
+        }
+
+        let connected = next(&mut socket).await;
+        assert_eq!(connected["type"], "Connected");
+        assert_eq!(connected["session_id"], info.replay_id.as_str());
+        let keyframe = next(&mut socket).await;
+        assert_eq!(
+            (&keyframe["replay"], &keyframe["type"]),
+            (&"a".into(), &"StateSync".into())
+        );
+        assert_eq!(keyframe["state"]["status"], "paused");
+
+        socket
+            .send(WsMessage::Text(r#"{"type":"Start"}"#.to_string()))
+            .await
+            .unwrap();
+        loop {
+            let message = next(&mut socket).await;
+            if message["type"] == "Tick" && message["time_s"].as_f64().unwrap() > 0.0 {
+                assert_eq!(message["replay"], "a");
+                break;
+            }
+        }
+        socket
+            .send(WsMessage::Text(r#"{"type":"Pause"}"#.to_string()))
+            .await
+            .unwrap();
+        loop {
+            let message = next(&mut socket).await;
+            if message["type"] == "StateSync" && message["state"]["status"] == "paused" {
+                break;
+            }
+        }
+
+        // Unknown replays get an error in place of keyframes
+        let (mut socket, _) = tokio_tungstenite::connect_async(url("missing"))
+            .await
+            .unwrap();
+        let error = next(&mut socket).await;
+        assert_eq!(error["type"], "Error");
+        assert_eq!(error["message"], "Replay not found");
+    }
+}
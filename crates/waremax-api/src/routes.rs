@@ -0,0 +1,364 @@
+//! The API's route table
+//!
+//! One entry per method and path, naming the handler that serves it, the
+//! owner check in front of it and what the OpenAPI document says about it.
+//! The router and the OpenAPI document are both built from this table.
+
+use axum::routing::{self, MethodRouter};
+use serde_json::Value;
+use std::sync::Arc;
+
+use crate::handlers::{api, websocket};
+use crate::metrics;
+use crate::openapi::{self, schema, SchemaFn};
+use crate::state::AppState;
+use crate::types::{
+    AddRobotRequest, ControlCommand, ControllerClientMessage, ControllerServerMessage,
+    ControllerStatus, EventRangeQuery, HealthResponse, Intervention, InterventionRecord, JobInfo,
+    JobRequest, JobResults, JobResultsQuery, MapData, PlayRequest, PresetInfo, ReplayInfo,
+    ReplayMapQuery, ReplayMessage, ReplayRequest, RunDetail, RunRecord, SeekRequest, SessionConfig,
+    SessionResponse, SpeedRequest, SpeedResponse, StatusResponse, WebSocketMessage,
+};
+
+/// What a route answers with on success
+#[derive(Clone, Copy)]
+pub(crate) enum Reply {
+    Json(SchemaFn),
+    /// JSON, or CSV with `?format=csv`
+    JsonOrCsv(SchemaFn),
+    /// Text of the given content type
+    Text(&'static str),
+    /// A WebSocket carrying messages both ways
+    WebSocket {
+        receive: SchemaFn,
+        send: SchemaFn,
+    },
+}
+
+/// Whose resource the `:id` of a route names; only its owner may use it
+#[derive(Clone, Copy)]
+pub(crate) enum Owner {
+    Session,
+    Run,
+    Job,
+    Replay,
+}
+
+/// One route: its handler, and how the OpenAPI document describes it
+#[derive(Clone, Copy)]
+pub(crate) struct Route {
+    pub(crate) method: &'static str,
+    /// In axum's syntax, e.g. `/api/session/:id`
+    pub(crate) path: &'static str,
+    pub(crate) handler: fn() -> MethodRouter<Arc<AppState>>,
+    pub(crate) owner: Option<Owner>,
+    pub(crate) tag: &'static str,
+    pub(crate) summary: &'static str,
+    pub(crate) query: Option<SchemaFn>,
+    pub(crate) body: Option<SchemaFn>,
+    pub(crate) body_required: bool,
+    pub(crate) status: u16,
+    pub(crate) reply: Reply,
+    pub(crate) errors: &'static [u16],
+    pub(crate) public: bool,
+}
+
+impl Route {
+    const fn new(
+        method: &'static str,
+        path: &'static str,
+        tag: &'static str,
+        handler: fn() -> MethodRouter<Arc<AppState>>,
+    ) -> Self {
+        Self {
+            method,
+            path,
+            handler,
+            owner: None,
+            tag,
+            summary: "",
+            query: None,
+            body: None,
+            body_required: false,
+            status: 200,
+            reply: Reply::Json(schema::<Value>),
+            errors: &[],
+            public: false,
+        }
+    }
+
+    const fn owner(mut self, owner: Owner) -> Self {
+        self.owner = Some(owner);
+        self
+    }
+
+    const fn summary(mut self, summary: &'static str) -> Self {
+        self.summary = summary;
+        self
+    }
+
+    const fn query(mut self, query: SchemaFn) -> Self {
+        self.query = Some(query);
+        self
+    }
+
+    const fn body(mut self, body: SchemaFn) -> Self {
+        self.body = Some(body);
+        self.body_required = true;
+        self
+    }
+
+    const fn optional_body(mut self, body: SchemaFn) -> Self {
+        self.body = Some(body);
+        self
+    }
+
+    const fn returns(mut self, status: u16, reply: Reply) -> Self {
+        self.status = status;
+        self.reply = reply;
+        self
+    }
+
+    const fn errors(mut self, errors: &'static [u16]) -> Self {
+        self.errors = errors;
+        self
+    }
+
+    const fn public(mut self) -> Self {
+        self.public = true;
+        self
+    }
+}
+
+/// `route!(get "/path", "tag", handler)` routes `GET /path` to `handler`
+macro_rules! route {
+    ($method:ident $path:literal, $tag:literal, $handler:path) => {
+        Route::new(stringify!($method), $path, $tag, || {
+            routing::$method($handler)
+        })
+    };
+}
+
+/// Every route of the API; [`crate::create_router`] serves exactly these
+/// and [`crate::openapi::openapi`] documents them
+pub(crate) const ROUTES: &[Route] = &[
+    // Meta
+    route!(get "/api/health", "meta", api::health_check)
+        .summary("Health check")
+        .returns(200, Reply::Json(schema::<HealthResponse>))
+        .public(),
+    route!(get "/openapi.json", "meta", openapi::openapi_json)
+        .summary("This OpenAPI document")
+        .public(),
+    route!(get "/websocket.schema.json", "meta", openapi::websocket_schema_json)
+        .summary("JSON Schema of every WebSocket message")
+        .public(),
+    route!(get "/metrics", "meta", metrics::scrape)
+        .summary("Server and session metrics in the OpenMetrics text format")
+        .returns(200, Reply::Text(metrics::CONTENT_TYPE))
+        .errors(&[403]),
+    // Sessions
+    route!(get "/api/presets", "sessions", api::get_presets)
+        .summary("List simulation presets")
+        .returns(200, Reply::Json(schema::<Vec<PresetInfo>>)),
+    route!(get "/api/scenarios", "sessions", api::get_scenarios)
+        .summary("List the scenarios in the server's scenario library")
+        .returns(200, Reply::Json(schema::<Vec<String>>)),
+    route!(post "/api/session", "sessions", api::create_session)
+        .summary("Create a session from a preset, inline scenario or library scenario")
+        .body(schema::<SessionConfig>)
+        .returns(201, Reply::Json(schema::<SessionResponse>))
+        .errors(&[400, 404, 422, 429]),
+    route!(delete "/api/session/:id", "sessions", api::delete_session)
+        .owner(Owner::Session)
+        .summary("Delete a session")
+        .returns(200, Reply::Json(schema::<StatusResponse>))
+        .errors(&[404]),
+    route!(get "/api/session/:id/map", "sessions", api::get_session_map)
+        .owner(Owner::Session)
+        .summary("Get the warehouse map of a session")
+        .returns(200, Reply::Json(schema::<MapData>))
+        .errors(&[404]),
+    route!(post "/api/session/:id/start", "sessions", api::start_session)
+        .owner(Owner::Session)
+        .summary("Start a session")
+        .returns(200, Reply::Json(schema::<StatusResponse>))
+        .errors(&[404, 500]),
+    route!(post "/api/session/:id/pause", "sessions", api::pause_session)
+        .owner(Owner::Session)
+        .summary("Pause a session")
+        .returns(200, Reply::Json(schema::<StatusResponse>))
+        .errors(&[404, 500]),
+    route!(post "/api/session/:id/resume", "sessions", api::resume_session)
+        .owner(Owner::Session)
+        .summary("Resume a paused session")
+        .returns(200, Reply::Json(schema::<StatusResponse>))
+        .errors(&[404, 500]),
+    route!(post "/api/session/:id/speed", "sessions", api::set_speed)
+        .owner(Owner::Session)
+        .summary("Set the simulation speed")
+        .body(schema::<SpeedRequest>)
+        .returns(200, Reply::Json(schema::<SpeedResponse>))
+        .errors(&[404, 500]),
+    route!(post "/api/session/:id/step", "sessions", api::step_session)
+        .owner(Owner::Session)
+        .summary("Process one event")
+        .returns(200, Reply::Json(schema::<StatusResponse>))
+        .errors(&[404, 500]),
+    route!(post "/api/session/:id/add-robot", "sessions", api::add_robot)
+        .owner(Owner::Session)
+        .summary("Add a robot at a node")
+        .body(schema::<AddRobotRequest>)
+        .returns(200, Reply::Json(schema::<StatusResponse>))
+        .errors(&[404, 409, 500]),
+    route!(post "/api/session/:id/intervene", "sessions", api::intervene)
+        .owner(Owner::Session)
+        .summary("Queue a live intervention for the next event boundary")
+        .body(schema::<Intervention>)
+        .returns(202, Reply::Json(schema::<StatusResponse>))
+        .errors(&[404, 409, 500]),
+    route!(get "/api/session/:id/interventions", "sessions", api::get_interventions)
+        .owner(Owner::Session)
+        .summary("Get the session's intervention log")
+        .returns(200, Reply::Json(schema::<Vec<InterventionRecord>>))
+        .errors(&[404]),
+    route!(get "/api/session/:id/controller", "sessions", api::get_controller)
+        .owner(Owner::Session)
+        .summary("Get the status of the session's external controller")
+        .returns(200, Reply::Json(schema::<Option<ControllerStatus>>))
+        .errors(&[404]),
+    // Run history
+    route!(get "/api/runs", "runs", api::list_runs)
+        .summary("List recorded runs")
+        .returns(200, Reply::Json(schema::<Vec<RunRecord>>))
+        .errors(&[500]),
+    route!(get "/api/runs/:id", "runs", api::get_run)
+        .owner(Owner::Run)
+        .summary("Get a run with its scenario, intervention log and report")
+        .returns(200, Reply::Json(schema::<RunDetail>))
+        .errors(&[404, 500]),
+    route!(delete "/api/runs/:id", "runs", api::delete_run)
+        .owner(Owner::Run)
+        .summary("Delete a run and its event log")
+        .returns(200, Reply::Json(schema::<StatusResponse>))
+        .errors(&[404, 409, 500]),
+    route!(get "/api/runs/:id/events", "runs", api::get_run_events)
+        .owner(Owner::Run)
+        .summary("Get a run's event log")
+        .query(schema::<EventRangeQuery>)
+        .returns(200, Reply::Json(schema::<Vec<Value>>))
+        .errors(&[404, 409, 500]),
+    route!(post "/api/runs/:id/reopen", "runs", api::reopen_run)
+        .owner(Owner::Run)
+        .summary("Re-open a run as a paused replay session")
+        .returns(201, Reply::Json(schema::<SessionResponse>))
+        .errors(&[400, 404, 429, 500]),
+    // Batch jobs
+    route!(post "/api/jobs", "jobs", api::submit_job)
+        .summary("Submit a sweep, comparison or A/B test")
+        .body(schema::<JobRequest>)
+        .returns(202, Reply::Json(schema::<JobInfo>))
+        .errors(&[400, 404, 422, 429]),
+    route!(get "/api/jobs", "jobs", api::list_jobs)
+        .summary("List batch jobs")
+        .returns(200, Reply::Json(schema::<Vec<JobInfo>>)),
+    route!(get "/api/jobs/:id", "jobs", api::get_job)
+        .owner(Owner::Job)
+        .summary("Get a job's status and progress")
+        .returns(200, Reply::Json(schema::<JobInfo>))
+        .errors(&[404]),
+    route!(delete "/api/jobs/:id", "jobs", api::cancel_job)
+        .owner(Owner::Job)
+        .summary("Cancel a job")
+        .returns(200, Reply::Json(schema::<JobInfo>))
+        .errors(&[404]),
+    route!(get "/api/jobs/:id/results", "jobs", api::get_job_results)
+        .owner(Owner::Job)
+        .summary("Get a completed job's results")
+        .query(schema::<JobResultsQuery>)
+        .returns(200, Reply::JsonOrCsv(schema::<JobResults>))
+        .errors(&[400, 404, 409]),
+    // Replays
+    route!(post "/api/replays", "replays", api::create_replay)
+        .summary("Create a replay of recorded runs and uploaded event logs")
+        .body(schema::<ReplayRequest>)
+        .returns(201, Reply::Json(schema::<ReplayInfo>))
+        .errors(&[400, 404, 409, 422, 500]),
+    route!(get "/api/replays", "replays", api::list_replays)
+        .summary("List replays")
+        .returns(200, Reply::Json(schema::<Vec<ReplayInfo>>)),
+    route!(get "/api/replays/:id", "replays", api::get_replay)
+        .owner(Owner::Replay)
+        .summary("Get a replay's playback position and event logs")
+        .returns(200, Reply::Json(schema::<ReplayInfo>))
+        .errors(&[404]),
+    route!(delete "/api/replays/:id", "replays", api::delete_replay)
+        .owner(Owner::Replay)
+        .summary("Delete a replay")
+        .returns(200, Reply::Json(schema::<StatusResponse>))
+        .errors(&[404]),
+    route!(post "/api/replays/:id/play", "replays", api::play_replay)
+        .owner(Owner::Replay)
+        .summary("Play a replay")
+        .optional_body(schema::<PlayRequest>)
+        .returns(200, Reply::Json(schema::<ReplayInfo>))
+        .errors(&[400, 404]),
+    route!(post "/api/replays/:id/pause", "replays", api::pause_replay)
+        .owner(Owner::Replay)
+        .summary("Pause a replay")
+        .returns(200, Reply::Json(schema::<ReplayInfo>))
+        .errors(&[404]),
+    route!(post "/api/replays/:id/seek", "replays", api::seek_replay)
+        .owner(Owner::Replay)
+        .summary("Move every event log of a replay to the same time")
+        .body(schema::<SeekRequest>)
+        .returns(200, Reply::Json(schema::<ReplayInfo>))
+        .errors(&[400, 404]),
+    route!(get "/api/replays/:id/map", "replays", api::get_replay_map)
+        .owner(Owner::Replay)
+        .summary("Get the warehouse map of one of a replay's event logs")
+        .query(schema::<ReplayMapQuery>)
+        .returns(200, Reply::Json(schema::<MapData>))
+        .errors(&[404]),
+    // WebSockets
+    route!(get "/ws/:id", "websocket", websocket::websocket_handler)
+        .owner(Owner::Session)
+        .summary("Stream a session's updates and control it")
+        .returns(
+            101,
+            Reply::WebSocket {
+                receive: schema::<WebSocketMessage>,
+                send: schema::<ControlCommand>,
+            },
+        )
+        .errors(&[404]),
+    route!(get "/ws/:id/controller", "websocket", websocket::controller_handler)
+        .owner(Owner::Session)
+        .summary("Make the session's dispatch decisions from an external controller")
+        .returns(
+            101,
+            Reply::WebSocket {
+                receive: schema::<ControllerServerMessage>,
+                send: schema::<ControllerClientMessage>,
+            },
+        )
+        .errors(&[404]),
+    route!(get "/ws/replays/:id", "websocket", websocket::replay_handler)
+        .owner(Owner::Replay)
+        .summary("Stream a replay and control its playback")
+        .returns(
+            101,
+            Reply::WebSocket {
+                receive: schema::<ReplayMessage>,
+                send: schema::<ControlCommand>,
+            },
+        )
+        .errors(&[404]),
+];
+
+/// Whether `path` is served without an API key
+pub(crate) fn is_public(path: &str) -> bool {
+    ROUTES
+        .iter()
+        .any(|route| route.public && route.path == path)
+}
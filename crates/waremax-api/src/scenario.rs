@@ -0,0 +1,391 @@
+//! Scenario resolution for new sessions
+//!
+//! A session or batch job runs either a built-in preset or a full
+//! `ScenarioConfig`, given inline (a JSON object, or YAML/JSON text) or by
+//! name from the server's scenario library. Full scenarios are checked with
+//! `waremax_config::validate_scenario` before they are used, and rejected if
+//! they name map or storage files or nodes the generated world doesn't have.
+
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+use waremax_config::{
+    validate_scenario, MapConfig, ScenarioConfig, StorageConfig, ValidationError,
+};
+use waremax_sim::create_policies_with_traffic;
+
+use crate::simulation::{check_generated_world, ControllableSimulation, SimulationConfig};
+use crate::types::{ScenarioSource, SessionConfig};
+
+/// File extensions recognised in the scenario library
+const SCENARIO_EXTENSIONS: [&str; 3] = ["yaml", "yml", "json"];
+
+/// Why a session's scenario could not be used
+#[derive(Error, Debug)]
+pub enum ScenarioError {
+    #[error("Only one of 'scenario' and 'scenario_name' may be set")]
+    Ambiguous,
+
+    #[error("No scenario library is configured on this server")]
+    NoLibrary,
+
+    #[error("Scenario '{0}' not found")]
+    NotFound(String),
+
+    #[error("Failed to parse scenario: {0}")]
+    Parse(String),
+
+    #[error("Scenario validation failed with {} error(s)", .0.len())]
+    Invalid(Vec<ValidationError>),
+
+    #[error("Invalid policies: {0}")]
+    Policies(String),
+}
+
+/// A validated scenario with its non-fatal warnings
+#[derive(Debug, Clone)]
+pub struct ResolvedScenario {
+    pub scenario: ScenarioConfig,
+    pub warnings: Vec<ValidationError>,
+}
+
+/// Resolve the scenario a session should run.
+///
+/// Returns `Ok(None)` when the request names no scenario and the preset
+/// should be used. The session's robot count, order rate and duration
+/// overrides are applied before validation.
+pub fn resolve_scenario(
+    config: &SessionConfig,
+    library: Option<&Path>,
+) -> Result<Option<ResolvedScenario>, ScenarioError> {
+    let Some((mut scenario, map, storage)) =
+        load_scenario(&config.scenario, &config.scenario_name, library)?
+    else {
+        return Ok(None);
+    };
+
+    if let Some(robots) = config.robot_count {
+        scenario.robots.count = robots;
+    }
+    if let Some(rate_per_hour) = config.order_rate {
+        scenario.orders.arrival_process.rate_per_min = rate_per_hour / 60.0;
+    }
+    if let Some(minutes) = config.duration_minutes {
+        scenario.simulation.duration_minutes = minutes;
+    }
+
+    validated(scenario, map, storage).map(Some)
+}
+
+/// Resolve a batch job's scenario, falling back to its preset (default
+/// "standard") when no scenario is given.
+pub fn resolve_source(
+    source: &ScenarioSource,
+    library: Option<&Path>,
+) -> Result<ResolvedScenario, ScenarioError> {
+    match load_scenario(&source.scenario, &source.scenario_name, library)? {
+        Some((scenario, map, storage)) => validated(scenario, map, storage),
+        None => {
+            let config = SimulationConfig {
+                preset: source
+                    .preset
+                    .clone()
+                    .unwrap_or_else(|| "standard".to_string()),
+                ..Default::default()
+            };
+            validated(ControllableSimulation::build_scenario(&config), None, None)
+        }
+    }
+}
+
+type LoadedScenario = (ScenarioConfig, Option<MapConfig>, Option<StorageConfig>);
+
+/// Load the inline or named scenario, if either is given
+fn load_scenario(
+    inline: &Option<serde_json::Value>,
+    name: &Option<String>,
+    library: Option<&Path>,
+) -> Result<Option<LoadedScenario>, ScenarioError> {
+    match (inline, name) {
+        (Some(_), Some(_)) => Err(ScenarioError::Ambiguous),
+        (None, None) => Ok(None),
+        (Some(inline), None) => Ok(Some((parse_inline(inline)?, None, None))),
+        (None, Some(name)) => {
+            let path = find_in_library(library.ok_or(ScenarioError::NoLibrary)?, name)?;
+            load_library_scenario(&path).map(Some)
+        }
+    }
+}
+
+fn validated(
+    scenario: ScenarioConfig,
+    map: Option<MapConfig>,
+    storage: Option<StorageConfig>,
+) -> Result<ResolvedScenario, ScenarioError> {
+    let warnings = validate_scenario(&scenario, map.as_ref(), storage.as_ref())
+        .map_err(ScenarioError::Invalid)?;
+    // Sessions run on a generated world, which validation knows nothing of
+    let unsupported = check_generated_world(&scenario);
+    if !unsupported.is_empty() {
+        return Err(ScenarioError::Invalid(unsupported));
+    }
+    // Validation can't tell whether a learned policy's weights parse
+    create_policies_with_traffic(&scenario.policies, &scenario.traffic)
+        .map_err(ScenarioError::Policies)?;
+    Ok(ResolvedScenario { scenario, warnings })
+}
+
+/// Names of the scenarios in the library, sorted
+pub fn list_library(library: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(library) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let ext = path.extension()?.to_str()?;
+            if !path.is_file() || !SCENARIO_EXTENSIONS.contains(&ext) {
+                return None;
+            }
+            let name = path.file_stem()?.to_str()?;
+            // Hidden files can't be loaded by name, so don't list them
+            (!name.starts_with('.')).then(|| name.to_string())
+        })
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Inline scenarios are either a JSON object or a string of YAML (or JSON)
+fn parse_inline(inline: &serde_json::Value) -> Result<ScenarioConfig, ScenarioError> {
+    match inline {
+        serde_json::Value::String(text) => {
+            ScenarioConfig::from_yaml(text).map_err(|e| ScenarioError::Parse(e.to_string()))
+        }
+        value => {
+            serde_json::from_value(value.clone()).map_err(|e| ScenarioError::Parse(e.to_string()))
+        }
+    }
+}
+
+/// Look up `name` (with or without extension) in the library directory.
+/// Names containing path components are rejected.
+fn find_in_library(library: &Path, name: &str) -> Result<PathBuf, ScenarioError> {
+    let plain = !name.is_empty()
+        && Path::new(name).file_name().and_then(|f| f.to_str()) == Some(name)
+        && !name.starts_with('.');
+    if !plain {
+        return Err(ScenarioError::NotFound(name.to_string()));
+    }
+
+    let exact = library.join(name);
+    let has_ext = exact
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| SCENARIO_EXTENSIONS.contains(&e));
+    if has_ext && exact.is_file() {
+        return Ok(exact);
+    }
+    SCENARIO_EXTENSIONS
+        .iter()
+        .map(|ext| library.join(format!("{}.{}", name, ext)))
+        .find(|path| path.is_file())
+        .ok_or_else(|| ScenarioError::NotFound(name.to_string()))
+}
+
+/// Load a library scenario with the map and storage files it references
+/// (relative to the scenario file), when they exist, for cross-reference
+/// validation.
+fn load_library_scenario(path: &Path) -> Result<LoadedScenario, ScenarioError> {
+    let scenario = ScenarioConfig::from_file(&path.to_string_lossy())
+        .map_err(|e| ScenarioError::Parse(e.to_string()))?;
+
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+    let map = MapConfig::from_file(&base_dir.join(&scenario.map.file).to_string_lossy()).ok();
+    let storage =
+        StorageConfig::from_file(&base_dir.join(&scenario.storage.file).to_string_lossy()).ok();
+
+    Ok((scenario, map, storage))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A library holding one valid scenario, `base.json`, plus decoys the
+    /// name lookup must never reach
+    fn library() -> (TempDir, PathBuf) {
+        let root = tempfile::tempdir().unwrap();
+        let library = root.path().join("library");
+        std::fs::create_dir_all(library.join("a")).unwrap();
+        let base = ControllableSimulation::build_scenario(&SimulationConfig::default());
+        for path in [
+            library.join("base.json"),
+            library.join("a").join("b.json"),
+            library.join(".hidden.json"),
+            root.path().join("x.json"),
+        ] {
+            write_scenario(&path, &base);
+        }
+        (root, library)
+    }
+
+    fn write_scenario(path: &Path, scenario: &ScenarioConfig) {
+        std::fs::write(path, serde_json::to_string(scenario).unwrap()).unwrap();
+    }
+
+    fn named(name: &str) -> SessionConfig {
+        SessionConfig {
+            scenario_name: Some(name.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_find_in_library_rejects_paths() {
+        let (_root, library) = library();
+        assert!(find_in_library(&library, "base").is_ok());
+        assert!(find_in_library(&library, "base.json").is_ok());
+        for name in ["../x", "/etc/passwd", ".hidden", "a/b", ""] {
+            assert!(
+                matches!(
+                    find_in_library(&library, name),
+                    Err(ScenarioError::NotFound(_))
+                ),
+                "{name:?} should not resolve"
+            );
+        }
+    }
+
+    #[test]
+    fn test_list_library_skips_hidden_and_nested() {
+        let (_root, library) = library();
+        std::fs::write(library.join("notes.txt"), "not a scenario").unwrap();
+        assert_eq!(list_library(&library), ["base"]);
+    }
+
+    #[test]
+    fn test_resolve_errors() {
+        let (_root, library) = library();
+        let both = SessionConfig {
+            scenario: Some(serde_json::json!({})),
+            ..named("base")
+        };
+        assert!(matches!(
+            resolve_scenario(&both, Some(&library)),
+            Err(ScenarioError::Ambiguous)
+        ));
+        assert!(matches!(
+            resolve_scenario(&named("base"), None),
+            Err(ScenarioError::NoLibrary)
+        ));
+        assert!(matches!(
+            resolve_scenario(&named("missing"), Some(&library)),
+            Err(ScenarioError::NotFound(_))
+        ));
+
+        let mut broken = ControllableSimulation::build_scenario(&SimulationConfig::default());
+        broken.simulation.duration_minutes = 0.0;
+        write_scenario(&library.join("broken.json"), &broken);
+        match resolve_scenario(&named("broken"), Some(&library)) {
+            Err(ScenarioError::Invalid(errors)) => {
+                assert!(errors
+                    .iter()
+                    .any(|e| e.path.to_string() == "scenario.simulation.duration_minutes"));
+            }
+            other => panic!("expected Invalid, got {:?}", other.map(|_| ())),
+        }
+
+        // No scenario at all means the preset
+        assert!(resolve_scenario(&SessionConfig::default(), Some(&library))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_rejects_what_the_generated_world_lacks() {
+        let (_root, library) = library();
+        let mut scenario = ControllableSimulation::build_scenario(&SimulationConfig::default());
+        scenario.map.file = "map.json".to_string();
+        scenario.storage.file = "storage.yaml".to_string();
+        scenario.stations[0].node = "S1".to_string();
+        scenario.stations[1].node = "100".to_string();
+        scenario
+            .charging_stations
+            .push(waremax_config::ChargingStationConfig {
+                id: "C1".to_string(),
+                node: "0".to_string(),
+                bays: 1,
+                charge_rate_w: 200.0,
+                queue_capacity: None,
+            });
+        write_scenario(&library.join("custom.json"), &scenario);
+
+        let Err(ScenarioError::Invalid(errors)) =
+            resolve_scenario(&named("custom"), Some(&library))
+        else {
+            panic!("expected Invalid");
+        };
+        let paths: Vec<String> = errors.iter().map(|e| e.path.to_string()).collect();
+        assert_eq!(
+            paths,
+            [
+                "scenario.map.file",
+                "scenario.storage.file",
+                "scenario.charging_stations[0]",
+                "scenario.stations[0].node",
+                "scenario.stations[1].node",
+            ]
+        );
+
+        // Inline scenarios too, and any node of the grid is fine
+        let mut inline = ControllableSimulation::build_scenario(&SimulationConfig::default());
+        inline.stations[1].node = "99".to_string();
+        let config = SessionConfig {
+            scenario: Some(serde_json::to_value(&inline).unwrap()),
+            ..Default::default()
+        };
+        assert!(resolve_scenario(&config, None).is_ok());
+        inline.map.file = "map.json".to_string();
+        let config = SessionConfig {
+            scenario: Some(serde_json::to_value(&inline).unwrap()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            resolve_scenario(&config, None),
+            Err(ScenarioError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_overrides_apply_before_validation() {
+        let (_root, library) = library();
+        let mut empty = ControllableSimulation::build_scenario(&SimulationConfig::default());
+        empty.robots.count = 0;
+        write_scenario(&library.join("empty.json"), &empty);
+
+        // Invalid as written, fixed by the override
+        assert!(matches!(
+            resolve_scenario(&named("empty"), Some(&library)),
+            Err(ScenarioError::Invalid(_))
+        ));
+        let fixed = SessionConfig {
+            robot_count: Some(3),
+            ..named("empty")
+        };
+        let resolved = resolve_scenario(&fixed, Some(&library)).unwrap().unwrap();
+        assert_eq!(resolved.scenario.robots.count, 3);
+
+        // Valid as written, broken by the override
+        let broken = SessionConfig {
+            duration_minutes: Some(-1.0),
+            ..named("base")
+        };
+        assert!(matches!(
+            resolve_scenario(&broken, Some(&library)),
+            Err(ScenarioError::Invalid(_))
+        ));
+    }
+}
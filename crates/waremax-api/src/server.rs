@@ -1,28 +1,44 @@
 //! Axum router builder for the Waremax simulation API
 
-use axum::{
-    routing::{delete, get, post},
-    Router,
-};
+use axum::{middleware, Router};
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 use tower_http::cors::{Any, CorsLayer};
 
-use crate::handlers::{api, websocket};
+use crate::audit::AuditLog;
+use crate::auth::{self, ApiKey, ApiKeys};
+use crate::history::RunHistory;
+use crate::jobs::JobManager;
+use crate::metrics::{start_metrics_task, ServerMetrics};
+use crate::replay::{start_replay_cleanup_task, ReplayManager};
+use crate::routes::{Owner, ROUTES};
 use crate::session::{start_cleanup_task, SessionManager};
 use crate::state::AppState;
 
 /// API configuration for router creation
 #[derive(Clone, Debug)]
 pub struct ApiConfig {
-    /// Session timeout in seconds
+    /// Session timeout in seconds (also applies to replays)
     pub session_timeout_secs: u64,
-    /// Maximum concurrent sessions
+    /// Maximum concurrent sessions (and, separately, replays)
     pub max_sessions: usize,
     /// CORS origins (empty = allow all for development)
     pub cors_origins: Vec<String>,
     /// Whether to enable request ID headers
     pub request_id_header: bool,
+    /// Scenario library directory (YAML/JSON scenarios selectable by name)
+    pub scenario_dir: Option<PathBuf>,
+    /// Directory batch jobs are saved in (None = jobs are kept in memory only)
+    pub job_dir: Option<PathBuf>,
+    /// Maximum batch jobs running at once
+    pub job_workers: usize,
+    /// Directory the run history is kept in (None = sessions are not recorded)
+    pub data_dir: Option<PathBuf>,
+    /// Accepted API keys (empty = the API is open to anyone)
+    pub api_keys: Vec<ApiKey>,
+    /// File the audit log is appended to, as JSON lines
+    pub audit_log: Option<PathBuf>,
 }
 
 impl Default for ApiConfig {
@@ -32,23 +48,95 @@ impl Default for ApiConfig {
             max_sessions: 100,
             cors_origins: vec![],
             request_id_header: true,
+            scenario_dir: None,
+            job_dir: None,
+            job_workers: 2,
+            data_dir: None,
+            api_keys: Vec::new(),
+            audit_log: None,
         }
     }
 }
 
 /// Create the application router
+///
+/// # Panics
+///
+/// If the API keys repeat a name or key.
 pub fn create_router(config: ApiConfig) -> Router {
-    // Create session manager
-    let session_manager = Arc::new(SessionManager::new(
+    // Create session manager, recording sessions if a data directory is set
+    let mut session_manager = SessionManager::new(
         Duration::from_secs(config.session_timeout_secs),
         config.max_sessions,
-    ));
+    );
+    if let Some(dir) = &config.data_dir {
+        match RunHistory::open(dir) {
+            Ok(history) => session_manager = session_manager.with_history(Arc::new(history)),
+            Err(e) => tracing::error!(
+                dir = %dir.display(),
+                error = %e,
+                "Failed to open run history; sessions will not be recorded"
+            ),
+        }
+    }
+    let session_manager = Arc::new(session_manager);
 
     // Start session cleanup task
     let _cleanup_task = start_cleanup_task(session_manager.clone());
 
+    // Create job manager, resuming jobs saved by a previous run
+    let job_manager = JobManager::new(
+        config.job_workers,
+        config.job_dir.clone(),
+        config.scenario_dir.clone(),
+    );
+
+    // Create replay manager, keeping its event log copies in the data
+    // directory if there is one
+    let replay_dir = match &config.data_dir {
+        Some(dir) => dir.join("replays"),
+        None => std::env::temp_dir().join(format!("waremax-replays-{}", std::process::id())),
+    };
+    let replay_manager = Arc::new(ReplayManager::new(
+        replay_dir,
+        Duration::from_secs(config.session_timeout_secs),
+        config.max_sessions,
+        session_manager.history().cloned(),
+        config.scenario_dir.clone(),
+    ));
+    let _replay_cleanup_task = start_replay_cleanup_task(replay_manager.clone());
+
+    // API keys and audit log
+    let api_keys = if config.api_keys.is_empty() {
+        None
+    } else {
+        Some(ApiKeys::new(config.api_keys.clone()).unwrap_or_else(|e| panic!("{}", e)))
+    };
+    let audit = match &config.audit_log {
+        Some(path) => AuditLog::with_file(path).unwrap_or_else(|e| {
+            tracing::error!(
+                path = %path.display(),
+                error = %e,
+                "Failed to open audit log; auditing to tracing only"
+            );
+            AuditLog::new()
+        }),
+        None => AuditLog::new(),
+    };
+
+    let server_metrics = Arc::new(ServerMetrics::new());
+    let _metrics_task = start_metrics_task(server_metrics.clone());
+
     // Create app state
-    let state = Arc::new(AppState { session_manager });
+    let state = Arc::new(AppState {
+        session_manager,
+        scenario_dir: config.scenario_dir.clone(),
+        job_manager,
+        replay_manager,
+        api_keys,
+        audit,
+        metrics: server_metrics,
+    });
 
     // CORS configuration
     let cors = if config.cors_origins.is_empty() {
@@ -69,30 +157,46 @@ pub fn create_router(config: ApiConfig) -> Router {
                 axum::http::Method::POST,
                 axum::http::Method::DELETE,
             ])
-            .allow_headers([axum::http::header::CONTENT_TYPE])
+            .allow_headers([
+                axum::http::header::CONTENT_TYPE,
+                axum::http::header::AUTHORIZATION,
+                axum::http::HeaderName::from_static("x-api-key"),
+            ])
     };
 
-    // API routes
-    let api_routes = Router::new()
-        .route("/health", get(api::health_check))
-        .route("/presets", get(api::get_presets))
-        .route("/session", post(api::create_session))
-        .route("/session/:id", delete(api::delete_session))
-        .route("/session/:id/map", get(api::get_session_map))
-        .route("/session/:id/start", post(api::start_session))
-        .route("/session/:id/pause", post(api::pause_session))
-        .route("/session/:id/resume", post(api::resume_session))
-        .route("/session/:id/speed", post(api::set_speed))
-        .route("/session/:id/step", post(api::step_session))
-        .route("/session/:id/add-robot", post(api::add_robot));
-
-    // WebSocket route
-    let ws_routes = Router::new().route("/:id", get(websocket::websocket_handler));
+    // Every route of the table, behind its owner check if it has one
+    let mut router = Router::new();
+    for route in ROUTES {
+        let handler = (route.handler)();
+        let state = state.clone();
+        let handler = match route.owner {
+            None => handler,
+            Some(Owner::Session) => handler.route_layer(middleware::from_fn_with_state(
+                state,
+                auth::require_session_owner,
+            )),
+            Some(Owner::Run) => handler.route_layer(middleware::from_fn_with_state(
+                state,
+                auth::require_run_owner,
+            )),
+            Some(Owner::Job) => handler.route_layer(middleware::from_fn_with_state(
+                state,
+                auth::require_job_owner,
+            )),
+            Some(Owner::Replay) => handler.route_layer(middleware::from_fn_with_state(
+                state,
+                auth::require_replay_owner,
+            )),
+        };
+        router = router.route(route.path, handler);
+    }
 
-    // Combine all routes
-    Router::new()
-        .nest("/api", api_routes)
-        .nest("/ws", ws_routes)
+    // Combine all routes; every request is authenticated before routing
+    router
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth::authenticate,
+        ))
         .layer(cors)
         .with_state(state)
 }
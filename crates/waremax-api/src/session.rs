@@ -5,13 +5,31 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
+use thiserror::Error;
 use tokio::sync::{broadcast, Mutex, RwLock};
 use uuid::Uuid;
+use waremax_config::ScenarioConfig;
 
+use crate::auth::{Caller, QuotaError};
+use crate::history::RunHistory;
 use crate::simulation::{
-    spawn_simulation, SimCommand, SimUpdate, SimulationConfig, SimulationHandle,
+    spawn_simulation, ControllableSimulation, RunRecording, SimCommand, SimUpdate,
+    SimulationConfig, SimulationHandle,
 };
-use crate::types::{MapData, SessionConfig};
+use crate::types::{
+    ControllerStatus, Intervention, InterventionRecord, MapData, RunDetail, SessionConfig,
+    SimulationState,
+};
+
+/// Why a session could not be created
+#[derive(Error, Debug)]
+pub enum SessionError {
+    #[error("Maximum number of sessions reached")]
+    Full,
+
+    #[error(transparent)]
+    Quota(#[from] QuotaError),
+}
 
 /// A simulation session for a single user/browser tab
 pub struct Session {
@@ -21,21 +39,44 @@ pub struct Session {
     pub config: SessionConfig,
     pub handle: SimulationHandle,
     pub task: tokio::task::JoinHandle<()>,
+    /// Id of the recorded run this session replays; replays can't be changed
+    pub replay_of: Option<String>,
 }
 
 impl Session {
-    /// Create a new session
-    pub fn new(config: SessionConfig) -> Self {
+    /// Create a new session, recorded in `history` as owned by `owner` if
+    /// given
+    pub fn new(
+        config: SessionConfig,
+        scenario: Option<ScenarioConfig>,
+        history: Option<&Arc<RunHistory>>,
+        owner: Option<&str>,
+    ) -> Self {
         let id = Uuid::new_v4().to_string();
 
-        let sim_config = SimulationConfig {
-            preset: config.preset.clone(),
-            robot_count: config.robot_count,
-            order_rate: config.order_rate,
-            duration_minutes: config.duration_minutes.unwrap_or(60.0),
-            grid_rows: config.grid_rows,
-            grid_cols: config.grid_cols,
-        };
+        let mut sim_config = simulation_config(&config, scenario);
+
+        if let Some(history) = history {
+            // Record the scenario as built, so the run keeps its seed even
+            // when it came from a preset
+            let scenario = ControllableSimulation::build_scenario(&sim_config);
+            let source = match (&config.scenario_name, &config.scenario) {
+                (Some(name), _) => name.clone(),
+                (None, Some(_)) => "inline".to_string(),
+                (None, None) => config.preset.clone(),
+            };
+            match history.begin(&id, &source, &scenario, config.record_events, owner) {
+                Ok(()) => {
+                    sim_config.recording = Some(RunRecording {
+                        history: history.clone(),
+                        run_id: id.clone(),
+                        event_log: config.record_events,
+                    });
+                }
+                Err(e) => tracing::warn!(session_id = %id, error = %e, "Failed to record run"),
+            }
+            sim_config.scenario = Some(scenario);
+        }
 
         let (handle, task) = spawn_simulation(sim_config);
 
@@ -46,9 +87,17 @@ impl Session {
             config,
             handle,
             task,
+            replay_of: None,
         }
     }
 
+    /// Simulated minutes, warmup included, a session of `config` would run
+    pub fn sim_minutes(config: &SessionConfig, scenario: Option<&ScenarioConfig>) -> f64 {
+        let scenario =
+            ControllableSimulation::build_scenario(&simulation_config(config, scenario.cloned()));
+        scenario.simulation.warmup_minutes + scenario.simulation.duration_minutes
+    }
+
     /// Touch the session (update last activity time)
     pub fn touch(&mut self) {
         self.last_activity = Instant::now();
@@ -64,6 +113,11 @@ impl Session {
         self.handle.map_data()
     }
 
+    /// Nodes of each human zone of the scenario, by zone id
+    pub fn zones(&self) -> &HashMap<String, Vec<u32>> {
+        self.handle.zones()
+    }
+
     /// Subscribe to simulation updates
     pub fn subscribe(&self) -> broadcast::Receiver<SimUpdate> {
         self.handle.subscribe()
@@ -113,6 +167,29 @@ impl Session {
             .map_err(|e| e.to_string())
     }
 
+    /// Apply a live intervention
+    pub async fn intervene(&self, intervention: Intervention) -> Result<(), String> {
+        self.handle
+            .intervene(intervention)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Interventions applied so far
+    pub fn interventions(&self) -> Vec<InterventionRecord> {
+        self.handle.interventions()
+    }
+
+    /// Status of the attached external controller, if any
+    pub fn controller_status(&self) -> Option<ControllerStatus> {
+        self.handle.controller_status()
+    }
+
+    /// State the simulation last sent to clients
+    pub fn latest_state(&self) -> Option<SimulationState> {
+        self.handle.latest_state()
+    }
+
     /// Stop the simulation
     pub async fn stop(&self) -> Result<(), String> {
         self.handle.stop().await.map_err(|e| e.to_string())
@@ -124,11 +201,19 @@ impl Session {
     }
 }
 
+/// A session and the tenant that created it. The owner is kept outside the
+/// session's lock so limits and ownership checks never wait on a session.
+struct SessionEntry {
+    owner: Option<String>,
+    session: Arc<Mutex<Session>>,
+}
+
 /// Session manager that handles multiple concurrent sessions
 pub struct SessionManager {
-    sessions: RwLock<HashMap<String, Arc<Mutex<Session>>>>,
+    sessions: RwLock<HashMap<String, SessionEntry>>,
     session_timeout: Duration,
     max_sessions: usize,
+    history: Option<Arc<RunHistory>>,
 }
 
 impl SessionManager {
@@ -138,35 +223,111 @@ impl SessionManager {
             sessions: RwLock::new(HashMap::new()),
             session_timeout,
             max_sessions,
+            history: None,
         }
     }
 
-    /// Create a new session
-    pub async fn create_session(&self, config: SessionConfig) -> Result<String, String> {
+    /// Record every new session in `history`
+    pub fn with_history(mut self, history: Arc<RunHistory>) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// The run history, if sessions are recorded
+    pub fn history(&self) -> Option<&Arc<RunHistory>> {
+        self.history.as_ref()
+    }
+
+    /// Create a new session for `caller`, running `scenario` if given and the
+    /// preset otherwise. The session counts against the caller's session
+    /// limit and is charged to its simulated-minute quota.
+    pub async fn create_session(
+        &self,
+        config: SessionConfig,
+        scenario: Option<ScenarioConfig>,
+        caller: &Caller,
+    ) -> Result<String, SessionError> {
         let mut sessions = self.sessions.write().await;
-        if sessions.len() >= self.max_sessions {
-            return Err("Maximum number of sessions reached".to_string());
-        }
+        self.admit(
+            &sessions,
+            caller,
+            Session::sim_minutes(&config, scenario.as_ref()),
+        )?;
 
-        let session = Session::new(config);
+        let session = Session::new(config, scenario, self.history.as_ref(), caller.name());
         let id = session.id.clone();
-        sessions.insert(id.clone(), Arc::new(Mutex::new(session)));
+        sessions.insert(id.clone(), SessionEntry::new(session, caller));
 
         Ok(id)
     }
 
+    /// Re-open a recorded run as a replay session: its scenario and seed
+    /// with its interventions re-applied where they happened. The replay
+    /// is not recorded itself.
+    pub async fn create_replay_session(
+        &self,
+        run: &RunDetail,
+        caller: &Caller,
+    ) -> Result<String, SessionError> {
+        let mut sessions = self.sessions.write().await;
+        let config = SessionConfig {
+            interventions: run.intervention_log.clone(),
+            ..Default::default()
+        };
+        self.admit(
+            &sessions,
+            caller,
+            Session::sim_minutes(&config, Some(&run.scenario)),
+        )?;
+
+        let mut session = Session::new(config, Some(run.scenario.clone()), None, None);
+        session.replay_of = Some(run.record.id.clone());
+        let id = session.id.clone();
+        sessions.insert(id.clone(), SessionEntry::new(session, caller));
+
+        Ok(id)
+    }
+
+    /// Check the session limits and charge `sim_minutes` to `caller`
+    fn admit(
+        &self,
+        sessions: &HashMap<String, SessionEntry>,
+        caller: &Caller,
+        sim_minutes: f64,
+    ) -> Result<(), SessionError> {
+        if sessions.len() >= self.max_sessions {
+            return Err(SessionError::Full);
+        }
+        if let Some(name) = caller.name() {
+            let owned = sessions
+                .values()
+                .filter(|entry| entry.owner.as_deref() == Some(name))
+                .count();
+            caller.check_sessions(owned)?;
+        }
+        caller.charge(sim_minutes)?;
+        Ok(())
+    }
+
     /// Get a session by ID
     pub async fn get_session(&self, id: &str) -> Option<Arc<Mutex<Session>>> {
         let sessions = self.sessions.read().await;
-        sessions.get(id).cloned()
+        sessions.get(id).map(|entry| entry.session.clone())
+    }
+
+    /// API key name of the tenant that created session `id`. `None` if
+    /// there is no such session.
+    pub async fn session_owner(&self, id: &str) -> Option<Option<String>> {
+        let sessions = self.sessions.read().await;
+        sessions.get(id).map(|entry| entry.owner.clone())
     }
 
     /// Remove a session
     pub async fn remove_session(&self, id: &str) -> bool {
         let mut sessions = self.sessions.write().await;
-        if let Some(session) = sessions.remove(id) {
+        if let Some(entry) = sessions.remove(id) {
             // Stop the simulation task
-            let session = session.lock().await;
+            let session = entry.session.lock().await;
             let _ = session.stop().await;
             session.task.abort();
             true
@@ -180,9 +341,9 @@ impl SessionManager {
         let mut sessions = self.sessions.write().await;
         let expired: Vec<String> = sessions
             .iter()
-            .filter_map(|(id, session)| {
+            .filter_map(|(id, entry)| {
                 // Try to lock without blocking
-                if let Ok(s) = session.try_lock() {
+                if let Ok(s) = entry.session.try_lock() {
                     if s.is_expired(self.session_timeout) {
                         return Some(id.clone());
                     }
@@ -192,8 +353,8 @@ impl SessionManager {
             .collect();
 
         for id in expired {
-            if let Some(session) = sessions.remove(&id) {
-                if let Ok(s) = session.try_lock() {
+            if let Some(entry) = sessions.remove(&id) {
+                if let Ok(s) = entry.session.try_lock() {
                     let _ = s.handle.stop().await;
                     s.task.abort();
                 }
@@ -212,6 +373,34 @@ impl SessionManager {
         let sessions = self.sessions.read().await;
         sessions.keys().cloned().collect()
     }
+
+    /// Latest state of every session that has started, by session ID
+    pub async fn session_states(&self) -> Vec<(String, SimulationState)> {
+        let sessions: Vec<_> = self
+            .sessions
+            .read()
+            .await
+            .values()
+            .map(|entry| entry.session.clone())
+            .collect();
+        let mut states = Vec::with_capacity(sessions.len());
+        for session in sessions {
+            let session = session.lock().await;
+            if let Some(state) = session.latest_state() {
+                states.push((session.id.clone(), state));
+            }
+        }
+        states
+    }
+}
+
+impl SessionEntry {
+    fn new(session: Session, caller: &Caller) -> Self {
+        Self {
+            owner: caller.name().map(str::to_string),
+            session: Arc::new(Mutex::new(session)),
+        }
+    }
 }
 
 impl Default for SessionManager {
@@ -223,6 +412,21 @@ impl Default for SessionManager {
     }
 }
 
+/// Simulation settings of a session of `config`
+fn simulation_config(config: &SessionConfig, scenario: Option<ScenarioConfig>) -> SimulationConfig {
+    SimulationConfig {
+        preset: config.preset.clone(),
+        robot_count: config.robot_count,
+        order_rate: config.order_rate,
+        duration_minutes: config.duration_minutes.unwrap_or(60.0),
+        grid_rows: config.grid_rows,
+        grid_cols: config.grid_cols,
+        scenario,
+        interventions: config.interventions.clone(),
+        recording: None,
+    }
+}
+
 /// Start a background task to periodically clean up expired sessions
 pub fn start_cleanup_task(manager: Arc<SessionManager>) -> tokio::task::JoinHandle<()> {
     tokio::spawn(async move {
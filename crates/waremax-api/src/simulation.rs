@@ -3,17 +3,37 @@
 //! Provides a simulation runner that can be paused, resumed, and speed-controlled
 //! for interactive visualization.
 
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tokio::sync::{broadcast, mpsc};
 
-use waremax_config::ScenarioConfig;
-use waremax_core::{Kernel, NodeId, RobotId, ScheduledEvent, SimEvent, SimTime};
+use waremax_config::{FieldPath, ScenarioConfig, ValidationError, ValidationErrorKind};
+use waremax_core::{EdgeId, Kernel, NodeId, RobotId, ScheduledEvent, SimEvent, SimTime, StationId};
 use waremax_entities::Robot;
-use waremax_metrics::MetricsCollector;
-use waremax_sim::{EventHandler, World};
+use waremax_metrics::{EventLogWriter, MetricsCollector};
+use waremax_sim::{
+    add_humans, apply_labor_model, create_distributions, create_station_assignment,
+    create_task_allocation, create_traffic_policy, enable_slotting, EventHandler, SimulationRunner,
+    World,
+};
 use waremax_testing::ScenarioBuilder;
 
-use crate::types::{MetricsSnapshot, RobotState, SimulationState, SimulationStatus, StationState};
+use crate::controller::{ControllerLink, RemoteAllocationPolicy, RemoteStationPolicy};
+use crate::history::RunHistory;
+use crate::types::{
+    ControllerRole, ControllerServerMessage, ControllerStatus, Intervention, InterventionRecord,
+    MetricsSnapshot, RobotState, SimulationState, SimulationStatus, StationState,
+};
+
+/// Events processed by every simulation of the process
+static EVENTS_PROCESSED: AtomicU64 = AtomicU64::new(0);
+
+/// Events processed by every simulation of the process so far
+pub fn events_processed_total() -> u64 {
+    EVENTS_PROCESSED.load(Ordering::Relaxed)
+}
 
 /// Control commands sent to the simulation task
 #[derive(Clone, Debug)]
@@ -22,7 +42,18 @@ pub enum SimCommand {
     Resume,
     SetSpeed(f64),
     Step,
-    AddRobot { node_id: Option<u32> },
+    AddRobot {
+        node_id: Option<u32>,
+    },
+    Intervene(Intervention),
+    /// Hand the controller's roles to a remote process
+    AttachController {
+        link: Arc<ControllerLink>,
+        fallback_allocation: Option<String>,
+        fallback_station: Option<String>,
+    },
+    /// Give the roles back to the session's policies, if `link` still has them
+    DetachController(Arc<ControllerLink>),
     Stop,
     GetState,
 }
@@ -61,6 +92,7 @@ pub enum SimUpdate {
         on_time: bool,
     },
     MetricsUpdate(MetricsSnapshot),
+    Intervention(InterventionRecord),
     Finished(MetricsSnapshot),
     Error(String),
 }
@@ -74,6 +106,21 @@ pub struct SimulationConfig {
     pub duration_minutes: f64,
     pub grid_rows: Option<u32>,
     pub grid_cols: Option<u32>,
+    /// Full scenario to run instead of the preset (already validated)
+    pub scenario: Option<ScenarioConfig>,
+    /// Recorded interventions to re-apply at their event positions
+    pub interventions: Vec<InterventionRecord>,
+    /// Run history to record this run in
+    pub recording: Option<RunRecording>,
+}
+
+/// Where a session's run is recorded
+#[derive(Clone, Debug)]
+pub struct RunRecording {
+    pub history: Arc<RunHistory>,
+    pub run_id: String,
+    /// Also save the run's event log
+    pub event_log: bool,
 }
 
 impl Default for SimulationConfig {
@@ -85,6 +132,9 @@ impl Default for SimulationConfig {
             duration_minutes: 60.0,
             grid_rows: None,
             grid_cols: None,
+            scenario: None,
+            interventions: Vec::new(),
+            recording: None,
         }
     }
 }
@@ -97,6 +147,22 @@ pub struct ControllableSimulation {
     metrics: MetricsCollector,
     end_time: SimTime,
     warmup_time: SimTime,
+    /// Scenario in effect, updated by interventions
+    scenario: ScenarioConfig,
+
+    // Interventions: pending replays and the log of applied ones
+    replay: VecDeque<InterventionRecord>,
+    intervention_log: Arc<Mutex<Vec<InterventionRecord>>>,
+
+    /// Last state sent to clients, for scraping
+    latest_state: Arc<Mutex<Option<SimulationState>>>,
+
+    /// External controller currently making decisions, if any
+    controller: Arc<Mutex<Option<Arc<ControllerLink>>>>,
+
+    // Run history: where the outcome is saved, and the event log being written
+    recording: Option<RunRecording>,
+    event_log: Option<EventLogWriter>,
 
     // Control state
     paused: bool,
@@ -125,11 +191,33 @@ impl ControllableSimulation {
         let scenario = Self::build_scenario(config);
         let seed = scenario.seed;
 
-        let world = build_world_from_config(&scenario, seed);
+        let mut world = build_world_from_config(&scenario, seed);
 
         let end_time = SimTime::from_minutes(
             scenario.simulation.warmup_minutes + scenario.simulation.duration_minutes,
         );
+
+        let event_log = config
+            .recording
+            .as_ref()
+            .filter(|r| r.event_log)
+            .and_then(|r| {
+                let path = r.history.event_log_path(&r.run_id);
+                let writer = EventLogWriter::new(&path).and_then(|w| {
+                    w.set_simulation_info(end_time.as_seconds(), seed)
+                        .map(|_| w)
+                });
+                match writer {
+                    Ok(writer) => Some(writer),
+                    Err(e) => {
+                        tracing::warn!(run_id = %r.run_id, error = %e, "Failed to open event log");
+                        None
+                    }
+                }
+            });
+        if event_log.is_some() {
+            world.trace_collector.set_enabled(true);
+        }
         let warmup_time = SimTime::from_minutes(scenario.simulation.warmup_minutes);
 
         Self {
@@ -139,6 +227,13 @@ impl ControllableSimulation {
             metrics: MetricsCollector::new(),
             end_time,
             warmup_time,
+            scenario,
+            replay: config.interventions.iter().cloned().collect(),
+            intervention_log: Arc::new(Mutex::new(Vec::new())),
+            latest_state: Arc::new(Mutex::new(None)),
+            controller: Arc::new(Mutex::new(None)),
+            recording: config.recording.clone(),
+            event_log,
             paused: true,
             speed: 1.0,
             events_processed: 0,
@@ -153,7 +248,11 @@ impl ControllableSimulation {
     }
 
     /// Build scenario config from simulation config
-    fn build_scenario(config: &SimulationConfig) -> ScenarioConfig {
+    pub(crate) fn build_scenario(config: &SimulationConfig) -> ScenarioConfig {
+        if let Some(scenario) = &config.scenario {
+            return scenario.clone();
+        }
+
         let mut builder = match config.preset.as_str() {
             "small" => ScenarioBuilder::new()
                 .grid(5, 5)
@@ -184,7 +283,14 @@ impl ControllableSimulation {
 
         builder = builder.duration(config.duration_minutes).warmup(5.0);
 
-        builder.build()
+        // Spread the stations around the perimeter of the generated grid
+        let mut scenario = builder.build();
+        let grid_size = generated_grid_size(scenario.stations.len());
+        let nodes = perimeter_nodes(scenario.stations.len(), grid_size);
+        for (station, node) in scenario.stations.iter_mut().zip(nodes) {
+            station.node = node.to_string();
+        }
+        scenario
     }
 
     /// Initialize the simulation
@@ -200,6 +306,31 @@ impl ControllableSimulation {
             self.world.traffic.enter_node(robot.current_node, robot.id);
         }
 
+        // v7: Apply initial station staffing; each change schedules the next
+        for station_id in self.world.staffed_station_ids() {
+            self.kernel
+                .schedule_now(SimEvent::StationStaffingChange { station_id });
+        }
+
+        // v7: Start human workers and apply human-only zones
+        if !self.world.humans.is_empty() || !self.world.human_zones.is_empty() {
+            self.kernel.schedule_now(SimEvent::HumanZoneUpdate);
+        }
+        for human_id in self.world.human_ids() {
+            waremax_sim::handlers::schedule_next_human_task(
+                &mut self.kernel,
+                &mut self.world,
+                human_id,
+            );
+        }
+
+        // v7: First re-slotting review; each review schedules the next
+        if let Some(slotting) = &self.world.slotting {
+            let review_time = SimTime::from_seconds(slotting.interval_s);
+            self.kernel
+                .schedule_after(review_time, SimEvent::SlottingReview);
+        }
+
         // Schedule first metrics sample tick
         if self.world.metrics_sample_interval_s > 0.0 {
             let sample_time = SimTime::from_seconds(self.world.metrics_sample_interval_s);
@@ -213,9 +344,7 @@ impl ControllableSimulation {
         self.initialize();
 
         // Send initial state
-        let _ = self
-            .update_tx
-            .send(SimUpdate::StateChanged(self.get_state()));
+        self.publish_state();
 
         // Track wall-clock time for proper pacing
         let mut last_frame = Instant::now();
@@ -229,6 +358,7 @@ impl ControllableSimulation {
                 match self.command_rx.try_recv() {
                     Ok(cmd) => {
                         if !self.handle_command(cmd) {
+                            self.save_stopped_run();
                             return;
                         }
                         // Reset time anchors on resume
@@ -238,16 +368,17 @@ impl ControllableSimulation {
                         }
                     }
                     Err(mpsc::error::TryRecvError::Empty) => break,
-                    Err(mpsc::error::TryRecvError::Disconnected) => return,
+                    Err(mpsc::error::TryRecvError::Disconnected) => {
+                        self.save_stopped_run();
+                        return;
+                    }
                 }
             }
 
             if self.paused {
                 // When paused, send state updates less frequently
                 if self.last_update.elapsed() >= Duration::from_millis(500) {
-                    let _ = self
-                        .update_tx
-                        .send(SimUpdate::StateChanged(self.get_state()));
+                    self.publish_state();
                     self.last_update = Instant::now();
                 }
                 tokio::time::sleep(Duration::from_millis(50)).await;
@@ -257,10 +388,11 @@ impl ControllableSimulation {
             // Check if simulation is finished
             if !self.kernel.has_events() || self.kernel.now() >= self.end_time {
                 let final_metrics = self.compute_metrics();
+                self.publish_state();
                 let _ = self
                     .update_tx
-                    .send(SimUpdate::StateChanged(self.get_state()));
-                let _ = self.update_tx.send(SimUpdate::Finished(final_metrics));
+                    .send(SimUpdate::Finished(final_metrics.clone()));
+                self.save_finished_run(final_metrics);
                 break;
             }
 
@@ -285,6 +417,7 @@ impl ControllableSimulation {
                     }
                 }
 
+                self.apply_due_interventions();
                 if let Some(event) = self.kernel.pop_next() {
                     // Record metrics after warmup
                     if self.kernel.now() >= self.warmup_time {
@@ -295,23 +428,18 @@ impl ControllableSimulation {
                     self.track_event_for_ui(&event);
 
                     // Handle the event
-                    self.handler.handle(
-                        &mut self.kernel,
-                        &mut self.world,
-                        &event,
-                        &mut self.metrics,
-                    );
+                    self.handle_event(&event);
 
                     self.events_processed += 1;
+                    EVENTS_PROCESSED.fetch_add(1, Ordering::Relaxed);
                     events_this_frame += 1;
                 }
             }
+            self.write_event_log();
 
             // Send state updates at frame rate
             if self.last_update.elapsed() >= self.update_interval {
-                let _ = self
-                    .update_tx
-                    .send(SimUpdate::StateChanged(self.get_state()));
+                self.publish_state();
                 self.last_update = Instant::now();
             }
 
@@ -329,15 +457,11 @@ impl ControllableSimulation {
         match cmd {
             SimCommand::Pause => {
                 self.paused = true;
-                let _ = self
-                    .update_tx
-                    .send(SimUpdate::StateChanged(self.get_state()));
+                self.publish_state();
             }
             SimCommand::Resume => {
                 self.paused = false;
-                let _ = self
-                    .update_tx
-                    .send(SimUpdate::StateChanged(self.get_state()));
+                self.publish_state();
             }
             SimCommand::SetSpeed(speed) => {
                 // Recalibrate time anchors before changing speed to avoid
@@ -345,39 +469,41 @@ impl ControllableSimulation {
                 self.sim_time_anchor = self.kernel.now().as_seconds();
                 self.wall_time_anchor = Instant::now();
                 self.speed = speed.clamp(0.1, 100.0);
-                let _ = self
-                    .update_tx
-                    .send(SimUpdate::StateChanged(self.get_state()));
+                self.publish_state();
             }
             SimCommand::Step => {
                 // Process exactly one event
+                self.apply_due_interventions();
                 if let Some(event) = self.kernel.pop_next() {
                     if self.kernel.now() >= self.warmup_time {
                         self.metrics.record_event(&event);
                     }
                     self.track_event_for_ui(&event);
-                    self.handler.handle(
-                        &mut self.kernel,
-                        &mut self.world,
-                        &event,
-                        &mut self.metrics,
-                    );
+                    self.handle_event(&event);
                     self.events_processed += 1;
+                    EVENTS_PROCESSED.fetch_add(1, Ordering::Relaxed);
                 }
-                let _ = self
-                    .update_tx
-                    .send(SimUpdate::StateChanged(self.get_state()));
+                self.write_event_log();
+                self.publish_state();
             }
             SimCommand::AddRobot { node_id } => {
-                self.add_robot(node_id);
-                let _ = self
-                    .update_tx
-                    .send(SimUpdate::StateChanged(self.get_state()));
+                self.intervene(Intervention::AddRobot { node_id });
+            }
+            SimCommand::Intervene(intervention) => {
+                self.intervene(intervention);
+            }
+            SimCommand::AttachController {
+                link,
+                fallback_allocation,
+                fallback_station,
+            } => {
+                self.attach_controller(link, fallback_allocation, fallback_station);
+            }
+            SimCommand::DetachController(link) => {
+                self.detach_controller(&link);
             }
             SimCommand::GetState => {
-                let _ = self
-                    .update_tx
-                    .send(SimUpdate::StateChanged(self.get_state()));
+                self.publish_state();
             }
             SimCommand::Stop => {
                 return false;
@@ -386,6 +512,177 @@ impl ControllableSimulation {
         true
     }
 
+    /// Run the event handler. With a controller attached, policies may block
+    /// for a remote reply; `ControllerLink` hands the worker over to blocking
+    /// while it waits.
+    fn handle_event(&mut self, event: &ScheduledEvent) {
+        self.handler
+            .handle(&mut self.kernel, &mut self.world, event, &mut self.metrics);
+    }
+
+    fn current_controller(&self) -> Option<Arc<ControllerLink>> {
+        self.controller.lock().ok().and_then(|c| c.clone())
+    }
+
+    /// Wrap the session's policies so `link` makes the decisions of its roles
+    fn attach_controller(
+        &mut self,
+        link: Arc<ControllerLink>,
+        fallback_allocation: Option<String>,
+        fallback_station: Option<String>,
+    ) {
+        if self
+            .current_controller()
+            .is_some_and(|current| current.is_connected())
+        {
+            link.send(ControllerServerMessage::Error {
+                message: "A controller is already registered for this session".to_string(),
+            });
+            return;
+        }
+
+        let mut config = self.scenario.policies.clone();
+        if let Some(name) = &fallback_allocation {
+            config.task_allocation.alloc_type = name.clone();
+        }
+        if let Some(name) = &fallback_station {
+            config.station_assignment.assign_type = name.clone();
+        }
+        let allocation = match create_task_allocation(&config) {
+            Ok(allocation) => allocation,
+            Err(message) => {
+                link.send(ControllerServerMessage::Error { message });
+                return;
+            }
+        };
+        let station = create_station_assignment(&config);
+        let unknown = match (&fallback_allocation, &fallback_station) {
+            (Some(name), _) if allocation.name() != name => {
+                Some(format!("Unknown task allocation policy '{}'", name))
+            }
+            (_, Some(name)) if station.name() != name => {
+                Some(format!("Unknown station assignment policy '{}'", name))
+            }
+            _ => None,
+        };
+        if let Some(message) = unknown {
+            link.send(ControllerServerMessage::Error { message });
+            return;
+        }
+
+        if link.has_role(ControllerRole::TaskAllocation) {
+            self.world.policies.task_allocation =
+                Box::new(RemoteAllocationPolicy::new(link.clone(), allocation));
+        }
+        if link.has_role(ControllerRole::StationAssignment) {
+            self.world.policies.station_assignment =
+                Box::new(RemoteStationPolicy::new(link.clone(), station));
+        }
+        link.send(ControllerServerMessage::Registered {
+            roles: link.roles().to_vec(),
+            timeout_ms: link.timeout_ms(),
+        });
+        if let Ok(mut controller) = self.controller.lock() {
+            *controller = Some(link);
+        }
+    }
+
+    /// Restore the scenario's own policies when `link` goes away
+    fn detach_controller(&mut self, link: &Arc<ControllerLink>) {
+        let Some(current) = self.current_controller() else {
+            return;
+        };
+        if !Arc::ptr_eq(&current, link) {
+            return;
+        }
+        current.disconnect();
+        if current.has_role(ControllerRole::TaskAllocation) {
+            match create_task_allocation(&self.scenario.policies) {
+                Ok(allocation) => self.world.policies.task_allocation = allocation,
+                // The disconnected controller's fallback keeps deciding
+                Err(e) => tracing::warn!(error = %e, "Could not restore task allocation"),
+            }
+        }
+        if current.has_role(ControllerRole::StationAssignment) {
+            self.world.policies.station_assignment =
+                create_station_assignment(&self.scenario.policies);
+        }
+        if let Ok(mut controller) = self.controller.lock() {
+            *controller = None;
+        }
+    }
+
+    /// Move traces collected since the last call into the event log
+    fn write_event_log(&mut self) {
+        let Some(writer) = &mut self.event_log else {
+            return;
+        };
+        let written = self
+            .world
+            .trace_collector
+            .entries()
+            .iter()
+            .try_for_each(|entry| {
+                writer.record(entry.timestamp, &entry.event_type, entry.details.clone())
+            });
+        self.world.trace_collector.clear();
+        if let Err(e) = written {
+            tracing::warn!(error = %e, "Failed to write event log; disabling it");
+            self.event_log = None;
+        }
+    }
+
+    fn recorded_interventions(&self) -> Vec<InterventionRecord> {
+        self.intervention_log
+            .lock()
+            .map(|log| log.clone())
+            .unwrap_or_default()
+    }
+
+    /// Save the report and intervention log of a run that reached its end
+    fn save_finished_run(mut self, final_metrics: MetricsSnapshot) {
+        let Some(recording) = self.recording.take() else {
+            return;
+        };
+        self.write_event_log();
+        if let Some(writer) = self.event_log.take() {
+            let _ = writer.flush();
+        }
+
+        let interventions = self.recorded_interventions();
+        let report = SimulationRunner::from_parts(
+            self.kernel,
+            self.world,
+            self.metrics,
+            self.end_time,
+            self.warmup_time,
+        )
+        .generate_full_report(true, true, true, false);
+        if let Err(e) =
+            recording
+                .history
+                .finish(&recording.run_id, &interventions, final_metrics, &report)
+        {
+            tracing::warn!(run_id = %recording.run_id, error = %e, "Failed to save run");
+        }
+    }
+
+    /// Record that the run ended before its end time
+    fn save_stopped_run(&mut self) {
+        let Some(recording) = self.recording.take() else {
+            return;
+        };
+        self.write_event_log();
+        // Close the event log first so it can be read once the run is stopped
+        if let Some(writer) = self.event_log.take() {
+            let _ = writer.flush();
+        }
+        let interventions = self.recorded_interventions();
+        if let Err(e) = recording.history.stop(&recording.run_id, &interventions) {
+            tracing::warn!(run_id = %recording.run_id, error = %e, "Failed to save run");
+        }
+    }
+
     /// Track events for UI updates
     fn track_event_for_ui(&mut self, event: &ScheduledEvent) {
         match &event.event {
@@ -522,24 +819,222 @@ impl ControllableSimulation {
         }
     }
 
-    /// Add a new robot to the simulation
-    fn add_robot(&mut self, node_id: Option<u32>) {
-        let new_id = RobotId(self.world.robots.len() as u32);
-        let start_node = node_id.map(NodeId).unwrap_or_else(|| {
-            // Find a node that isn't too crowded
-            self.world
-                .map
-                .nodes
-                .keys()
-                .next()
-                .copied()
-                .unwrap_or(NodeId(0))
+    /// Apply recorded interventions whose turn has come (replay)
+    fn apply_due_interventions(&mut self) {
+        while self
+            .replay
+            .front()
+            .is_some_and(|r| r.at_event <= self.events_processed)
+        {
+            if let Some(record) = self.replay.pop_front() {
+                self.intervene(record.intervention);
+            }
+        }
+    }
+
+    /// Apply an intervention now, log it and announce it on the stream
+    fn intervene(&mut self, mut intervention: Intervention) {
+        match self.apply_intervention(&mut intervention) {
+            Ok(()) => {
+                let record = InterventionRecord {
+                    at_event: self.events_processed,
+                    time_s: self.kernel.now().as_seconds(),
+                    intervention,
+                };
+                if let Ok(mut log) = self.intervention_log.lock() {
+                    log.push(record.clone());
+                }
+                let _ = self.update_tx.send(SimUpdate::Intervention(record));
+                self.publish_state();
+            }
+            Err(message) => {
+                let _ = self.update_tx.send(SimUpdate::Error(message));
+            }
+        }
+    }
+
+    /// Change the world between two events. Choices made here (like where
+    /// a robot is added) are written back so the record replays exactly.
+    fn apply_intervention(&mut self, intervention: &mut Intervention) -> Result<(), String> {
+        match intervention {
+            Intervention::BlockEdge { edge_id } => self.set_edge_blocked(*edge_id, true),
+            Intervention::UnblockEdge { edge_id } => self.set_edge_blocked(*edge_id, false),
+            Intervention::BlockNode { node_id } => self.set_node_blocked(*node_id, true),
+            Intervention::UnblockNode { node_id } => self.set_node_blocked(*node_id, false),
+            Intervention::FailRobot { robot_id } => self.fail_robot(RobotId(*robot_id)),
+            Intervention::RemoveRobot { robot_id } => self.remove_robot(RobotId(*robot_id)),
+            Intervention::AddRobot { node_id } => {
+                *node_id = Some(self.add_robot(*node_id)?);
+                Ok(())
+            }
+            Intervention::OpenStation { station_id } => self.set_station_open(*station_id, true),
+            Intervention::CloseStation { station_id } => self.set_station_open(*station_id, false),
+            Intervention::SetOrderRate { orders_per_hour } => {
+                if !orders_per_hour.is_finite() || *orders_per_hour <= 0.0 {
+                    return Err(format!(
+                        "Order rate must be positive, got {}",
+                        orders_per_hour
+                    ));
+                }
+                // Takes effect from the next scheduled arrival
+                self.scenario.orders.arrival_process.rate_per_min = *orders_per_hour / 60.0;
+                self.world.distributions.arrivals =
+                    create_distributions(&self.scenario.orders).arrivals;
+                Ok(())
+            }
+            Intervention::SetTaskAllocation { policy } => {
+                let mut config = self.scenario.policies.clone();
+                config.task_allocation.alloc_type = policy.clone();
+                let allocation = create_task_allocation(&config)?;
+                if allocation.name() != policy {
+                    return Err(format!("Unknown task allocation policy '{}'", policy));
+                }
+                // An attached controller keeps deciding; the new policy
+                // becomes its fallback
+                self.world.policies.task_allocation = match self.current_controller() {
+                    Some(link) if link.has_role(ControllerRole::TaskAllocation) => {
+                        Box::new(RemoteAllocationPolicy::new(link, allocation))
+                    }
+                    _ => allocation,
+                };
+                self.scenario.policies = config;
+                Ok(())
+            }
+            Intervention::SetTrafficPolicy { policy } => {
+                let mut config = self.scenario.traffic.clone();
+                config.policy = policy.clone();
+                let traffic = create_traffic_policy(&config);
+                if traffic.name() != policy {
+                    return Err(format!("Unknown traffic policy '{}'", policy));
+                }
+                self.world.policies.traffic = traffic;
+                self.scenario.traffic = config;
+                Ok(())
+            }
+        }
+    }
+
+    /// Close or reopen an edge in both directions
+    fn set_edge_blocked(&mut self, edge_id: u32, blocked: bool) -> Result<(), String> {
+        let edge = EdgeId(edge_id);
+        if self.world.map.get_edge(edge).is_none() {
+            return Err(format!("Unknown edge {}", edge_id));
+        }
+        self.world.map.set_edge_blocked(edge, blocked);
+        if let Some(reverse) = self.world.map.reverse_edge(edge) {
+            self.world.map.set_edge_blocked(reverse, blocked);
+        }
+        self.world.router.invalidate_cache();
+        Ok(())
+    }
+
+    fn set_node_blocked(&mut self, node_id: u32, blocked: bool) -> Result<(), String> {
+        let node = NodeId(node_id);
+        if self.world.map.get_node(node).is_none() {
+            return Err(format!("Unknown node {}", node_id));
+        }
+        self.world.map.set_node_blocked(node, blocked);
+        self.world.router.invalidate_cache();
+        Ok(())
+    }
+
+    /// Fail a robot: it stops at the next node of its path and its task is
+    /// requeued
+    fn fail_robot(&mut self, robot_id: RobotId) -> Result<(), String> {
+        let robot = self
+            .world
+            .get_robot_mut(robot_id)
+            .ok_or_else(|| format!("Unknown robot {}", robot_id.0))?;
+        if robot.is_failed() {
+            return Err(format!("Robot {} has already failed", robot_id.0));
+        }
+        let stop_at = (robot.path_index + 2).min(robot.current_path.len());
+        robot.current_path.truncate(stop_at);
+        let interrupted_task = robot.current_task;
+        self.kernel.schedule_now(SimEvent::RobotFailure {
+            robot_id,
+            interrupted_task,
         });
+        Ok(())
+    }
+
+    /// Remove a robot that holds no work. One about to leave for a parking
+    /// spot has that trip called off first.
+    fn remove_robot(&mut self, robot_id: RobotId) -> Result<(), String> {
+        let robot = self
+            .world
+            .get_robot(robot_id)
+            .ok_or_else(|| format!("Unknown robot {}", robot_id.0))?;
+        let free = matches!(
+            robot.state,
+            waremax_entities::RobotState::Idle | waremax_entities::RobotState::Failed
+        ) && robot.current_task.is_none()
+            && robot.task_queue.is_empty();
+        if !free {
+            return Err(format!(
+                "Robot {} is busy; only idle or failed robots can be removed",
+                robot_id.0
+            ));
+        }
+        let node = robot.current_node;
+        let now = self.kernel.now();
+        self.handler
+            .cancel_repositioning(&mut self.kernel, &mut self.world, now, robot_id);
+        self.world
+            .awaiting_idle_positioning
+            .retain(|&id| id != robot_id);
+        self.world.reservation_manager.release_all(robot_id);
+        self.world.traffic.clear_wait(robot_id);
+        self.world.traffic.leave_node(node, robot_id);
+        self.world.robots.remove(&robot_id);
+        Ok(())
+    }
+
+    /// Open or close a station the way a staffing change does: robots queued
+    /// at a reopened station are served, and pending tasks are redispatched
+    fn set_station_open(&mut self, station_id: u32, open: bool) -> Result<(), String> {
+        let station_id = StationId(station_id);
+        let station = self
+            .world
+            .stations
+            .get_mut(&station_id)
+            .ok_or_else(|| format!("Unknown station {}", station_id.0))?;
+        station.open = open;
+        self.handler
+            .start_queued_service(&mut self.kernel, &mut self.world, station_id);
+        self.kernel.schedule_now(SimEvent::DispatchTasks);
+        Ok(())
+    }
+
+    /// Add a new robot to the simulation, at the lowest-numbered node when
+    /// none is given. Returns the node it was placed on.
+    fn add_robot(&mut self, node_id: Option<u32>) -> Result<u32, String> {
+        if let Some(id) = node_id {
+            if self.world.map.get_node(NodeId(id)).is_none() {
+                return Err(format!("Unknown node {}", id));
+            }
+        }
+        // Ids stay unique after removals
+        let new_id = RobotId(self.world.robots.keys().map(|r| r.0 + 1).max().unwrap_or(0));
+        let start_node = node_id
+            .map(NodeId)
+            .or_else(|| self.world.map.nodes.keys().map(|n| n.0).min().map(NodeId))
+            .ok_or("The map has no nodes")?;
 
         let mut robot = Robot::new(new_id, start_node, 1.5, 25.0);
         robot.last_state_change = self.kernel.now();
         self.world.traffic.enter_node(start_node, new_id);
         self.world.robots.insert(new_id, robot);
+        Ok(start_node.0)
+    }
+
+    /// Send the current state to clients, keeping it for scraping
+    fn publish_state(&self) {
+        let state = self.get_state();
+        if let Ok(mut latest) = self.latest_state.lock() {
+            *latest = Some(state.clone());
+        }
+        let _ = self.update_tx.send(SimUpdate::StateChanged(state));
     }
 
     /// Get current simulation state
@@ -552,51 +1047,14 @@ impl ControllableSimulation {
             SimulationStatus::Running
         };
 
-        let robots: Vec<RobotState> = self
-            .world
-            .robots
-            .values()
-            .map(|r| RobotState {
-                id: r.id.0,
-                node_id: r.current_node.0,
-                state: format!("{:?}", r.state)
-                    .split_whitespace()
-                    .next()
-                    .unwrap_or("Unknown")
-                    .to_string(),
-                battery_soc: if r.battery.capacity_wh > 0.0 {
-                    Some(r.battery.soc)
-                } else {
-                    None
-                },
-                current_task: r.current_task.map(|t| t.0),
-                is_failed: r.maintenance.is_failed,
-            })
-            .collect();
-
-        let stations: Vec<StationState> = self
-            .world
-            .stations
-            .values()
-            .map(|s| StationState {
-                id: s.id.0,
-                name: s.string_id.clone(),
-                node_id: s.node.0,
-                station_type: format!("{:?}", s.station_type),
-                queue_length: s.queue.len(),
-                serving_count: s.serving.len(),
-                concurrency: s.concurrency,
-            })
-            .collect();
-
         SimulationState {
             status,
             time_s: self.kernel.now().as_seconds(),
             speed: self.speed,
             events_processed: self.events_processed,
             orders_completed: self.orders_completed,
-            robots,
-            stations,
+            robots: robot_states(&self.world),
+            stations: station_states(&self.world),
             metrics: self.compute_metrics(),
         }
     }
@@ -667,64 +1125,254 @@ impl ControllableSimulation {
 
     /// Get the world map data for frontend rendering
     pub fn get_map_data(&self) -> crate::types::MapData {
-        use crate::types::{EdgeData, MapBounds, MapData, NodeData};
+        map_data(&self.world)
+    }
 
-        let mut min_x = f64::MAX;
-        let mut max_x = f64::MIN;
-        let mut min_y = f64::MAX;
-        let mut max_y = f64::MIN;
+    /// Nodes of each human zone, by zone id
+    pub fn zone_nodes(&self) -> HashMap<String, Vec<u32>> {
+        self.world
+            .human_zones
+            .iter()
+            .map(|zone| (zone.id.clone(), zone.nodes.iter().map(|n| n.0).collect()))
+            .collect()
+    }
+}
 
-        let nodes: Vec<NodeData> = self
-            .world
-            .map
-            .nodes
-            .values()
-            .map(|n| {
-                min_x = min_x.min(n.x);
-                max_x = max_x.max(n.x);
-                min_y = min_y.min(n.y);
-                max_y = max_y.max(n.y);
-
-                NodeData {
-                    id: n.id.0,
-                    name: n.string_id.clone(),
-                    x: n.x,
-                    y: n.y,
-                    node_type: format!("{:?}", n.node_type),
-                }
-            })
-            .collect();
+/// A scenario's map, robots and stations before it runs
+pub(crate) struct ScenarioLayout {
+    pub map: crate::types::MapData,
+    pub robots: Vec<RobotState>,
+    pub stations: Vec<StationState>,
+}
 
-        let edges: Vec<EdgeData> = self
-            .world
-            .map
-            .edges
-            .values()
-            .map(|e| EdgeData {
-                id: e.id.0,
-                from: e.from.0,
-                to: e.to.0,
-                length: e.length_m,
-                bidirectional: matches!(e.direction, waremax_map::EdgeDirection::Bidirectional),
-            })
-            .collect();
+impl ScenarioLayout {
+    pub(crate) fn new(scenario: &ScenarioConfig) -> Self {
+        let world = build_world_from_config(scenario, scenario.seed);
+        Self {
+            map: map_data(&world),
+            robots: robot_states(&world),
+            stations: station_states(&world),
+        }
+    }
+}
+
+fn robot_states(world: &World) -> Vec<RobotState> {
+    world
+        .robots
+        .values()
+        .map(|r| RobotState {
+            id: r.id.0,
+            node_id: r.current_node.0,
+            state: format!("{:?}", r.state)
+                .split_whitespace()
+                .next()
+                .unwrap_or("Unknown")
+                .to_string(),
+            battery_soc: if r.battery.capacity_wh > 0.0 {
+                Some(r.battery.soc)
+            } else {
+                None
+            },
+            current_task: r.current_task.map(|t| t.0),
+            is_failed: r.maintenance.is_failed,
+        })
+        .collect()
+}
+
+fn station_states(world: &World) -> Vec<StationState> {
+    world
+        .stations
+        .values()
+        .map(|s| StationState {
+            id: s.id.0,
+            name: s.string_id.clone(),
+            node_id: s.node.0,
+            station_type: format!("{:?}", s.station_type),
+            queue_length: s.queue.len(),
+            serving_count: s.serving.len(),
+            concurrency: s.concurrency,
+        })
+        .collect()
+}
+
+fn map_data(world: &World) -> crate::types::MapData {
+    use crate::types::{EdgeData, MapBounds, MapData, NodeData};
+
+    let mut min_x = f64::MAX;
+    let mut max_x = f64::MIN;
+    let mut min_y = f64::MAX;
+    let mut max_y = f64::MIN;
+
+    let nodes: Vec<NodeData> = world
+        .map
+        .nodes
+        .values()
+        .map(|n| {
+            min_x = min_x.min(n.x);
+            max_x = max_x.max(n.x);
+            min_y = min_y.min(n.y);
+            max_y = max_y.max(n.y);
+
+            NodeData {
+                id: n.id.0,
+                name: n.string_id.clone(),
+                x: n.x,
+                y: n.y,
+                node_type: format!("{:?}", n.node_type),
+            }
+        })
+        .collect();
+
+    let edges: Vec<EdgeData> = world
+        .map
+        .edges
+        .values()
+        .map(|e| EdgeData {
+            id: e.id.0,
+            from: e.from.0,
+            to: e.to.0,
+            length: e.length_m,
+            bidirectional: matches!(e.direction, waremax_map::EdgeDirection::Bidirectional),
+        })
+        .collect();
+
+    // Add padding to bounds
+    let padding = 2.0;
+    MapData {
+        nodes,
+        edges,
+        bounds: MapBounds {
+            min_x: min_x - padding,
+            max_x: max_x + padding,
+            min_y: min_y - padding,
+            max_y: max_y + padding,
+        },
+    }
+}
+
+/// Side of the square grid generated for a scenario with `station_count`
+/// stations
+fn generated_grid_size(station_count: usize) -> usize {
+    match station_count {
+        0..=2 => 5,  // small preset
+        3..=5 => 10, // standard preset
+        _ => 15,     // large preset
+    }
+}
 
-        // Add padding to bounds
-        let padding = 2.0;
-        MapData {
-            nodes,
-            edges,
-            bounds: MapBounds {
-                min_x: min_x - padding,
-                max_x: max_x + padding,
-                min_y: min_y - padding,
-                max_y: max_y + padding,
+/// `count` nodes spread along the edges of a `grid_size` grid
+fn perimeter_nodes(count: usize, grid_size: usize) -> Vec<u32> {
+    (0..count)
+        .map(|i| {
+            let along = (i / 4 + 1) * grid_size / (count / 4 + 2);
+            let node = match i % 4 {
+                0 => along,                               // Top edge
+                1 => along * grid_size + grid_size - 1,   // Right edge
+                2 => (grid_size - 1) * grid_size + along, // Bottom edge
+                _ => along * grid_size,                   // Left edge
+            };
+            node as u32
+        })
+        .collect()
+}
+
+/// Check that `scenario` only names what the generated world provides.
+///
+/// Sessions run on a generated grid with demo inventory, so map and storage
+/// files can't be loaded, charging and maintenance stations aren't built,
+/// and every node a scenario names must be a node id of the grid.
+pub(crate) fn check_generated_world(scenario: &ScenarioConfig) -> Vec<ValidationError> {
+    let root = FieldPath::new("scenario");
+    let unsupported = |path: FieldPath, value: &str, reason: &str| {
+        ValidationError::new(
+            path.clone(),
+            ValidationErrorKind::Unsupported {
+                field: path.0,
+                value: value.to_string(),
+                reason: reason.to_string(),
             },
+        )
+    };
+    let mut errors = Vec::new();
+
+    if scenario.map.file != "inline" {
+        errors.push(
+            unsupported(
+                root.field("map").field("file"),
+                &scenario.map.file,
+                "the server generates the map",
+            )
+            .with_suggestion("Set map.file to \"inline\""),
+        );
+    }
+    if scenario.storage.file != "inline" {
+        errors.push(
+            unsupported(
+                root.field("storage").field("file"),
+                &scenario.storage.file,
+                "the server generates the inventory",
+            )
+            .with_suggestion("Set storage.file to \"inline\""),
+        );
+    }
+    for (i, station) in scenario.charging_stations.iter().enumerate() {
+        errors.push(unsupported(
+            root.field("charging_stations").index(i),
+            &station.id,
+            "the generated map has no charging stations",
+        ));
+    }
+    for (i, station) in scenario.maintenance_stations.iter().enumerate() {
+        errors.push(unsupported(
+            root.field("maintenance_stations").index(i),
+            &station.id,
+            "the generated map has no maintenance stations",
+        ));
+    }
+
+    let grid_size = generated_grid_size(scenario.stations.len());
+    let node_count = grid_size * grid_size;
+    let mut check_node = |path: FieldPath, node: &str| {
+        if node.parse::<usize>().map_or(true, |n| n >= node_count) {
+            errors.push(
+                ValidationError::new(
+                    path,
+                    ValidationErrorKind::NodeNotFound {
+                        node_id: node.to_string(),
+                    },
+                )
+                .with_suggestion(format!(
+                    "The generated {}x{} grid has nodes 0 to {}",
+                    grid_size,
+                    grid_size,
+                    node_count - 1
+                )),
+            );
+        }
+    };
+    for (i, station) in scenario.stations.iter().enumerate() {
+        check_node(root.field("stations").index(i).field("node"), &station.node);
+    }
+    for (i, human) in scenario.humans.iter().enumerate() {
+        check_node(
+            root.field("humans").index(i).field("home_node"),
+            &human.home_node,
+        );
+    }
+    for (i, zone) in scenario.human_zones.iter().enumerate() {
+        for (j, node) in zone.nodes.iter().enumerate() {
+            check_node(
+                root.field("human_zones").index(i).field("nodes").index(j),
+                node,
+            );
         }
     }
+
+    errors
 }
 
-/// Build a World from ScenarioConfig (simplified version for UI)
+/// Build the World a session runs: a generated grid holding the scenario's
+/// robots and stations. The scenario must pass [`check_generated_world`].
 fn build_world_from_config(scenario: &ScenarioConfig, seed: u64) -> World {
     use waremax_core::{EdgeId, NodeId, RobotId, StationId};
     use waremax_entities::{
@@ -735,46 +1383,17 @@ fn build_world_from_config(scenario: &ScenarioConfig, seed: u64) -> World {
 
     let mut world = World::new(seed);
 
-    // Determine grid size based on preset
-    let grid_rows = match scenario.stations.len() {
-        0..=2 => 5,  // small preset
-        3..=5 => 10, // standard preset
-        _ => 15,     // large preset
-    };
-    let grid_cols = grid_rows;
-    let grid_size = grid_rows;
+    let grid_size = generated_grid_size(scenario.stations.len());
     let spacing = 3.0;
 
-    // Calculate station positions - distribute them around the perimeter
-    let num_stations = scenario.stations.len();
-    let mut station_nodes: std::collections::HashSet<u32> = std::collections::HashSet::new();
-
-    for i in 0..num_stations {
-        // Place stations along the edges of the grid, evenly spaced
-        let node_id = match i % 4 {
-            0 => {
-                // Top edge
-                let col = (i / 4 + 1) * grid_cols / (num_stations / 4 + 2);
-                col as u32
-            }
-            1 => {
-                // Right edge
-                let row = (i / 4 + 1) * grid_rows / (num_stations / 4 + 2);
-                (row * grid_cols + grid_cols - 1) as u32
-            }
-            2 => {
-                // Bottom edge
-                let col = (i / 4 + 1) * grid_cols / (num_stations / 4 + 2);
-                ((grid_rows - 1) * grid_cols + col) as u32
-            }
-            _ => {
-                // Left edge
-                let row = (i / 4 + 1) * grid_rows / (num_stations / 4 + 2);
-                (row * grid_cols) as u32
-            }
-        };
-        station_nodes.insert(node_id);
-    }
+    let station_node = |station: &waremax_config::StationConfig| -> u32 {
+        station
+            .node
+            .parse()
+            .expect("station nodes are checked when the scenario is resolved")
+    };
+    let station_nodes: std::collections::HashSet<u32> =
+        scenario.stations.iter().map(station_node).collect();
 
     // Helper to determine if a position should be a rack (storage) node
     // Creates a warehouse-like layout with alternating rack/aisle rows
@@ -870,7 +1489,7 @@ fn build_world_from_config(scenario: &ScenarioConfig, seed: u64) -> World {
         while station_nodes.contains(&start_node) {
             start_node = (start_node + 1) % total_nodes;
         }
-        let robot = if scenario.robots.battery.enabled {
+        let mut robot = if scenario.robots.battery.enabled {
             Robot::with_battery(
                 RobotId(i),
                 NodeId(start_node),
@@ -893,11 +1512,19 @@ fn build_world_from_config(scenario: &ScenarioConfig, seed: u64) -> World {
                 scenario.robots.max_payload_kg,
             )
         };
+
+        // v3: Maintenance and random failures from full scenarios
+        if scenario.robots.maintenance.enabled {
+            robot.enable_maintenance(scenario.robots.maintenance.interval_hours * 3600.0);
+        }
+        if scenario.robots.failure.enabled {
+            robot.enable_failures(scenario.robots.failure.mtbf_hours * 3600.0);
+        }
+
         world.robots.insert(RobotId(i), robot);
     }
 
     // Add stations - use the distributed positions we calculated
-    let station_node_vec: Vec<u32> = station_nodes.iter().cloned().collect();
     for (idx, station_cfg) in scenario.stations.iter().enumerate() {
         let (station_type, node_type) = match station_cfg.station_type.as_str() {
             "pick" => (StationType::Pick, NodeType::StationPick),
@@ -920,12 +1547,7 @@ fn build_world_from_config(scenario: &ScenarioConfig, seed: u64) -> World {
             ),
         };
 
-        // Use our calculated distributed positions instead of config (which puts them adjacent)
-        let node_id = if idx < station_node_vec.len() {
-            station_node_vec[idx]
-        } else {
-            station_cfg.node.parse().unwrap_or(0)
-        };
+        let node_id = station_node(station_cfg);
 
         let station = Station::new(
             StationId(idx as u32),
@@ -936,6 +1558,7 @@ fn build_world_from_config(scenario: &ScenarioConfig, seed: u64) -> World {
             station_cfg.queue_capacity,
             service_time,
         );
+        let station = apply_labor_model(station, station_cfg);
         world.stations.insert(StationId(idx as u32), station);
 
         // Update the map node type to match the actual station type
@@ -952,7 +1575,8 @@ fn build_world_from_config(scenario: &ScenarioConfig, seed: u64) -> World {
 
     // Create policies
     world.policies =
-        waremax_sim::create_policies_with_traffic(&scenario.policies, &scenario.traffic);
+        waremax_sim::create_policies_with_traffic(&scenario.policies, &scenario.traffic)
+            .expect("policies are checked when the scenario is resolved");
 
     // Set metrics sample interval
     world.metrics_sample_interval_s = scenario.metrics.sample_interval_s;
@@ -962,6 +1586,18 @@ fn build_world_from_config(scenario: &ScenarioConfig, seed: u64) -> World {
     // Initialize inventory with more SKUs to spread across rack nodes
     world.init_demo_inventory(50);
 
+    // v7: Level access times for generated racks
+    world.set_rack_access_times(
+        scenario.storage.base_access_time_s,
+        scenario.storage.per_level_time_s,
+    );
+
+    // v7: Human workers and manual vehicles
+    add_humans(&mut world, scenario);
+
+    // v7: Dynamic re-slotting (baseline layout is the inventory placed above)
+    enable_slotting(&mut world, scenario);
+
     world
 }
 
@@ -970,6 +1606,10 @@ pub struct SimulationHandle {
     command_tx: mpsc::Sender<SimCommand>,
     update_rx: broadcast::Receiver<SimUpdate>,
     map_data: crate::types::MapData,
+    zones: HashMap<String, Vec<u32>>,
+    interventions: Arc<Mutex<Vec<InterventionRecord>>>,
+    controller: Arc<Mutex<Option<Arc<ControllerLink>>>>,
+    latest_state: Arc<Mutex<Option<SimulationState>>>,
 }
 
 impl SimulationHandle {
@@ -991,6 +1631,11 @@ impl SimulationHandle {
         &self.map_data
     }
 
+    /// Nodes of each human zone, by zone id (doesn't change either)
+    pub fn zones(&self) -> &HashMap<String, Vec<u32>> {
+        &self.zones
+    }
+
     /// Pause the simulation
     pub async fn pause(&self) -> Result<(), mpsc::error::SendError<SimCommand>> {
         self.send_command(SimCommand::Pause).await
@@ -1019,6 +1664,36 @@ impl SimulationHandle {
         self.send_command(SimCommand::AddRobot { node_id }).await
     }
 
+    /// Apply an intervention at the next event boundary
+    pub async fn intervene(
+        &self,
+        intervention: Intervention,
+    ) -> Result<(), mpsc::error::SendError<SimCommand>> {
+        self.send_command(SimCommand::Intervene(intervention)).await
+    }
+
+    /// Interventions applied so far, in order
+    pub fn interventions(&self) -> Vec<InterventionRecord> {
+        self.interventions
+            .lock()
+            .map(|log| log.clone())
+            .unwrap_or_default()
+    }
+
+    /// State last sent to clients (None before the simulation starts)
+    pub fn latest_state(&self) -> Option<SimulationState> {
+        self.latest_state
+            .lock()
+            .ok()
+            .and_then(|state| state.clone())
+    }
+
+    /// Status of the attached external controller, if any
+    pub fn controller_status(&self) -> Option<ControllerStatus> {
+        let controller = self.controller.lock().ok()?;
+        controller.as_ref().map(|link| link.status())
+    }
+
     /// Stop the simulation
     pub async fn stop(&self) -> Result<(), mpsc::error::SendError<SimCommand>> {
         self.send_command(SimCommand::Stop).await
@@ -1044,11 +1719,16 @@ pub fn spawn_simulation(
 
     let sim = ControllableSimulation::new(&config, command_rx, update_tx);
     let map_data = sim.get_map_data();
+    let zones = sim.zone_nodes();
 
     let handle = SimulationHandle {
         command_tx,
         update_rx,
         map_data,
+        zones,
+        interventions: sim.intervention_log.clone(),
+        controller: sim.controller.clone(),
+        latest_state: sim.latest_state.clone(),
     };
 
     // Spawn an outer task that monitors the inner simulation task for panics.
@@ -1082,3 +1762,330 @@ pub fn spawn_simulation(
 
     (handle, task)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use waremax_entities::RobotState as EntityState;
+
+    fn session(config: &SimulationConfig) -> ControllableSimulation {
+        let (_command_tx, command_rx) = mpsc::channel(8);
+        let (update_tx, _) = broadcast::channel(1024);
+        let mut sim = ControllableSimulation::new(config, command_rx, update_tx);
+        sim.initialize();
+        sim
+    }
+
+    fn advance(sim: &mut ControllableSimulation, events: u64) {
+        for _ in 0..events {
+            sim.handle_command(SimCommand::Step);
+        }
+    }
+
+    /// Advance until `done` holds, one event at a time
+    fn advance_until(
+        sim: &mut ControllableSimulation,
+        done: impl Fn(&ControllableSimulation) -> bool,
+    ) {
+        for _ in 0..50_000 {
+            if done(sim) {
+                return;
+            }
+            sim.handle_command(SimCommand::Step);
+        }
+        panic!("condition not reached");
+    }
+
+    /// Apply an intervention the way a client would, returning what was
+    /// recorded or the error sent back
+    fn apply(
+        sim: &mut ControllableSimulation,
+        intervention: Intervention,
+    ) -> Result<Intervention, String> {
+        let mut updates = sim.update_tx.subscribe();
+        sim.handle_command(SimCommand::Intervene(intervention));
+        while let Ok(update) = updates.try_recv() {
+            match update {
+                SimUpdate::Intervention(record) => return Ok(record.intervention),
+                SimUpdate::Error(message) => return Err(message),
+                _ => {}
+            }
+        }
+        panic!("intervention produced no update");
+    }
+
+    fn first_edge(sim: &ControllableSimulation) -> u32 {
+        sim.world.map.edges.keys().map(|e| e.0).min().unwrap()
+    }
+
+    #[test]
+    fn test_world_places_stations_at_their_nodes() {
+        // Presets spread their stations around the grid's perimeter
+        let preset = ControllableSimulation::build_scenario(&SimulationConfig::default());
+        let nodes: Vec<&str> = preset.stations.iter().map(|s| s.node.as_str()).collect();
+        assert_eq!(nodes, ["3", "39", "93", "30"]);
+        assert!(check_generated_world(&preset).is_empty());
+
+        let mut scenario = preset.clone();
+        scenario.stations[0].node = "55".to_string();
+        let world = build_world_from_config(&scenario, scenario.seed);
+        let placed: Vec<u32> = (0..4)
+            .map(|i| world.stations[&StationId(i)].node.0)
+            .collect();
+        assert_eq!(placed, [55, 39, 93, 30]);
+        assert!(world
+            .robots
+            .values()
+            .all(|r| !placed.contains(&r.current_node.0)));
+    }
+
+    #[test]
+    fn test_map_interventions() {
+        let mut sim = session(&SimulationConfig::default());
+        let edge = first_edge(&sim);
+        let reverse = sim.world.map.reverse_edge(EdgeId(edge)).unwrap();
+
+        apply(&mut sim, Intervention::BlockEdge { edge_id: edge }).unwrap();
+        assert_eq!(sim.world.map.blocked_edges, [EdgeId(edge), reverse]);
+        apply(&mut sim, Intervention::UnblockEdge { edge_id: edge }).unwrap();
+        assert!(sim.world.map.blocked_edges.is_empty());
+        assert!(apply(&mut sim, Intervention::BlockEdge { edge_id: u32::MAX }).is_err());
+
+        apply(&mut sim, Intervention::BlockNode { node_id: 0 }).unwrap();
+        assert_eq!(sim.world.map.blocked_nodes, [NodeId(0)]);
+        apply(&mut sim, Intervention::UnblockNode { node_id: 0 }).unwrap();
+        assert!(sim.world.map.blocked_nodes.is_empty());
+        assert!(apply(&mut sim, Intervention::BlockNode { node_id: u32::MAX }).is_err());
+    }
+
+    #[test]
+    fn test_station_and_policy_interventions() {
+        let mut sim = session(&SimulationConfig::default());
+
+        apply(&mut sim, Intervention::CloseStation { station_id: 0 }).unwrap();
+        assert!(!sim.world.stations[&StationId(0)].open);
+        apply(&mut sim, Intervention::OpenStation { station_id: 0 }).unwrap();
+        assert!(sim.world.stations[&StationId(0)].open);
+        assert!(apply(&mut sim, Intervention::OpenStation { station_id: 999 }).is_err());
+
+        let rate = Intervention::SetOrderRate {
+            orders_per_hour: 120.0,
+        };
+        apply(&mut sim, rate).unwrap();
+        assert_eq!(sim.scenario.orders.arrival_process.rate_per_min, 2.0);
+        let bad_rate = Intervention::SetOrderRate {
+            orders_per_hour: -1.0,
+        };
+        assert!(apply(&mut sim, bad_rate).is_err());
+
+        let policy = |name: &str| Intervention::SetTaskAllocation {
+            policy: name.to_string(),
+        };
+        apply(&mut sim, policy("least_busy")).unwrap();
+        assert_eq!(sim.world.policies.task_allocation.name(), "least_busy");
+        assert!(apply(&mut sim, policy("no_such_policy")).is_err());
+        assert_eq!(sim.world.policies.task_allocation.name(), "least_busy");
+
+        let traffic = |name: &str| Intervention::SetTrafficPolicy {
+            policy: name.to_string(),
+        };
+        apply(&mut sim, traffic("reroute_on_wait")).unwrap();
+        assert_eq!(sim.world.policies.traffic.name(), "reroute_on_wait");
+        assert!(apply(&mut sim, traffic("no_such_policy")).is_err());
+
+        // Only applied interventions are recorded
+        assert_eq!(sim.recorded_interventions().len(), 5);
+    }
+
+    #[test]
+    fn test_reopened_station_serves_its_queue() {
+        let mut sim = session(&SimulationConfig::default());
+        let station = StationId(0);
+        advance_until(&mut sim, |sim| {
+            sim.world.tasks.values().any(|t| {
+                t.destination_station == station && t.assigned_robot.is_some() && !t.is_pending()
+            })
+        });
+        apply(&mut sim, Intervention::CloseStation { station_id: 0 }).unwrap();
+        advance_until(&mut sim, |sim| {
+            let station = &sim.world.stations[&station];
+            station.serving.is_empty() && !station.queue.is_empty()
+        });
+        let queued = sim.world.stations[&station].queue.len();
+
+        apply(&mut sim, Intervention::OpenStation { station_id: 0 }).unwrap();
+        let station = &sim.world.stations[&station];
+        assert!(!station.serving.is_empty());
+        assert!(station.queue.len() < queued);
+    }
+
+    #[test]
+    fn test_add_robot_records_its_node() {
+        let mut sim = session(&SimulationConfig::default());
+        let lowest = sim.world.map.nodes.keys().map(|n| n.0).min().unwrap();
+        let count = sim.world.robots.len();
+
+        let recorded = apply(&mut sim, Intervention::AddRobot { node_id: None }).unwrap();
+        assert_eq!(
+            recorded,
+            Intervention::AddRobot {
+                node_id: Some(lowest)
+            }
+        );
+        assert_eq!(sim.world.robots.len(), count + 1);
+        let added = RobotId(count as u32);
+        assert_eq!(sim.world.robots[&added].current_node, NodeId(lowest));
+
+        assert!(apply(
+            &mut sim,
+            Intervention::AddRobot {
+                node_id: Some(u32::MAX)
+            }
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_fail_and_remove_robot() {
+        let mut sim = session(&SimulationConfig::default());
+        advance_until(&mut sim, |sim| {
+            sim.world
+                .robots
+                .values()
+                .any(|r| matches!(r.state, EntityState::Moving { .. }))
+        });
+        let traveling = sim
+            .world
+            .robots
+            .values()
+            .filter(|r| matches!(r.state, EntityState::Moving { .. }))
+            .map(|r| r.id.0)
+            .min()
+            .unwrap();
+
+        // A robot on its way somewhere can't be removed
+        let remove = Intervention::RemoveRobot {
+            robot_id: traveling,
+        };
+        assert!(apply(&mut sim, remove.clone()).is_err());
+        assert!(sim.world.robots.contains_key(&RobotId(traveling)));
+
+        // Failing it stops it; once failed it can go
+        let fail = Intervention::FailRobot {
+            robot_id: traveling,
+        };
+        apply(&mut sim, fail.clone()).unwrap();
+        advance_until(&mut sim, |sim| {
+            sim.world.robots[&RobotId(traveling)].is_failed()
+        });
+        assert!(apply(&mut sim, fail).is_err());
+        apply(&mut sim, remove.clone()).unwrap();
+        assert!(!sim.world.robots.contains_key(&RobotId(traveling)));
+        assert!(apply(&mut sim, remove).is_err());
+
+        advance(&mut sim, 500);
+        assert!(!sim.world.robots.contains_key(&RobotId(traveling)));
+    }
+
+    #[test]
+    fn test_remove_robot_about_to_park() {
+        let mut scenario = ControllableSimulation::build_scenario(&SimulationConfig::default());
+        scenario.policies.idle_positioning.positioning_type = "nearest_staging".to_string();
+        scenario.policies.idle_positioning.parking_nodes = vec![0];
+        let config = SimulationConfig {
+            scenario: Some(scenario),
+            ..Default::default()
+        };
+        let mut sim = session(&config);
+        advance_until(&mut sim, |sim| !sim.world.repositioning_departs.is_empty());
+        let robot_id = sim
+            .world
+            .repositioning_departs
+            .keys()
+            .map(|r| r.0)
+            .min()
+            .map(RobotId)
+            .unwrap();
+        let node = sim.world.robots[&robot_id].current_node;
+        let canceled = sim.world.repositioning_stats.trips_canceled;
+
+        apply(
+            &mut sim,
+            Intervention::RemoveRobot {
+                robot_id: robot_id.0,
+            },
+        )
+        .unwrap();
+        assert!(!sim.world.repositioning_departs.contains_key(&robot_id));
+        assert!(!sim.world.awaiting_idle_positioning.contains(&robot_id));
+        assert!(!sim
+            .world
+            .traffic
+            .robots_at_node(node)
+            .any(|r| r == robot_id));
+        assert!(sim
+            .world
+            .reservation_manager
+            .get_robot_reservations(robot_id)
+            .is_empty());
+        assert_eq!(sim.world.repositioning_stats.trips_canceled, canceled + 1);
+
+        // Its departure was called off, so it never comes back
+        advance(&mut sim, 2_000);
+        assert!(!sim.world.robots.contains_key(&robot_id));
+    }
+
+    #[test]
+    fn test_replayed_interventions_reproduce_the_run() {
+        fn outcome(sim: &ControllableSimulation) -> (u64, u64, f64, Vec<RobotState>) {
+            let mut robots = robot_states(&sim.world);
+            robots.sort_by_key(|r| r.id);
+            (
+                sim.events_processed,
+                sim.orders_completed,
+                sim.kernel.now().as_seconds(),
+                robots,
+            )
+        }
+
+        let mut live = session(&SimulationConfig::default());
+        let edge = first_edge(&live);
+        advance(&mut live, 300);
+        apply(&mut live, Intervention::AddRobot { node_id: None }).unwrap();
+        apply(&mut live, Intervention::BlockEdge { edge_id: edge }).unwrap();
+        advance(&mut live, 300);
+        apply(&mut live, Intervention::CloseStation { station_id: 0 }).unwrap();
+        apply(&mut live, Intervention::FailRobot { robot_id: 0 }).unwrap();
+        apply(
+            &mut live,
+            Intervention::SetTaskAllocation {
+                policy: "least_busy".to_string(),
+            },
+        )
+        .unwrap();
+        apply(
+            &mut live,
+            Intervention::SetOrderRate {
+                orders_per_hour: 300.0,
+            },
+        )
+        .unwrap();
+        advance(&mut live, 600);
+
+        let recorded = live.recorded_interventions();
+        let mut replay = session(&SimulationConfig {
+            interventions: recorded.clone(),
+            ..Default::default()
+        });
+        advance(&mut replay, 1_200);
+
+        assert_eq!(replay.recorded_interventions(), recorded);
+        assert_eq!(outcome(&replay), outcome(&live));
+        // Utilization is summed over robots in hash order, so it may differ
+        // in the last bit
+        let (replayed, original) = (replay.compute_metrics(), live.compute_metrics());
+        assert_eq!(replayed.orders_pending, original.orders_pending);
+        assert!((replayed.robot_utilization - original.robot_utilization).abs() < 1e-9);
+        assert!((replayed.station_utilization - original.station_utilization).abs() < 1e-9);
+    }
+}
@@ -1,10 +1,28 @@
 //! Application state shared across handlers
 
+use std::path::PathBuf;
 use std::sync::Arc;
 
+use crate::audit::AuditLog;
+use crate::auth::ApiKeys;
+use crate::jobs::JobManager;
+use crate::metrics::ServerMetrics;
+use crate::replay::ReplayManager;
 use crate::session::SessionManager;
 
 /// Application state shared across handlers
 pub struct AppState {
     pub session_manager: Arc<SessionManager>,
+    /// Directory of named scenarios sessions may reference
+    pub scenario_dir: Option<PathBuf>,
+    /// Background batch jobs
+    pub job_manager: Arc<JobManager>,
+    /// Replays of recorded event logs
+    pub replay_manager: Arc<ReplayManager>,
+    /// Accepted API keys (None = the API is open)
+    pub api_keys: Option<ApiKeys>,
+    /// Who ran what
+    pub audit: AuditLog,
+    /// Server statistics for `/metrics`
+    pub metrics: Arc<ServerMetrics>,
 }
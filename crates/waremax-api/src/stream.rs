@@ -0,0 +1,530 @@
+//! Per-client view of a session's update stream
+//!
+//! A `/ws/:id` client narrows its stream with a `Subscribe` command: the
+//! topics it wants, a subset of robots or zones, `StateDelta`s between
+//! periodic `StateSync` keyframes instead of full states, and a message
+//! budget. A client that falls behind the broadcast is sent a fresh
+//! keyframe instead of the messages it missed.
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use crate::simulation::SimUpdate;
+use crate::types::{SimulationState, StateDelta, StreamSubscription, WebSocketMessage};
+
+/// Turns simulation updates into the messages one client asked for
+pub struct ClientStream {
+    subscription: StreamSubscription,
+    /// Nodes of the subscribed zones (None = anywhere)
+    zone_nodes: Option<HashSet<u32>>,
+    /// Node each robot was last seen at, to place it in a zone
+    robot_nodes: HashMap<u32, u32>,
+    /// View of the state the client holds, the base of the next delta
+    sent: Option<SimulationState>,
+    last_keyframe: Instant,
+    /// Messages the client may still be sent right now
+    budget: f64,
+    last_refill: Instant,
+    /// Newest state held back by the message budget
+    pending: Option<SimulationState>,
+}
+
+impl Default for ClientStream {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClientStream {
+    /// Stream every update, as to a client that never subscribed
+    pub fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            subscription: StreamSubscription::default(),
+            zone_nodes: None,
+            robot_nodes: HashMap::new(),
+            sent: None,
+            last_keyframe: now,
+            budget: 0.0,
+            last_refill: now,
+            pending: None,
+        }
+    }
+
+    /// Replace the subscription, resolving its zones against the
+    /// session's. The next state sent is a keyframe.
+    pub fn subscribe(
+        &mut self,
+        subscription: StreamSubscription,
+        zones: &HashMap<String, Vec<u32>>,
+    ) -> Result<(), String> {
+        if subscription.max_messages_per_second == Some(0) {
+            return Err("max_messages_per_second must be positive".to_string());
+        }
+        let zone_nodes = match &subscription.zones {
+            Some(ids) => {
+                let mut nodes = HashSet::new();
+                for id in ids {
+                    let zone = zones
+                        .get(id)
+                        .ok_or_else(|| format!("Unknown zone '{}'", id))?;
+                    nodes.extend(zone);
+                }
+                Some(nodes)
+            }
+            None => None,
+        };
+
+        self.budget = subscription.max_messages_per_second.unwrap_or(0) as f64;
+        self.last_refill = Instant::now();
+        self.subscription = subscription;
+        self.zone_nodes = zone_nodes;
+        self.sent = None;
+        self.pending = None;
+        Ok(())
+    }
+
+    /// A full `StateSync` of `state`, which later deltas build on. Sent
+    /// after subscribing and when the client lagged.
+    pub fn keyframe(&mut self, state: SimulationState, now: Instant) -> WebSocketMessage {
+        self.track(&state);
+        let state = self.view(state);
+        self.pending = None;
+        self.sent = Some(state.clone());
+        self.last_keyframe = now;
+        WebSocketMessage::StateSync { state }
+    }
+
+    /// Messages to send the client for `update`
+    pub fn update(&mut self, update: SimUpdate, now: Instant) -> Vec<WebSocketMessage> {
+        let message = match update {
+            SimUpdate::StateChanged(state) => {
+                self.track(&state);
+                let state = self.view(state);
+                if !self.take_budget(now) {
+                    self.pending = Some(state);
+                    return Vec::new();
+                }
+                self.pending = None;
+                return self.state_message(state, now).into_iter().collect();
+            }
+            SimUpdate::Tick {
+                time_s,
+                events_processed,
+            } => (self.subscription.ticks && self.take_budget(now)).then_some(
+                WebSocketMessage::Tick {
+                    time_s,
+                    events_processed,
+                },
+            ),
+            SimUpdate::RobotMoved {
+                robot_id,
+                from_node,
+                to_node,
+                time_s,
+            } => {
+                self.robot_nodes.insert(robot_id, to_node);
+                let wanted = self.subscription.robot_moves
+                    && self.wants_robot(robot_id, &[from_node, to_node]);
+                (wanted && self.take_budget(now)).then_some(WebSocketMessage::RobotMoved {
+                    robot_id,
+                    from_node,
+                    to_node,
+                    time_s,
+                })
+            }
+            SimUpdate::RobotStateChanged {
+                robot_id,
+                old_state,
+                new_state,
+                time_s,
+            } => {
+                let node = self.robot_nodes.get(&robot_id).copied();
+                let wanted = self.subscription.robot_state_changes
+                    && self.wants_robot(robot_id, node.as_slice());
+                (wanted && self.take_budget(now)).then_some(WebSocketMessage::RobotStateChanged {
+                    robot_id,
+                    old_state,
+                    new_state,
+                    time_s,
+                })
+            }
+            SimUpdate::RobotPositionUpdate {
+                robot_id,
+                edge_id,
+                from_node,
+                to_node,
+                progress,
+                time_s,
+            } => {
+                let wanted = self.subscription.robot_positions
+                    && self.wants_robot(robot_id, &[from_node, to_node]);
+                (wanted && self.take_budget(now)).then_some(WebSocketMessage::RobotPositionUpdate {
+                    robot_id,
+                    edge_id,
+                    from_node,
+                    to_node,
+                    progress,
+                    time_s,
+                })
+            }
+            SimUpdate::OrderCompleted {
+                order_id,
+                cycle_time_s,
+                on_time,
+            } => self
+                .subscription
+                .order_events
+                .then_some(WebSocketMessage::OrderCompleted {
+                    order_id,
+                    cycle_time_s,
+                    on_time,
+                }),
+            SimUpdate::MetricsUpdate(metrics) => (self.subscription.metrics_snapshots
+                && self.take_budget(now))
+            .then_some(WebSocketMessage::MetricsUpdate { metrics }),
+            SimUpdate::Intervention(record) => self
+                .subscription
+                .interventions
+                .then_some(WebSocketMessage::Intervention { record }),
+            SimUpdate::Finished(final_metrics) => {
+                // The final state goes out even over budget
+                let mut messages: Vec<_> = self
+                    .pending
+                    .take()
+                    .and_then(|state| self.state_message(state, now))
+                    .into_iter()
+                    .collect();
+                messages.push(WebSocketMessage::Finished { final_metrics });
+                return messages;
+            }
+            SimUpdate::Error(message) => Some(WebSocketMessage::Error { message }),
+        };
+        message.into_iter().collect()
+    }
+
+    /// The state held back by the message budget, once it allows
+    pub fn flush(&mut self, now: Instant) -> Option<WebSocketMessage> {
+        if self.pending.is_none() || !self.take_budget(now) {
+            return None;
+        }
+        let state = self.pending.take()?;
+        self.state_message(state, now)
+    }
+
+    /// `StateSync`, or a `StateDelta` between keyframes; None when nothing
+    /// the client sees changed
+    fn state_message(&mut self, state: SimulationState, now: Instant) -> Option<WebSocketMessage> {
+        if !self.subscription.deltas {
+            return Some(WebSocketMessage::StateSync { state });
+        }
+        let interval = Duration::from_millis(self.subscription.keyframe_interval_ms);
+        let Some(sent) = self
+            .sent
+            .as_ref()
+            .filter(|_| now.duration_since(self.last_keyframe) < interval)
+        else {
+            self.sent = Some(state.clone());
+            self.last_keyframe = now;
+            return Some(WebSocketMessage::StateSync { state });
+        };
+        let delta = state_delta(sent, &state);
+        self.sent = Some(state);
+        delta.map(|delta| WebSocketMessage::StateDelta { delta })
+    }
+
+    /// Remember where every robot is
+    fn track(&mut self, state: &SimulationState) {
+        self.robot_nodes = state.robots.iter().map(|r| (r.id, r.node_id)).collect();
+    }
+
+    /// The robots and stations of `state` the client subscribed to
+    fn view(&self, mut state: SimulationState) -> SimulationState {
+        state
+            .robots
+            .retain(|robot| self.wants_robot(robot.id, &[robot.node_id]));
+        if let Some(zone) = &self.zone_nodes {
+            state
+                .stations
+                .retain(|station| zone.contains(&station.node_id));
+        }
+        state
+    }
+
+    /// Whether the client follows `robot_id`, now at (or between) `nodes`
+    fn wants_robot(&self, robot_id: u32, nodes: &[u32]) -> bool {
+        let subscription = &self.subscription;
+        if let Some(robots) = &subscription.robots {
+            if !robots.contains(&robot_id) {
+                return false;
+            }
+        }
+        match &self.zone_nodes {
+            Some(zone) => nodes.iter().any(|node| zone.contains(node)),
+            None => true,
+        }
+    }
+
+    /// Spend one message of the budget; always true when unthrottled
+    fn take_budget(&mut self, now: Instant) -> bool {
+        let Some(rate) = self.subscription.max_messages_per_second else {
+            return true;
+        };
+        let rate = rate as f64;
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.budget = (self.budget + elapsed * rate).min(rate);
+        self.last_refill = now;
+        if self.budget >= 1.0 {
+            self.budget -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// What changed from `old` to `new`; None if nothing did
+fn state_delta(old: &SimulationState, new: &SimulationState) -> Option<StateDelta> {
+    let old_robots: HashMap<_, _> = old.robots.iter().map(|r| (r.id, r)).collect();
+    let new_ids: HashSet<_> = new.robots.iter().map(|r| r.id).collect();
+    let old_stations: HashMap<_, _> = old.stations.iter().map(|s| (s.id, s)).collect();
+
+    let delta = StateDelta {
+        time_s: new.time_s,
+        events_processed: new.events_processed,
+        orders_completed: new.orders_completed,
+        status: (new.status != old.status).then(|| new.status.clone()),
+        speed: (new.speed != old.speed).then_some(new.speed),
+        robots: new
+            .robots
+            .iter()
+            .filter(|r| old_robots.get(&r.id) != Some(r))
+            .cloned()
+            .collect(),
+        removed_robots: old
+            .robots
+            .iter()
+            .map(|r| r.id)
+            .filter(|id| !new_ids.contains(id))
+            .collect(),
+        stations: new
+            .stations
+            .iter()
+            .filter(|s| old_stations.get(&s.id) != Some(s))
+            .cloned()
+            .collect(),
+        metrics: (new.metrics != old.metrics).then(|| new.metrics.clone()),
+    };
+
+    let unchanged = StateDelta {
+        time_s: old.time_s,
+        events_processed: old.events_processed,
+        orders_completed: old.orders_completed,
+        ..Default::default()
+    };
+    (delta != unchanged).then_some(delta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{MetricsSnapshot, RobotState, SimulationStatus, StationState};
+
+    fn robot(id: u32, node_id: u32, state: &str) -> RobotState {
+        RobotState {
+            id,
+            node_id,
+            state: state.to_string(),
+            battery_soc: None,
+            current_task: None,
+            is_failed: false,
+        }
+    }
+
+    fn sim_state(time_s: f64, robots: Vec<RobotState>) -> SimulationState {
+        SimulationState {
+            status: SimulationStatus::Running,
+            time_s,
+            speed: 1.0,
+            events_processed: time_s as u64,
+            orders_completed: 0,
+            robots,
+            stations: vec![StationState {
+                id: 0,
+                name: "S0".to_string(),
+                node_id: 9,
+                station_type: "Pick".to_string(),
+                queue_length: 0,
+                serving_count: 0,
+                concurrency: 1,
+            }],
+            metrics: MetricsSnapshot::default(),
+        }
+    }
+
+    fn subscribe(stream: &mut ClientStream, subscription: StreamSubscription) {
+        let zones = HashMap::from([("aisle".to_string(), vec![1, 2])]);
+        stream.subscribe(subscription, &zones).unwrap();
+    }
+
+    #[test]
+    fn test_deltas_between_keyframes() {
+        let mut stream = ClientStream::new();
+        subscribe(
+            &mut stream,
+            StreamSubscription {
+                deltas: true,
+                ..Default::default()
+            },
+        );
+        let now = Instant::now();
+
+        let first = sim_state(1.0, vec![robot(0, 1, "Idle"), robot(1, 5, "Idle")]);
+        let sync = stream.update(SimUpdate::StateChanged(first.clone()), now);
+        assert!(matches!(sync[..], [WebSocketMessage::StateSync { .. }]));
+
+        // Only robot 1 moved, and robot 0 is gone
+        let second = sim_state(2.0, vec![robot(1, 6, "Moving")]);
+        let delta = stream.update(SimUpdate::StateChanged(second), now);
+        let [WebSocketMessage::StateDelta { delta }] = &delta[..] else {
+            panic!("expected a delta, got {:?}", delta);
+        };
+        assert_eq!(delta.robots, vec![robot(1, 6, "Moving")]);
+        assert_eq!(delta.removed_robots, vec![0]);
+        assert!(delta.stations.is_empty());
+        assert!(delta.status.is_none() && delta.speed.is_none() && delta.metrics.is_none());
+
+        // Nothing changed: nothing to send
+        let second = sim_state(2.0, vec![robot(1, 6, "Moving")]);
+        assert!(stream
+            .update(SimUpdate::StateChanged(second), now)
+            .is_empty());
+
+        // Keyframe once the interval is up
+        let later = now + Duration::from_millis(2000);
+        let sync = stream.update(SimUpdate::StateChanged(first), later);
+        assert!(matches!(sync[..], [WebSocketMessage::StateSync { .. }]));
+    }
+
+    #[test]
+    fn test_robot_and_zone_subsets() {
+        let mut stream = ClientStream::new();
+        subscribe(
+            &mut stream,
+            StreamSubscription {
+                zones: Some(vec!["aisle".to_string()]),
+                ..Default::default()
+            },
+        );
+        let now = Instant::now();
+
+        let messages = stream.update(
+            SimUpdate::StateChanged(sim_state(
+                1.0,
+                vec![robot(0, 1, "Idle"), robot(1, 5, "Idle")],
+            )),
+            now,
+        );
+        let [WebSocketMessage::StateSync { state }] = &messages[..] else {
+            panic!("expected a state, got {:?}", messages);
+        };
+        assert_eq!(state.robots, vec![robot(0, 1, "Idle")]);
+        assert!(state.stations.is_empty());
+
+        let moved = |robot_id, from_node, to_node| SimUpdate::RobotMoved {
+            robot_id,
+            from_node,
+            to_node,
+            time_s: 1.0,
+        };
+        assert_eq!(stream.update(moved(0, 1, 3), now).len(), 1);
+        assert!(stream.update(moved(1, 5, 6), now).is_empty());
+        let changed = SimUpdate::RobotStateChanged {
+            robot_id: 0,
+            old_state: "Moving".to_string(),
+            new_state: "Idle".to_string(),
+            time_s: 2.0,
+        };
+        // Robot 0 has left the zone
+        assert!(stream.update(changed, now).is_empty());
+
+        subscribe(
+            &mut stream,
+            StreamSubscription {
+                robots: Some(vec![1]),
+                robot_moves: false,
+                ..Default::default()
+            },
+        );
+        assert!(stream.update(moved(1, 5, 6), now).is_empty());
+        let messages = stream.update(
+            SimUpdate::StateChanged(sim_state(
+                2.0,
+                vec![robot(0, 1, "Idle"), robot(1, 6, "Idle")],
+            )),
+            now,
+        );
+        let [WebSocketMessage::StateSync { state }] = &messages[..] else {
+            panic!("expected a state, got {:?}", messages);
+        };
+        assert_eq!(state.robots, vec![robot(1, 6, "Idle")]);
+        assert_eq!(state.stations.len(), 1);
+
+        let unknown = StreamSubscription {
+            zones: Some(vec!["dock".to_string()]),
+            ..Default::default()
+        };
+        assert!(stream.subscribe(unknown, &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_throttling_merges_states() {
+        let mut stream = ClientStream::new();
+        subscribe(
+            &mut stream,
+            StreamSubscription {
+                max_messages_per_second: Some(2),
+                ..Default::default()
+            },
+        );
+        let now = Instant::now();
+        let tick = SimUpdate::Tick {
+            time_s: 1.0,
+            events_processed: 1,
+        };
+
+        assert_eq!(stream.update(tick.clone(), now).len(), 1);
+        assert_eq!(stream.update(tick.clone(), now).len(), 1);
+        assert!(stream.update(tick, now).is_empty());
+        for time_s in [1.0, 2.0, 3.0] {
+            let update = SimUpdate::StateChanged(sim_state(time_s, Vec::new()));
+            assert!(stream.update(update, now).is_empty());
+        }
+        assert!(stream.flush(now).is_none());
+
+        // The newest state goes out once the budget refills
+        let later = now + Duration::from_millis(500);
+        let Some(WebSocketMessage::StateSync { state }) = stream.flush(later) else {
+            panic!("expected the held-back state");
+        };
+        assert_eq!(state.time_s, 3.0);
+        assert!(stream.flush(later).is_none());
+
+        // Orders and the end of the run are never dropped
+        let order = SimUpdate::OrderCompleted {
+            order_id: 1,
+            cycle_time_s: 60.0,
+            on_time: true,
+        };
+        assert_eq!(stream.update(order, later).len(), 1);
+        stream.update(SimUpdate::StateChanged(sim_state(4.0, Vec::new())), later);
+        let finished = stream.update(SimUpdate::Finished(MetricsSnapshot::default()), later);
+        assert!(matches!(
+            finished[..],
+            [
+                WebSocketMessage::StateSync { .. },
+                WebSocketMessage::Finished { .. }
+            ]
+        ));
+    }
+}
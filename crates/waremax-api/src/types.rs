@@ -1,12 +1,23 @@
 //! API data types and DTOs for the web UI
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use waremax_config::{ScenarioConfig, ValidationError};
+use waremax_metrics::{SimulationReport, TraceEntry};
+use waremax_testing::{ABTestResult, AggregatedStats, ComparisonReport, RunResult};
 
 /// Session configuration for creating new simulations
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct SessionConfig {
-    /// Preset name: "small", "standard", "large"
+    /// Preset name: "small", "standard", "large" (ignored when a scenario is given)
+    #[serde(default = "default_preset")]
     pub preset: String,
+    /// Inline scenario: a ScenarioConfig object, or YAML/JSON text
+    #[serde(default)]
+    pub scenario: Option<serde_json::Value>,
+    /// Name of a scenario in the server's scenario library
+    #[serde(default)]
+    pub scenario_name: Option<String>,
     /// Number of robots (overrides preset if set)
     pub robot_count: Option<u32>,
     /// Order arrival rate per hour (overrides preset if set)
@@ -17,30 +28,95 @@ pub struct SessionConfig {
     pub grid_rows: Option<u32>,
     /// Grid size columns (overrides preset if set)
     pub grid_cols: Option<u32>,
+    /// Intervention log of an earlier run to replay (same scenario and seed)
+    #[serde(default)]
+    pub interventions: Vec<InterventionRecord>,
+    /// Save the run's event log with its history (needs a data directory)
+    #[serde(default)]
+    pub record_events: bool,
+}
+
+fn default_preset() -> String {
+    "standard".to_string()
 }
 
 impl Default for SessionConfig {
     fn default() -> Self {
         Self {
-            preset: "standard".to_string(),
+            preset: default_preset(),
+            scenario: None,
+            scenario_name: None,
             robot_count: None,
             order_rate: None,
             duration_minutes: None,
             grid_rows: None,
             grid_cols: None,
+            interventions: Vec::new(),
+            record_events: false,
         }
     }
 }
 
 /// Response for session creation
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct SessionResponse {
     pub session_id: String,
     pub status: String,
+    /// Non-fatal validation findings for the session's scenario
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<ValidationIssue>,
+}
+
+/// Response of an action that only reports its outcome
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct StatusResponse {
+    /// e.g. "started", "paused" or "deleted"
+    pub status: String,
+}
+
+impl StatusResponse {
+    pub fn new(status: impl Into<String>) -> Self {
+        Self {
+            status: status.into(),
+        }
+    }
+}
+
+/// Response for setting the simulation speed
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SpeedResponse {
+    /// Speed after clamping to the allowed range
+    pub speed: f64,
+}
+
+/// Response of the health check
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct HealthResponse {
+    pub status: String,
+    pub version: String,
+}
+
+/// A scenario validation error or warning
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ValidationIssue {
+    /// Path of the offending field, e.g. "scenario.stations[0].node"
+    pub path: String,
+    pub message: String,
+    pub suggestion: Option<String>,
+}
+
+impl From<&ValidationError> for ValidationIssue {
+    fn from(error: &ValidationError) -> Self {
+        Self {
+            path: error.path.to_string(),
+            message: error.kind.to_string(),
+            suggestion: error.suggestion.clone(),
+        }
+    }
 }
 
 /// Simulation status
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum SimulationStatus {
     Idle,
@@ -51,7 +127,7 @@ pub enum SimulationStatus {
 }
 
 /// Simulation state response
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct SimulationState {
     pub status: SimulationStatus,
     pub time_s: f64,
@@ -64,7 +140,7 @@ pub struct SimulationState {
 }
 
 /// Robot state for frontend display
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct RobotState {
     pub id: u32,
     pub node_id: u32,
@@ -75,7 +151,7 @@ pub struct RobotState {
 }
 
 /// Station state for frontend display
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct StationState {
     pub id: u32,
     pub name: String,
@@ -87,7 +163,7 @@ pub struct StationState {
 }
 
 /// Metrics snapshot for dashboard
-#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[derive(Clone, Debug, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
 pub struct MetricsSnapshot {
     pub throughput_per_hour: f64,
     pub orders_completed: u64,
@@ -99,7 +175,7 @@ pub struct MetricsSnapshot {
 }
 
 /// Warehouse map data for frontend rendering
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct MapData {
     pub nodes: Vec<NodeData>,
     pub edges: Vec<EdgeData>,
@@ -107,7 +183,7 @@ pub struct MapData {
 }
 
 /// Node data for map rendering
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct NodeData {
     pub id: u32,
     pub name: String,
@@ -117,7 +193,7 @@ pub struct NodeData {
 }
 
 /// Edge data for map rendering
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct EdgeData {
     pub id: u32,
     pub from: u32,
@@ -127,7 +203,7 @@ pub struct EdgeData {
 }
 
 /// Map bounds for canvas sizing
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct MapBounds {
     pub min_x: f64,
     pub max_x: f64,
@@ -136,7 +212,7 @@ pub struct MapBounds {
 }
 
 /// Control command from frontend
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", content = "payload")]
 pub enum ControlCommand {
     Start,
@@ -147,22 +223,155 @@ pub enum ControlCommand {
     AddRobot { node_id: Option<u32> },
     Reset,
     Stop,
+    /// Narrow this client's stream (`/ws/:id` only)
+    Subscribe(StreamSubscription),
+}
+
+/// Which updates a `/ws/:id` client receives and how. Clients that never
+/// subscribe get every update with a full `StateSync` each time.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(default)]
+pub struct StreamSubscription {
+    pub ticks: bool,
+    pub robot_moves: bool,
+    /// Progress along edges; the bulk of a busy stream
+    pub robot_positions: bool,
+    pub robot_state_changes: bool,
+    pub order_events: bool,
+    pub metrics_snapshots: bool,
+    pub interventions: bool,
+    /// Only these robots (default: all)
+    pub robots: Option<Vec<u32>>,
+    /// Only robots and stations inside these human zones of the scenario
+    /// (default: anywhere)
+    pub zones: Option<Vec<String>>,
+    /// Send `StateDelta`s between periodic `StateSync` keyframes
+    pub deltas: bool,
+    /// Time between keyframes when sending deltas
+    pub keyframe_interval_ms: u64,
+    /// Most messages a second; robot and metrics updates over it are
+    /// dropped and state updates merged. Orders, interventions and the
+    /// end of the run always go through.
+    pub max_messages_per_second: Option<u32>,
+}
+
+impl Default for StreamSubscription {
+    fn default() -> Self {
+        Self {
+            ticks: true,
+            robot_moves: true,
+            robot_positions: true,
+            robot_state_changes: true,
+            order_events: true,
+            metrics_snapshots: true,
+            interventions: true,
+            robots: None,
+            zones: None,
+            deltas: false,
+            keyframe_interval_ms: 2000,
+            max_messages_per_second: None,
+        }
+    }
+}
+
+/// What changed in a client's view of the simulation state. Robots and
+/// stations are sent whole when any of their fields changed.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct StateDelta {
+    pub time_s: f64,
+    pub events_processed: u64,
+    pub orders_completed: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<SimulationStatus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speed: Option<f64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub robots: Vec<RobotState>,
+    /// Robots that left the view (removed, or out of the subscribed zones)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed_robots: Vec<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stations: Vec<StationState>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<MetricsSnapshot>,
+}
+
+/// Live intervention on a running session, applied at the next event boundary
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(tag = "type")]
+pub enum Intervention {
+    /// Close an edge in both directions; robots route around it
+    BlockEdge {
+        edge_id: u32,
+    },
+    UnblockEdge {
+        edge_id: u32,
+    },
+    /// Close a node; robots route around it
+    BlockNode {
+        node_id: u32,
+    },
+    UnblockNode {
+        node_id: u32,
+    },
+    /// Break a robot down at its next node, requeueing its task
+    FailRobot {
+        robot_id: u32,
+    },
+    /// Take an idle or failed robot out of the fleet
+    RemoveRobot {
+        robot_id: u32,
+    },
+    /// Add a robot, at the lowest-numbered node when none is given. The
+    /// recorded intervention always names the node used.
+    AddRobot {
+        node_id: Option<u32>,
+    },
+    /// Reopen a station; robots already queued there are served
+    OpenStation {
+        station_id: u32,
+    },
+    CloseStation {
+        station_id: u32,
+    },
+    /// Change the order arrival rate (orders per hour)
+    SetOrderRate {
+        orders_per_hour: f64,
+    },
+    /// Hot-swap the task allocation policy (e.g. "least_busy")
+    SetTaskAllocation {
+        policy: String,
+    },
+    /// Hot-swap the traffic policy (e.g. "reroute_on_wait")
+    SetTrafficPolicy {
+        policy: String,
+    },
+}
+
+/// An applied intervention and where it happened, for replay
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct InterventionRecord {
+    /// Events processed before it was applied; a replay applies it at the
+    /// same point
+    pub at_event: u64,
+    pub time_s: f64,
+    pub intervention: Intervention,
 }
 
 /// Speed change request
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct SpeedRequest {
     pub speed: f64,
 }
 
 /// Add robot request
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct AddRobotRequest {
     pub node_id: Option<u32>,
 }
 
 /// Preset information for frontend
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct PresetInfo {
     pub name: String,
     pub description: String,
@@ -174,7 +383,7 @@ pub struct PresetInfo {
 }
 
 /// WebSocket event wrapper for frontend
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type")]
 pub enum WebSocketMessage {
     /// Connection established
@@ -214,17 +423,144 @@ pub enum WebSocketMessage {
     MetricsUpdate { metrics: MetricsSnapshot },
     /// Full state sync
     StateSync { state: SimulationState },
+    /// Changes since the previous `StateSync` or `StateDelta`
+    StateDelta { delta: StateDelta },
     /// Simulation finished
     Finished { final_metrics: MetricsSnapshot },
+    /// Intervention applied
+    Intervention { record: InterventionRecord },
     /// Error occurred
     Error { message: String },
 }
 
+/// Decision an external controller can take over from the session's policies
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ControllerRole {
+    /// Which robot handles a task
+    TaskAllocation,
+    /// Which station a task is sent to
+    StationAssignment,
+}
+
+/// Message from an external controller on `/ws/:id/controller`
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type")]
+pub enum ControllerClientMessage {
+    /// First message: take over `roles`. Unanswered requests fall back to the
+    /// session's policy, or to the named fallback, after `timeout_ms`.
+    Register {
+        roles: Vec<ControllerRole>,
+        #[serde(default)]
+        timeout_ms: Option<u64>,
+        #[serde(default)]
+        fallback_allocation: Option<String>,
+        #[serde(default)]
+        fallback_station: Option<String>,
+    },
+    /// Answer to a decision request: a candidate robot or station id, or
+    /// `null` for no robot this round (allocation) or the fallback (station)
+    Decide {
+        request_id: u64,
+        choice: Option<u32>,
+    },
+}
+
+/// Message to an external controller
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type")]
+pub enum ControllerServerMessage {
+    /// Registration accepted
+    Registered {
+        roles: Vec<ControllerRole>,
+        timeout_ms: u64,
+    },
+    /// Pick a robot for `task` from `candidates`
+    AllocationRequest {
+        request_id: u64,
+        time_s: f64,
+        task: TaskView,
+        candidates: Vec<RobotCandidate>,
+    },
+    /// Pick a station for `task` from `candidates`
+    StationRequest {
+        request_id: u64,
+        time_s: f64,
+        task: TaskView,
+        candidates: Vec<StationCandidate>,
+    },
+    /// The simulation finished; no further requests follow
+    Finished,
+    /// Registration rejected or the session failed
+    Error { message: String },
+}
+
+/// How a session's external controller is doing
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ControllerStatus {
+    pub connected: bool,
+    pub roles: Vec<ControllerRole>,
+    pub timeout_ms: u64,
+    pub stats: ControllerStats,
+}
+
+/// Counts of a controller's decisions
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ControllerStats {
+    /// Requests answered by the controller
+    pub decisions: u64,
+    /// Requests that timed out and used the fallback
+    pub timeouts: u64,
+    /// Replies naming a non-candidate, which used the fallback
+    pub invalid: u64,
+}
+
+/// The task a controller decision is about
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct TaskView {
+    pub task_id: u32,
+    pub task_type: String,
+    pub order_id: Option<u32>,
+    pub sku_id: u32,
+    pub quantity: u32,
+    pub source_node: u32,
+    pub destination_station: u32,
+    pub created_at_s: f64,
+}
+
+/// A robot that can take the task
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RobotCandidate {
+    pub robot_id: u32,
+    pub node_id: u32,
+    pub state: String,
+    pub queued_tasks: usize,
+    /// Battery state of charge (0-1), when batteries are modelled
+    pub soc: Option<f64>,
+    /// Straight-line distance to the task's pickup node
+    pub distance_m: f64,
+}
+
+/// A station that can accept the task
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct StationCandidate {
+    pub station_id: u32,
+    pub name: String,
+    pub station_type: String,
+    pub queue_length: usize,
+    pub serving: usize,
+    /// Straight-line distance from the task's pickup node
+    pub distance_m: f64,
+}
+
 /// Error response
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ErrorResponse {
     pub error: String,
     pub details: Option<String>,
+    /// Scenario validation errors, when the request was rejected by validation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub validation_errors: Vec<ValidationIssue>,
 }
 
 impl ErrorResponse {
@@ -232,6 +568,7 @@ impl ErrorResponse {
         Self {
             error: error.into(),
             details: None,
+            validation_errors: Vec::new(),
         }
     }
 
@@ -239,6 +576,354 @@ impl ErrorResponse {
         Self {
             error: error.into(),
             details: Some(details.into()),
+            validation_errors: Vec::new(),
         }
     }
+
+    pub fn with_validation_errors(error: impl Into<String>, errors: &[ValidationError]) -> Self {
+        Self {
+            error: error.into(),
+            details: None,
+            validation_errors: errors.iter().map(ValidationIssue::from).collect(),
+        }
+    }
+}
+
+/// Where a batch job's scenario comes from: a preset, an inline scenario or a
+/// library scenario (at most one of `scenario` and `scenario_name`)
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ScenarioSource {
+    /// Preset name, used when no scenario is given (default "standard")
+    #[serde(default)]
+    pub preset: Option<String>,
+    /// Inline scenario: a ScenarioConfig object, or YAML/JSON text
+    #[serde(default)]
+    pub scenario: Option<serde_json::Value>,
+    /// Name of a scenario in the server's scenario library
+    #[serde(default)]
+    pub scenario_name: Option<String>,
+}
+
+/// A batch job to run in the background
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JobRequest {
+    /// Parameter sweep over `base`, e.g. `sweep: "robots:5,10,15"`
+    Sweep {
+        base: ScenarioSource,
+        sweep: String,
+        #[serde(default = "default_sweep_replications")]
+        replications: u32,
+    },
+    /// Baseline vs variant comparison
+    Compare {
+        baseline: ScenarioSource,
+        variant: ScenarioSource,
+        #[serde(default = "default_compare_replications")]
+        replications: u32,
+    },
+    /// A/B test with Welch's t-test
+    AbTest {
+        baseline: ScenarioSource,
+        variant: ScenarioSource,
+        #[serde(default = "default_ab_replications")]
+        replications: u32,
+        #[serde(default = "default_alpha")]
+        alpha: f64,
+    },
+}
+
+fn default_sweep_replications() -> u32 {
+    3
+}
+
+fn default_compare_replications() -> u32 {
+    5
+}
+
+fn default_ab_replications() -> u32 {
+    10
+}
+
+fn default_alpha() -> f64 {
+    0.05
+}
+
+impl JobRequest {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            JobRequest::Sweep { .. } => "sweep",
+            JobRequest::Compare { .. } => "compare",
+            JobRequest::AbTest { .. } => "ab_test",
+        }
+    }
+}
+
+/// Lifecycle of a batch job
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn is_finished(self) -> bool {
+        matches!(
+            self,
+            JobStatus::Completed | JobStatus::Failed | JobStatus::Cancelled
+        )
+    }
+}
+
+/// A batch job and its progress
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct JobInfo {
+    pub id: String,
+    /// "sweep", "compare" or "ab_test"
+    pub kind: String,
+    /// API key name of the tenant that submitted it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    pub status: JobStatus,
+    pub total_runs: usize,
+    pub completed_runs: usize,
+    /// RFC 3339 timestamps
+    pub created_at: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Headline metrics of one simulation run of a job
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RunSummary {
+    pub label: String,
+    pub seed: u64,
+    pub throughput_per_hour: f64,
+    pub p95_cycle_time_s: f64,
+    pub robot_utilization: f64,
+    pub station_utilization: f64,
+    pub orders_completed: u32,
+    pub orders_late: u32,
+    pub duration_ms: u64,
+}
+
+impl From<&RunResult> for RunSummary {
+    fn from(run: &RunResult) -> Self {
+        Self {
+            label: run.label.clone(),
+            seed: run.seed,
+            throughput_per_hour: run.throughput(),
+            p95_cycle_time_s: run.p95_cycle_time(),
+            robot_utilization: run.robot_utilization(),
+            station_utilization: run.station_utilization(),
+            orders_completed: run.report.orders_completed,
+            orders_late: run.report.orders_late,
+            duration_ms: run.duration_ms,
+        }
+    }
+}
+
+/// Throughput of one sweep configuration across its replications
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RankedConfig {
+    pub label: String,
+    #[schemars(with = "serde_json::Value")]
+    pub throughput: AggregatedStats,
+}
+
+/// Results of a finished batch job
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JobResults {
+    Sweep {
+        runs: Vec<RunSummary>,
+        /// Configurations by mean throughput, best first
+        ranking: Vec<RankedConfig>,
+    },
+    Compare {
+        runs: Vec<RunSummary>,
+        #[schemars(with = "serde_json::Value")]
+        report: ComparisonReport,
+    },
+    AbTest {
+        runs: Vec<RunSummary>,
+        #[schemars(with = "serde_json::Value")]
+        result: ABTestResult,
+    },
+}
+
+impl JobResults {
+    pub fn runs(&self) -> &[RunSummary] {
+        match self {
+            JobResults::Sweep { runs, .. }
+            | JobResults::Compare { runs, .. }
+            | JobResults::AbTest { runs, .. } => runs,
+        }
+    }
+}
+
+/// Query parameters for `GET /jobs/:id/results`
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+pub struct JobResultsQuery {
+    /// "json" (default) or "csv"
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+/// State of a recorded run
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    /// Its session is still live
+    Running,
+    /// Simulated to the end; the report is available
+    Finished,
+    /// Its session was deleted, expired or interrupted before the end
+    Stopped,
+}
+
+/// A session recorded in the run history
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RunRecord {
+    /// Id of the session that ran it
+    pub id: String,
+    pub status: RunStatus,
+    /// Preset name, library scenario name, or "inline"
+    pub source: String,
+    /// API key name of the tenant that ran it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    pub seed: u64,
+    pub created_at: String,
+    pub finished_at: Option<String>,
+    /// Whether the run's event log was saved
+    pub event_log: bool,
+    pub interventions: usize,
+    pub final_metrics: Option<MetricsSnapshot>,
+}
+
+/// A recorded run with everything needed to inspect or reproduce it
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RunDetail {
+    #[serde(flatten)]
+    pub record: RunRecord,
+    #[schemars(with = "serde_json::Value")]
+    pub scenario: ScenarioConfig,
+    pub intervention_log: Vec<InterventionRecord>,
+    #[schemars(with = "Option<serde_json::Value>")]
+    pub report: Option<SimulationReport>,
+}
+
+/// Query parameters for `GET /runs/:id/events`
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+pub struct EventRangeQuery {
+    #[serde(default)]
+    pub start_s: Option<f64>,
+    #[serde(default)]
+    pub end_s: Option<f64>,
+}
+
+/// One event log of a replay: a recorded run (`run_id`) or an uploaded log
+/// (`events`), exactly one of the two
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ReplaySourceRequest {
+    /// Name the replay's messages are tagged with; unique within the replay
+    pub name: String,
+    /// Id of a recorded run with an event log
+    #[serde(default)]
+    pub run_id: Option<String>,
+    /// Uploaded event log entries
+    #[serde(default)]
+    #[schemars(with = "Option<Vec<serde_json::Value>>")]
+    pub events: Option<Vec<TraceEntry>>,
+    /// Length of an uploaded log (default: its last event)
+    #[serde(default)]
+    pub duration_s: Option<f64>,
+    /// Seed an uploaded log was recorded with
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Scenario an uploaded log was recorded from, for the map and the
+    /// robots' starting positions
+    #[serde(default)]
+    pub scenario: Option<ScenarioSource>,
+}
+
+/// Request to create a replay of one or more event logs played in sync
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ReplayRequest {
+    pub replays: Vec<ReplaySourceRequest>,
+    /// Playback speed in simulated seconds per second (default 1.0)
+    #[serde(default)]
+    pub speed: Option<f64>,
+}
+
+/// Playback state of a replay
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplayStatus {
+    Paused,
+    Playing,
+    /// Every event log has played to its end
+    Finished,
+}
+
+/// One event log of a replay
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ReplayTrackInfo {
+    pub name: String,
+    pub run_id: Option<String>,
+    pub duration_s: f64,
+    pub event_count: u64,
+    pub seed: Option<u64>,
+    pub finished: bool,
+    /// Whether `GET /replays/:id/map` has a map for it
+    pub has_map: bool,
+}
+
+/// A replay and its playback position
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ReplayInfo {
+    pub replay_id: String,
+    /// API key name of the tenant that created it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    pub status: ReplayStatus,
+    pub time_s: f64,
+    pub speed: f64,
+    pub replays: Vec<ReplayTrackInfo>,
+}
+
+/// Request body of `POST /replays/:id/play` (all fields optional)
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct PlayRequest {
+    #[serde(default)]
+    pub speed: Option<f64>,
+}
+
+/// Request body of `POST /replays/:id/seek`
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SeekRequest {
+    pub time_s: f64,
+}
+
+/// Query parameters for `GET /replays/:id/map`
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+pub struct ReplayMapQuery {
+    /// Which event log's map (default: the first)
+    #[serde(default)]
+    pub replay: Option<String>,
+}
+
+/// Message on `/ws/replays/:id`: a `WebSocketMessage` tagged with the name
+/// of the event log it belongs to
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ReplayMessage {
+    pub replay: String,
+    #[serde(flatten)]
+    pub message: WebSocketMessage,
 }
@@ -51,6 +51,15 @@ pub struct ScenarioConfig {
     /// v3: Maintenance station configuration
     #[serde(default)]
     pub maintenance_stations: Vec<MaintenanceStationConfig>,
+    /// v7: Human workers and manual vehicles sharing the aisles
+    #[serde(default)]
+    pub humans: Vec<HumanAgentConfig>,
+    /// v7: Zones reserved for humans during time windows
+    #[serde(default)]
+    pub human_zones: Vec<HumanZoneConfig>,
+    /// v7: Dynamic re-slotting driven by observed SKU velocity
+    #[serde(default)]
+    pub slotting: Option<SlottingConfig>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -74,6 +83,12 @@ pub struct MapRef {
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct StorageRef {
     pub file: String,
+    /// v7: Time to access level 0 of generated racks (seconds)
+    #[serde(default)]
+    pub base_access_time_s: f64,
+    /// v7: Additional access time per level above 0 for generated racks (seconds)
+    #[serde(default)]
+    pub per_level_time_s: f64,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -107,12 +122,95 @@ pub struct StationConfig {
     pub concurrency: u32,
     pub queue_capacity: Option<u32>,
     pub service_time_s: ServiceTimeConfig,
+    /// v7: Operator staffing schedule (None = always open at `concurrency`)
+    #[serde(default)]
+    pub staffing: Option<StaffingConfig>,
+    /// v7: Operator learning/fatigue effects on service time
+    #[serde(default)]
+    pub productivity: ProductivityConfig,
 }
 
 fn default_concurrency() -> u32 {
     1
 }
 
+/// v7: Station staffing schedule
+///
+/// While a schedule is present, the number of operators working sets the
+/// station's concurrency; periods without operators close the station.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct StaffingConfig {
+    /// Repeat the schedule with this period (e.g. 1440 for daily)
+    #[serde(default)]
+    pub repeat_every_min: Option<f64>,
+    #[serde(default)]
+    pub shifts: Vec<ShiftConfig>,
+    #[serde(default)]
+    pub breaks: Vec<BreakConfig>,
+}
+
+/// v7: Operators on shift between two times (minutes from simulation start)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ShiftConfig {
+    pub start_min: f64,
+    pub end_min: f64,
+    #[serde(default = "default_operators")]
+    pub operators: u32,
+}
+
+/// v7: Operators on break between two times (minutes from simulation start)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BreakConfig {
+    pub start_min: f64,
+    pub end_min: f64,
+    #[serde(default = "default_operators")]
+    pub operators: u32,
+}
+
+fn default_operators() -> u32 {
+    1
+}
+
+/// v7: Operator productivity model
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProductivityConfig {
+    /// Service time multiplier for an inexperienced crew (1.0 = no learning curve)
+    #[serde(default = "default_multiplier_one")]
+    pub learning_initial_multiplier: f64,
+    /// Tasks served for the learning penalty to halve
+    #[serde(default = "default_learning_half_life")]
+    pub learning_half_life_tasks: f64,
+    /// Service time increase per hour on duty (0.05 = +5%/h)
+    #[serde(default)]
+    pub fatigue_per_hour: f64,
+    /// Cap on the fatigue multiplier
+    #[serde(default = "default_max_fatigue")]
+    pub max_fatigue_multiplier: f64,
+}
+
+fn default_multiplier_one() -> f64 {
+    1.0
+}
+
+fn default_learning_half_life() -> f64 {
+    50.0
+}
+
+fn default_max_fatigue() -> f64 {
+    1.5
+}
+
+impl Default for ProductivityConfig {
+    fn default() -> Self {
+        Self {
+            learning_initial_multiplier: default_multiplier_one(),
+            learning_half_life_tasks: default_learning_half_life(),
+            fatigue_per_hour: 0.0,
+            max_fatigue_multiplier: default_max_fatigue(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ServiceTimeConfig {
     /// Distribution type: constant, lognormal, exponential, uniform (v1)
@@ -270,6 +368,7 @@ pub struct PolicyConfig {
     /// v6: When true, the pickup bin for a SKU is re-selected per assignment to
     /// minimize robot->bin + bin->station travel (vs. taking the first in-stock
     /// replica). A spatial lever heuristics otherwise leave unused.
+    /// v7: Shorthand for `bin_selection.type: nearest`.
     #[serde(default)]
     pub smart_bins: bool,
     /// v6: Number of distinct SKUs to stock (default 100). Fewer SKUs over the
@@ -277,6 +376,83 @@ pub struct PolicyConfig {
     /// real leverage. `None` keeps the default.
     #[serde(default)]
     pub inventory_skus: Option<u32>,
+    /// v7: Where robots wait between tasks (stay, nearest_staging, demand_weighted)
+    #[serde(default)]
+    pub idle_positioning: IdlePositioningConfig,
+    /// v7: Which replica bin a pick task draws from (first_available, nearest, level_aware)
+    #[serde(default)]
+    pub bin_selection: BinSelectionConfig,
+}
+
+/// v7: Pickup bin selection among in-stock replicas of a SKU
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BinSelectionConfig {
+    #[serde(rename = "type", default = "default_bin_selection")]
+    pub selection_type: String,
+    /// Weight on rack level access time relative to travel time (level_aware)
+    #[serde(default = "default_level_weight")]
+    pub level_weight: f64,
+    /// Seconds of cost per unit left in the bin after the pick, favoring
+    /// nearly-empty bins (level_aware)
+    #[serde(default)]
+    pub consolidation_weight_s: f64,
+}
+
+fn default_bin_selection() -> String {
+    "first_available".to_string()
+}
+
+fn default_level_weight() -> f64 {
+    1.0
+}
+
+impl Default for BinSelectionConfig {
+    fn default() -> Self {
+        Self {
+            selection_type: default_bin_selection(),
+            level_weight: default_level_weight(),
+            consolidation_weight_s: 0.0,
+        }
+    }
+}
+
+/// v7: Idle robot parking / proactive repositioning
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct IdlePositioningConfig {
+    #[serde(rename = "type", default = "default_idle_positioning")]
+    pub positioning_type: String,
+    /// Node ids robots may park on (empty = all staging nodes of the map)
+    #[serde(default)]
+    pub parking_nodes: Vec<u32>,
+    /// Trailing window of task creations used to predict pickup demand (demand_weighted)
+    #[serde(default = "default_demand_window")]
+    pub demand_window_s: f64,
+    /// Radius around a parking node in which pickups count as its demand (demand_weighted)
+    #[serde(default = "default_demand_radius")]
+    pub demand_radius_m: f64,
+}
+
+fn default_idle_positioning() -> String {
+    "stay".to_string()
+}
+
+fn default_demand_window() -> f64 {
+    600.0
+}
+
+fn default_demand_radius() -> f64 {
+    15.0
+}
+
+impl Default for IdlePositioningConfig {
+    fn default() -> Self {
+        Self {
+            positioning_type: default_idle_positioning(),
+            parking_nodes: Vec::new(),
+            demand_window_s: default_demand_window(),
+            demand_radius_m: default_demand_radius(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -289,6 +465,9 @@ pub struct TaskAllocationConfig {
     /// Queue weight for auction policy (v1)
     #[serde(default = "default_queue_weight")]
     pub queue_weight: f64,
+    /// v7: Candidate-scorer weights file for the `learned` policy
+    #[serde(default)]
+    pub weights_path: Option<String>,
 }
 
 fn default_allocation() -> String {
@@ -309,6 +488,7 @@ impl Default for TaskAllocationConfig {
             alloc_type: default_allocation(),
             travel_weight: default_weight(),
             queue_weight: default_queue_weight(),
+            weights_path: None,
         }
     }
 }
@@ -833,6 +1013,142 @@ fn default_maintenance_duration() -> f64 {
     300.0 // 5 minutes for scheduled maintenance
 }
 
+/// v7: A group of human workers or manual vehicles
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HumanAgentConfig {
+    /// ID prefix; agents are named `<id>_<n>`
+    pub id: String,
+    /// Agent class: picker, forklift
+    #[serde(rename = "type", default = "default_human_type")]
+    pub agent_type: String,
+    #[serde(default = "default_human_count")]
+    pub count: u32,
+    /// Node the agents start from and return to between tasks
+    pub home_node: String,
+    /// Travel speed (defaults by type: picker 1.2, forklift 2.5)
+    #[serde(default)]
+    pub speed_m_s: Option<f64>,
+    /// Work source: manual_pick, replenishment
+    #[serde(default = "default_human_task_source")]
+    pub task_source: String,
+    /// Mean idle time between tasks (exponentially distributed)
+    #[serde(default = "default_human_task_interval")]
+    pub task_interval_s: f64,
+    /// Time spent working at each task location
+    #[serde(default = "default_human_work_time")]
+    pub work_time_s: f64,
+    /// Units picked or restocked per task
+    #[serde(default = "default_human_quantity")]
+    pub quantity: u32,
+    /// Robots stop rather than come this close (defaults to
+    /// `traffic.continuous.safety_distance_m`)
+    #[serde(default)]
+    pub stop_radius_m: Option<f64>,
+    /// Robots slow down within this distance
+    #[serde(default = "default_human_slow_radius")]
+    pub slow_radius_m: f64,
+    /// Robot speed factor inside the slow radius
+    #[serde(default = "default_human_slow_factor")]
+    pub slow_factor: f64,
+}
+
+fn default_human_type() -> String {
+    "picker".to_string()
+}
+
+fn default_human_count() -> u32 {
+    1
+}
+
+fn default_human_task_source() -> String {
+    "manual_pick".to_string()
+}
+
+fn default_human_task_interval() -> f64 {
+    60.0
+}
+
+fn default_human_work_time() -> f64 {
+    20.0
+}
+
+fn default_human_quantity() -> u32 {
+    1
+}
+
+fn default_human_slow_radius() -> f64 {
+    5.0
+}
+
+fn default_human_slow_factor() -> f64 {
+    0.5
+}
+
+/// v7: Nodes reserved for humans during a time window (minutes from start)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HumanZoneConfig {
+    pub id: String,
+    pub nodes: Vec<String>,
+    #[serde(default)]
+    pub start_min: f64,
+    pub end_min: f64,
+    /// Repeat the window with this period (e.g. 480 for every shift)
+    #[serde(default)]
+    pub repeat_every_min: Option<f64>,
+}
+
+/// v7: Periodic re-slotting of fast-moving SKUs toward stations
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SlottingConfig {
+    /// Slotting strategy: "abc" or "affinity"
+    #[serde(default = "default_slotting_strategy")]
+    pub strategy: String,
+    /// Minutes between re-slotting reviews
+    #[serde(default = "default_slotting_interval_min")]
+    pub interval_min: f64,
+    /// Maximum relocation moves per hour
+    #[serde(default = "default_slotting_moves_per_hour")]
+    pub moves_per_hour: f64,
+    /// Trailing window for SKU velocity (minutes)
+    #[serde(default = "default_slotting_velocity_window_min")]
+    pub velocity_window_min: f64,
+    /// Fraction of SKUs treated as fast movers (class A)
+    #[serde(default = "default_slotting_a_fraction")]
+    pub a_fraction: f64,
+}
+
+fn default_slotting_strategy() -> String {
+    "abc".to_string()
+}
+
+fn default_slotting_interval_min() -> f64 {
+    30.0
+}
+
+fn default_slotting_moves_per_hour() -> f64 {
+    20.0
+}
+
+fn default_slotting_velocity_window_min() -> f64 {
+    60.0
+}
+
+fn default_slotting_a_fraction() -> f64 {
+    0.2
+}
+
+impl Default for SlottingConfig {
+    fn default() -> Self {
+        Self {
+            strategy: default_slotting_strategy(),
+            interval_min: default_slotting_interval_min(),
+            moves_per_hour: default_slotting_moves_per_hour(),
+            velocity_window_min: default_slotting_velocity_window_min(),
+            a_fraction: default_slotting_a_fraction(),
+        }
+    }
+}
+
 impl ScenarioConfig {
     pub fn from_file(path: &str) -> Result<Self, ConfigError> {
         let content = std::fs::read_to_string(path)?;
@@ -98,6 +98,24 @@ pub enum ValidationErrorKind {
         field: String,
         value: u32,
     },
+    /// v7: A time window whose end is not after its start
+    InvalidTimeWindow {
+        start: f64,
+        end: f64,
+    },
+    /// v7: A time window of a repeating plan that doesn't fit in one period
+    TimeWindowOutsidePeriod {
+        start: f64,
+        end: f64,
+        period: f64,
+    },
+    /// v7: A value outside its allowed inclusive range
+    ValueOutOfRange {
+        field: String,
+        value: f64,
+        min: f64,
+        max: f64,
+    },
 
     // Placement errors
     PlacementLevelOutOfBounds {
@@ -125,6 +143,22 @@ pub enum ValidationErrorKind {
     EmptyCollection {
         collection: String,
     },
+    /// v7: A field the chosen option can't run without
+    MissingField {
+        field: String,
+        required_by: String,
+    },
+    /// v7: A path that doesn't name a readable file
+    FileNotFound {
+        field: String,
+        path: String,
+    },
+    /// v7: A value the simulation it is loaded into can't honor
+    Unsupported {
+        field: String,
+        value: String,
+        reason: String,
+    },
 }
 
 impl fmt::Display for ValidationErrorKind {
@@ -145,6 +179,32 @@ impl fmt::Display for ValidationErrorKind {
             Self::IntValueMustBePositive { field, value } => {
                 write!(f, "{} must be positive, got {}", field, value)
             }
+            Self::InvalidTimeWindow { start, end } => {
+                write!(
+                    f,
+                    "Time window end ({}) must be after start ({})",
+                    end, start
+                )
+            }
+            Self::TimeWindowOutsidePeriod { start, end, period } => {
+                write!(
+                    f,
+                    "Time window {}-{} must fit within the repeat period (0-{})",
+                    start, end, period
+                )
+            }
+            Self::ValueOutOfRange {
+                field,
+                value,
+                min,
+                max,
+            } => {
+                write!(
+                    f,
+                    "{} must be between {} and {}, got {}",
+                    field, min, max, value
+                )
+            }
             Self::PlacementLevelOutOfBounds {
                 level,
                 max_level,
@@ -185,6 +245,19 @@ impl fmt::Display for ValidationErrorKind {
             Self::EmptyCollection { collection } => {
                 write!(f, "{} cannot be empty", collection)
             }
+            Self::MissingField { field, required_by } => {
+                write!(f, "{} is required by {}", field, required_by)
+            }
+            Self::FileNotFound { field, path } => {
+                write!(f, "{} '{}' is not a readable file", field, path)
+            }
+            Self::Unsupported {
+                field,
+                value,
+                reason,
+            } => {
+                write!(f, "{} '{}' is not supported: {}", field, value, reason)
+            }
         }
     }
 }
@@ -397,6 +470,86 @@ fn validate_scenario_standalone(scenario: &ScenarioConfig, ctx: &mut ValidationE
                 },
             ));
         }
+
+        // v7: Check staffing windows
+        if let Some(staffing) = &station.staffing {
+            let staffing_path = station_path.field("staffing");
+            if let Some(period) = staffing.repeat_every_min {
+                if period <= 0.0 {
+                    ctx.add_error(ValidationError::new(
+                        staffing_path.field("repeat_every_min"),
+                        ValidationErrorKind::ValueMustBePositive {
+                            field: "repeat_every_min".to_string(),
+                            value: period,
+                        },
+                    ));
+                }
+            }
+            let windows = staffing
+                .shifts
+                .iter()
+                .enumerate()
+                .map(|(j, w)| {
+                    (
+                        staffing_path.field("shifts").index(j),
+                        w.start_min,
+                        w.end_min,
+                    )
+                })
+                .chain(staffing.breaks.iter().enumerate().map(|(j, w)| {
+                    (
+                        staffing_path.field("breaks").index(j),
+                        w.start_min,
+                        w.end_min,
+                    )
+                }));
+            for (window_path, start, end) in windows {
+                if start < 0.0 {
+                    ctx.add_error(ValidationError::new(
+                        window_path.field("start_min"),
+                        ValidationErrorKind::ValueMustBeNonNegative {
+                            field: "start_min".to_string(),
+                            value: start,
+                        },
+                    ));
+                }
+                if end <= start {
+                    ctx.add_error(ValidationError::new(
+                        window_path,
+                        ValidationErrorKind::InvalidTimeWindow { start, end },
+                    ));
+                } else if let Some(period) = staffing.repeat_every_min.filter(|&p| p > 0.0) {
+                    // A repeating plan only looks at times within one period
+                    if end > period {
+                        ctx.add_error(ValidationError::new(
+                            window_path,
+                            ValidationErrorKind::TimeWindowOutsidePeriod { start, end, period },
+                        ));
+                    }
+                }
+            }
+        }
+
+        // v7: Check productivity model
+        let productivity = &station.productivity;
+        if productivity.learning_initial_multiplier <= 0.0 {
+            ctx.add_error(ValidationError::new(
+                station_path.field("productivity.learning_initial_multiplier"),
+                ValidationErrorKind::ValueMustBePositive {
+                    field: "learning_initial_multiplier".to_string(),
+                    value: productivity.learning_initial_multiplier,
+                },
+            ));
+        }
+        if productivity.fatigue_per_hour < 0.0 {
+            ctx.add_error(ValidationError::new(
+                station_path.field("productivity.fatigue_per_hour"),
+                ValidationErrorKind::ValueMustBeNonNegative {
+                    field: "fatigue_per_hour".to_string(),
+                    value: productivity.fatigue_per_hour,
+                },
+            ));
+        }
     }
 
     // Orders config
@@ -438,6 +591,55 @@ fn validate_scenario_standalone(scenario: &ScenarioConfig, ctx: &mut ValidationE
         ));
     }
 
+    // v7: Human agents
+    let humans = root.field("humans");
+    for (i, human) in scenario.humans.iter().enumerate() {
+        let human_path = humans.index(i);
+        if human.count == 0 {
+            ctx.add_error(ValidationError::new(
+                human_path.field("count"),
+                ValidationErrorKind::IntValueMustBePositive {
+                    field: "count".to_string(),
+                    value: 0,
+                },
+            ));
+        }
+        if let Some(speed) = human.speed_m_s {
+            if speed <= 0.0 {
+                ctx.add_error(ValidationError::new(
+                    human_path.field("speed_m_s"),
+                    ValidationErrorKind::ValueMustBePositive {
+                        field: "speed_m_s".to_string(),
+                        value: speed,
+                    },
+                ));
+            }
+        }
+        if human.slow_factor <= 0.0 {
+            ctx.add_error(ValidationError::new(
+                human_path.field("slow_factor"),
+                ValidationErrorKind::ValueMustBePositive {
+                    field: "slow_factor".to_string(),
+                    value: human.slow_factor,
+                },
+            ));
+        }
+    }
+
+    // v7: Human-only zones
+    let zones = root.field("human_zones");
+    for (i, zone) in scenario.human_zones.iter().enumerate() {
+        if zone.end_min <= zone.start_min {
+            ctx.add_error(ValidationError::new(
+                zones.index(i),
+                ValidationErrorKind::InvalidTimeWindow {
+                    start: zone.start_min,
+                    end: zone.end_min,
+                },
+            ));
+        }
+    }
+
     // Traffic config
     let traffic = root.field("traffic");
     if scenario.traffic.edge_capacity_default == 0 {
@@ -458,6 +660,114 @@ fn validate_scenario_standalone(scenario: &ScenarioConfig, ctx: &mut ValidationE
             },
         ));
     }
+
+    // v7: The learned policy needs its weights; whether they parse is
+    // checked when the policy is built
+    let allocation = &scenario.policies.task_allocation;
+    if allocation.alloc_type == "learned" {
+        let weights = root
+            .field("policies")
+            .field("task_allocation")
+            .field("weights_path");
+        match allocation.weights_path.as_deref() {
+            None => ctx.add_error(ValidationError::new(
+                weights,
+                ValidationErrorKind::MissingField {
+                    field: "weights_path".to_string(),
+                    required_by: "the 'learned' task allocation".to_string(),
+                },
+            )),
+            Some(path) if !std::path::Path::new(path).is_file() => {
+                ctx.add_error(ValidationError::new(
+                    weights,
+                    ValidationErrorKind::FileNotFound {
+                        field: "weights_path".to_string(),
+                        path: path.to_string(),
+                    },
+                ))
+            }
+            Some(_) => {}
+        }
+    }
+
+    // v7: Level access times and bin selection weights
+    let storage = root.field("storage");
+    let bin_selection = root.field("policies").field("bin_selection");
+    if scenario.storage.base_access_time_s < 0.0 {
+        ctx.add_error(ValidationError::new(
+            storage.field("base_access_time_s"),
+            ValidationErrorKind::ValueMustBeNonNegative {
+                field: "base_access_time_s".to_string(),
+                value: scenario.storage.base_access_time_s,
+            },
+        ));
+    }
+    if scenario.storage.per_level_time_s < 0.0 {
+        ctx.add_error(ValidationError::new(
+            storage.field("per_level_time_s"),
+            ValidationErrorKind::ValueMustBeNonNegative {
+                field: "per_level_time_s".to_string(),
+                value: scenario.storage.per_level_time_s,
+            },
+        ));
+    }
+    if scenario.policies.bin_selection.level_weight < 0.0 {
+        ctx.add_error(ValidationError::new(
+            bin_selection.field("level_weight"),
+            ValidationErrorKind::ValueMustBeNonNegative {
+                field: "level_weight".to_string(),
+                value: scenario.policies.bin_selection.level_weight,
+            },
+        ));
+    }
+    if scenario.policies.bin_selection.consolidation_weight_s < 0.0 {
+        ctx.add_error(ValidationError::new(
+            bin_selection.field("consolidation_weight_s"),
+            ValidationErrorKind::ValueMustBeNonNegative {
+                field: "consolidation_weight_s".to_string(),
+                value: scenario.policies.bin_selection.consolidation_weight_s,
+            },
+        ));
+    }
+
+    // v7: Dynamic slotting
+    if let Some(slotting) = &scenario.slotting {
+        let slotting_path = root.field("slotting");
+        for (field, value) in [
+            ("interval_min", slotting.interval_min),
+            ("velocity_window_min", slotting.velocity_window_min),
+        ] {
+            if value <= 0.0 {
+                ctx.add_error(ValidationError::new(
+                    slotting_path.field(field),
+                    ValidationErrorKind::ValueMustBePositive {
+                        field: field.to_string(),
+                        value,
+                    },
+                ));
+            }
+        }
+        if slotting.moves_per_hour < 0.0 {
+            ctx.add_error(ValidationError::new(
+                slotting_path.field("moves_per_hour"),
+                ValidationErrorKind::ValueMustBeNonNegative {
+                    field: "moves_per_hour".to_string(),
+                    value: slotting.moves_per_hour,
+                },
+            ));
+        }
+        if !(0.0..=1.0).contains(&slotting.a_fraction) {
+            ctx.add_error(ValidationError::new(
+                slotting_path.field("a_fraction"),
+                ValidationErrorKind::ValueOutOfRange {
+                    field: "a_fraction".to_string(),
+                    value: slotting.a_fraction,
+                    min: 0.0,
+                    max: 1.0,
+                },
+            ));
+        }
+    }
 }
 
 fn validate_map_standalone(map: &MapConfig, ctx: &mut ValidationErrors) {
@@ -620,6 +930,30 @@ fn validate_cross_references(
         }
     }
 
+    // v7: Validate human home nodes and zone nodes exist in map
+    for (i, human) in scenario.humans.iter().enumerate() {
+        if !node_ids.contains(human.home_node.as_str()) {
+            ctx.add_error(ValidationError::new(
+                FieldPath::new(&format!("scenario.humans[{}].home_node", i)),
+                ValidationErrorKind::NodeNotFound {
+                    node_id: human.home_node.clone(),
+                },
+            ));
+        }
+    }
+    for (i, zone) in scenario.human_zones.iter().enumerate() {
+        for (j, node) in zone.nodes.iter().enumerate() {
+            if !node_ids.contains(node.as_str()) {
+                ctx.add_error(ValidationError::new(
+                    FieldPath::new(&format!("scenario.human_zones[{}].nodes[{}]", i, j)),
+                    ValidationErrorKind::NodeNotFound {
+                        node_id: node.clone(),
+                    },
+                ));
+            }
+        }
+    }
+
     // Validate rack access_nodes exist in map
     for (i, rack) in storage.racks.iter().enumerate() {
         if !node_ids.contains(rack.access_node.as_str()) {
@@ -712,6 +1046,8 @@ mod tests {
             },
             storage: StorageRef {
                 file: "storage.yaml".to_string(),
+                base_access_time_s: 0.0,
+                per_level_time_s: 0.0,
             },
             robots: RobotConfig {
                 count: 5,
@@ -728,6 +1064,8 @@ mod tests {
                 concurrency: 2,
                 queue_capacity: None,
                 service_time_s: ServiceTimeConfig::constant(5.0, 2.0),
+                staffing: None,
+                productivity: ProductivityConfig::default(),
             }],
             orders: OrderConfig {
                 arrival_process: ArrivalProcess {
@@ -756,6 +1094,9 @@ mod tests {
             charging_stations: vec![],
             metrics: MetricsConfig::default(),
             maintenance_stations: vec![],
+            humans: vec![],
+            human_zones: vec![],
+            slotting: None,
         }
     }
 
@@ -814,6 +1155,8 @@ mod tests {
             concurrency: 1,
             queue_capacity: None,
             service_time_s: ServiceTimeConfig::constant(3.0, 1.0),
+            staffing: None,
+            productivity: ProductivityConfig::default(),
         });
         let result = validate_scenario_only(&scenario);
         assert!(result.is_err());
@@ -848,6 +1191,127 @@ mod tests {
         )));
     }
 
+    #[test]
+    fn test_invalid_staffing_window_rejected() {
+        let mut scenario = minimal_scenario();
+        scenario.stations[0].staffing = Some(StaffingConfig {
+            repeat_every_min: Some(1440.0),
+            shifts: vec![ShiftConfig {
+                start_min: 0.0,
+                end_min: 480.0,
+                operators: 2,
+            }],
+            breaks: vec![BreakConfig {
+                start_min: 240.0,
+                end_min: 200.0,
+                operators: 1,
+            }],
+        });
+        let result = validate_scenario_only(&scenario);
+        assert!(result.is_err());
+        let errors = result.unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            errors[0].kind,
+            ValidationErrorKind::InvalidTimeWindow { .. }
+        ));
+        assert_eq!(
+            errors[0].path.to_string(),
+            "scenario.stations[0].staffing.breaks[0]"
+        );
+    }
+
+    #[test]
+    fn test_staffing_window_outside_period_rejected() {
+        let mut scenario = minimal_scenario();
+        scenario.stations[0].staffing = Some(StaffingConfig {
+            repeat_every_min: Some(480.0),
+            shifts: vec![
+                ShiftConfig {
+                    start_min: 0.0,
+                    end_min: 480.0,
+                    operators: 2,
+                },
+                ShiftConfig {
+                    start_min: 420.0,
+                    end_min: 540.0,
+                    operators: 1,
+                },
+            ],
+            breaks: vec![BreakConfig {
+                start_min: 500.0,
+                end_min: 520.0,
+                operators: 1,
+            }],
+        });
+        let errors = validate_scenario_only(&scenario).unwrap_err();
+        let paths: Vec<_> = errors.iter().map(|e| e.path.to_string()).collect();
+        assert_eq!(
+            paths,
+            [
+                "scenario.stations[0].staffing.shifts[1]",
+                "scenario.stations[0].staffing.breaks[0]"
+            ]
+        );
+        assert!(errors.iter().all(|e| matches!(
+            e.kind,
+            ValidationErrorKind::TimeWindowOutsidePeriod { period, .. } if period == 480.0
+        )));
+
+        // Without a period any window goes
+        scenario.stations[0]
+            .staffing
+            .as_mut()
+            .unwrap()
+            .repeat_every_min = None;
+        assert!(validate_scenario_only(&scenario).is_ok());
+    }
+
+    #[test]
+    fn test_learned_allocation_needs_readable_weights() {
+        let mut scenario = minimal_scenario();
+        scenario.policies.task_allocation.alloc_type = "learned".to_string();
+        let errors = validate_scenario_only(&scenario).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].path.to_string(),
+            "scenario.policies.task_allocation.weights_path"
+        );
+        assert!(matches!(
+            errors[0].kind,
+            ValidationErrorKind::MissingField { .. }
+        ));
+
+        scenario.policies.task_allocation.weights_path = Some("does/not/exist.json".to_string());
+        let errors = validate_scenario_only(&scenario).unwrap_err();
+        assert!(matches!(
+            &errors[0].kind,
+            ValidationErrorKind::FileNotFound { path, .. } if path == "does/not/exist.json"
+        ));
+
+        // Any readable file passes here; its contents are checked on load
+        scenario.policies.task_allocation.weights_path = Some("Cargo.toml".to_string());
+        assert!(validate_scenario_only(&scenario).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_slotting_fraction_rejected() {
+        let mut scenario = minimal_scenario();
+        scenario.slotting = Some(SlottingConfig {
+            a_fraction: 1.5,
+            ..SlottingConfig::default()
+        });
+        let result = validate_scenario_only(&scenario);
+        assert!(result.is_err());
+        let errors = result.unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            errors[0].kind,
+            ValidationErrorKind::ValueOutOfRange { .. }
+        ));
+        assert_eq!(errors[0].path.to_string(), "scenario.slotting.a_fraction");
+    }
+
     #[test]
     fn test_map_edge_references_invalid_node() {
         use crate::map_config::*;
@@ -1,8 +1,8 @@
 //! Simulation events for the discrete-event simulation
 
 use crate::{
-    BinId, ChargingStationId, EdgeId, EventId, MaintenanceStationId, NodeId, OrderId, RobotId,
-    ShipmentId, SimTime, SkuId, StationId, TaskId,
+    BinId, ChargingStationId, EdgeId, EventId, HumanId, MaintenanceStationId, NodeId, OrderId,
+    RobotId, ShipmentId, SimTime, SkuId, StationId, TaskId,
 };
 use rkyv::{Archive, Deserialize, Serialize};
 use std::cmp::Ordering;
@@ -182,6 +182,38 @@ pub enum SimEvent {
         /// Duration of the maintenance/repair in seconds
         duration_s: f64,
     },
+
+    // === v7: Station Labor Events ===
+    /// Station staffing level changes (shift start/end, break start/end)
+    StationStaffingChange { station_id: StationId },
+
+    // === v7: Human Worker & Manual Vehicle Events ===
+    /// Human agent starts its next manual task
+    HumanTaskStart { human_id: HumanId },
+
+    /// Human agent arrives at a node
+    HumanArriveNode {
+        human_id: HumanId,
+        node_id: NodeId,
+        from_node: NodeId,
+    },
+
+    /// Human agent finishes working at its task location
+    HumanWorkEnd { human_id: HumanId },
+
+    /// A human-only zone opens or closes
+    HumanZoneUpdate,
+
+    // === v7: Dynamic Slotting Events ===
+    /// Periodic re-slotting review: recompute SKU velocity and plan moves
+    SlottingReview,
+
+    /// Robot finishes placing a relocated bin's contents in its new slot
+    RobotDropOff {
+        robot_id: RobotId,
+        task_id: TaskId,
+        node_id: NodeId,
+    },
 }
 
 impl SimEvent {
@@ -221,6 +253,16 @@ impl SimEvent {
             SimEvent::RobotMaintenanceDue { .. } => "robot_maintenance_due",
             SimEvent::MaintenanceStart { .. } => "maintenance_start",
             SimEvent::MaintenanceEnd { .. } => "maintenance_end",
+            // v7: Station labor events
+            SimEvent::StationStaffingChange { .. } => "station_staffing_change",
+            // v7: Human worker events
+            SimEvent::HumanTaskStart { .. } => "human_task_start",
+            SimEvent::HumanArriveNode { .. } => "human_arrive_node",
+            SimEvent::HumanWorkEnd { .. } => "human_work_end",
+            SimEvent::HumanZoneUpdate => "human_zone_update",
+            // v7: Dynamic slotting events
+            SimEvent::SlottingReview => "slotting_review",
+            SimEvent::RobotDropOff { .. } => "robot_drop_off",
         }
     }
 
@@ -235,6 +277,7 @@ impl SimEvent {
             SimEvent::RobotWaitStart { robot_id, .. } => Some(*robot_id),
             SimEvent::RobotWaitEnd { robot_id, .. } => Some(*robot_id),
             SimEvent::RobotPickup { robot_id, .. } => Some(*robot_id),
+            SimEvent::RobotDropOff { robot_id, .. } => Some(*robot_id),
             SimEvent::RobotChargingStart { robot_id, .. } => Some(*robot_id),
             SimEvent::RobotChargingEnd { robot_id, .. } => Some(*robot_id),
             SimEvent::RobotLowBattery { robot_id, .. } => Some(*robot_id),
@@ -256,6 +299,7 @@ impl SimEvent {
             SimEvent::StationServiceEnd { task_id, .. } => Some(*task_id),
             SimEvent::InventoryUpdate { task_id, .. } => Some(*task_id),
             SimEvent::RobotPickup { task_id, .. } => Some(*task_id),
+            SimEvent::RobotDropOff { task_id, .. } => Some(*task_id),
             SimEvent::PutawayTaskCreated { task_id, .. } => Some(*task_id),
             _ => None,
         }
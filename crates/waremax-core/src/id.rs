@@ -87,6 +87,10 @@ define_id!(
     MaintenanceStationId,
     "Unique identifier for a maintenance station"
 );
+define_id!(
+    HumanId,
+    "Unique identifier for a human worker or manual vehicle"
+);
 
 /// ID generator for creating sequential IDs
 #[derive(Debug, Clone, Default)]
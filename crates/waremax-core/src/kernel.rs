@@ -7,7 +7,7 @@ use crate::{EventId, IdGenerator, ScheduledEvent, SimEvent, SimTime};
 use std::collections::BinaryHeap;
 
 /// The discrete-event simulation kernel
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Kernel {
     /// Priority queue of scheduled events (min-heap by time)
     event_queue: BinaryHeap<ScheduledEvent>,
@@ -161,11 +161,10 @@ impl Kernel {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::OrderId;
 
     #[test]
     fn test_kernel_basic() {
-        let mut kernel = Kernel::new();
+        let kernel = Kernel::new();
         assert_eq!(kernel.now(), SimTime::ZERO);
         assert!(!kernel.has_events());
     }
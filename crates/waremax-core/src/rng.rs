@@ -5,7 +5,7 @@ use rand_chacha::ChaCha8Rng;
 use rand_distr::{Distribution, Exp, Gamma, Poisson};
 
 /// Seeded RNG wrapper for deterministic simulation
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct SimRng {
     rng: ChaCha8Rng,
 }
@@ -0,0 +1,218 @@
+//! Human workers and manual vehicles sharing the aisle network with robots (v7)
+//!
+//! Humans are a separate agent class: they follow their own task source,
+//! walk the same graph without obeying robot capacity rules, and impose
+//! safety restrictions (stop/slow radii, reserved zones) on robots instead.
+
+use std::collections::VecDeque;
+use waremax_core::{EdgeId, HumanId, NodeId, SimTime};
+use waremax_storage::BinAddress;
+
+/// Kind of human agent
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HumanAgentType {
+    /// Person on foot
+    Picker,
+    /// Manually driven vehicle (forklift, pallet truck)
+    Forklift,
+}
+
+impl HumanAgentType {
+    /// Typical travel speed (m/s)
+    pub fn default_speed(&self) -> f64 {
+        match self {
+            HumanAgentType::Picker => 1.2,
+            HumanAgentType::Forklift => 2.5,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            HumanAgentType::Picker => "picker",
+            HumanAgentType::Forklift => "forklift",
+        }
+    }
+}
+
+/// Where a human agent's work comes from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HumanTaskSource {
+    /// Manual picks from random storage locations
+    ManualPick,
+    /// Restocking the lowest-stocked bins
+    Replenishment,
+}
+
+/// Robot safety behavior around a human agent
+#[derive(Clone, Debug, PartialEq)]
+pub struct HumanSafetyZone {
+    /// Robots stop rather than enter nodes within this distance (m)
+    pub stop_radius_m: f64,
+    /// Robots slow down on edges touching nodes within this distance (m)
+    pub slow_radius_m: f64,
+    /// Speed factor applied to robots inside the slow radius (0-1]
+    pub slow_factor: f64,
+}
+
+/// Phase of a human agent's current trip
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HumanTripPhase {
+    /// Heading to the task location
+    ToTarget,
+    /// Heading back to the home node
+    Returning,
+}
+
+/// Human agent state
+#[derive(Clone, Debug, PartialEq)]
+pub enum HumanState {
+    Idle,
+    Walking { destination: NodeId },
+    Working { at_node: NodeId },
+}
+
+/// A human worker or manual vehicle
+#[derive(Clone, Debug)]
+pub struct HumanAgent {
+    pub id: HumanId,
+    pub string_id: String,
+    pub agent_type: HumanAgentType,
+    pub task_source: HumanTaskSource,
+    pub home_node: NodeId,
+    pub speed_m_s: f64,
+    /// Mean idle time between tasks (seconds)
+    pub task_interval_s: f64,
+    /// Time spent working at the task location (seconds)
+    pub work_time_s: f64,
+    /// Units picked or restocked per task
+    pub quantity: u32,
+    pub safety: HumanSafetyZone,
+
+    // Runtime state
+    pub state: HumanState,
+    pub current_node: NodeId,
+    /// Edge being traversed (edge, from, to)
+    pub on_edge: Option<(EdgeId, NodeId, NodeId)>,
+    pub path: VecDeque<NodeId>,
+    pub phase: HumanTripPhase,
+    pub target_bin: Option<BinAddress>,
+
+    // Statistics
+    pub tasks_completed: u32,
+    pub distance_m: f64,
+    pub walking_time: SimTime,
+    pub working_time: SimTime,
+}
+
+impl HumanAgent {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        id: HumanId,
+        string_id: String,
+        agent_type: HumanAgentType,
+        task_source: HumanTaskSource,
+        home_node: NodeId,
+        speed_m_s: f64,
+        safety: HumanSafetyZone,
+    ) -> Self {
+        Self {
+            id,
+            string_id,
+            agent_type,
+            task_source,
+            home_node,
+            speed_m_s,
+            task_interval_s: 60.0,
+            work_time_s: 20.0,
+            quantity: 1,
+            safety,
+            state: HumanState::Idle,
+            current_node: home_node,
+            on_edge: None,
+            path: VecDeque::new(),
+            phase: HumanTripPhase::ToTarget,
+            target_bin: None,
+            tasks_completed: 0,
+            distance_m: 0.0,
+            walking_time: SimTime::ZERO,
+            working_time: SimTime::ZERO,
+        }
+    }
+
+    /// Set task pacing: mean idle gap, work time per task and units per task
+    pub fn with_task_timing(
+        mut self,
+        task_interval_s: f64,
+        work_time_s: f64,
+        quantity: u32,
+    ) -> Self {
+        self.task_interval_s = task_interval_s;
+        self.work_time_s = work_time_s;
+        self.quantity = quantity;
+        self
+    }
+
+    /// Time to walk/drive a distance
+    pub fn travel_time(&self, distance_m: f64) -> SimTime {
+        SimTime::from_seconds(distance_m / self.speed_m_s.max(0.01))
+    }
+
+    pub fn is_idle(&self) -> bool {
+        matches!(self.state, HumanState::Idle)
+    }
+
+    /// Nodes the agent physically occupies (both ends while on an edge)
+    pub fn occupied_nodes(&self) -> Vec<NodeId> {
+        match self.on_edge {
+            Some((_, from, to)) => vec![from, to],
+            None => vec![self.current_node],
+        }
+    }
+}
+
+/// A set of nodes reserved for humans during a time window
+///
+/// Robots may not enter the zone's nodes while it is active. With
+/// `period_s` set the window repeats (e.g. every shift).
+#[derive(Clone, Debug, PartialEq)]
+pub struct HumanZone {
+    pub id: String,
+    pub nodes: Vec<NodeId>,
+    pub start_s: f64,
+    pub end_s: f64,
+    pub period_s: Option<f64>,
+}
+
+impl HumanZone {
+    fn local_seconds(&self, time: SimTime) -> f64 {
+        let t = time.as_seconds();
+        match self.period_s {
+            Some(period) if period > 0.0 => t.rem_euclid(period),
+            _ => t,
+        }
+    }
+
+    /// Whether the zone is reserved at `time`
+    pub fn is_active(&self, time: SimTime) -> bool {
+        let t = self.local_seconds(time);
+        self.start_s <= t && t < self.end_s
+    }
+
+    /// Next time strictly after `time` at which the zone opens or closes
+    pub fn next_change_after(&self, time: SimTime) -> Option<SimTime> {
+        let t = self.local_seconds(time);
+        let cycle_start = time.as_seconds() - t;
+        if t < self.start_s {
+            return Some(SimTime::from_seconds(cycle_start + self.start_s));
+        }
+        if t < self.end_s {
+            return Some(SimTime::from_seconds(cycle_start + self.end_s));
+        }
+        match self.period_s {
+            Some(period) if period > 0.0 => {
+                Some(SimTime::from_seconds(cycle_start + period + self.start_s))
+            }
+            _ => None,
+        }
+    }
+}
@@ -1,6 +1,7 @@
 //! Waremax Entities - Robots, stations, orders, and tasks
 
 pub mod charging_station;
+pub mod human;
 pub mod maintenance_station;
 pub mod order;
 pub mod robot;
@@ -8,10 +9,17 @@ pub mod station;
 pub mod task;
 
 pub use charging_station::ChargingStation;
+pub use human::{
+    HumanAgent, HumanAgentType, HumanSafetyZone, HumanState, HumanTaskSource, HumanTripPhase,
+    HumanZone,
+};
 pub use maintenance_station::MaintenanceStation;
 pub use order::{Order, OrderLine, OrderStatus};
 pub use robot::{
     BatteryConsumptionModel, BatteryState, FailureModel, MaintenanceState, Robot, RobotState,
 };
-pub use station::{ServiceTimeModel, Station, StationType};
+pub use station::{
+    OperatorProductivity, ServiceTimeModel, StaffingBreak, StaffingSchedule, StaffingShift,
+    Station, StationType,
+};
 pub use task::{BinLocation, Task, TaskStatus, TaskType};
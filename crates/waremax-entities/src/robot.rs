@@ -270,6 +270,9 @@ pub struct Robot {
     pub seeking_maintenance: bool,
     pub assigned_maintenance_station: Option<MaintenanceStationId>,
 
+    // v7: Idle repositioning target (robot stays assignable while en route)
+    pub repositioning_to: Option<NodeId>,
+
     // Statistics
     pub total_distance: f64,
     pub total_idle_time: SimTime,
@@ -309,6 +312,7 @@ impl Robot {
             failure_model: FailureModel::default(),
             seeking_maintenance: false,
             assigned_maintenance_station: None,
+            repositioning_to: None,
             total_distance: 0.0,
             total_idle_time: SimTime::ZERO,
             total_wait_time: SimTime::ZERO,
@@ -345,8 +349,13 @@ impl Robot {
         matches!(self.state, RobotState::Idle)
     }
 
+    /// v7: Whether the robot is driving to a parking node while idle
+    pub fn is_repositioning(&self) -> bool {
+        self.repositioning_to.is_some()
+    }
+
     pub fn is_available(&self) -> bool {
-        self.is_idle()
+        (self.is_idle() || self.is_repositioning())
             && self.current_task.is_none()
             && !self.maintenance.is_failed
             && !self.maintenance.needs_maintenance()
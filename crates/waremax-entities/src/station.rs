@@ -152,6 +152,136 @@ impl Default for ServiceTimeModel {
     }
 }
 
+/// v7: An interval during which operators staff a station
+#[derive(Clone, Debug, PartialEq)]
+pub struct StaffingShift {
+    pub start_s: f64,
+    pub end_s: f64,
+    pub operators: u32,
+}
+
+/// v7: An interval during which some of the shift's operators are on break
+#[derive(Clone, Debug, PartialEq)]
+pub struct StaffingBreak {
+    pub start_s: f64,
+    pub end_s: f64,
+    pub operators: u32,
+}
+
+/// v7: Staffing plan of a station
+///
+/// The staffing level at a time is the operators on shift minus those on
+/// break; it sets the station's concurrency, and a level of zero closes the
+/// station. With `period_s` set the plan repeats (e.g. daily).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StaffingSchedule {
+    pub shifts: Vec<StaffingShift>,
+    pub breaks: Vec<StaffingBreak>,
+    pub period_s: Option<f64>,
+}
+
+impl StaffingSchedule {
+    /// Fold an absolute time into the schedule's period
+    fn local_seconds(&self, time: SimTime) -> f64 {
+        let t = time.as_seconds();
+        match self.period_s {
+            Some(period) if period > 0.0 => t.rem_euclid(period),
+            _ => t,
+        }
+    }
+
+    /// Number of operators working at `time`
+    pub fn operators_at(&self, time: SimTime) -> u32 {
+        let t = self.local_seconds(time);
+        let on_shift: u32 = self
+            .shifts
+            .iter()
+            .filter(|s| s.start_s <= t && t < s.end_s)
+            .map(|s| s.operators)
+            .sum();
+        let on_break: u32 = self
+            .breaks
+            .iter()
+            .filter(|b| b.start_s <= t && t < b.end_s)
+            .map(|b| b.operators)
+            .sum();
+        on_shift.saturating_sub(on_break)
+    }
+
+    /// Whether a shift starts at `time`
+    pub fn shift_starts_at(&self, time: SimTime) -> bool {
+        let t = self.local_seconds(time);
+        self.shifts.iter().any(|s| (s.start_s - t).abs() < 1e-6)
+    }
+
+    /// Next time strictly after `time` at which the staffing level may change
+    pub fn next_change_after(&self, time: SimTime) -> Option<SimTime> {
+        let mut boundaries: Vec<f64> = self
+            .shifts
+            .iter()
+            .flat_map(|s| [s.start_s, s.end_s])
+            .chain(self.breaks.iter().flat_map(|b| [b.start_s, b.end_s]))
+            .collect();
+        boundaries.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+        let now = time.as_seconds();
+        let t = self.local_seconds(time);
+        let cycle_start = now - t;
+
+        if let Some(next) = boundaries.iter().find(|&&b| b > t) {
+            return Some(SimTime::from_seconds(cycle_start + next));
+        }
+        match self.period_s {
+            Some(period) if period > 0.0 => boundaries
+                .iter()
+                .find(|&&b| b >= 0.0 && b < period)
+                .map(|b| SimTime::from_seconds(cycle_start + period + b)),
+            _ => None,
+        }
+    }
+}
+
+/// v7: Operator learning and fatigue effects on service time
+///
+/// The service time multiplier is the product of a learning term, which
+/// starts at `learning_initial_multiplier` and halves its excess over 1.0
+/// every `learning_half_life_tasks` tasks the station serves, and a fatigue
+/// term growing by `fatigue_per_hour` per hour on duty (reset at every shift
+/// start, as a fresh crew takes over), capped at `max_fatigue_multiplier`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OperatorProductivity {
+    pub learning_initial_multiplier: f64,
+    pub learning_half_life_tasks: f64,
+    pub fatigue_per_hour: f64,
+    pub max_fatigue_multiplier: f64,
+}
+
+impl OperatorProductivity {
+    /// Multiplier after `tasks_served` tasks and `hours_on_duty` hours
+    pub fn multiplier(&self, tasks_served: u32, hours_on_duty: f64) -> f64 {
+        let learning = if self.learning_half_life_tasks > 0.0 {
+            let excess = (self.learning_initial_multiplier - 1.0).max(0.0);
+            1.0 + excess * 0.5f64.powf(tasks_served as f64 / self.learning_half_life_tasks)
+        } else {
+            1.0
+        };
+        let fatigue = (1.0 + self.fatigue_per_hour * hours_on_duty.max(0.0))
+            .min(self.max_fatigue_multiplier.max(1.0));
+        learning * fatigue
+    }
+}
+
+impl Default for OperatorProductivity {
+    fn default() -> Self {
+        Self {
+            learning_initial_multiplier: 1.0,
+            learning_half_life_tasks: 0.0,
+            fatigue_per_hour: 0.0,
+            max_fatigue_multiplier: 1.0,
+        }
+    }
+}
+
 /// A station in the warehouse
 #[derive(Clone, Debug)]
 pub struct Station {
@@ -163,15 +293,30 @@ pub struct Station {
     pub queue_capacity: Option<u32>,
     pub service_time: ServiceTimeModel,
 
+    // v7: Labor model
+    pub staffing: Option<StaffingSchedule>,
+    pub productivity: OperatorProductivity,
+
     // Runtime state
     pub queue: VecDeque<RobotId>,
     pub serving: Vec<RobotId>,
+    /// v7: Manual open/close switch (independent of staffing)
+    pub open: bool,
+    /// v7: Operators currently working (only meaningful with a staffing schedule)
+    pub staffed_operators: u32,
+    /// v7: Start of the crew's current stretch on duty (fatigue clock)
+    pub on_duty_since: SimTime,
+    /// v7: Service duration decided at service start, per robot being served
+    pub service_durations: Vec<(RobotId, SimTime)>,
 
     // Statistics
     pub total_served: u32,
     pub total_service_time: SimTime,
     pub total_queue_time: SimTime,
     pub max_queue_length: usize,
+    /// v7: Operator-seconds staffed up to `last_staffing_change`
+    pub staffed_seconds: f64,
+    pub last_staffing_change: SimTime,
 }
 
 impl Station {
@@ -192,13 +337,92 @@ impl Station {
             concurrency,
             queue_capacity,
             service_time,
+            staffing: None,
+            productivity: OperatorProductivity::default(),
             queue: VecDeque::new(),
             serving: Vec::new(),
+            open: true,
+            staffed_operators: concurrency,
+            on_duty_since: SimTime::ZERO,
+            service_durations: Vec::new(),
             total_served: 0,
             total_service_time: SimTime::ZERO,
             total_queue_time: SimTime::ZERO,
             max_queue_length: 0,
+            staffed_seconds: 0.0,
+            last_staffing_change: SimTime::ZERO,
+        }
+    }
+
+    /// v7: Attach a staffing schedule (staffing level at time zero applies immediately)
+    pub fn with_staffing(mut self, schedule: StaffingSchedule) -> Self {
+        self.staffed_operators = schedule.operators_at(SimTime::ZERO);
+        self.staffing = Some(schedule);
+        self
+    }
+
+    /// v7: Set the operator learning/fatigue model
+    pub fn with_productivity(mut self, productivity: OperatorProductivity) -> Self {
+        self.productivity = productivity;
+        self
+    }
+
+    /// v7: Number of robots that can be served at once right now
+    pub fn effective_concurrency(&self) -> u32 {
+        if self.staffing.is_some() {
+            self.staffed_operators
+        } else {
+            self.concurrency
+        }
+    }
+
+    /// v7: Whether the station is open and staffed
+    pub fn is_open(&self) -> bool {
+        self.open && self.effective_concurrency() > 0
+    }
+
+    /// v7: Change the staffing level, accumulating the operator time so far.
+    /// A shift starting resets the crew's fatigue clock, whatever the headcount.
+    pub fn set_staffing(&mut self, operators: u32, now: SimTime, shift_start: bool) {
+        self.staffed_seconds +=
+            self.staffed_operators as f64 * (now - self.last_staffing_change).as_seconds().max(0.0);
+        self.last_staffing_change = now;
+        if shift_start {
+            self.on_duty_since = now;
         }
+        self.staffed_operators = operators;
+    }
+
+    /// v7: Operator-seconds staffed up to `now` (None without a staffing schedule)
+    pub fn staffed_seconds_until(&self, now: SimTime) -> Option<f64> {
+        self.staffing.as_ref()?;
+        Some(
+            self.staffed_seconds
+                + self.staffed_operators as f64
+                    * (now - self.last_staffing_change).as_seconds().max(0.0),
+        )
+    }
+
+    /// v7: Service time multiplier from operator learning and fatigue at `now`
+    pub fn service_multiplier(&self, now: SimTime) -> f64 {
+        let hours_on_duty = (now - self.on_duty_since).as_seconds() / 3600.0;
+        self.productivity
+            .multiplier(self.total_served, hours_on_duty)
+    }
+
+    /// v7: Remember the service duration decided for a robot at service start
+    pub fn record_service_duration(&mut self, robot: RobotId, duration: SimTime) {
+        self.service_durations.retain(|(r, _)| *r != robot);
+        self.service_durations.push((robot, duration));
+    }
+
+    /// v7: Take the service duration recorded for a robot at service start
+    pub fn take_service_duration(&mut self, robot: RobotId) -> Option<SimTime> {
+        let pos = self
+            .service_durations
+            .iter()
+            .position(|(r, _)| *r == robot)?;
+        Some(self.service_durations.remove(pos).1)
     }
 
     pub fn can_accept(&self) -> bool {
@@ -209,7 +433,7 @@ impl Station {
     }
 
     pub fn can_serve(&self) -> bool {
-        (self.serving.len() as u32) < self.concurrency
+        self.open && (self.serving.len() as u32) < self.effective_concurrency()
     }
 
     pub fn enqueue(&mut self, robot: RobotId) {
@@ -259,3 +483,143 @@ impl Station {
         self.total_service_time.as_seconds() / capacity_seconds
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(seconds: f64) -> SimTime {
+        SimTime::from_seconds(seconds)
+    }
+
+    fn plan(period_s: Option<f64>) -> StaffingSchedule {
+        StaffingSchedule {
+            shifts: vec![
+                StaffingShift {
+                    start_s: 100.0,
+                    end_s: 500.0,
+                    operators: 3,
+                },
+                StaffingShift {
+                    start_s: 400.0,
+                    end_s: 800.0,
+                    operators: 1,
+                },
+            ],
+            breaks: vec![StaffingBreak {
+                start_s: 200.0,
+                end_s: 300.0,
+                operators: 2,
+            }],
+            period_s,
+        }
+    }
+
+    #[test]
+    fn test_operators_at() {
+        let schedule = plan(None);
+        assert_eq!(schedule.operators_at(at(0.0)), 0);
+        assert_eq!(schedule.operators_at(at(100.0)), 3);
+        assert_eq!(schedule.operators_at(at(250.0)), 1);
+        assert_eq!(schedule.operators_at(at(300.0)), 3);
+        assert_eq!(schedule.operators_at(at(450.0)), 4);
+        assert_eq!(schedule.operators_at(at(500.0)), 1);
+        assert_eq!(schedule.operators_at(at(800.0)), 0);
+        assert_eq!(schedule.operators_at(at(1100.0)), 0);
+
+        // A break larger than the shift closes the station
+        let mut schedule = schedule;
+        schedule.breaks[0].operators = 5;
+        assert_eq!(schedule.operators_at(at(250.0)), 0);
+    }
+
+    #[test]
+    fn test_operators_repeat_every_period() {
+        let schedule = plan(Some(1000.0));
+        assert_eq!(schedule.operators_at(at(1050.0)), 0);
+        assert_eq!(schedule.operators_at(at(1250.0)), 1);
+        assert_eq!(schedule.operators_at(at(3450.0)), 4);
+        assert_eq!(schedule.operators_at(at(2900.0)), 0);
+    }
+
+    #[test]
+    fn test_next_change_after() {
+        let schedule = plan(None);
+        assert_eq!(schedule.next_change_after(at(0.0)), Some(at(100.0)));
+        assert_eq!(schedule.next_change_after(at(100.0)), Some(at(200.0)));
+        assert_eq!(schedule.next_change_after(at(450.0)), Some(at(500.0)));
+        assert_eq!(schedule.next_change_after(at(800.0)), None);
+
+        // Past the last boundary, a repeating plan wraps to the next period
+        let schedule = plan(Some(1000.0));
+        assert_eq!(schedule.next_change_after(at(800.0)), Some(at(1100.0)));
+        assert_eq!(schedule.next_change_after(at(950.0)), Some(at(1100.0)));
+        assert_eq!(schedule.next_change_after(at(2250.0)), Some(at(2300.0)));
+        assert_eq!(StaffingSchedule::default().next_change_after(at(0.0)), None);
+    }
+
+    #[test]
+    fn test_shift_start_resets_fatigue_at_equal_or_lower_headcount() {
+        // Two crews of two hand over at 1000s, then one of one at 2000s
+        let schedule = StaffingSchedule {
+            shifts: vec![
+                StaffingShift {
+                    start_s: 0.0,
+                    end_s: 1000.0,
+                    operators: 2,
+                },
+                StaffingShift {
+                    start_s: 1000.0,
+                    end_s: 2000.0,
+                    operators: 2,
+                },
+                StaffingShift {
+                    start_s: 2000.0,
+                    end_s: 3000.0,
+                    operators: 1,
+                },
+            ],
+            breaks: vec![StaffingBreak {
+                start_s: 2200.0,
+                end_s: 2300.0,
+                operators: 1,
+            }],
+            period_s: Some(3000.0),
+        };
+        let mut station = Station::new(
+            StationId(0),
+            "S0".to_string(),
+            NodeId(0),
+            StationType::Pick,
+            2,
+            None,
+            ServiceTimeModel::default(),
+        )
+        .with_staffing(schedule.clone());
+        let change = |station: &mut Station, seconds: f64| {
+            let now = at(seconds);
+            station.set_staffing(
+                schedule.operators_at(now),
+                now,
+                schedule.shift_starts_at(now),
+            );
+        };
+
+        change(&mut station, 1000.0);
+        assert_eq!(station.staffed_operators, 2);
+        assert_eq!(station.on_duty_since, at(1000.0));
+        change(&mut station, 2000.0);
+        assert_eq!(station.staffed_operators, 1);
+        assert_eq!(station.on_duty_since, at(2000.0));
+
+        // Coming back from break is not a new crew
+        change(&mut station, 2200.0);
+        change(&mut station, 2300.0);
+        assert_eq!(station.on_duty_since, at(2000.0));
+
+        // The repeating plan starts its first shift again
+        change(&mut station, 3000.0);
+        assert_eq!(station.on_duty_since, at(3000.0));
+        assert!(!schedule.shift_starts_at(at(3500.0)));
+    }
+}
@@ -11,6 +11,8 @@ pub enum TaskType {
     Pick,
     Putaway,
     Replenishment,
+    /// v7: Re-slotting move of a bin's contents to another storage slot
+    Relocation,
 }
 
 /// Location of a bin with its access node
@@ -147,6 +149,33 @@ impl Task {
         }
     }
 
+    /// v7: Create a re-slotting move (storage bin → storage bin). The
+    /// destination station is unused.
+    pub fn new_relocation(
+        id: TaskId,
+        sku_id: SkuId,
+        quantity: u32,
+        source: BinLocation,
+        destination_bin: BinLocation,
+        created_at: SimTime,
+    ) -> Self {
+        Self {
+            id,
+            task_type: TaskType::Relocation,
+            order_id: None,
+            sku_id,
+            quantity,
+            source,
+            destination_station: StationId(0),
+            destination_bin: Some(destination_bin),
+            status: TaskStatus::Pending,
+            assigned_robot: None,
+            created_at,
+            started_at: None,
+            completed_at: None,
+        }
+    }
+
     pub fn is_pending(&self) -> bool {
         matches!(self.status, TaskStatus::Pending)
     }
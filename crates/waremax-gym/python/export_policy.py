@@ -0,0 +1,67 @@
+"""Export a trained ``CandidateScoringPolicy`` for native Rust inference.
+
+The actor's shared scorer is written in the weights format read by
+``waremax_policies::learned``, so a scenario can run it with no Python::
+
+    policies:
+      task_allocation:
+        type: learned
+        weights_path: scorer.wmxnn
+
+Usage::
+
+    from sb3_contrib import MaskablePPO
+    from export_policy import export_candidate_scorer
+
+    model = MaskablePPO.load("runs/ppo_candidate.zip")
+    export_candidate_scorer(model.policy, "scorer.wmxnn")
+"""
+
+from __future__ import annotations
+
+import struct
+
+import torch.nn as nn
+
+_MAGIC = b"WMXNN\0\0\0"
+_VERSION = 1
+_ACTIVATIONS = {nn.Identity: 0, nn.Tanh: 1, nn.ReLU: 2}
+
+
+def export_candidate_scorer(policy, path: str) -> None:
+    """Write ``policy.candidate_scorer`` (Linear layers, each optionally
+    followed by Tanh or ReLU) to ``path``."""
+    rf = int(policy.observation_space["robots"].shape[1])
+    tf = int(policy.observation_space["task"].shape[0])
+
+    layers: list[tuple[nn.Linear, int]] = []
+    for module in policy.candidate_scorer:
+        if isinstance(module, nn.Linear):
+            layers.append((module, 0))
+        elif type(module) in _ACTIVATIONS and layers:
+            linear, _ = layers[-1]
+            layers[-1] = (linear, _ACTIVATIONS[type(module)])
+        else:
+            raise ValueError(f"unsupported scorer module: {module!r}")
+
+    with open(path, "wb") as f:
+        f.write(_MAGIC)
+        f.write(struct.pack("<4I", _VERSION, rf, tf, len(layers)))
+        for linear, activation in layers:
+            weight = linear.weight.detach().cpu().float().contiguous()
+            bias = linear.bias.detach().cpu().float().contiguous()
+            f.write(struct.pack("<2IB", linear.out_features, linear.in_features, activation))
+            f.write(weight.numpy().astype("<f4").tobytes())
+            f.write(bias.numpy().astype("<f4").tobytes())
+
+
+if __name__ == "__main__":
+    import argparse
+
+    from sb3_contrib import MaskablePPO
+
+    ap = argparse.ArgumentParser(description=__doc__.splitlines()[0])
+    ap.add_argument("model", help="saved MaskablePPO .zip using CandidateScoringPolicy")
+    ap.add_argument("out", help="weights file to write")
+    args = ap.parse_args()
+    export_candidate_scorer(MaskablePPO.load(args.model).policy, args.out)
@@ -8,6 +8,8 @@ Dict of fixed-shape arrays plus a boolean action mask. Use with
 
 from __future__ import annotations
 
+import copy
+
 import numpy as np
 import gymnasium as gym
 from gymnasium import spaces
@@ -23,7 +25,10 @@ class WaremaxAllocEnv(gym.Env):
     def __init__(self, reward_mode: str | None = None, base_seed: int = 0, **scenario_kwargs):
         """`scenario_kwargs` are forwarded to ``waremax_gym.WaremaxEnv``: any of
         preset, scenario_path, duration_minutes, warmup_minutes, due_time_minutes,
-        n_robots, order_rate, node_capacity, edge_capacity."""
+        n_robots, order_rate, node_capacity, edge_capacity, plus the domain
+        randomization kwargs (templates, robots_range, order_rate_range,
+        stations_range, due_time_range, mtbf_range, curriculum_episodes,
+        curriculum_initial_width)."""
         super().__init__()
         self._env = waremax_gym.WaremaxEnv(reward_mode=reward_mode, **scenario_kwargs)
         self.max_robots = self._env.max_robots
@@ -81,6 +86,26 @@ class WaremaxAllocEnv(gym.Env):
         """Valid-action mask for sb3-contrib MaskablePPO."""
         return self._mask
 
+    def fork(self) -> "WaremaxAllocEnv":
+        """Independent copy at the pending decision (for rollouts / MCTS).
+
+        The fork replays this episode's seed and actions, so stepping it with
+        the same actions as the original gives identical results.
+        """
+        native = self._env.fork()
+        if native is None:
+            raise RuntimeError("fork() called before reset()")
+        clone = copy.copy(self)
+        clone._env = native
+        clone._seed_rng = copy.deepcopy(self._seed_rng)
+        clone._mask = self._mask.copy()
+        return clone
+
+    def set_curriculum_progress(self, progress: float) -> None:
+        """Pin the domain-randomization curriculum (0..1) instead of counting
+        episodes."""
+        self._env.set_curriculum_progress(float(progress))
+
     def last_report(self) -> dict | None:
         import json
 
@@ -0,0 +1,55 @@
+"""Load offline-RL datasets written by ``waremax_rl::dataset``.
+
+Record one with the native recorder (no Python in the simulation loop)::
+
+    import waremax_gym
+    waremax_gym.record_expert_dataset("expert.wmxds", "nearest_robot", seeds=range(10),
+                                      preset="standard", reward_mode="routed")
+
+then load it as a numpy structured array for behavior cloning or offline RL.
+"""
+
+from __future__ import annotations
+
+import numpy as np
+
+_MAGIC = b"WMXDS\0\0\0"
+_VERSION = 1
+_HEADER = np.dtype([("magic", "S8"), ("version", "<u4"), ("rows", "<u4"),
+                    ("robot_feats", "<u4"), ("task_feats", "<u4")])
+
+
+def _obs_dtype(rows: int, robot_feats: int, task_feats: int) -> np.dtype:
+    return np.dtype([
+        ("kind", "u1"),
+        ("robots", "<f4", (rows, robot_feats)),
+        ("action_mask", "u1", (rows,)),
+        ("task", "<f4", (task_feats,)),
+    ])
+
+
+def record_dtype(rows: int, robot_feats: int, task_feats: int) -> np.dtype:
+    """Packed dtype of one transition record."""
+    obs = _obs_dtype(rows, robot_feats, task_feats)
+    return np.dtype([
+        ("obs", obs),
+        ("action", "<i4"),   # -1: no action
+        ("reward", "<f4"),
+        ("done", "u1"),
+        ("next_obs", obs),
+        ("sim_time_s", "<f8"),
+        ("lateness_delta_s", "<f8"),
+        ("completed_delta", "<i4"),
+        ("late_delta", "<i4"),
+        ("pending", "<u4"),
+        ("errored", "u1"),
+    ])
+
+
+def load_dataset(path: str) -> np.ndarray:
+    """All transitions of a dataset file as a structured array."""
+    header = np.fromfile(path, dtype=_HEADER, count=1)[0]
+    if header["magic"] != _MAGIC.rstrip(b"\0") or header["version"] != _VERSION:
+        raise ValueError(f"{path}: not a waremax dataset (or an unsupported version)")
+    dtype = record_dtype(int(header["rows"]), int(header["robot_feats"]), int(header["task_feats"]))
+    return np.fromfile(path, dtype=dtype, offset=_HEADER.itemsize)
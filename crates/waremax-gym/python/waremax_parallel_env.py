@@ -0,0 +1,109 @@
+"""PettingZoo parallel environment for decentralized waremax dispatching.
+
+Wraps ``waremax_gym.WaremaxMultiEnv``. Each robot is an agent named
+``robot_<id>``; whenever robots are free at a dispatch round, every one of them
+acts in the same ``step``: an index into the shared pending-task rows, or
+``PARK_ACTION`` to take no task. Only the deciding robots appear in ``agents``
+and in the returned dicts, so a robot's reward is the team reward accrued
+since its own previous decision (semi-MDP style). Determinism is preserved:
+the same seed and action dicts reproduce the run exactly.
+"""
+
+from __future__ import annotations
+
+import numpy as np
+from gymnasium import spaces
+from pettingzoo import ParallelEnv
+
+import waremax_gym  # compiled extension (maturin develop)
+
+
+def _name(robot_id: int) -> str:
+    return f"robot_{robot_id}"
+
+
+class WaremaxParallelEnv(ParallelEnv):
+    """Multi-agent task selection over one waremax scenario."""
+
+    metadata = {"render_modes": [], "name": "waremax_parallel_v0"}
+
+    def __init__(self, reward_mode: str | None = None, base_seed: int = 0, **scenario_kwargs):
+        """`scenario_kwargs` are forwarded to ``waremax_gym.WaremaxMultiEnv``
+        (same knobs as ``WaremaxAllocEnv``)."""
+        self._env = waremax_gym.WaremaxMultiEnv(reward_mode=reward_mode, **scenario_kwargs)
+        self.possible_agents = [_name(r) for r in self._env.possible_agents]
+        self.agents: list[str] = []
+        self.max_tasks = self._env.max_tasks
+        self.agent_feats = self._env.agent_feats
+        self.agent_task_feats = self._env.agent_task_feats
+        self.park_action = self._env.park_action
+
+        self._obs_space = spaces.Dict(
+            {
+                "agent": spaces.Box(
+                    low=-1.0, high=1.0, shape=(self.agent_feats,), dtype=np.float32
+                ),
+                "tasks": spaces.Box(
+                    low=-1.0,
+                    high=1.0,
+                    shape=(self.max_tasks, self.agent_task_feats),
+                    dtype=np.float32,
+                ),
+                "action_mask": spaces.Box(
+                    low=0, high=1, shape=(self.max_tasks + 1,), dtype=np.int8
+                ),
+            }
+        )
+        self._act_space = spaces.Discrete(self.max_tasks + 1)
+        self._seed_rng = np.random.default_rng(base_seed)
+
+    def observation_space(self, agent):
+        return self._obs_space
+
+    def action_space(self, agent):
+        return self._act_space
+
+    def _make_obs(self, joint):
+        ids, agent_feats, task_feats, masks = joint
+        self.agents = [_name(r) for r in ids]
+        obs = {
+            _name(r): {
+                "agent": np.asarray(af, dtype=np.float32),
+                "tasks": np.asarray(tf, dtype=np.float32).reshape(
+                    self.max_tasks, self.agent_task_feats
+                ),
+                "action_mask": np.asarray(m, dtype=np.int8),
+            }
+            for r, af, tf, m in zip(ids, agent_feats, task_feats, masks)
+        }
+        return obs
+
+    def reset(self, seed=None, options=None):
+        if seed is None:
+            seed = int(self._seed_rng.integers(0, 2**63 - 1))
+        obs = self._make_obs(self._env.reset(int(seed)))
+        return obs, {a: {} for a in self.agents}
+
+    def step(self, actions):
+        # Missing agents park; order must match the deciding agents.
+        joint = [int(actions.get(a, self.park_action)) for a in self.agents]
+        obs_tuple, rewards, done, info = self._env.step(joint)
+        obs = self._make_obs(obs_tuple)
+        rew = {a: float(r) for a, r in zip(self.agents, rewards)}
+        terminations = {a: bool(done) for a in self.agents}
+        truncations = {a: False for a in self.agents}
+        infos = {a: dict(info) for a in self.agents}
+        if done:
+            # PettingZoo convention: no live agents after termination.
+            self.agents = []
+        return obs, rew, terminations, truncations, infos
+
+    def last_report(self) -> dict | None:
+        import json
+
+        js = self._env.last_report_json()
+        return json.loads(js) if js else None
+
+
+def make_parallel_env(**kwargs) -> WaremaxParallelEnv:
+    return WaremaxParallelEnv(**kwargs)
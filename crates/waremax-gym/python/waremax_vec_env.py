@@ -0,0 +1,123 @@
+"""Stable-Baselines3 ``VecEnv`` over the native ``waremax_gym.WaremaxVecEnv``.
+
+All N environments are stepped by one Rust call that releases the GIL while
+the simulations run concurrently on their worker threads. Observations come
+back as flat buffers and are viewed with ``np.frombuffer`` (no per-env Python
+work). Finished episodes are reset on the Rust side from deterministic per-env
+seed streams, so training is reproducible from ``base_seed``.
+
+Observation and action spaces match ``WaremaxAllocEnv``; ``action_masks()``
+returns an ``(N, max_robots)`` mask for ``sb3_contrib.MaskablePPO``.
+"""
+
+from __future__ import annotations
+
+import numpy as np
+from gymnasium import spaces
+from stable_baselines3.common.vec_env import VecEnv
+
+import waremax_gym  # compiled extension (maturin develop)
+
+
+class WaremaxVecAllocEnv(VecEnv):
+    """N task-allocation envs stepped in one batched, GIL-free call."""
+
+    def __init__(self, num_envs: int, reward_mode: str | None = None, base_seed: int = 0,
+                 **scenario_kwargs):
+        self._env = waremax_gym.WaremaxVecEnv(
+            num_envs, base_seed=base_seed, reward_mode=reward_mode, **scenario_kwargs
+        )
+        self.max_robots = self._env.max_robots
+        self.robot_feats = self._env.robot_feats
+        self.task_feats = self._env.task_feats
+
+        observation_space = spaces.Dict(
+            {
+                "robots": spaces.Box(
+                    low=-1.0,
+                    high=1.0,
+                    shape=(self.max_robots, self.robot_feats),
+                    dtype=np.float32,
+                ),
+                "task": spaces.Box(
+                    low=-1.0, high=1.0, shape=(self.task_feats,), dtype=np.float32
+                ),
+                "action_mask": spaces.Box(
+                    low=0, high=1, shape=(self.max_robots,), dtype=np.int8
+                ),
+            }
+        )
+        super().__init__(num_envs, observation_space, spaces.Discrete(self.max_robots))
+        self._actions = None
+        self._mask = np.zeros((num_envs, self.max_robots), dtype=bool)
+
+    def _make_obs(self, buffers):
+        robot_buf, mask_buf, task_buf = buffers
+        n = self.num_envs
+        self._mask = np.frombuffer(mask_buf, dtype=np.uint8).reshape(n, self.max_robots) != 0
+        return {
+            "robots": np.frombuffer(robot_buf, dtype=np.float32).reshape(
+                n, self.max_robots, self.robot_feats
+            ),
+            "task": np.frombuffer(task_buf, dtype=np.float32).reshape(n, self.task_feats),
+            "action_mask": self._mask.astype(np.int8),
+        }
+
+    def reset(self):
+        return self._make_obs(self._env.reset())
+
+    def step_async(self, actions):
+        self._actions = [int(a) for a in np.asarray(actions).reshape(-1)]
+
+    def step_wait(self):
+        buffers, reward_buf, dones, infos = self._env.step(self._actions)
+        obs = self._make_obs(buffers)
+        rewards = np.frombuffer(reward_buf, dtype=np.float32).copy()
+        # SMDP episodes end naturally (time horizon): termination, not truncation.
+        for i, info in enumerate(infos):
+            info["TimeLimit.truncated"] = False
+            if dones[i]:
+                # The episode's terminal observation is all-zero by construction.
+                info["terminal_observation"] = {
+                    k: np.zeros(v.shape[1:], dtype=v.dtype) for k, v in obs.items()
+                }
+        return obs, rewards, np.asarray(dones, dtype=bool), list(infos)
+
+    def action_masks(self) -> np.ndarray:
+        """Valid-action masks, shape (num_envs, max_robots)."""
+        return self._mask
+
+    def close(self):
+        pass
+
+    def get_attr(self, attr_name, indices=None):
+        return [getattr(self, attr_name)] * len(self._get_indices(indices))
+
+    def set_attr(self, attr_name, value, indices=None):
+        setattr(self, attr_name, value)
+
+    def env_method(self, method_name, *method_args, indices=None, **method_kwargs):
+        # MaskablePPO asks each sub-env for "action_masks".
+        if method_name == "action_masks":
+            return [self._mask[i] for i in self._get_indices(indices)]
+        raise NotImplementedError(method_name)
+
+    def env_is_wrapped(self, wrapper_class, indices=None):
+        return [False] * len(self._get_indices(indices))
+
+    def _get_indices(self, indices):
+        if indices is None:
+            return range(self.num_envs)
+        if isinstance(indices, int):
+            return [indices]
+        return indices
+
+    def set_curriculum_progress(self, progress: float) -> None:
+        """Pin the domain-randomization curriculum (0..1) for every env."""
+        self._env.set_curriculum_progress(float(progress))
+
+    def last_report(self, i: int = 0) -> dict | None:
+        import json
+
+        js = self._env.last_report_json(i)
+        return json.loads(js) if js else None
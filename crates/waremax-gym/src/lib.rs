@@ -1,21 +1,35 @@
 //! Python (PyO3) bindings for the waremax RL environment.
 //!
-//! Exposes [`WaremaxEnv`], a thin handle over `waremax_rl::RlEnv`, plus a
+//! Exposes [`WaremaxEnv`], a thin handle over `waremax_rl::RlEnv`,
+//! [`WaremaxMultiEnv`] over `waremax_rl::MultiAgentEnv`, [`WaremaxVecEnv`] over
+//! `waremax_rl::VecEnv`, plus a
 //! `run_baseline` helper that runs a fixed heuristic on an identical scenario/seed
 //! for apples-to-apples comparison. Observations are returned as plain Python
 //! lists (the Python wrapper assembles numpy arrays), which keeps the Rust side
 //! free of any numpy-ABI version coupling.
 //!
+//! The vectorized env returns its stacked buffers as `bytes` (little-endian
+//! `f32`, or `u8` for masks) that Python views with `np.frombuffer`, so a
+//! batched step costs one copy regardless of the number of envs.
+//!
 //! Every blocking call into the simulation releases the GIL via `allow_threads`,
 //! so the simulation worker thread is never starved.
 
+// pyo3 0.22's `#[pymethods]`/`#[pyfunction]` expansion wraps `PyResult` returns
+// in an `Into<PyErr>` conversion that newer clippy flags as useless.
+#![allow(clippy::useless_conversion)]
+
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
-use pyo3::types::PyDict;
+use pyo3::types::{PyBytes, PyDict};
 
 use waremax_config::{DueTimeConfig, ScenarioConfig};
 use waremax_rl::{
-    ActionMsg, RewardConfig, RewardMode, RlEnv, StepInfo, MAX_ROBOTS, ROBOT_FEATS, TASK_FEATS,
+    record_episode, ActionMsg, ControlMode, Curriculum, DatasetHeader, DatasetWriter, DecisionKind,
+    MultiAgentEnv, MultiObservation, Observation, ObservationSpec, ParamRange, RewardConfig,
+    RewardMode, RewardSpec, RewardTerm, RlEnv, RobotFeature, ScenarioDistribution, StepInfo,
+    TaskFeature, VecEnv, VecObservation, AGENT_FEATS, AGENT_TASK_FEATS, EDGE_FEATS, MAX_ROBOTS,
+    MAX_STATIONS, MAX_TASKS, NODE_FEATS, PARK_ACTION, ROBOT_FEATS, STATION_FEATS, TASK_FEATS,
 };
 use waremax_testing::presets::ScenarioPreset;
 use waremax_testing::runner::run_simulation_from_config;
@@ -102,16 +116,81 @@ fn build_scenario(
     Ok(scenario)
 }
 
-/// A Gym-style environment over one waremax scenario (task-allocation control).
+/// Per-episode scenario randomization from the `*_range` / `templates` /
+/// `curriculum_*` kwargs, or `None` when none is given. Each template (a
+/// preset name or a YAML path) is built by `build` with the env's other
+/// scenario knobs.
+#[allow(clippy::too_many_arguments)]
+fn scenario_distribution(
+    templates: Option<Vec<String>>,
+    robots_range: Option<(u32, u32)>,
+    order_rate_range: Option<(f64, f64)>,
+    stations_range: Option<(u32, u32)>,
+    due_time_range: Option<(f64, f64)>,
+    mtbf_range: Option<(f64, f64)>,
+    curriculum_episodes: Option<u64>,
+    curriculum_initial_width: Option<f64>,
+    build: impl Fn(Option<String>, Option<String>) -> PyResult<ScenarioConfig>,
+) -> PyResult<Option<ScenarioDistribution>> {
+    let range = |r: Option<(f64, f64)>| r.map(|(a, b)| ParamRange::new(a, b));
+    let count_range = |r: Option<(u32, u32)>| r.map(|(a, b)| ParamRange::new(a as f64, b as f64));
+    let mut dist = ScenarioDistribution {
+        robots: count_range(robots_range),
+        order_rate: range(order_rate_range),
+        stations: count_range(stations_range),
+        due_time_minutes: range(due_time_range),
+        failure_mtbf_hours: range(mtbf_range),
+        curriculum: curriculum_episodes.map(|n| {
+            Curriculum::new(n).with_initial_width(curriculum_initial_width.unwrap_or(0.0))
+        }),
+        ..ScenarioDistribution::default()
+    };
+    for t in templates.unwrap_or_default() {
+        let is_path = t.ends_with(".yaml") || t.ends_with(".yml") || t.ends_with(".json");
+        dist.templates.push(if is_path {
+            build(None, Some(t))?
+        } else {
+            build(Some(t), None)?
+        });
+    }
+    let randomized = !dist.templates.is_empty()
+        || dist.robots.is_some()
+        || dist.order_rate.is_some()
+        || dist.stations.is_some()
+        || dist.due_time_minutes.is_some()
+        || dist.failure_mtbf_hours.is_some();
+    Ok(randomized.then_some(dist))
+}
+
+/// (robot_feats, action_mask, task_feats, reward, done, info) returned by `step`.
+type StepTuple = (Vec<f32>, Vec<bool>, Vec<f32>, f32, bool, Py<PyDict>);
+
+/// (node_feats, edge_src, edge_dst, edge_feats, pickup_node, candidate_nodes)
+/// returned by `graph_observation`.
+type GraphTuple = (Vec<f32>, Vec<u32>, Vec<u32>, Vec<f32>, u32, Vec<u32>);
+
+/// Python-facing name of a decision kind (reported as `info["decision_kind"]`).
+fn decision_kind_name(kind: DecisionKind) -> &'static str {
+    match kind {
+        DecisionKind::Allocation => "allocation",
+        DecisionKind::StationAssignment => "station",
+    }
+}
+
+/// A Gym-style environment over one waremax scenario (task-allocation and/or
+/// station-assignment control).
 #[pyclass]
 struct WaremaxEnv {
     env: RlEnv,
+    /// The pending decision's observation; station rows are read from it by
+    /// `station_observation`.
+    last_obs: Observation,
 }
 
 #[pymethods]
 impl WaremaxEnv {
     #[new]
-    #[pyo3(signature = (preset=None, scenario_path=None, duration_minutes=None, warmup_minutes=None, reward_mode=None, due_time_minutes=None, n_robots=None, order_rate=None, node_capacity=None, edge_capacity=None, congestion_weight=None, smart_bins=None, inventory_skus=None))]
+    #[pyo3(signature = (preset=None, scenario_path=None, duration_minutes=None, warmup_minutes=None, reward_mode=None, due_time_minutes=None, n_robots=None, order_rate=None, node_capacity=None, edge_capacity=None, congestion_weight=None, smart_bins=None, inventory_skus=None, control=None, max_candidates=None, robot_features=None, task_features=None, graph_obs=None, expert=None, templates=None, robots_range=None, order_rate_range=None, stations_range=None, due_time_range=None, mtbf_range=None, curriculum_episodes=None, curriculum_initial_width=None, reward_spec=None))]
     #[allow(clippy::too_many_arguments)]
     fn new(
         preset: Option<String>,
@@ -127,53 +206,150 @@ impl WaremaxEnv {
         congestion_weight: Option<f64>,
         smart_bins: Option<bool>,
         inventory_skus: Option<u32>,
+        control: Option<String>,
+        max_candidates: Option<usize>,
+        robot_features: Option<Vec<String>>,
+        task_features: Option<Vec<String>>,
+        graph_obs: Option<bool>,
+        expert: Option<String>,
+        templates: Option<Vec<String>>,
+        robots_range: Option<(u32, u32)>,
+        order_rate_range: Option<(f64, f64)>,
+        stations_range: Option<(u32, u32)>,
+        due_time_range: Option<(f64, f64)>,
+        mtbf_range: Option<(f64, f64)>,
+        curriculum_episodes: Option<u64>,
+        curriculum_initial_width: Option<f64>,
+        reward_spec: Option<String>,
     ) -> PyResult<Self> {
-        let scenario = build_scenario(
-            preset,
-            scenario_path,
-            duration_minutes,
-            warmup_minutes,
-            due_time_minutes,
-            n_robots,
-            order_rate,
-            node_capacity,
-            edge_capacity,
-            congestion_weight,
-            smart_bins,
-            inventory_skus,
-        )?;
-        let mode = match reward_mode.as_deref() {
-            None => RewardMode::Dense,
-            Some(s) => RewardMode::from_str(s)
-                .ok_or_else(|| PyValueError::new_err(format!("unknown reward mode '{s}'")))?,
+        let build = |preset, scenario_path| {
+            build_scenario(
+                preset,
+                scenario_path,
+                duration_minutes,
+                warmup_minutes,
+                due_time_minutes,
+                n_robots,
+                order_rate,
+                node_capacity,
+                edge_capacity,
+                congestion_weight,
+                smart_bins,
+                inventory_skus,
+            )
         };
-        let reward_cfg = RewardConfig {
-            mode,
-            ..RewardConfig::default()
+        let scenario = build(preset, scenario_path)?;
+        let distribution = scenario_distribution(
+            templates,
+            robots_range,
+            order_rate_range,
+            stations_range,
+            due_time_range,
+            mtbf_range,
+            curriculum_episodes,
+            curriculum_initial_width,
+            build,
+        )?;
+        let reward_cfg = reward_config(reward_mode, reward_spec)?;
+        let control = match control.as_deref() {
+            None => ControlMode::Allocation,
+            Some(s) => ControlMode::from_str(s)
+                .ok_or_else(|| PyValueError::new_err(format!("unknown control mode '{s}'")))?,
         };
+        let spec = observation_spec(max_candidates, robot_features, task_features)?
+            .with_graph(graph_obs.unwrap_or(false));
+        let mut env = RlEnv::new(scenario, reward_cfg)
+            .with_control(control)
+            .with_observation_spec(spec.clone());
+        if let Some(name) = expert {
+            env = env.with_expert(name);
+        }
+        if let Some(dist) = distribution {
+            env = env.with_scenario_distribution(dist);
+        }
         Ok(Self {
-            env: RlEnv::new(scenario, reward_cfg),
+            last_obs: Observation::zeros_for(&spec),
+            env,
         })
     }
 
+    /// Candidate rows (and action-space size) of the observation spec.
     #[getter]
     fn max_robots(&self) -> usize {
-        MAX_ROBOTS
+        self.env.observation_spec().max_candidates
     }
     #[getter]
     fn robot_feats(&self) -> usize {
-        ROBOT_FEATS
+        self.env.observation_spec().robot_feats()
     }
     #[getter]
     fn task_feats(&self) -> usize {
-        TASK_FEATS
+        self.env.observation_spec().task_feats()
+    }
+    /// Names of the robot-row features, in column order.
+    #[getter]
+    fn robot_feature_names(&self) -> Vec<&'static str> {
+        self.env.observation_spec().robot_feature_names()
+    }
+    /// Names of the task features, in order.
+    #[getter]
+    fn task_feature_names(&self) -> Vec<&'static str> {
+        self.env.observation_spec().task_feature_names()
+    }
+    #[getter]
+    fn max_stations(&self) -> usize {
+        MAX_STATIONS
+    }
+    #[getter]
+    fn station_feats(&self) -> usize {
+        STATION_FEATS
+    }
+    /// Kind of the pending decision: "allocation" or "station".
+    #[getter]
+    fn decision_kind(&self) -> &'static str {
+        decision_kind_name(self.last_obs.kind)
+    }
+
+    /// (station_feats, station_mask) of the pending decision. When
+    /// `decision_kind == "station"`, actions index these rows.
+    fn station_observation(&self) -> (Vec<f32>, Vec<bool>) {
+        (
+            self.last_obs.station_feats.clone(),
+            self.last_obs.station_mask.clone(),
+        )
+    }
+
+    /// Graph view of the pending decision (with `graph_obs=True`), else `None`.
+    fn graph_observation(&self) -> Option<GraphTuple> {
+        self.last_obs.graph.as_ref().map(|g| {
+            (
+                g.node_feats.clone(),
+                g.edge_src.clone(),
+                g.edge_dst.clone(),
+                g.edge_feats.clone(),
+                g.pickup_node,
+                g.candidate_nodes.clone(),
+            )
+        })
+    }
+
+    /// An independent copy of this env at its pending decision (same
+    /// scenario, seed and action history), for rollouts and tree search.
+    /// `None` before the first `reset`.
+    fn fork(&self, py: Python<'_>) -> Option<WaremaxEnv> {
+        py.allow_threads(|| self.env.fork())
+            .map(|(env, last_obs)| WaremaxEnv { env, last_obs })
     }
 
     /// Begin an episode. Returns (robot_feats, action_mask, task_feats) for the
     /// first decision; the Python wrapper turns these into the observation dict.
     fn reset(&mut self, py: Python<'_>, seed: u64) -> (Vec<f32>, Vec<bool>, Vec<f32>) {
-        let obs = py.allow_threads(|| self.env.reset(seed));
-        (obs.robot_feats, obs.action_mask, obs.task_feats)
+        self.last_obs = py.allow_threads(|| self.env.reset(seed));
+        (
+            self.last_obs.robot_feats.clone(),
+            self.last_obs.action_mask.clone(),
+            self.last_obs.task_feats.clone(),
+        )
     }
 
     /// Apply an action (index into the masked candidate list). Returns
@@ -182,18 +358,171 @@ impl WaremaxEnv {
         &mut self,
         py: Python<'_>,
         action: usize,
-    ) -> PyResult<(Vec<f32>, Vec<bool>, Vec<f32>, f32, bool, Py<PyDict>)> {
-        let res = py.allow_threads(|| self.env.step(ActionMsg::Choose(action)));
+    ) -> PyResult<StepTuple> {
+        self.apply(py, ActionMsg::Choose(action))
+    }
+
+    /// Apply the expert's choice (requires `expert=...`); otherwise as `step`.
+    fn step_expert(&mut self, py: Python<'_>) -> PyResult<StepTuple> {
+        self.apply(py, ActionMsg::Expert)
+    }
+
+    /// Row the expert would choose at the pending decision, if any.
+    #[getter]
+    fn expert_action(&self) -> Option<usize> {
+        self.last_obs.expert_action
+    }
+
+    /// JSON of the most recent completed episode's full simulation report.
+    fn last_report_json(&self) -> Option<String> {
+        self.env
+            .last_report()
+            .and_then(|r| serde_json::to_string(&r).ok())
+    }
+
+    /// JSON of the current episode's (possibly randomized) scenario.
+    fn episode_scenario_json(&self) -> Option<String> {
+        self.env
+            .episode_scenario()
+            .and_then(|s| serde_json::to_string(s).ok())
+    }
+
+    /// Curriculum progress (0..=1) the next episode is drawn at.
+    #[getter]
+    fn curriculum_progress(&self) -> f64 {
+        self.env.curriculum_progress()
+    }
+
+    /// Pin the curriculum progress, e.g. to the trainer's fraction of total
+    /// timesteps.
+    fn set_curriculum_progress(&mut self, progress: f64) {
+        self.env.set_curriculum_progress(progress);
+    }
+}
+
+impl WaremaxEnv {
+    fn apply(&mut self, py: Python<'_>, action: ActionMsg) -> PyResult<StepTuple> {
+        let res = py.allow_threads(|| self.env.step(action));
         let info = step_info_to_dict(py, &res.info)?;
+        let bound = info.bind(py);
+        bound.set_item("decision_kind", decision_kind_name(res.obs.kind))?;
+        bound.set_item("truncated_candidates", res.obs.truncated_candidates)?;
+        bound.set_item("truncated_stations", res.obs.truncated_stations)?;
+        self.last_obs = res.obs;
         Ok((
-            res.obs.robot_feats,
-            res.obs.action_mask,
-            res.obs.task_feats,
+            self.last_obs.robot_feats.clone(),
+            self.last_obs.action_mask.clone(),
+            self.last_obs.task_feats.clone(),
             res.reward,
             res.done,
             info,
         ))
     }
+}
+
+/// (agents, agent_feats, task_feats, action_masks) of one joint decision, one
+/// entry per agent.
+type JointObs = (Vec<u32>, Vec<Vec<f32>>, Vec<Vec<f32>>, Vec<Vec<bool>>);
+
+/// (joint obs, rewards, done, info) returned by `WaremaxMultiEnv.step`.
+type MultiStepTuple = (JointObs, Vec<f32>, bool, Py<PyDict>);
+
+fn joint_obs(obs: MultiObservation) -> JointObs {
+    let agents = obs.agents.iter().map(|r| r.0).collect();
+    let mut agent_feats = Vec::with_capacity(obs.per_agent.len());
+    let mut task_feats = Vec::with_capacity(obs.per_agent.len());
+    let mut masks = Vec::with_capacity(obs.per_agent.len());
+    for a in obs.per_agent {
+        agent_feats.push(a.agent_feats);
+        task_feats.push(a.task_feats);
+        masks.push(a.action_mask);
+    }
+    (agents, agent_feats, task_feats, masks)
+}
+
+/// A multi-agent environment over one waremax scenario: each robot is an agent
+/// that picks a pending task (or parks) when free.
+#[pyclass]
+struct WaremaxMultiEnv {
+    env: MultiAgentEnv,
+}
+
+#[pymethods]
+impl WaremaxMultiEnv {
+    #[new]
+    #[pyo3(signature = (preset=None, scenario_path=None, duration_minutes=None, warmup_minutes=None, reward_mode=None, due_time_minutes=None, n_robots=None, order_rate=None, node_capacity=None, edge_capacity=None, congestion_weight=None, smart_bins=None, inventory_skus=None, reward_spec=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        preset: Option<String>,
+        scenario_path: Option<String>,
+        duration_minutes: Option<f64>,
+        warmup_minutes: Option<f64>,
+        reward_mode: Option<String>,
+        due_time_minutes: Option<f64>,
+        n_robots: Option<u32>,
+        order_rate: Option<f64>,
+        node_capacity: Option<u32>,
+        edge_capacity: Option<u32>,
+        congestion_weight: Option<f64>,
+        smart_bins: Option<bool>,
+        inventory_skus: Option<u32>,
+        reward_spec: Option<String>,
+    ) -> PyResult<Self> {
+        let scenario = build_scenario(
+            preset,
+            scenario_path,
+            duration_minutes,
+            warmup_minutes,
+            due_time_minutes,
+            n_robots,
+            order_rate,
+            node_capacity,
+            edge_capacity,
+            congestion_weight,
+            smart_bins,
+            inventory_skus,
+        )?;
+        Ok(Self {
+            env: MultiAgentEnv::new(scenario, reward_config(reward_mode, reward_spec)?),
+        })
+    }
+
+    /// Robot ids of the whole fleet (the PettingZoo `possible_agents`).
+    #[getter]
+    fn possible_agents(&self) -> Vec<u32> {
+        self.env.possible_agents().iter().map(|r| r.0).collect()
+    }
+    #[getter]
+    fn max_tasks(&self) -> usize {
+        MAX_TASKS
+    }
+    #[getter]
+    fn agent_feats(&self) -> usize {
+        AGENT_FEATS
+    }
+    #[getter]
+    fn agent_task_feats(&self) -> usize {
+        AGENT_TASK_FEATS
+    }
+    #[getter]
+    fn park_action(&self) -> usize {
+        PARK_ACTION
+    }
+
+    /// Begin an episode and return the first joint decision.
+    fn reset(&mut self, py: Python<'_>, seed: u64) -> JointObs {
+        joint_obs(py.allow_threads(|| self.env.reset(seed)))
+    }
+
+    /// Apply one action per deciding agent (in the order of the last
+    /// observation's agents). Returns (joint obs, rewards, done, info); on the
+    /// terminal step the agents are the whole fleet.
+    fn step(&mut self, py: Python<'_>, actions: Vec<usize>) -> PyResult<MultiStepTuple> {
+        let res = py.allow_threads(|| self.env.step(actions));
+        let info = step_info_to_dict(py, &res.info)?;
+        info.bind(py).set_item("conflicts", res.conflicts)?;
+        Ok((joint_obs(res.obs), res.rewards, res.done, info))
+    }
 
     /// JSON of the most recent completed episode's full simulation report.
     fn last_report_json(&self) -> Option<String> {
@@ -203,6 +532,249 @@ impl WaremaxEnv {
     }
 }
 
+/// (robot_feats, action_mask, task_feats) of every env as raw buffers.
+type VecObsBytes = (Py<PyBytes>, Py<PyBytes>, Py<PyBytes>);
+
+/// (stacked obs, rewards, dones, infos) returned by `WaremaxVecEnv.step`.
+type VecStepTuple = (VecObsBytes, Py<PyBytes>, Vec<bool>, Vec<Py<PyDict>>);
+
+fn f32_bytes(py: Python<'_>, values: &[f32]) -> Py<PyBytes> {
+    let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+    PyBytes::new_bound(py, &bytes).unbind()
+}
+
+fn mask_bytes(py: Python<'_>, mask: &[bool]) -> Py<PyBytes> {
+    let bytes: Vec<u8> = mask.iter().map(|&m| m as u8).collect();
+    PyBytes::new_bound(py, &bytes).unbind()
+}
+
+fn vec_obs_bytes(py: Python<'_>, obs: &VecObservation) -> VecObsBytes {
+    (
+        f32_bytes(py, &obs.robot_feats),
+        mask_bytes(py, &obs.action_mask),
+        f32_bytes(py, &obs.task_feats),
+    )
+}
+
+/// N task-allocation envs stepped with one batched call, auto-resetting
+/// finished episodes.
+#[pyclass]
+struct WaremaxVecEnv {
+    env: VecEnv,
+}
+
+#[pymethods]
+impl WaremaxVecEnv {
+    #[new]
+    #[pyo3(signature = (num_envs, base_seed=0, preset=None, scenario_path=None, duration_minutes=None, warmup_minutes=None, reward_mode=None, due_time_minutes=None, n_robots=None, order_rate=None, node_capacity=None, edge_capacity=None, congestion_weight=None, smart_bins=None, inventory_skus=None, max_candidates=None, robot_features=None, task_features=None, templates=None, robots_range=None, order_rate_range=None, stations_range=None, due_time_range=None, mtbf_range=None, curriculum_episodes=None, curriculum_initial_width=None, reward_spec=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        num_envs: usize,
+        base_seed: u64,
+        preset: Option<String>,
+        scenario_path: Option<String>,
+        duration_minutes: Option<f64>,
+        warmup_minutes: Option<f64>,
+        reward_mode: Option<String>,
+        due_time_minutes: Option<f64>,
+        n_robots: Option<u32>,
+        order_rate: Option<f64>,
+        node_capacity: Option<u32>,
+        edge_capacity: Option<u32>,
+        congestion_weight: Option<f64>,
+        smart_bins: Option<bool>,
+        inventory_skus: Option<u32>,
+        max_candidates: Option<usize>,
+        robot_features: Option<Vec<String>>,
+        task_features: Option<Vec<String>>,
+        templates: Option<Vec<String>>,
+        robots_range: Option<(u32, u32)>,
+        order_rate_range: Option<(f64, f64)>,
+        stations_range: Option<(u32, u32)>,
+        due_time_range: Option<(f64, f64)>,
+        mtbf_range: Option<(f64, f64)>,
+        curriculum_episodes: Option<u64>,
+        curriculum_initial_width: Option<f64>,
+        reward_spec: Option<String>,
+    ) -> PyResult<Self> {
+        if num_envs == 0 {
+            return Err(PyValueError::new_err("num_envs must be at least 1"));
+        }
+        let build = |preset, scenario_path| {
+            build_scenario(
+                preset,
+                scenario_path,
+                duration_minutes,
+                warmup_minutes,
+                due_time_minutes,
+                n_robots,
+                order_rate,
+                node_capacity,
+                edge_capacity,
+                congestion_weight,
+                smart_bins,
+                inventory_skus,
+            )
+        };
+        let scenario = build(preset, scenario_path)?;
+        let distribution = scenario_distribution(
+            templates,
+            robots_range,
+            order_rate_range,
+            stations_range,
+            due_time_range,
+            mtbf_range,
+            curriculum_episodes,
+            curriculum_initial_width,
+            build,
+        )?;
+        let spec = observation_spec(max_candidates, robot_features, task_features)?;
+        let mut env = VecEnv::new(
+            scenario,
+            reward_config(reward_mode, reward_spec)?,
+            num_envs,
+            base_seed,
+        )
+        .with_observation_spec(spec);
+        if let Some(dist) = distribution {
+            env = env.with_scenario_distribution(dist);
+        }
+        Ok(Self { env })
+    }
+
+    #[getter]
+    fn num_envs(&self) -> usize {
+        self.env.num_envs()
+    }
+    #[getter]
+    fn max_robots(&self) -> usize {
+        self.env.observation_spec().max_candidates
+    }
+    #[getter]
+    fn robot_feats(&self) -> usize {
+        self.env.observation_spec().robot_feats()
+    }
+    #[getter]
+    fn task_feats(&self) -> usize {
+        self.env.observation_spec().task_feats()
+    }
+    #[getter]
+    fn robot_feature_names(&self) -> Vec<&'static str> {
+        self.env.observation_spec().robot_feature_names()
+    }
+    #[getter]
+    fn task_feature_names(&self) -> Vec<&'static str> {
+        self.env.observation_spec().task_feature_names()
+    }
+
+    /// Begin a fresh episode in every env. Returns the stacked
+    /// (robot_feats, action_mask, task_feats) buffers.
+    fn reset(&mut self, py: Python<'_>) -> VecObsBytes {
+        let obs = py.allow_threads(|| self.env.reset());
+        vec_obs_bytes(py, &obs)
+    }
+
+    /// Apply one action per env. Returns (stacked obs, rewards as `f32`
+    /// bytes, dones, infos); finished envs come back already reset.
+    fn step(&mut self, py: Python<'_>, actions: Vec<usize>) -> PyResult<VecStepTuple> {
+        if actions.len() != self.env.num_envs() {
+            return Err(PyValueError::new_err(format!(
+                "expected {} actions, got {}",
+                self.env.num_envs(),
+                actions.len()
+            )));
+        }
+        let actions: Vec<ActionMsg> = actions.into_iter().map(ActionMsg::Choose).collect();
+        let res = py.allow_threads(|| self.env.step(&actions));
+        let infos = res
+            .infos
+            .iter()
+            .map(|info| step_info_to_dict(py, info))
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok((
+            vec_obs_bytes(py, &res.obs),
+            f32_bytes(py, &res.rewards),
+            res.dones,
+            infos,
+        ))
+    }
+
+    /// JSON of env `i`'s most recent completed episode report.
+    fn last_report_json(&self, i: usize) -> Option<String> {
+        self.env
+            .last_report(i)
+            .and_then(|r| serde_json::to_string(&r).ok())
+    }
+
+    /// Curriculum progress (0..=1) the next episode is drawn at, counted over
+    /// the whole pool.
+    #[getter]
+    fn curriculum_progress(&self) -> f64 {
+        self.env.curriculum_progress()
+    }
+
+    /// Pin the curriculum progress for every env.
+    fn set_curriculum_progress(&mut self, progress: f64) {
+        self.env.set_curriculum_progress(progress);
+    }
+}
+
+/// Observation spec from Python-side feature names (defaults when omitted).
+fn observation_spec(
+    max_candidates: Option<usize>,
+    robot_features: Option<Vec<String>>,
+    task_features: Option<Vec<String>>,
+) -> PyResult<ObservationSpec> {
+    ObservationSpec::from_names(
+        max_candidates,
+        robot_features.as_deref(),
+        task_features.as_deref(),
+    )
+    .map_err(PyValueError::new_err)
+}
+
+/// Every registered feature name: (robot features, task features).
+#[pyfunction]
+fn available_features() -> (Vec<&'static str>, Vec<&'static str>) {
+    (
+        RobotFeature::ALL.iter().map(|f| f.name()).collect(),
+        TaskFeature::ALL.iter().map(|f| f.name()).collect(),
+    )
+}
+
+/// Reward config for a Python-side `reward_mode` (dense when omitted).
+/// Reward config from `reward_mode`, or a custom spec from `reward_spec`
+/// (which implies `reward_mode="custom"`).
+fn reward_config(
+    reward_mode: Option<String>,
+    reward_spec: Option<String>,
+) -> PyResult<RewardConfig> {
+    if let Some(text) = reward_spec {
+        if reward_mode.as_deref().is_some_and(|m| m != "custom") {
+            return Err(PyValueError::new_err(
+                "reward_spec requires reward_mode='custom' (or no reward_mode)",
+            ));
+        }
+        let spec = RewardSpec::parse(&text).map_err(PyValueError::new_err)?;
+        return Ok(RewardConfig::custom(spec));
+    }
+    let mode = match reward_mode.as_deref() {
+        None => RewardMode::Dense,
+        Some(s) => RewardMode::from_str(s)
+            .ok_or_else(|| PyValueError::new_err(format!("unknown reward mode '{s}'")))?,
+    };
+    Ok(RewardConfig {
+        mode,
+        ..RewardConfig::default()
+    })
+}
+
+/// Names of the terms a `reward_spec` can weight.
+#[pyfunction]
+fn reward_terms() -> Vec<&'static str> {
+    RewardTerm::all().iter().map(|t| t.name()).collect()
+}
+
 fn step_info_to_dict(py: Python<'_>, info: &StepInfo) -> PyResult<Py<PyDict>> {
     let d = PyDict::new_bound(py);
     d.set_item("completed_delta", info.completed_delta)?;
@@ -211,6 +783,11 @@ fn step_info_to_dict(py: Python<'_>, info: &StepInfo) -> PyResult<Py<PyDict>> {
     d.set_item("pending", info.pending)?;
     d.set_item("sim_time_s", info.sim_time_s)?;
     d.set_item("errored", info.errored)?;
+    let terms = PyDict::new_bound(py);
+    for (name, contribution) in &info.reward_terms {
+        terms.set_item(*name, *contribution)?;
+    }
+    d.set_item("reward_terms", terms)?;
     if let Some(fm) = &info.final_metrics {
         d.set_item("orders_completed", fm.orders_completed)?;
         d.set_item("orders_late", fm.orders_late)?;
@@ -267,12 +844,80 @@ fn run_baseline(
     serde_json::to_string(&report).map_err(|e| PyValueError::new_err(e.to_string()))
 }
 
+/// Record `expert` (a heuristic allocation policy name) acting for one
+/// episode per seed into the dataset file at `path`, entirely in Rust. Returns
+/// the number of transitions written.
+#[pyfunction]
+#[pyo3(signature = (path, expert, seeds, reward_mode=None, preset=None, scenario_path=None, duration_minutes=None, warmup_minutes=None, due_time_minutes=None, n_robots=None, order_rate=None, node_capacity=None, edge_capacity=None, congestion_weight=None, smart_bins=None, inventory_skus=None, reward_spec=None))]
+#[allow(clippy::too_many_arguments)]
+fn record_expert_dataset(
+    py: Python<'_>,
+    path: String,
+    expert: String,
+    seeds: Vec<u64>,
+    reward_mode: Option<String>,
+    preset: Option<String>,
+    scenario_path: Option<String>,
+    duration_minutes: Option<f64>,
+    warmup_minutes: Option<f64>,
+    due_time_minutes: Option<f64>,
+    n_robots: Option<u32>,
+    order_rate: Option<f64>,
+    node_capacity: Option<u32>,
+    edge_capacity: Option<u32>,
+    congestion_weight: Option<f64>,
+    smart_bins: Option<bool>,
+    inventory_skus: Option<u32>,
+    reward_spec: Option<String>,
+) -> PyResult<usize> {
+    let scenario = build_scenario(
+        preset,
+        scenario_path,
+        duration_minutes,
+        warmup_minutes,
+        due_time_minutes,
+        n_robots,
+        order_rate,
+        node_capacity,
+        edge_capacity,
+        congestion_weight,
+        smart_bins,
+        inventory_skus,
+    )?;
+    let mut env =
+        RlEnv::new(scenario, reward_config(reward_mode, reward_spec)?).with_expert(expert);
+    let header = DatasetHeader::for_spec(env.observation_spec());
+    py.allow_threads(|| {
+        let mut writer = DatasetWriter::create(&path, header)?;
+        for seed in seeds {
+            record_episode(&mut env, seed, &mut writer, |_| ActionMsg::Expert)?;
+        }
+        let written = writer.len();
+        writer.finish()?;
+        Ok::<_, std::io::Error>(written)
+    })
+    .map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
 #[pymodule]
 fn waremax_gym(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<WaremaxEnv>()?;
+    m.add_class::<WaremaxMultiEnv>()?;
+    m.add_class::<WaremaxVecEnv>()?;
     m.add_function(wrap_pyfunction!(run_baseline, m)?)?;
+    m.add_function(wrap_pyfunction!(available_features, m)?)?;
+    m.add_function(wrap_pyfunction!(record_expert_dataset, m)?)?;
+    m.add_function(wrap_pyfunction!(reward_terms, m)?)?;
     m.add("MAX_ROBOTS", MAX_ROBOTS)?;
     m.add("ROBOT_FEATS", ROBOT_FEATS)?;
     m.add("TASK_FEATS", TASK_FEATS)?;
+    m.add("MAX_STATIONS", MAX_STATIONS)?;
+    m.add("STATION_FEATS", STATION_FEATS)?;
+    m.add("MAX_TASKS", MAX_TASKS)?;
+    m.add("AGENT_FEATS", AGENT_FEATS)?;
+    m.add("AGENT_TASK_FEATS", AGENT_TASK_FEATS)?;
+    m.add("PARK_ACTION", PARK_ACTION)?;
+    m.add("NODE_FEATS", NODE_FEATS)?;
+    m.add("EDGE_FEATS", EDGE_FEATS)?;
     Ok(())
 }
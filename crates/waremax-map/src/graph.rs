@@ -207,6 +207,42 @@ impl WarehouseMap {
             })
     }
 
+    /// v7: Edge from `from` to `to`, whether or not it is blocked
+    pub fn edge_between(&self, from: NodeId, to: NodeId) -> Option<EdgeId> {
+        self.adjacency
+            .get(&from)?
+            .iter()
+            .find(|(n, _, _)| *n == to)
+            .map(|(_, edge_id, _)| *edge_id)
+    }
+
+    /// v7: Edge running the opposite way of `edge`, if any
+    pub fn reverse_edge(&self, edge: EdgeId) -> Option<EdgeId> {
+        let e = self.edges.get(&edge)?;
+        self.edge_between(e.to, e.from)
+    }
+
+    /// v7: Whether entering `edge` toward `to` is closed
+    pub fn is_blocked(&self, edge: EdgeId, to: NodeId) -> bool {
+        self.blocked_edges.contains(&edge) || self.blocked_nodes.contains(&to)
+    }
+
+    /// v7: Close or reopen a node (routes avoid closed nodes)
+    pub fn set_node_blocked(&mut self, node: NodeId, blocked: bool) {
+        self.blocked_nodes.retain(|n| *n != node);
+        if blocked {
+            self.blocked_nodes.push(node);
+        }
+    }
+
+    /// v7: Close or reopen one direction of an edge
+    pub fn set_edge_blocked(&mut self, edge: EdgeId, blocked: bool) {
+        self.blocked_edges.retain(|e| *e != edge);
+        if blocked {
+            self.blocked_edges.push(edge);
+        }
+    }
+
     pub fn euclidean_distance(&self, from: NodeId, to: NodeId) -> f64 {
         let n1 = self.nodes.get(&from);
         let n2 = self.nodes.get(&to);
@@ -39,6 +39,7 @@ impl Route {
 }
 
 /// Cache for computed routes
+#[derive(Clone)]
 pub struct RouteCache {
     cache: HashMap<(NodeId, NodeId), Route>,
     max_size: usize,
@@ -72,6 +73,7 @@ impl RouteCache {
 }
 
 /// Router for finding paths in the warehouse
+#[derive(Clone)]
 pub struct Router {
     cache: RouteCache,
     cache_enabled: bool,
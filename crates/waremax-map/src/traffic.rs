@@ -6,6 +6,7 @@ use std::collections::{HashMap, HashSet};
 use waremax_core::{EdgeId, NodeId, RobotId};
 
 /// Manages traffic flow and capacity constraints in the warehouse
+#[derive(Clone)]
 pub struct TrafficManager {
     edge_occupancy: HashMap<EdgeId, HashSet<RobotId>>,
     node_occupancy: HashMap<NodeId, HashSet<RobotId>>,
@@ -23,6 +24,16 @@ pub struct TrafficManager {
     edge_directions: HashMap<EdgeId, Option<(NodeId, NodeId)>>,
     /// v4: Edge lengths for progress calculations
     edge_lengths: HashMap<EdgeId, f64>,
+    /// v7: Nodes robots must not enter because a human is within stop radius
+    human_stop_nodes: HashSet<NodeId>,
+    /// v7: Robot speed factor near humans, per node (lowest factor wins)
+    human_slow_nodes: HashMap<NodeId, f64>,
+    /// v7: Edges a human is currently traversing
+    human_edges: HashSet<EdgeId>,
+    /// v7: Nodes inside active human-only zones
+    human_reserved_nodes: HashSet<NodeId>,
+    /// v7: Deadlock cycles detected so far
+    deadlocks_detected: u64,
 }
 
 impl TrafficManager {
@@ -39,6 +50,11 @@ impl TrafficManager {
             continuous_states: HashMap::new(),
             edge_directions: HashMap::new(),
             edge_lengths: HashMap::new(),
+            human_stop_nodes: HashSet::new(),
+            human_slow_nodes: HashMap::new(),
+            human_edges: HashSet::new(),
+            human_reserved_nodes: HashSet::new(),
+            deadlocks_detected: 0,
         }
     }
 
@@ -47,6 +63,16 @@ impl TrafficManager {
         self.deadlock_detection_enabled = enabled;
     }
 
+    /// v7: Count a detected deadlock cycle
+    pub fn record_deadlock(&mut self) {
+        self.deadlocks_detected += 1;
+    }
+
+    /// v7: Deadlock cycles detected so far
+    pub fn deadlocks_detected(&self) -> u64 {
+        self.deadlocks_detected
+    }
+
     pub fn set_edge_capacity(&mut self, edge: EdgeId, capacity: u32) {
         self.edge_capacity.insert(edge, capacity);
     }
@@ -266,6 +292,49 @@ impl TrafficManager {
         }
     }
 
+    // === v7: Human Safety Restrictions ===
+
+    /// Replace the restrictions derived from human positions
+    pub fn set_human_restrictions(
+        &mut self,
+        stop_nodes: HashSet<NodeId>,
+        slow_nodes: HashMap<NodeId, f64>,
+        human_edges: HashSet<EdgeId>,
+    ) {
+        self.human_stop_nodes = stop_nodes;
+        self.human_slow_nodes = slow_nodes;
+        self.human_edges = human_edges;
+    }
+
+    /// Replace the set of nodes inside active human-only zones
+    pub fn set_human_reserved_nodes(&mut self, nodes: HashSet<NodeId>) {
+        self.human_reserved_nodes = nodes;
+    }
+
+    /// Whether a robot must stop rather than enter `edge` toward `to`
+    /// because of a nearby human or a reserved zone
+    pub fn blocked_by_humans(&self, edge: EdgeId, to: NodeId) -> bool {
+        self.human_edges.contains(&edge)
+            || self.human_stop_nodes.contains(&to)
+            || self.human_reserved_nodes.contains(&to)
+    }
+
+    /// Whether `node` is inside an active human-only zone
+    pub fn is_reserved_for_humans(&self, node: NodeId) -> bool {
+        self.human_reserved_nodes.contains(&node)
+    }
+
+    /// Speed factor for a robot traveling between `from` and `to`
+    /// (None when no human is close enough to slow it down)
+    pub fn human_slowdown(&self, from: NodeId, to: NodeId) -> Option<f64> {
+        let a = self.human_slow_nodes.get(&from).copied();
+        let b = self.human_slow_nodes.get(&to).copied();
+        match (a, b) {
+            (Some(x), Some(y)) => Some(x.min(y)),
+            (x, y) => x.or(y),
+        }
+    }
+
     /// Count occupants on an edge (backward-compatible alias).
     pub fn edge_occupancy_count(&self, edge: EdgeId) -> usize {
         self.get_edge_occupancy(edge)
@@ -397,6 +397,7 @@ mod tests {
             avg_queue_length: 1.5,
             max_queue_length: 5,
             utilization: 0.75,
+            operator_hours: None,
         }];
 
         write_station_csv(&path, &stations).unwrap();
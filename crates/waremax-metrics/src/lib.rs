@@ -20,8 +20,9 @@ pub use export::{
 pub use html_report::HtmlReportGenerator;
 pub use pdf_report::PdfReportGenerator;
 pub use report::{
-    BatteryReport, CongestionReport, EdgeCongestion, HeatmapData, NodeCongestion,
-    ReliabilityReport, RobotReport, SLAReport, SimulationReport, StationReport,
+    BatteryReport, CongestionReport, EdgeCongestion, HeatmapData, HumanAgentReport, HumanReport,
+    NodeCongestion, PickLevelReport, ReliabilityReport, RepositioningReport, RobotReport,
+    SLAReport, SimulationReport, SlottingReport, StationReport,
 };
 pub use timeseries::{
     ChargingTimeSeriesData, CongestionMetrics, CongestionRanking, DataPoint, StationTimeSeriesData,
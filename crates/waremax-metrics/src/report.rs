@@ -61,6 +61,9 @@ pub struct StationReport {
     pub avg_queue_length: f64,
     pub max_queue_length: usize,
     pub utilization: f64,
+    /// v7: Staffed operator-hours (stations with a staffing schedule only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operator_hours: Option<f64>,
 }
 
 /// Reliability metrics summary (v3)
@@ -104,6 +107,76 @@ pub struct HeatmapData {
     pub edge_congestion: Vec<EdgeCongestion>,
 }
 
+/// Idle repositioning (deadhead) section of the report (v7)
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RepositioningReport {
+    pub policy: String,
+    pub trips_started: u32,
+    pub trips_completed: u32,
+    /// Trips abandoned because the robot was assigned a task (or needed charge/repair) en route
+    pub trips_canceled: u32,
+    pub deadhead_distance_m: f64,
+    pub deadhead_time_s: f64,
+}
+
+impl RepositioningReport {
+    pub fn avg_trip_distance_m(&self) -> f64 {
+        if self.trips_started > 0 {
+            self.deadhead_distance_m / self.trips_started as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Per-agent human worker / manual vehicle summary (v7)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HumanAgentReport {
+    pub id: String,
+    pub agent_type: String,
+    pub tasks_completed: u32,
+    pub distance_m: f64,
+    pub walking_time_s: f64,
+    pub working_time_s: f64,
+}
+
+/// Human workers and their effect on robot traffic (v7)
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct HumanReport {
+    pub agents: Vec<HumanAgentReport>,
+    pub tasks_completed: u32,
+    pub distance_m: f64,
+    /// Robot departures held back by a nearby human or reserved zone
+    pub robot_yield_events: u32,
+    pub robot_yield_wait_s: f64,
+    /// Edges robots traversed at reduced speed near a human
+    pub robot_slowdowns: u32,
+    /// Extra travel time caused by slowdowns
+    pub robot_slowdown_delay_s: f64,
+}
+
+/// Picks served from one rack level (v7)
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PickLevelReport {
+    pub level: u32,
+    pub picks: u32,
+    /// Total level access time charged at pickup
+    pub access_time_s: f64,
+}
+
+/// Dynamic re-slotting activity and its effect on pick travel (v7)
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SlottingReport {
+    pub strategy: String,
+    pub reviews: u32,
+    pub moves_planned: u32,
+    pub moves_completed: u32,
+    /// Pick-weighted mean distance from SKU slots to the nearest station, initial layout
+    pub avg_pick_distance_before_m: f64,
+    /// Same measure over the final layout
+    pub avg_pick_distance_after_m: f64,
+}
+
 /// Final simulation report
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SimulationReport {
@@ -132,6 +205,15 @@ pub struct SimulationReport {
     pub reliability: Option<ReliabilityReport>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub heatmap: Option<HeatmapData>,
+    // v7 additions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repositioning: Option<RepositioningReport>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub humans: Option<HumanReport>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pick_levels: Option<Vec<PickLevelReport>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slotting: Option<SlottingReport>,
 }
 
 impl SimulationReport {
@@ -170,6 +252,10 @@ impl SimulationReport {
             station_reports: None,
             reliability: None,
             heatmap: None,
+            repositioning: None,
+            humans: None,
+            pick_levels: None,
+            slotting: None,
         }
     }
 
@@ -215,6 +301,30 @@ impl SimulationReport {
         self
     }
 
+    /// Set idle repositioning section (v7)
+    pub fn with_repositioning(mut self, repositioning: RepositioningReport) -> Self {
+        self.repositioning = Some(repositioning);
+        self
+    }
+
+    /// Set human workers section (v7)
+    pub fn with_humans(mut self, humans: HumanReport) -> Self {
+        self.humans = Some(humans);
+        self
+    }
+
+    /// Set per-level pick counts (v7)
+    pub fn with_pick_levels(mut self, levels: Vec<PickLevelReport>) -> Self {
+        self.pick_levels = Some(levels);
+        self
+    }
+
+    /// Set dynamic slotting section (v7)
+    pub fn with_slotting(mut self, slotting: SlottingReport) -> Self {
+        self.slotting = Some(slotting);
+        self
+    }
+
     pub fn to_json(&self) -> String {
         serde_json::to_string_pretty(self).unwrap_or_default()
     }
@@ -351,6 +461,79 @@ Reliability:
             ));
         }
 
+        // Add repositioning section if present (v7)
+        if let Some(ref repositioning) = self.repositioning {
+            output.push_str(&format!(
+                r#"
+Idle Repositioning ({}):
+  Trips: {} started, {} completed, {} canceled
+  Deadhead Distance: {:.1}m ({:.1}m/trip)
+  Deadhead Time: {:.1}s
+"#,
+                repositioning.policy,
+                repositioning.trips_started,
+                repositioning.trips_completed,
+                repositioning.trips_canceled,
+                repositioning.deadhead_distance_m,
+                repositioning.avg_trip_distance_m(),
+                repositioning.deadhead_time_s,
+            ));
+        }
+
+        // Add human workers section if present (v7)
+        if let Some(ref humans) = self.humans {
+            output.push_str(&format!(
+                r#"
+Human Workers ({} agents):
+  Tasks Completed: {}
+  Distance: {:.1}m
+  Robot Yields: {} ({:.1}s waiting)
+  Robot Slowdowns: {} (+{:.1}s travel)
+"#,
+                humans.agents.len(),
+                humans.tasks_completed,
+                humans.distance_m,
+                humans.robot_yield_events,
+                humans.robot_yield_wait_s,
+                humans.robot_slowdowns,
+                humans.robot_slowdown_delay_s,
+            ));
+        }
+
+        // Add per-level pick counts if present (v7)
+        if let Some(ref levels) = self.pick_levels {
+            output.push_str("\nPicks by Rack Level:\n");
+            for level in levels {
+                let avg_access_s = if level.picks > 0 {
+                    level.access_time_s / level.picks as f64
+                } else {
+                    0.0
+                };
+                output.push_str(&format!(
+                    "  Level {}: {} picks, {:.1}s avg access\n",
+                    level.level, level.picks, avg_access_s
+                ));
+            }
+        }
+
+        // Add dynamic slotting section if present (v7)
+        if let Some(ref slotting) = self.slotting {
+            output.push_str(&format!(
+                r#"
+Dynamic Slotting ({}):
+  Reviews: {}
+  Moves: {} planned, {} completed
+  Avg Pick Distance: {:.1}m -> {:.1}m
+"#,
+                slotting.strategy,
+                slotting.reviews,
+                slotting.moves_planned,
+                slotting.moves_completed,
+                slotting.avg_pick_distance_before_m,
+                slotting.avg_pick_distance_after_m,
+            ));
+        }
+
         // Add per-robot summary if present (v3)
         if let Some(ref robots) = self.robot_reports {
             output.push_str(&format!("\nPer-Robot Summary ({} robots):\n", robots.len()));
@@ -376,13 +559,17 @@ Reliability:
             ));
             for station in stations.iter().take(5) {
                 output.push_str(&format!(
-                    "  {} ({}): {} served, avg queue {:.1}, {:.1}% utilization\n",
+                    "  {} ({}): {} served, avg queue {:.1}, {:.1}% utilization",
                     station.string_id,
                     station.station_type,
                     station.orders_served,
                     station.avg_queue_length,
                     station.utilization * 100.0,
                 ));
+                if let Some(hours) = station.operator_hours {
+                    output.push_str(&format!(", {:.1} operator-h", hours));
+                }
+                output.push('\n');
             }
             if stations.len() > 5 {
                 output.push_str(&format!("  ... and {} more stations\n", stations.len() - 5));
@@ -412,6 +599,10 @@ impl Default for SimulationReport {
             station_reports: None,
             reliability: None,
             heatmap: None,
+            repositioning: None,
+            humans: None,
+            pick_levels: None,
+            slotting: None,
         }
     }
 }
@@ -265,6 +265,6 @@ mod tests {
         }
 
         // With 50% sample rate, should sample approximately 50
-        assert!(sampled_count >= 40 && sampled_count <= 60);
+        assert!((40..=60).contains(&sampled_count));
     }
 }
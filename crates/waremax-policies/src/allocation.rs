@@ -5,6 +5,7 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 use waremax_core::{RobotId, TaskId};
 
 /// Allocate tasks to the nearest idle robot
+#[derive(Clone)]
 pub struct NearestRobotPolicy;
 
 impl NearestRobotPolicy {
@@ -56,6 +57,15 @@ pub struct RoundRobinPolicy {
     last_index: AtomicUsize,
 }
 
+impl Clone for RoundRobinPolicy {
+    /// A clone continues the cycle from where this policy is
+    fn clone(&self) -> Self {
+        Self {
+            last_index: AtomicUsize::new(self.last_index.load(Ordering::Relaxed)),
+        }
+    }
+}
+
 impl RoundRobinPolicy {
     pub fn new() -> Self {
         Self {
@@ -92,6 +102,7 @@ impl TaskAllocationPolicy for RoundRobinPolicy {
 }
 
 /// Allocate tasks to the robot with the smallest task queue
+#[derive(Clone)]
 pub struct LeastBusyPolicy;
 
 impl LeastBusyPolicy {
@@ -124,6 +135,7 @@ impl TaskAllocationPolicy for LeastBusyPolicy {
 
 /// Auction-based task allocation
 /// Each robot "bids" based on estimated completion cost
+#[derive(Clone)]
 pub struct AuctionPolicy {
     /// Weight for travel distance in bid calculation
     travel_weight: f64,
@@ -184,6 +196,7 @@ impl TaskAllocationPolicy for AuctionPolicy {
 
 /// Workload-balanced allocation policy
 /// Aims to equalize total estimated work across robots
+#[derive(Clone)]
 pub struct WorkloadBalancedPolicy {
     /// Consider travel time in workload estimation
     include_travel: bool,
@@ -282,6 +295,7 @@ mod tests {
             stations,
             orders,
             attribution: None,
+            traffic: None,
         }
     }
 
@@ -4,6 +4,7 @@ use crate::traits::{BatchingPolicy, PolicyContext};
 use waremax_core::TaskId;
 
 /// No batching - each task is independent
+#[derive(Clone)]
 pub struct NoBatchingPolicy;
 
 impl NoBatchingPolicy {
@@ -30,6 +31,7 @@ impl BatchingPolicy for NoBatchingPolicy {
 }
 
 /// Groups tasks by proximity (zone-based clustering)
+#[derive(Clone)]
 pub struct ZoneBatchingPolicy {
     max_items: u32,
     zone_radius: f64,
@@ -101,6 +103,7 @@ impl BatchingPolicy for ZoneBatchingPolicy {
 
 /// Groups tasks by destination station
 /// Respects max_items and optional max_weight constraints
+#[derive(Clone)]
 pub struct StationBatchPolicy {
     max_items: u32,
     max_weight_kg: Option<f64>,
@@ -218,6 +221,7 @@ mod tests {
             stations,
             orders,
             attribution: None,
+            traffic: None,
         }
     }
 
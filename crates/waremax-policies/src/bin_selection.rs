@@ -0,0 +1,265 @@
+//! Pickup bin selection policies: which in-stock replica a pick task draws from
+//!
+//! Invoked when a pick task is assigned to a robot. Orders are created against
+//! the first bin with stock; a policy may move the pickup to another replica of
+//! the same SKU once the robot and destination station are known.
+
+use dyn_clone::DynClone;
+use waremax_core::NodeId;
+use waremax_map::WarehouseMap;
+use waremax_storage::BinAddress;
+
+/// An in-stock bin holding the requested SKU
+#[derive(Clone, Debug)]
+pub struct PickupBinCandidate {
+    pub address: BinAddress,
+    pub access_node: NodeId,
+    /// Units currently in the bin
+    pub quantity: u32,
+    /// Time to reach the bin's level at the rack (seconds)
+    pub access_time_s: f64,
+}
+
+/// Context for pickup bin selection
+pub struct BinSelectionContext<'a> {
+    pub map: &'a WarehouseMap,
+    pub robot_node: NodeId,
+    pub station_node: NodeId,
+    pub robot_speed_mps: f64,
+    /// Units the task picks
+    pub quantity: u32,
+}
+
+impl BinSelectionContext<'_> {
+    /// Straight-line robot -> bin -> station distance through a candidate
+    pub fn trip_distance(&self, candidate: &PickupBinCandidate) -> f64 {
+        self.map
+            .euclidean_distance(self.robot_node, candidate.access_node)
+            + self
+                .map
+                .euclidean_distance(candidate.access_node, self.station_node)
+    }
+}
+
+/// Policy choosing the pickup bin for an assigned pick task
+pub trait BinSelectionPolicy: DynClone + Send + Sync {
+    /// Index of the candidate to pick from (`None` = keep the current bin)
+    fn select_bin(
+        &self,
+        ctx: &BinSelectionContext,
+        candidates: &[PickupBinCandidate],
+    ) -> Option<usize>;
+
+    /// Policy name for logging
+    fn name(&self) -> &'static str;
+}
+
+dyn_clone::clone_trait_object!(BinSelectionPolicy);
+
+/// Keep the first in-stock bin chosen at order creation (the historical behavior)
+#[derive(Clone)]
+pub struct FirstAvailableBinPolicy;
+
+impl FirstAvailableBinPolicy {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for FirstAvailableBinPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BinSelectionPolicy for FirstAvailableBinPolicy {
+    fn select_bin(
+        &self,
+        _ctx: &BinSelectionContext,
+        _candidates: &[PickupBinCandidate],
+    ) -> Option<usize> {
+        None
+    }
+
+    fn name(&self) -> &'static str {
+        "first_available"
+    }
+}
+
+/// Pick the replica minimizing robot -> bin -> station travel
+#[derive(Clone)]
+pub struct NearestBinPolicy;
+
+impl NearestBinPolicy {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for NearestBinPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BinSelectionPolicy for NearestBinPolicy {
+    fn select_bin(
+        &self,
+        ctx: &BinSelectionContext,
+        candidates: &[PickupBinCandidate],
+    ) -> Option<usize> {
+        candidates
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| {
+                ctx.trip_distance(a)
+                    .partial_cmp(&ctx.trip_distance(b))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+                    .then(a.access_node.0.cmp(&b.access_node.0))
+            })
+            .map(|(idx, _)| idx)
+    }
+
+    fn name(&self) -> &'static str {
+        "nearest"
+    }
+}
+
+/// Trade travel time against level access time and stock consolidation
+///
+/// Cost in seconds is travel time, plus `level_weight` times the level access
+/// time, plus `consolidation_weight_s` per unit left in the bin after the
+/// pick. A positive consolidation weight drains nearly-empty bins first, freeing
+/// slots instead of spreading stock thinly.
+#[derive(Clone)]
+pub struct LevelAwareBinPolicy {
+    level_weight: f64,
+    consolidation_weight_s: f64,
+}
+
+impl LevelAwareBinPolicy {
+    pub fn new(level_weight: f64, consolidation_weight_s: f64) -> Self {
+        Self {
+            level_weight,
+            consolidation_weight_s,
+        }
+    }
+
+    fn cost(&self, ctx: &BinSelectionContext, candidate: &PickupBinCandidate) -> f64 {
+        let travel_s = ctx.trip_distance(candidate) / ctx.robot_speed_mps.max(0.01);
+        let remaining = candidate.quantity.saturating_sub(ctx.quantity) as f64;
+        travel_s
+            + self.level_weight * candidate.access_time_s
+            + self.consolidation_weight_s * remaining
+    }
+}
+
+impl Default for LevelAwareBinPolicy {
+    fn default() -> Self {
+        Self::new(1.0, 0.0)
+    }
+}
+
+impl BinSelectionPolicy for LevelAwareBinPolicy {
+    fn select_bin(
+        &self,
+        ctx: &BinSelectionContext,
+        candidates: &[PickupBinCandidate],
+    ) -> Option<usize> {
+        candidates
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| {
+                self.cost(ctx, a)
+                    .partial_cmp(&self.cost(ctx, b))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+                    .then(a.access_node.0.cmp(&b.access_node.0))
+                    .then(a.address.level.cmp(&b.address.level))
+                    .then(a.address.bin.cmp(&b.address.bin))
+            })
+            .map(|(idx, _)| idx)
+    }
+
+    fn name(&self) -> &'static str {
+        "level_aware"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use waremax_core::RackId;
+    use waremax_map::{Node, NodeType};
+
+    fn line_map() -> WarehouseMap {
+        // 0 - 1 - 2 - 3 along x, 10m apart
+        let mut map = WarehouseMap::new();
+        for i in 0..4u32 {
+            map.add_node(Node::new(
+                NodeId(i),
+                format!("N{}", i),
+                i as f64 * 10.0,
+                0.0,
+                NodeType::Rack,
+            ));
+        }
+        map
+    }
+
+    fn candidate(node: u32, level: u32, quantity: u32, access_time_s: f64) -> PickupBinCandidate {
+        PickupBinCandidate {
+            address: BinAddress::new(RackId(node), level, 0),
+            access_node: NodeId(node),
+            quantity,
+            access_time_s,
+        }
+    }
+
+    fn ctx(map: &WarehouseMap) -> BinSelectionContext<'_> {
+        BinSelectionContext {
+            map,
+            robot_node: NodeId(0),
+            station_node: NodeId(0),
+            robot_speed_mps: 1.0,
+            quantity: 1,
+        }
+    }
+
+    #[test]
+    fn test_first_available_keeps_current_bin() {
+        let map = line_map();
+        let candidates = vec![candidate(3, 0, 10, 0.0), candidate(1, 0, 10, 0.0)];
+        assert_eq!(
+            FirstAvailableBinPolicy::new().select_bin(&ctx(&map), &candidates),
+            None
+        );
+        assert_eq!(
+            NearestBinPolicy::new().select_bin(&ctx(&map), &candidates),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_level_aware_trades_height_against_travel() {
+        let map = line_map();
+        // Near bin on a high level (20m round trip + 30s lift) vs. a far
+        // ground-level bin (40m round trip + 2s lift)
+        let candidates = vec![candidate(1, 4, 10, 30.0), candidate(2, 0, 10, 2.0)];
+
+        // Ignoring level height, the near bin wins
+        let travel_only = LevelAwareBinPolicy::new(0.0, 0.0);
+        assert_eq!(travel_only.select_bin(&ctx(&map), &candidates), Some(0));
+
+        let level_aware = LevelAwareBinPolicy::default();
+        assert_eq!(level_aware.select_bin(&ctx(&map), &candidates), Some(1));
+    }
+
+    #[test]
+    fn test_level_aware_consolidates_stock() {
+        let map = line_map();
+        // Same location and level; one bin nearly empty
+        let candidates = vec![candidate(1, 0, 30, 2.0), candidate(1, 1, 2, 2.0)];
+        let policy = LevelAwareBinPolicy::new(1.0, 0.5);
+        assert_eq!(policy.select_bin(&ctx(&map), &candidates), Some(1));
+    }
+}
@@ -2,6 +2,7 @@
 //!
 //! Provides strategies for resolving deadlocks when detected in the wait-for graph.
 
+use dyn_clone::DynClone;
 use waremax_core::{NodeId, RobotId, SimTime};
 
 /// Result of a deadlock resolution decision
@@ -91,7 +92,7 @@ impl DeadlockContext {
 }
 
 /// Trait for deadlock resolution strategies
-pub trait DeadlockResolver: Send + Sync {
+pub trait DeadlockResolver: DynClone + Send + Sync {
     /// Resolve a deadlock given the context
     fn resolve(&self, ctx: &DeadlockContext) -> DeadlockResolution;
 
@@ -99,6 +100,8 @@ pub trait DeadlockResolver: Send + Sync {
     fn name(&self) -> &'static str;
 }
 
+dyn_clone::clone_trait_object!(DeadlockResolver);
+
 /// Resolver that makes the youngest robot (highest ID) back up
 ///
 /// This is a simple deterministic strategy that ensures consistent
@@ -0,0 +1,371 @@
+//! Idle positioning policies: where a robot should wait after finishing a task
+//!
+//! Invoked when a robot completes a task and is not immediately given new
+//! work. A policy either keeps the robot where it is or names a parking node
+//! to reposition to. Repositioning robots stay assignable, so any trip is
+//! canceled as soon as real work arrives.
+
+use dyn_clone::DynClone;
+use std::collections::HashMap;
+use waremax_core::{NodeId, RobotId, SimTime, TaskId};
+use waremax_entities::{Robot, Task};
+use waremax_map::{NodeType, WarehouseMap};
+
+/// Context for idle positioning decisions
+pub struct IdlePositioningContext<'a> {
+    pub current_time: SimTime,
+    pub map: &'a WarehouseMap,
+    pub robots: &'a HashMap<RobotId, Robot>,
+    pub tasks: &'a HashMap<TaskId, Task>,
+}
+
+impl IdlePositioningContext<'_> {
+    /// Nodes idle robots may park on: the configured list when non-empty,
+    /// otherwise every `Staging` node of the map. Sorted by id for determinism.
+    pub fn parking_candidates(&self, configured: &[NodeId]) -> Vec<NodeId> {
+        let mut nodes: Vec<NodeId> = if configured.is_empty() {
+            self.map
+                .nodes
+                .values()
+                .filter(|n| n.node_type == NodeType::Staging)
+                .map(|n| n.id)
+                .collect()
+        } else {
+            configured
+                .iter()
+                .copied()
+                .filter(|id| self.map.get_node(*id).is_some())
+                .collect()
+        };
+        nodes.sort_by_key(|n| n.0);
+        nodes.dedup();
+        nodes
+    }
+
+    /// Whether another robot already occupies or is heading to `node`
+    pub fn is_claimed(&self, node: NodeId, robot_id: RobotId) -> bool {
+        self.robots.values().any(|r| {
+            r.id != robot_id
+                && (r.repositioning_to == Some(node)
+                    || (r.current_node == node && r.is_available()))
+        })
+    }
+}
+
+/// Policy deciding where an idle robot should wait for its next task
+pub trait IdlePositioningPolicy: DynClone + Send + Sync {
+    /// Select a node for the robot to reposition to (`None` = stay put)
+    fn select_parking(&self, ctx: &IdlePositioningContext, robot_id: RobotId) -> Option<NodeId>;
+
+    /// Policy name for logging
+    fn name(&self) -> &'static str;
+}
+
+dyn_clone::clone_trait_object!(IdlePositioningPolicy);
+
+/// Leave idle robots where they finished (the historical behavior)
+#[derive(Clone)]
+pub struct StayPolicy;
+
+impl StayPolicy {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for StayPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IdlePositioningPolicy for StayPolicy {
+    fn select_parking(&self, _ctx: &IdlePositioningContext, _robot_id: RobotId) -> Option<NodeId> {
+        None
+    }
+
+    fn name(&self) -> &'static str {
+        "stay"
+    }
+}
+
+/// Send idle robots to the nearest unclaimed parking node
+#[derive(Clone)]
+pub struct NearestStagingPolicy {
+    parking_nodes: Vec<NodeId>,
+}
+
+impl NearestStagingPolicy {
+    /// Create with an explicit parking list (empty = the map's staging nodes)
+    pub fn new(parking_nodes: Vec<NodeId>) -> Self {
+        Self { parking_nodes }
+    }
+}
+
+impl Default for NearestStagingPolicy {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl IdlePositioningPolicy for NearestStagingPolicy {
+    fn select_parking(&self, ctx: &IdlePositioningContext, robot_id: RobotId) -> Option<NodeId> {
+        let robot = ctx.robots.get(&robot_id)?;
+        let candidates = ctx.parking_candidates(&self.parking_nodes);
+
+        // Already parked
+        if candidates.contains(&robot.current_node) {
+            return None;
+        }
+
+        // Candidates are id-sorted, so strict `<` keeps the lowest id on ties
+        let mut best: Option<(NodeId, f64)> = None;
+        for node in candidates {
+            if ctx.is_claimed(node, robot_id) {
+                continue;
+            }
+            let dist = ctx.map.euclidean_distance(robot.current_node, node);
+            if best.is_none_or(|(_, d)| dist < d) {
+                best = Some((node, dist));
+            }
+        }
+        best.map(|(node, _)| node)
+    }
+
+    fn name(&self) -> &'static str {
+        "nearest_staging"
+    }
+}
+
+/// Rebalance idle robots toward predicted pickup hotspots
+///
+/// Demand is estimated from the pickup nodes of tasks created within the
+/// trailing window; each parking node scores the demand within
+/// `radius_m`, shared between the robots already parked there or en route.
+/// Travel distance is a small penalty so that nearer parking wins ties.
+#[derive(Clone)]
+pub struct DemandWeightedPolicy {
+    parking_nodes: Vec<NodeId>,
+    window_s: f64,
+    radius_m: f64,
+    travel_weight: f64,
+}
+
+impl DemandWeightedPolicy {
+    pub fn new(parking_nodes: Vec<NodeId>, window_s: f64, radius_m: f64) -> Self {
+        Self {
+            parking_nodes,
+            window_s,
+            radius_m,
+            travel_weight: 0.01,
+        }
+    }
+}
+
+impl Default for DemandWeightedPolicy {
+    fn default() -> Self {
+        Self::new(Vec::new(), 600.0, 15.0)
+    }
+}
+
+impl IdlePositioningPolicy for DemandWeightedPolicy {
+    fn select_parking(&self, ctx: &IdlePositioningContext, robot_id: RobotId) -> Option<NodeId> {
+        let robot = ctx.robots.get(&robot_id)?;
+        let candidates = ctx.parking_candidates(&self.parking_nodes);
+        if candidates.is_empty() {
+            return None;
+        }
+
+        // Recent pickup locations
+        let now_s = ctx.current_time.as_seconds();
+        let pickups: Vec<NodeId> = ctx
+            .tasks
+            .values()
+            .filter(|t| now_s - t.created_at.as_seconds() <= self.window_s)
+            .map(|t| t.source.access_node)
+            .collect();
+
+        // With no recent demand there is nothing to rebalance toward
+        if pickups.is_empty() {
+            return None;
+        }
+
+        let score = |node: NodeId| -> f64 {
+            let demand = pickups
+                .iter()
+                .filter(|p| ctx.map.euclidean_distance(node, **p) <= self.radius_m)
+                .count() as f64;
+            let supply = ctx
+                .robots
+                .values()
+                .filter(|r| {
+                    r.id != robot_id
+                        && (r.repositioning_to == Some(node)
+                            || (r.current_node == node && r.is_available()))
+                })
+                .count() as f64;
+            let travel = ctx.map.euclidean_distance(robot.current_node, node);
+            demand / (1.0 + supply) - self.travel_weight * travel
+        };
+
+        let mut best: Option<(NodeId, f64)> = None;
+        for node in candidates {
+            let s = score(node);
+            if best.is_none_or(|(_, b)| s > b) {
+                best = Some((node, s));
+            }
+        }
+
+        // Staying is free: only move when the best spot beats the current one
+        let (node, best_score) = best?;
+        if node == robot.current_node || best_score <= score(robot.current_node) {
+            return None;
+        }
+        Some(node)
+    }
+
+    fn name(&self) -> &'static str {
+        "demand_weighted"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use waremax_core::{OrderId, SkuId, StationId};
+    use waremax_entities::BinLocation;
+    use waremax_map::Node;
+    use waremax_storage::BinAddress;
+
+    fn line_map() -> WarehouseMap {
+        // 0 - 1 - 2 - 3 - 4 along x, 10m apart; 0 and 4 are staging
+        let mut map = WarehouseMap::new();
+        for i in 0..5u32 {
+            let node_type = if i == 0 || i == 4 {
+                NodeType::Staging
+            } else {
+                NodeType::Aisle
+            };
+            map.add_node(Node::new(
+                NodeId(i),
+                format!("N{}", i),
+                i as f64 * 10.0,
+                0.0,
+                node_type,
+            ));
+        }
+        map
+    }
+
+    fn pick_at(id: u32, node: u32, created_s: f64) -> Task {
+        Task::new_pick(
+            TaskId(id),
+            OrderId(id),
+            SkuId(0),
+            1,
+            BinLocation::new(BinAddress::new(waremax_core::RackId(0), 0, 0), NodeId(node)),
+            StationId(0),
+            SimTime::from_seconds(created_s),
+        )
+    }
+
+    #[test]
+    fn test_stay_never_moves() {
+        let map = line_map();
+        let mut robots = HashMap::new();
+        robots.insert(RobotId(0), Robot::new(RobotId(0), NodeId(2), 1.5, 25.0));
+        let tasks = HashMap::new();
+        let ctx = IdlePositioningContext {
+            current_time: SimTime::ZERO,
+            map: &map,
+            robots: &robots,
+            tasks: &tasks,
+        };
+        assert_eq!(StayPolicy::new().select_parking(&ctx, RobotId(0)), None);
+    }
+
+    #[test]
+    fn test_nearest_staging_skips_claimed() {
+        let map = line_map();
+        let mut robots = HashMap::new();
+        robots.insert(RobotId(0), Robot::new(RobotId(0), NodeId(1), 1.5, 25.0));
+        let tasks = HashMap::new();
+        let policy = NearestStagingPolicy::default();
+
+        let ctx = IdlePositioningContext {
+            current_time: SimTime::ZERO,
+            map: &map,
+            robots: &robots,
+            tasks: &tasks,
+        };
+        assert_eq!(policy.select_parking(&ctx, RobotId(0)), Some(NodeId(0)));
+
+        // Another robot parked at node 0 pushes us to node 4
+        robots.insert(RobotId(1), Robot::new(RobotId(1), NodeId(0), 1.5, 25.0));
+        let ctx = IdlePositioningContext {
+            current_time: SimTime::ZERO,
+            map: &map,
+            robots: &robots,
+            tasks: &tasks,
+        };
+        assert_eq!(policy.select_parking(&ctx, RobotId(0)), Some(NodeId(4)));
+    }
+
+    #[test]
+    fn test_nearest_staging_stays_when_parked() {
+        let map = line_map();
+        let mut robots = HashMap::new();
+        robots.insert(RobotId(0), Robot::new(RobotId(0), NodeId(4), 1.5, 25.0));
+        let tasks = HashMap::new();
+        let ctx = IdlePositioningContext {
+            current_time: SimTime::ZERO,
+            map: &map,
+            robots: &robots,
+            tasks: &tasks,
+        };
+        assert_eq!(
+            NearestStagingPolicy::default().select_parking(&ctx, RobotId(0)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_demand_weighted_moves_toward_hotspot() {
+        let map = line_map();
+        let mut robots = HashMap::new();
+        robots.insert(RobotId(0), Robot::new(RobotId(0), NodeId(1), 1.5, 25.0));
+        let mut tasks = HashMap::new();
+        for i in 0..3 {
+            tasks.insert(TaskId(i), pick_at(i, 4, 100.0));
+        }
+        // Stale demand outside the window is ignored
+        tasks.insert(TaskId(9), pick_at(9, 0, 0.0));
+
+        let ctx = IdlePositioningContext {
+            current_time: SimTime::from_seconds(200.0),
+            map: &map,
+            robots: &robots,
+            tasks: &tasks,
+        };
+        let policy = DemandWeightedPolicy::new(Vec::new(), 150.0, 5.0);
+        assert_eq!(policy.select_parking(&ctx, RobotId(0)), Some(NodeId(4)));
+    }
+
+    #[test]
+    fn test_demand_weighted_stays_without_demand() {
+        let map = line_map();
+        let mut robots = HashMap::new();
+        robots.insert(RobotId(0), Robot::new(RobotId(0), NodeId(2), 1.5, 25.0));
+        let tasks = HashMap::new();
+        let ctx = IdlePositioningContext {
+            current_time: SimTime::ZERO,
+            map: &map,
+            robots: &robots,
+            tasks: &tasks,
+        };
+        assert_eq!(
+            DemandWeightedPolicy::default().select_parking(&ctx, RobotId(0)),
+            None
+        );
+    }
+}
@@ -0,0 +1,516 @@
+//! Learned task allocation: a trained candidate-scoring network run in Rust (v7)
+//!
+//! The network is the actor of `waremax-gym`'s `CandidateScoringPolicy`: a
+//! shared MLP that scores each available robot from its features concatenated
+//! with the task's features. The robot with the highest score gets the task.
+//!
+//! Weights file (little-endian): magic `WMXNN\0\0\0`, `u32` version (1), `u32`
+//! robot features, `u32` task features, `u32` layer count; then per layer `u32`
+//! outputs, `u32` inputs, `u8` activation (0 identity, 1 tanh, 2 relu),
+//! `outputs * inputs` `f32` weights in row-major `[out][in]` order (PyTorch
+//! `Linear.weight` layout) and `outputs` `f32` biases. The first layer takes
+//! `robot + task` features and the last produces one score.
+//! `python/export_policy.py` in `waremax-gym` writes this format.
+//!
+//! Features follow the default observation layout of `waremax-rl` (8 robot
+//! features, 6 task features). Both take their normalization from [`norms`],
+//! and a parity test there keeps the two in step.
+
+use std::path::Path;
+
+use crate::traits::{PolicyContext, TaskAllocationPolicy};
+use waremax_core::{RobotId, TaskId};
+use waremax_entities::Robot;
+
+const MAGIC: &[u8; 8] = b"WMXNN\0\0\0";
+const FORMAT_VERSION: u32 = 1;
+
+/// Robot features per candidate.
+pub const LEARNED_ROBOT_FEATS: usize = 8;
+/// Task features appended to every candidate.
+pub const LEARNED_TASK_FEATS: usize = 6;
+
+/// Normalization scales of the default observation layout.
+///
+/// `waremax-rl`'s default `ObsNorms` and candidate rows are these, so a model
+/// trained there sees the features this policy computes.
+pub mod norms {
+    pub const DISTANCE_M: f64 = 50.0;
+    pub const TRAVEL_M: f64 = 1000.0;
+    pub const QUEUE: f64 = 10.0;
+    pub const TASKS_COMPLETED: f64 = 100.0;
+    pub const PENDING: f64 = 50.0;
+    /// Half-width of the due-slack window, in minutes.
+    pub const DUE_WINDOW_MIN: f64 = 120.0;
+    pub const PHASE_S: f64 = 7200.0;
+    /// Candidate rows the features' `candidate_fraction` is relative to.
+    pub const CANDIDATE_ROWS: usize = 64;
+}
+
+/// Activation applied after a dense layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Activation {
+    Identity,
+    Tanh,
+    Relu,
+}
+
+impl Activation {
+    fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(Self::Identity),
+            1 => Some(Self::Tanh),
+            2 => Some(Self::Relu),
+            _ => None,
+        }
+    }
+
+    fn code(self) -> u8 {
+        match self {
+            Self::Identity => 0,
+            Self::Tanh => 1,
+            Self::Relu => 2,
+        }
+    }
+
+    fn apply(self, x: f32) -> f32 {
+        match self {
+            Self::Identity => x,
+            Self::Tanh => x.tanh(),
+            Self::Relu => x.max(0.0),
+        }
+    }
+}
+
+/// One fully connected layer.
+#[derive(Debug, Clone)]
+pub struct DenseLayer {
+    pub inputs: usize,
+    pub outputs: usize,
+    /// Row-major `[outputs][inputs]`.
+    pub weights: Vec<f32>,
+    pub bias: Vec<f32>,
+    pub activation: Activation,
+}
+
+/// The candidate-scoring network.
+#[derive(Debug, Clone)]
+pub struct CandidateScorer {
+    robot_feats: usize,
+    task_feats: usize,
+    layers: Vec<DenseLayer>,
+}
+
+impl CandidateScorer {
+    /// Validate layer shapes: the first takes `robot_feats + task_feats`
+    /// inputs, each feeds the next, and the last has one output.
+    pub fn new(
+        robot_feats: usize,
+        task_feats: usize,
+        layers: Vec<DenseLayer>,
+    ) -> Result<Self, String> {
+        if layers.is_empty() {
+            return Err("scorer has no layers".to_string());
+        }
+        let mut width = robot_feats + task_feats;
+        for (i, layer) in layers.iter().enumerate() {
+            if layer.inputs != width {
+                return Err(format!(
+                    "layer {i} takes {} inputs, expected {width}",
+                    layer.inputs
+                ));
+            }
+            if layer.inputs.checked_mul(layer.outputs) != Some(layer.weights.len())
+                || layer.bias.len() != layer.outputs
+            {
+                return Err(format!("layer {i} has mis-sized weights or bias"));
+            }
+            width = layer.outputs;
+        }
+        if width != 1 {
+            return Err(format!("last layer has {width} outputs, expected 1"));
+        }
+        Ok(Self {
+            robot_feats,
+            task_feats,
+            layers,
+        })
+    }
+
+    /// Load a weights file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        Self::from_bytes(&bytes).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    /// Parse the weights format.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let mut r = Reader { bytes, pos: 0 };
+        if r.take(8)? != MAGIC {
+            return Err("not a waremax weights file".to_string());
+        }
+        let version = r.u32()?;
+        if version != FORMAT_VERSION {
+            return Err(format!("unsupported weights version {version}"));
+        }
+        let robot_feats = r.u32()? as usize;
+        let task_feats = r.u32()? as usize;
+        let n_layers = r.u32()?;
+        let mut layers = Vec::new();
+        for _ in 0..n_layers {
+            let outputs = r.u32()? as usize;
+            let inputs = r.u32()? as usize;
+            let code = r.take(1)?[0];
+            let activation = Activation::from_code(code)
+                .ok_or_else(|| format!("unknown activation code {code}"))?;
+            let size = outputs
+                .checked_mul(inputs)
+                .ok_or_else(|| "layer size overflows".to_string())?;
+            let weights = r.f32s(size)?;
+            let bias = r.f32s(outputs)?;
+            layers.push(DenseLayer {
+                inputs,
+                outputs,
+                weights,
+                bias,
+                activation,
+            });
+        }
+        if r.pos != bytes.len() {
+            return Err("trailing bytes after the last layer".to_string());
+        }
+        Self::new(robot_feats, task_feats, layers)
+    }
+
+    /// Serialize to the weights format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+        for v in [
+            FORMAT_VERSION,
+            self.robot_feats as u32,
+            self.task_feats as u32,
+            self.layers.len() as u32,
+        ] {
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        for layer in &self.layers {
+            out.extend_from_slice(&(layer.outputs as u32).to_le_bytes());
+            out.extend_from_slice(&(layer.inputs as u32).to_le_bytes());
+            out.push(layer.activation.code());
+            for v in layer.weights.iter().chain(&layer.bias) {
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+        out
+    }
+
+    pub fn robot_feats(&self) -> usize {
+        self.robot_feats
+    }
+
+    pub fn task_feats(&self) -> usize {
+        self.task_feats
+    }
+
+    /// Score one candidate from its robot features and the task features.
+    pub fn score(&self, robot: &[f32], task: &[f32]) -> f32 {
+        let mut x: Vec<f32> = robot.iter().chain(task).copied().collect();
+        for layer in &self.layers {
+            x = (0..layer.outputs)
+                .map(|o| {
+                    let row = &layer.weights[o * layer.inputs..(o + 1) * layer.inputs];
+                    let sum: f32 = row.iter().zip(&x).map(|(w, v)| w * v).sum();
+                    layer.activation.apply(sum + layer.bias[o])
+                })
+                .collect();
+        }
+        x[0]
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| "weights file is truncated".to_string())?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn f32s(&mut self, n: usize) -> Result<Vec<f32>, String> {
+        let len = n
+            .checked_mul(4)
+            .ok_or_else(|| "weights file is truncated".to_string())?;
+        Ok(self
+            .take(len)?
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+            .collect())
+    }
+}
+
+/// Features of one allocation decision in the default layout.
+#[derive(Debug, Clone)]
+pub struct CandidateFeatures {
+    /// Available robots, sorted by id.
+    pub robot_ids: Vec<RobotId>,
+    /// `LEARNED_ROBOT_FEATS` per robot, in `robot_ids` order.
+    pub robot_feats: Vec<[f32; LEARNED_ROBOT_FEATS]>,
+    pub task_feats: [f32; LEARNED_TASK_FEATS],
+}
+
+/// Compute the default-layout features for allocating `task_id`, or `None`
+/// when the task is unknown or no robot is available.
+pub fn candidate_features(ctx: &PolicyContext, task_id: TaskId) -> Option<CandidateFeatures> {
+    let task = ctx.tasks.get(&task_id)?;
+    let pickup = task.source.access_node;
+    let mut robots: Vec<&Robot> = ctx.robots.values().filter(|r| r.is_available()).collect();
+    if robots.is_empty() {
+        return None;
+    }
+    robots.sort_by_key(|r| r.id.0);
+
+    let xy = |node| {
+        ctx.map
+            .get_node(node)
+            .map(|n| (n.x, n.y))
+            .unwrap_or((0.0, 0.0))
+    };
+    let (px, py) = xy(pickup);
+    let robot_feats = robots
+        .iter()
+        .map(|robot| {
+            let (rx, ry) = xy(robot.current_node);
+            [
+                norm(
+                    ctx.map.euclidean_distance(robot.current_node, pickup),
+                    norms::DISTANCE_M,
+                ),
+                robot.battery.soc as f32,
+                norm(robot.task_queue.len() as f64, norms::QUEUE),
+                1.0,
+                norm(robot.tasks_completed as f64, norms::TASKS_COMPLETED),
+                ((rx - px) / norms::DISTANCE_M).clamp(-1.0, 1.0) as f32,
+                ((ry - py) / norms::DISTANCE_M).clamp(-1.0, 1.0) as f32,
+                norm(robot.total_distance, norms::TRAVEL_M),
+            ]
+        })
+        .collect();
+
+    let due_slack = task
+        .order_id
+        .and_then(|oid| ctx.orders.get(&oid))
+        .and_then(|order| order.due_time)
+        .map_or(0.0, |due| {
+            let mins = (due.as_seconds() - ctx.current_time.as_seconds()) / 60.0;
+            (mins / norms::DUE_WINDOW_MIN).clamp(-1.0, 1.0) as f32
+        });
+    let pending = ctx.tasks.values().filter(|t| t.is_pending()).count();
+    let station_queue = ctx
+        .stations
+        .get(&task.destination_station)
+        .map(|s| s.queue_length())
+        .unwrap_or(0);
+    let task_feats = [
+        due_slack,
+        norm(task.quantity as f64, norms::QUEUE),
+        norm(pending as f64, norms::PENDING),
+        norm(robots.len() as f64, norms::CANDIDATE_ROWS as f64),
+        (ctx.current_time.as_seconds() / norms::PHASE_S).clamp(0.0, 1.0) as f32,
+        norm(station_queue as f64, norms::QUEUE),
+    ];
+
+    Some(CandidateFeatures {
+        robot_ids: robots.iter().map(|r| r.id).collect(),
+        robot_feats,
+        task_feats,
+    })
+}
+
+fn norm(v: f64, scale: f64) -> f32 {
+    (v / scale).clamp(0.0, 1.0) as f32
+}
+
+/// Allocate each task to the available robot the trained scorer rates highest
+/// (ties to the lowest robot id).
+#[derive(Clone)]
+pub struct LearnedAllocationPolicy {
+    scorer: CandidateScorer,
+}
+
+impl LearnedAllocationPolicy {
+    /// Wrap a scorer built for the default feature layout.
+    pub fn new(scorer: CandidateScorer) -> Result<Self, String> {
+        if scorer.robot_feats != LEARNED_ROBOT_FEATS || scorer.task_feats != LEARNED_TASK_FEATS {
+            return Err(format!(
+                "scorer expects {}+{} features, the default layout has {}+{}",
+                scorer.robot_feats, scorer.task_feats, LEARNED_ROBOT_FEATS, LEARNED_TASK_FEATS
+            ));
+        }
+        Ok(Self { scorer })
+    }
+
+    /// Load the scorer from a weights file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        Self::new(CandidateScorer::load(path)?)
+    }
+
+    pub fn scorer(&self) -> &CandidateScorer {
+        &self.scorer
+    }
+}
+
+impl TaskAllocationPolicy for LearnedAllocationPolicy {
+    fn allocate(&self, ctx: &PolicyContext, task_id: TaskId) -> Option<RobotId> {
+        let features = candidate_features(ctx, task_id)?;
+        let mut best: Option<(RobotId, f32)> = None;
+        for (id, robot) in features.robot_ids.iter().zip(&features.robot_feats) {
+            let score = self.scorer.score(robot, &features.task_feats);
+            if best.is_none_or(|(_, s)| score > s) {
+                best = Some((*id, score));
+            }
+        }
+        best.map(|(id, _)| id)
+    }
+
+    fn name(&self) -> &'static str {
+        "learned"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use waremax_core::{NodeId, OrderId, SimTime, StationId};
+    use waremax_entities::{BinLocation, Order, Station, Task};
+    use waremax_map::{Node, NodeType, WarehouseMap};
+    use waremax_storage::BinAddress;
+
+    /// One identity layer weighting only the distance feature by `w`.
+    fn distance_scorer(w: f32) -> CandidateScorer {
+        let mut weights = vec![0.0; LEARNED_ROBOT_FEATS + LEARNED_TASK_FEATS];
+        weights[0] = w;
+        CandidateScorer::new(
+            LEARNED_ROBOT_FEATS,
+            LEARNED_TASK_FEATS,
+            vec![DenseLayer {
+                inputs: weights.len(),
+                outputs: 1,
+                weights,
+                bias: vec![0.0],
+                activation: Activation::Identity,
+            }],
+        )
+        .unwrap()
+    }
+
+    fn line_map() -> WarehouseMap {
+        let mut map = WarehouseMap::new();
+        for i in 0..4 {
+            map.add_node(Node::new(
+                NodeId(i),
+                format!("n{i}"),
+                i as f64 * 10.0,
+                0.0,
+                NodeType::Aisle,
+            ));
+        }
+        map
+    }
+
+    #[test]
+    fn test_weights_round_trip() {
+        let scorer = distance_scorer(-1.0);
+        let parsed = CandidateScorer::from_bytes(&scorer.to_bytes()).unwrap();
+        assert_eq!(parsed.to_bytes(), scorer.to_bytes());
+
+        let bytes = scorer.to_bytes();
+        assert!(CandidateScorer::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(CandidateScorer::from_bytes(b"not weights").is_err());
+    }
+
+    #[test]
+    fn test_overflowing_layer_size_is_an_error() {
+        let mut bytes = MAGIC.to_vec();
+        for v in [FORMAT_VERSION, 8, 6, 1, u32::MAX, u32::MAX] {
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        bytes.push(0);
+        assert!(CandidateScorer::from_bytes(&bytes).is_err());
+
+        // A size that fits but runs past the end of the file
+        let mut bytes = MAGIC.to_vec();
+        for v in [FORMAT_VERSION, 8, 6, 1, 1 << 30, 4] {
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        bytes.push(0);
+        assert!(CandidateScorer::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_shape_validation() {
+        let layer = DenseLayer {
+            inputs: 3,
+            outputs: 1,
+            weights: vec![0.0; 3],
+            bias: vec![0.0],
+            activation: Activation::Tanh,
+        };
+        assert!(CandidateScorer::new(8, 6, vec![layer]).is_err());
+        assert!(CandidateScorer::new(8, 6, Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_learned_picks_highest_score() {
+        let map = line_map();
+        let mut robots = HashMap::new();
+        robots.insert(RobotId(0), Robot::new(RobotId(0), NodeId(0), 1.5, 25.0));
+        robots.insert(RobotId(1), Robot::new(RobotId(1), NodeId(2), 1.5, 25.0));
+        let mut tasks = HashMap::new();
+        tasks.insert(
+            TaskId(0),
+            Task::new_pick(
+                TaskId(0),
+                OrderId(0),
+                waremax_core::SkuId(0),
+                1,
+                BinLocation::new(BinAddress::new(waremax_core::RackId(0), 0, 0), NodeId(3)),
+                StationId(0),
+                SimTime::ZERO,
+            ),
+        );
+        let stations: HashMap<StationId, Station> = HashMap::new();
+        let orders: HashMap<OrderId, Order> = HashMap::new();
+        let ctx = PolicyContext {
+            current_time: SimTime::ZERO,
+            map: &map,
+            robots: &robots,
+            tasks: &tasks,
+            stations: &stations,
+            orders: &orders,
+            attribution: None,
+            traffic: None,
+        };
+
+        // Penalizing distance picks the nearer robot; rewarding it the farther.
+        let near = LearnedAllocationPolicy::new(distance_scorer(-1.0)).unwrap();
+        let far = LearnedAllocationPolicy::new(distance_scorer(1.0)).unwrap();
+        assert_eq!(near.allocate(&ctx, TaskId(0)), Some(RobotId(1)));
+        assert_eq!(far.allocate(&ctx, TaskId(0)), Some(RobotId(0)));
+        assert_eq!(near.name(), "learned");
+    }
+}
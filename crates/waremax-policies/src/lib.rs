@@ -2,11 +2,15 @@
 
 pub mod allocation;
 pub mod batching;
+pub mod bin_selection;
 pub mod deadlock;
 pub mod destination;
+pub mod idle;
+pub mod learned;
 pub mod priority;
 pub mod station;
 pub mod robot_state_machine;
+pub mod slotting;
 pub mod traffic;
 pub mod traffic_control;
 pub mod traits;
@@ -16,8 +20,13 @@ pub use traits::*;
 // Task allocation policies
 pub use allocation::{AuctionPolicy, WorkloadBalancedPolicy};
 pub use allocation::{LeastBusyPolicy, NearestRobotPolicy, RoundRobinPolicy}; // v1
+pub use learned::{candidate_features, CandidateFeatures, LearnedAllocationPolicy}; // v7
+pub use learned::{
+    Activation, CandidateScorer, DenseLayer, LEARNED_ROBOT_FEATS, LEARNED_TASK_FEATS,
+};
 
 // Station assignment policies
+pub use station::candidate_stations; // v7
 pub use station::{DueTimePriorityStationPolicy, FastestServicePolicy};
 pub use station::{LeastQueuePolicy, NearestStationPolicy}; // v1
 
@@ -32,6 +41,18 @@ pub use priority::{DueTimePolicy, FifoPolicy, StrictPriorityPolicy}; // v1
 // Destination policies (v1)
 pub use destination::{DestinationContext, DestinationPolicy, NearestEmptyBinPolicy};
 
+// Pickup bin selection policies (v7)
+pub use bin_selection::{BinSelectionContext, BinSelectionPolicy, PickupBinCandidate};
+pub use bin_selection::{FirstAvailableBinPolicy, LevelAwareBinPolicy, NearestBinPolicy};
+
+// Idle positioning policies (v7)
+pub use idle::{DemandWeightedPolicy, NearestStagingPolicy, StayPolicy};
+pub use idle::{IdlePositioningContext, IdlePositioningPolicy};
+
+// Re-slotting policies (v7)
+pub use slotting::{AbcSlottingPolicy, AffinitySlottingPolicy};
+pub use slotting::{SlotMove, SlottingContext, SlottingPolicy};
+
 // Traffic policies (v1)
 pub use traffic::{AdaptiveTrafficPolicy, RerouteOnWaitPolicy, WaitAtNodePolicy};
 pub use traffic::{TrafficAction, TrafficPolicy, TrafficPolicyContext};
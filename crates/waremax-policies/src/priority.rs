@@ -4,7 +4,8 @@ use crate::traits::{PolicyContext, PriorityPolicy};
 use waremax_core::{SimTime, TaskId};
 use waremax_entities::TaskType;
 
-/// Strict priority: pick > replen > putaway
+/// Strict priority: pick > replen > putaway > relocation
+#[derive(Clone)]
 pub struct StrictPriorityPolicy;
 
 impl StrictPriorityPolicy {
@@ -16,7 +17,8 @@ impl StrictPriorityPolicy {
         match task_type {
             TaskType::Pick => 0, // Highest
             TaskType::Replenishment => 1,
-            TaskType::Putaway => 2, // Lowest
+            TaskType::Putaway => 2,
+            TaskType::Relocation => 3, // Lowest (v7)
         }
     }
 }
@@ -43,6 +45,7 @@ impl PriorityPolicy for StrictPriorityPolicy {
 }
 
 /// First-in-first-out priority by task creation time
+#[derive(Clone)]
 pub struct FifoPolicy;
 
 impl FifoPolicy {
@@ -82,6 +85,7 @@ impl PriorityPolicy for FifoPolicy {
 }
 
 /// Prioritizes tasks from orders with nearest due time (earliest due first)
+#[derive(Clone)]
 pub struct DueTimePolicy;
 
 impl DueTimePolicy {
@@ -128,6 +132,7 @@ impl PriorityPolicy for DueTimePolicy {
 
 /// Weighted fair queuing across task types
 /// Provides balanced processing of pick, putaway, and replenishment tasks
+#[derive(Clone)]
 pub struct WeightedFairPolicy {
     /// Weight for pick tasks (lower = more frequent selection)
     pick_weight: u32,
@@ -151,6 +156,8 @@ impl WeightedFairPolicy {
             TaskType::Pick => self.pick_weight,
             TaskType::Putaway => self.putaway_weight,
             TaskType::Replenishment => self.replen_weight,
+            // v7: Re-slotting moves are storage work like putaway
+            TaskType::Relocation => self.putaway_weight,
         }
     }
 }
@@ -219,6 +226,7 @@ mod tests {
             stations,
             orders,
             attribution: None,
+            traffic: None,
         }
     }
 
@@ -133,7 +133,7 @@ impl StateMachine for RobotStateMachinePolicy {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use waremax_core::{ChargingStationId, MaintenanceStationId, NodeId, RobotId, StationId, TaskId};
+    use waremax_core::{ChargingStationId, MaintenanceStationId, RobotId, StationId, TaskId};
 
     #[test]
     fn test_idle_to_moving() {
@@ -0,0 +1,298 @@
+//! Re-slotting policies: which storage slots to swap so fast movers sit near stations
+//!
+//! Invoked periodically with SKU velocities observed during the run. A policy
+//! proposes swaps of bin contents; the simulation turns them into robot move
+//! tasks within a move budget.
+
+use dyn_clone::DynClone;
+use std::collections::{HashMap, HashSet};
+use waremax_core::{NodeId, RackId, SkuId};
+use waremax_map::WarehouseMap;
+use waremax_storage::{BinAddress, Inventory};
+
+/// A proposed swap of two bins' contents
+#[derive(Clone, Debug, PartialEq)]
+pub struct SlotMove {
+    /// SKU being moved closer to its target
+    pub sku_id: SkuId,
+    pub from: BinAddress,
+    pub to: BinAddress,
+}
+
+/// Context for re-slotting decisions
+pub struct SlottingContext<'a> {
+    pub map: &'a WarehouseMap,
+    pub inventory: &'a Inventory,
+    pub rack_access_nodes: &'a HashMap<RackId, NodeId>,
+    pub station_nodes: &'a [NodeId],
+    /// Picks per hour by SKU over the trailing window
+    pub velocity: &'a HashMap<SkuId, f64>,
+    /// Station node each SKU is most often delivered to
+    pub station_affinity: &'a HashMap<SkuId, NodeId>,
+    /// Bins with open tasks that must not move
+    pub locked_bins: &'a HashSet<BinAddress>,
+}
+
+impl SlottingContext<'_> {
+    /// Distance from a bin's access node to `target`, or to the nearest
+    /// station when no target is given
+    pub fn slot_distance(&self, addr: &BinAddress, target: Option<NodeId>) -> Option<f64> {
+        let access_node = *self.rack_access_nodes.get(&addr.rack_id)?;
+        match target {
+            Some(node) => Some(self.map.euclidean_distance(access_node, node)),
+            None => self
+                .station_nodes
+                .iter()
+                .map(|&s| self.map.euclidean_distance(access_node, s))
+                .min_by(|a, b| a.total_cmp(b)),
+        }
+    }
+
+    /// The `fraction` fastest-moving SKUs, fastest first (ties by SKU id)
+    pub fn fast_movers(&self, fraction: f64) -> Vec<SkuId> {
+        let mut skus: Vec<(SkuId, f64)> = self
+            .velocity
+            .iter()
+            .filter(|(_, &v)| v > 0.0)
+            .map(|(&sku, &v)| (sku, v))
+            .collect();
+        skus.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0 .0.cmp(&b.0 .0)));
+        let count = (skus.len() as f64 * fraction.clamp(0.0, 1.0)).ceil() as usize;
+        skus.into_iter().take(count).map(|(sku, _)| sku).collect()
+    }
+
+    /// Greedily swap each fast mover's bins into the closest slots to its
+    /// target that currently hold slower SKUs
+    fn plan_swaps(
+        &self,
+        fast_movers: &[SkuId],
+        target: impl Fn(SkuId) -> Option<NodeId>,
+        max_moves: usize,
+    ) -> Vec<SlotMove> {
+        let fast: HashSet<SkuId> = fast_movers.iter().copied().collect();
+        let mut used: HashSet<BinAddress> = HashSet::new();
+        let mut moves = Vec::new();
+
+        // Slots holding slower SKUs (or nothing) are swap partners
+        let mut partners: Vec<&BinAddress> = self
+            .inventory
+            .all_bins()
+            .iter()
+            .filter(|addr| !self.locked_bins.contains(*addr))
+            .filter(|addr| {
+                self.inventory
+                    .get_slot(addr)
+                    .is_none_or(|slot| !fast.contains(&slot.sku_id))
+            })
+            .collect();
+        partners.sort_by_key(|a| (a.rack_id.0, a.level, a.bin));
+
+        for &sku in fast_movers {
+            let goal = target(sku);
+            let mut bins: Vec<(BinAddress, f64)> = self
+                .inventory
+                .find_sku(sku)
+                .filter(|addr| !self.locked_bins.contains(*addr))
+                .filter_map(|addr| self.slot_distance(addr, goal).map(|d| (addr.clone(), d)))
+                .collect();
+            // Worst-placed bins first
+            bins.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+            for (from, from_distance) in bins {
+                if moves.len() >= max_moves {
+                    return moves;
+                }
+                let best = partners
+                    .iter()
+                    .filter(|addr| !used.contains(**addr))
+                    .filter_map(|addr| self.slot_distance(addr, goal).map(|d| (*addr, d)))
+                    .filter(|(_, d)| *d + 1e-6 < from_distance)
+                    .min_by(|a, b| a.1.total_cmp(&b.1).then(a.0.level.cmp(&b.0.level)));
+                if let Some((to, _)) = best {
+                    used.insert(to.clone());
+                    used.insert(from.clone());
+                    moves.push(SlotMove {
+                        sku_id: sku,
+                        from,
+                        to: to.clone(),
+                    });
+                }
+            }
+        }
+        moves
+    }
+}
+
+/// Policy proposing re-slotting moves
+pub trait SlottingPolicy: DynClone + Send + Sync {
+    /// Propose at most `max_moves` swaps
+    fn plan_moves(&self, ctx: &SlottingContext, max_moves: usize) -> Vec<SlotMove>;
+
+    /// Policy name for logging
+    fn name(&self) -> &'static str;
+}
+
+dyn_clone::clone_trait_object!(SlottingPolicy);
+
+/// ABC slotting: the fastest `a_fraction` of SKUs take the slots nearest
+/// to any station
+#[derive(Clone)]
+pub struct AbcSlottingPolicy {
+    a_fraction: f64,
+}
+
+impl AbcSlottingPolicy {
+    pub fn new(a_fraction: f64) -> Self {
+        Self { a_fraction }
+    }
+}
+
+impl Default for AbcSlottingPolicy {
+    fn default() -> Self {
+        Self::new(0.2)
+    }
+}
+
+impl SlottingPolicy for AbcSlottingPolicy {
+    fn plan_moves(&self, ctx: &SlottingContext, max_moves: usize) -> Vec<SlotMove> {
+        let fast = ctx.fast_movers(self.a_fraction);
+        ctx.plan_swaps(&fast, |_| None, max_moves)
+    }
+
+    fn name(&self) -> &'static str {
+        "abc"
+    }
+}
+
+/// Affinity slotting: fast movers cluster near the station that consumes
+/// them most, falling back to the nearest station
+#[derive(Clone)]
+pub struct AffinitySlottingPolicy {
+    a_fraction: f64,
+}
+
+impl AffinitySlottingPolicy {
+    pub fn new(a_fraction: f64) -> Self {
+        Self { a_fraction }
+    }
+}
+
+impl Default for AffinitySlottingPolicy {
+    fn default() -> Self {
+        Self::new(0.2)
+    }
+}
+
+impl SlottingPolicy for AffinitySlottingPolicy {
+    fn plan_moves(&self, ctx: &SlottingContext, max_moves: usize) -> Vec<SlotMove> {
+        let fast = ctx.fast_movers(self.a_fraction);
+        ctx.plan_swaps(
+            &fast,
+            |sku| ctx.station_affinity.get(&sku).copied(),
+            max_moves,
+        )
+    }
+
+    fn name(&self) -> &'static str {
+        "affinity"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use waremax_map::{Node, NodeType};
+
+    /// Stations at nodes 0 and 4; racks 1-3 at nodes 1-3, 10m apart
+    fn setup() -> (WarehouseMap, Inventory, HashMap<RackId, NodeId>) {
+        let mut map = WarehouseMap::new();
+        for i in 0..5u32 {
+            let node_type = if i == 0 || i == 4 {
+                NodeType::StationPick
+            } else {
+                NodeType::Rack
+            };
+            map.add_node(Node::new(
+                NodeId(i),
+                format!("N{}", i),
+                i as f64 * 10.0,
+                0.0,
+                node_type,
+            ));
+        }
+        let mut inventory = Inventory::new();
+        let mut racks = HashMap::new();
+        for r in 1..=3u32 {
+            racks.insert(RackId(r), NodeId(r));
+            inventory.add_placement(BinAddress::new(RackId(r), 0, 0), SkuId(r), 10);
+        }
+        (map, inventory, racks)
+    }
+
+    #[test]
+    fn test_abc_moves_fast_mover_toward_station() {
+        let (map, inventory, racks) = setup();
+        let stations = [NodeId(0)];
+        // SKU 3 sits farthest from the station but moves fastest
+        let velocity = HashMap::from([(SkuId(1), 1.0), (SkuId(2), 2.0), (SkuId(3), 30.0)]);
+        let affinity = HashMap::new();
+        let locked = HashSet::new();
+        let ctx = SlottingContext {
+            map: &map,
+            inventory: &inventory,
+            rack_access_nodes: &racks,
+            station_nodes: &stations,
+            velocity: &velocity,
+            station_affinity: &affinity,
+            locked_bins: &locked,
+        };
+
+        let moves = AbcSlottingPolicy::new(0.3).plan_moves(&ctx, 5);
+        assert_eq!(
+            moves,
+            vec![SlotMove {
+                sku_id: SkuId(3),
+                from: BinAddress::new(RackId(3), 0, 0),
+                to: BinAddress::new(RackId(1), 0, 0),
+            }]
+        );
+
+        // No budget, no moves
+        assert!(AbcSlottingPolicy::new(0.3).plan_moves(&ctx, 0).is_empty());
+    }
+
+    #[test]
+    fn test_affinity_clusters_near_consuming_station() {
+        let (map, inventory, racks) = setup();
+        let stations = [NodeId(0), NodeId(4)];
+        // SKU 1 is next to station 0 but is delivered to station 4
+        let velocity = HashMap::from([(SkuId(1), 30.0), (SkuId(2), 1.0), (SkuId(3), 1.0)]);
+        let affinity = HashMap::from([(SkuId(1), NodeId(4))]);
+        let unlocked = HashSet::new();
+        let ctx = SlottingContext {
+            map: &map,
+            inventory: &inventory,
+            rack_access_nodes: &racks,
+            station_nodes: &stations,
+            velocity: &velocity,
+            station_affinity: &affinity,
+            locked_bins: &unlocked,
+        };
+
+        // ABC considers SKU 1 well placed already
+        assert!(AbcSlottingPolicy::new(0.3).plan_moves(&ctx, 5).is_empty());
+
+        let moves = AffinitySlottingPolicy::new(0.3).plan_moves(&ctx, 5);
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].to, BinAddress::new(RackId(3), 0, 0));
+
+        // Locked bins are left alone
+        let locked = HashSet::from([BinAddress::new(RackId(3), 0, 0)]);
+        let ctx = SlottingContext {
+            locked_bins: &locked,
+            ..ctx
+        };
+        let moves = AffinitySlottingPolicy::new(0.3).plan_moves(&ctx, 5);
+        assert_eq!(moves[0].to, BinAddress::new(RackId(2), 0, 0));
+    }
+}
@@ -2,9 +2,31 @@
 
 use crate::traits::{PolicyContext, StationAssignmentPolicy};
 use waremax_core::StationId;
-use waremax_entities::{StationType, Task};
+use waremax_entities::{Station, StationType, Task};
+
+/// v7: Stations of the given type that can accept a task, skipping closed ones.
+/// When every matching station is closed, those only waiting for operators
+/// (not switched off) remain candidates so work queues up for the next shift
+/// instead of being dropped.
+pub fn candidate_stations<'a>(
+    ctx: &PolicyContext<'a>,
+    station_type: &StationType,
+) -> Vec<&'a Station> {
+    let accepting: Vec<&Station> = ctx
+        .stations
+        .values()
+        .filter(|s| &s.station_type == station_type)
+        .filter(|s| s.can_accept())
+        .collect();
+    if accepting.iter().any(|s| s.is_open()) {
+        accepting.into_iter().filter(|s| s.is_open()).collect()
+    } else {
+        accepting.into_iter().filter(|s| s.open).collect()
+    }
+}
 
 /// Assign tasks to the station with the least queue
+#[derive(Clone)]
 pub struct LeastQueuePolicy {
     station_type: StationType,
 }
@@ -27,10 +49,8 @@ impl Default for LeastQueuePolicy {
 
 impl StationAssignmentPolicy for LeastQueuePolicy {
     fn assign(&self, ctx: &PolicyContext, _task: &Task) -> Option<StationId> {
-        ctx.stations
-            .values()
-            .filter(|s| s.station_type == self.station_type)
-            .filter(|s| s.can_accept())
+        candidate_stations(ctx, &self.station_type)
+            .into_iter()
             // Tie-break by station id: `ctx.stations` is a HashMap with
             // randomized iteration order, so without a stable secondary key the
             // chosen station (and thus routing/timing) would vary per run.
@@ -44,6 +64,7 @@ impl StationAssignmentPolicy for LeastQueuePolicy {
 }
 
 /// Assign tasks to the nearest station of matching type
+#[derive(Clone)]
 pub struct NearestStationPolicy {
     station_type: StationType,
 }
@@ -68,10 +89,8 @@ impl StationAssignmentPolicy for NearestStationPolicy {
     fn assign(&self, ctx: &PolicyContext, task: &Task) -> Option<StationId> {
         let task_node = task.source.access_node;
 
-        ctx.stations
-            .values()
-            .filter(|s| s.station_type == self.station_type)
-            .filter(|s| s.can_accept())
+        candidate_stations(ctx, &self.station_type)
+            .into_iter()
             .min_by(|a, b| {
                 let dist_a = ctx.map.euclidean_distance(task_node, a.node);
                 let dist_b = ctx.map.euclidean_distance(task_node, b.node);
@@ -92,6 +111,7 @@ impl StationAssignmentPolicy for NearestStationPolicy {
 
 /// Assign to station with fastest expected service completion
 /// Considers both queue wait time and travel time
+#[derive(Clone)]
 pub struct FastestServicePolicy {
     station_type: StationType,
     /// Average service time per item (seconds) for estimation
@@ -121,10 +141,8 @@ impl StationAssignmentPolicy for FastestServicePolicy {
     fn assign(&self, ctx: &PolicyContext, task: &Task) -> Option<StationId> {
         let task_node = task.source.access_node;
 
-        ctx.stations
-            .values()
-            .filter(|s| s.station_type == self.station_type)
-            .filter(|s| s.can_accept())
+        candidate_stations(ctx, &self.station_type)
+            .into_iter()
             .min_by(|a, b| {
                 // Estimate total time: travel + queue wait + service
                 let travel_a = ctx.map.euclidean_distance(task_node, a.node) / 1.5; // Assume 1.5 m/s
@@ -152,6 +170,7 @@ impl StationAssignmentPolicy for FastestServicePolicy {
 
 /// Assign urgent orders to less-congested stations
 /// Non-urgent orders go to nearest station
+#[derive(Clone)]
 pub struct DueTimePriorityStationPolicy {
     station_type: StationType,
     /// Urgency threshold in minutes (order is urgent if due_time - current_time < threshold)
@@ -197,12 +216,7 @@ impl StationAssignmentPolicy for DueTimePriorityStationPolicy {
             false
         };
 
-        let matching_stations: Vec<_> = ctx
-            .stations
-            .values()
-            .filter(|s| s.station_type == self.station_type)
-            .filter(|s| s.can_accept())
-            .collect();
+        let matching_stations = candidate_stations(ctx, &self.station_type);
 
         if matching_stations.is_empty() {
             return None;
@@ -240,7 +254,9 @@ mod tests {
     use super::*;
     use std::collections::HashMap;
     use waremax_core::{NodeId, OrderId, RobotId, SimTime, TaskId};
-    use waremax_entities::{BinLocation, Order, Robot, ServiceTimeModel, Station, Task};
+    use waremax_entities::{
+        BinLocation, Order, Robot, ServiceTimeModel, StaffingSchedule, Station, Task,
+    };
     use waremax_map::{Node, NodeType, WarehouseMap};
     use waremax_storage::BinAddress;
 
@@ -259,6 +275,7 @@ mod tests {
             stations,
             orders,
             attribution: None,
+            traffic: None,
         }
     }
 
@@ -369,6 +386,45 @@ mod tests {
         assert_eq!(policy.assign(&ctx, &task), Some(StationId(1)));
     }
 
+    #[test]
+    fn test_closed_stations_are_skipped() {
+        let map = make_map_with_nodes();
+        let robots = HashMap::new();
+        let orders = HashMap::new();
+
+        let mut stations = HashMap::new();
+        stations.insert(StationId(0), make_station(0, 1, 0)); // At x=10
+        stations.insert(StationId(1), make_station(1, 2, 0)); // At x=5, closed
+        stations.insert(StationId(2), make_station(2, 3, 0)); // At x=20
+        stations.get_mut(&StationId(1)).unwrap().open = false;
+
+        let task = make_task(0, 0);
+        let mut tasks = HashMap::new();
+        tasks.insert(TaskId(0), task.clone());
+
+        let ctx = test_context(&map, &robots, &tasks, &stations, &orders);
+        assert_eq!(
+            NearestStationPolicy::default().assign(&ctx, &task),
+            Some(StationId(0))
+        );
+        assert_eq!(
+            LeastQueuePolicy::default().assign(&ctx, &task),
+            Some(StationId(0))
+        );
+
+        // Unstaffed stations still take work to queue for the next shift,
+        // but switched-off stations never do
+        for station in stations.values_mut() {
+            station.staffing = Some(StaffingSchedule::default());
+            station.staffed_operators = 0;
+        }
+        let ctx = test_context(&map, &robots, &tasks, &stations, &orders);
+        assert_eq!(
+            NearestStationPolicy::default().assign(&ctx, &task),
+            Some(StationId(0))
+        );
+    }
+
     #[test]
     fn test_policy_names() {
         assert_eq!(LeastQueuePolicy::default().name(), "least_queue");
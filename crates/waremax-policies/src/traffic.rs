@@ -1,5 +1,6 @@
 //! Traffic management policies for handling congestion
 
+use dyn_clone::DynClone;
 use waremax_core::{EdgeId, NodeId, RobotId, SimTime};
 use waremax_map::{TrafficManager, WarehouseMap};
 
@@ -27,7 +28,7 @@ pub enum TrafficAction {
 }
 
 /// Policy for handling traffic congestion
-pub trait TrafficPolicy: Send + Sync {
+pub trait TrafficPolicy: DynClone + Send + Sync {
     /// Decide action when robot cannot enter an edge
     fn on_blocked(&self, ctx: &TrafficPolicyContext) -> TrafficAction;
 
@@ -35,8 +36,11 @@ pub trait TrafficPolicy: Send + Sync {
     fn name(&self) -> &'static str;
 }
 
+dyn_clone::clone_trait_object!(TrafficPolicy);
+
 /// Simple wait-at-node policy (v0 behavior)
 /// Robot waits until the edge becomes available
+#[derive(Clone)]
 pub struct WaitAtNodePolicy;
 
 impl WaitAtNodePolicy {
@@ -63,6 +67,7 @@ impl TrafficPolicy for WaitAtNodePolicy {
 
 /// v1: Reroute-on-wait policy
 /// Attempts to find an alternate route after waiting for a threshold duration
+#[derive(Clone)]
 pub struct RerouteOnWaitPolicy {
     /// Wait threshold before attempting reroute (seconds)
     wait_threshold_s: f64,
@@ -106,6 +111,7 @@ impl TrafficPolicy for RerouteOnWaitPolicy {
 
 /// v1: Adaptive traffic policy
 /// Combines waiting with intelligent rerouting based on congestion levels
+#[derive(Clone)]
 pub struct AdaptiveTrafficPolicy {
     /// Base wait threshold (seconds)
     base_wait_s: f64,
@@ -4,6 +4,7 @@
 //! handle lifecycle callbacks (enter, leave, position update).
 //! They are orthogonal to the congestion policies in `traffic.rs`.
 
+use dyn_clone::DynClone;
 use waremax_core::{EdgeId, NodeId, RobotId, SimTime};
 use waremax_map::TrafficManager;
 
@@ -11,7 +12,7 @@ use waremax_map::TrafficManager;
 ///
 /// Implementations can range from simple capacity checks to continuous
 /// sub-edge position tracking with collision avoidance.
-pub trait EdgeTrafficPolicy: Send + Sync {
+pub trait EdgeTrafficPolicy: DynClone + Send + Sync {
     /// Human-readable policy name.
     fn name(&self) -> &str;
 
@@ -90,10 +91,13 @@ pub trait EdgeTrafficPolicy: Send + Sync {
     }
 }
 
+dyn_clone::clone_trait_object!(EdgeTrafficPolicy);
+
 /// Capacity-based coarse policy that matches the original v0 behavior.
 ///
 /// Robots occupy entire edges/nodes. No direction locking, no continuous
 /// position tracking. Multiple robots may share an edge up to capacity.
+#[derive(Clone)]
 pub struct CoarseTrafficPolicy;
 
 impl CoarseTrafficPolicy {
@@ -119,9 +123,10 @@ impl EdgeTrafficPolicy for CoarseTrafficPolicy {
         edge: EdgeId,
         robot: RobotId,
         _from: NodeId,
-        _to: NodeId,
+        to: NodeId,
     ) -> bool {
-        traffic.can_enter_edge(edge, robot)
+        // v7: Robots yield to humans
+        !traffic.blocked_by_humans(edge, to) && traffic.can_enter_edge(edge, robot)
     }
 
     fn can_enter_node(
@@ -178,6 +183,7 @@ impl EdgeTrafficPolicy for CoarseTrafficPolicy {
 /// Robots track progress along edges (0.0 to 1.0). An edge may only
 /// be traversed in one direction at a time. Robots must maintain a
 /// minimum following distance from the robot ahead of them.
+#[derive(Clone)]
 pub struct ContinuousTrafficPolicy {
     /// Minimum distance (meters) between robots on the same edge.
     pub safety_distance_m: f64,
@@ -217,6 +223,11 @@ impl EdgeTrafficPolicy for ContinuousTrafficPolicy {
         from: NodeId,
         to: NodeId,
     ) -> bool {
+        // 0. v7: Robots yield to humans
+        if traffic.blocked_by_humans(edge, to) {
+            return false;
+        }
+
         // 1. Capacity check
         let capacity = traffic.edge_capacity(edge);
         if traffic.edge_occupancy_count(edge) >= capacity as usize {
@@ -236,10 +247,8 @@ impl EdgeTrafficPolicy for ContinuousTrafficPolicy {
             if edge_length > 0.0 {
                 let min_gap = self.safety_distance_m / edge_length;
                 for state in states {
-                    if state.from == from && state.to == to {
-                        if state.progress < min_gap {
-                            return false; // Too close to robot ahead
-                        }
+                    if state.from == from && state.to == to && state.progress < min_gap {
+                        return false; // Too close to robot ahead
                     }
                 }
             }
@@ -378,4 +387,29 @@ mod tests {
         traffic.update_continuous_progress(EdgeId(1), RobotId(1), 0.1);
         assert!(!policy.can_enter_edge(&traffic, EdgeId(1), RobotId(2), NodeId(0), NodeId(1)));
     }
+
+    #[test]
+    fn test_policies_yield_to_humans() {
+        use std::collections::{HashMap, HashSet};
+
+        let mut traffic = TrafficManager::new(2, 2);
+        let coarse = CoarseTrafficPolicy::new();
+        let continuous = ContinuousTrafficPolicy::default_config();
+
+        // Human standing at node 1
+        traffic.set_human_restrictions(HashSet::from([NodeId(1)]), HashMap::new(), HashSet::new());
+        assert!(!coarse.can_enter_edge(&traffic, EdgeId(1), RobotId(1), NodeId(0), NodeId(1)));
+        assert!(!continuous.can_enter_edge(&traffic, EdgeId(1), RobotId(1), NodeId(0), NodeId(1)));
+        assert!(coarse.can_enter_edge(&traffic, EdgeId(2), RobotId(1), NodeId(0), NodeId(2)));
+
+        // Human walking along edge 2
+        traffic.set_human_restrictions(HashSet::new(), HashMap::new(), HashSet::from([EdgeId(2)]));
+        assert!(!continuous.can_enter_edge(&traffic, EdgeId(2), RobotId(1), NodeId(0), NodeId(2)));
+
+        // Reserved zone
+        traffic.set_human_restrictions(HashSet::new(), HashMap::new(), HashSet::new());
+        traffic.set_human_reserved_nodes(HashSet::from([NodeId(2)]));
+        assert!(!coarse.can_enter_edge(&traffic, EdgeId(2), RobotId(1), NodeId(0), NodeId(2)));
+        assert!(coarse.can_enter_edge(&traffic, EdgeId(1), RobotId(1), NodeId(0), NodeId(1)));
+    }
 }
@@ -1,10 +1,14 @@
 //! Policy trait definitions
+//!
+//! v7: Every policy trait requires `Clone` (through `DynClone`), so a `World`
+//! holding boxed policies can be cloned along with their state.
 
+use dyn_clone::DynClone;
 use std::collections::HashMap;
 use waremax_analysis::AttributionCollector;
 use waremax_core::{RobotId, SimTime, StationId, TaskId};
 use waremax_entities::{Order, Robot, Station, Task};
-use waremax_map::WarehouseMap;
+use waremax_map::{TrafficManager, WarehouseMap};
 
 /// Context provided to policies for decision-making
 pub struct PolicyContext<'a> {
@@ -18,10 +22,14 @@ pub struct PolicyContext<'a> {
     /// enabled (used by the RL attribution-shaped reward). Heuristic policies
     /// ignore this field.
     pub attribution: Option<&'a AttributionCollector>,
+    /// v7: Node/edge occupancy, present when the caller has a live traffic
+    /// manager (used by RL graph observations). Heuristic policies ignore
+    /// this field.
+    pub traffic: Option<&'a TrafficManager>,
 }
 
 /// Task allocation policy: which robot should handle a task
-pub trait TaskAllocationPolicy: Send + Sync {
+pub trait TaskAllocationPolicy: DynClone + Send + Sync {
     /// Select a robot for the given task
     fn allocate(&self, ctx: &PolicyContext, task_id: TaskId) -> Option<RobotId>;
 
@@ -29,8 +37,10 @@ pub trait TaskAllocationPolicy: Send + Sync {
     fn name(&self) -> &'static str;
 }
 
+dyn_clone::clone_trait_object!(TaskAllocationPolicy);
+
 /// Station assignment policy: which station should receive a task
-pub trait StationAssignmentPolicy: Send + Sync {
+pub trait StationAssignmentPolicy: DynClone + Send + Sync {
     /// Select a station for the given task
     fn assign(&self, ctx: &PolicyContext, task: &Task) -> Option<StationId>;
 
@@ -38,8 +48,10 @@ pub trait StationAssignmentPolicy: Send + Sync {
     fn name(&self) -> &'static str;
 }
 
+dyn_clone::clone_trait_object!(StationAssignmentPolicy);
+
 /// Batching policy: how to group tasks
-pub trait BatchingPolicy: Send + Sync {
+pub trait BatchingPolicy: DynClone + Send + Sync {
     /// Given pending tasks, return groups to batch together
     fn batch(&self, ctx: &PolicyContext, pending_tasks: &[TaskId]) -> Vec<Vec<TaskId>>;
 
@@ -47,11 +59,15 @@ pub trait BatchingPolicy: Send + Sync {
     fn name(&self) -> &'static str;
 }
 
+dyn_clone::clone_trait_object!(BatchingPolicy);
+
 /// Priority arbitration policy: ordering of task types
-pub trait PriorityPolicy: Send + Sync {
+pub trait PriorityPolicy: DynClone + Send + Sync {
     /// Sort tasks by priority (highest priority first)
     fn prioritize(&self, ctx: &PolicyContext, tasks: &mut [TaskId]);
 
     /// Policy name for logging
     fn name(&self) -> &'static str;
 }
+
+dyn_clone::clone_trait_object!(PriorityPolicy);
@@ -0,0 +1,348 @@
+//! Offline RL datasets: transitions recorded straight from an [`RlEnv`].
+//!
+//! A dataset file is a small header followed by fixed-width little-endian
+//! records, one per transition, so it can be memory-mapped or loaded with a
+//! single structured `numpy.fromfile` (see `python/waremax_dataset.py`).
+//!
+//! Header (24 bytes): magic `WMXDS\0\0\0`, then `u32` format version, `u32`
+//! rows `R`, `u32` robot features `F`, `u32` task features `T`.
+//!
+//! Record: `obs`, `i32` action (`-1` for none), `f32` reward, `u8` done,
+//! `next_obs`, then info as `f64` sim time, `f64` lateness delta, `i32`
+//! completed delta, `i32` late delta, `u32` pending, `u8` errored. Each
+//! observation is `u8` kind, `R * F` `f32` robot features, `R` `u8` mask and
+//! `T` `f32` task features. Only the allocation block is stored, so recording
+//! needs `ControlMode::Allocation`.
+//!
+//! [`record_episode`] drives an env with any agent closure. Sending
+//! [`ActionMsg::Expert`] from an env built with `RlEnv::with_expert` makes the
+//! heuristic the demonstrator and logs the row it chose.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::env::{ControlMode, RlEnv};
+use crate::obs_spec::ObservationSpec;
+use crate::observation::{DecisionKind, Observation};
+use crate::protocol::{ActionMsg, StepInfo};
+
+const MAGIC: &[u8; 8] = b"WMXDS\0\0\0";
+const FORMAT_VERSION: u32 = 1;
+
+/// Shape of every observation in a dataset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DatasetHeader {
+    pub rows: u32,
+    pub robot_feats: u32,
+    pub task_feats: u32,
+}
+
+impl DatasetHeader {
+    pub fn for_spec(spec: &ObservationSpec) -> Self {
+        Self {
+            rows: spec.max_candidates as u32,
+            robot_feats: spec.robot_feats() as u32,
+            task_feats: spec.task_feats() as u32,
+        }
+    }
+
+    fn obs_bytes(&self) -> usize {
+        let (r, f, t) = (
+            self.rows as usize,
+            self.robot_feats as usize,
+            self.task_feats as usize,
+        );
+        1 + r * f * 4 + r + t * 4
+    }
+
+    /// Size of one record in bytes.
+    pub fn record_bytes(&self) -> usize {
+        2 * self.obs_bytes() + 4 + 4 + 1 + 8 + 8 + 4 + 4 + 4 + 1
+    }
+}
+
+/// One recorded step. Observations read back from a file carry only the
+/// stored fields (kind, robot features, mask, task features).
+#[derive(Debug, Clone)]
+pub struct Transition {
+    pub obs: Observation,
+    /// Row acted on, or `None` for a no-op.
+    pub action: Option<usize>,
+    pub reward: f32,
+    pub next_obs: Observation,
+    pub done: bool,
+    pub info: StepInfo,
+}
+
+/// Writes transitions to a dataset file.
+pub struct DatasetWriter<W: Write> {
+    out: W,
+    header: DatasetHeader,
+    written: usize,
+}
+
+impl DatasetWriter<BufWriter<File>> {
+    /// Create (or truncate) the file at `path`.
+    pub fn create(path: impl AsRef<Path>, header: DatasetHeader) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?), header)
+    }
+}
+
+impl<W: Write> DatasetWriter<W> {
+    /// Write the header to `out`.
+    pub fn new(mut out: W, header: DatasetHeader) -> io::Result<Self> {
+        out.write_all(MAGIC)?;
+        for v in [
+            FORMAT_VERSION,
+            header.rows,
+            header.robot_feats,
+            header.task_feats,
+        ] {
+            out.write_all(&v.to_le_bytes())?;
+        }
+        Ok(Self {
+            out,
+            header,
+            written: 0,
+        })
+    }
+
+    pub fn header(&self) -> DatasetHeader {
+        self.header
+    }
+
+    /// Transitions written so far.
+    pub fn len(&self) -> usize {
+        self.written
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.written == 0
+    }
+
+    /// Append one transition. Fails if an observation's shape differs from
+    /// the header.
+    pub fn write(&mut self, t: &Transition) -> io::Result<()> {
+        let mut buf = Vec::with_capacity(self.header.record_bytes());
+        self.put_obs(&mut buf, &t.obs)?;
+        let action = t.action.map_or(-1, |a| a as i32);
+        buf.extend_from_slice(&action.to_le_bytes());
+        buf.extend_from_slice(&t.reward.to_le_bytes());
+        buf.push(t.done as u8);
+        self.put_obs(&mut buf, &t.next_obs)?;
+        buf.extend_from_slice(&t.info.sim_time_s.to_le_bytes());
+        buf.extend_from_slice(&t.info.lateness_delta_s.to_le_bytes());
+        buf.extend_from_slice(&(t.info.completed_delta as i32).to_le_bytes());
+        buf.extend_from_slice(&(t.info.late_delta as i32).to_le_bytes());
+        buf.extend_from_slice(&(t.info.pending as u32).to_le_bytes());
+        buf.push(t.info.errored as u8);
+        self.out.write_all(&buf)?;
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Flush and return the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    fn put_obs(&self, buf: &mut Vec<u8>, obs: &Observation) -> io::Result<()> {
+        let h = &self.header;
+        if obs.action_mask.len() != h.rows as usize
+            || obs.robot_feats.len() != (h.rows * h.robot_feats) as usize
+            || obs.task_feats.len() != h.task_feats as usize
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "observation shape does not match the dataset header",
+            ));
+        }
+        buf.push(match obs.kind {
+            DecisionKind::Allocation => 0,
+            DecisionKind::StationAssignment => 1,
+        });
+        for f in &obs.robot_feats {
+            buf.extend_from_slice(&f.to_le_bytes());
+        }
+        buf.extend(obs.action_mask.iter().map(|&m| m as u8));
+        for f in &obs.task_feats {
+            buf.extend_from_slice(&f.to_le_bytes());
+        }
+        Ok(())
+    }
+}
+
+/// Read a whole dataset file.
+pub fn read_dataset(path: impl AsRef<Path>) -> io::Result<(DatasetHeader, Vec<Transition>)> {
+    let mut input = BufReader::new(File::open(path)?);
+    let mut magic = [0u8; 8];
+    input.read_exact(&mut magic)?;
+    let mut fields = [0u32; 4];
+    for v in &mut fields {
+        let mut b = [0u8; 4];
+        input.read_exact(&mut b)?;
+        *v = u32::from_le_bytes(b);
+    }
+    if &magic != MAGIC || fields[0] != FORMAT_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not a waremax dataset (or an unsupported version)",
+        ));
+    }
+    let header = DatasetHeader {
+        rows: fields[1],
+        robot_feats: fields[2],
+        task_feats: fields[3],
+    };
+
+    let mut data = Vec::new();
+    input.read_to_end(&mut data)?;
+    let size = header.record_bytes();
+    if data.len() % size != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "truncated dataset record",
+        ));
+    }
+    let transitions = data
+        .chunks_exact(size)
+        .map(|record| {
+            let mut r = Cursor {
+                buf: record,
+                pos: 0,
+            };
+            let obs = r.obs(&header);
+            let action = r.i32();
+            let reward = r.f32();
+            let done = r.u8() != 0;
+            let next_obs = r.obs(&header);
+            let info = StepInfo {
+                sim_time_s: r.f64(),
+                lateness_delta_s: r.f64(),
+                completed_delta: r.i32() as i64,
+                late_delta: r.i32() as i64,
+                pending: r.u32() as usize,
+                errored: r.u8() != 0,
+                final_metrics: None,
+                reward_terms: Vec::new(),
+            };
+            Transition {
+                obs,
+                action: usize::try_from(action).ok(),
+                reward,
+                next_obs,
+                done,
+                info,
+            }
+        })
+        .collect();
+    Ok((header, transitions))
+}
+
+/// Little-endian reader over one record.
+struct Cursor<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl Cursor<'_> {
+    fn take<const N: usize>(&mut self) -> [u8; N] {
+        let b = self.buf[self.pos..self.pos + N].try_into().unwrap();
+        self.pos += N;
+        b
+    }
+
+    fn u8(&mut self) -> u8 {
+        self.take::<1>()[0]
+    }
+
+    fn u32(&mut self) -> u32 {
+        u32::from_le_bytes(self.take())
+    }
+
+    fn i32(&mut self) -> i32 {
+        i32::from_le_bytes(self.take())
+    }
+
+    fn f32(&mut self) -> f32 {
+        f32::from_le_bytes(self.take())
+    }
+
+    fn f64(&mut self) -> f64 {
+        f64::from_le_bytes(self.take())
+    }
+
+    fn obs(&mut self, h: &DatasetHeader) -> Observation {
+        let kind = match self.u8() {
+            1 => DecisionKind::StationAssignment,
+            _ => DecisionKind::Allocation,
+        };
+        let robot_feats = (0..h.rows * h.robot_feats).map(|_| self.f32()).collect();
+        let action_mask = (0..h.rows).map(|_| self.u8() != 0).collect();
+        let task_feats = (0..h.task_feats).map(|_| self.f32()).collect();
+        Observation {
+            kind,
+            robot_feats,
+            action_mask,
+            task_feats,
+            ..Observation::zeros()
+        }
+    }
+}
+
+/// Totals of one recorded episode.
+#[derive(Debug, Clone, Default)]
+pub struct RecordSummary {
+    pub transitions: usize,
+    pub total_reward: f64,
+}
+
+/// Run one episode of `env` from `seed`, acting with `agent`, and append every
+/// transition to `writer`.
+///
+/// The logged action is the row chosen: the index of `Choose`, the expert's
+/// row for `Expert`, and none for a no-op.
+pub fn record_episode<W: Write>(
+    env: &mut RlEnv,
+    seed: u64,
+    writer: &mut DatasetWriter<W>,
+    mut agent: impl FnMut(&Observation) -> ActionMsg,
+) -> io::Result<RecordSummary> {
+    if env.control() != ControlMode::Allocation {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "datasets record allocation control only",
+        ));
+    }
+    let mut summary = RecordSummary::default();
+    let mut obs = env.reset(seed);
+    if env.is_done() {
+        return Ok(summary);
+    }
+    loop {
+        let action = agent(&obs);
+        let logged = match action {
+            ActionMsg::Choose(i) => Some(i),
+            ActionMsg::Expert => obs.expert_action,
+            ActionMsg::NoOp | ActionMsg::Abort => None,
+        };
+        let res = env.step(action);
+        summary.transitions += 1;
+        summary.total_reward += res.reward as f64;
+        let transition = Transition {
+            obs,
+            action: logged,
+            reward: res.reward,
+            next_obs: res.obs,
+            done: res.done,
+            info: res.info,
+        };
+        writer.write(&transition)?;
+        if transition.done {
+            return Ok(summary);
+        }
+        obs = transition.next_obs;
+    }
+}
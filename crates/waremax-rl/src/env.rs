@@ -6,6 +6,22 @@
 //! A strict ping-pong over two bounded(1) channels means exactly one side runs
 //! at a time, so the run is deterministic given the seed and the action sequence
 //! — there is no concurrent access to simulation state.
+//!
+//! [`ControlMode`] selects which decision points are handed to the agent: task
+//! allocation, station assignment, or both interleaved on the same channel
+//! (tell them apart with `Observation::kind`).
+//!
+//! Mid-episode state can be captured with [`RlEnv::clone_state`] and
+//! re-entered with [`RlEnv::restore`] or [`RlEnv::fork`], for tree search and
+//! counterfactual rollouts. A decision point lies inside an event handler, so
+//! the worker checkpoints the whole runner (world, kernel, RNG streams,
+//! policies and metrics) at event boundaries, every
+//! [`RlEnv::with_checkpoint_interval`] decisions. A snapshot is the latest
+//! checkpoint plus the actions taken since; restoring resumes a copy of the
+//! checkpoint on a fresh worker and replays just those actions.
+//!
+//! With a [`ScenarioDistribution`] each reset draws a new scenario from the
+//! seed (and the curriculum progress) instead of reusing one fixed scenario.
 
 use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::sync::{Arc, Mutex};
@@ -17,10 +33,14 @@ use waremax_metrics::SimulationReport;
 use waremax_sim::SimulationRunner;
 use waremax_testing::runner::build_world_from_config;
 
+use waremax_policies::{LeastQueuePolicy, NearestRobotPolicy};
+
+use crate::obs_spec::ObservationSpec;
 use crate::observation::Observation;
-use crate::policy::RlPolicy;
+use crate::policy::{DecisionChannel, RlPolicy, RlStationPolicy};
 use crate::protocol::{ActionMsg, FinalMetrics, Message, StepInfo};
-use crate::reward::{self, RewardConfig, RewardSnapshot};
+use crate::randomization::ScenarioDistribution;
+use crate::reward::{self, RewardConfig, RewardMode, RewardSnapshot};
 
 /// Result of a single `step`.
 #[derive(Debug, Clone)]
@@ -31,6 +51,135 @@ pub struct StepResult {
     pub info: StepInfo,
 }
 
+/// A decision point of an episode, captured by [`RlEnv::clone_state`].
+///
+/// The decision point itself sits halfway through an event handler, where
+/// the simulation can't be copied. A snapshot therefore holds the env's latest
+/// checkpoint, a copy of the simulation taken between two events, plus the
+/// actions sent since. Restoring resumes the copy and replays those actions,
+/// fewer than the checkpoint interval plus one event's worth. Snapshots are
+/// cheap to clone and share their checkpoint.
+#[derive(Clone)]
+pub struct EnvSnapshot {
+    /// Seed the episode was reset with.
+    pub seed: u64,
+    /// Curriculum progress the episode's scenario was drawn at (only matters
+    /// with a scenario distribution).
+    pub progress: f64,
+    checkpoint: Arc<Checkpoint>,
+    actions: Vec<ActionMsg>,
+}
+
+impl EnvSnapshot {
+    /// Actions sent since the checkpoint, which a restore replays.
+    pub fn actions(&self) -> &[ActionMsg] {
+        &self.actions
+    }
+
+    /// Simulated time of the checkpoint, in seconds.
+    pub fn checkpoint_time_s(&self) -> f64 {
+        self.checkpoint.runner.now().as_seconds()
+    }
+}
+
+impl PartialEq for EnvSnapshot {
+    /// Same episode, same checkpoint and same actions since.
+    fn eq(&self, other: &Self) -> bool {
+        self.seed == other.seed
+            && self.progress == other.progress
+            && Arc::ptr_eq(&self.checkpoint, &other.checkpoint)
+            && self.actions == other.actions
+    }
+}
+
+impl std::fmt::Debug for EnvSnapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EnvSnapshot")
+            .field("seed", &self.seed)
+            .field("progress", &self.progress)
+            .field("checkpoint_time_s", &self.checkpoint_time_s())
+            .field("actions", &self.actions)
+            .finish()
+    }
+}
+
+/// Decisions between checkpoints unless set with
+/// [`RlEnv::with_checkpoint_interval`].
+pub const DEFAULT_CHECKPOINT_INTERVAL: u64 = 64;
+
+/// The simulation between two events, taken by an episode's worker.
+struct Checkpoint {
+    /// The runner, its RL policies bound to `channel`.
+    runner: SimulationRunner,
+    /// Disconnected channel holding the reward state at the checkpoint.
+    channel: Arc<DecisionChannel>,
+    /// The episode's scenario.
+    scenario: Arc<ScenarioConfig>,
+}
+
+impl Checkpoint {
+    /// Copy `runner`, whose RL policies use `channel`.
+    fn take(
+        runner: &SimulationRunner,
+        channel: &DecisionChannel,
+        scenario: &Arc<ScenarioConfig>,
+    ) -> Self {
+        let (obs_tx, _) = bounded(0);
+        let (_, action_rx) = bounded(0);
+        let channel = Arc::new(channel.branch(obs_tx, action_rx));
+        Self {
+            runner: DecisionChannel::bind_clone(runner, &channel),
+            channel,
+            scenario: scenario.clone(),
+        }
+    }
+
+    /// Decisions made before the checkpoint.
+    fn decisions(&self) -> u64 {
+        self.channel.decisions()
+    }
+}
+
+/// How a worker's episode begins.
+enum Start {
+    /// Build the world for the scenario and run from the beginning.
+    Fresh(Box<ScenarioConfig>),
+    /// Continue from a checkpoint.
+    Resume(Arc<Checkpoint>),
+}
+
+/// Which simulator decisions the agent controls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ControlMode {
+    /// Task allocation only (robot choice); stations use the scenario policy.
+    #[default]
+    Allocation,
+    /// Station assignment only; robots use the scenario allocation policy.
+    StationAssignment,
+    /// Both decision kinds, interleaved in simulation order.
+    Both,
+}
+
+impl ControlMode {
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "allocation" => Some(Self::Allocation),
+            "station" | "station_assignment" => Some(Self::StationAssignment),
+            "both" => Some(Self::Both),
+            _ => None,
+        }
+    }
+
+    fn controls_allocation(self) -> bool {
+        matches!(self, Self::Allocation | Self::Both)
+    }
+
+    fn controls_stations(self) -> bool {
+        matches!(self, Self::StationAssignment | Self::Both)
+    }
+}
+
 /// A Gym-style environment over one waremax scenario.
 ///
 /// Construct with [`RlEnv::new`], then call [`RlEnv::reset`] to begin an episode
@@ -39,6 +188,18 @@ pub struct StepResult {
 pub struct RlEnv {
     scenario: ScenarioConfig,
     reward_cfg: RewardConfig,
+    control: ControlMode,
+    obs_spec: ObservationSpec,
+    /// Allocation policy consulted as the expert, by factory name.
+    expert: Option<String>,
+    /// Per-episode scenario randomization.
+    distribution: Option<ScenarioDistribution>,
+    /// Episodes started so far (drives the curriculum).
+    episodes: u64,
+    /// Curriculum progress pinned by the caller.
+    pinned_progress: Option<f64>,
+    /// Decisions between checkpoints of the simulation.
+    checkpoint_interval: u64,
 
     // Live episode handles (None between episodes).
     obs_rx: Option<Receiver<Message>>,
@@ -46,6 +207,15 @@ pub struct RlEnv {
     worker: Option<JoinHandle<()>>,
     shared_report: Arc<Mutex<Option<SimulationReport>>>,
     done: bool,
+    // Seed, progress, latest checkpoint and the actions sent since, for
+    // `clone_state`. The worker stores each checkpoint it takes.
+    episode_seed: Option<u64>,
+    episode_progress: f64,
+    checkpoint: Arc<Mutex<Option<Arc<Checkpoint>>>>,
+    history: Vec<ActionMsg>,
+    /// Decisions of the episode made before `history[0]`.
+    history_start: u64,
+    episode_scenario: Option<ScenarioConfig>,
 }
 
 impl RlEnv {
@@ -53,14 +223,105 @@ impl RlEnv {
         Self {
             scenario,
             reward_cfg,
+            control: ControlMode::default(),
+            obs_spec: ObservationSpec::default(),
+            expert: None,
+            distribution: None,
+            episodes: 0,
+            pinned_progress: None,
+            checkpoint_interval: DEFAULT_CHECKPOINT_INTERVAL,
             obs_rx: None,
             action_tx: None,
             worker: None,
             shared_report: Arc::new(Mutex::new(None)),
             done: false,
+            episode_seed: None,
+            episode_progress: 1.0,
+            checkpoint: Arc::new(Mutex::new(None)),
+            history: Vec::new(),
+            history_start: 0,
+            episode_scenario: None,
         }
     }
 
+    /// Select which decisions the agent controls (takes effect on next reset).
+    pub fn with_control(mut self, control: ControlMode) -> Self {
+        self.control = control;
+        self
+    }
+
+    /// Lay out observations by `spec` (takes effect on next reset).
+    pub fn with_observation_spec(mut self, spec: ObservationSpec) -> Self {
+        self.obs_spec = spec;
+        self
+    }
+
+    /// Consult the heuristic allocation policy `name` (as in the scenario's
+    /// `task_allocation.type`, e.g. `nearest_robot` or `auction`) at every
+    /// allocation decision: `Observation::expert_action` reports its choice
+    /// and `ActionMsg::Expert` applies it (takes effect on next reset).
+    pub fn with_expert(mut self, name: impl Into<String>) -> Self {
+        self.expert = Some(name.into());
+        self
+    }
+
+    /// Draw every episode's scenario from `distribution` (takes effect on next
+    /// reset). The env's own scenario is the template when the distribution
+    /// has none.
+    pub fn with_scenario_distribution(mut self, distribution: ScenarioDistribution) -> Self {
+        self.distribution = Some(distribution);
+        self
+    }
+
+    /// Checkpoint the simulation every `decisions` decisions (at least 1;
+    /// takes effect on next reset). A checkpoint copies the whole simulation,
+    /// so a shorter interval slows stepping but leaves fewer actions for
+    /// `restore` and `fork` to replay.
+    pub fn with_checkpoint_interval(mut self, decisions: u64) -> Self {
+        self.checkpoint_interval = decisions.max(1);
+        self
+    }
+
+    pub fn scenario_distribution(&self) -> Option<&ScenarioDistribution> {
+        self.distribution.as_ref()
+    }
+
+    /// Curriculum progress the next episode is drawn at: the pinned value if
+    /// any, otherwise the curriculum's progress after the episodes so far.
+    pub fn curriculum_progress(&self) -> f64 {
+        self.pinned_progress.unwrap_or_else(|| {
+            self.distribution
+                .as_ref()
+                .map_or(1.0, |d| d.progress(self.episodes))
+        })
+    }
+
+    /// Pin the curriculum progress (0..=1) instead of counting episodes, e.g.
+    /// from a trainer's global step.
+    pub fn set_curriculum_progress(&mut self, progress: f64) {
+        self.pinned_progress = Some(progress.clamp(0.0, 1.0));
+    }
+
+    /// The scenario of the current (or last) episode.
+    pub fn episode_scenario(&self) -> Option<&ScenarioConfig> {
+        self.episode_scenario.as_ref()
+    }
+
+    /// The expert policy's name, if one is set.
+    pub fn expert(&self) -> Option<&str> {
+        self.expert.as_deref()
+    }
+
+    /// The observation layout.
+    pub fn observation_spec(&self) -> &ObservationSpec {
+        &self.obs_spec
+    }
+
+    /// The decisions the agent controls.
+    pub fn control(&self) -> ControlMode {
+        self.control
+    }
+
     /// Whether the current episode has ended.
     pub fn is_done(&self) -> bool {
         self.done
@@ -73,20 +334,113 @@ impl RlEnv {
 
     /// Start a new episode with the given seed and return the first observation.
     pub fn reset(&mut self, seed: u64) -> Observation {
+        self.start_episode(seed);
+        self.recv_first()
+    }
+
+    /// Capture the current decision point, or `None` before the first reset.
+    /// Cheap: it shares the worker's latest checkpoint (see [`EnvSnapshot`]).
+    pub fn clone_state(&self) -> Option<EnvSnapshot> {
+        let seed = self.episode_seed?;
+        let checkpoint = self.checkpoint.lock().unwrap().clone()?;
+        let since = (checkpoint.decisions() - self.history_start) as usize;
+        Some(EnvSnapshot {
+            seed,
+            progress: self.episode_progress,
+            actions: self.history[since..].to_vec(),
+            checkpoint,
+        })
+    }
+
+    /// Return to the decision point captured in `snapshot` (of this env or a
+    /// fork of it) and return its observation. Resumes a copy of the
+    /// snapshot's checkpoint on a fresh worker and replays the actions taken
+    /// since it.
+    pub fn restore(&mut self, snapshot: &EnvSnapshot) -> Observation {
+        self.abort_and_join();
+        self.episode_seed = Some(snapshot.seed);
+        self.episode_progress = snapshot.progress;
+        self.episode_scenario = Some((*snapshot.checkpoint.scenario).clone());
+        self.spawn(Start::Resume(snapshot.checkpoint.clone()));
+        let mut obs = self.recv_first();
+        for action in &snapshot.actions {
+            if self.done {
+                break;
+            }
+            obs = self.step(action.clone()).obs;
+        }
+        obs
+    }
+
+    /// An independent env at this env's current decision point, with its
+    /// observation. `None` before the first reset.
+    ///
+    /// The fork resumes a copy of this env's simulation on its own worker
+    /// (see [`EnvSnapshot`]). Afterwards the two envs share nothing and step
+    /// independently.
+    pub fn fork(&self) -> Option<(RlEnv, Observation)> {
+        let snapshot = self.clone_state()?;
+        let mut env = RlEnv::new(self.scenario.clone(), self.reward_cfg.clone())
+            .with_control(self.control)
+            .with_observation_spec(self.obs_spec.clone());
+        env.expert = self.expert.clone();
+        env.distribution = self.distribution.clone();
+        env.checkpoint_interval = self.checkpoint_interval;
+        let obs = env.restore(&snapshot);
+        Some((env, obs))
+    }
+
+    /// Spawn the worker for a new episode without waiting for its first
+    /// decision, so several envs can simulate concurrently (see `VecEnv`).
+    pub(crate) fn start_episode(&mut self, seed: u64) {
+        let progress = self.curriculum_progress();
+        self.episodes += 1;
+        self.launch(seed, progress);
+    }
+
+    /// Spawn the worker for the episode seeded with `seed`, drawing its
+    /// scenario at curriculum `progress`.
+    fn launch(&mut self, seed: u64, progress: f64) {
         // Drain and join any previous episode.
         self.abort_and_join();
+        self.episode_seed = Some(seed);
+        self.episode_progress = progress;
+
+        let mut scenario = match &self.distribution {
+            Some(d) => d.sample(&self.scenario, seed, progress),
+            None => self.scenario.clone(),
+        };
+        scenario.seed = seed;
+        self.episode_scenario = Some(scenario.clone());
+        self.spawn(Start::Fresh(Box::new(scenario)));
+    }
 
+    /// Spawn a worker running the episode from `start`.
+    fn spawn(&mut self, start: Start) {
         let (obs_tx, obs_rx) = bounded::<Message>(1);
         let (action_tx, action_rx) = bounded::<ActionMsg>(1);
 
-        let mut scenario = self.scenario.clone();
-        scenario.seed = seed;
-        let duration = scenario.simulation.duration_minutes;
-        let warmup = scenario.simulation.warmup_minutes;
+        let resumed = match &start {
+            Start::Fresh(_) => None,
+            Start::Resume(checkpoint) => Some(checkpoint.clone()),
+        };
+        self.history.clear();
+        self.history_start = resumed.as_ref().map_or(0, |c| c.decisions());
+        *self.checkpoint.lock().unwrap() = resumed;
+        let checkpoint_slot = self.checkpoint.clone();
+
+        // The world builder constructs the expert as the scenario's own
+        // allocation policy; the worker then takes it out of the world.
+        let expert = self
+            .expert
+            .clone()
+            .filter(|_| self.control.controls_allocation());
 
         let reward_cfg = self.reward_cfg.clone();
-        let snapshot: Arc<Mutex<RewardSnapshot>> = Arc::new(Mutex::new(RewardSnapshot::default()));
-        let snapshot_for_terminal = snapshot.clone();
+        let control = self.control;
+        let obs_spec = self.obs_spec.clone();
+        let terminal_obs = Observation::zeros_for(&obs_spec);
+        let checkpoint_interval = self.checkpoint_interval;
 
         // Reset the shared report slot for this episode.
         let report_slot = self.shared_report.clone();
@@ -95,34 +449,99 @@ impl RlEnv {
         let obs_tx_terminal = obs_tx.clone();
 
         let worker = thread::spawn(move || {
-            let policy = RlPolicy::new(obs_tx, action_rx, reward_cfg.clone(), snapshot);
-            let mut world = build_world_from_config(&scenario);
-            world.policies.task_allocation = Box::new(policy);
-            // Attribution-shaped reward needs the per-task delay attribution
-            // collector running.
-            if reward_cfg.mode.needs_attribution() {
-                world.attribution_collector.enable();
-            }
-            let mut runner = SimulationRunner::new(world, duration, warmup);
+            let (mut runner, channel, scenario, mut checkpointed) = match start {
+                Start::Fresh(mut scenario) => {
+                    if let Some(name) = &expert {
+                        scenario.policies.task_allocation.alloc_type = name.clone();
+                    }
+                    // One channel for every controlled decision point, so each
+                    // reward is the delta since the previous decision of either
+                    // kind.
+                    let channel = Arc::new(
+                        DecisionChannel::new(
+                            obs_tx,
+                            action_rx,
+                            reward_cfg.clone(),
+                            Arc::new(Mutex::new(RewardSnapshot::default())),
+                        )
+                        .with_observation_spec(obs_spec),
+                    );
+                    let mut world = build_world_from_config(&scenario);
+                    if control.controls_allocation() {
+                        let mut policy = RlPolicy::with_channel(channel.clone());
+                        if expert.is_some() {
+                            let expert = std::mem::replace(
+                                &mut world.policies.task_allocation,
+                                Box::new(NearestRobotPolicy::new()),
+                            );
+                            policy = policy.with_expert(expert);
+                        }
+                        world.policies.task_allocation = Box::new(policy);
+                    }
+                    if control.controls_stations() {
+                        // The scenario's station policy stays on as the
+                        // fallback for no-op / masked choices.
+                        let fallback = std::mem::replace(
+                            &mut world.policies.station_assignment,
+                            Box::new(LeastQueuePolicy::for_pick()),
+                        );
+                        world.policies.station_assignment =
+                            Box::new(RlStationPolicy::new(channel.clone(), fallback));
+                    }
+                    // Attribution-shaped reward needs the per-task delay
+                    // attribution collector running.
+                    if reward_cfg.needs_attribution() {
+                        world.attribution_collector.enable();
+                    }
+                    let mut runner = SimulationRunner::new(
+                        world,
+                        scenario.simulation.duration_minutes,
+                        scenario.simulation.warmup_minutes,
+                    );
+                    runner.initialize();
+                    (runner, channel, Arc::from(scenario), None)
+                }
+                Start::Resume(checkpoint) => {
+                    let channel = Arc::new(checkpoint.channel.branch(obs_tx, action_rx));
+                    let runner = DecisionChannel::bind_clone(&checkpoint.runner, &channel);
+                    let decisions = Some(checkpoint.decisions());
+                    (runner, channel, checkpoint.scenario.clone(), decisions)
+                }
+            };
 
-            // Run to completion, then compute the final reward + metrics. The
-            // whole thing is unwind-guarded so a simulator panic becomes a clean
-            // terminal rather than a poisoned/hung channel.
+            // Run to completion, checkpointing at the first event boundary
+            // once `checkpoint_interval` decisions have passed, then compute
+            // the final reward + metrics. The whole thing is unwind-guarded so
+            // a simulator panic becomes a clean terminal rather than a
+            // poisoned/hung channel.
             let result = catch_unwind(AssertUnwindSafe(|| {
-                let report = runner.run();
+                loop {
+                    let decisions = channel.decisions();
+                    let due = checkpointed.is_none_or(|c| decisions >= c + checkpoint_interval);
+                    if due && !channel.is_aborted() {
+                        let checkpoint = Checkpoint::take(&runner, &channel, &scenario);
+                        *checkpoint_slot.lock().unwrap() = Some(Arc::new(checkpoint));
+                        checkpointed = Some(decisions);
+                    }
+                    if !runner.step() {
+                        break;
+                    }
+                }
+                let report = runner.generate_report();
                 let w = runner.world();
                 let attribution = if w.attribution_collector.is_enabled() {
                     Some(&w.attribution_collector)
                 } else {
                     None
                 };
-                let final_snap = reward::snapshot_from(&w.orders, &w.tasks, attribution);
+                let final_snap = reward::snapshot_from(&w.orders, &w.tasks, attribution)
+                    .with_fleet(&w.robots, Some(&w.traffic));
                 (report, final_snap)
             }));
 
             let terminal = match result {
                 Ok((report, final_snap)) => {
-                    let prev = snapshot_for_terminal.lock().unwrap().clone();
+                    let prev = channel.reward_snapshot();
                     let reward = reward::delta(&prev, &final_snap, &reward_cfg);
                     let info = StepInfo {
                         completed_delta: final_snap.completed as i64 - prev.completed as i64,
@@ -132,6 +551,11 @@ impl RlEnv {
                         sim_time_s: report.duration_s,
                         final_metrics: Some(final_metrics(&report)),
                         errored: false,
+                        reward_terms: if reward_cfg.mode == RewardMode::Custom {
+                            reward_cfg.spec.contributions(&prev, &final_snap, 0.0)
+                        } else {
+                            Vec::new()
+                        },
                     };
                     *report_slot.lock().unwrap() = Some(report);
                     (reward, info)
@@ -146,7 +570,7 @@ impl RlEnv {
             };
 
             let _ = obs_tx_terminal.send(Message::Terminal {
-                obs: Observation::zeros(),
+                obs: terminal_obs,
                 reward: terminal.0,
                 info: terminal.1,
             });
@@ -156,7 +580,10 @@ impl RlEnv {
         self.action_tx = Some(action_tx);
         self.worker = Some(worker);
         self.done = false;
+    }
 
+    /// Block for the first message of an episode started by `start_episode`.
+    pub(crate) fn recv_first(&mut self) -> Observation {
         // Receive the first message (the first decision, or a terminal if the
         // episode produced no allocation decisions at all).
         match self.obs_rx.as_ref().unwrap().recv() {
@@ -168,33 +595,42 @@ impl RlEnv {
             }
             Err(_) => {
                 self.done = true;
-                Observation::zeros()
+                Observation::zeros_for(&self.obs_spec)
             }
         }
     }
 
     /// Apply an action and advance to the next decision (or episode end).
     pub fn step(&mut self, action: ActionMsg) -> StepResult {
-        if self.done {
-            return StepResult {
-                obs: Observation::zeros(),
-                reward: 0.0,
-                done: true,
-                info: StepInfo::default(),
-            };
+        if !self.send_action(action) {
+            return self.finished();
         }
+        self.recv_step()
+    }
 
+    /// Hand `action` to the worker without waiting for the next decision.
+    /// Returns false when the episode is already over.
+    pub(crate) fn send_action(&mut self, action: ActionMsg) -> bool {
+        if self.done {
+            return false;
+        }
         let action_tx = self.action_tx.as_ref().expect("step called before reset");
-        if action_tx.send(action).is_err() {
+        if action_tx.send(action.clone()).is_err() {
             self.done = true;
-            return StepResult {
-                obs: Observation::zeros(),
-                reward: 0.0,
-                done: true,
-                info: StepInfo::default(),
-            };
+            return false;
         }
+        self.history.push(action);
+        true
+    }
 
+    /// Block for the worker's answer to an action sent with `send_action`.
+    pub(crate) fn recv_step(&mut self) -> StepResult {
+        let result = self.recv_result();
+        self.trim_history();
+        result
+    }
+
+    fn recv_result(&mut self) -> StepResult {
         match self.obs_rx.as_ref().unwrap().recv() {
             Ok(Message::Decision { obs, reward, info }) => StepResult {
                 obs,
@@ -214,16 +650,37 @@ impl RlEnv {
             }
             Err(_) => {
                 self.done = true;
-                StepResult {
-                    obs: Observation::zeros(),
-                    reward: 0.0,
-                    done: true,
-                    info: StepInfo::default(),
-                }
+                self.finished()
             }
         }
     }
 
+    /// The zero-reward result returned once an episode is over.
+    pub(crate) fn finished(&self) -> StepResult {
+        StepResult {
+            obs: Observation::zeros_for(&self.obs_spec),
+            reward: 0.0,
+            done: true,
+            info: StepInfo::default(),
+        }
+    }
+
+    /// Forget the actions sent before the worker's latest checkpoint.
+    fn trim_history(&mut self) {
+        let Some(decisions) = self
+            .checkpoint
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|c| c.decisions())
+        else {
+            return;
+        };
+        self.history
+            .drain(..(decisions - self.history_start) as usize);
+        self.history_start = decisions;
+    }
+
     fn join_worker(&mut self) {
         if let Some(h) = self.worker.take() {
             let _ = h.join();
@@ -254,7 +711,7 @@ impl Drop for RlEnv {
     }
 }
 
-fn final_metrics(report: &SimulationReport) -> FinalMetrics {
+pub(crate) fn final_metrics(report: &SimulationReport) -> FinalMetrics {
     // Prefer SLA-based on-time rate / lateness when available, matching the
     // `metrics_from_report` path on the Python side so the comparison table is
     // apples-to-apples. Fall back to completed-vs-late when no SLA data.
@@ -0,0 +1,159 @@
+//! Graph-structured observations for message-passing policies.
+//!
+//! A [`GraphObservation`] exports the warehouse map as node/edge index
+//! tensors, in the layout graph libraries expect (`edge_src` / `edge_dst` are
+//! the two rows of a `[2, E]` edge index). Nodes are sorted by `NodeId.0` and
+//! edges follow each node's adjacency list, so the layout is deterministic and
+//! the same for every decision of an episode; only the features change.
+//!
+//! Node features (`NODE_FEATS` per node, in order):
+//! x and y scaled to the map's bounding box, station node, charging node,
+//! traffic occupancy over node capacity, robots present, pending pickups,
+//! station queue, task pickup flag, candidate flag. Edge features
+//! (`EDGE_FEATS` per edge): length and traffic occupancy over edge capacity.
+
+use std::collections::HashMap;
+
+use waremax_core::NodeId;
+use waremax_map::NodeType;
+use waremax_policies::PolicyContext;
+
+use crate::obs_spec::ObsNorms;
+use crate::observation::norm;
+
+/// Features per node.
+pub const NODE_FEATS: usize = 10;
+/// Features per directed edge.
+pub const EDGE_FEATS: usize = 2;
+
+/// The map as a graph, with dynamic features at one decision point.
+#[derive(Debug, Clone, Default)]
+pub struct GraphObservation {
+    /// Node of each row, sorted by id.
+    pub node_ids: Vec<NodeId>,
+    /// Flattened `N * NODE_FEATS`.
+    pub node_feats: Vec<f32>,
+    /// Source node row of each directed edge.
+    pub edge_src: Vec<u32>,
+    /// Destination node row of each directed edge.
+    pub edge_dst: Vec<u32>,
+    /// Flattened `E * EDGE_FEATS`.
+    pub edge_feats: Vec<f32>,
+    /// Node row of the task's pickup.
+    pub pickup_node: u32,
+    /// Node row of each action row's candidate (robot or station), in action
+    /// order.
+    pub candidate_nodes: Vec<u32>,
+}
+
+impl GraphObservation {
+    pub fn num_nodes(&self) -> usize {
+        self.node_ids.len()
+    }
+
+    pub fn num_edges(&self) -> usize {
+        self.edge_src.len()
+    }
+}
+
+/// Build the graph view of the world for a decision about the task picked at
+/// `pickup`, whose action rows sit at `candidates`.
+///
+/// Occupancy features are zero when `ctx.traffic` is absent.
+pub fn build_graph_observation(
+    ctx: &PolicyContext,
+    pickup: NodeId,
+    candidates: &[NodeId],
+    norms: &ObsNorms,
+) -> GraphObservation {
+    let mut node_ids: Vec<NodeId> = ctx.map.nodes.keys().copied().collect();
+    node_ids.sort_by_key(|n| n.0);
+    let row: HashMap<NodeId, u32> = node_ids
+        .iter()
+        .enumerate()
+        .map(|(i, &n)| (n, i as u32))
+        .collect();
+
+    let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+    let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+    for node in ctx.map.nodes.values() {
+        min_x = min_x.min(node.x);
+        min_y = min_y.min(node.y);
+        max_x = max_x.max(node.x);
+        max_y = max_y.max(node.y);
+    }
+    let span_x = (max_x - min_x).max(1e-9);
+    let span_y = (max_y - min_y).max(1e-9);
+
+    // Per-node counts of the dynamic quantities.
+    let mut robots_present: HashMap<NodeId, usize> = HashMap::new();
+    for robot in ctx.robots.values() {
+        *robots_present.entry(robot.current_node).or_default() += 1;
+    }
+    let mut pending_pickups: HashMap<NodeId, usize> = HashMap::new();
+    for task in ctx.tasks.values().filter(|t| t.is_pending()) {
+        *pending_pickups.entry(task.source.access_node).or_default() += 1;
+    }
+    let mut station_queue: HashMap<NodeId, usize> = HashMap::new();
+    let mut station_nodes: Vec<NodeId> = Vec::new();
+    for station in ctx.stations.values() {
+        *station_queue.entry(station.node).or_default() += station.queue_length();
+        station_nodes.push(station.node);
+    }
+
+    let mut node_feats = vec![0.0f32; node_ids.len() * NODE_FEATS];
+    for (i, id) in node_ids.iter().enumerate() {
+        let node = &ctx.map.nodes[id];
+        let occupancy = ctx.traffic.map_or(0, |t| t.get_node_occupancy(*id));
+        let count = |m: &HashMap<NodeId, usize>| m.get(id).copied().unwrap_or(0) as f64;
+        let f = &mut node_feats[i * NODE_FEATS..(i + 1) * NODE_FEATS];
+        f[0] = ((node.x - min_x) / span_x) as f32;
+        f[1] = ((node.y - min_y) / span_y) as f32;
+        f[2] = flag(station_nodes.contains(id));
+        f[3] = flag(node.node_type == NodeType::Charging);
+        f[4] = norm(occupancy as f64, node.capacity.max(1) as f64);
+        f[5] = norm(count(&robots_present), norms.congestion);
+        f[6] = norm(count(&pending_pickups), norms.queue);
+        f[7] = norm(count(&station_queue), norms.queue);
+        f[8] = flag(*id == pickup);
+        f[9] = flag(candidates.contains(id));
+    }
+
+    let mut edge_src = Vec::new();
+    let mut edge_dst = Vec::new();
+    let mut edge_feats = Vec::new();
+    for &from in &node_ids {
+        for (to, edge_id, length) in ctx.map.neighbors(from) {
+            let Some(&dst) = row.get(&to) else {
+                continue;
+            };
+            let capacity = ctx.map.get_edge(edge_id).map_or(1, |e| e.capacity.max(1));
+            let occupancy = ctx.traffic.map_or(0, |t| t.get_edge_occupancy(edge_id));
+            edge_src.push(row[&from]);
+            edge_dst.push(dst);
+            edge_feats.push(norm(length, norms.distance_m));
+            edge_feats.push(norm(occupancy as f64, capacity as f64));
+        }
+    }
+
+    GraphObservation {
+        pickup_node: row.get(&pickup).copied().unwrap_or(0),
+        candidate_nodes: candidates
+            .iter()
+            .map(|n| row.get(n).copied().unwrap_or(0))
+            .collect(),
+        node_ids,
+        node_feats,
+        edge_src,
+        edge_dst,
+        edge_feats,
+    }
+}
+
+fn flag(b: bool) -> f32 {
+    if b {
+        1.0
+    } else {
+        0.0
+    }
+}
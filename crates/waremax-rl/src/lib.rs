@@ -7,16 +7,59 @@
 //! simulation runs on a worker thread. The handshake is strict ping-pong, so the
 //! run is deterministic given a seed and an action sequence.
 //!
+//! Station assignment can be exposed as a second decision point
+//! ([`RlStationPolicy`], selected with [`ControlMode`]); both kinds share one
+//! channel and one reward stream.
+//!
+//! [`MultiAgentEnv`] is the decentralized mode: each robot is an agent that,
+//! when free, picks a pending task or parks, with all robots free at the same
+//! `SimTime` deciding in one joint step.
+//!
+//! [`VecEnv`] steps N `RlEnv`s with one batched call and auto-resets
+//! finished episodes from deterministic per-env seed streams.
+//!
+//! The allocation observation's rows and features are configured with an
+//! [`ObservationSpec`], which can also attach a [`GraphObservation`] of the
+//! map for message-passing policies.
+//!
+//! [`RlEnv::fork`] / [`RlEnv::clone_state`] branch an episode at a decision
+//! point for tree search and counterfactual rollouts.
+//!
+//! [`record_episode`] writes offline-RL datasets of transitions, optionally
+//! with a heuristic allocation policy acting as the expert
+//! ([`RlEnv::with_expert`]).
+//!
+//! A [`ScenarioDistribution`] randomizes the scenario at every reset (robot
+//! count, order rate, stations, templates, due times, failures), optionally
+//! widened over training by a [`Curriculum`].
+//!
 //! See [`crate::env::RlEnv`] for the entry point.
 
+pub mod dataset;
 pub mod env;
+pub mod graph_obs;
+pub mod multi_env;
+pub mod obs_spec;
 pub mod observation;
 pub mod policy;
 pub mod protocol;
+pub mod randomization;
 pub mod reward;
+pub mod vec_env;
 
-pub use env::{RlEnv, StepResult};
-pub use observation::{Observation, MAX_ROBOTS, ROBOT_FEATS, TASK_FEATS};
-pub use policy::RlPolicy;
-pub use protocol::{ActionMsg, FinalMetrics, Message, StepInfo};
-pub use reward::{RewardConfig, RewardMode, RewardSnapshot};
+pub use dataset::{
+    read_dataset, record_episode, DatasetHeader, DatasetWriter, RecordSummary, Transition,
+};
+pub use env::{ControlMode, EnvSnapshot, RlEnv, StepResult, DEFAULT_CHECKPOINT_INTERVAL};
+pub use graph_obs::{build_graph_observation, GraphObservation, EDGE_FEATS, NODE_FEATS};
+pub use multi_env::{MultiAgentEnv, MultiStepResult};
+pub use obs_spec::{ObsNorms, ObservationSpec, RobotFeature, TaskFeature};
+pub use observation::{
+    AgentObservation, DecisionKind, MultiObservation, Observation, AGENT_FEATS, AGENT_TASK_FEATS,
+    MAX_ROBOTS, MAX_STATIONS, MAX_TASKS, PARK_ACTION, ROBOT_FEATS, STATION_FEATS, TASK_FEATS,
+};
+pub use policy::{AgentCredit, DecisionChannel, MultiAgentPolicy, RlPolicy, RlStationPolicy};
+pub use protocol::{ActionMsg, FinalMetrics, JointAction, Message, MultiMessage, StepInfo};
+pub use randomization::{Curriculum, ParamRange, ScenarioDistribution};
+pub use reward::{RewardConfig, RewardMode, RewardSnapshot, RewardSpec, RewardTerm, TermSpec};
+pub use vec_env::{episode_seed, VecEnv, VecObservation, VecStepResult};
@@ -0,0 +1,292 @@
+//! `MultiAgentEnv`: the decentralized counterpart of `RlEnv`, where every robot
+//! is an agent.
+//!
+//! Whenever robots are free at a dispatch round, all of them receive their own
+//! observation in one [`MultiObservation`] and answer with one
+//! [`JointAction`]. The worker thread and the bounded(1) ping-pong are the same
+//! as `RlEnv`'s, so a run is deterministic given the seed and the sequence of
+//! joint actions.
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use crossbeam_channel::{bounded, Receiver, Sender};
+use waremax_config::ScenarioConfig;
+use waremax_core::RobotId;
+use waremax_metrics::SimulationReport;
+use waremax_sim::SimulationRunner;
+use waremax_testing::runner::build_world_from_config;
+
+use crate::env::final_metrics;
+use crate::observation::{AgentObservation, MultiObservation};
+use crate::policy::{AgentCredit, MultiAgentPolicy};
+use crate::protocol::{JointAction, MultiMessage, StepInfo};
+use crate::reward::{self, RewardConfig, RewardMode, RewardSnapshot};
+
+/// Result of a single multi-agent `step`.
+#[derive(Debug, Clone)]
+pub struct MultiStepResult {
+    /// The next joint decision; on the terminal step, the whole fleet with
+    /// zeroed views.
+    pub obs: MultiObservation,
+    /// One reward per entry of `obs.agents`.
+    pub rewards: Vec<f32>,
+    pub done: bool,
+    pub info: StepInfo,
+    /// Task choices of the applied joint action that lost to a lower-id robot.
+    pub conflicts: usize,
+}
+
+/// A multi-agent environment over one waremax scenario.
+///
+/// Construct with [`MultiAgentEnv::new`], call [`MultiAgentEnv::reset`] to get
+/// the first joint decision, then [`MultiAgentEnv::step`] with one action per
+/// agent until `MultiStepResult::done` is true.
+pub struct MultiAgentEnv {
+    scenario: ScenarioConfig,
+    reward_cfg: RewardConfig,
+
+    // Live episode handles (None between episodes).
+    obs_rx: Option<Receiver<MultiMessage>>,
+    action_tx: Option<Sender<JointAction>>,
+    worker: Option<JoinHandle<()>>,
+    shared_report: Arc<Mutex<Option<SimulationReport>>>,
+    done: bool,
+}
+
+impl MultiAgentEnv {
+    pub fn new(scenario: ScenarioConfig, reward_cfg: RewardConfig) -> Self {
+        Self {
+            scenario,
+            reward_cfg,
+            obs_rx: None,
+            action_tx: None,
+            worker: None,
+            shared_report: Arc::new(Mutex::new(None)),
+            done: false,
+        }
+    }
+
+    /// Every robot that can ever be an agent, in id order.
+    pub fn possible_agents(&self) -> Vec<RobotId> {
+        (0..self.scenario.robots.count).map(RobotId).collect()
+    }
+
+    /// Whether the current episode has ended.
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// The report of the most recently completed episode, if any.
+    pub fn last_report(&self) -> Option<SimulationReport> {
+        self.shared_report.lock().unwrap().clone()
+    }
+
+    /// Start a new episode with the given seed and return the first joint
+    /// decision.
+    pub fn reset(&mut self, seed: u64) -> MultiObservation {
+        self.abort_and_join();
+
+        let (obs_tx, obs_rx) = bounded::<MultiMessage>(1);
+        let (action_tx, action_rx) = bounded::<JointAction>(1);
+
+        let mut scenario = self.scenario.clone();
+        scenario.seed = seed;
+        let duration = scenario.simulation.duration_minutes;
+        let warmup = scenario.simulation.warmup_minutes;
+
+        let reward_cfg = self.reward_cfg.clone();
+        let snapshot = Arc::new(Mutex::new(RewardSnapshot::default()));
+        let credit = Arc::new(Mutex::new(AgentCredit::default()));
+        let snapshot_for_terminal = snapshot.clone();
+        let credit_for_terminal = credit.clone();
+        let fleet = self.possible_agents();
+
+        let report_slot = self.shared_report.clone();
+        *report_slot.lock().unwrap() = None;
+
+        let obs_tx_terminal = obs_tx.clone();
+
+        let worker = thread::spawn(move || {
+            let policy =
+                MultiAgentPolicy::new(obs_tx, action_rx, reward_cfg.clone(), snapshot, credit);
+            let mut world = build_world_from_config(&scenario);
+            world.policies.task_allocation = Box::new(policy);
+            if reward_cfg.needs_attribution() {
+                world.attribution_collector.enable();
+            }
+            let mut runner = SimulationRunner::new(world, duration, warmup);
+
+            let result = catch_unwind(AssertUnwindSafe(|| {
+                let report = runner.run();
+                let w = runner.world();
+                let attribution = if w.attribution_collector.is_enabled() {
+                    Some(&w.attribution_collector)
+                } else {
+                    None
+                };
+                let final_snap = reward::snapshot_from(&w.orders, &w.tasks, attribution)
+                    .with_fleet(&w.robots, Some(&w.traffic));
+                (report, final_snap)
+            }));
+
+            // Settle every robot's outstanding credit against the final delta.
+            let mut credit = credit_for_terminal.lock().unwrap();
+            let (team_reward, info) = match result {
+                Ok((report, final_snap)) => {
+                    let prev = snapshot_for_terminal.lock().unwrap().clone();
+                    let team_reward = reward::delta(&prev, &final_snap, &reward_cfg);
+                    let info = StepInfo {
+                        completed_delta: final_snap.completed as i64 - prev.completed as i64,
+                        late_delta: final_snap.late as i64 - prev.late as i64,
+                        lateness_delta_s: final_snap.cum_lateness_s - prev.cum_lateness_s,
+                        pending: final_snap.pending,
+                        sim_time_s: report.duration_s,
+                        final_metrics: Some(final_metrics(&report)),
+                        errored: false,
+                        reward_terms: if reward_cfg.mode == RewardMode::Custom {
+                            reward_cfg.spec.contributions(&prev, &final_snap, 0.0)
+                        } else {
+                            Vec::new()
+                        },
+                    };
+                    *report_slot.lock().unwrap() = Some(report);
+                    (team_reward, info)
+                }
+                Err(_) => {
+                    let info = StepInfo {
+                        errored: true,
+                        ..Default::default()
+                    };
+                    (0.0, info)
+                }
+            };
+            credit.accrue(fleet.iter().copied(), team_reward);
+            let rewards = fleet.iter().map(|&r| credit.settle(r)).collect();
+
+            let _ = obs_tx_terminal.send(MultiMessage::Terminal {
+                obs: terminal_observation(fleet),
+                rewards,
+                info,
+                conflicts: credit.take_conflicts(),
+            });
+        });
+
+        self.obs_rx = Some(obs_rx);
+        self.action_tx = Some(action_tx);
+        self.worker = Some(worker);
+        self.done = false;
+
+        match self.obs_rx.as_ref().unwrap().recv() {
+            Ok(MultiMessage::Decision { obs, .. }) => obs,
+            Ok(MultiMessage::Terminal { obs, .. }) => {
+                self.done = true;
+                self.join_worker();
+                obs
+            }
+            Err(_) => {
+                self.done = true;
+                terminal_observation(self.possible_agents())
+            }
+        }
+    }
+
+    /// Apply one joint action and advance to the next joint decision (or
+    /// episode end).
+    pub fn step(&mut self, actions: Vec<usize>) -> MultiStepResult {
+        if self.done {
+            return self.finished();
+        }
+
+        let action_tx = self.action_tx.as_ref().expect("step called before reset");
+        if action_tx.send(JointAction::Act(actions)).is_err() {
+            self.done = true;
+            return self.finished();
+        }
+
+        match self.obs_rx.as_ref().unwrap().recv() {
+            Ok(MultiMessage::Decision {
+                obs,
+                rewards,
+                info,
+                conflicts,
+            }) => MultiStepResult {
+                obs,
+                rewards,
+                done: false,
+                info,
+                conflicts,
+            },
+            Ok(MultiMessage::Terminal {
+                obs,
+                rewards,
+                info,
+                conflicts,
+            }) => {
+                self.done = true;
+                self.join_worker();
+                MultiStepResult {
+                    obs,
+                    rewards,
+                    done: true,
+                    info,
+                    conflicts,
+                }
+            }
+            Err(_) => {
+                self.done = true;
+                self.finished()
+            }
+        }
+    }
+
+    /// The zero-reward result returned once an episode is over.
+    fn finished(&self) -> MultiStepResult {
+        let obs = terminal_observation(self.possible_agents());
+        MultiStepResult {
+            rewards: vec![0.0; obs.agents.len()],
+            obs,
+            done: true,
+            info: StepInfo::default(),
+            conflicts: 0,
+        }
+    }
+
+    fn join_worker(&mut self) {
+        if let Some(h) = self.worker.take() {
+            let _ = h.join();
+        }
+    }
+
+    /// Drain an in-flight episode (every robot parks) and join the worker.
+    fn abort_and_join(&mut self) {
+        if let (Some(tx), Some(rx)) = (self.action_tx.take(), self.obs_rx.take()) {
+            loop {
+                if tx.send(JointAction::Abort).is_err() {
+                    break;
+                }
+                match rx.recv() {
+                    Ok(MultiMessage::Decision { .. }) => continue,
+                    Ok(MultiMessage::Terminal { .. }) | Err(_) => break,
+                }
+            }
+        }
+        self.join_worker();
+    }
+}
+
+impl Drop for MultiAgentEnv {
+    fn drop(&mut self) {
+        self.abort_and_join();
+    }
+}
+
+/// The whole fleet with zeroed views, reported on the terminal step.
+fn terminal_observation(fleet: Vec<RobotId>) -> MultiObservation {
+    MultiObservation {
+        per_agent: vec![AgentObservation::zeros(); fleet.len()],
+        agents: fleet,
+        task_ids: Vec::new(),
+    }
+}
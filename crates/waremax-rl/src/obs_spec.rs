@@ -0,0 +1,283 @@
+//! Observation spec: which features the allocation observation carries, how
+//! many candidate rows it has, and how features are normalized.
+//!
+//! [`ObservationSpec::default`] reproduces the original fixed layout
+//! (`MAX_ROBOTS` rows of `ROBOT_FEATS` features plus `TASK_FEATS` task
+//! features), so existing agents keep their shapes. Features are picked by
+//! name from a registry ([`RobotFeature::ALL`], [`TaskFeature::ALL`]) so
+//! larger fleets and feature ablations are a config change, not a rebuild.
+
+use waremax_policies::learned::norms;
+
+use crate::observation::{MAX_ROBOTS, ROBOT_FEATS, TASK_FEATS};
+
+/// A per-candidate feature, computed relative to the task's pickup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RobotFeature {
+    /// Straight-line distance to the pickup.
+    Distance,
+    /// Shortest-path distance to the pickup over the map graph.
+    RouteDistance,
+    /// Shortest-path travel time to the pickup at the robot's top speed.
+    EtaToPickup,
+    /// Battery state of charge.
+    Battery,
+    /// Tasks already queued on the robot.
+    TaskQueue,
+    /// Availability flag (1 for every real candidate row).
+    Available,
+    /// Tasks completed so far.
+    TasksCompleted,
+    /// Signed x offset from the pickup.
+    OffsetX,
+    /// Signed y offset from the pickup.
+    OffsetY,
+    /// Distance travelled so far.
+    TotalDistance,
+    /// Other robots at the robot's node or one hop away.
+    LocalCongestion,
+    /// Whether the robot is driving to a parking node.
+    Repositioning,
+}
+
+impl RobotFeature {
+    /// Every registered robot feature.
+    pub const ALL: &'static [Self] = &[
+        Self::Distance,
+        Self::RouteDistance,
+        Self::EtaToPickup,
+        Self::Battery,
+        Self::TaskQueue,
+        Self::Available,
+        Self::TasksCompleted,
+        Self::OffsetX,
+        Self::OffsetY,
+        Self::TotalDistance,
+        Self::LocalCongestion,
+        Self::Repositioning,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Distance => "distance",
+            Self::RouteDistance => "route_distance",
+            Self::EtaToPickup => "eta_to_pickup",
+            Self::Battery => "battery",
+            Self::TaskQueue => "task_queue",
+            Self::Available => "available",
+            Self::TasksCompleted => "tasks_completed",
+            Self::OffsetX => "offset_x",
+            Self::OffsetY => "offset_y",
+            Self::TotalDistance => "total_distance",
+            Self::LocalCongestion => "local_congestion",
+            Self::Repositioning => "repositioning",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        let s = s.to_lowercase();
+        Self::ALL.iter().copied().find(|f| f.name() == s)
+    }
+
+    /// Whether computing this feature needs shortest-path distances.
+    pub(crate) fn needs_routes(self) -> bool {
+        matches!(self, Self::RouteDistance | Self::EtaToPickup)
+    }
+}
+
+/// A task-level feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskFeature {
+    /// Minutes until the order is due (negative once late).
+    DueSlack,
+    /// Units to pick.
+    Quantity,
+    /// Pending tasks in the system.
+    Pending,
+    /// Candidate rows in use, as a fraction of the row count.
+    CandidateFraction,
+    /// Simulation time within the phase window.
+    Phase,
+    /// Queue length at the task's destination station.
+    StationQueue,
+}
+
+impl TaskFeature {
+    /// Every registered task feature.
+    pub const ALL: &'static [Self] = &[
+        Self::DueSlack,
+        Self::Quantity,
+        Self::Pending,
+        Self::CandidateFraction,
+        Self::Phase,
+        Self::StationQueue,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::DueSlack => "due_slack",
+            Self::Quantity => "quantity",
+            Self::Pending => "pending",
+            Self::CandidateFraction => "candidate_fraction",
+            Self::Phase => "phase",
+            Self::StationQueue => "station_queue",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        let s = s.to_lowercase();
+        Self::ALL.iter().copied().find(|f| f.name() == s)
+    }
+}
+
+/// Normalization scales: each feature is divided by its scale and clamped.
+/// The defaults are the learned allocation policy's
+/// ([`waremax_policies::learned::norms`]).
+#[derive(Debug, Clone)]
+pub struct ObsNorms {
+    pub distance_m: f64,
+    pub travel_m: f64,
+    pub eta_s: f64,
+    pub queue: f64,
+    pub tasks_completed: f64,
+    pub pending: f64,
+    /// Half-width of the due-slack window, in minutes.
+    pub due_window_min: f64,
+    pub phase_s: f64,
+    pub congestion: f64,
+}
+
+impl Default for ObsNorms {
+    fn default() -> Self {
+        Self {
+            distance_m: norms::DISTANCE_M,
+            travel_m: norms::TRAVEL_M,
+            eta_s: 120.0,
+            queue: norms::QUEUE,
+            tasks_completed: norms::TASKS_COMPLETED,
+            pending: norms::PENDING,
+            due_window_min: norms::DUE_WINDOW_MIN,
+            phase_s: norms::PHASE_S,
+            congestion: 4.0,
+        }
+    }
+}
+
+/// Layout of the allocation observation.
+#[derive(Debug, Clone)]
+pub struct ObservationSpec {
+    /// Candidate rows (and action-space size). Candidates beyond this are
+    /// dropped, nearest to the pickup kept, and counted in
+    /// `Observation::truncated_candidates`.
+    pub max_candidates: usize,
+    pub robot_features: Vec<RobotFeature>,
+    pub task_features: Vec<TaskFeature>,
+    pub norms: ObsNorms,
+    /// Also attach a [`GraphObservation`](crate::graph_obs::GraphObservation)
+    /// of the map to every decision.
+    pub graph: bool,
+}
+
+impl Default for ObservationSpec {
+    fn default() -> Self {
+        let spec = Self {
+            max_candidates: MAX_ROBOTS,
+            robot_features: vec![
+                RobotFeature::Distance,
+                RobotFeature::Battery,
+                RobotFeature::TaskQueue,
+                RobotFeature::Available,
+                RobotFeature::TasksCompleted,
+                RobotFeature::OffsetX,
+                RobotFeature::OffsetY,
+                RobotFeature::TotalDistance,
+            ],
+            task_features: vec![
+                TaskFeature::DueSlack,
+                TaskFeature::Quantity,
+                TaskFeature::Pending,
+                TaskFeature::CandidateFraction,
+                TaskFeature::Phase,
+                TaskFeature::StationQueue,
+            ],
+            norms: ObsNorms::default(),
+            graph: false,
+        };
+        debug_assert_eq!(spec.robot_feats(), ROBOT_FEATS);
+        debug_assert_eq!(spec.task_feats(), TASK_FEATS);
+        spec
+    }
+}
+
+impl ObservationSpec {
+    /// Build a spec from feature names; `None` keeps the default list.
+    ///
+    /// Returns an error naming the first unknown feature, or if a list or
+    /// `max_candidates` is empty.
+    pub fn from_names(
+        max_candidates: Option<usize>,
+        robot_features: Option<&[String]>,
+        task_features: Option<&[String]>,
+    ) -> Result<Self, String> {
+        let mut spec = Self::default();
+        if let Some(n) = max_candidates {
+            if n == 0 {
+                return Err("max_candidates must be at least 1".to_string());
+            }
+            spec.max_candidates = n;
+        }
+        if let Some(names) = robot_features {
+            spec.robot_features = names
+                .iter()
+                .map(|n| {
+                    RobotFeature::from_str(n).ok_or_else(|| format!("unknown robot feature '{n}'"))
+                })
+                .collect::<Result<_, _>>()?;
+            if spec.robot_features.is_empty() {
+                return Err("robot_features must not be empty".to_string());
+            }
+        }
+        if let Some(names) = task_features {
+            spec.task_features = names
+                .iter()
+                .map(|n| {
+                    TaskFeature::from_str(n).ok_or_else(|| format!("unknown task feature '{n}'"))
+                })
+                .collect::<Result<_, _>>()?;
+            if spec.task_features.is_empty() {
+                return Err("task_features must not be empty".to_string());
+            }
+        }
+        Ok(spec)
+    }
+
+    /// Attach a graph observation of the map to every decision.
+    pub fn with_graph(mut self, graph: bool) -> Self {
+        self.graph = graph;
+        self
+    }
+
+    /// Features per candidate row.
+    pub fn robot_feats(&self) -> usize {
+        self.robot_features.len()
+    }
+
+    /// Task-level features.
+    pub fn task_feats(&self) -> usize {
+        self.task_features.len()
+    }
+
+    pub fn robot_feature_names(&self) -> Vec<&'static str> {
+        self.robot_features.iter().map(|f| f.name()).collect()
+    }
+
+    pub fn task_feature_names(&self) -> Vec<&'static str> {
+        self.task_features.iter().map(|f| f.name()).collect()
+    }
+
+    pub(crate) fn needs_routes(&self) -> bool {
+        self.robot_features.iter().any(|f| f.needs_routes())
+    }
+}
@@ -7,60 +7,136 @@
 //! Candidate robots (the available ones) occupy the first rows of `robot_feats`,
 //! sorted by `RobotId.0` for determinism (HashMap iteration order is otherwise
 //! randomized per-process), and `action_mask[i]` marks row `i` as a real
-//! candidate. The action is an index into these rows.
+//! candidate. The action is an index into these rows. Which features each row
+//! carries, and how many rows there are, is set by an [`ObservationSpec`].
+//!
+//! Station-assignment decisions ([`DecisionKind::StationAssignment`]) fill
+//! `station_feats` / `station_mask` instead, one row per pick station sorted by
+//! `StationId.0`; the action is an index into those rows. Both blocks are always
+//! present (zeroed when unused) so the observation keeps a single fixed shape.
+//!
+//! Multi-agent decisions use [`MultiObservation`] instead: one
+//! [`AgentObservation`] per deciding robot, whose rows are the pending tasks
+//! sorted by `TaskId.0`, plus a final [`PARK_ACTION`] column.
+//!
+//! With `ObservationSpec::graph` set, single-agent observations also carry a
+//! [`GraphObservation`] of the map whose candidate nodes follow the action
+//! rows.
 
-use waremax_core::{RobotId, TaskId};
-use waremax_entities::Robot;
-use waremax_policies::PolicyContext;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
 
-/// Maximum number of robots the observation/action space supports.
-pub const MAX_ROBOTS: usize = 64;
-/// Number of features per robot row.
+use waremax_core::{NodeId, RobotId, StationId, TaskId};
+use waremax_entities::{Robot, StationType, Task};
+use waremax_policies::{candidate_stations, PolicyContext};
+
+use crate::graph_obs::{build_graph_observation, GraphObservation};
+use crate::obs_spec::{ObsNorms, ObservationSpec, RobotFeature, TaskFeature};
+
+/// Candidate rows of the default [`ObservationSpec`] (and the cap on
+/// multi-agent batch size).
+pub const MAX_ROBOTS: usize = waremax_policies::learned::norms::CANDIDATE_ROWS;
+/// Features per robot row in the default [`ObservationSpec`].
 pub const ROBOT_FEATS: usize = 8;
-/// Number of task-level features.
+/// Task-level features in the default [`ObservationSpec`].
 pub const TASK_FEATS: usize = 6;
+/// Maximum number of pick stations the station-assignment action space supports.
+pub const MAX_STATIONS: usize = 16;
+/// Number of features per station row.
+pub const STATION_FEATS: usize = 6;
+/// Maximum number of pending tasks a multi-agent decision offers each robot.
+pub const MAX_TASKS: usize = 32;
+/// Number of per-robot features in a multi-agent observation.
+pub const AGENT_FEATS: usize = 6;
+/// Number of features per task row in a multi-agent observation.
+pub const AGENT_TASK_FEATS: usize = 6;
+/// Multi-agent action index meaning "take no task" (the robot stays idle and
+/// the idle positioning policy decides where it waits).
+pub const PARK_ACTION: usize = MAX_TASKS;
 
-// Normalization constants (rough; exact values are not critical for a baseline).
-const DIST_NORM: f64 = 50.0;
-const TRAVEL_NORM: f64 = 1000.0;
-const QUEUE_NORM: f64 = 10.0;
-const TASKS_DONE_NORM: f64 = 100.0;
-const PENDING_NORM: f64 = 50.0;
-const DUE_CLAMP_MIN: f64 = 120.0; // +/- 120 minutes window
-const PHASE_NORM_S: f64 = 7200.0; // 2-hour phase scale
+// Service-rate scale for station rows; other scales live in `ObsNorms`.
+const RATE_NORM: f64 = 10.0; // tasks per minute
 
-/// A fixed-shape, fully-owned observation at one allocation decision point.
+/// Which simulator decision an observation asks the agent to make.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DecisionKind {
+    /// Choose a robot for a pending task (rows of `robot_feats`).
+    #[default]
+    Allocation,
+    /// Choose the pick station for a new order line (rows of `station_feats`).
+    StationAssignment,
+}
+
+/// A fixed-shape, fully-owned observation at one decision point.
+///
+/// The allocation block's shape comes from the env's [`ObservationSpec`]:
+/// `R = spec.max_candidates` rows of `F = spec.robot_feats()` features.
 #[derive(Debug, Clone)]
 pub struct Observation {
-    /// Flattened `MAX_ROBOTS * ROBOT_FEATS`; row `r` is candidate `r` (or zero-padded).
+    /// Which decision this observation is for; selects the action rows.
+    pub kind: DecisionKind,
+    /// Flattened `R * F`; row `r` is candidate `r` (or zero-padded).
     pub robot_feats: Vec<f32>,
-    /// Length `MAX_ROBOTS`; `true` if row `r` is a real available candidate.
+    /// Length `R`; `true` if row `r` is a real available candidate.
     pub action_mask: Vec<bool>,
-    /// Length `TASK_FEATS`; features of the task being allocated.
+    /// Length `spec.task_feats()`; features of the task being decided.
     pub task_feats: Vec<f32>,
     /// Actual `RobotId` for each filled row, in row order. Length == number of candidates.
     /// Used to decode the agent's action index back into a robot.
     pub candidate_robot_ids: Vec<RobotId>,
+    /// Available robots left out because there were more than `R`.
+    pub truncated_candidates: usize,
+    /// Flattened `MAX_STATIONS * STATION_FEATS`; row `s` is pick station `s` (or zero-padded).
+    pub station_feats: Vec<f32>,
+    /// Length `MAX_STATIONS`; `true` if station row `s` may be chosen.
+    pub station_mask: Vec<bool>,
+    /// Actual `StationId` for each filled station row, in row order (masked rows included).
+    pub candidate_station_ids: Vec<StationId>,
+    /// Pick stations left out because there were more than `MAX_STATIONS`.
+    pub truncated_stations: usize,
+    /// The map as a graph, when `spec.graph` is set.
+    pub graph: Option<GraphObservation>,
+    /// Row the env's expert policy would choose (`RlEnv::with_expert`);
+    /// `None` without an expert, or when it picks no robot or one outside the
+    /// rows.
+    pub expert_action: Option<usize>,
 }
 
 impl Observation {
-    /// An all-zero observation with an all-false mask (used for terminal steps).
+    /// An all-zero observation in the default layout, with an all-false mask.
     pub fn zeros() -> Self {
+        Self::zeros_for(&ObservationSpec::default())
+    }
+
+    /// An all-zero observation shaped by `spec` (used for terminal steps).
+    pub fn zeros_for(spec: &ObservationSpec) -> Self {
         Self {
-            robot_feats: vec![0.0; MAX_ROBOTS * ROBOT_FEATS],
-            action_mask: vec![false; MAX_ROBOTS],
-            task_feats: vec![0.0; TASK_FEATS],
+            kind: DecisionKind::Allocation,
+            robot_feats: vec![0.0; spec.max_candidates * spec.robot_feats()],
+            action_mask: vec![false; spec.max_candidates],
+            task_feats: vec![0.0; spec.task_feats()],
             candidate_robot_ids: Vec::new(),
+            truncated_candidates: 0,
+            station_feats: vec![0.0; MAX_STATIONS * STATION_FEATS],
+            station_mask: vec![false; MAX_STATIONS],
+            candidate_station_ids: Vec::new(),
+            truncated_stations: 0,
+            graph: None,
+            expert_action: None,
         }
     }
 }
 
-/// Build an observation for allocating `task_id`.
+/// Build an observation for allocating `task_id`, laid out by `spec`.
 ///
 /// Returns `None` when there are no available candidate robots — the caller
 /// treats this as "no decision needed" and never consults the agent, keeping
 /// every emitted decision guaranteed to have at least one valid action.
-pub fn build_observation(ctx: &PolicyContext, task_id: TaskId) -> Option<Observation> {
+pub fn build_observation(
+    ctx: &PolicyContext,
+    task_id: TaskId,
+    spec: &ObservationSpec,
+) -> Option<Observation> {
     let task = ctx.tasks.get(&task_id)?;
     let pickup = task.source.access_node;
 
@@ -70,70 +146,405 @@ pub fn build_observation(ctx: &PolicyContext, task_id: TaskId) -> Option<Observa
         return None;
     }
     candidates.sort_by_key(|r| r.id.0);
-    candidates.truncate(MAX_ROBOTS);
+
+    // Over capacity: keep the robots nearest the pickup (ties by id), then
+    // restore id order.
+    let truncated_candidates = candidates.len().saturating_sub(spec.max_candidates);
+    if truncated_candidates > 0 {
+        candidates.sort_by(|a, b| {
+            let da = ctx.map.euclidean_distance(a.current_node, pickup);
+            let db = ctx.map.euclidean_distance(b.current_node, pickup);
+            da.total_cmp(&db).then(a.id.0.cmp(&b.id.0))
+        });
+        candidates.truncate(spec.max_candidates);
+        candidates.sort_by_key(|r| r.id.0);
+    }
+
+    // Shortest-path distances to the pickup, only when a feature needs them.
+    let route_dist = spec.needs_routes().then(|| distances_to(ctx, pickup));
 
     let (px, py) = node_xy(ctx, pickup);
+    let norms = &spec.norms;
+    let feats = spec.robot_feats();
 
-    let mut robot_feats = vec![0.0f32; MAX_ROBOTS * ROBOT_FEATS];
-    let mut action_mask = vec![false; MAX_ROBOTS];
+    let mut robot_feats = vec![0.0f32; spec.max_candidates * feats];
+    let mut action_mask = vec![false; spec.max_candidates];
     let mut candidate_robot_ids = Vec::with_capacity(candidates.len());
 
     for (row, robot) in candidates.iter().enumerate() {
         let (rx, ry) = node_xy(ctx, robot.current_node);
-        let dist = ctx.map.euclidean_distance(robot.current_node, pickup);
-        let base = row * ROBOT_FEATS;
-        robot_feats[base] = norm(dist, DIST_NORM);
-        robot_feats[base + 1] = robot.battery.soc as f32;
-        robot_feats[base + 2] = norm(robot.task_queue.len() as f64, QUEUE_NORM);
-        robot_feats[base + 3] = 1.0; // availability flag (always 1 for a candidate row)
-        robot_feats[base + 4] = norm(robot.tasks_completed as f64, TASKS_DONE_NORM);
-        robot_feats[base + 5] = ((rx - px) / DIST_NORM).clamp(-1.0, 1.0) as f32;
-        robot_feats[base + 6] = ((ry - py) / DIST_NORM).clamp(-1.0, 1.0) as f32;
-        robot_feats[base + 7] = norm(robot.total_distance, TRAVEL_NORM);
+        let route = || {
+            route_dist
+                .as_ref()
+                .and_then(|d| d.get(&robot.current_node).copied())
+        };
+        let base = row * feats;
+        for (col, feature) in spec.robot_features.iter().enumerate() {
+            robot_feats[base + col] = match feature {
+                RobotFeature::Distance => norm(
+                    ctx.map.euclidean_distance(robot.current_node, pickup),
+                    norms.distance_m,
+                ),
+                // Unreachable pickups saturate at 1.
+                RobotFeature::RouteDistance => route().map_or(1.0, |d| norm(d, norms.distance_m)),
+                RobotFeature::EtaToPickup => {
+                    route().map_or(1.0, |d| norm(d / robot.max_speed_mps.max(0.1), norms.eta_s))
+                }
+                RobotFeature::Battery => robot.battery.soc as f32,
+                RobotFeature::TaskQueue => norm(robot.task_queue.len() as f64, norms.queue),
+                RobotFeature::Available => 1.0,
+                RobotFeature::TasksCompleted => {
+                    norm(robot.tasks_completed as f64, norms.tasks_completed)
+                }
+                RobotFeature::OffsetX => ((rx - px) / norms.distance_m).clamp(-1.0, 1.0) as f32,
+                RobotFeature::OffsetY => ((ry - py) / norms.distance_m).clamp(-1.0, 1.0) as f32,
+                RobotFeature::TotalDistance => norm(robot.total_distance, norms.travel_m),
+                RobotFeature::LocalCongestion => {
+                    norm(local_congestion(ctx, robot) as f64, norms.congestion)
+                }
+                RobotFeature::Repositioning => {
+                    if robot.is_repositioning() {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                }
+            };
+        }
         action_mask[row] = true;
         candidate_robot_ids.push(robot.id);
     }
 
     // Task features.
-    let now_s = ctx.current_time.as_seconds();
-    let time_to_due = match task.order_id.and_then(|oid| ctx.orders.get(&oid)) {
-        Some(order) => match order.due_time {
-            Some(due) => {
-                let mins = (due.as_seconds() - now_s) / 60.0;
-                (mins / DUE_CLAMP_MIN).clamp(-1.0, 1.0)
-            }
-            None => 0.0,
-        },
-        None => 0.0,
-    };
-    let pending = ctx.tasks.values().filter(|t| t.is_pending()).count();
     let station_queue = ctx
         .stations
         .get(&task.destination_station)
         .map(|s| s.queue_length())
         .unwrap_or(0);
+    let candidate_frac = candidates.len() as f64 / spec.max_candidates as f64;
 
-    let task_feats = vec![
-        time_to_due as f32,
-        norm(task.quantity as f64, QUEUE_NORM),
-        norm(pending as f64, PENDING_NORM),
-        (candidates.len() as f64 / MAX_ROBOTS as f64) as f32,
-        (now_s / PHASE_NORM_S).clamp(0.0, 1.0) as f32,
-        norm(station_queue as f64, QUEUE_NORM),
-    ];
+    let graph = spec.graph.then(|| {
+        let nodes: Vec<NodeId> = candidates.iter().map(|r| r.current_node).collect();
+        build_graph_observation(ctx, pickup, &nodes, norms)
+    });
 
     Some(Observation {
+        kind: DecisionKind::Allocation,
         robot_feats,
         action_mask,
-        task_feats,
+        task_feats: task_features(ctx, task, spec, candidate_frac, station_queue),
         candidate_robot_ids,
+        truncated_candidates,
+        graph,
+        ..Observation::zeros_for(spec)
+    })
+}
+
+/// Build an observation for choosing the pick station of `task`, a new pick
+/// task that is not yet in `ctx.tasks`.
+///
+/// Every pick station gets a row; the mask allows the same stations the
+/// built-in station policies consider (accepting work, open ones preferred).
+/// With more than `MAX_STATIONS` pick stations, the allowed ones keep their
+/// rows first. Returns `None` when no station may be chosen.
+pub fn build_station_observation(
+    ctx: &PolicyContext,
+    task: &Task,
+    spec: &ObservationSpec,
+) -> Option<Observation> {
+    let allowed: Vec<StationId> = candidate_stations(ctx, &StationType::Pick)
+        .iter()
+        .map(|s| s.id)
+        .collect();
+    if allowed.is_empty() {
+        return None;
+    }
+
+    let mut stations: Vec<_> = ctx
+        .stations
+        .values()
+        .filter(|s| s.station_type == StationType::Pick)
+        .collect();
+    let truncated_stations = stations.len().saturating_sub(MAX_STATIONS);
+    if truncated_stations > 0 {
+        stations.sort_by_key(|s| (!allowed.contains(&s.id), s.id.0));
+        stations.truncate(MAX_STATIONS);
+    }
+    stations.sort_by_key(|s| s.id.0);
+
+    let pickup = task.source.access_node;
+    let norms = &spec.norms;
+
+    let mut station_feats = vec![0.0f32; MAX_STATIONS * STATION_FEATS];
+    let mut station_mask = vec![false; MAX_STATIONS];
+    let mut candidate_station_ids = Vec::with_capacity(stations.len());
+
+    for (row, station) in stations.iter().enumerate() {
+        let dist = ctx.map.euclidean_distance(pickup, station.node);
+        let concurrency = station.effective_concurrency();
+        let service_s = station.service_time.calculate(task.quantity).as_seconds()
+            * station.service_multiplier(ctx.current_time);
+        let rate_per_min = if service_s > 0.0 {
+            concurrency as f64 * 60.0 / service_s
+        } else {
+            RATE_NORM
+        };
+        let base = row * STATION_FEATS;
+        station_feats[base] = norm(station.queue_length() as f64, norms.queue);
+        station_feats[base + 1] = if concurrency > 0 {
+            norm(station.serving.len() as f64, concurrency as f64)
+        } else {
+            1.0
+        };
+        station_feats[base + 2] = norm(rate_per_min, RATE_NORM);
+        station_feats[base + 3] = norm(dist, norms.distance_m);
+        station_feats[base + 4] = match station.queue_capacity {
+            Some(cap) if cap > 0 => 1.0 - norm(station.queue.len() as f64, cap as f64),
+            Some(_) => 0.0,
+            None => 1.0,
+        };
+        station_feats[base + 5] = if station.is_open() { 1.0 } else { 0.0 };
+        station_mask[row] = allowed.contains(&station.id);
+        candidate_station_ids.push(station.id);
+    }
+
+    let total_queue: usize = stations.iter().map(|s| s.queue_length()).sum();
+    let allowed_frac = allowed.len() as f64 / MAX_STATIONS as f64;
+
+    let graph = spec.graph.then(|| {
+        let nodes: Vec<NodeId> = stations.iter().map(|s| s.node).collect();
+        build_graph_observation(ctx, pickup, &nodes, norms)
+    });
+
+    Some(Observation {
+        kind: DecisionKind::StationAssignment,
+        task_feats: task_features(ctx, task, spec, allowed_frac, total_queue),
+        station_feats,
+        station_mask,
+        candidate_station_ids,
+        truncated_stations,
+        graph,
+        ..Observation::zeros_for(spec)
+    })
+}
+
+/// Task-level features shared by both decision kinds, in `spec` order.
+fn task_features(
+    ctx: &PolicyContext,
+    task: &Task,
+    spec: &ObservationSpec,
+    candidate_frac: f64,
+    station_queue: usize,
+) -> Vec<f32> {
+    let norms = &spec.norms;
+    spec.task_features
+        .iter()
+        .map(|feature| match feature {
+            TaskFeature::DueSlack => due_slack(ctx, task, norms.due_window_min),
+            TaskFeature::Quantity => norm(task.quantity as f64, norms.queue),
+            TaskFeature::Pending => {
+                let pending = ctx.tasks.values().filter(|t| t.is_pending()).count();
+                norm(pending as f64, norms.pending)
+            }
+            TaskFeature::CandidateFraction => candidate_frac as f32,
+            TaskFeature::Phase => {
+                (ctx.current_time.as_seconds() / norms.phase_s).clamp(0.0, 1.0) as f32
+            }
+            TaskFeature::StationQueue => norm(station_queue as f64, norms.queue),
+        })
+        .collect()
+}
+
+/// Shortest-path distance from every node that can reach `target` to it.
+///
+/// One Dijkstra over the reversed graph, so all candidates are served by a
+/// single search.
+fn distances_to(ctx: &PolicyContext, target: NodeId) -> HashMap<NodeId, f64> {
+    let mut reverse: HashMap<NodeId, Vec<(NodeId, f64)>> = HashMap::new();
+    for &from in ctx.map.nodes.keys() {
+        for (to, _, length) in ctx.map.neighbors(from) {
+            reverse.entry(to).or_default().push((from, length));
+        }
+    }
+
+    let mut dist: HashMap<NodeId, f64> = HashMap::new();
+    let mut heap = BinaryHeap::new();
+    dist.insert(target, 0.0);
+    heap.push(Reverse((OrderedDist(0.0), target.0)));
+    while let Some(Reverse((OrderedDist(d), node))) = heap.pop() {
+        let node = NodeId(node);
+        if dist.get(&node).is_some_and(|&best| d > best) {
+            continue;
+        }
+        for &(prev, length) in reverse.get(&node).into_iter().flatten() {
+            let nd = d + length;
+            if dist.get(&prev).is_none_or(|&best| nd < best) {
+                dist.insert(prev, nd);
+                heap.push(Reverse((OrderedDist(nd), prev.0)));
+            }
+        }
+    }
+    dist
+}
+
+/// Total order on non-NaN distances for the Dijkstra heap.
+#[derive(PartialEq)]
+struct OrderedDist(f64);
+
+impl Eq for OrderedDist {}
+
+impl PartialOrd for OrderedDist {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrderedDist {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+/// Other robots at `robot`'s node or at a node one hop away.
+fn local_congestion(ctx: &PolicyContext, robot: &Robot) -> usize {
+    let mut near: HashSet<NodeId> = ctx
+        .map
+        .neighbors(robot.current_node)
+        .map(|(n, _, _)| n)
+        .collect();
+    near.insert(robot.current_node);
+    ctx.robots
+        .values()
+        .filter(|r| r.id != robot.id && near.contains(&r.current_node))
+        .count()
+}
+
+/// One robot agent's view at a joint (multi-agent) decision.
+#[derive(Debug, Clone)]
+pub struct AgentObservation {
+    /// Length `AGENT_FEATS`: the robot's own state.
+    pub agent_feats: Vec<f32>,
+    /// Flattened `MAX_TASKS * AGENT_TASK_FEATS`; row `t` is pending task `t`
+    /// as seen from this robot (or zero-padded).
+    pub task_feats: Vec<f32>,
+    /// Length `MAX_TASKS + 1`; `true` for real task rows. The last entry
+    /// ([`PARK_ACTION`]) is the always-valid "take no task" action.
+    pub action_mask: Vec<bool>,
+}
+
+impl AgentObservation {
+    /// An all-zero view whose only valid action is parking.
+    pub fn zeros() -> Self {
+        let mut action_mask = vec![false; MAX_TASKS + 1];
+        action_mask[PARK_ACTION] = true;
+        Self {
+            agent_feats: vec![0.0; AGENT_FEATS],
+            task_feats: vec![0.0; MAX_TASKS * AGENT_TASK_FEATS],
+            action_mask,
+        }
+    }
+}
+
+/// A joint decision: every available robot picks a task (or parks) at once.
+#[derive(Debug, Clone, Default)]
+pub struct MultiObservation {
+    /// The deciding robots, sorted by `RobotId.0`.
+    pub agents: Vec<RobotId>,
+    /// One view per entry of `agents`, in the same order.
+    pub per_agent: Vec<AgentObservation>,
+    /// Actual `TaskId` for each filled task row, in row order. Shared by all
+    /// agents so an action index means the same task for everyone.
+    pub task_ids: Vec<TaskId>,
+}
+
+/// Build the joint observation for the robots in `agents` over `tasks`, both
+/// already sorted and truncated by the caller.
+///
+/// Returns `None` when there are no agents or none of the tasks exist.
+pub fn build_multi_observation(
+    ctx: &PolicyContext,
+    agents: &[RobotId],
+    tasks: &[TaskId],
+) -> Option<MultiObservation> {
+    let tasks: Vec<&Task> = tasks
+        .iter()
+        .take(MAX_TASKS)
+        .filter_map(|id| ctx.tasks.get(id))
+        .collect();
+    if agents.is_empty() || tasks.is_empty() {
+        return None;
+    }
+
+    let norms = ObsNorms::default();
+    let agent_frac = agents.len() as f64 / MAX_ROBOTS as f64;
+    let per_agent = agents
+        .iter()
+        .map(|robot_id| {
+            let mut obs = AgentObservation::zeros();
+            let Some(robot) = ctx.robots.get(robot_id) else {
+                return obs;
+            };
+            obs.agent_feats = vec![
+                robot.battery.soc as f32,
+                norm(robot.task_queue.len() as f64, norms.queue),
+                norm(robot.tasks_completed as f64, norms.tasks_completed),
+                norm(robot.total_distance, norms.travel_m),
+                if robot.is_repositioning() { 1.0 } else { 0.0 },
+                agent_frac as f32,
+            ];
+
+            let (rx, ry) = node_xy(ctx, robot.current_node);
+            for (row, task) in tasks.iter().enumerate() {
+                let pickup = task.source.access_node;
+                let (px, py) = node_xy(ctx, pickup);
+                let station_queue = ctx
+                    .stations
+                    .get(&task.destination_station)
+                    .map(|s| s.queue_length())
+                    .unwrap_or(0);
+                let base = row * AGENT_TASK_FEATS;
+                obs.task_feats[base] = norm(
+                    ctx.map.euclidean_distance(robot.current_node, pickup),
+                    norms.distance_m,
+                );
+                obs.task_feats[base + 1] = ((px - rx) / norms.distance_m).clamp(-1.0, 1.0) as f32;
+                obs.task_feats[base + 2] = ((py - ry) / norms.distance_m).clamp(-1.0, 1.0) as f32;
+                obs.task_feats[base + 3] = due_slack(ctx, task, norms.due_window_min);
+                obs.task_feats[base + 4] = norm(task.quantity as f64, norms.queue);
+                obs.task_feats[base + 5] = norm(station_queue as f64, norms.queue);
+                obs.action_mask[row] = true;
+            }
+            obs
+        })
+        .collect();
+
+    Some(MultiObservation {
+        agents: agents.to_vec(),
+        per_agent,
+        task_ids: tasks.iter().map(|t| t.id).collect(),
     })
 }
 
+/// Minutes until the task's order is due over `window_min`, clamped to
+/// [-1, 1]; 0 without a due time.
+fn due_slack(ctx: &PolicyContext, task: &Task, window_min: f64) -> f32 {
+    let due = task
+        .order_id
+        .and_then(|oid| ctx.orders.get(&oid))
+        .and_then(|order| order.due_time);
+    match due {
+        Some(due) => {
+            let mins = (due.as_seconds() - ctx.current_time.as_seconds()) / 60.0;
+            (mins / window_min).clamp(-1.0, 1.0) as f32
+        }
+        None => 0.0,
+    }
+}
+
 fn node_xy(ctx: &PolicyContext, node: waremax_core::NodeId) -> (f64, f64) {
     ctx.map.get_node(node).map(|n| (n.x, n.y)).unwrap_or((0.0, 0.0))
 }
 
-fn norm(v: f64, scale: f64) -> f32 {
+pub(crate) fn norm(v: f64, scale: f64) -> f32 {
     (v / scale).clamp(0.0, 1.0) as f32
 }
@@ -1,36 +1,67 @@
-//! The `RlPolicy`: a `TaskAllocationPolicy` whose decisions come from an
-//! external agent over channels.
+//! The RL policies: simulator policies whose decisions come from an external
+//! agent over channels.
 //!
-//! `allocate` builds an observation, computes the reward accrued since the
-//! previous decision, sends both to the agent, and blocks for the chosen
-//! action. Control is inverted without touching the event handler: from the
-//! simulator's perspective this is just another allocation policy.
+//! [`RlPolicy`] is a `TaskAllocationPolicy` and [`RlStationPolicy`] a
+//! `StationAssignmentPolicy`. At each decision they build an observation, hand
+//! it to a shared [`DecisionChannel`] together with the reward accrued since
+//! the previous decision (of either kind), and block for the chosen action.
+//! Control is inverted without touching the event handler: from the
+//! simulator's perspective these are just other policies.
+//!
+//! [`MultiAgentPolicy`] is the decentralized variant: at each dispatch round
+//! every available robot is an agent, and all of them choose a pending task
+//! (or park) in one joint decision.
+//!
+//! Cloning an RL policy shares its channel, unless the clone is made through
+//! [`DecisionChannel::bind_clone`]: that is how an env checkpoints a whole
+//! `SimulationRunner` and later resumes it on a channel of its own.
 
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 
 use crossbeam_channel::{Receiver, Sender};
-use waremax_core::{RobotId, TaskId};
-use waremax_policies::{PolicyContext, TaskAllocationPolicy};
+use waremax_core::{RobotId, SimTime, StationId, TaskId};
+use waremax_entities::Task;
+use waremax_policies::{PolicyContext, StationAssignmentPolicy, TaskAllocationPolicy};
 
-use crate::observation::build_observation;
-use crate::protocol::{ActionMsg, Message, StepInfo};
+use crate::obs_spec::ObservationSpec;
+use crate::observation::{
+    build_multi_observation, build_observation, build_station_observation, Observation, MAX_ROBOTS,
+    MAX_TASKS,
+};
+use crate::protocol::{ActionMsg, JointAction, Message, MultiMessage, StepInfo};
 use crate::reward::{self, RewardConfig, RewardMode, RewardSnapshot};
 
-/// Allocation policy driven by an external agent over channels.
-pub struct RlPolicy {
+/// Worker side of the agent handshake, shared by every RL decision point of
+/// one episode so each reward is the delta since the previous decision.
+pub struct DecisionChannel {
     obs_tx: Sender<Message>,
     action_rx: Receiver<ActionMsg>,
     reward_cfg: RewardConfig,
     /// Shared with the env so the terminal reward delta can be computed against
     /// the last decision's snapshot. Interior mutability is required because the
-    /// trait method takes `&self` and the trait is `Sync`.
+    /// policy trait methods take `&self` and the traits are `Sync`.
     snapshot: Arc<Mutex<RewardSnapshot>>,
     /// Routed mode only: the controllable cost of the *previous* decision,
     /// charged to it by emitting it with the reward returned for that action.
     pending_decision_cost: Mutex<f32>,
+    /// Layout of the observations sent over this channel.
+    spec: ObservationSpec,
+    /// Decisions sent so far in the episode, across branches.
+    decisions: AtomicU64,
+    /// Set once the env aborts the episode.
+    aborted: AtomicBool,
 }
 
-impl RlPolicy {
+thread_local! {
+    /// Channel that RL policies cloned on this thread bind to, set for the
+    /// duration of [`DecisionChannel::bind_clone`].
+    static BIND_TARGET: RefCell<Option<Arc<DecisionChannel>>> = const { RefCell::new(None) };
+}
+
+impl DecisionChannel {
     pub fn new(
         obs_tx: Sender<Message>,
         action_rx: Receiver<ActionMsg>,
@@ -43,16 +74,186 @@ impl RlPolicy {
             reward_cfg,
             snapshot,
             pending_decision_cost: Mutex::new(0.0),
+            spec: ObservationSpec::default(),
+            decisions: AtomicU64::new(0),
+            aborted: AtomicBool::new(false),
+        }
+    }
+
+    /// A channel over `obs_tx` / `action_rx` that carries on from this one's
+    /// reward state and decision count, sharing nothing with it.
+    pub fn branch(&self, obs_tx: Sender<Message>, action_rx: Receiver<ActionMsg>) -> Self {
+        Self {
+            obs_tx,
+            action_rx,
+            reward_cfg: self.reward_cfg.clone(),
+            snapshot: Arc::new(Mutex::new(self.snapshot.lock().unwrap().clone())),
+            pending_decision_cost: Mutex::new(*self.pending_decision_cost.lock().unwrap()),
+            spec: self.spec.clone(),
+            decisions: AtomicU64::new(self.decisions()),
+            aborted: AtomicBool::new(false),
+        }
+    }
+
+    /// Clone `value` (a `World`, a `SimulationRunner`, ...) with every RL
+    /// policy in it bound to `channel` instead of the channel it was using.
+    pub fn bind_clone<T: Clone>(value: &T, channel: &Arc<DecisionChannel>) -> T {
+        BIND_TARGET.with(|target| *target.borrow_mut() = Some(channel.clone()));
+        let clone = value.clone();
+        BIND_TARGET.with(|target| *target.borrow_mut() = None);
+        clone
+    }
+
+    /// The channel a clone of a policy on `channel` uses.
+    fn for_clone(channel: &Arc<DecisionChannel>) -> Arc<DecisionChannel> {
+        BIND_TARGET
+            .with(|target| target.borrow().clone())
+            .unwrap_or_else(|| channel.clone())
+    }
+
+    /// Decisions sent so far in the episode.
+    pub fn decisions(&self) -> u64 {
+        self.decisions.load(Ordering::Relaxed)
+    }
+
+    /// Whether the env has aborted the episode (or gone away).
+    pub fn is_aborted(&self) -> bool {
+        self.aborted.load(Ordering::Relaxed)
+    }
+
+    /// The reward snapshot taken at the latest decision.
+    pub fn reward_snapshot(&self) -> RewardSnapshot {
+        self.snapshot.lock().unwrap().clone()
+    }
+
+    /// Lay out observations by `spec` instead of the default layout.
+    pub fn with_observation_spec(mut self, spec: ObservationSpec) -> Self {
+        self.spec = spec;
+        self
+    }
+
+    /// The observation layout of this channel.
+    pub fn observation_spec(&self) -> &ObservationSpec {
+        &self.spec
+    }
+
+    /// Send `obs` with the reward accrued since the previous decision and block
+    /// for the agent's action. A dropped env reads as `Abort`.
+    fn decide(&self, ctx: &PolicyContext, obs: Observation) -> ActionMsg {
+        // Reward = global delta since the previous decision; then advance the
+        // snapshot. In routed mode, subtract the previous decision's own
+        // controllable cost (charged to the action that incurred it); a custom
+        // spec weights that cost as its `decision_cost` term.
+        let cur = reward::snapshot_from(ctx.orders, ctx.tasks, ctx.attribution)
+            .with_fleet(ctx.robots, ctx.traffic);
+        let (reward, info) = {
+            let mut prev = self.snapshot.lock().unwrap();
+            let cost = *self.pending_decision_cost.lock().unwrap();
+            let mut info = step_info(&prev, &cur, ctx.current_time);
+            let reward = if self.reward_cfg.mode == RewardMode::Custom {
+                info.reward_terms = self.reward_cfg.spec.contributions(&prev, &cur, cost);
+                info.reward_terms.iter().map(|(_, c)| c).sum()
+            } else {
+                reward::delta(&prev, &cur, &self.reward_cfg)
+                    - self.reward_cfg.decision_penalty(cost)
+            };
+            *prev = cur;
+            (reward, info)
+        };
+
+        // Hand the decision to the agent and block for its action. A send/recv
+        // error means the env was dropped mid-episode: bail out gracefully.
+        self.decisions.fetch_add(1, Ordering::Relaxed);
+        let action = if self
+            .obs_tx
+            .send(Message::Decision { obs, reward, info })
+            .is_err()
+        {
+            ActionMsg::Abort
+        } else {
+            self.action_rx.recv().unwrap_or(ActionMsg::Abort)
+        };
+        if action == ActionMsg::Abort {
+            self.aborted.store(true, Ordering::Relaxed);
+        }
+        action
+    }
+
+    /// Routed mode (or a custom spec with `decision_cost`): stash the
+    /// controllable cost of the decision just made so it is charged to that
+    /// action on the next reward emission.
+    fn charge(&self, cost: impl FnOnce(&RewardConfig) -> f32) {
+        if self.reward_cfg.charges_decisions() {
+            *self.pending_decision_cost.lock().unwrap() = cost(&self.reward_cfg);
+        }
+    }
+}
+
+/// Diagnostic info for the step between two reward snapshots.
+fn step_info(prev: &RewardSnapshot, cur: &RewardSnapshot, now: SimTime) -> StepInfo {
+    StepInfo {
+        completed_delta: cur.completed as i64 - prev.completed as i64,
+        late_delta: cur.late as i64 - prev.late as i64,
+        lateness_delta_s: cur.cum_lateness_s - prev.cum_lateness_s,
+        pending: cur.pending,
+        sim_time_s: now.as_seconds(),
+        final_metrics: None,
+        errored: false,
+        reward_terms: Vec::new(),
+    }
+}
+
+/// Allocation policy driven by an external agent over channels.
+///
+/// With an expert attached, every observation carries the expert's choice in
+/// `expert_action`, and `ActionMsg::Expert` applies it.
+pub struct RlPolicy {
+    channel: Arc<DecisionChannel>,
+    expert: Option<Box<dyn TaskAllocationPolicy>>,
+}
+
+impl Clone for RlPolicy {
+    fn clone(&self) -> Self {
+        Self {
+            channel: DecisionChannel::for_clone(&self.channel),
+            expert: self.expert.clone(),
         }
     }
+}
+
+impl RlPolicy {
+    pub fn new(
+        obs_tx: Sender<Message>,
+        action_rx: Receiver<ActionMsg>,
+        reward_cfg: RewardConfig,
+        snapshot: Arc<Mutex<RewardSnapshot>>,
+    ) -> Self {
+        Self::with_channel(Arc::new(DecisionChannel::new(
+            obs_tx, action_rx, reward_cfg, snapshot,
+        )))
+    }
+
+    /// Share a channel with other RL decision points of the same episode.
+    pub fn with_channel(channel: Arc<DecisionChannel>) -> Self {
+        Self {
+            channel,
+            expert: None,
+        }
+    }
+
+    /// Consult `expert` (a heuristic allocation policy) at every decision.
+    pub fn with_expert(mut self, expert: Box<dyn TaskAllocationPolicy>) -> Self {
+        self.expert = Some(expert);
+        self
+    }
 
     /// Controllable cost of assigning `chosen` to `task_id`: estimated
     /// travel-to-pickup time plus the robot's current backlog. Zero for a no-op.
     fn routed_decision_cost(
-        &self,
         ctx: &PolicyContext,
         task_id: TaskId,
         chosen: Option<RobotId>,
+        cfg: &RewardConfig,
     ) -> f32 {
         let (Some(robot_id), Some(task)) = (chosen, ctx.tasks.get(&task_id)) else {
             return 0.0;
@@ -65,65 +266,315 @@ impl RlPolicy {
             .euclidean_distance(robot.current_node, task.source.access_node);
         let travel_min = (dist / robot.max_speed_mps.max(0.1)) / 60.0;
         let backlog = robot.task_queue.len() as f64;
-        reward::decision_cost(travel_min, backlog, &self.reward_cfg)
+        reward::decision_cost(travel_min, backlog, cfg)
     }
 }
 
 impl TaskAllocationPolicy for RlPolicy {
     fn allocate(&self, ctx: &PolicyContext, task_id: TaskId) -> Option<RobotId> {
         // No available candidates => no decision; never consult the agent.
-        let obs = build_observation(ctx, task_id)?;
+        let mut obs = build_observation(ctx, task_id, &self.channel.spec)?;
         let candidate_ids = obs.candidate_robot_ids.clone();
 
-        // Reward = global delta since the previous decision; then advance the
-        // snapshot. In routed mode, subtract the previous decision's own
-        // controllable cost (charged to the action that incurred it).
-        let cur = reward::snapshot_from(ctx.orders, ctx.tasks, ctx.attribution);
-        let (reward, info) = {
+        let expert_choice = self
+            .expert
+            .as_ref()
+            .and_then(|expert| expert.allocate(ctx, task_id));
+        obs.expert_action =
+            expert_choice.and_then(|id| candidate_ids.iter().position(|&c| c == id));
+
+        let chosen = match self.channel.decide(ctx, obs) {
+            ActionMsg::Choose(i) => candidate_ids.get(i).copied(),
+            ActionMsg::Expert => expert_choice,
+            ActionMsg::NoOp | ActionMsg::Abort => None,
+        };
+
+        self.channel
+            .charge(|cfg| Self::routed_decision_cost(ctx, task_id, chosen, cfg));
+
+        chosen
+    }
+
+    fn name(&self) -> &'static str {
+        "rl_agent"
+    }
+}
+
+/// Station assignment policy driven by an external agent over channels.
+///
+/// A no-op, an expert request, an abort, or a masked row defers to the
+/// scenario's own station policy, so an order line is never dropped for lack of a station.
+pub struct RlStationPolicy {
+    channel: Arc<DecisionChannel>,
+    fallback: Box<dyn StationAssignmentPolicy>,
+}
+
+impl Clone for RlStationPolicy {
+    fn clone(&self) -> Self {
+        Self {
+            channel: DecisionChannel::for_clone(&self.channel),
+            fallback: self.fallback.clone(),
+        }
+    }
+}
+
+impl RlStationPolicy {
+    pub fn new(channel: Arc<DecisionChannel>, fallback: Box<dyn StationAssignmentPolicy>) -> Self {
+        Self { channel, fallback }
+    }
+
+    /// Controllable cost of sending `task` to `chosen`: estimated
+    /// bin-to-station travel plus the work already waiting there. Zero when the
+    /// fallback policy decided.
+    fn routed_decision_cost(
+        ctx: &PolicyContext,
+        task: &Task,
+        chosen: Option<StationId>,
+        cfg: &RewardConfig,
+    ) -> f32 {
+        let Some(station) = chosen.and_then(|id| ctx.stations.get(&id)) else {
+            return 0.0;
+        };
+        let speeds: Vec<f64> = ctx.robots.values().map(|r| r.max_speed_mps).collect();
+        let speed = speeds.iter().sum::<f64>() / speeds.len().max(1) as f64;
+        let dist = ctx
+            .map
+            .euclidean_distance(task.source.access_node, station.node);
+        let travel_min = (dist / speed.max(0.1)) / 60.0;
+        let backlog = (station.queue_length() + station.serving.len()) as f64;
+        reward::decision_cost(travel_min, backlog, cfg)
+    }
+}
+
+impl StationAssignmentPolicy for RlStationPolicy {
+    fn assign(&self, ctx: &PolicyContext, task: &Task) -> Option<StationId> {
+        // No selectable station => let the scenario's policy handle it.
+        let Some(obs) = build_station_observation(ctx, task, &self.channel.spec) else {
+            return self.fallback.assign(ctx, task);
+        };
+        let candidate_ids = obs.candidate_station_ids.clone();
+        let mask = obs.station_mask.clone();
+
+        let chosen = match self.channel.decide(ctx, obs) {
+            ActionMsg::Choose(i) if mask.get(i).copied().unwrap_or(false) => {
+                candidate_ids.get(i).copied()
+            }
+            _ => None,
+        };
+
+        self.channel
+            .charge(|cfg| Self::routed_decision_cost(ctx, task, chosen, cfg));
+
+        chosen.or_else(|| self.fallback.assign(ctx, task))
+    }
+
+    fn name(&self) -> &'static str {
+        "rl_agent"
+    }
+}
+
+/// Per-robot reward bookkeeping for a multi-agent episode.
+///
+/// Every robot accrues the shared team reward between its own decisions and is
+/// paid it (less its routed cost, if any) when it next decides — or at the
+/// terminal step. Shared with the env so the terminal can settle all robots.
+#[derive(Debug, Default)]
+pub struct AgentCredit {
+    accrued: HashMap<RobotId, f32>,
+    pending_cost: HashMap<RobotId, f32>,
+    conflicts: usize,
+}
+
+impl AgentCredit {
+    /// Add `team_reward` to every robot in `fleet`.
+    pub fn accrue(&mut self, fleet: impl IntoIterator<Item = RobotId>, team_reward: f32) {
+        for robot_id in fleet {
+            *self.accrued.entry(robot_id).or_insert(0.0) += team_reward;
+        }
+    }
+
+    /// Pay out and clear `robot_id`'s outstanding reward.
+    pub fn settle(&mut self, robot_id: RobotId) -> f32 {
+        self.accrued.remove(&robot_id).unwrap_or(0.0)
+            - self.pending_cost.remove(&robot_id).unwrap_or(0.0)
+    }
+
+    /// Conflicts since the last call.
+    pub fn take_conflicts(&mut self) -> usize {
+        std::mem::take(&mut self.conflicts)
+    }
+}
+
+/// The joint decisions made at one `SimTime`.
+#[derive(Clone, Default)]
+struct JointRound {
+    time: Option<SimTime>,
+    /// Tasks already offered to the agents at this time.
+    offered: HashSet<TaskId>,
+    /// Task -> robot choices that won their task.
+    plan: HashMap<TaskId, RobotId>,
+    /// Robots that already hold a task this round.
+    claimed: HashSet<RobotId>,
+}
+
+/// Multi-agent allocation: each available robot picks a pending task (or
+/// parks), with every robot free at the same `SimTime` deciding jointly.
+///
+/// Plugged in as the task-allocation policy: the first `allocate` call of a
+/// dispatch round emits one joint decision and caches the resulting plan; the
+/// handler's remaining `allocate` calls at that time read from the plan. When
+/// two robots pick the same task the lower id wins and the other parks.
+pub struct MultiAgentPolicy {
+    obs_tx: Sender<MultiMessage>,
+    action_rx: Receiver<JointAction>,
+    reward_cfg: RewardConfig,
+    snapshot: Arc<Mutex<RewardSnapshot>>,
+    credit: Arc<Mutex<AgentCredit>>,
+    round: Mutex<JointRound>,
+}
+
+impl Clone for MultiAgentPolicy {
+    /// A clone talks to the same agents and shares their reward bookkeeping.
+    fn clone(&self) -> Self {
+        Self {
+            obs_tx: self.obs_tx.clone(),
+            action_rx: self.action_rx.clone(),
+            reward_cfg: self.reward_cfg.clone(),
+            snapshot: self.snapshot.clone(),
+            credit: self.credit.clone(),
+            round: Mutex::new(self.round.lock().unwrap().clone()),
+        }
+    }
+}
+
+impl MultiAgentPolicy {
+    pub fn new(
+        obs_tx: Sender<MultiMessage>,
+        action_rx: Receiver<JointAction>,
+        reward_cfg: RewardConfig,
+        snapshot: Arc<Mutex<RewardSnapshot>>,
+        credit: Arc<Mutex<AgentCredit>>,
+    ) -> Self {
+        Self {
+            obs_tx,
+            action_rx,
+            reward_cfg,
+            snapshot,
+            credit,
+            round: Mutex::new(JointRound::default()),
+        }
+    }
+
+    /// Emit one joint decision over `agents` and `tasks` and record the
+    /// winning choices in `round`.
+    fn decide(
+        &self,
+        ctx: &PolicyContext,
+        agents: &[RobotId],
+        tasks: &[TaskId],
+        round: &mut JointRound,
+    ) {
+        let Some(obs) = build_multi_observation(ctx, agents, tasks) else {
+            return;
+        };
+        round.offered.extend(obs.task_ids.iter().copied());
+
+        let cur = reward::snapshot_from(ctx.orders, ctx.tasks, ctx.attribution)
+            .with_fleet(ctx.robots, ctx.traffic);
+        let (rewards, info, conflicts) = {
             let mut prev = self.snapshot.lock().unwrap();
-            let mut reward = reward::delta(&prev, &cur, &self.reward_cfg);
-            if self.reward_cfg.mode == RewardMode::Routed {
-                reward -= *self.pending_decision_cost.lock().unwrap();
+            let team_reward = reward::delta(&prev, &cur, &self.reward_cfg);
+            let mut info = step_info(&prev, &cur, ctx.current_time);
+            if self.reward_cfg.mode == RewardMode::Custom {
+                info.reward_terms = self.reward_cfg.spec.contributions(&prev, &cur, 0.0);
             }
-            let info = StepInfo {
-                completed_delta: cur.completed as i64 - prev.completed as i64,
-                late_delta: cur.late as i64 - prev.late as i64,
-                lateness_delta_s: cur.cum_lateness_s - prev.cum_lateness_s,
-                pending: cur.pending,
-                sim_time_s: ctx.current_time.as_seconds(),
-                final_metrics: None,
-                errored: false,
-            };
             *prev = cur;
-            (reward, info)
+
+            let mut credit = self.credit.lock().unwrap();
+            credit.accrue(ctx.robots.keys().copied(), team_reward);
+            let rewards = obs.agents.iter().map(|&r| credit.settle(r)).collect();
+            (rewards, info, credit.take_conflicts())
         };
 
-        // Hand the decision to the agent and block for its action. A send/recv
-        // error means the env was dropped mid-episode: bail out gracefully.
-        if self
-            .obs_tx
-            .send(Message::Decision { obs, reward, info })
-            .is_err()
-        {
-            return None;
+        let task_ids = obs.task_ids.clone();
+        let message = MultiMessage::Decision {
+            obs,
+            rewards,
+            info,
+            conflicts,
+        };
+        // A send/recv error means the env was dropped mid-episode: every robot
+        // parks.
+        let joint = if self.obs_tx.send(message).is_err() {
+            JointAction::Abort
+        } else {
+            self.action_rx.recv().unwrap_or(JointAction::Abort)
+        };
+
+        // Resolve in robot-id order so the lower id wins a contested task.
+        let mut credit = self.credit.lock().unwrap();
+        for (i, &robot_id) in agents.iter().enumerate() {
+            let Some(&task_id) = joint.get(i).and_then(|a| task_ids.get(a)) else {
+                continue;
+            };
+            if round.plan.contains_key(&task_id) {
+                credit.conflicts += 1;
+                continue;
+            }
+            round.plan.insert(task_id, robot_id);
+            round.claimed.insert(robot_id);
+            if self.reward_cfg.charges_decisions() {
+                let cost =
+                    RlPolicy::routed_decision_cost(ctx, task_id, Some(robot_id), &self.reward_cfg);
+                credit
+                    .pending_cost
+                    .insert(robot_id, self.reward_cfg.decision_penalty(cost));
+            }
         }
+    }
+}
 
-        let chosen = match self.action_rx.recv() {
-            Ok(ActionMsg::Choose(i)) => candidate_ids.get(i).copied(),
-            Ok(ActionMsg::NoOp) | Ok(ActionMsg::Abort) | Err(_) => None,
-        };
+impl TaskAllocationPolicy for MultiAgentPolicy {
+    fn allocate(&self, ctx: &PolicyContext, task_id: TaskId) -> Option<RobotId> {
+        let mut round = self.round.lock().unwrap();
+        if round.time != Some(ctx.current_time) {
+            *round = JointRound {
+                time: Some(ctx.current_time),
+                ..Default::default()
+            };
+        }
 
-        // Routed mode: compute the controllable cost of THIS assignment and
-        // stash it so it is charged to this action on the next reward emission.
-        if self.reward_cfg.mode == RewardMode::Routed {
-            let cost = self.routed_decision_cost(ctx, task_id, chosen);
-            *self.pending_decision_cost.lock().unwrap() = cost;
+        // Offer unseen tasks (in id order, MAX_TASKS at a time) to the robots
+        // still free until this one has been offered or nobody is left.
+        while !round.offered.contains(&task_id) {
+            let mut agents: Vec<RobotId> = ctx
+                .robots
+                .values()
+                .filter(|r| r.is_available() && !round.claimed.contains(&r.id))
+                .map(|r| r.id)
+                .collect();
+            agents.sort_by_key(|r| r.0);
+            agents.truncate(MAX_ROBOTS);
+
+            let mut tasks: Vec<TaskId> = ctx
+                .tasks
+                .values()
+                .filter(|t| t.is_pending() && !round.offered.contains(&t.id))
+                .map(|t| t.id)
+                .collect();
+            tasks.sort_by_key(|t| t.0);
+            tasks.truncate(MAX_TASKS);
+
+            if agents.is_empty() || tasks.is_empty() {
+                break;
+            }
+            self.decide(ctx, &agents, &tasks, &mut round);
         }
 
-        chosen
+        round.plan.get(&task_id).copied()
     }
 
     fn name(&self) -> &'static str {
-        "rl_agent"
+        "rl_multi_agent"
     }
 }
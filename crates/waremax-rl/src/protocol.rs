@@ -5,7 +5,7 @@
 //! channels enforce a strict ping-pong handshake so exactly one side runs at a
 //! time — which is what preserves determinism across the boundary.
 
-use crate::observation::Observation;
+use crate::observation::{MultiObservation, Observation};
 
 /// Per-step diagnostic info (mirrors the reward breakdown; also carries final
 /// metrics on the terminal message).
@@ -20,6 +20,9 @@ pub struct StepInfo {
     pub final_metrics: Option<FinalMetrics>,
     /// True if the episode ended because the simulation worker panicked.
     pub errored: bool,
+    /// Contribution of each term of a custom reward, by term name (empty for
+    /// the built-in modes).
+    pub reward_terms: Vec<(&'static str, f32)>,
 }
 
 /// End-of-episode summary metrics, surfaced to the agent in `info`.
@@ -54,12 +57,62 @@ pub enum Message {
 }
 
 /// A message from the agent to the simulation worker.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ActionMsg {
-    /// Choose the candidate at this index into `Observation::candidate_robot_ids`.
+    /// Choose the candidate at this index into `Observation::candidate_robot_ids`,
+    /// or into `Observation::candidate_station_ids` for a station decision.
     Choose(usize),
-    /// Explicitly allocate no robot this round (`allocate` returns `None`).
+    /// Explicitly allocate no robot this round (`allocate` returns `None`); for a
+    /// station decision, defer to the scenario's station policy.
     NoOp,
+    /// Follow the expert's choice (see `RlEnv::with_expert`); a no-op when the
+    /// env has no expert. For a station decision, defer to the scenario's
+    /// station policy.
+    Expert,
     /// Abort the in-flight episode (used when resetting early); worker drains and exits.
     Abort,
 }
+
+/// A message from the simulation worker to the agents of a multi-agent episode.
+#[derive(Debug, Clone)]
+pub enum MultiMessage {
+    /// A joint decision is required from every robot in `obs.agents`.
+    Decision {
+        obs: MultiObservation,
+        /// One reward per entry of `obs.agents`: the team reward accrued since
+        /// that robot's previous decision (less its own routed cost).
+        rewards: Vec<f32>,
+        info: StepInfo,
+        /// Task choices of the previous joint action that lost to a
+        /// lower-id robot picking the same task.
+        conflicts: usize,
+    },
+    /// The episode has finished. `obs.agents` lists the whole fleet and
+    /// `rewards` settles every robot's outstanding credit.
+    Terminal {
+        obs: MultiObservation,
+        rewards: Vec<f32>,
+        info: StepInfo,
+        conflicts: usize,
+    },
+}
+
+/// A joint action from the agents of a multi-agent episode.
+#[derive(Debug, Clone)]
+pub enum JointAction {
+    /// One action per deciding robot, in `MultiObservation::agents` order: an
+    /// index into `MultiObservation::task_ids`, or `PARK_ACTION`.
+    Act(Vec<usize>),
+    /// Abort the in-flight episode; worker drains and exits.
+    Abort,
+}
+
+impl JointAction {
+    /// The action chosen by the robot in position `i` (parks if absent).
+    pub fn get(&self, i: usize) -> Option<usize> {
+        match self {
+            Self::Act(actions) => actions.get(i).copied(),
+            Self::Abort => None,
+        }
+    }
+}
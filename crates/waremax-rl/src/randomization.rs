@@ -0,0 +1,234 @@
+//! Domain randomization: a distribution over scenarios sampled at every reset.
+//!
+//! A [`ScenarioDistribution`] picks a base template and draws robot count,
+//! order rate, pick station count, due-time tightness and failure MTBF from
+//! ranges. The draw is a pure function of the episode seed and the curriculum
+//! progress, so an episode (and any fork or replay of it) is reproducible.
+//!
+//! A [`Curriculum`] starts every range narrowed around its midpoint (and only
+//! the first templates open) and widens them linearly to their full extent
+//! over a number of episodes.
+
+use waremax_config::{DueTimeConfig, ScenarioConfig};
+use waremax_core::SimRng;
+
+/// Salt mixed into the episode seed so the sampler's draws are unrelated to
+/// the simulation's own RNG streams.
+const SAMPLER_SALT: u64 = 0xD0_3A1D_5CE7_A210;
+
+/// An inclusive range, sampled uniformly. Integer parameters round the draw.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParamRange {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl ParamRange {
+    pub fn new(a: f64, b: f64) -> Self {
+        Self {
+            min: a.min(b),
+            max: a.max(b),
+        }
+    }
+
+    /// A range holding a single value.
+    pub fn fixed(value: f64) -> Self {
+        Self::new(value, value)
+    }
+
+    /// The range shrunk around its midpoint to `width` (0..=1) of its span.
+    pub fn narrowed(&self, width: f64) -> Self {
+        let mid = (self.min + self.max) / 2.0;
+        let half = (self.max - self.min) / 2.0 * width.clamp(0.0, 1.0);
+        Self::new(mid - half, mid + half)
+    }
+
+    fn sample(&self, rng: &mut SimRng) -> f64 {
+        if self.max > self.min {
+            rng.gen_range(self.min..=self.max)
+        } else {
+            self.min
+        }
+    }
+
+    fn sample_u32(&self, rng: &mut SimRng) -> u32 {
+        let lo = self.min.round().max(0.0) as u32;
+        let hi = self.max.round().max(0.0) as u32;
+        if hi > lo {
+            rng.gen_range(lo..=hi)
+        } else {
+            lo
+        }
+    }
+}
+
+/// Widens a distribution's ranges as training progresses.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Curriculum {
+    /// Episodes until every range reaches its full width.
+    pub episodes: u64,
+    /// Fraction of each range (and of the template list) open at the start.
+    pub initial_width: f64,
+}
+
+impl Curriculum {
+    /// Widen from the midpoints to full ranges over `episodes` episodes.
+    pub fn new(episodes: u64) -> Self {
+        Self {
+            episodes,
+            initial_width: 0.0,
+        }
+    }
+
+    pub fn with_initial_width(mut self, width: f64) -> Self {
+        self.initial_width = width.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Progress (0..=1) after `episode` episodes.
+    pub fn progress(&self, episode: u64) -> f64 {
+        if self.episodes == 0 {
+            1.0
+        } else {
+            (episode as f64 / self.episodes as f64).min(1.0)
+        }
+    }
+
+    /// Fraction of each range open at `progress`.
+    pub fn width(&self, progress: f64) -> f64 {
+        self.initial_width + (1.0 - self.initial_width) * progress.clamp(0.0, 1.0)
+    }
+}
+
+/// Per-episode scenario randomization. Unset parameters keep the template's
+/// value.
+#[derive(Debug, Clone, Default)]
+pub struct ScenarioDistribution {
+    /// Base scenarios (map layout, presets), one chosen per episode. Under a
+    /// curriculum they open in order, so list the easiest first. Empty means
+    /// the env's own scenario.
+    pub templates: Vec<ScenarioConfig>,
+    pub robots: Option<ParamRange>,
+    /// Orders per minute.
+    pub order_rate: Option<ParamRange>,
+    /// Pick stations.
+    pub stations: Option<ParamRange>,
+    /// Fixed due time after arrival, in minutes.
+    pub due_time_minutes: Option<ParamRange>,
+    /// Robot mean time between failures, in hours; setting it enables
+    /// failures.
+    pub failure_mtbf_hours: Option<ParamRange>,
+    pub curriculum: Option<Curriculum>,
+}
+
+impl ScenarioDistribution {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_templates(mut self, templates: Vec<ScenarioConfig>) -> Self {
+        self.templates = templates;
+        self
+    }
+
+    pub fn with_robots(mut self, min: u32, max: u32) -> Self {
+        self.robots = Some(ParamRange::new(min as f64, max as f64));
+        self
+    }
+
+    pub fn with_order_rate(mut self, min: f64, max: f64) -> Self {
+        self.order_rate = Some(ParamRange::new(min, max));
+        self
+    }
+
+    pub fn with_stations(mut self, min: u32, max: u32) -> Self {
+        self.stations = Some(ParamRange::new(min as f64, max as f64));
+        self
+    }
+
+    pub fn with_due_time_minutes(mut self, min: f64, max: f64) -> Self {
+        self.due_time_minutes = Some(ParamRange::new(min, max));
+        self
+    }
+
+    pub fn with_failure_mtbf_hours(mut self, min: f64, max: f64) -> Self {
+        self.failure_mtbf_hours = Some(ParamRange::new(min, max));
+        self
+    }
+
+    pub fn with_curriculum(mut self, curriculum: Curriculum) -> Self {
+        self.curriculum = Some(curriculum);
+        self
+    }
+
+    /// Curriculum progress after `episode` episodes (1.0 without a
+    /// curriculum).
+    pub fn progress(&self, episode: u64) -> f64 {
+        self.curriculum.map_or(1.0, |c| c.progress(episode))
+    }
+
+    /// Draw the scenario of the episode seeded with `seed` at curriculum
+    /// `progress`. `base` stands in for an empty template list. The result
+    /// carries `seed`.
+    pub fn sample(&self, base: &ScenarioConfig, seed: u64, progress: f64) -> ScenarioConfig {
+        let width = self.curriculum.map_or(1.0, |c| c.width(progress));
+        let mut rng = SimRng::new(seed ^ SAMPLER_SALT);
+
+        let mut scenario = if self.templates.is_empty() {
+            base.clone()
+        } else {
+            let open = ((self.templates.len() as f64 * width).ceil() as usize)
+                .clamp(1, self.templates.len());
+            let pick = if open > 1 { rng.gen_range(0..open) } else { 0 };
+            self.templates[pick].clone()
+        };
+        scenario.seed = seed;
+
+        if let Some(r) = self.robots {
+            scenario.robots.count = r.narrowed(width).sample_u32(&mut rng).max(1);
+        }
+        if let Some(r) = self.order_rate {
+            scenario.orders.arrival_process.rate_per_min = r.narrowed(width).sample(&mut rng);
+        }
+        if let Some(r) = self.stations {
+            let n = r.narrowed(width).sample_u32(&mut rng).max(1);
+            resize_stations(&mut scenario, n as usize);
+        }
+        if let Some(r) = self.due_time_minutes {
+            scenario.orders.due_times = Some(DueTimeConfig {
+                due_type: "fixed".to_string(),
+                minutes: r.narrowed(width).sample(&mut rng),
+            });
+        }
+        if let Some(r) = self.failure_mtbf_hours {
+            scenario.robots.failure.enabled = true;
+            scenario.robots.failure.mtbf_hours = r.narrowed(width).sample(&mut rng);
+        }
+        scenario
+    }
+}
+
+/// Drop trailing stations, or add copies of the last one. On generated maps
+/// station `i` sits at node `i` (as `ScenarioBuilder` places them); on map
+/// files the existing station nodes are reused in turn.
+fn resize_stations(scenario: &mut ScenarioConfig, n: usize) {
+    let existing = scenario.stations.len();
+    if existing == 0 || n == existing {
+        return;
+    }
+    if n < existing {
+        scenario.stations.truncate(n);
+        return;
+    }
+    let inline = scenario.map.file == "inline";
+    for i in existing..n {
+        let mut station = scenario.stations[existing - 1].clone();
+        station.id = format!("S{}", i);
+        station.node = if inline {
+            i.to_string()
+        } else {
+            scenario.stations[i % existing].node.clone()
+        };
+        scenario.stations.push(station);
+    }
+}
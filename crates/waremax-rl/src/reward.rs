@@ -12,11 +12,16 @@
 //!   attributed* delay components an allocation decision actually influences
 //!   (assignment/queue/congestion "waste" and travel-to-pickup), using the
 //!   simulator's per-task delay attribution, instead of a flat lateness penalty.
+//!
+//! [`RewardMode::Custom`] replaces the fixed formulas with a user-composed
+//! [`RewardSpec`]: a weighted, optionally clipped sum of named [`RewardTerm`]s,
+//! each of whose contributions is reported in `StepInfo::reward_terms`.
 
 use std::collections::HashMap;
 use waremax_analysis::{AttributionCollector, DelayCategory};
-use waremax_core::{OrderId, TaskId};
-use waremax_entities::{Order, Task};
+use waremax_core::{OrderId, RobotId, TaskId};
+use waremax_entities::{Order, Robot, Task};
+use waremax_map::TrafficManager;
 
 /// Which reward signal to use.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -32,9 +37,12 @@ pub enum RewardMode {
     /// (estimated travel-to-pickup + the chosen robot's backlog) is charged to
     /// the exact decision that made it, rather than smeared across completions.
     Routed,
+    /// The weighted terms of `RewardConfig::spec`.
+    Custom,
 }
 
 impl RewardMode {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "sparse" => Some(Self::Sparse),
@@ -42,16 +50,268 @@ impl RewardMode {
             "attribution" => Some(Self::Attribution),
             "attribution_full" => Some(Self::AttributionFull),
             "routed" => Some(Self::Routed),
+            "custom" => Some(Self::Custom),
             _ => None,
         }
     }
 
-    /// Whether this mode needs the attribution collector enabled.
+    /// Whether this mode needs the attribution collector enabled (for
+    /// `Custom`, see [`RewardConfig::needs_attribution`]).
     pub fn needs_attribution(self) -> bool {
         matches!(self, Self::Attribution | Self::AttributionFull)
     }
 }
 
+/// Delay categories in snapshot order.
+const DELAY_CATEGORIES: [DelayCategory; 9] = [
+    DelayCategory::RobotAssignment,
+    DelayCategory::TravelToPickup,
+    DelayCategory::TravelToStation,
+    DelayCategory::CongestionWait,
+    DelayCategory::StationQueue,
+    DelayCategory::StationService,
+    DelayCategory::ChargingDetour,
+    DelayCategory::MaintenanceDetour,
+    DelayCategory::FailureRecovery,
+];
+
+fn delay_index(cat: &DelayCategory) -> usize {
+    match cat {
+        DelayCategory::RobotAssignment => 0,
+        DelayCategory::TravelToPickup => 1,
+        DelayCategory::TravelToStation => 2,
+        DelayCategory::CongestionWait => 3,
+        DelayCategory::StationQueue => 4,
+        DelayCategory::StationService => 5,
+        DelayCategory::ChargingDetour => 6,
+        DelayCategory::MaintenanceDetour => 7,
+        DelayCategory::FailureRecovery => 8,
+    }
+}
+
+/// A quantity a [`RewardSpec`] can weight. Every term is measured between two
+/// consecutive decisions, except `Pending` (the backlog level at the later
+/// one) and `DecisionCost` (the previous decision's own routed cost).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RewardTerm {
+    /// Orders completed.
+    Throughput,
+    /// Orders that completed late.
+    LateOrders,
+    /// Minutes of lateness accrued by completed orders.
+    LatenessMinutes,
+    /// Pending tasks.
+    Pending,
+    /// Minutes of attributed delay in one category, over completed tasks
+    /// (enables the attribution collector).
+    Delay(DelayCategory),
+    /// Watt-hours of energy used by the fleet.
+    EnergyWh,
+    /// Deadlock cycles detected.
+    Deadlocks,
+    /// Routed cost of the previous decision (`w_travel` x travel-to-pickup
+    /// minutes + `w_backlog` x the chosen robot's backlog).
+    DecisionCost,
+}
+
+impl RewardTerm {
+    pub fn all() -> Vec<RewardTerm> {
+        let mut terms = vec![
+            Self::Throughput,
+            Self::LateOrders,
+            Self::LatenessMinutes,
+            Self::Pending,
+        ];
+        terms.extend(DELAY_CATEGORIES.iter().cloned().map(Self::Delay));
+        terms.extend([Self::EnergyWh, Self::Deadlocks, Self::DecisionCost]);
+        terms
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Throughput => "throughput",
+            Self::LateOrders => "late_orders",
+            Self::LatenessMinutes => "lateness_min",
+            Self::Pending => "pending",
+            Self::Delay(cat) => match cat {
+                DelayCategory::RobotAssignment => "robot_assignment_min",
+                DelayCategory::TravelToPickup => "travel_to_pickup_min",
+                DelayCategory::TravelToStation => "travel_to_station_min",
+                DelayCategory::CongestionWait => "congestion_wait_min",
+                DelayCategory::StationQueue => "station_queue_min",
+                DelayCategory::StationService => "station_service_min",
+                DelayCategory::ChargingDetour => "charging_detour_min",
+                DelayCategory::MaintenanceDetour => "maintenance_detour_min",
+                DelayCategory::FailureRecovery => "failure_recovery_min",
+            },
+            Self::EnergyWh => "energy_wh",
+            Self::Deadlocks => "deadlocks",
+            Self::DecisionCost => "decision_cost",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        let s = s.trim().to_lowercase();
+        Self::all().into_iter().find(|t| t.name() == s)
+    }
+
+    /// Raw value between `prev` and `cur`.
+    fn value(&self, prev: &RewardSnapshot, cur: &RewardSnapshot, decision_cost: f32) -> f64 {
+        match self {
+            Self::Throughput => cur.completed as f64 - prev.completed as f64,
+            Self::LateOrders => cur.late as f64 - prev.late as f64,
+            Self::LatenessMinutes => (cur.cum_lateness_s - prev.cum_lateness_s) / 60.0,
+            Self::Pending => cur.pending as f64,
+            Self::Delay(cat) => {
+                let i = delay_index(cat);
+                (cur.attr_delay_s[i] - prev.attr_delay_s[i]) / 60.0
+            }
+            Self::EnergyWh => cur.energy_wh - prev.energy_wh,
+            Self::Deadlocks => cur.deadlocks as f64 - prev.deadlocks as f64,
+            Self::DecisionCost => decision_cost as f64,
+        }
+    }
+}
+
+/// One weighted term of a [`RewardSpec`]. The contribution is `weight *
+/// value`, clamped to `clip` when set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TermSpec {
+    pub term: RewardTerm,
+    pub weight: f64,
+    pub clip: Option<(f64, f64)>,
+}
+
+impl TermSpec {
+    fn contribution(&self, value: f64) -> f32 {
+        let c = self.weight * value;
+        let c = match self.clip {
+            Some((lo, hi)) => c.clamp(lo, hi),
+            None => c,
+        };
+        c as f32
+    }
+}
+
+/// A user-composed reward: the sum of its terms' contributions.
+///
+/// Written as text, terms are comma-separated `name=weight`, each optionally
+/// followed by a clip range `[lo..hi]` whose bounds may be left open:
+/// `"throughput=1, late_orders=-2, lateness_min=-0.1[-5..], energy_wh=-0.001"`.
+/// Penalties take negative weights.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RewardSpec {
+    pub terms: Vec<TermSpec>,
+}
+
+impl RewardSpec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `term` with `weight`.
+    pub fn term(mut self, term: RewardTerm, weight: f64) -> Self {
+        self.terms.push(TermSpec {
+            term,
+            weight,
+            clip: None,
+        });
+        self
+    }
+
+    /// Add `term` with `weight`, its contribution clamped to `[lo, hi]`.
+    pub fn clipped(mut self, term: RewardTerm, weight: f64, lo: f64, hi: f64) -> Self {
+        self.terms.push(TermSpec {
+            term,
+            weight,
+            clip: Some((lo.min(hi), lo.max(hi))),
+        });
+        self
+    }
+
+    /// Parse the text form (see the type docs).
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut spec = Self::new();
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (name, rest) = part
+                .split_once('=')
+                .ok_or_else(|| format!("expected name=weight, got '{part}'"))?;
+            let term = RewardTerm::from_str(name)
+                .ok_or_else(|| format!("unknown reward term '{}'", name.trim()))?;
+            if spec.terms.iter().any(|t| t.term == term) {
+                return Err(format!("reward term '{}' given twice", term.name()));
+            }
+            let (weight, clip) = match rest.split_once('[') {
+                Some((w, range)) => {
+                    let range = range
+                        .trim()
+                        .strip_suffix(']')
+                        .ok_or_else(|| format!("unclosed clip range in '{part}'"))?;
+                    let (lo, hi) = range
+                        .split_once("..")
+                        .ok_or_else(|| format!("expected [lo..hi] in '{part}'"))?;
+                    let bound = |b: &str, open: f64| match b.trim() {
+                        "" => Ok(open),
+                        b => b
+                            .parse::<f64>()
+                            .map_err(|_| format!("bad clip bound '{b}' in '{part}'")),
+                    };
+                    (
+                        w,
+                        Some((bound(lo, f64::NEG_INFINITY)?, bound(hi, f64::INFINITY)?)),
+                    )
+                }
+                None => (rest, None),
+            };
+            let weight = weight
+                .trim()
+                .parse::<f64>()
+                .map_err(|_| format!("bad weight in '{part}'"))?;
+            spec.terms.push(TermSpec { term, weight, clip });
+        }
+        Ok(spec)
+    }
+
+    /// Whether any term reads the attribution collector.
+    pub fn needs_attribution(&self) -> bool {
+        self.terms
+            .iter()
+            .any(|t| matches!(t.term, RewardTerm::Delay(_)))
+    }
+
+    /// Whether the spec charges decisions their routed cost.
+    pub fn charges_decisions(&self) -> bool {
+        self.terms
+            .iter()
+            .any(|t| t.term == RewardTerm::DecisionCost)
+    }
+
+    /// Each term's contribution between `prev` and `cur`, in spec order.
+    pub fn contributions(
+        &self,
+        prev: &RewardSnapshot,
+        cur: &RewardSnapshot,
+        decision_cost: f32,
+    ) -> Vec<(&'static str, f32)> {
+        self.terms
+            .iter()
+            .map(|t| {
+                let value = t.term.value(prev, cur, decision_cost);
+                (t.term.name(), t.contribution(value))
+            })
+            .collect()
+    }
+
+    /// Contribution of the `decision_cost` term for a decision costing `cost`.
+    pub fn decision_cost_contribution(&self, cost: f32) -> f32 {
+        self.terms
+            .iter()
+            .find(|t| t.term == RewardTerm::DecisionCost)
+            .map_or(0.0, |t| t.contribution(cost as f64))
+    }
+}
+
 /// Controllable cost of a single allocation decision, charged to the decision
 /// that made it (used by [`RewardMode::Routed`]). `travel_min` is the estimated
 /// travel-to-pickup time for the chosen robot; `backlog` is how many tasks were
@@ -80,6 +340,8 @@ pub struct RewardConfig {
     pub w_travel: f64,
     /// Penalty per queued task on the chosen robot at decision time (routed mode).
     pub w_backlog: f64,
+    /// Terms of the custom mode.
+    pub spec: RewardSpec,
 }
 
 impl Default for RewardConfig {
@@ -93,6 +355,42 @@ impl Default for RewardConfig {
             w_waste: 0.2,
             w_travel: 0.1,
             w_backlog: 0.1,
+            spec: RewardSpec::default(),
+        }
+    }
+}
+
+impl RewardConfig {
+    /// A `Custom` config over `spec`.
+    pub fn custom(spec: RewardSpec) -> Self {
+        Self {
+            mode: RewardMode::Custom,
+            spec,
+            ..Self::default()
+        }
+    }
+
+    /// Whether the attribution collector must run.
+    pub fn needs_attribution(&self) -> bool {
+        self.mode.needs_attribution()
+            || (self.mode == RewardMode::Custom && self.spec.needs_attribution())
+    }
+
+    /// Whether each decision is charged its own routed cost.
+    pub fn charges_decisions(&self) -> bool {
+        match self.mode {
+            RewardMode::Routed => true,
+            RewardMode::Custom => self.spec.charges_decisions(),
+            _ => false,
+        }
+    }
+
+    /// Amount subtracted from a decision's reward for its routed `cost`.
+    pub fn decision_penalty(&self, cost: f32) -> f32 {
+        match self.mode {
+            RewardMode::Routed => cost,
+            RewardMode::Custom => -self.spec.decision_cost_contribution(cost),
+            _ => 0.0,
         }
     }
 }
@@ -111,6 +409,31 @@ pub struct RewardSnapshot {
     /// Cumulative attributed UNCONTROLLABLE delay (congestion + station queue),
     /// seconds. Used only by the AttributionFull ablation.
     pub attr_uncontrollable_s: f64,
+    /// Cumulative attributed delay per category over completed tasks
+    /// (seconds, in `DelayCategory::all()` order).
+    pub attr_delay_s: [f64; 9],
+    /// Energy used by the fleet so far (Wh); set by [`RewardSnapshot::with_fleet`].
+    pub energy_wh: f64,
+    /// Deadlock cycles detected so far; set by [`RewardSnapshot::with_fleet`].
+    pub deadlocks: u64,
+}
+
+impl RewardSnapshot {
+    /// Add the fleet-level aggregates (energy, deadlocks).
+    pub fn with_fleet(
+        mut self,
+        robots: &HashMap<RobotId, Robot>,
+        traffic: Option<&TrafficManager>,
+    ) -> Self {
+        let mut ids: Vec<&RobotId> = robots.keys().collect();
+        ids.sort_by_key(|id| id.0);
+        self.energy_wh = ids
+            .iter()
+            .map(|id| robots[*id].total_energy_consumed_wh)
+            .sum();
+        self.deadlocks = traffic.map_or(0, |t| t.deadlocks_detected());
+        self
+    }
 }
 
 /// Compute a snapshot from the current order/task maps and (optional) attribution.
@@ -144,6 +467,7 @@ pub fn snapshot_from(
     // deliberately EXCLUDE congestion and station-queue: under allocation-only
     // control the agent cannot influence them, and penalizing such uncontrollable
     // delay injects reward variance that degrades learning (shown empirically).
+    let mut attr_delay_s = [0.0; 9];
     let (attr_waste_s, attr_travel_s, attr_uncontrollable_s) = match attribution {
         Some(ac) => {
             let mut waste = 0.0;
@@ -151,6 +475,7 @@ pub fn snapshot_from(
             let mut uncontrollable = 0.0;
             for attr in ac.completed_attributions() {
                 for (cat, secs) in &attr.time_breakdown {
+                    attr_delay_s[delay_index(cat)] += *secs;
                     match cat {
                         DelayCategory::TravelToPickup => travel += *secs,
                         DelayCategory::RobotAssignment => waste += *secs,
@@ -175,6 +500,8 @@ pub fn snapshot_from(
         attr_waste_s,
         attr_travel_s,
         attr_uncontrollable_s,
+        attr_delay_s,
+        ..RewardSnapshot::default()
     }
 }
 
@@ -211,6 +538,14 @@ pub fn delta(prev: &RewardSnapshot, cur: &RewardSnapshot, cfg: &RewardConfig) ->
         // is added in the policy (charged to the responsible decision) via
         // `decision_cost`, not here.
         RewardMode::Routed => cfg.w_throughput * d_completed - cfg.w_late * d_late,
+        // Custom: the spec's terms, without a decision cost (added by the
+        // policy, as in routed mode).
+        RewardMode::Custom => cfg
+            .spec
+            .contributions(prev, cur, 0.0)
+            .iter()
+            .map(|(_, c)| *c as f64)
+            .sum(),
     };
 
     r as f32
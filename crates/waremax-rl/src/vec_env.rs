@@ -0,0 +1,266 @@
+//! `VecEnv`: N `RlEnv`s stepped together with one batched call.
+//!
+//! Each env already simulates on its own worker thread, so a batched step
+//! sends every action first and only then collects the answers: the N
+//! simulations advance concurrently while the caller blocks once. Observations
+//! come back stacked into flat row-major buffers (env-major), ready to be
+//! viewed as `[N, ...]` arrays without copying per env.
+//!
+//! Finished episodes are reset automatically. Env `i`'s `k`-th episode is
+//! seeded with [`episode_seed`]`(base_seed, i, k)`, so the whole pool is
+//! reproducible from `base_seed` and the action sequence, independent of how
+//! the OS schedules the worker threads.
+//!
+//! With a scenario distribution, a curriculum advances with the episodes
+//! started across the whole pool, not per env.
+
+use waremax_config::ScenarioConfig;
+use waremax_metrics::SimulationReport;
+
+use crate::env::{ControlMode, RlEnv, StepResult};
+use crate::obs_spec::ObservationSpec;
+use crate::observation::{DecisionKind, Observation, MAX_STATIONS, STATION_FEATS};
+use crate::protocol::{ActionMsg, StepInfo};
+use crate::randomization::{Curriculum, ScenarioDistribution};
+use crate::reward::RewardConfig;
+
+/// Observations of all envs, stacked env-major. `R`, `F` and `T` are the
+/// pool's `ObservationSpec` rows, robot features and task features.
+#[derive(Debug, Clone, Default)]
+pub struct VecObservation {
+    /// `N * R * F`.
+    pub robot_feats: Vec<f32>,
+    /// `N * R`.
+    pub action_mask: Vec<bool>,
+    /// `N * T`.
+    pub task_feats: Vec<f32>,
+    /// `N * MAX_STATIONS * STATION_FEATS`.
+    pub station_feats: Vec<f32>,
+    /// `N * MAX_STATIONS`.
+    pub station_mask: Vec<bool>,
+    /// One per env.
+    pub kinds: Vec<DecisionKind>,
+}
+
+impl VecObservation {
+    fn with_capacity(n: usize, spec: &ObservationSpec) -> Self {
+        Self {
+            robot_feats: Vec::with_capacity(n * spec.max_candidates * spec.robot_feats()),
+            action_mask: Vec::with_capacity(n * spec.max_candidates),
+            task_feats: Vec::with_capacity(n * spec.task_feats()),
+            station_feats: Vec::with_capacity(n * MAX_STATIONS * STATION_FEATS),
+            station_mask: Vec::with_capacity(n * MAX_STATIONS),
+            kinds: Vec::with_capacity(n),
+        }
+    }
+
+    fn push(&mut self, obs: &Observation) {
+        self.robot_feats.extend_from_slice(&obs.robot_feats);
+        self.action_mask.extend_from_slice(&obs.action_mask);
+        self.task_feats.extend_from_slice(&obs.task_feats);
+        self.station_feats.extend_from_slice(&obs.station_feats);
+        self.station_mask.extend_from_slice(&obs.station_mask);
+        self.kinds.push(obs.kind);
+    }
+}
+
+/// Result of one batched `step`.
+#[derive(Debug, Clone)]
+pub struct VecStepResult {
+    /// Next observation of every env; for an env whose episode just ended,
+    /// the first observation of its new episode.
+    pub obs: VecObservation,
+    pub rewards: Vec<f32>,
+    pub dones: Vec<bool>,
+    /// Per-env info; a finished env's carries the episode's final metrics.
+    pub infos: Vec<StepInfo>,
+}
+
+/// A pool of `RlEnv`s over one scenario, stepped in lockstep.
+pub struct VecEnv {
+    envs: Vec<RlEnv>,
+    base_seed: u64,
+    /// Episodes started so far per env (the next episode's index).
+    episodes: Vec<u64>,
+    spec: ObservationSpec,
+    curriculum: Option<Curriculum>,
+    /// Episodes started across the pool since construction (drives the
+    /// curriculum; not cleared by `reset`).
+    started: u64,
+    pinned_progress: Option<f64>,
+}
+
+impl VecEnv {
+    pub fn new(
+        scenario: ScenarioConfig,
+        reward_cfg: RewardConfig,
+        num_envs: usize,
+        base_seed: u64,
+    ) -> Self {
+        let envs = (0..num_envs)
+            .map(|_| RlEnv::new(scenario.clone(), reward_cfg.clone()))
+            .collect();
+        Self {
+            envs,
+            base_seed,
+            episodes: vec![0; num_envs],
+            spec: ObservationSpec::default(),
+            curriculum: None,
+            started: 0,
+            pinned_progress: None,
+        }
+    }
+
+    /// Select which decisions the agent controls in every env (takes effect on
+    /// next reset).
+    pub fn with_control(mut self, control: ControlMode) -> Self {
+        self.envs = self
+            .envs
+            .into_iter()
+            .map(|env| env.with_control(control))
+            .collect();
+        self
+    }
+
+    /// Lay out every env's observations by `spec` (takes effect on next
+    /// reset).
+    pub fn with_observation_spec(mut self, spec: ObservationSpec) -> Self {
+        self.spec = spec.clone();
+        self.envs = self
+            .envs
+            .into_iter()
+            .map(|env| env.with_observation_spec(spec.clone()))
+            .collect();
+        self
+    }
+
+    /// Draw every episode's scenario from `distribution` (takes effect on next
+    /// reset).
+    pub fn with_scenario_distribution(mut self, distribution: ScenarioDistribution) -> Self {
+        self.curriculum = distribution.curriculum;
+        self.envs = self
+            .envs
+            .into_iter()
+            .map(|env| env.with_scenario_distribution(distribution.clone()))
+            .collect();
+        self
+    }
+
+    /// Curriculum progress the next episode is drawn at.
+    pub fn curriculum_progress(&self) -> f64 {
+        self.pinned_progress
+            .unwrap_or_else(|| self.curriculum.map_or(1.0, |c| c.progress(self.started)))
+    }
+
+    /// Pin the curriculum progress (0..=1) instead of counting episodes.
+    pub fn set_curriculum_progress(&mut self, progress: f64) {
+        self.pinned_progress = Some(progress.clamp(0.0, 1.0));
+    }
+
+    /// The observation layout shared by every env.
+    pub fn observation_spec(&self) -> &ObservationSpec {
+        &self.spec
+    }
+
+    pub fn num_envs(&self) -> usize {
+        self.envs.len()
+    }
+
+    /// The report of env `i`'s most recently completed episode, if any.
+    pub fn last_report(&self, i: usize) -> Option<SimulationReport> {
+        self.envs.get(i).and_then(|env| env.last_report())
+    }
+
+    /// Restart the seed streams and begin a fresh episode in every env.
+    pub fn reset(&mut self) -> VecObservation {
+        self.episodes.iter_mut().for_each(|k| *k = 0);
+        let all: Vec<usize> = (0..self.envs.len()).collect();
+        let first = self.restart(&all);
+
+        let mut obs = VecObservation::with_capacity(self.envs.len(), &self.spec);
+        for o in &first {
+            obs.push(o);
+        }
+        obs
+    }
+
+    /// Apply one action per env and advance all of them to their next
+    /// decision, auto-resetting finished episodes.
+    ///
+    /// # Panics
+    /// If `actions.len()` differs from `num_envs()`.
+    pub fn step(&mut self, actions: &[ActionMsg]) -> VecStepResult {
+        assert_eq!(
+            actions.len(),
+            self.envs.len(),
+            "one action per env is required"
+        );
+
+        // Send every action before receiving any result so the simulations
+        // run concurrently.
+        let sent: Vec<bool> = self
+            .envs
+            .iter_mut()
+            .zip(actions)
+            .map(|(env, action)| env.send_action(action.clone()))
+            .collect();
+        let mut results: Vec<StepResult> = self
+            .envs
+            .iter_mut()
+            .zip(&sent)
+            .map(|(env, &sent)| {
+                if sent {
+                    env.recv_step()
+                } else {
+                    env.finished()
+                }
+            })
+            .collect();
+
+        let finished: Vec<usize> = (0..results.len()).filter(|&i| results[i].done).collect();
+        for (i, first) in finished.iter().zip(self.restart(&finished)) {
+            results[*i].obs = first;
+        }
+
+        let mut out = VecStepResult {
+            obs: VecObservation::with_capacity(results.len(), &self.spec),
+            rewards: Vec::with_capacity(results.len()),
+            dones: Vec::with_capacity(results.len()),
+            infos: Vec::with_capacity(results.len()),
+        };
+        for r in results {
+            out.obs.push(&r.obs);
+            out.rewards.push(r.reward);
+            out.dones.push(r.done);
+            out.infos.push(r.info);
+        }
+        out
+    }
+
+    /// Start the next episode of each env in `indices` (concurrently) and
+    /// return their first observations in the same order.
+    fn restart(&mut self, indices: &[usize]) -> Vec<Observation> {
+        for &i in indices {
+            let seed = episode_seed(self.base_seed, i as u64, self.episodes[i]);
+            self.episodes[i] += 1;
+            let progress = self.curriculum_progress();
+            self.started += 1;
+            self.envs[i].set_curriculum_progress(progress);
+            self.envs[i].start_episode(seed);
+        }
+        indices.iter().map(|&i| self.envs[i].recv_first()).collect()
+    }
+}
+
+/// Seed of env `env`'s `episode`-th episode in a pool seeded with `base_seed`.
+///
+/// A SplitMix64 finalizer over the three inputs, so neighbouring envs and
+/// episodes get unrelated seeds.
+pub fn episode_seed(base_seed: u64, env: u64, episode: u64) -> u64 {
+    let mut z = base_seed
+        .wrapping_add(env.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+        .wrapping_add(episode.wrapping_mul(0xD1B5_4A32_D192_ED03));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
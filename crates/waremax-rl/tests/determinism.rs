@@ -6,7 +6,15 @@
 //! freshly spawned worker threads (no global/thread state leaks in).
 
 use waremax_config::ScenarioConfig;
-use waremax_rl::{ActionMsg, RewardConfig, RewardMode, RlEnv};
+use waremax_metrics::SimulationReport;
+use waremax_policies::{Activation, CandidateScorer, DenseLayer};
+use waremax_rl::{
+    episode_seed, read_dataset, record_episode, ActionMsg, ControlMode, Curriculum, DatasetHeader,
+    DatasetWriter, DecisionKind, MultiAgentEnv, MultiObservation, ObservationSpec, RewardConfig,
+    RewardMode, RewardSpec, RlEnv, ScenarioDistribution, VecEnv, EDGE_FEATS, MAX_ROBOTS,
+    NODE_FEATS, PARK_ACTION,
+};
+use waremax_testing::runner::run_simulation_from_config;
 use waremax_testing::ScenarioPreset;
 
 /// A short scenario with enough activity to exercise many allocation decisions.
@@ -129,6 +137,55 @@ fn routed_mode_is_deterministic() {
     );
 }
 
+/// Count the allocation and station decisions of one episode driven with
+/// `Choose(0)`.
+fn count_kinds(env: &mut RlEnv, seed: u64) -> (usize, usize) {
+    let mut obs = env.reset(seed);
+    let (mut alloc, mut station) = (0, 0);
+    while !env.is_done() {
+        match obs.kind {
+            DecisionKind::Allocation => alloc += 1,
+            DecisionKind::StationAssignment => station += 1,
+        }
+        obs = env.step(ActionMsg::Choose(0)).obs;
+    }
+    (alloc, station)
+}
+
+#[test]
+fn station_control_is_deterministic() {
+    let cfg = RewardConfig {
+        mode: RewardMode::Routed,
+        ..RewardConfig::default()
+    };
+    let scenario = scenario_from(ScenarioPreset::Standard, 8.0);
+    let new_env =
+        || RlEnv::new(scenario.clone(), cfg.clone()).with_control(ControlMode::StationAssignment);
+    let a = drive(&mut new_env(), 5);
+    let b = drive(&mut new_env(), 5);
+    assert_eq!(a, b, "station-assignment control must be deterministic");
+    assert!(a.orders_completed > 0, "fallback must keep orders flowing");
+    assert!(
+        a.rewards.iter().any(|r| *r != 0.0),
+        "routed station reward was all zeros"
+    );
+
+    let (alloc, station) = count_kinds(&mut new_env(), 5);
+    assert_eq!(alloc, 0, "allocation stays with the scenario policy");
+    assert!(station > 0, "station decisions should reach the agent");
+}
+
+#[test]
+fn both_control_interleaves_decision_kinds() {
+    let scenario = scenario_from(ScenarioPreset::Standard, 8.0);
+    let mut env = RlEnv::new(scenario, RewardConfig::default()).with_control(ControlMode::Both);
+    let (alloc, station) = count_kinds(&mut env, 11);
+    assert!(
+        alloc > 0 && station > 0,
+        "both decision kinds should be emitted"
+    );
+}
+
 #[test]
 fn episode_runs_and_terminates() {
     let mut env = RlEnv::new(test_scenario(), RewardConfig::default());
@@ -151,3 +208,555 @@ fn reset_midway_does_not_hang() {
     let fp = drive(&mut env, 123); // reset midway, then run a full episode
     assert!(fp.steps > 0);
 }
+
+/// Agent `i` takes task row `i` when there is one and parks otherwise, so
+/// agents never contend.
+fn spread_actions(obs: &MultiObservation) -> Vec<usize> {
+    (0..obs.agents.len())
+        .map(|i| {
+            if i < obs.task_ids.len() {
+                i
+            } else {
+                PARK_ACTION
+            }
+        })
+        .collect()
+}
+
+/// Drive a multi-agent episode with `policy`; returns the fingerprint (with the
+/// summed rewards of each step), the largest joint batch, and total conflicts.
+fn drive_multi(
+    env: &mut MultiAgentEnv,
+    seed: u64,
+    policy: fn(&MultiObservation) -> Vec<usize>,
+) -> (Fingerprint, usize, usize) {
+    let mut obs = env.reset(seed);
+    let mut rewards = Vec::new();
+    let (mut steps, mut max_batch, mut conflicts) = (0u64, 0, 0);
+    while !env.is_done() {
+        max_batch = max_batch.max(obs.agents.len());
+        let res = env.step(policy(&obs));
+        rewards.push(res.rewards.iter().sum());
+        conflicts += res.conflicts;
+        steps += 1;
+        obs = res.obs;
+        assert!(steps < 1_000_000, "episode did not terminate");
+    }
+    let report = env.last_report().expect("a completed episode has a report");
+    let fp = Fingerprint {
+        steps,
+        rewards,
+        events_processed: report.events_processed,
+        orders_completed: report.orders_completed,
+        orders_late: report.orders_late,
+        p95_cycle_time_s: report.p95_cycle_time_s,
+    };
+    (fp, max_batch, conflicts)
+}
+
+#[test]
+fn multi_agent_is_deterministic_and_batches_agents() {
+    let cfg = RewardConfig {
+        mode: RewardMode::Routed,
+        ..RewardConfig::default()
+    };
+    let a = drive_multi(
+        &mut MultiAgentEnv::new(test_scenario(), cfg.clone()),
+        17,
+        spread_actions,
+    );
+    let b = drive_multi(
+        &mut MultiAgentEnv::new(test_scenario(), cfg),
+        17,
+        spread_actions,
+    );
+    assert_eq!(a, b, "multi-agent runs must reproduce exactly");
+    assert!(
+        a.0.orders_completed > 0,
+        "agents taking tasks must complete orders"
+    );
+    assert!(
+        a.1 > 1,
+        "robots free at the same time should decide jointly"
+    );
+    assert_eq!(a.2, 0, "distinct task choices never conflict");
+}
+
+#[test]
+fn multi_agent_conflicts_and_parking() {
+    let scenario = test_scenario();
+
+    // Everyone grabs the first task: only the lowest-id robot wins it.
+    let (fp, _, conflicts) = drive_multi(
+        &mut MultiAgentEnv::new(scenario.clone(), RewardConfig::default()),
+        3,
+        |obs| vec![0; obs.agents.len()],
+    );
+    assert!(conflicts > 0, "contested tasks should be reported");
+    assert!(fp.orders_completed > 0);
+
+    // Everyone parks: no task is ever assigned.
+    let (fp, _, _) = drive_multi(
+        &mut MultiAgentEnv::new(scenario, RewardConfig::default()),
+        3,
+        |obs| vec![PARK_ACTION; obs.agents.len()],
+    );
+    assert_eq!(fp.orders_completed, 0);
+}
+
+#[test]
+fn vec_env_matches_serial_envs_and_auto_resets() {
+    let scenario = scenario_from(ScenarioPreset::Quick, 3.0);
+    let (n, base_seed) = (3, 2024);
+
+    // Reference: each env's first episode run on its own.
+    let serial: Vec<Fingerprint> = (0..n)
+        .map(|i| {
+            let mut env = RlEnv::new(scenario.clone(), RewardConfig::default());
+            drive(&mut env, episode_seed(base_seed, i as u64, 0))
+        })
+        .collect();
+
+    let mut vec_env = VecEnv::new(scenario, RewardConfig::default(), n, base_seed);
+    let obs = vec_env.reset();
+    assert_eq!(obs.action_mask.len(), n * MAX_ROBOTS);
+
+    let actions = vec![ActionMsg::Choose(0); n];
+    let mut rewards: Vec<Vec<f32>> = vec![Vec::new(); n];
+    let mut finished = vec![false; n];
+    let mut steps = 0;
+    while !finished.iter().all(|&f| f) {
+        let res = vec_env.step(&actions);
+        for i in 0..n {
+            if finished[i] {
+                continue;
+            }
+            rewards[i].push(res.rewards[i]);
+            if res.dones[i] {
+                finished[i] = true;
+                assert!(res.infos[i].final_metrics.is_some());
+                // Auto-reset: the returned observation starts a new episode.
+                let mask = &res.obs.action_mask[i * MAX_ROBOTS..(i + 1) * MAX_ROBOTS];
+                assert!(mask.iter().any(|&m| m), "env {i} was not auto-reset");
+            }
+        }
+        steps += 1;
+        assert!(steps < 1_000_000, "vec episodes did not terminate");
+    }
+
+    for i in 0..n {
+        assert_eq!(
+            rewards[i], serial[i].rewards,
+            "env {i} must match a serial run with the same seed"
+        );
+    }
+}
+
+#[test]
+fn custom_observation_spec_shapes_and_determinism() {
+    let names = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    let robot = names(&[
+        "route_distance",
+        "eta_to_pickup",
+        "local_congestion",
+        "battery",
+    ]);
+    let task = names(&["due_slack", "pending"]);
+    let spec = ObservationSpec::from_names(Some(2), Some(&robot), Some(&task)).unwrap();
+    assert_eq!(spec.robot_feature_names(), robot);
+
+    let new_env =
+        || RlEnv::new(test_scenario(), RewardConfig::default()).with_observation_spec(spec.clone());
+    let mut env = new_env();
+    let obs = env.reset(8);
+    assert_eq!(obs.robot_feats.len(), 2 * 4);
+    assert_eq!(obs.action_mask.len(), 2);
+    assert_eq!(obs.task_feats.len(), 2);
+    assert!(
+        obs.truncated_candidates > 0,
+        "an idle fleet larger than two rows must be truncated"
+    );
+    // Route distance and ETA are real, in-range features.
+    assert!(obs.robot_feats.iter().all(|f| (0.0..=1.0).contains(f)));
+
+    let a = drive(&mut new_env(), 8);
+    let b = drive(&mut new_env(), 8);
+    assert_eq!(a, b, "a custom spec must stay deterministic");
+
+    assert!(ObservationSpec::from_names(None, Some(&names(&["warp_speed"])), None).is_err());
+    assert!(ObservationSpec::from_names(Some(0), None, None).is_err());
+    assert!(ObservationSpec::from_names(None, Some(&[]), None).is_err());
+    assert!(ObservationSpec::from_names(None, None, Some(&[])).is_err());
+}
+
+#[test]
+fn graph_observation_marks_pickup_and_candidates() {
+    let spec = ObservationSpec::default().with_graph(true);
+    let mut env = RlEnv::new(test_scenario(), RewardConfig::default()).with_observation_spec(spec);
+    let obs = env.reset(5);
+    let g = obs.graph.expect("graph attached");
+    let n = g.num_nodes();
+    assert!(n > 0 && g.num_edges() > 0);
+    assert_eq!(g.node_feats.len(), n * NODE_FEATS);
+    assert_eq!(g.edge_feats.len(), g.num_edges() * EDGE_FEATS);
+    assert_eq!(g.edge_dst.len(), g.num_edges());
+    let mut endpoints = g.edge_src.iter().chain(&g.edge_dst);
+    assert!(endpoints.all(|&i| (i as usize) < n));
+    assert!(g.node_feats.iter().all(|f| (0.0..=1.0).contains(f)));
+
+    // The pickup and every candidate's node carry their flags.
+    assert_eq!(g.node_feats[g.pickup_node as usize * NODE_FEATS + 8], 1.0);
+    assert_eq!(g.candidate_nodes.len(), obs.candidate_robot_ids.len());
+    for &c in &g.candidate_nodes {
+        assert_eq!(g.node_feats[c as usize * NODE_FEATS + 9], 1.0);
+    }
+
+    // Graph features are derived state only: the run is unchanged.
+    let plain = drive(&mut RlEnv::new(test_scenario(), RewardConfig::default()), 5);
+    let with_graph = drive(&mut env, 5);
+    assert_eq!(plain, with_graph);
+}
+
+/// Step `env` with "first candidate" until done; returns the rewards.
+fn finish_episode(env: &mut RlEnv) -> Vec<f32> {
+    let mut rewards = Vec::new();
+    loop {
+        let res = env.step(ActionMsg::Choose(0));
+        rewards.push(res.reward);
+        if res.done {
+            return rewards;
+        }
+    }
+}
+
+#[test]
+fn forked_branches_are_identical_under_the_same_actions() {
+    let mut env = RlEnv::new(test_scenario(), RewardConfig::default()).with_checkpoint_interval(8);
+    assert!(env.fork().is_none(), "nothing to fork before reset");
+    let first = env.reset(13);
+    let root = env.clone_state().unwrap();
+    let mut sim_time_s = 0.0;
+    let mut episode = Vec::new();
+    for _ in 0..20 {
+        let res = env.step(ActionMsg::Choose(0));
+        assert!(!res.done);
+        sim_time_s = res.info.sim_time_s;
+        episode.push(res.reward);
+    }
+    // The snapshot holds a copy of the simulation from a few decisions ago
+    // rather than replaying the episode from the start.
+    let snapshot = env.clone_state().unwrap();
+    assert!(snapshot.actions().len() < 10);
+    assert!(snapshot.checkpoint_time_s() > 0.0);
+    assert!(snapshot.checkpoint_time_s() <= sim_time_s);
+
+    let (mut a, obs_a) = env.fork().unwrap();
+    let (mut b, obs_b) = env.fork().unwrap();
+    assert_eq!(obs_a.robot_feats, obs_b.robot_feats);
+    assert_eq!(obs_a.candidate_robot_ids, obs_b.candidate_robot_ids);
+    assert_eq!(a.clone_state().unwrap(), snapshot);
+
+    let rewards_a = finish_episode(&mut a);
+    let rewards_b = finish_episode(&mut b);
+    let rewards_orig = finish_episode(&mut env);
+    assert_eq!(rewards_a, rewards_b, "forks must agree");
+    assert_eq!(rewards_a, rewards_orig, "a fork must match its parent");
+    let report = |e: &RlEnv| e.last_report().unwrap().events_processed;
+    assert_eq!(report(&a), report(&b));
+    assert_eq!(report(&a), report(&env));
+
+    // Snapshots outlive their episode, and restoring one is repeatable.
+    episode.extend(&rewards_orig);
+    let obs = env.restore(&root);
+    assert_eq!(obs.robot_feats, first.robot_feats);
+    assert_eq!(finish_episode(&mut env), episode);
+    env.restore(&snapshot);
+    assert_eq!(env.clone_state().unwrap(), snapshot);
+    assert_eq!(finish_episode(&mut env), rewards_orig);
+}
+
+#[test]
+fn forks_resume_every_controlled_policy_and_its_reward_state() {
+    // Both decision kinds share one channel; a fork must carry both, the
+    // expert behind the allocation policy, and the reward baseline.
+    let cfg = RewardConfig {
+        mode: RewardMode::Routed,
+        ..RewardConfig::default()
+    };
+    let mut env = RlEnv::new(test_scenario(), cfg)
+        .with_control(ControlMode::Both)
+        .with_expert("nearest_robot")
+        .with_checkpoint_interval(1);
+    env.reset(5);
+    for _ in 0..30 {
+        env.step(ActionMsg::Expert);
+    }
+    let snapshot = env.clone_state().unwrap();
+    assert!(snapshot.checkpoint_time_s() > 0.0);
+
+    let expert_episode = |env: &mut RlEnv| {
+        let mut out = Vec::new();
+        while !env.is_done() {
+            let res = env.step(ActionMsg::Expert);
+            out.push((res.reward, res.obs.kind, res.obs.expert_action));
+        }
+        (out, env.last_report().unwrap().events_processed)
+    };
+    let (mut fork, _) = env.fork().unwrap();
+    let forked = expert_episode(&mut fork);
+    assert_eq!(forked, expert_episode(&mut env));
+    fork.restore(&snapshot);
+    assert_eq!(expert_episode(&mut fork), forked);
+}
+
+#[test]
+fn forks_diverge_independently_of_their_parent() {
+    let mut env = RlEnv::new(test_scenario(), RewardConfig::default());
+    env.reset(13);
+    let mut parent_rewards: Vec<f32> = (0..20)
+        .map(|_| env.step(ActionMsg::Choose(0)).reward)
+        .collect();
+    let (mut fork, mut obs) = env.fork().unwrap();
+
+    // The fork takes the last candidate while the parent keeps taking the
+    // first; the fork runs to the end before the parent moves on.
+    let mut fork_actions = 0;
+    loop {
+        let last = obs.candidate_robot_ids.len().saturating_sub(1);
+        let res = fork.step(ActionMsg::Choose(last));
+        fork_actions += 1;
+        if res.done {
+            break;
+        }
+        obs = res.obs;
+    }
+    parent_rewards.extend(finish_episode(&mut env));
+
+    let fork_report = fork.last_report().unwrap();
+    let parent_report = env.last_report().unwrap();
+    assert!(fork_actions > 0);
+    assert_ne!(fork.clone_state(), env.clone_state());
+    let outcome = |r: &SimulationReport| (r.events_processed, r.p95_cycle_time_s);
+    assert_ne!(
+        outcome(&fork_report),
+        outcome(&parent_report),
+        "different actions must lead to different outcomes"
+    );
+
+    // The parent's run is the one it would have had without the fork.
+    let mut solo_env = RlEnv::new(test_scenario(), RewardConfig::default());
+    let solo = drive(&mut solo_env, 13);
+    assert_eq!(parent_rewards, solo.rewards);
+    assert_eq!(parent_report.events_processed, solo.events_processed);
+    assert_eq!(parent_report.p95_cycle_time_s, solo.p95_cycle_time_s);
+}
+
+#[test]
+fn expert_dataset_round_trips_and_reproduces_the_heuristic() {
+    let path = std::env::temp_dir().join(format!("waremax-expert-{}.wmxds", std::process::id()));
+    let mut env = RlEnv::new(test_scenario(), RewardConfig::default()).with_expert("nearest_robot");
+    let header = DatasetHeader::for_spec(env.observation_spec());
+    let mut writer = DatasetWriter::create(&path, header).unwrap();
+    let summary = record_episode(&mut env, 21, &mut writer, |_| ActionMsg::Expert).unwrap();
+    assert_eq!(writer.len(), summary.transitions);
+    writer.finish().unwrap();
+
+    let (read_header, transitions) = read_dataset(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(read_header, header);
+    assert_eq!(transitions.len(), summary.transitions);
+    assert!(transitions.last().unwrap().done);
+    for t in &transitions {
+        let a = t.action.expect("nearest_robot always picks a candidate");
+        assert!(t.obs.action_mask[a], "expert actions are valid rows");
+    }
+    let total: f64 = transitions.iter().map(|t| t.reward as f64).sum();
+    assert!((total - summary.total_reward).abs() < 1e-6);
+
+    // Following the expert is the heuristic run itself.
+    let mut cfg = test_scenario();
+    cfg.seed = 21;
+    cfg.policies.task_allocation.alloc_type = "nearest_robot".to_string();
+    let baseline = run_simulation_from_config(&cfg);
+    let report = env.last_report().unwrap();
+    assert_eq!(report.orders_completed, baseline.orders_completed);
+    assert_eq!(report.events_processed, baseline.events_processed);
+}
+
+/// A small tanh MLP with fixed pseudo-random weights.
+fn test_scorer() -> CandidateScorer {
+    let mut state: u32 = 12345;
+    let mut next = move || {
+        state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        ((state >> 8) as f32 / (1u32 << 24) as f32) * 2.0 - 1.0
+    };
+    let mut layer = |inputs: usize, outputs: usize, activation| DenseLayer {
+        inputs,
+        outputs,
+        weights: (0..inputs * outputs).map(|_| next()).collect(),
+        bias: (0..outputs).map(|_| next()).collect(),
+        activation,
+    };
+    let layers = vec![
+        layer(14, 16, Activation::Tanh),
+        layer(16, 1, Activation::Identity),
+    ];
+    CandidateScorer::new(8, 6, layers).unwrap()
+}
+
+#[test]
+fn learned_policy_matches_scoring_rl_observations() {
+    let scorer = test_scorer();
+    let path = std::env::temp_dir().join(format!("waremax-scorer-{}.bin", std::process::id()));
+    std::fs::write(&path, scorer.to_bytes()).unwrap();
+
+    let mut cfg = test_scenario();
+    cfg.seed = 17;
+    cfg.policies.task_allocation.alloc_type = "learned".to_string();
+    cfg.policies.task_allocation.weights_path = Some(path.to_string_lossy().into_owned());
+    let native = run_simulation_from_config(&cfg);
+    std::fs::remove_file(&path).unwrap();
+
+    // An RL agent scoring the default observation with the same network must
+    // make every decision the native policy makes.
+    let mut env = RlEnv::new(test_scenario(), RewardConfig::default());
+    let mut obs = env.reset(17);
+    while !env.is_done() {
+        let feats = env.observation_spec().robot_feats();
+        let mut best: Option<(usize, f32)> = None;
+        for (row, robot) in obs.robot_feats.chunks(feats).enumerate() {
+            if !obs.action_mask[row] {
+                continue;
+            }
+            let score = scorer.score(robot, &obs.task_feats);
+            if best.is_none_or(|(_, s)| score > s) {
+                best = Some((row, score));
+            }
+        }
+        obs = env.step(ActionMsg::Choose(best.unwrap().0)).obs;
+    }
+    let report = env.last_report().unwrap();
+    assert_eq!(report.events_processed, native.events_processed);
+    assert_eq!(report.orders_completed, native.orders_completed);
+}
+
+#[test]
+fn randomized_scenarios_are_seeded_and_widen_with_the_curriculum() {
+    let dist = ScenarioDistribution::new()
+        .with_robots(2, 8)
+        .with_order_rate(0.5, 1.5)
+        .with_stations(1, 3)
+        .with_due_time_minutes(5.0, 30.0)
+        .with_curriculum(Curriculum::new(4));
+    let base = test_scenario();
+
+    let a = dist.sample(&base, 5, 0.7);
+    let b = dist.sample(&base, 5, 0.7);
+    assert_eq!(a.robots.count, b.robots.count);
+    assert_eq!(
+        a.orders.arrival_process.rate_per_min,
+        b.orders.arrival_process.rate_per_min
+    );
+    assert_eq!(a.stations.len(), b.stations.len());
+
+    // At the start of the curriculum every range collapses to its midpoint.
+    let start = dist.sample(&base, 5, 0.0);
+    assert_eq!(start.robots.count, 5);
+    assert_eq!(start.orders.arrival_process.rate_per_min, 1.0);
+    assert_eq!(start.stations.len(), 2);
+    assert_eq!(start.orders.due_times.unwrap().minutes, 17.5);
+
+    // Fully widened, the draws spread over the whole range.
+    let counts: std::collections::HashSet<u32> = (0..50)
+        .map(|seed| dist.sample(&base, seed, 1.0).robots.count)
+        .collect();
+    assert!(counts.len() > 3);
+    assert!(counts.iter().all(|c| (2..=8).contains(c)));
+
+    // The env advances the curriculum per reset, and a fork keeps its
+    // parent's draw.
+    let mut env = RlEnv::new(base, RewardConfig::default()).with_scenario_distribution(dist);
+    env.reset(1);
+    assert_eq!(env.episode_scenario().unwrap().robots.count, 5);
+    for seed in 2..6 {
+        env.reset(seed);
+    }
+    assert_eq!(env.curriculum_progress(), 1.0);
+    env.reset(9);
+    env.step(ActionMsg::Choose(0));
+    let (mut fork, _) = env.fork().unwrap();
+    assert_eq!(
+        fork.episode_scenario().unwrap().robots.count,
+        env.episode_scenario().unwrap().robots.count
+    );
+    assert_eq!(finish_episode(&mut fork), finish_episode(&mut env));
+}
+
+/// Per-step rewards and term breakdowns of one first-candidate episode.
+fn rewards_and_terms(cfg: RewardConfig, seed: u64) -> Vec<(f32, Vec<(&'static str, f32)>)> {
+    let mut env = RlEnv::new(test_scenario(), cfg);
+    env.reset(seed);
+    let mut out = Vec::new();
+    while !env.is_done() {
+        let res = env.step(ActionMsg::Choose(0));
+        out.push((res.reward, res.info.reward_terms));
+    }
+    out
+}
+
+#[test]
+fn custom_reward_specs_reproduce_the_built_in_modes() {
+    assert!(RewardSpec::parse("throughput=1, nonsense=2").is_err());
+    assert!(RewardSpec::parse("throughput=1, throughput=2").is_err());
+    assert!(RewardSpec::parse("lateness_min=-0.1[-5..").is_err());
+    let spec = RewardSpec::parse("throughput=1, lateness_min=-0.1[-5..], energy_wh=-0.01").unwrap();
+    assert_eq!(spec.terms.len(), 3);
+    assert_eq!(spec.terms[1].clip, Some((-5.0, f64::INFINITY)));
+
+    // The same weights as `Dense` and `Routed`, written as specs.
+    for (mode, text) in [
+        (
+            RewardMode::Dense,
+            "throughput=1, late_orders=-2, lateness_min=-0.1, pending=-0.01",
+        ),
+        (
+            RewardMode::Routed,
+            "throughput=1, late_orders=-2, decision_cost=-1",
+        ),
+    ] {
+        let builtin = rewards_and_terms(
+            RewardConfig {
+                mode,
+                ..RewardConfig::default()
+            },
+            4,
+        );
+        let custom = rewards_and_terms(RewardConfig::custom(RewardSpec::parse(text).unwrap()), 4);
+        assert_eq!(builtin.len(), custom.len());
+        for ((r_builtin, terms_builtin), (r_custom, terms)) in builtin.iter().zip(&custom) {
+            assert!(terms_builtin.is_empty());
+            assert!(
+                (r_builtin - r_custom).abs() < 1e-4,
+                "{mode:?}: {r_builtin} vs {r_custom}"
+            );
+            let sum: f32 = terms.iter().map(|(_, c)| c).sum();
+            assert_eq!(*r_custom, sum);
+        }
+    }
+
+    // Clipping bounds each term's contribution.
+    let clipped = rewards_and_terms(
+        RewardConfig::custom(
+            RewardSpec::parse("throughput=100[..1], travel_to_pickup_min=-1").unwrap(),
+        ),
+        4,
+    );
+    assert!(clipped.iter().any(|(_, terms)| terms[0].1 == 1.0));
+    assert!(clipped
+        .iter()
+        .all(|(_, terms)| terms[0].0 == "throughput" && terms[0].1 <= 1.0));
+    assert!(
+        clipped.iter().any(|(_, terms)| terms[1].1 < 0.0),
+        "attribution must be enabled"
+    );
+}
@@ -0,0 +1,85 @@
+//! Observation building outside a running env.
+
+use std::collections::HashMap;
+
+use waremax_core::{NodeId, OrderId, RackId, SimTime, SkuId, StationId, TaskId};
+use waremax_entities::{BinLocation, ServiceTimeModel, Station, StationType, Task};
+use waremax_map::{Node, NodeType, WarehouseMap};
+use waremax_policies::PolicyContext;
+use waremax_rl::observation::build_station_observation;
+use waremax_rl::{ObservationSpec, MAX_STATIONS};
+use waremax_storage::BinAddress;
+
+#[test]
+fn station_observation_keeps_allowed_stations_past_the_row_limit() {
+    let count = MAX_STATIONS as u32 + 4;
+    let mut map = WarehouseMap::new();
+    map.add_node(Node::new(
+        NodeId(0),
+        "N0".to_string(),
+        0.0,
+        0.0,
+        NodeType::Aisle,
+    ));
+    let mut stations = HashMap::new();
+    for id in 0..count {
+        let node = NodeId(id + 1);
+        map.add_node(Node::new(
+            node,
+            format!("N{}", node.0),
+            id as f64,
+            1.0,
+            NodeType::StationPick,
+        ));
+        let mut station = Station::new(
+            StationId(id),
+            format!("S{}", id),
+            node,
+            StationType::Pick,
+            1,
+            None,
+            ServiceTimeModel::default(),
+        );
+        // Only the two stations sorting last are open
+        station.open = id >= count - 2;
+        stations.insert(station.id, station);
+    }
+    let (robots, tasks, orders) = (HashMap::new(), HashMap::new(), HashMap::new());
+    let ctx = PolicyContext {
+        current_time: SimTime::ZERO,
+        map: &map,
+        robots: &robots,
+        tasks: &tasks,
+        stations: &stations,
+        orders: &orders,
+        attribution: None,
+        traffic: None,
+    };
+    let task = Task::new_pick(
+        TaskId(0),
+        OrderId(0),
+        SkuId(0),
+        1,
+        BinLocation::new(BinAddress::new(RackId(0), 0, 0), NodeId(0)),
+        StationId(0),
+        SimTime::ZERO,
+    );
+
+    let obs = build_station_observation(&ctx, &task, &ObservationSpec::default())
+        .expect("open stations to choose from");
+    assert_eq!(obs.candidate_station_ids.len(), MAX_STATIONS);
+    assert_eq!(obs.truncated_stations, 4);
+    assert!(obs
+        .candidate_station_ids
+        .windows(2)
+        .all(|w| w[0].0 < w[1].0));
+
+    let chosen: Vec<StationId> = obs
+        .candidate_station_ids
+        .iter()
+        .zip(&obs.station_mask)
+        .filter(|(_, &allowed)| allowed)
+        .map(|(id, _)| *id)
+        .collect();
+    assert_eq!(chosen, [StationId(count - 2), StationId(count - 1)]);
+}
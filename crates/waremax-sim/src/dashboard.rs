@@ -671,8 +671,10 @@ mod tests {
         assert!(receiver.try_recv().is_ok());
 
         // Disable robot moves
-        let mut filter = DashboardEventFilter::default();
-        filter.robot_moves = false;
+        let filter = DashboardEventFilter {
+            robot_moves: false,
+            ..Default::default()
+        };
         hook.set_filter(filter);
 
         hook.robot_moved(
@@ -5,6 +5,7 @@
 //! - Lines per order
 //! - SKU popularity/selection
 
+use dyn_clone::DynClone;
 use waremax_core::SimRng;
 
 // ============================================================================
@@ -12,34 +13,41 @@ use waremax_core::SimRng;
 // ============================================================================
 
 /// Generates inter-arrival times for orders
-pub trait ArrivalDistribution: Send + Sync {
+pub trait ArrivalDistribution: DynClone + Send + Sync {
     /// Distribution name for logging
     fn name(&self) -> &str;
     /// Generate next inter-arrival time in seconds
     fn next_interarrival(&self, rng: &mut SimRng) -> f64;
 }
 
+dyn_clone::clone_trait_object!(ArrivalDistribution);
+
 /// Generates number of lines per order
-pub trait LinesDistribution: Send + Sync {
+pub trait LinesDistribution: DynClone + Send + Sync {
     /// Distribution name for logging
     fn name(&self) -> &str;
     /// Generate next line count (always >= 1)
     fn next_lines(&self, rng: &mut SimRng) -> u32;
 }
 
+dyn_clone::clone_trait_object!(LinesDistribution);
+
 /// Generates SKU indices based on popularity
-pub trait SkuDistribution: Send + Sync {
+pub trait SkuDistribution: DynClone + Send + Sync {
     /// Distribution name for logging
     fn name(&self) -> &str;
     /// Generate next SKU index (0 to num_skus-1)
     fn next_sku(&self, rng: &mut SimRng, num_skus: u32) -> u32;
 }
 
+dyn_clone::clone_trait_object!(SkuDistribution);
+
 // ============================================================================
 // Arrival Distributions
 // ============================================================================
 
 /// Exponential inter-arrival times (Poisson process)
+#[derive(Clone)]
 pub struct ExponentialArrivals {
     rate_per_sec: f64,
 }
@@ -61,6 +69,7 @@ impl ArrivalDistribution for ExponentialArrivals {
 }
 
 /// Constant inter-arrival times (deterministic)
+#[derive(Clone)]
 pub struct ConstantArrivals {
     interval_sec: f64,
 }
@@ -86,6 +95,7 @@ impl ArrivalDistribution for ConstantArrivals {
 // ============================================================================
 
 /// Negative binomial distribution for lines per order
+#[derive(Clone)]
 pub struct NegBinomialLines {
     mean: f64,
     dispersion: f64,
@@ -108,6 +118,7 @@ impl LinesDistribution for NegBinomialLines {
 }
 
 /// Poisson distribution for lines per order
+#[derive(Clone)]
 pub struct PoissonLines {
     mean: f64,
 }
@@ -129,6 +140,7 @@ impl LinesDistribution for PoissonLines {
 }
 
 /// Constant number of lines per order
+#[derive(Clone)]
 pub struct ConstantLines {
     count: u32,
 }
@@ -156,6 +168,7 @@ impl LinesDistribution for ConstantLines {
 // ============================================================================
 
 /// Zipf distribution for SKU popularity (power-law)
+#[derive(Clone)]
 pub struct ZipfSkus {
     alpha: f64,
 }
@@ -179,6 +192,7 @@ impl SkuDistribution for ZipfSkus {
 }
 
 /// Uniform distribution for SKU selection (equal popularity)
+#[derive(Clone)]
 pub struct UniformSkus;
 
 impl UniformSkus {
@@ -211,6 +225,7 @@ impl SkuDistribution for UniformSkus {
 // ============================================================================
 
 /// Container for all distribution generators
+#[derive(Clone)]
 pub struct DistributionSet {
     pub arrivals: Box<dyn ArrivalDistribution>,
     pub lines: Box<dyn LinesDistribution>,
@@ -1,16 +1,23 @@
 //! Event handlers for simulation events
 
 use crate::world::World;
+use std::collections::{HashMap, HashSet};
 use waremax_analysis::DelayCategory;
 use waremax_core::{
-    ChargingStationId, Kernel, MaintenanceStationId, OrderId, RobotId, ScheduledEvent, SimEvent,
-    SimTime, TaskId,
+    ChargingStationId, HumanId, Kernel, MaintenanceStationId, NodeId, OrderId, RobotId,
+    ScheduledEvent, SimEvent, SimTime, SkuId, TaskId,
+};
+use waremax_entities::{
+    BinLocation, HumanState, HumanTaskSource, HumanTripPhase, Order, OrderLine, RobotState, Task,
+    TaskStatus, TaskType,
 };
-use waremax_entities::{BinLocation, Order, OrderLine, RobotState, Task};
 use waremax_map::ReservableResource;
-use waremax_metrics::{MetricsCollector, TraceDetails};
+use waremax_metrics::{MetricsCollector, PickLevelReport, TraceDetails};
+use waremax_policies::{BinSelectionContext, SlottingContext};
+use waremax_storage::BinAddress;
 
 /// Handles simulation events and produces new events
+#[derive(Clone)]
 pub struct EventHandler {
     /// Tracks assigned robots to avoid double-assignment in dispatch
     assigned_this_round: Vec<waremax_core::RobotId>,
@@ -200,31 +207,742 @@ impl EventHandler {
                     metrics,
                 );
             }
-            SimEvent::RobotPositionUpdate {
-                robot_id,
-                edge_id,
-                from_node,
-                to_node,
-                progress,
-            } => {
-                self.handle_robot_position_update(
-                    kernel,
-                    world,
-                    current_time,
-                    *robot_id,
-                    *edge_id,
-                    *from_node,
-                    *to_node,
-                    *progress,
-                );
+            SimEvent::RobotPositionUpdate {
+                robot_id,
+                edge_id,
+                from_node,
+                to_node,
+                progress,
+            } => {
+                self.handle_robot_position_update(
+                    kernel,
+                    world,
+                    current_time,
+                    *robot_id,
+                    *edge_id,
+                    *from_node,
+                    *to_node,
+                    *progress,
+                );
+            }
+            SimEvent::StationStaffingChange { station_id } => {
+                self.handle_station_staffing_change(kernel, world, current_time, *station_id);
+            }
+            SimEvent::RobotPickup {
+                robot_id,
+                task_id,
+                node_id,
+            } => {
+                self.handle_robot_pickup(
+                    kernel,
+                    world,
+                    current_time,
+                    *robot_id,
+                    *task_id,
+                    *node_id,
+                );
+            }
+            SimEvent::HumanTaskStart { human_id } => {
+                self.handle_human_task_start(kernel, world, current_time, *human_id);
+            }
+            SimEvent::HumanArriveNode {
+                human_id,
+                node_id,
+                from_node,
+            } => {
+                self.handle_human_arrive(
+                    kernel,
+                    world,
+                    current_time,
+                    *human_id,
+                    *node_id,
+                    *from_node,
+                );
+            }
+            SimEvent::HumanWorkEnd { human_id } => {
+                self.handle_human_work_end(kernel, world, current_time, *human_id);
+            }
+            SimEvent::HumanZoneUpdate => {
+                self.handle_human_zone_update(kernel, world, current_time);
+            }
+            SimEvent::SlottingReview => {
+                self.handle_slotting_review(kernel, world, current_time);
+            }
+            SimEvent::RobotDropOff {
+                robot_id,
+                task_id,
+                node_id,
+            } => {
+                self.handle_robot_drop_off(
+                    kernel,
+                    world,
+                    current_time,
+                    *robot_id,
+                    *task_id,
+                    *node_id,
+                    metrics,
+                );
+            }
+            _ => {
+                // Handle other events as needed (inbound/outbound flow - future)
+            }
+        }
+    }
+
+    /// v7: Apply a staffing change and schedule the next one
+    fn handle_station_staffing_change(
+        &self,
+        kernel: &mut Kernel,
+        world: &mut World,
+        current_time: SimTime,
+        station_id: waremax_core::StationId,
+    ) {
+        let next_change = match world.get_station_mut(station_id) {
+            Some(station) => match station.staffing.as_ref() {
+                Some(schedule) => {
+                    let operators = schedule.operators_at(current_time);
+                    let next = schedule.next_change_after(current_time);
+                    let shift_start = schedule.shift_starts_at(current_time);
+                    station.set_staffing(operators, current_time, shift_start);
+                    next
+                }
+                None => return,
+            },
+            None => return,
+        };
+
+        if let Some(next) = next_change {
+            kernel.schedule_at(next, SimEvent::StationStaffingChange { station_id });
+        }
+
+        self.start_queued_service(kernel, world, station_id);
+
+        // Stations opening or closing changes where tasks can be routed
+        kernel.schedule_now(SimEvent::DispatchTasks);
+    }
+
+    /// v7: Robot has reached the bin's level; record the pick and head to the station
+    fn handle_robot_pickup(
+        &self,
+        kernel: &mut Kernel,
+        world: &mut World,
+        current_time: SimTime,
+        robot_id: RobotId,
+        task_id: TaskId,
+        node_id: NodeId,
+    ) {
+        // Ignore stale pickups (robot failed or was reassigned meanwhile)
+        let still_picking = world.get_robot(robot_id).is_some_and(|r| {
+            r.current_task == Some(task_id)
+                && matches!(r.state, RobotState::PickingUp { at_node } if at_node == node_id)
+        });
+        let Some((task_type, sku_id, bin_address, destination_station, destination_bin)) = world
+            .get_task(task_id)
+            .map(|t| {
+                (
+                    t.task_type.clone(),
+                    t.sku_id,
+                    t.source.bin_address.clone(),
+                    t.destination_station,
+                    t.destination_bin.clone(),
+                )
+            })
+            .filter(|_| still_picking)
+        else {
+            return;
+        };
+
+        // v7: Re-slotting moves carry the bin to its new slot instead
+        if task_type == TaskType::Relocation {
+            if let Some(destination) = destination_bin {
+                self.carry_relocation(kernel, world, current_time, robot_id, task_id, destination);
+            }
+            return;
+        }
+
+        let access_time = world.bin_access_time(&bin_address);
+        let level = world
+            .level_picks
+            .entry(bin_address.level)
+            .or_insert_with(|| PickLevelReport {
+                level: bin_address.level,
+                ..Default::default()
+            });
+        level.picks += 1;
+        level.access_time_s += access_time;
+
+        let station_node = world
+            .get_station(destination_station)
+            .map_or(node_id, |s| s.node);
+        if let Some(robot) = world.get_robot_mut(robot_id) {
+            robot.update_stats(current_time);
+            robot.state = RobotState::Moving {
+                destination: station_node,
+            };
+        }
+
+        // v7: Observed picks drive SKU velocity for dynamic slotting
+        if let Some(slotting) = world.slotting.as_mut() {
+            if task_type == TaskType::Pick {
+                slotting
+                    .pick_log
+                    .push_back((current_time, sku_id, station_node));
+                *slotting.sku_picks.entry(sku_id).or_insert(0) += 1;
+            }
+        }
+
+        // At pickup - end TravelToPickup, start TravelToStation
+        if world.attribution_collector.is_enabled() {
+            // End travel-to-pickup phase, start travel-to-station
+            world.attribution_collector.start_phase(
+                task_id,
+                DelayCategory::TravelToStation,
+                current_time,
+            );
+        }
+
+        // Go to station
+        if let Some(robot_current) = world.get_robot(robot_id).map(|r| r.current_node) {
+            // v6: congestion-aware routing when enabled.
+            let route_opt = if world.congestion_routing {
+                world.router.find_route_with_traffic(
+                    &world.map,
+                    robot_current,
+                    station_node,
+                    &world.traffic,
+                )
+            } else {
+                world
+                    .router
+                    .find_route(&world.map, robot_current, station_node)
+            };
+            if let Some(route) = route_opt {
+                if let Some(robot) = world.get_robot_mut(robot_id) {
+                    robot.set_path(route.path);
+                }
+            }
+        }
+
+        // Continue to next node or arrive at station
+        if let Some(robot) = world.get_robot(robot_id) {
+            if let Some(next) = robot.next_node_in_path() {
+                if let Some((_, edge_id, _)) = world
+                    .map
+                    .neighbors(robot.current_node)
+                    .find(|(n, _, _)| *n == next)
+                {
+                    kernel.schedule_now(SimEvent::RobotDepartNode {
+                        robot_id,
+                        from_node: robot.current_node,
+                        to_node: next,
+                        edge_id,
+                    });
+                }
+            } else {
+                // At station - end travel phase, start queue/service phase
+                if world.attribution_collector.is_enabled() {
+                    world.attribution_collector.start_phase(
+                        task_id,
+                        DelayCategory::StationQueue,
+                        current_time,
+                    );
+                }
+                kernel.schedule_now(SimEvent::StationServiceStart {
+                    robot_id,
+                    station_id: destination_station,
+                    task_id,
+                });
+            }
+        }
+    }
+
+    /// v7: Robot holds a relocated bin's contents; take them to the new slot
+    fn carry_relocation(
+        &self,
+        kernel: &mut Kernel,
+        world: &mut World,
+        current_time: SimTime,
+        robot_id: RobotId,
+        task_id: TaskId,
+        destination: BinLocation,
+    ) {
+        if let Some(task) = world.get_task_mut(task_id) {
+            task.start_moving_to_station();
+        }
+        if let Some(robot) = world.get_robot_mut(robot_id) {
+            robot.update_stats(current_time);
+            robot.state = RobotState::Moving {
+                destination: destination.access_node,
+            };
+        }
+        if world.attribution_collector.is_enabled() {
+            world.attribution_collector.start_phase(
+                task_id,
+                DelayCategory::TravelToStation,
+                current_time,
+            );
+        }
+
+        if let Some(robot_current) = world.get_robot(robot_id).map(|r| r.current_node) {
+            let route_opt = if world.congestion_routing {
+                world.router.find_route_with_traffic(
+                    &world.map,
+                    robot_current,
+                    destination.access_node,
+                    &world.traffic,
+                )
+            } else {
+                world
+                    .router
+                    .find_route(&world.map, robot_current, destination.access_node)
+            };
+            if let Some(route) = route_opt {
+                if let Some(robot) = world.get_robot_mut(robot_id) {
+                    robot.set_path(route.path);
+                }
+            }
+        }
+
+        if let Some(robot) = world.get_robot(robot_id) {
+            if let Some(next) = robot.next_node_in_path() {
+                if let Some((_, edge_id, _)) = world
+                    .map
+                    .neighbors(robot.current_node)
+                    .find(|(n, _, _)| *n == next)
+                {
+                    kernel.schedule_now(SimEvent::RobotDepartNode {
+                        robot_id,
+                        from_node: robot.current_node,
+                        to_node: next,
+                        edge_id,
+                    });
+                }
+            } else {
+                // New slot is in the same rack
+                self.start_drop_off(kernel, world, current_time, robot_id, task_id, &destination);
+            }
+        }
+    }
+
+    /// v7: Robot reached the new slot's rack; reach its level and drop off
+    fn start_drop_off(
+        &self,
+        kernel: &mut Kernel,
+        world: &mut World,
+        current_time: SimTime,
+        robot_id: RobotId,
+        task_id: TaskId,
+        destination: &BinLocation,
+    ) {
+        let access_time = world.bin_access_time(&destination.bin_address);
+        let node_id = destination.access_node;
+        if let Some(robot) = world.get_robot_mut(robot_id) {
+            robot.update_stats(current_time);
+            robot.state = RobotState::PickingUp { at_node: node_id };
+        }
+        kernel.schedule_after(
+            SimTime::from_seconds(access_time),
+            SimEvent::RobotDropOff {
+                robot_id,
+                task_id,
+                node_id,
+            },
+        );
+    }
+
+    /// v7: Relocated contents are in their new slot; swap the bins and free the robot
+    #[allow(clippy::too_many_arguments)]
+    fn handle_robot_drop_off(
+        &self,
+        kernel: &mut Kernel,
+        world: &mut World,
+        current_time: SimTime,
+        robot_id: RobotId,
+        task_id: TaskId,
+        node_id: NodeId,
+        metrics: &mut MetricsCollector,
+    ) {
+        // Ignore stale drop-offs (robot failed or was reassigned meanwhile)
+        let still_dropping = world.get_robot(robot_id).is_some_and(|r| {
+            r.current_task == Some(task_id)
+                && matches!(r.state, RobotState::PickingUp { at_node } if at_node == node_id)
+        });
+        let Some((source, destination)) = world
+            .get_task(task_id)
+            .and_then(|t| {
+                t.destination_bin
+                    .as_ref()
+                    .map(|d| (t.source.bin_address.clone(), d.bin_address.clone()))
+            })
+            .filter(|_| still_dropping)
+        else {
+            return;
+        };
+
+        if world.inventory.swap_bins(&source, &destination).is_ok() {
+            if let Some(slotting) = world.slotting.as_mut() {
+                slotting.stats.moves_completed += 1;
+            }
+        }
+
+        if let Some(task) = world.get_task_mut(task_id) {
+            task.complete(current_time);
+        }
+        if world.attribution_collector.is_enabled() {
+            world
+                .attribution_collector
+                .complete_task(task_id, current_time);
+        }
+        world.reservation_manager.release_all(robot_id);
+        metrics.record_task_complete(robot_id);
+
+        world.trace_collector.record_sampled(
+            current_time,
+            "TaskComplete",
+            TraceDetails::TaskComplete {
+                task_id: task_id.0,
+                robot_id: robot_id.0,
+            },
+        );
+
+        if let Some(robot) = world.get_robot_mut(robot_id) {
+            robot.state = RobotState::Idle;
+            robot.complete_task();
+            robot.update_stats(current_time);
+        }
+        world.awaiting_idle_positioning.push(robot_id);
+
+        kernel.schedule_now(SimEvent::DispatchTasks);
+    }
+
+    /// v7: Recompute SKU velocity and queue re-slotting moves within the budget
+    fn handle_slotting_review(
+        &self,
+        kernel: &mut Kernel,
+        world: &mut World,
+        current_time: SimTime,
+    ) {
+        let Some(slotting) = world.slotting.as_mut() else {
+            return;
+        };
+
+        // Trailing-window velocity (picks/hour) and most frequent station per SKU
+        let window_start = current_time.as_seconds() - slotting.velocity_window_s;
+        while slotting
+            .pick_log
+            .front()
+            .is_some_and(|(t, _, _)| t.as_seconds() < window_start)
+        {
+            slotting.pick_log.pop_front();
+        }
+        let window_h =
+            (slotting.velocity_window_s.min(current_time.as_seconds()) / 3600.0).max(1e-9);
+        let mut velocity: HashMap<SkuId, f64> = HashMap::new();
+        let mut station_counts: HashMap<(SkuId, NodeId), u32> = HashMap::new();
+        for (_, sku_id, station_node) in &slotting.pick_log {
+            *velocity.entry(*sku_id).or_insert(0.0) += 1.0 / window_h;
+            *station_counts.entry((*sku_id, *station_node)).or_insert(0) += 1;
+        }
+        let mut station_affinity: HashMap<SkuId, (NodeId, u32)> = HashMap::new();
+        for ((sku_id, node), count) in station_counts {
+            let best = station_affinity.entry(sku_id).or_insert((node, count));
+            if count > best.1 || (count == best.1 && node.0 < best.0 .0) {
+                *best = (node, count);
+            }
+        }
+        let station_affinity: HashMap<SkuId, NodeId> = station_affinity
+            .into_iter()
+            .map(|(sku_id, (node, _))| (sku_id, node))
+            .collect();
+
+        // Accrue the move budget, capped at one hour's worth
+        slotting.move_credit = (slotting.move_credit
+            + slotting.moves_per_hour * slotting.interval_s / 3600.0)
+            .min(slotting.moves_per_hour.max(1.0));
+        let max_moves = slotting.move_credit.floor() as usize;
+        let interval_s = slotting.interval_s;
+
+        // Bins with work in flight stay put
+        let locked_bins: HashSet<BinAddress> = world
+            .tasks
+            .values()
+            .filter(|t| !t.is_complete())
+            .flat_map(|t| {
+                std::iter::once(t.source.bin_address.clone())
+                    .chain(t.destination_bin.as_ref().map(|d| d.bin_address.clone()))
+            })
+            .collect();
+        let rack_access_nodes = world.rack_access_nodes();
+        let station_nodes = world.pick_station_nodes();
+
+        let moves = match world.slotting.as_ref() {
+            Some(slotting) if max_moves > 0 => {
+                let ctx = SlottingContext {
+                    map: &world.map,
+                    inventory: &world.inventory,
+                    rack_access_nodes: &rack_access_nodes,
+                    station_nodes: &station_nodes,
+                    velocity: &velocity,
+                    station_affinity: &station_affinity,
+                    locked_bins: &locked_bins,
+                };
+                slotting.policy.plan_moves(&ctx, max_moves)
+            }
+            _ => Vec::new(),
+        };
+
+        for slot_move in &moves {
+            let source = rack_access_nodes
+                .get(&slot_move.from.rack_id)
+                .map(|&n| BinLocation::new(slot_move.from.clone(), n));
+            let destination = rack_access_nodes
+                .get(&slot_move.to.rack_id)
+                .map(|&n| BinLocation::new(slot_move.to.clone(), n));
+            if let (Some(source), Some(destination)) = (source, destination) {
+                let quantity = world.inventory.get_quantity(&slot_move.from).unwrap_or(0);
+                let task_id = world.next_task_id();
+                let task = Task::new_relocation(
+                    task_id,
+                    slot_move.sku_id,
+                    quantity,
+                    source,
+                    destination,
+                    current_time,
+                );
+                world.tasks.insert(task_id, task);
+                world.pending_tasks.push(task_id);
+            }
+        }
+
+        if let Some(slotting) = world.slotting.as_mut() {
+            slotting.move_credit -= moves.len() as f64;
+            slotting.stats.reviews += 1;
+            slotting.stats.moves_planned += moves.len() as u32;
+        }
+
+        kernel.schedule_after(SimTime::from_seconds(interval_s), SimEvent::SlottingReview);
+        if !moves.is_empty() {
+            kernel.schedule_now(SimEvent::DispatchTasks);
+        }
+    }
+
+    /// v7: A human agent picks its next manual task and sets off
+    fn handle_human_task_start(
+        &self,
+        kernel: &mut Kernel,
+        world: &mut World,
+        current_time: SimTime,
+        human_id: HumanId,
+    ) {
+        let Some(human) = world.get_human(human_id) else {
+            return;
+        };
+        if !human.is_idle() {
+            return;
+        }
+
+        let Some((bin, target_node)) = select_human_target(world, human_id) else {
+            // Nothing to do right now; check again after the usual gap
+            schedule_next_human_task(kernel, world, human_id);
+            return;
+        };
+
+        if let Some(human) = world.get_human_mut(human_id) {
+            human.target_bin = Some(bin);
+            human.phase = HumanTripPhase::ToTarget;
+        }
+        self.start_human_trip(kernel, world, current_time, human_id, target_node);
+    }
+
+    /// v7: A human agent reaches the next node of its path
+    fn handle_human_arrive(
+        &self,
+        kernel: &mut Kernel,
+        world: &mut World,
+        current_time: SimTime,
+        human_id: HumanId,
+        node_id: NodeId,
+        from_node: NodeId,
+    ) {
+        let edge_length = world
+            .map
+            .neighbors(from_node)
+            .find(|(n, _, _)| *n == node_id)
+            .and_then(|(_, edge_id, _)| world.map.get_edge(edge_id))
+            .map(|e| e.length_m)
+            .unwrap_or(0.0);
+
+        if let Some(human) = world.get_human_mut(human_id) {
+            human.current_node = node_id;
+            human.on_edge = None;
+            human.distance_m += edge_length;
+        }
+        self.advance_human(kernel, world, current_time, human_id);
+    }
+
+    /// v7: A human agent finishes work at its task location and heads home
+    fn handle_human_work_end(
+        &self,
+        kernel: &mut Kernel,
+        world: &mut World,
+        current_time: SimTime,
+        human_id: HumanId,
+    ) {
+        let Some(human) = world.get_human_mut(human_id) else {
+            return;
+        };
+        let target_bin = human.target_bin.take();
+        let task_source = human.task_source;
+        let quantity = human.quantity;
+        let home = human.home_node;
+        human.phase = HumanTripPhase::Returning;
+
+        // Manual picks take stock out of the bin; replenishment puts it back
+        if let Some(bin) = target_bin {
+            match task_source {
+                HumanTaskSource::ManualPick => {
+                    let available = world.inventory.get_quantity(&bin).unwrap_or(0);
+                    let _ = world.inventory.decrement(&bin, quantity.min(available));
+                }
+                HumanTaskSource::Replenishment => {
+                    let _ = world.inventory.increment(&bin, quantity);
+                }
+            }
+        }
+
+        self.start_human_trip(kernel, world, current_time, human_id, home);
+    }
+
+    /// v7: Apply human-only zone changes and schedule the next one
+    fn handle_human_zone_update(
+        &self,
+        kernel: &mut Kernel,
+        world: &mut World,
+        current_time: SimTime,
+    ) {
+        world.refresh_human_restrictions(current_time);
+
+        let next_change = world
+            .human_zones
+            .iter()
+            .filter_map(|z| z.next_change_after(current_time))
+            .min_by(|a, b| a.as_seconds().total_cmp(&b.as_seconds()));
+        if let Some(next) = next_change {
+            kernel.schedule_at(next, SimEvent::HumanZoneUpdate);
+        }
+    }
+
+    /// v7: Route a human agent to `destination` and start walking
+    fn start_human_trip(
+        &self,
+        kernel: &mut Kernel,
+        world: &mut World,
+        current_time: SimTime,
+        human_id: HumanId,
+        destination: NodeId,
+    ) {
+        let Some(from) = world.get_human(human_id).map(|h| h.current_node) else {
+            return;
+        };
+        let path: Vec<NodeId> = world
+            .router
+            .find_route(&world.map, from, destination)
+            .map(|route| route.path.into_iter().skip(1).collect())
+            .unwrap_or_default();
+
+        if let Some(human) = world.get_human_mut(human_id) {
+            human.path = path.into();
+            human.state = HumanState::Walking { destination };
+        }
+        self.advance_human(kernel, world, current_time, human_id);
+    }
+
+    /// v7: Move a human agent onto the next edge of its path, or handle
+    /// arrival at the end of it
+    fn advance_human(
+        &self,
+        kernel: &mut Kernel,
+        world: &mut World,
+        current_time: SimTime,
+        human_id: HumanId,
+    ) {
+        let Some(human) = world.get_human(human_id) else {
+            return;
+        };
+        let current = human.current_node;
+        let next_leg = human.path.front().copied().and_then(|next| {
+            world
+                .map
+                .neighbors(current)
+                .find(|(n, _, _)| *n == next)
+                .and_then(|(_, edge_id, _)| world.map.get_edge(edge_id).map(|e| (edge_id, e)))
+                .map(|(edge_id, edge)| (next, edge_id, human.travel_time(edge.length_m)))
+        });
+
+        if let Some((next, edge_id, travel_time)) = next_leg {
+            if let Some(human) = world.get_human_mut(human_id) {
+                human.path.pop_front();
+                human.on_edge = Some((edge_id, current, next));
+                human.walking_time += travel_time;
+            }
+            kernel.schedule_after(
+                travel_time,
+                SimEvent::HumanArriveNode {
+                    human_id,
+                    node_id: next,
+                    from_node: current,
+                },
+            );
+        } else if let Some(human) = world.get_human_mut(human_id) {
+            // End of path (or no route): the agent is at its destination
+            human.path.clear();
+            match human.phase {
+                HumanTripPhase::ToTarget => {
+                    let work_time = SimTime::from_seconds(human.work_time_s);
+                    human.state = HumanState::Working {
+                        at_node: human.current_node,
+                    };
+                    human.working_time += work_time;
+                    kernel.schedule_after(work_time, SimEvent::HumanWorkEnd { human_id });
+                }
+                HumanTripPhase::Returning => {
+                    human.state = HumanState::Idle;
+                    human.tasks_completed += 1;
+                    world.human_stats.tasks_completed += 1;
+                    schedule_next_human_task(kernel, world, human_id);
+                }
             }
-            _ => {
-                // Handle other events as needed (inbound/outbound flow - future)
+        }
+
+        world.refresh_human_restrictions(current_time);
+    }
+
+    /// v7: Start service for queued robots while the station has free capacity
+    ///
+    /// Public so a session can resume service at a station it reopens
+    pub fn start_queued_service(
+        &self,
+        kernel: &mut Kernel,
+        world: &mut World,
+        station_id: waremax_core::StationId,
+    ) {
+        while let Some(next_robot_id) = world
+            .get_station_mut(station_id)
+            .and_then(|s| s.start_service())
+        {
+            if let Some(next_task_id) = world.get_robot(next_robot_id).and_then(|r| r.current_task)
+            {
+                kernel.schedule_now(SimEvent::StationServiceStart {
+                    robot_id: next_robot_id,
+                    station_id,
+                    task_id: next_task_id,
+                });
             }
         }
     }
 
     /// v4: Handle robot position update during edge traversal
+    #[allow(clippy::too_many_arguments)]
     fn handle_robot_position_update(
         &self,
         kernel: &mut Kernel,
@@ -411,6 +1129,10 @@ impl EventHandler {
         task_id: waremax_core::TaskId,
         robot_id: waremax_core::RobotId,
     ) {
+        // v7: Abandon any idle repositioning trip. If the robot is mid-edge,
+        // plan from the node it is about to reach instead of its last node.
+        let arriving_at = self.cancel_repositioning(kernel, world, current_time, robot_id);
+
         // Assign task to robot
         if let Some(task) = world.get_task_mut(task_id) {
             task.assign(robot_id, current_time);
@@ -442,30 +1164,41 @@ impl EventHandler {
             },
         );
 
-        // v6: Smart pickup-bin selection — re-pick the in-stock replica bin that
-        // minimizes robot->bin + bin->station travel for the assigned robot.
-        if world.smart_bin_selection {
-            let info = world.get_task(task_id).and_then(|t| {
-                world
-                    .get_robot(robot_id)
-                    .map(|r| (t.sku_id, t.quantity, t.destination_station, r.current_node))
+        // v6: Smart pickup-bin selection — re-pick the in-stock replica bin for
+        // the assigned robot. v7: The choice is delegated to the bin selection
+        // policy (nearest = minimize robot->bin + bin->station travel).
+        let info = world
+            .get_task(task_id)
+            .filter(|t| t.task_type != TaskType::Relocation)
+            .and_then(|t| {
+                world.get_robot(robot_id).map(|r| {
+                    (
+                        t.sku_id,
+                        t.quantity,
+                        t.destination_station,
+                        r.current_node,
+                        r.max_speed_mps,
+                    )
+                })
             });
-            if let Some((sku_id, qty, station_id, robot_node)) = info {
-                if let Some(station_node) = world.get_station(station_id).map(|s| s.node) {
-                    let bins = world.find_sku_bins(sku_id, qty);
-                    let best = bins.into_iter().min_by(|a, b| {
-                        let ca = world.map.euclidean_distance(robot_node, a.1)
-                            + world.map.euclidean_distance(a.1, station_node);
-                        let cb = world.map.euclidean_distance(robot_node, b.1)
-                            + world.map.euclidean_distance(b.1, station_node);
-                        ca.partial_cmp(&cb)
-                            .unwrap_or(std::cmp::Ordering::Equal)
-                            .then(a.1 .0.cmp(&b.1 .0))
-                    });
-                    if let Some((bin_addr, bin_node)) = best {
-                        if let Some(task) = world.get_task_mut(task_id) {
-                            task.source = waremax_entities::BinLocation::new(bin_addr, bin_node);
-                        }
+        if let Some((sku_id, qty, station_id, robot_node, robot_speed)) = info {
+            if let Some(station_node) = world.get_station(station_id).map(|s| s.node) {
+                let candidates = world.pickup_bin_candidates(sku_id, qty);
+                let ctx = BinSelectionContext {
+                    map: &world.map,
+                    robot_node,
+                    station_node,
+                    robot_speed_mps: robot_speed,
+                    quantity: qty,
+                };
+                let selected = world
+                    .policies
+                    .bin_selection
+                    .select_bin(&ctx, &candidates)
+                    .and_then(|idx| candidates.into_iter().nth(idx));
+                if let Some(best) = selected {
+                    if let Some(task) = world.get_task_mut(task_id) {
+                        task.source = BinLocation::new(best.address, best.access_node);
                     }
                 }
             }
@@ -476,7 +1209,11 @@ impl EventHandler {
             let task = world.get_task(task_id);
             let robot = world.get_robot(robot_id);
             match (task, robot) {
-                (Some(t), Some(r)) => Some((t.source.access_node, r.current_node, r.max_speed_mps)),
+                (Some(t), Some(r)) => Some((
+                    t.source.access_node,
+                    arriving_at.unwrap_or(r.current_node),
+                    r.max_speed_mps,
+                )),
                 _ => None,
             }
         };
@@ -525,8 +1262,17 @@ impl EventHandler {
                     }
                 }
 
+                // v7: Mid-edge robots keep their pending arrival; the path
+                // starts at the node they are leaving so it advances onto the route.
                 if let Some(robot) = world.get_robot_mut(robot_id) {
-                    robot.set_path(route.path);
+                    let mut path = route.path;
+                    if arriving_at.is_some() {
+                        path.insert(0, robot.current_node);
+                    }
+                    robot.set_path(path);
+                }
+                if arriving_at.is_some() {
+                    return;
                 }
                 // Start movement
                 if let Some(robot) = world.get_robot(robot_id) {
@@ -572,6 +1318,12 @@ impl EventHandler {
         edge_id: waremax_core::EdgeId,
         _metrics: &mut MetricsCollector,
     ) {
+        // v7: Route around edges and nodes closed while the robot was en route
+        if world.map.is_blocked(edge_id, to_node) {
+            self.reroute_around_closure(kernel, world, robot_id, from_node, to_node, edge_id);
+            return;
+        }
+
         // v4: Use edge traffic policy for entry check
         if !world
             .policies
@@ -596,6 +1348,12 @@ impl EventHandler {
                 }
             }
 
+            // v7: Count robots held back by human safety zones
+            if world.traffic.blocked_by_humans(edge_id, to_node) {
+                world.human_stats.robot_yield_events += 1;
+                world.human_stats.robot_yield_wait_s += 0.5;
+            }
+
             // v2: Record wait in wait-for graph for deadlock detection
             world.traffic.record_edge_wait(robot_id, edge_id);
 
@@ -605,7 +1363,7 @@ impl EventHandler {
             }
 
             // Schedule wait and retry
-            kernel.schedule_after(
+            let retry_id = kernel.schedule_after(
                 SimTime::from_seconds(0.5),
                 SimEvent::RobotDepartNode {
                     robot_id,
@@ -614,6 +1372,10 @@ impl EventHandler {
                     edge_id,
                 },
             );
+            // v7: Keep the repositioning trip cancelable while it waits
+            if world.repositioning_departs.contains_key(&robot_id) {
+                world.repositioning_departs.insert(robot_id, retry_id);
+            }
             return;
         }
 
@@ -640,6 +1402,17 @@ impl EventHandler {
             .map(|e| e.length_m)
             .unwrap_or(0.0);
 
+        // v7: Account deadhead travel of idle repositioning trips
+        world.repositioning_departs.remove(&robot_id);
+        let deadhead_time = world
+            .get_robot(robot_id)
+            .filter(|r| r.is_repositioning())
+            .map(|r| r.travel_time(edge_length).as_seconds());
+        if let Some(time_s) = deadhead_time {
+            world.repositioning_stats.deadhead_distance_m += edge_length;
+            world.repositioning_stats.deadhead_time_s += time_s;
+        }
+
         // Update robot state and consume battery
         if let Some(robot) = world.get_robot_mut(robot_id) {
             robot.state = RobotState::Moving {
@@ -660,7 +1433,15 @@ impl EventHandler {
         // Calculate travel time and schedule arrival
         if let (Some(robot), Some(edge)) = (world.get_robot(robot_id), world.map.get_edge(edge_id))
         {
-            let travel_time = robot.travel_time(edge.length_m);
+            let mut travel_time = robot.travel_time(edge.length_m);
+
+            // v7: Slow down near human workers
+            if let Some(factor) = world.traffic.human_slowdown(from_node, to_node) {
+                let slowed = SimTime::from_seconds(travel_time.as_seconds() / factor);
+                world.human_stats.robot_slowdowns += 1;
+                world.human_stats.robot_slowdown_delay_s += (slowed - travel_time).as_seconds();
+                travel_time = slowed;
+            }
             let arrival_time = current_time + travel_time;
 
             // v4: If using continuous policy, schedule position updates
@@ -679,7 +1460,7 @@ impl EventHandler {
                                 progress: progress.clamp(0.0, 1.0),
                             },
                         );
-                        next_time = next_time + SimTime::from_seconds(interval);
+                        next_time += SimTime::from_seconds(interval);
                     }
             }
 
@@ -694,6 +1475,63 @@ impl EventHandler {
         }
     }
 
+    /// v7: Replan the rest of a robot's path from `from_node` after its next
+    /// leg was closed. Without an open route the robot waits and retries.
+    fn reroute_around_closure(
+        &self,
+        kernel: &mut Kernel,
+        world: &mut World,
+        robot_id: RobotId,
+        from_node: NodeId,
+        to_node: NodeId,
+        edge_id: waremax_core::EdgeId,
+    ) {
+        let destination = world
+            .get_robot(robot_id)
+            .and_then(|r| r.current_path.last().copied())
+            .unwrap_or(to_node);
+        let detour = world
+            .router
+            .find_route(&world.map, from_node, destination)
+            .filter(|route| route.path.len() > 1)
+            .and_then(|route| {
+                let next = route.path[1];
+                world
+                    .map
+                    .edge_between(from_node, next)
+                    .map(|edge| (route.path, next, edge))
+            });
+
+        let (event, delay) = match detour {
+            Some((path, next, edge)) => {
+                if let Some(robot) = world.get_robot_mut(robot_id) {
+                    robot.set_path(path);
+                }
+                let depart = SimEvent::RobotDepartNode {
+                    robot_id,
+                    from_node,
+                    to_node: next,
+                    edge_id: edge,
+                };
+                (depart, SimTime::ZERO)
+            }
+            None => {
+                let retry = SimEvent::RobotDepartNode {
+                    robot_id,
+                    from_node,
+                    to_node,
+                    edge_id,
+                };
+                (retry, SimTime::from_seconds(0.5))
+            }
+        };
+        let event_id = kernel.schedule_after(delay, event);
+        // Keep a repositioning trip cancelable
+        if world.repositioning_departs.contains_key(&robot_id) {
+            world.repositioning_departs.insert(robot_id, event_id);
+        }
+    }
+
     fn handle_robot_arrive(
         &self,
         kernel: &mut Kernel,
@@ -703,12 +1541,8 @@ impl EventHandler {
         node_id: waremax_core::NodeId,
         from_node: waremax_core::NodeId,
     ) {
-        // Leave the edge we just traversed
-        if let Some((_, edge_id, _)) = world
-            .map
-            .neighbors(from_node)
-            .find(|(n, _, _)| *n == node_id)
-        {
+        // Leave the edge we just traversed (v7: even if it was closed meanwhile)
+        if let Some(edge_id) = world.map.edge_between(from_node, node_id) {
             // v4: Use edge traffic policy for leave
             world
                 .policies
@@ -753,78 +1587,45 @@ impl EventHandler {
             // Check what to do at this destination
             if let Some(task_id) = current_task {
                 // Extract task info first to avoid borrow conflicts
-                let task_info = world
-                    .get_task(task_id)
-                    .map(|task| (task.source.access_node, task.destination_station));
-
-                if let Some((source_node, destination_station)) = task_info {
-                    if node_id == source_node {
-                        // At pickup - end TravelToPickup, start TravelToStation
-                        if world.attribution_collector.is_enabled() {
-                            // End travel-to-pickup phase, start travel-to-station
-                            world.attribution_collector.start_phase(
-                                task_id,
-                                DelayCategory::TravelToStation,
-                                current_time,
-                            );
-                        }
-
-                        // Go to station
-                        let station_node = world.get_station(destination_station).map(|s| s.node);
-                        let robot_current = world.get_robot(robot_id).map(|r| r.current_node);
-
-                        if let (Some(station_node), Some(robot_current)) =
-                            (station_node, robot_current)
-                        {
-                            // v6: congestion-aware routing when enabled.
-                            let route_opt = if world.congestion_routing {
-                                world.router.find_route_with_traffic(
-                                    &world.map,
-                                    robot_current,
-                                    station_node,
-                                    &world.traffic,
-                                )
-                            } else {
-                                world.router.find_route(&world.map, robot_current, station_node)
-                            };
-                            if let Some(route) = route_opt {
-                                if let Some(robot) = world.get_robot_mut(robot_id) {
-                                    robot.set_path(route.path);
-                                }
-                            }
-                        }
+                let task_info = world.get_task(task_id).map(|task| {
+                    (
+                        task.source.access_node,
+                        task.destination_station,
+                        // v7: A relocation carrying its bin heads for the new slot
+                        task.destination_bin
+                            .clone()
+                            .filter(|_| task.status == TaskStatus::MovingToStation),
+                    )
+                });
 
-                        // Continue to next node or arrive at station
-                        if let Some(robot) = world.get_robot(robot_id) {
-                            if let Some(next) = robot.next_node_in_path() {
-                                if let Some((_, edge_id, _)) = world
-                                    .map
-                                    .neighbors(robot.current_node)
-                                    .find(|(n, _, _)| *n == next)
-                                {
-                                    kernel.schedule_now(SimEvent::RobotDepartNode {
-                                        robot_id,
-                                        from_node: robot.current_node,
-                                        to_node: next,
-                                        edge_id,
-                                    });
-                                }
-                            } else {
-                                // At station - end travel phase, start queue/service phase
-                                if world.attribution_collector.is_enabled() {
-                                    world.attribution_collector.start_phase(
-                                        task_id,
-                                        DelayCategory::StationQueue,
-                                        current_time,
-                                    );
-                                }
-                                kernel.schedule_now(SimEvent::StationServiceStart {
-                                    robot_id,
-                                    station_id: destination_station,
-                                    task_id,
-                                });
-                            }
+                if let Some((source_node, destination_station, carrying_to)) = task_info {
+                    if let Some(destination) = carrying_to {
+                        self.start_drop_off(
+                            kernel,
+                            world,
+                            current_time,
+                            robot_id,
+                            task_id,
+                            &destination,
+                        );
+                    } else if node_id == source_node {
+                        // v7: Reach the bin's rack level before heading to the station
+                        let access_time = world
+                            .get_task(task_id)
+                            .map(|t| world.bin_access_time(&t.source.bin_address))
+                            .unwrap_or(0.0);
+                        if let Some(robot) = world.get_robot_mut(robot_id) {
+                            robot.update_stats(current_time);
+                            robot.state = RobotState::PickingUp { at_node: node_id };
                         }
+                        kernel.schedule_after(
+                            SimTime::from_seconds(access_time),
+                            SimEvent::RobotPickup {
+                                robot_id,
+                                task_id,
+                                node_id,
+                            },
+                        );
                     } else {
                         // At station - end travel phase, start queue/service phase
                         if world.attribution_collector.is_enabled() {
@@ -841,21 +1642,29 @@ impl EventHandler {
                         });
                     }
                 }
+            } else if let Some(robot) = world.get_robot_mut(robot_id) {
+                // v7: Repositioning trip complete - wait here for work
+                if robot.repositioning_to == Some(node_id) {
+                    robot.repositioning_to = None;
+                    robot.update_stats(current_time);
+                    robot.state = RobotState::Idle;
+                    world.repositioning_stats.trips_completed += 1;
+                }
             }
         } else if let Some(next) = next_node {
-            // Continue along path
+            // Continue along path (v7: a closed leg is rerouted on departure)
             if let Some(robot) = world.get_robot(robot_id) {
-                if let Some((_, edge_id, _)) = world
-                    .map
-                    .neighbors(robot.current_node)
-                    .find(|(n, _, _)| *n == next)
-                {
-                    kernel.schedule_now(SimEvent::RobotDepartNode {
+                if let Some(edge_id) = world.map.edge_between(robot.current_node, next) {
+                    let repositioning = robot.is_repositioning();
+                    let event_id = kernel.schedule_now(SimEvent::RobotDepartNode {
                         robot_id,
                         from_node: robot.current_node,
                         to_node: next,
                         edge_id,
                     });
+                    if repositioning {
+                        world.repositioning_departs.insert(robot_id, event_id);
+                    }
                 }
             }
         }
@@ -904,25 +1713,23 @@ impl EventHandler {
                 );
             }
 
-            // Get task quantity and bin location for service time calculation
-            let (item_count, level_access_time) = world
-                .get_task(task_id)
-                .map(|t| {
-                    let access_time = world
-                        .racks
-                        .get(&t.source.bin_address.rack_id)
-                        .map(|rack| rack.access_time(t.source.bin_address.level))
-                        .unwrap_or(0.0);
-                    (t.quantity, access_time)
-                })
-                .unwrap_or((1, 0.0));
+            // Get task quantity for service time calculation
+            let item_count = world.get_task(task_id).map(|t| t.quantity).unwrap_or(1);
 
-            // Calculate service time based on item count plus level access time
-            let base_service_time = world
+            // Calculate service time based on item count (v7: level access
+            // time is charged at the rack, see handle_robot_pickup)
+            let mut service_time = world
                 .get_station(station_id)
                 .map(|s| s.service_time.calculate(item_count))
                 .unwrap_or(SimTime::from_seconds(10.0));
-            let service_time = base_service_time + SimTime::from_seconds(level_access_time);
+
+            // v7: Operator learning/fatigue scales the duration; remember it for service end
+            if let Some(station) = world.get_station_mut(station_id) {
+                service_time = SimTime::from_seconds(
+                    service_time.as_seconds() * station.service_multiplier(current_time),
+                );
+                station.record_service_duration(robot_id, service_time);
+            }
 
             kernel.schedule_after(
                 service_time,
@@ -951,23 +1758,18 @@ impl EventHandler {
         task_id: waremax_core::TaskId,
         metrics: &mut MetricsCollector,
     ) {
-        // Get service time from task quantity plus level access time
-        let (item_count, level_access_time) = world
-            .get_task(task_id)
-            .map(|t| {
-                let access_time = world
-                    .racks
-                    .get(&t.source.bin_address.rack_id)
-                    .map(|rack| rack.access_time(t.source.bin_address.level))
-                    .unwrap_or(0.0);
-                (t.quantity, access_time)
-            })
-            .unwrap_or((1, 0.0));
-        let base_service_time = world
-            .get_station(station_id)
-            .map(|s| s.service_time.calculate(item_count))
-            .unwrap_or(SimTime::from_seconds(10.0));
-        let service_time = base_service_time + SimTime::from_seconds(level_access_time);
+        // v7: Use the duration decided at service start; fall back to
+        // recomputing it from task quantity
+        let recorded = world
+            .get_station_mut(station_id)
+            .and_then(|s| s.take_service_duration(robot_id));
+        let service_time = recorded.unwrap_or_else(|| {
+            let item_count = world.get_task(task_id).map(|t| t.quantity).unwrap_or(1);
+            world
+                .get_station(station_id)
+                .map(|s| s.service_time.calculate(item_count))
+                .unwrap_or(SimTime::from_seconds(10.0))
+        });
 
         // Complete service with actual service time
         if let Some(station) = world.get_station_mut(station_id) {
@@ -1072,6 +1874,9 @@ impl EventHandler {
             robot.update_stats(current_time);
         }
 
+        // v7: Decide where the robot waits once dispatch has had a chance at it
+        world.awaiting_idle_positioning.push(robot_id);
+
         // Try to start next robot in queue
         let next_robot = world
             .get_station_mut(station_id)
@@ -1103,6 +1908,7 @@ impl EventHandler {
         // Get pending tasks
         let mut pending: Vec<TaskId> = world.pending_tasks.clone();
         if pending.is_empty() {
+            self.position_idle_robots(kernel, world, current_time);
             return;
         }
 
@@ -1167,6 +1973,110 @@ impl EventHandler {
         for task_id in tasks_to_remove {
             world.pending_tasks.retain(|&t| t != task_id);
         }
+
+        self.position_idle_robots(kernel, world, current_time);
+    }
+
+    // ==========================================================================
+    // v7: Idle positioning
+    // ==========================================================================
+
+    /// Run the idle positioning policy for robots that finished a task and
+    /// were not handed new work by the dispatch round that just ran.
+    fn position_idle_robots(&self, kernel: &mut Kernel, world: &mut World, current_time: SimTime) {
+        let awaiting = std::mem::take(&mut world.awaiting_idle_positioning);
+        for robot_id in awaiting {
+            if self.assigned_this_round.contains(&robot_id) {
+                continue;
+            }
+            let ready = world
+                .get_robot(robot_id)
+                .map(|r| r.is_idle() && r.is_available())
+                .unwrap_or(false);
+            if !ready {
+                continue;
+            }
+
+            let target = {
+                let ctx = world.idle_positioning_context(current_time);
+                world
+                    .policies
+                    .idle_positioning
+                    .select_parking(&ctx, robot_id)
+            };
+            let Some(target) = target else {
+                continue;
+            };
+
+            let Some(from_node) = world.get_robot(robot_id).map(|r| r.current_node) else {
+                continue;
+            };
+            let Some(route) = world.router.find_route(&world.map, from_node, target) else {
+                continue;
+            };
+            if route.path.len() < 2 {
+                continue;
+            }
+            let next_node = route.path[1];
+            let Some((_, edge_id, _)) = world
+                .map
+                .neighbors(from_node)
+                .find(|(n, _, _)| *n == next_node)
+            else {
+                continue;
+            };
+
+            if let Some(robot) = world.get_robot_mut(robot_id) {
+                robot.set_path(route.path);
+                robot.repositioning_to = Some(target);
+            }
+            world.repositioning_stats.trips_started += 1;
+
+            let event_id = kernel.schedule_now(SimEvent::RobotDepartNode {
+                robot_id,
+                from_node,
+                to_node: next_node,
+                edge_id,
+            });
+            world.repositioning_departs.insert(robot_id, event_id);
+        }
+    }
+
+    /// Abandon a robot's repositioning trip, if it is on one.
+    ///
+    /// A trip that has not left its node yet is stopped in place. A robot
+    /// already on an edge cannot turn around, so the node its pending arrival
+    /// lands on is returned for the caller to plan onward from.
+    ///
+    /// v7: Public so a session can withdraw a robot that is about to park
+    pub fn cancel_repositioning(
+        &self,
+        kernel: &mut Kernel,
+        world: &mut World,
+        current_time: SimTime,
+        robot_id: RobotId,
+    ) -> Option<waremax_core::NodeId> {
+        let robot = world.get_robot_mut(robot_id)?;
+        robot.repositioning_to.take()?;
+        world.repositioning_stats.trips_canceled += 1;
+
+        if let Some(event_id) = world.repositioning_departs.remove(&robot_id) {
+            kernel.cancel(event_id);
+            world.traffic.clear_wait(robot_id);
+            if let Some(robot) = world.get_robot_mut(robot_id) {
+                robot.update_stats(current_time);
+                robot.state = RobotState::Idle;
+            }
+            return None;
+        }
+
+        let robot = world.get_robot(robot_id)?;
+        match robot.state {
+            RobotState::Moving { destination } if destination != robot.current_node => {
+                Some(destination)
+            }
+            _ => None,
+        }
     }
 
     // ==========================================================================
@@ -1236,7 +2146,7 @@ impl EventHandler {
         &self,
         kernel: &mut Kernel,
         world: &mut World,
-        _current_time: SimTime,
+        current_time: SimTime,
         robot_id: RobotId,
     ) {
         // Mark robot as seeking charging
@@ -1247,8 +2157,14 @@ impl EventHandler {
             robot.seeking_charging = true;
         }
 
+        // v7: Charging takes precedence over idle repositioning. If the robot
+        // is mid-edge, head for the charger from the node it is about to reach.
+        let arriving_at = self.cancel_repositioning(kernel, world, current_time, robot_id);
+
         // Find nearest available charging station
-        let robot_node = world.get_robot(robot_id).map(|r| r.current_node);
+        let robot_node = world
+            .get_robot(robot_id)
+            .map(|r| arriving_at.unwrap_or(r.current_node));
         if let Some(from_node) = robot_node {
             if let Some(charging_station_id) = world.find_nearest_charging_station(from_node) {
                 // Route robot to charging station
@@ -1259,12 +2175,20 @@ impl EventHandler {
                 if let Some(dest_node) = station_node {
                     if let Some(route) = world.router.find_route(&world.map, from_node, dest_node) {
                         if let Some(robot) = world.get_robot_mut(robot_id) {
-                            robot.set_path(route.path);
+                            // The pending arrival continues along the new path
+                            let mut path = route.path;
+                            if arriving_at.is_some() {
+                                path.insert(0, robot.current_node);
+                            }
+                            robot.set_path(path);
                             robot.assigned_charging_station = Some(charging_station_id);
                             robot.state = RobotState::SeekingCharge {
                                 destination: charging_station_id,
                             };
                         }
+                        if arriving_at.is_some() {
+                            return;
+                        }
 
                         // Start movement to charging station
                         if let Some(robot) = world.get_robot(robot_id) {
@@ -1455,6 +2379,8 @@ impl EventHandler {
     ) {
         use waremax_policies::{DeadlockContext, DeadlockResolution};
 
+        world.traffic.record_deadlock();
+
         // Build context for the resolver
         let mut ctx = DeadlockContext::new(cycle.clone());
 
@@ -1468,11 +2394,13 @@ impl EventHandler {
                 // Use task priority based on task type
                 if let Some(task_id) = robot.current_task {
                     if let Some(task) = world.get_task(task_id) {
-                        // Derive priority from task type (Pick=1, Putaway=2, Replenishment=3)
+                        // Derive priority from task type (Pick=1, Putaway=2, Replenishment=3,
+                        // Relocation=4)
                         let priority = match task.task_type {
                             waremax_entities::TaskType::Pick => 1,
                             waremax_entities::TaskType::Putaway => 2,
                             waremax_entities::TaskType::Replenishment => 3,
+                            waremax_entities::TaskType::Relocation => 4,
                         };
                         ctx = ctx.with_priority(robot_id, priority);
                     }
@@ -1573,6 +2501,10 @@ impl EventHandler {
         interrupted_task: Option<TaskId>,
         metrics: &mut MetricsCollector,
     ) {
+        // v7: A failed robot no longer repositions. If it is mid-edge, it
+        // heads for repair from the node it is about to reach.
+        let arriving_at = self.cancel_repositioning(kernel, world, current_time, robot_id);
+
         // Mark robot as failed
         if let Some(robot) = world.get_robot_mut(robot_id) {
             robot.update_stats(current_time);
@@ -1606,7 +2538,9 @@ impl EventHandler {
         );
 
         // Find nearest maintenance station for repair
-        let robot_node = world.get_robot(robot_id).map(|r| r.current_node);
+        let robot_node = world
+            .get_robot(robot_id)
+            .map(|r| arriving_at.unwrap_or(r.current_node));
         if let Some(from_node) = robot_node {
             if let Some(station_id) = world.find_nearest_maintenance_station(from_node) {
                 // Route robot to maintenance station for repair
@@ -1614,7 +2548,12 @@ impl EventHandler {
                 if let Some(dest_node) = station_node {
                     if let Some(route) = world.router.find_route(&world.map, from_node, dest_node) {
                         if let Some(robot) = world.get_robot_mut(robot_id) {
-                            robot.set_path(route.path.clone());
+                            // The pending arrival continues along the new path
+                            let mut path = route.path.clone();
+                            if arriving_at.is_some() {
+                                path.insert(0, robot.current_node);
+                            }
+                            robot.set_path(path);
                             robot.state = RobotState::SeekingMaintenance {
                                 destination: station_id,
                                 is_repair: true,
@@ -1622,6 +2561,9 @@ impl EventHandler {
                             robot.seeking_maintenance = true;
                             robot.assigned_maintenance_station = Some(station_id);
                         }
+                        if arriving_at.is_some() {
+                            return;
+                        }
 
                         // Schedule first depart event if robot can move
                         if route.path.len() > 1 {
@@ -1867,6 +2809,68 @@ impl EventHandler {
     }
 }
 
+/// v7: Schedule a human agent's next task after an exponential idle gap
+pub fn schedule_next_human_task(kernel: &mut Kernel, world: &mut World, human_id: HumanId) {
+    let Some(mean) = world.get_human(human_id).map(|h| h.task_interval_s) else {
+        return;
+    };
+    let delay = if mean > 0.0 {
+        world.rng.exponential(1.0 / mean)
+    } else {
+        0.0
+    };
+    kernel.schedule_after(
+        SimTime::from_seconds(delay),
+        SimEvent::HumanTaskStart { human_id },
+    );
+}
+
+/// v7: Choose the bin (and its access node) for a human agent's next task
+///
+/// Manual pickers draw a random stocked bin; replenishment goes to the
+/// lowest-stocked bin not already claimed by another agent.
+fn select_human_target(world: &mut World, human_id: HumanId) -> Option<(BinAddress, NodeId)> {
+    let human = world.get_human(human_id)?;
+    let quantity = human.quantity;
+    let task_source = human.task_source;
+    let claimed: Vec<&BinAddress> = world
+        .humans
+        .values()
+        .filter(|h| h.id != human_id)
+        .filter_map(|h| h.target_bin.as_ref())
+        .collect();
+
+    let bin = match task_source {
+        HumanTaskSource::ManualPick => {
+            let stocked: Vec<BinAddress> = world
+                .inventory
+                .all_bins()
+                .iter()
+                .filter(|addr| {
+                    world
+                        .inventory
+                        .get_quantity(addr)
+                        .is_some_and(|q| q >= quantity)
+                })
+                .filter(|addr| !claimed.contains(addr))
+                .cloned()
+                .collect();
+            world.rng.choose(&stocked).cloned()?
+        }
+        HumanTaskSource::Replenishment => world
+            .inventory
+            .all_bins()
+            .iter()
+            .filter(|addr| !claimed.contains(addr))
+            .filter_map(|addr| world.inventory.get_quantity(addr).map(|q| (addr, q)))
+            .min_by_key(|(addr, q)| (*q, addr.rack_id.0, addr.level, addr.bin))
+            .map(|(addr, _)| addr.clone())?,
+    };
+
+    let access_node = world.racks.get(&bin.rack_id)?.access_node;
+    Some((bin, access_node))
+}
+
 /// v4: Helper to get position update interval from the active edge traffic policy
 fn get_position_update_interval(world: &World) -> Option<f64> {
     world.position_update_interval_s
@@ -1877,3 +2881,131 @@ impl Default for EventHandler {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use waremax_core::EdgeId;
+    use waremax_entities::{ChargingStation, MaintenanceStation, Robot};
+    use waremax_map::{Edge, Node, NodeType};
+
+    /// A line of nodes 0-1-2-3, 10 m apart, with a charger and a repair bay
+    /// at node 3 and one robot at node 0
+    fn line_world() -> World {
+        let mut world = World::new(7);
+        for i in 0..4 {
+            world.map.add_node(Node::new(
+                NodeId(i),
+                format!("N{}", i),
+                i as f64 * 10.0,
+                0.0,
+                NodeType::Aisle,
+            ));
+        }
+        for i in 0..3 {
+            world
+                .map
+                .add_edge(Edge::new(EdgeId(i), NodeId(i), NodeId(i + 1), 10.0));
+        }
+        let charger = ChargingStationId(0);
+        world.charging_stations.insert(
+            charger,
+            ChargingStation::new(charger, "C0".to_string(), NodeId(3), 1, 1000.0),
+        );
+        let bay = MaintenanceStationId(0);
+        world.maintenance_stations.insert(
+            bay,
+            MaintenanceStation::new(bay, "M0".to_string(), NodeId(3), 1, 60.0),
+        );
+        world
+            .robots
+            .insert(RobotId(0), Robot::new(RobotId(0), NodeId(0), 1.0, 25.0));
+        world
+    }
+
+    /// Start robot 0 repositioning to node 2 and interrupt it with `event`
+    /// once it is on its first edge. Returns the legs it departed on.
+    fn interrupt_repositioning(event: SimEvent) -> (World, Vec<(NodeId, NodeId)>) {
+        let mut world = line_world();
+        let mut kernel = Kernel::new();
+        let mut handler = EventHandler::new();
+        let mut metrics = MetricsCollector::new();
+
+        if let Some(robot) = world.get_robot_mut(RobotId(0)) {
+            robot.set_path(vec![NodeId(0), NodeId(1), NodeId(2)]);
+            robot.repositioning_to = Some(NodeId(2));
+        }
+        let depart = kernel.schedule_now(SimEvent::RobotDepartNode {
+            robot_id: RobotId(0),
+            from_node: NodeId(0),
+            to_node: NodeId(1),
+            edge_id: EdgeId(0),
+        });
+        world.repositioning_departs.insert(RobotId(0), depart);
+
+        let mut legs = Vec::new();
+        let mut interrupt = Some(event);
+        while let Some(scheduled) = kernel.pop_next() {
+            if let SimEvent::RobotDepartNode {
+                from_node, to_node, ..
+            } = scheduled.event
+            {
+                legs.push((from_node, to_node));
+            }
+            handler.handle(&mut kernel, &mut world, &scheduled, &mut metrics);
+
+            // Halfway along the first edge
+            if let Some(event) = interrupt.take() {
+                kernel.schedule_after(SimTime::from_seconds(5.0), event);
+            }
+        }
+        (world, legs)
+    }
+
+    #[test]
+    fn test_low_battery_mid_edge_continues_from_the_arrival_node() {
+        let (world, legs) = interrupt_repositioning(SimEvent::RobotLowBattery {
+            robot_id: RobotId(0),
+            soc: 0.1,
+        });
+
+        // One trip along the line: no second departure from node 0
+        assert_eq!(
+            legs,
+            [
+                (NodeId(0), NodeId(1)),
+                (NodeId(1), NodeId(2)),
+                (NodeId(2), NodeId(3)),
+            ]
+        );
+        let robot = world.get_robot(RobotId(0)).unwrap();
+        assert_eq!(robot.current_node, NodeId(3));
+        assert_eq!(robot.repositioning_to, None);
+        assert_eq!(robot.assigned_charging_station, Some(ChargingStationId(0)));
+        assert_eq!(world.repositioning_stats.trips_canceled, 1);
+        assert_eq!(world.repositioning_stats.trips_completed, 0);
+    }
+
+    #[test]
+    fn test_failure_mid_edge_continues_from_the_arrival_node() {
+        let (world, legs) = interrupt_repositioning(SimEvent::RobotFailure {
+            robot_id: RobotId(0),
+            interrupted_task: None,
+        });
+
+        assert_eq!(
+            legs,
+            [
+                (NodeId(0), NodeId(1)),
+                (NodeId(1), NodeId(2)),
+                (NodeId(2), NodeId(3)),
+            ]
+        );
+        let robot = world.get_robot(RobotId(0)).unwrap();
+        assert_eq!(robot.current_node, NodeId(3));
+        assert_eq!(
+            robot.assigned_maintenance_station,
+            Some(MaintenanceStationId(0))
+        );
+    }
+}
@@ -0,0 +1,91 @@
+//! Human factory - creates human workers and human-only zones from configuration
+
+use waremax_config::{HumanAgentConfig, HumanZoneConfig, ScenarioConfig};
+use waremax_core::{HumanId, NodeId};
+use waremax_entities::{HumanAgent, HumanAgentType, HumanSafetyZone, HumanTaskSource, HumanZone};
+
+use crate::world::World;
+
+/// Add the scenario's human agents and zones to the world
+///
+/// Stop radii left unset fall back to the continuous traffic safety distance.
+pub fn add_humans(world: &mut World, scenario: &ScenarioConfig) {
+    let safety_distance_m = scenario.traffic.continuous.safety_distance_m;
+    let mut next_id = world.humans.len() as u32;
+
+    for config in &scenario.humans {
+        for i in 0..config.count {
+            let human = create_human(HumanId(next_id), i, config, safety_distance_m);
+            world.humans.insert(human.id, human);
+            next_id += 1;
+        }
+    }
+
+    world
+        .human_zones
+        .extend(scenario.human_zones.iter().map(create_human_zone));
+}
+
+fn create_human(
+    id: HumanId,
+    index: u32,
+    config: &HumanAgentConfig,
+    safety_distance_m: f64,
+) -> HumanAgent {
+    let agent_type = match config.agent_type.as_str() {
+        "picker" => HumanAgentType::Picker,
+        "forklift" => HumanAgentType::Forklift,
+        unknown => {
+            eprintln!(
+                "Warning: Unknown human agent type '{}', using picker",
+                unknown
+            );
+            HumanAgentType::Picker
+        }
+    };
+    let task_source = match config.task_source.as_str() {
+        "manual_pick" => HumanTaskSource::ManualPick,
+        "replenishment" => HumanTaskSource::Replenishment,
+        unknown => {
+            eprintln!(
+                "Warning: Unknown human task source '{}', using manual_pick",
+                unknown
+            );
+            HumanTaskSource::ManualPick
+        }
+    };
+    let safety = HumanSafetyZone {
+        stop_radius_m: config.stop_radius_m.unwrap_or(safety_distance_m),
+        slow_radius_m: config.slow_radius_m,
+        slow_factor: config.slow_factor.clamp(0.01, 1.0),
+    };
+    let home_node: u32 = config.home_node.parse().unwrap_or(0);
+
+    HumanAgent::new(
+        id,
+        format!("{}_{}", config.id, index),
+        agent_type,
+        task_source,
+        NodeId(home_node),
+        config
+            .speed_m_s
+            .unwrap_or_else(|| agent_type.default_speed()),
+        safety,
+    )
+    .with_task_timing(config.task_interval_s, config.work_time_s, config.quantity)
+}
+
+fn create_human_zone(config: &HumanZoneConfig) -> HumanZone {
+    HumanZone {
+        id: config.id.clone(),
+        nodes: config
+            .nodes
+            .iter()
+            .filter_map(|n| n.parse::<u32>().ok())
+            .map(NodeId)
+            .collect(),
+        start_s: config.start_min * 60.0,
+        end_s: config.end_min * 60.0,
+        period_s: config.repeat_every_min.map(|m| m * 60.0),
+    }
+}
@@ -0,0 +1,50 @@
+//! Labor factory - creates station staffing and productivity models from configuration
+
+use waremax_config::{ProductivityConfig, StaffingConfig, StationConfig};
+use waremax_entities::{
+    OperatorProductivity, StaffingBreak, StaffingSchedule, StaffingShift, Station,
+};
+
+/// Attach the staffing schedule and productivity model configured for a station
+pub fn apply_labor_model(station: Station, config: &StationConfig) -> Station {
+    let station = station.with_productivity(create_productivity(&config.productivity));
+    match &config.staffing {
+        Some(staffing) => station.with_staffing(create_staffing_schedule(staffing)),
+        None => station,
+    }
+}
+
+/// Create a StaffingSchedule from staffing configuration (minutes to seconds)
+pub fn create_staffing_schedule(config: &StaffingConfig) -> StaffingSchedule {
+    StaffingSchedule {
+        shifts: config
+            .shifts
+            .iter()
+            .map(|s| StaffingShift {
+                start_s: s.start_min * 60.0,
+                end_s: s.end_min * 60.0,
+                operators: s.operators,
+            })
+            .collect(),
+        breaks: config
+            .breaks
+            .iter()
+            .map(|b| StaffingBreak {
+                start_s: b.start_min * 60.0,
+                end_s: b.end_min * 60.0,
+                operators: b.operators,
+            })
+            .collect(),
+        period_s: config.repeat_every_min.map(|m| m * 60.0),
+    }
+}
+
+/// Create an OperatorProductivity from productivity configuration
+pub fn create_productivity(config: &ProductivityConfig) -> OperatorProductivity {
+    OperatorProductivity {
+        learning_initial_multiplier: config.learning_initial_multiplier,
+        learning_half_life_tasks: config.learning_half_life_tasks,
+        fatigue_per_hour: config.fatigue_per_hour,
+        max_fatigue_multiplier: config.max_fatigue_multiplier,
+    }
+}
@@ -4,9 +4,12 @@ pub mod dashboard;
 pub mod distribution_factory;
 pub mod distributions;
 pub mod handlers;
+pub mod human_factory;
+pub mod labor_factory;
 pub mod policy_factory;
 pub mod replay;
 pub mod runner;
+pub mod slotting_factory;
 pub mod snapshot;
 pub mod world;
 
@@ -16,10 +19,17 @@ pub use dashboard::{
 pub use distribution_factory::create_distributions;
 pub use distributions::{ArrivalDistribution, DistributionSet, LinesDistribution, SkuDistribution};
 pub use handlers::EventHandler;
-pub use policy_factory::{create_policies, create_policies_with_traffic};
+pub use human_factory::add_humans;
+pub use labor_factory::apply_labor_model;
+pub use policy_factory::{
+    create_policies, create_policies_with_traffic, create_station_assignment,
+    create_task_allocation, create_traffic_policy,
+};
 pub use replay::{PlaybackState, ReplayEngine, ReplayEvent, ReplayMetadata, ReplaySession};
 pub use runner::SimulationRunner;
+pub use slotting_factory::enable_slotting;
 pub use snapshot::{
-    OrderSnapshot, RobotSnapshot, SnapshotManager, StationSnapshot, TaskSnapshot, WorldSnapshot,
+    OrderSnapshot, RobotSnapshot, Snapshot, SnapshotManager, StationSnapshot, TaskSnapshot,
+    WorldSnapshot,
 };
-pub use world::{PolicySet, World};
+pub use world::{PolicySet, SlottingState, World};
@@ -2,42 +2,57 @@
 
 use crate::world::PolicySet;
 use waremax_config::{PolicyConfig, TrafficConfig};
+use waremax_core::NodeId;
 use waremax_policies::{
-    AdaptiveTrafficPolicy, AuctionPolicy, BatchingPolicy, CoarseTrafficPolicy,
-    ContinuousTrafficPolicy, DueTimePolicy, EdgeTrafficPolicy, FifoPolicy, LeastBusyPolicy,
-    LeastQueuePolicy, NearestRobotPolicy, NearestStationPolicy, NoBatchingPolicy, PriorityPolicy,
-    RerouteOnWaitPolicy, RoundRobinPolicy, StationAssignmentPolicy, StrictPriorityPolicy,
-    TaskAllocationPolicy, TrafficPolicy, WaitAtNodePolicy, WorkloadBalancedPolicy, ZoneBatchingPolicy,
+    AdaptiveTrafficPolicy, AuctionPolicy, BatchingPolicy, BinSelectionPolicy, CoarseTrafficPolicy,
+    ContinuousTrafficPolicy, DemandWeightedPolicy, DueTimePolicy, EdgeTrafficPolicy, FifoPolicy,
+    FirstAvailableBinPolicy, IdlePositioningPolicy, LearnedAllocationPolicy, LeastBusyPolicy,
+    LeastQueuePolicy, LevelAwareBinPolicy, NearestBinPolicy, NearestRobotPolicy,
+    NearestStagingPolicy, NearestStationPolicy, NoBatchingPolicy, PriorityPolicy,
+    RerouteOnWaitPolicy, RoundRobinPolicy, StationAssignmentPolicy, StayPolicy,
+    StrictPriorityPolicy, TaskAllocationPolicy, TrafficPolicy, WaitAtNodePolicy,
+    WorkloadBalancedPolicy, ZoneBatchingPolicy,
 };
 
 /// Create a PolicySet from scenario configuration
-pub fn create_policies(config: &PolicyConfig) -> PolicySet {
-    PolicySet {
-        task_allocation: create_task_allocation(config),
+///
+/// Fails when a `learned` task allocation's weights can't be loaded.
+pub fn create_policies(config: &PolicyConfig) -> Result<PolicySet, String> {
+    Ok(PolicySet {
+        task_allocation: create_task_allocation(config)?,
         station_assignment: create_station_assignment(config),
         batching: create_batching(config),
         priority: create_priority(config),
         traffic: Box::new(WaitAtNodePolicy::new()), // Default traffic policy
         edge_traffic: Box::new(CoarseTrafficPolicy::new()), // Default edge traffic policy
-    }
+        idle_positioning: create_idle_positioning(config),
+        bin_selection: create_bin_selection(config),
+    })
 }
 
 /// Create a PolicySet with traffic config (v1)
+///
+/// Fails when a `learned` task allocation's weights can't be loaded.
 pub fn create_policies_with_traffic(
     config: &PolicyConfig,
     traffic_config: &TrafficConfig,
-) -> PolicySet {
-    PolicySet {
-        task_allocation: create_task_allocation(config),
+) -> Result<PolicySet, String> {
+    Ok(PolicySet {
+        task_allocation: create_task_allocation(config)?,
         station_assignment: create_station_assignment(config),
         batching: create_batching(config),
         priority: create_priority(config),
         traffic: create_traffic_policy(traffic_config),
         edge_traffic: create_edge_traffic_policy(traffic_config),
-    }
+        idle_positioning: create_idle_positioning(config),
+        bin_selection: create_bin_selection(config),
+    })
 }
 
-fn create_traffic_policy(config: &TrafficConfig) -> Box<dyn TrafficPolicy> {
+/// Create the traffic policy named by `config.policy`
+///
+/// v7: Public so running sessions can hot-swap it
+pub fn create_traffic_policy(config: &TrafficConfig) -> Box<dyn TrafficPolicy> {
     match config.policy.as_str() {
         "wait_at_node" => Box::new(WaitAtNodePolicy::new()),
         "reroute_on_wait" => Box::new(RerouteOnWaitPolicy::new(
@@ -75,8 +90,14 @@ fn create_edge_traffic_policy(config: &TrafficConfig) -> Box<dyn EdgeTrafficPoli
     }
 }
 
-fn create_task_allocation(config: &PolicyConfig) -> Box<dyn TaskAllocationPolicy> {
-    match config.task_allocation.alloc_type.as_str() {
+/// Create the task allocation policy named by `config.task_allocation`
+///
+/// v7: Public so running sessions can hot-swap it. Fails when `learned` has
+/// no loadable weights rather than quietly running another policy.
+pub fn create_task_allocation(
+    config: &PolicyConfig,
+) -> Result<Box<dyn TaskAllocationPolicy>, String> {
+    let policy: Box<dyn TaskAllocationPolicy> = match config.task_allocation.alloc_type.as_str() {
         "nearest_robot" => Box::new(NearestRobotPolicy::new()),
         "round_robin" => Box::new(RoundRobinPolicy::new()),
         "least_busy" => Box::new(LeastBusyPolicy::new()),
@@ -85,6 +106,7 @@ fn create_task_allocation(config: &PolicyConfig) -> Box<dyn TaskAllocationPolicy
             config.task_allocation.queue_weight,
         )),
         "workload_balanced" => Box::new(WorkloadBalancedPolicy::default()),
+        "learned" => create_learned_allocation(config)?,
         // Placeholder for the RL control seam: waremax-rl's `RlEnv` overwrites
         // `world.policies.task_allocation` with an `RlPolicy` after construction.
         // Recognized here so configs naming it don't fall through to the warning.
@@ -96,10 +118,28 @@ fn create_task_allocation(config: &PolicyConfig) -> Box<dyn TaskAllocationPolicy
             );
             Box::new(NearestRobotPolicy::new())
         }
-    }
+    };
+    Ok(policy)
 }
 
-fn create_station_assignment(config: &PolicyConfig) -> Box<dyn StationAssignmentPolicy> {
+/// v7: Load the `learned` policy's weights
+fn create_learned_allocation(
+    config: &PolicyConfig,
+) -> Result<Box<dyn TaskAllocationPolicy>, String> {
+    let path = config
+        .task_allocation
+        .weights_path
+        .as_deref()
+        .ok_or("'learned' task allocation needs weights_path")?;
+    let policy = LearnedAllocationPolicy::load(path)
+        .map_err(|e| format!("Could not load learned policy: {}", e))?;
+    Ok(Box::new(policy))
+}
+
+/// Create the station assignment policy named by `config.station_assignment`
+///
+/// v7: Public so an external controller can fall back to it
+pub fn create_station_assignment(config: &PolicyConfig) -> Box<dyn StationAssignmentPolicy> {
     match config.station_assignment.assign_type.as_str() {
         "least_queue" => Box::new(LeastQueuePolicy::default()),
         "nearest_station" => Box::new(NearestStationPolicy::default()),
@@ -127,6 +167,51 @@ fn create_batching(config: &PolicyConfig) -> Box<dyn BatchingPolicy> {
     }
 }
 
+fn create_idle_positioning(config: &PolicyConfig) -> Box<dyn IdlePositioningPolicy> {
+    let idle = &config.idle_positioning;
+    let parking_nodes: Vec<NodeId> = idle.parking_nodes.iter().map(|&n| NodeId(n)).collect();
+    match idle.positioning_type.as_str() {
+        "stay" => Box::new(StayPolicy::new()),
+        "nearest_staging" => Box::new(NearestStagingPolicy::new(parking_nodes)),
+        "demand_weighted" => Box::new(DemandWeightedPolicy::new(
+            parking_nodes,
+            idle.demand_window_s,
+            idle.demand_radius_m,
+        )),
+        unknown => {
+            eprintln!(
+                "Warning: Unknown idle positioning policy '{}', using stay",
+                unknown
+            );
+            Box::new(StayPolicy::new())
+        }
+    }
+}
+
+fn create_bin_selection(config: &PolicyConfig) -> Box<dyn BinSelectionPolicy> {
+    let selection = &config.bin_selection;
+    // `smart_bins` predates bin selection policies and means `nearest`
+    let selection_type = match selection.selection_type.as_str() {
+        "first_available" if config.smart_bins => "nearest",
+        other => other,
+    };
+    match selection_type {
+        "first_available" => Box::new(FirstAvailableBinPolicy::new()),
+        "nearest" => Box::new(NearestBinPolicy::new()),
+        "level_aware" => Box::new(LevelAwareBinPolicy::new(
+            selection.level_weight,
+            selection.consolidation_weight_s,
+        )),
+        unknown => {
+            eprintln!(
+                "Warning: Unknown bin selection policy '{}', using first_available",
+                unknown
+            );
+            Box::new(FirstAvailableBinPolicy::new())
+        }
+    }
+}
+
 fn create_priority(config: &PolicyConfig) -> Box<dyn PriorityPolicy> {
     match config.priority.priority_type.as_str() {
         "strict_priority" => Box::new(StrictPriorityPolicy::new()),
@@ -141,3 +226,42 @@ fn create_priority(config: &PolicyConfig) -> Box<dyn PriorityPolicy> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn learned(weights_path: Option<String>) -> PolicyConfig {
+        let mut config = PolicyConfig::default();
+        config.task_allocation.alloc_type = "learned".to_string();
+        config.task_allocation.weights_path = weights_path;
+        config
+    }
+
+    #[test]
+    fn test_learned_allocation_without_weights_fails() {
+        let err = create_task_allocation(&learned(None)).err().unwrap();
+        assert!(err.contains("weights_path"));
+
+        let missing = learned(Some("does/not/exist.json".to_string()));
+        assert!(create_task_allocation(&missing).is_err());
+    }
+
+    #[test]
+    fn test_learned_allocation_with_bad_weights_fails() {
+        let path = std::env::temp_dir().join(format!("waremax-bad-weights-{}", std::process::id()));
+        std::fs::write(&path, b"not weights").unwrap();
+        let config = learned(Some(path.to_string_lossy().into_owned()));
+        let result = create_policies(&config);
+        std::fs::remove_file(&path).unwrap();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_unknown_allocation_falls_back() {
+        let mut config = PolicyConfig::default();
+        config.task_allocation.alloc_type = "no_such_policy".to_string();
+        let policy = create_task_allocation(&config).unwrap();
+        assert_eq!(policy.name(), "nearest_robot");
+    }
+}
@@ -358,6 +358,11 @@ impl ReplaySession {
         self.replays.get(name)
     }
 
+    /// v7: Get a replay by name mutably, to play or step it on its own
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut ReplayEngine> {
+        self.replays.get_mut(name)
+    }
+
     /// Get all replay names
     pub fn names(&self) -> Vec<&str> {
         self.replays.keys().map(|s| s.as_str()).collect()
@@ -475,4 +480,26 @@ mod tests {
         assert!(session.set_active("run2"));
         assert_eq!(session.names().len(), 2);
     }
+
+    #[test]
+    fn test_replay_session_get_mut() {
+        let mut manager = SnapshotManager::new(60.0);
+        manager.store(WorldSnapshot {
+            timestamp_s: 120.0,
+            robots: HashMap::new(),
+            stations: HashMap::new(),
+            orders: HashMap::new(),
+            tasks: HashMap::new(),
+            pending_tasks: vec![],
+            pending_events: vec![],
+            node_occupancy: HashMap::new(),
+        });
+
+        let mut session = ReplaySession::new();
+        session.add("run1", ReplayEngine::from_snapshots(manager));
+
+        session.get_mut("run1").unwrap().seek(30.0);
+        assert_eq!(session.get("run1").unwrap().current_time(), 30.0);
+        assert!(session.get_mut("missing").is_none());
+    }
 }
@@ -4,11 +4,16 @@ use crate::handlers::EventHandler;
 use crate::world::World;
 use waremax_core::{Kernel, SimEvent, SimTime};
 use waremax_metrics::{
-    BatteryReport, CongestionReport, EdgeCongestion, HeatmapData, MetricsCollector, NodeCongestion,
-    ReliabilityReport, RobotReport, SLAReport, SimulationReport, StationReport,
+    BatteryReport, CongestionReport, EdgeCongestion, HeatmapData, HumanAgentReport, HumanReport,
+    MetricsCollector, NodeCongestion, ReliabilityReport, RepositioningReport, RobotReport,
+    SLAReport, SimulationReport, StationReport,
 };
 
 /// Main simulation runner
+///
+/// v7: Cloning a runner copies the whole simulation (kernel, world, policies
+/// and metrics), so the copy continues exactly as the original would.
+#[derive(Clone)]
 pub struct SimulationRunner {
     kernel: Kernel,
     world: World,
@@ -30,6 +35,25 @@ impl SimulationRunner {
         }
     }
 
+    /// v7: Wrap a kernel, world and metrics advanced elsewhere (e.g. by an
+    /// interactive session) so their reports can be generated
+    pub fn from_parts(
+        kernel: Kernel,
+        world: World,
+        metrics: MetricsCollector,
+        end_time: SimTime,
+        warmup_time: SimTime,
+    ) -> Self {
+        Self {
+            kernel,
+            world,
+            handler: EventHandler::new(),
+            metrics,
+            end_time,
+            warmup_time,
+        }
+    }
+
     /// Initialize the simulation
     pub fn initialize(&mut self) {
         // Schedule first order arrival
@@ -43,6 +67,27 @@ impl SimulationRunner {
             self.world.traffic.enter_node(robot.current_node, robot.id);
         }
 
+        // v7: Apply initial station staffing; each change schedules the next
+        for station_id in self.world.staffed_station_ids() {
+            self.kernel
+                .schedule_now(SimEvent::StationStaffingChange { station_id });
+        }
+
+        // v7: Start human workers and apply human-only zones
+        if !self.world.humans.is_empty() || !self.world.human_zones.is_empty() {
+            self.kernel.schedule_now(SimEvent::HumanZoneUpdate);
+        }
+        for human_id in self.world.human_ids() {
+            crate::handlers::schedule_next_human_task(&mut self.kernel, &mut self.world, human_id);
+        }
+
+        // v7: First re-slotting review; each review schedules the next
+        if let Some(slotting) = &self.world.slotting {
+            let review_time = SimTime::from_seconds(slotting.interval_s);
+            self.kernel
+                .schedule_after(review_time, SimEvent::SlottingReview);
+        }
+
         // v1: Schedule first metrics sample tick
         if self.world.metrics_sample_interval_s > 0.0 {
             let sample_time = SimTime::from_seconds(self.world.metrics_sample_interval_s);
@@ -59,28 +104,33 @@ impl SimulationRunner {
     /// Run the simulation
     pub fn run(&mut self) -> SimulationReport {
         self.initialize();
+        while self.step() {}
+        self.generate_report()
+    }
 
-        while self.kernel.has_events() {
-            if self.kernel.now() >= self.end_time {
-                break;
-            }
-
-            if let Some(event) = self.kernel.pop_next() {
-                // Record metrics after warmup
-                if self.kernel.now() >= self.warmup_time {
-                    self.metrics.record_event(&event);
-                }
+    /// v7: Handle the next event of an initialized run, returning false once
+    /// the run is over. `run` is `initialize`, then `step` until false, then
+    /// `generate_report`.
+    pub fn step(&mut self) -> bool {
+        if !self.kernel.has_events() || self.kernel.now() >= self.end_time {
+            return false;
+        }
 
-                // Handle the event
-                self.handler
-                    .handle(&mut self.kernel, &mut self.world, &event, &mut self.metrics);
+        if let Some(event) = self.kernel.pop_next() {
+            // Record metrics after warmup
+            if self.kernel.now() >= self.warmup_time {
+                self.metrics.record_event(&event);
             }
-        }
 
-        self.generate_report()
+            // Handle the event
+            self.handler
+                .handle(&mut self.kernel, &mut self.world, &event, &mut self.metrics);
+        }
+        true
     }
 
-    fn generate_report(&self) -> SimulationReport {
+    /// Build the report of the run so far
+    pub fn generate_report(&self) -> SimulationReport {
         let duration = self.kernel.now() - self.warmup_time;
         let duration_s = duration.as_seconds().max(0.0);
 
@@ -103,7 +153,10 @@ impl SimulationRunner {
             .world
             .stations
             .values()
-            .map(|s| s.concurrency as f64 * duration_s)
+            .map(|s| {
+                s.staffed_seconds_until(self.kernel.now())
+                    .unwrap_or(s.concurrency as f64 * duration_s)
+            })
             .sum();
         let total_station_busy: f64 = self
             .world
@@ -184,6 +237,49 @@ impl SimulationRunner {
             });
         }
 
+        // v7: Add idle repositioning metrics when a positioning policy is active
+        let repositioning = &self.world.repositioning_stats;
+        if repositioning.trips_started > 0 || self.world.policies.idle_positioning.name() != "stay"
+        {
+            report = report.with_repositioning(RepositioningReport {
+                policy: self.world.policies.idle_positioning.name().to_string(),
+                ..repositioning.clone()
+            });
+        }
+
+        // v7: Human workers and their effect on robot traffic
+        if !self.world.humans.is_empty() {
+            let agents: Vec<HumanAgentReport> = self
+                .world
+                .human_ids()
+                .into_iter()
+                .filter_map(|id| self.world.get_human(id))
+                .map(|h| HumanAgentReport {
+                    id: h.string_id.clone(),
+                    agent_type: h.agent_type.name().to_string(),
+                    tasks_completed: h.tasks_completed,
+                    distance_m: h.distance_m,
+                    walking_time_s: h.walking_time.as_seconds(),
+                    working_time_s: h.working_time.as_seconds(),
+                })
+                .collect();
+            report = report.with_humans(HumanReport {
+                distance_m: agents.iter().map(|a| a.distance_m).sum(),
+                agents,
+                ..self.world.human_stats.clone()
+            });
+        }
+
+        // v7: Picks per rack level
+        if !self.world.level_picks.is_empty() {
+            report = report.with_pick_levels(self.world.level_picks.values().cloned().collect());
+        }
+
+        // v7: Dynamic slotting
+        if let Some(slotting) = self.world.slotting_report() {
+            report = report.with_slotting(slotting);
+        }
+
         report
     }
 
@@ -281,7 +377,10 @@ impl SimulationRunner {
                     (0.0, station.max_queue_length)
                 };
 
-                let capacity_seconds = total_time_s * station.concurrency as f64;
+                // v7: Staffed stations are measured against the operator time actually staffed
+                let staffed_seconds = station.staffed_seconds_until(self.kernel.now());
+                let capacity_seconds =
+                    staffed_seconds.unwrap_or(total_time_s * station.concurrency as f64);
                 let utilization = if capacity_seconds > 0.0 {
                     total_service_time / capacity_seconds
                 } else {
@@ -298,6 +397,7 @@ impl SimulationRunner {
                     avg_queue_length: avg_queue,
                     max_queue_length: max_queue,
                     utilization,
+                    operator_hours: staffed_seconds.map(|s| s / 3600.0),
                 }
             })
             .collect()
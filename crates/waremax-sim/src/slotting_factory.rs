@@ -0,0 +1,59 @@
+//! Slotting factory - enables dynamic re-slotting from configuration
+
+use std::collections::{HashMap, VecDeque};
+use waremax_config::{ScenarioConfig, SlottingConfig};
+use waremax_metrics::SlottingReport;
+use waremax_policies::{AbcSlottingPolicy, AffinitySlottingPolicy, SlottingPolicy};
+
+use crate::world::{SlottingState, World};
+
+/// Enable dynamic slotting if the scenario configures it
+///
+/// Must run after inventory is placed: the initial layout is the baseline
+/// for the before/after pick distance.
+pub fn enable_slotting(world: &mut World, scenario: &ScenarioConfig) {
+    let Some(config) = &scenario.slotting else {
+        return;
+    };
+
+    let policy = create_slotting_policy(config);
+    let station_nodes = world.pick_station_nodes();
+    let initial_sku_distance: HashMap<_, _> = world
+        .skus
+        .ids()
+        .filter_map(|sku_id| {
+            world
+                .sku_slot_distance(sku_id, &station_nodes)
+                .map(|d| (sku_id, d))
+        })
+        .collect();
+
+    world.slotting = Some(SlottingState {
+        stats: SlottingReport {
+            strategy: policy.name().to_string(),
+            ..Default::default()
+        },
+        policy,
+        interval_s: config.interval_min * 60.0,
+        moves_per_hour: config.moves_per_hour,
+        velocity_window_s: config.velocity_window_min * 60.0,
+        move_credit: 0.0,
+        pick_log: VecDeque::new(),
+        sku_picks: HashMap::new(),
+        initial_sku_distance,
+    });
+}
+
+fn create_slotting_policy(config: &SlottingConfig) -> Box<dyn SlottingPolicy> {
+    match config.strategy.as_str() {
+        "abc" => Box::new(AbcSlottingPolicy::new(config.a_fraction)),
+        "affinity" => Box::new(AffinitySlottingPolicy::new(config.a_fraction)),
+        unknown => {
+            eprintln!(
+                "Warning: Unknown slotting strategy '{}', using abc",
+                unknown
+            );
+            Box::new(AbcSlottingPolicy::new(config.a_fraction))
+        }
+    }
+}
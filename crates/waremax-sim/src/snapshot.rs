@@ -236,19 +236,34 @@ impl WorldSnapshot {
     }
 }
 
+/// State captured at a simulated time
+pub trait Snapshot {
+    /// Simulation time the state was captured at (seconds)
+    fn timestamp_s(&self) -> f64;
+}
+
+impl Snapshot for WorldSnapshot {
+    fn timestamp_s(&self) -> f64 {
+        self.timestamp_s
+    }
+}
+
 /// Snapshot manager for taking periodic snapshots during simulation
-pub struct SnapshotManager {
+///
+/// v7: Generic over the snapshot type, so other views of a run (such as a
+/// replay's folded state) can be checkpointed the same way
+pub struct SnapshotManager<S = WorldSnapshot> {
     /// Interval between snapshots in seconds
     pub interval_s: f64,
     /// Last snapshot time
     last_snapshot_time: f64,
     /// Stored snapshots (in-memory)
-    snapshots: Vec<WorldSnapshot>,
+    snapshots: Vec<S>,
     /// Maximum number of snapshots to keep (0 = unlimited)
     max_snapshots: usize,
 }
 
-impl SnapshotManager {
+impl<S: Snapshot> SnapshotManager<S> {
     /// Create a new snapshot manager
     pub fn new(interval_s: f64) -> Self {
         Self {
@@ -275,8 +290,8 @@ impl SnapshotManager {
     }
 
     /// Store a snapshot
-    pub fn store(&mut self, snapshot: WorldSnapshot) {
-        self.last_snapshot_time = snapshot.timestamp_s;
+    pub fn store(&mut self, snapshot: S) {
+        self.last_snapshot_time = snapshot.timestamp_s();
         self.snapshots.push(snapshot);
 
         // Enforce rolling buffer limit
@@ -286,7 +301,7 @@ impl SnapshotManager {
     }
 
     /// Get all snapshots
-    pub fn snapshots(&self) -> &[WorldSnapshot] {
+    pub fn snapshots(&self) -> &[S] {
         &self.snapshots
     }
 
@@ -296,7 +311,7 @@ impl SnapshotManager {
     }
 
     /// Find snapshot nearest to a time
-    pub fn find_nearest(&self, time_s: f64) -> Option<&WorldSnapshot> {
+    pub fn find_nearest(&self, time_s: f64) -> Option<&S> {
         if self.snapshots.is_empty() {
             return None;
         }
@@ -306,7 +321,7 @@ impl SnapshotManager {
         let mut best_diff = f64::MAX;
 
         for (i, snap) in self.snapshots.iter().enumerate() {
-            let diff = (snap.timestamp_s - time_s).abs();
+            let diff = (snap.timestamp_s() - time_s).abs();
             if diff < best_diff {
                 best_diff = diff;
                 best_idx = i;
@@ -317,11 +332,11 @@ impl SnapshotManager {
     }
 
     /// Find snapshot at or before a time (for replay starting point)
-    pub fn find_at_or_before(&self, time_s: f64) -> Option<&WorldSnapshot> {
+    pub fn find_at_or_before(&self, time_s: f64) -> Option<&S> {
         self.snapshots
             .iter()
             .rev()
-            .find(|s| s.timestamp_s <= time_s)
+            .find(|s| s.timestamp_s() <= time_s)
     }
 
     /// Clear all snapshots
@@ -329,14 +344,16 @@ impl SnapshotManager {
         self.snapshots.clear();
         self.last_snapshot_time = -self.interval_s;
     }
+}
 
+impl<S: Serialize> SnapshotManager<S> {
     /// Export all snapshots to JSON array
     pub fn export_json(&self) -> Result<String, serde_json::Error> {
         serde_json::to_string_pretty(&self.snapshots)
     }
 }
 
-impl Default for SnapshotManager {
+impl<S: Snapshot> Default for SnapshotManager<S> {
     fn default() -> Self {
         Self::new(60.0) // Default: 1 minute intervals
     }
@@ -1,25 +1,32 @@
 //! World state container
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use waremax_analysis::AttributionCollector;
 use waremax_core::{
-    ChargingStationId, IdGenerator, MaintenanceStationId, NodeId, OrderId, RackId, RobotId, SimRng,
-    SimTime, SkuId, StationId, TaskId,
+    ChargingStationId, EventId, HumanId, IdGenerator, MaintenanceStationId, NodeId, OrderId,
+    RackId, RobotId, SimRng, SimTime, SkuId, StationId, TaskId,
+};
+use waremax_entities::{
+    ChargingStation, HumanAgent, HumanZone, MaintenanceStation, Order, Robot, Station, Task,
 };
-use waremax_entities::{ChargingStation, MaintenanceStation, Order, Robot, Station, Task};
 use waremax_map::{NodeType, ReservationManager, Router, TrafficManager, WarehouseMap};
-use waremax_metrics::{EventTraceCollector, TimeSeriesCollector};
+use waremax_metrics::{
+    EventTraceCollector, HumanReport, PickLevelReport, RepositioningReport, SlottingReport,
+    TimeSeriesCollector,
+};
 use waremax_policies::{
-    BatchingPolicy, CoarseTrafficPolicy, DeadlockResolver, EdgeTrafficPolicy, LeastQueuePolicy,
-    NearestRobotPolicy, NoBatchingPolicy, PolicyContext, PriorityPolicy,
-    StationAssignmentPolicy, StrictPriorityPolicy, TaskAllocationPolicy, TrafficPolicy,
-    WaitAtNodePolicy, YoungestRobotBacksUp,
+    BatchingPolicy, BinSelectionPolicy, CoarseTrafficPolicy, DeadlockResolver, EdgeTrafficPolicy,
+    FirstAvailableBinPolicy, IdlePositioningContext, IdlePositioningPolicy, LeastQueuePolicy,
+    NearestRobotPolicy, NoBatchingPolicy, PickupBinCandidate, PolicyContext, PriorityPolicy,
+    SlottingPolicy, StationAssignmentPolicy, StayPolicy, StrictPriorityPolicy,
+    TaskAllocationPolicy, TrafficPolicy, WaitAtNodePolicy, YoungestRobotBacksUp,
 };
 use waremax_storage::{BinAddress, Inventory, Rack, Sku, SkuCatalog};
 
 use crate::distributions::DistributionSet;
 
 /// Container for all active policies
+#[derive(Clone)]
 pub struct PolicySet {
     pub task_allocation: Box<dyn TaskAllocationPolicy>,
     pub station_assignment: Box<dyn StationAssignmentPolicy>,
@@ -29,6 +36,10 @@ pub struct PolicySet {
     pub traffic: Box<dyn TrafficPolicy>,
     /// v4: Edge traffic control policy (entry/leave/position tracking)
     pub edge_traffic: Box<dyn EdgeTrafficPolicy>,
+    /// v7: Where robots wait between tasks
+    pub idle_positioning: Box<dyn IdlePositioningPolicy>,
+    /// v7: Which replica bin an assigned pick task draws from
+    pub bin_selection: Box<dyn BinSelectionPolicy>,
 }
 
 impl PolicySet {
@@ -64,11 +75,32 @@ impl Default for PolicySet {
             priority: Box::new(StrictPriorityPolicy::new()),
             traffic: Box::new(WaitAtNodePolicy::new()),
             edge_traffic: Box::new(CoarseTrafficPolicy::new()),
+            idle_positioning: Box::new(StayPolicy::new()),
+            bin_selection: Box::new(FirstAvailableBinPolicy::new()),
         }
     }
 }
 
+/// v7: Dynamic slotting state: observed picks, move budget and statistics
+#[derive(Clone)]
+pub struct SlottingState {
+    pub policy: Box<dyn SlottingPolicy>,
+    pub interval_s: f64,
+    pub moves_per_hour: f64,
+    pub velocity_window_s: f64,
+    /// Unused move budget; accrues each review up to one hour's worth
+    pub move_credit: f64,
+    /// Picks inside the velocity window: (time, SKU, destination station node)
+    pub pick_log: VecDeque<(SimTime, SkuId, NodeId)>,
+    /// Picks per SKU over the whole run, weighting the before/after distances
+    pub sku_picks: HashMap<SkuId, u32>,
+    /// Distance from each SKU's nearest slot to a station in the initial layout
+    pub initial_sku_distance: HashMap<SkuId, f64>,
+    pub stats: SlottingReport,
+}
+
 /// Container for all simulation state
+#[derive(Clone)]
 pub struct World {
     // Random number generator
     pub rng: SimRng,
@@ -137,8 +169,24 @@ pub struct World {
     /// path finding instead of plain shortest path.
     pub congestion_routing: bool,
 
-    /// v6: When true, re-select the pickup bin per assignment to minimize travel.
-    pub smart_bin_selection: bool,
+    /// v7: Robots that completed a task and still need an idle positioning decision
+    pub awaiting_idle_positioning: Vec<RobotId>,
+    /// v7: Not-yet-executed depart events of repositioning trips, canceled
+    /// when the robot is given a task before leaving its node
+    pub repositioning_departs: HashMap<RobotId, EventId>,
+    /// v7: Deadhead repositioning statistics
+    pub repositioning_stats: RepositioningReport,
+
+    /// v7: Human workers and manual vehicles
+    pub humans: HashMap<HumanId, HumanAgent>,
+    /// v7: Zones reserved for humans during time windows
+    pub human_zones: Vec<HumanZone>,
+    /// v7: Robot/human interaction statistics (agents filled in at report time)
+    pub human_stats: HumanReport,
+    /// v7: Picks served per rack level, with the access time charged
+    pub level_picks: BTreeMap<u32, PickLevelReport>,
+    /// v7: Dynamic re-slotting (None when disabled)
+    pub slotting: Option<SlottingState>,
 }
 
 impl World {
@@ -173,10 +221,75 @@ impl World {
             attribution_collector: AttributionCollector::new(),
             position_update_interval_s: None,
             congestion_routing: false,
-            smart_bin_selection: false,
+            awaiting_idle_positioning: Vec::new(),
+            repositioning_departs: HashMap::new(),
+            repositioning_stats: RepositioningReport::default(),
+            humans: HashMap::new(),
+            human_zones: Vec::new(),
+            human_stats: HumanReport::default(),
+            level_picks: BTreeMap::new(),
+            slotting: None,
         }
     }
 
+    /// v7: Get a human agent by ID
+    pub fn get_human(&self, id: HumanId) -> Option<&HumanAgent> {
+        self.humans.get(&id)
+    }
+
+    /// v7: Get a mutable human agent by ID
+    pub fn get_human_mut(&mut self, id: HumanId) -> Option<&mut HumanAgent> {
+        self.humans.get_mut(&id)
+    }
+
+    /// v7: Human agent IDs sorted for deterministic iteration
+    pub fn human_ids(&self) -> Vec<HumanId> {
+        let mut ids: Vec<HumanId> = self.humans.keys().copied().collect();
+        ids.sort_by_key(|id| id.0);
+        ids
+    }
+
+    /// v7: Recompute the robot restrictions humans impose on traffic
+    ///
+    /// Nodes within a human's stop radius become off-limits, nodes within its
+    /// slow radius reduce robot speed, and nodes of active human-only zones
+    /// are reserved.
+    pub fn refresh_human_restrictions(&mut self, current_time: SimTime) {
+        let mut stop_nodes = HashSet::new();
+        let mut slow_nodes: HashMap<NodeId, f64> = HashMap::new();
+        let mut human_edges = HashSet::new();
+
+        for human in self.humans.values() {
+            if let Some((edge, _, _)) = human.on_edge {
+                human_edges.insert(edge);
+            }
+            let occupied = human.occupied_nodes();
+            for node in self.map.nodes.values() {
+                let dist = occupied
+                    .iter()
+                    .map(|n| self.map.euclidean_distance(*n, node.id))
+                    .fold(f64::INFINITY, f64::min);
+                if dist <= human.safety.stop_radius_m {
+                    stop_nodes.insert(node.id);
+                } else if dist <= human.safety.slow_radius_m {
+                    let factor = slow_nodes.entry(node.id).or_insert(1.0);
+                    *factor = factor.min(human.safety.slow_factor);
+                }
+            }
+        }
+
+        let reserved: HashSet<NodeId> = self
+            .human_zones
+            .iter()
+            .filter(|z| z.is_active(current_time))
+            .flat_map(|z| z.nodes.iter().copied())
+            .collect();
+
+        self.traffic
+            .set_human_restrictions(stop_nodes, slow_nodes, human_edges);
+        self.traffic.set_human_reserved_nodes(reserved);
+    }
+
     /// Get a charging station by ID
     pub fn get_charging_station(&self, id: ChargingStationId) -> Option<&ChargingStation> {
         self.charging_stations.get(&id)
@@ -293,6 +406,29 @@ impl World {
             } else {
                 None
             },
+            traffic: Some(&self.traffic),
+        }
+    }
+
+    /// v7: Stations with a staffing schedule, sorted by id
+    pub fn staffed_station_ids(&self) -> Vec<StationId> {
+        let mut ids: Vec<StationId> = self
+            .stations
+            .values()
+            .filter(|s| s.staffing.is_some())
+            .map(|s| s.id)
+            .collect();
+        ids.sort_by_key(|id| id.0);
+        ids
+    }
+
+    /// v7: Create an IdlePositioningContext from current world state
+    pub fn idle_positioning_context(&self, current_time: SimTime) -> IdlePositioningContext<'_> {
+        IdlePositioningContext {
+            current_time,
+            map: &self.map,
+            robots: &self.robots,
+            tasks: &self.tasks,
         }
     }
 
@@ -429,6 +565,101 @@ impl World {
         None
     }
 
+    /// v7: Apply level access times to every rack
+    pub fn set_rack_access_times(&mut self, base_access_time_s: f64, per_level_time_s: f64) {
+        for rack in self.racks.values_mut() {
+            rack.base_access_time_s = base_access_time_s;
+            rack.per_level_time_s = per_level_time_s;
+        }
+    }
+
+    /// v7: Level access time for a bin (0 if its rack is unknown)
+    pub fn bin_access_time(&self, addr: &BinAddress) -> f64 {
+        self.racks
+            .get(&addr.rack_id)
+            .map(|rack| rack.access_time(addr.level))
+            .unwrap_or(0.0)
+    }
+
+    /// v7: Access node of every rack
+    pub fn rack_access_nodes(&self) -> HashMap<RackId, NodeId> {
+        self.racks
+            .iter()
+            .map(|(id, rack)| (*id, rack.access_node))
+            .collect()
+    }
+
+    /// v7: Pick station nodes, sorted for deterministic iteration
+    pub fn pick_station_nodes(&self) -> Vec<NodeId> {
+        let mut nodes: Vec<NodeId> = self.pick_stations().map(|s| s.node).collect();
+        nodes.sort_by_key(|n| n.0);
+        nodes.dedup();
+        nodes
+    }
+
+    /// v7: Mean straight-line distance from a SKU's slots to their nearest
+    /// pick station
+    pub fn sku_slot_distance(&self, sku_id: SkuId, station_nodes: &[NodeId]) -> Option<f64> {
+        let distances: Vec<f64> = self
+            .inventory
+            .find_sku(sku_id)
+            .filter_map(|addr| self.racks.get(&addr.rack_id))
+            .filter_map(|rack| {
+                station_nodes
+                    .iter()
+                    .map(|&s| self.map.euclidean_distance(rack.access_node, s))
+                    .min_by(|a, b| a.total_cmp(b))
+            })
+            .collect();
+        if distances.is_empty() {
+            None
+        } else {
+            Some(distances.iter().sum::<f64>() / distances.len() as f64)
+        }
+    }
+
+    /// v7: Slotting statistics with pick-weighted before/after distances
+    pub fn slotting_report(&self) -> Option<SlottingReport> {
+        let slotting = self.slotting.as_ref()?;
+        let station_nodes = self.pick_station_nodes();
+        // Sorted so the float sums do not depend on HashMap order
+        let mut sku_picks: Vec<(SkuId, u32)> =
+            slotting.sku_picks.iter().map(|(s, c)| (*s, *c)).collect();
+        sku_picks.sort_by_key(|(s, _)| s.0);
+
+        let mut picks = 0.0;
+        let mut before = 0.0;
+        let mut after = 0.0;
+        for (sku_id, count) in sku_picks {
+            let initial = slotting.initial_sku_distance.get(&sku_id).copied();
+            let current = self.sku_slot_distance(sku_id, &station_nodes);
+            if let (Some(initial), Some(current)) = (initial, current) {
+                picks += count as f64;
+                before += initial * count as f64;
+                after += current * count as f64;
+            }
+        }
+        let mut report = slotting.stats.clone();
+        if picks > 0.0 {
+            report.avg_pick_distance_before_m = before / picks;
+            report.avg_pick_distance_after_m = after / picks;
+        }
+        Some(report)
+    }
+
+    /// v7: In-stock replica bins for a SKU as bin selection candidates
+    pub fn pickup_bin_candidates(&self, sku_id: SkuId, quantity: u32) -> Vec<PickupBinCandidate> {
+        self.find_sku_bins(sku_id, quantity)
+            .into_iter()
+            .map(|(address, access_node)| PickupBinCandidate {
+                quantity: self.inventory.get_quantity(&address).unwrap_or(0),
+                access_time_s: self.bin_access_time(&address),
+                address,
+                access_node,
+            })
+            .collect()
+    }
+
     /// All in-stock replica bins for a SKU, with their access nodes. Used by
     /// smart bin selection to choose among replicas.
     pub fn find_sku_bins(&self, sku_id: SkuId, quantity: u32) -> Vec<(BinAddress, NodeId)> {
@@ -118,6 +118,39 @@ impl Inventory {
         Ok(())
     }
 
+    /// Exchange the contents of two bins (re-slotting). Either bin may be
+    /// empty; SKU locations keep their order so lookups stay deterministic.
+    pub fn swap_bins(&mut self, a: &BinAddress, b: &BinAddress) -> Result<(), InventoryError> {
+        for addr in [a, b] {
+            if !self.all_bins.contains(addr) {
+                return Err(InventoryError::BinNotFound(addr.clone()));
+            }
+        }
+
+        let slot_a = self.bins.remove(a);
+        let slot_b = self.bins.remove(b);
+        let mut skus: Vec<SkuId> = slot_a.iter().chain(&slot_b).map(|s| s.sku_id).collect();
+        skus.dedup();
+        for sku_id in skus {
+            if let Some(locations) = self.sku_locations.get_mut(&sku_id) {
+                for loc in locations.iter_mut() {
+                    if loc == a {
+                        *loc = b.clone();
+                    } else if loc == b {
+                        *loc = a.clone();
+                    }
+                }
+            }
+        }
+        if let Some(slot) = slot_a {
+            self.bins.insert(b.clone(), slot);
+        }
+        if let Some(slot) = slot_b {
+            self.bins.insert(a.clone(), slot);
+        }
+        Ok(())
+    }
+
     pub fn total_quantity(&self, sku_id: SkuId) -> u32 {
         self.sku_locations
             .get(&sku_id)
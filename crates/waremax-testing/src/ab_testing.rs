@@ -8,7 +8,7 @@ use statrs::distribution::{ContinuousCDF, StudentsT};
 use std::collections::HashMap;
 
 use crate::comparison::AggregatedStats;
-use crate::runner::{BatchRunner, RunResult};
+use crate::runner::{BatchRunner, LabeledScenarios, RunResult};
 use waremax_config::ScenarioConfig;
 
 /// Configuration for an A/B test
@@ -190,39 +190,46 @@ impl ABTestRunner {
 
     /// Run the A/B test
     pub fn run(&self) -> ABTestResult {
+        let (baseline_scenarios, variant_scenarios) = self.scenarios();
+
+        let baseline_results = BatchRunner::new(baseline_scenarios).run();
+        let variant_results = BatchRunner::new(variant_scenarios).run();
+
+        self.analyze(&baseline_results, &variant_results)
+    }
+
+    /// The baseline and variant runs of the test, one per replication seed
+    pub fn scenarios(&self) -> (LabeledScenarios, LabeledScenarios) {
         // Generate seeds for replications
         let seeds: Vec<u64> = (0..self.config.replications)
             .map(|i| 1000 + i as u64)
             .collect();
 
-        // Run baseline scenarios
-        let baseline_scenarios: Vec<(String, ScenarioConfig)> = seeds
-            .iter()
-            .map(|&seed| {
-                let mut config = self.config.baseline_config.clone();
-                config.seed = seed;
-                (format!("baseline_seed={}", seed), config)
-            })
-            .collect();
-
-        let baseline_runner = BatchRunner::new(baseline_scenarios);
-        let baseline_results = baseline_runner.run();
-
-        // Run variant scenarios
-        let variant_scenarios: Vec<(String, ScenarioConfig)> = seeds
-            .iter()
-            .map(|&seed| {
-                let mut config = self.config.variant_config.clone();
-                config.seed = seed;
-                (format!("variant_seed={}", seed), config)
-            })
-            .collect();
+        let with_seeds = |label: &str, base: &ScenarioConfig| -> LabeledScenarios {
+            seeds
+                .iter()
+                .map(|&seed| {
+                    let mut config = base.clone();
+                    config.seed = seed;
+                    (format!("{}_seed={}", label, seed), config)
+                })
+                .collect()
+        };
 
-        let variant_runner = BatchRunner::new(variant_scenarios);
-        let variant_results = variant_runner.run();
+        (
+            with_seeds("baseline", &self.config.baseline_config),
+            with_seeds("variant", &self.config.variant_config),
+        )
+    }
 
+    /// Compute the test's statistics from finished baseline and variant runs
+    pub fn analyze(
+        &self,
+        baseline_results: &[RunResult],
+        variant_results: &[RunResult],
+    ) -> ABTestResult {
         // Extract metrics and compute statistics
-        let metrics = self.extract_and_compare(&baseline_results, &variant_results);
+        let metrics = self.extract_and_compare(baseline_results, variant_results);
 
         // Determine conclusion
         let conclusion = self.determine_conclusion(&metrics);
@@ -534,6 +541,24 @@ mod tests {
         assert!((p - 1.0).abs() < f64::EPSILON);
     }
 
+    #[test]
+    fn test_ab_scenarios_share_seeds() {
+        let config = ABTestConfig::new(
+            crate::ScenarioPreset::Minimal.config(),
+            crate::ScenarioPreset::Minimal.config(),
+        )
+        .replications(3);
+        let (baseline, variant) = ABTestRunner::new(config).scenarios();
+
+        let seeds = |runs: &[(String, ScenarioConfig)]| -> Vec<u64> {
+            runs.iter().map(|(_, c)| c.seed).collect()
+        };
+        assert_eq!(seeds(&baseline), vec![1000, 1001, 1002]);
+        assert_eq!(seeds(&baseline), seeds(&variant));
+        assert_eq!(baseline[0].0, "baseline_seed=1000");
+        assert_eq!(variant[2].0, "variant_seed=1002");
+    }
+
     #[test]
     fn test_required_sample_size() {
         let n = ABTestRunner::required_sample_size(
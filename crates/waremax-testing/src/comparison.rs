@@ -56,7 +56,12 @@ impl AggregatedStats {
         let n = samples.len();
 
         let mean = data.mean().unwrap_or(0.0);
-        let std_dev = data.std_dev().unwrap_or(0.0);
+        // A single sample has no spread, not an undefined one
+        let std_dev = if n > 1 {
+            data.std_dev().unwrap_or(0.0)
+        } else {
+            0.0
+        };
         let min = data.min();
         let max = data.max();
         let p50 = data.median();
@@ -455,6 +460,16 @@ mod tests {
         assert_eq!(stats.mean, 0.0);
     }
 
+    #[test]
+    fn test_aggregated_stats_single_sample() {
+        let stats = AggregatedStats::from_samples(&[7.0]);
+
+        assert_eq!(stats.n, 1);
+        assert_eq!(stats.std_dev, 0.0);
+        assert_eq!(stats.ci_95_lower, 7.0);
+        assert_eq!(stats.ci_95_upper, 7.0);
+    }
+
     #[test]
     fn test_metric_comparison() {
         let baseline = vec![10.0, 11.0, 10.5, 10.2, 10.8];
@@ -4,11 +4,12 @@
 //! SweepGenerator for parameter sweeps.
 
 use waremax_config::{
-    ArrivalProcess, BatchingConfig, BatteryConfig, ChargingStationConfig, ConsumptionConfig,
-    DueTimeConfig, FailureConfig, LinesConfig, MaintenanceStationConfig, MapRef, MetricsConfig,
-    OrderConfig, PolicyConfig, PriorityConfig, RobotConfig, RobotMaintenanceConfig, RoutingConfig,
-    ScenarioConfig, ServiceTimeConfig, SimulationParams, SkuPopularity, StationAssignmentConfig,
-    StationConfig, StorageRef, TaskAllocationConfig, TrafficConfig,
+    ArrivalProcess, BatchingConfig, BatteryConfig, BinSelectionConfig, ChargingStationConfig,
+    ConsumptionConfig, DueTimeConfig, FailureConfig, IdlePositioningConfig, LinesConfig,
+    MaintenanceStationConfig, MapRef, MetricsConfig, OrderConfig, PolicyConfig, PriorityConfig,
+    ProductivityConfig, RobotConfig, RobotMaintenanceConfig, RoutingConfig, ScenarioConfig,
+    ServiceTimeConfig, SimulationParams, SkuPopularity, StationAssignmentConfig, StationConfig,
+    StorageRef, TaskAllocationConfig, TrafficConfig,
 };
 
 /// Builder for creating ScenarioConfig programmatically
@@ -386,6 +387,8 @@ impl ScenarioBuilder {
                 } else {
                     ServiceTimeConfig::constant(self.service_time_base, self.service_time_per_item)
                 },
+                staffing: None,
+                productivity: ProductivityConfig::default(),
             });
         }
 
@@ -428,6 +431,8 @@ impl ScenarioBuilder {
             },
             storage: StorageRef {
                 file: "inline".to_string(),
+                base_access_time_s: 0.0,
+                per_level_time_s: 0.0,
             },
             robots: RobotConfig {
                 count: self.robot_count,
@@ -473,6 +478,7 @@ impl ScenarioBuilder {
                     alloc_type: self.task_allocation_policy,
                     travel_weight: 1.0,
                     queue_weight: 0.5,
+                    weights_path: None,
                 },
                 station_assignment: StationAssignmentConfig {
                     assign_type: self.station_assignment_policy,
@@ -490,6 +496,8 @@ impl ScenarioBuilder {
                 },
                 smart_bins: false,
                 inventory_skus: None,
+                idle_positioning: IdlePositioningConfig::default(),
+                bin_selection: BinSelectionConfig::default(),
             },
             traffic: TrafficConfig {
                 policy: self.traffic_policy,
@@ -513,6 +521,9 @@ impl ScenarioBuilder {
                 ..MetricsConfig::default()
             },
             maintenance_stations,
+            humans: Vec::new(),
+            human_zones: Vec::new(),
+            slotting: None,
         }
     }
 
@@ -618,6 +629,41 @@ impl SweepGenerator {
         self
     }
 
+    /// Add the sweep described by `spec`, written `param:v1,v2,...` with
+    /// `param` one of `robots`, `order_rate`, `stations` or `task_allocation`
+    /// (e.g. `robots:5,10,15`)
+    pub fn with_spec(self, spec: &str) -> Result<Self, String> {
+        let Some((param, values)) = spec.split_once(':') else {
+            return Err(format!(
+                "Invalid sweep '{}'. Use 'param:v1,v2,v3' (e.g. 'robots:5,10,15')",
+                spec
+            ));
+        };
+        let values: Vec<&str> = values.split(',').map(str::trim).collect();
+
+        fn parse_all<T: std::str::FromStr>(param: &str, values: &[&str]) -> Result<Vec<T>, String> {
+            values
+                .iter()
+                .map(|v| {
+                    v.parse().map_err(|_| {
+                        format!("Invalid value '{}' for sweep parameter '{}'", v, param)
+                    })
+                })
+                .collect()
+        }
+
+        match param.trim() {
+            "robots" => Ok(self.sweep_robot_count(&parse_all(param, &values)?)),
+            "order_rate" => Ok(self.sweep_order_rate(&parse_all(param, &values)?)),
+            "stations" => Ok(self.sweep_station_count(&parse_all(param, &values)?)),
+            "task_allocation" => Ok(self.sweep_task_allocation(&values)),
+            unknown => Err(format!(
+                "Unknown sweep parameter: {}. Supported: robots, order_rate, stations, task_allocation",
+                unknown
+            )),
+        }
+    }
+
     /// Sweep over random seeds
     pub fn sweep_seeds(mut self, count: u32) -> Self {
         let seeds: Vec<u64> = (0..count).map(|i| 42 + i as u64 * 1000).collect();
@@ -775,6 +821,31 @@ mod tests {
         assert_eq!(config.policies.task_allocation.alloc_type, "auction");
     }
 
+    #[test]
+    fn test_sweep_generator_from_spec() {
+        let sweep = SweepGenerator::new(ScenarioBuilder::new())
+            .with_spec("order_rate:30, 60")
+            .unwrap();
+        let scenarios = sweep.generate();
+        assert_eq!(scenarios.len(), 2);
+        assert_eq!(scenarios[1].0, "order_rate=60");
+
+        let sweep = SweepGenerator::new(ScenarioBuilder::new())
+            .with_spec("task_allocation:nearest_robot,auction")
+            .unwrap();
+        assert_eq!(sweep.scenario_count(), 2);
+
+        assert!(SweepGenerator::new(ScenarioBuilder::new())
+            .with_spec("robots:5,ten")
+            .is_err());
+        assert!(SweepGenerator::new(ScenarioBuilder::new())
+            .with_spec("speed:1,2")
+            .is_err());
+        assert!(SweepGenerator::new(ScenarioBuilder::new())
+            .with_spec("robots")
+            .is_err());
+    }
+
     #[test]
     fn test_sweep_generator_single_dimension() {
         let base = ScenarioBuilder::new();
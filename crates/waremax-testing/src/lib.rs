@@ -22,4 +22,4 @@ pub use benchmark::{
 pub use comparison::{AggregatedStats, ComparisonReport, MetricComparison, ScenarioComparator};
 pub use generator::{ScenarioBuilder, SweepDimension, SweepGenerator};
 pub use presets::ScenarioPreset;
-pub use runner::{BatchRunner, RunResult};
+pub use runner::{BatchRunner, LabeledScenarios, RunResult};
@@ -6,6 +6,7 @@
 use rayon::prelude::*;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Instant;
 
 use waremax_config::ScenarioConfig;
@@ -17,7 +18,7 @@ use waremax_entities::{
 use waremax_map::{Edge, Node, NodeType, Router, TrafficManager, WarehouseMap};
 use waremax_metrics::SimulationReport;
 use waremax_metrics::TimeSeriesCollector;
-use waremax_sim::{SimulationRunner, World};
+use waremax_sim::{add_humans, apply_labor_model, enable_slotting, SimulationRunner, World};
 
 /// Result of a single simulation run
 #[derive(Clone)]
@@ -61,6 +62,9 @@ impl RunResult {
     }
 }
 
+/// Labeled scenarios, as taken by [`BatchRunner::new`]
+pub type LabeledScenarios = Vec<(String, ScenarioConfig)>;
+
 /// Runs multiple simulations in parallel
 pub struct BatchRunner {
     scenarios: Vec<(String, ScenarioConfig)>,
@@ -94,20 +98,28 @@ impl BatchRunner {
 
         self.scenarios
             .par_iter()
-            .map(|(label, config)| {
-                let start = Instant::now();
-                let report = run_simulation_from_config(config);
-                let duration_ms = start.elapsed().as_millis() as u64;
-
-                let config_hash = hash_config(config);
-
-                RunResult {
-                    label: label.clone(),
-                    config_hash,
-                    seed: config.seed,
-                    report,
-                    duration_ms,
+            .map(|(label, config)| run_labeled(label, config))
+            .collect()
+    }
+
+    /// Run all scenarios in parallel, passing each finished run to
+    /// `on_result`. Once `cancelled` is set, runs not yet started are
+    /// skipped; the results of the runs that finished are returned in
+    /// scenario order.
+    pub fn run_with_progress(
+        &self,
+        cancelled: &AtomicBool,
+        on_result: impl Fn(&RunResult) + Sync,
+    ) -> Vec<RunResult> {
+        self.scenarios
+            .par_iter()
+            .filter_map(|(label, config)| {
+                if cancelled.load(Ordering::Relaxed) {
+                    return None;
                 }
+                let result = run_labeled(label, config);
+                on_result(&result);
+                Some(result)
             })
             .collect()
     }
@@ -129,21 +141,7 @@ impl BatchRunner {
         // Run in parallel
         all_scenarios
             .par_iter()
-            .map(|(label, config)| {
-                let start = Instant::now();
-                let report = run_simulation_from_config(config);
-                let duration_ms = start.elapsed().as_millis() as u64;
-
-                let config_hash = hash_config(config);
-
-                RunResult {
-                    label: label.clone(),
-                    config_hash,
-                    seed: config.seed,
-                    report,
-                    duration_ms,
-                }
-            })
+            .map(|(label, config)| run_labeled(label, config))
             .collect()
     }
 
@@ -153,6 +151,21 @@ impl BatchRunner {
     }
 }
 
+/// Run one labeled scenario, timing it
+fn run_labeled(label: &str, config: &ScenarioConfig) -> RunResult {
+    let start = Instant::now();
+    let report = run_simulation_from_config(config);
+    let duration_ms = start.elapsed().as_millis() as u64;
+
+    RunResult {
+        label: label.to_string(),
+        config_hash: hash_config(config),
+        seed: config.seed,
+        report,
+        duration_ms,
+    }
+}
+
 /// Run a simulation from a ScenarioConfig and return the report
 pub fn run_simulation_from_config(config: &ScenarioConfig) -> SimulationReport {
     let world = build_world_from_config(config);
@@ -165,6 +178,9 @@ pub fn run_simulation_from_config(config: &ScenarioConfig) -> SimulationReport {
 }
 
 /// Build a World from ScenarioConfig
+///
+/// Panics if the scenario's policies can't be built, e.g. a `learned`
+/// task allocation whose weights file doesn't load.
 pub fn build_world_from_config(scenario: &ScenarioConfig) -> World {
     let mut world = World::new(scenario.seed);
 
@@ -331,6 +347,7 @@ pub fn build_world_from_config(scenario: &ScenarioConfig) -> World {
             None, // queue_capacity
             service_time,
         );
+        let station = apply_labor_model(station, station_cfg);
         world.stations.insert(StationId(idx as u32), station);
     }
 
@@ -388,8 +405,8 @@ pub fn build_world_from_config(scenario: &ScenarioConfig) -> World {
 
     // Set up policies
     world.policies =
-        waremax_sim::create_policies_with_traffic(&scenario.policies, &scenario.traffic);
-    world.smart_bin_selection = scenario.policies.smart_bins;
+        waremax_sim::create_policies_with_traffic(&scenario.policies, &scenario.traffic)
+            .unwrap_or_else(|e| panic!("Invalid policies: {}", e));
 
     // Set up distributions
     world.distributions = waremax_sim::create_distributions(&scenario.orders);
@@ -398,6 +415,12 @@ pub fn build_world_from_config(scenario: &ScenarioConfig) -> World {
     // replicas per SKU, giving the pickup-bin choice real spatial leverage.
     world.init_demo_inventory(scenario.policies.inventory_skus.unwrap_or(100));
 
+    // v7: Level access times for generated racks
+    world.set_rack_access_times(
+        scenario.storage.base_access_time_s,
+        scenario.storage.per_level_time_s,
+    );
+
     // Set metrics sample interval
     world.metrics_sample_interval_s = scenario.metrics.sample_interval_s;
     world.time_series = TimeSeriesCollector::new(scenario.metrics.sample_interval_s);
@@ -407,6 +430,12 @@ pub fn build_world_from_config(scenario: &ScenarioConfig) -> World {
         world.due_time_offset_min = Some(due_cfg.minutes);
     }
 
+    // v7: Human workers and manual vehicles
+    add_humans(&mut world, scenario);
+
+    // v7: Dynamic re-slotting (baseline layout is the inventory placed above)
+    enable_slotting(&mut world, scenario);
+
     world
 }
 
@@ -423,6 +452,11 @@ fn hash_config(config: &ScenarioConfig) -> u64 {
         .to_bits()
         .hash(&mut hasher);
     config.policies.task_allocation.alloc_type.hash(&mut hasher);
+    config
+        .policies
+        .task_allocation
+        .weights_path
+        .hash(&mut hasher);
 
     hasher.finish()
 }
@@ -440,6 +474,41 @@ mod tests {
         assert!(report.duration_s > 0.0);
     }
 
+    #[test]
+    fn test_cloned_runner_continues_like_the_original() {
+        let mut config = ScenarioPreset::MaintenanceTest.config();
+        config.simulation.warmup_minutes = 0.0;
+        config.simulation.duration_minutes = 30.0;
+        let straight = run_simulation_from_config(&config);
+
+        let mut runner = SimulationRunner::new(build_world_from_config(&config), 30.0, 0.0);
+        runner.initialize();
+        while runner.now() < waremax_core::SimTime::from_minutes(10.0) {
+            assert!(runner.step());
+        }
+        let mut copy = runner.clone();
+        while runner.step() {}
+        while copy.step() {}
+
+        let report = |r: &SimulationReport| serde_json::to_value(r).unwrap();
+        assert_eq!(
+            report(&copy.generate_report()),
+            report(&runner.generate_report())
+        );
+        // Tied congestion entries and per-robot float sums follow hash order,
+        // which differs between separately built worlds
+        let outcome = |r: &SimulationReport| {
+            (
+                r.events_processed,
+                r.orders_completed,
+                r.avg_cycle_time_s,
+                r.p95_cycle_time_s,
+            )
+        };
+        assert_eq!(outcome(&runner.generate_report()), outcome(&straight));
+        assert!(straight.orders_completed > 0);
+    }
+
     #[test]
     fn test_batch_runner_single() {
         let config = ScenarioPreset::Minimal.config();
@@ -465,6 +534,30 @@ mod tests {
         assert_eq!(results.len(), 2);
     }
 
+    #[test]
+    fn test_batch_runner_progress_and_cancel() {
+        use std::sync::atomic::AtomicUsize;
+
+        let scenarios: Vec<_> = (1..=3)
+            .map(|seed| {
+                let config = ScenarioPreset::Minimal.builder().seed(seed).build();
+                (format!("seed{}", seed), config)
+            })
+            .collect();
+        let runner = BatchRunner::new(scenarios);
+
+        let done = AtomicUsize::new(0);
+        let results = runner.run_with_progress(&AtomicBool::new(false), |_| {
+            done.fetch_add(1, Ordering::Relaxed);
+        });
+        assert_eq!(done.load(Ordering::Relaxed), 3);
+        let labels: Vec<_> = results.iter().map(|r| r.label.as_str()).collect();
+        assert_eq!(labels, vec!["seed1", "seed2", "seed3"]);
+
+        let cancelled = runner.run_with_progress(&AtomicBool::new(true), |_| {});
+        assert!(cancelled.is_empty());
+    }
+
     #[test]
     fn test_run_result_metrics() {
         let config = ScenarioPreset::Quick.config();
@@ -495,4 +588,199 @@ mod tests {
         let seeds: Vec<u64> = results.iter().map(|r| r.seed).collect();
         assert_eq!(seeds, vec![1, 2, 3]);
     }
+
+    #[test]
+    fn test_idle_positioning_reports_deadhead() {
+        let mut config = ScenarioPreset::Quick.config();
+        config.policies.idle_positioning.positioning_type = "nearest_staging".to_string();
+        config.policies.idle_positioning.parking_nodes = vec![12, 24];
+        let report = run_simulation_from_config(&config);
+
+        let repositioning = report.repositioning.expect("repositioning section");
+        assert_eq!(repositioning.policy, "nearest_staging");
+        assert!(repositioning.trips_started > 0);
+        assert!(repositioning.deadhead_distance_m > 0.0);
+        assert!(
+            repositioning.trips_completed + repositioning.trips_canceled
+                <= repositioning.trips_started
+        );
+        assert!(report.orders_completed > 0);
+    }
+
+    #[test]
+    fn test_stay_policy_omits_repositioning() {
+        let report = run_simulation_from_config(&ScenarioPreset::Minimal.config());
+        assert!(report.repositioning.is_none());
+    }
+
+    #[test]
+    fn test_station_staffing_closes_and_opens_stations() {
+        use waremax_config::{BreakConfig, ShiftConfig, StaffingConfig};
+
+        let mut config = ScenarioPreset::Quick.config();
+        // S0 is not staffed within the run; S1 has two operators, one of
+        // whom takes a break during minute 1-2
+        config.stations[0].staffing = Some(StaffingConfig {
+            repeat_every_min: None,
+            shifts: vec![ShiftConfig {
+                start_min: 10.0,
+                end_min: 20.0,
+                operators: 1,
+            }],
+            breaks: vec![],
+        });
+        config.stations[1].staffing = Some(StaffingConfig {
+            repeat_every_min: None,
+            shifts: vec![ShiftConfig {
+                start_min: 0.0,
+                end_min: 20.0,
+                operators: 2,
+            }],
+            breaks: vec![BreakConfig {
+                start_min: 1.0,
+                end_min: 2.0,
+                operators: 1,
+            }],
+        });
+
+        let world = build_world_from_config(&config);
+        let mut runner = SimulationRunner::new(
+            world,
+            config.simulation.duration_minutes,
+            config.simulation.warmup_minutes,
+        );
+        runner.run();
+        let report = runner.generate_full_report(false, true, false, false);
+
+        let stations = report.station_reports.expect("station reports");
+        let station = |id: &str| stations.iter().find(|s| s.string_id == id).unwrap();
+        assert_eq!(station("S0").orders_served, 0);
+        assert_eq!(station("S0").operator_hours, Some(0.0));
+        assert!(station("S1").orders_served > 0);
+        let s1_hours = station("S1").operator_hours.expect("staffed station");
+        // 6 minutes (warmup + duration) of two operators, minus the 1-minute break
+        assert!((s1_hours - (2.0 * 6.0 - 1.0) / 60.0).abs() < 0.01);
+        assert!(report.orders_completed > 0);
+    }
+
+    #[test]
+    fn test_humans_share_aisles_with_robots() {
+        use waremax_config::{HumanAgentConfig, HumanZoneConfig};
+
+        let mut config = ScenarioPreset::Quick.config();
+        config.humans = vec![
+            HumanAgentConfig {
+                id: "picker".to_string(),
+                agent_type: "picker".to_string(),
+                count: 2,
+                home_node: "0".to_string(),
+                speed_m_s: None,
+                task_source: "manual_pick".to_string(),
+                task_interval_s: 10.0,
+                work_time_s: 5.0,
+                quantity: 1,
+                stop_radius_m: None,
+                slow_radius_m: 5.0,
+                slow_factor: 0.5,
+            },
+            HumanAgentConfig {
+                id: "forklift".to_string(),
+                agent_type: "forklift".to_string(),
+                count: 1,
+                home_node: "0".to_string(),
+                speed_m_s: None,
+                task_source: "replenishment".to_string(),
+                task_interval_s: 30.0,
+                work_time_s: 10.0,
+                quantity: 10,
+                stop_radius_m: Some(3.0),
+                slow_radius_m: 6.0,
+                slow_factor: 0.5,
+            },
+        ];
+        config.human_zones = vec![HumanZoneConfig {
+            id: "replen_aisle".to_string(),
+            nodes: vec!["1".to_string()],
+            start_min: 1.0,
+            end_min: 2.0,
+            repeat_every_min: None,
+        }];
+
+        let world = build_world_from_config(&config);
+        let mut runner = SimulationRunner::new(
+            world,
+            config.simulation.duration_minutes,
+            config.simulation.warmup_minutes,
+        );
+        let report = runner.run();
+
+        let humans = report.humans.expect("human report");
+        assert_eq!(humans.agents.len(), 3);
+        assert!(humans.tasks_completed > 0);
+        assert!(humans.distance_m > 0.0);
+        assert!(humans.robot_slowdowns > 0);
+        assert!(report.orders_completed > 0);
+    }
+
+    #[test]
+    fn test_level_aware_bin_selection_favors_low_levels() {
+        let run = |selection_type: &str| {
+            let mut config = ScenarioPreset::Quick.config();
+            config.storage.base_access_time_s = 2.0;
+            config.storage.per_level_time_s = 15.0;
+            config.policies.inventory_skus = Some(20);
+            config.policies.bin_selection.selection_type = selection_type.to_string();
+
+            let world = build_world_from_config(&config);
+            let mut runner = SimulationRunner::new(
+                world,
+                config.simulation.duration_minutes,
+                config.simulation.warmup_minutes,
+            );
+            runner.run()
+        };
+        let avg_access = |report: &SimulationReport| {
+            let levels = report.pick_levels.as_ref().expect("pick levels");
+            let picks: u32 = levels.iter().map(|l| l.picks).sum();
+            let access: f64 = levels.iter().map(|l| l.access_time_s).sum();
+            assert!(picks > 0);
+            access / picks as f64
+        };
+
+        let first_available = run("first_available");
+        let level_aware = run("level_aware");
+
+        // Every pick pays at least the base access time
+        assert!(avg_access(&first_available) >= 2.0);
+        assert!(avg_access(&level_aware) < avg_access(&first_available));
+        assert!(level_aware.orders_completed > 0);
+    }
+
+    #[test]
+    fn test_dynamic_slotting_moves_fast_movers_closer() {
+        let mut config = ScenarioPreset::Quick.config();
+        config.policies.inventory_skus = Some(20);
+        config.slotting = Some(waremax_config::SlottingConfig {
+            interval_min: 2.0,
+            moves_per_hour: 60.0,
+            velocity_window_min: 10.0,
+            ..Default::default()
+        });
+
+        let world = build_world_from_config(&config);
+        let mut runner = SimulationRunner::new(
+            world,
+            config.simulation.duration_minutes,
+            config.simulation.warmup_minutes,
+        );
+        let report = runner.run();
+
+        let slotting = report.slotting.expect("slotting report");
+        assert_eq!(slotting.strategy, "abc");
+        assert!(slotting.reviews > 0);
+        assert!(slotting.moves_completed > 0);
+        assert!(slotting.moves_completed <= slotting.moves_planned);
+        assert!(slotting.avg_pick_distance_after_m < slotting.avg_pick_distance_before_m);
+        assert!(report.orders_completed > 0);
+    }
 }
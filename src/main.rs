@@ -255,6 +255,10 @@ enum Commands {
         /// Open browser automatically
         #[arg(long, default_value = "true")]
         open: bool,
+
+        /// Scenario library sessions can select by name
+        #[arg(long)]
+        scenario_dir: Option<PathBuf>,
     },
 }
 
@@ -380,8 +384,12 @@ fn main() {
         }
 
         // v6: Interactive Web UI
-        Commands::Ui { port, open } => {
-            run_ui(port, open);
+        Commands::Ui {
+            port,
+            open,
+            scenario_dir,
+        } => {
+            run_ui(port, open, scenario_dir);
         }
     }
 }
@@ -771,6 +779,7 @@ fn build_world_from_scenario(
             station_cfg.queue_capacity,
             service_time,
         );
+        let station = waremax_sim::apply_labor_model(station, station_cfg);
         world.stations.insert(StationId(idx as u32), station);
     }
 
@@ -865,7 +874,13 @@ fn build_world_from_scenario(
 
     // Create policies from config (including traffic policy)
     world.policies =
-        waremax_sim::create_policies_with_traffic(&scenario.policies, &scenario.traffic);
+        match waremax_sim::create_policies_with_traffic(&scenario.policies, &scenario.traffic) {
+            Ok(policies) => policies,
+            Err(e) => {
+                eprintln!("Error creating policies: {}", e);
+                std::process::exit(1);
+            }
+        };
     let (alloc, station, batch, prio, traffic, edge_traffic) = world.policies.all_names();
     println!("Policies:");
     println!("  Task Allocation: {}", alloc);
@@ -891,6 +906,24 @@ fn build_world_from_scenario(
     // Initialize demo inventory with 20 SKUs
     world.init_demo_inventory(20);
 
+    // v7: Level access times for generated racks
+    world.set_rack_access_times(
+        scenario.storage.base_access_time_s,
+        scenario.storage.per_level_time_s,
+    );
+
+    // v7: Human workers and manual vehicles
+    waremax_sim::add_humans(&mut world, scenario);
+    if !world.humans.is_empty() {
+        println!("Human Workers: {}", world.humans.len());
+    }
+
+    // v7: Dynamic re-slotting
+    waremax_sim::enable_slotting(&mut world, scenario);
+    if let Some(slotting) = &world.slotting {
+        println!("Dynamic Slotting: {}", slotting.policy.name());
+    }
+
     world
 }
 
@@ -1139,40 +1172,14 @@ fn run_sweep(
         }
     };
 
-    // Parse sweep specification (e.g., "robots:5,10,15,20" or "order_rate:30,60,90")
-    let parts: Vec<&str> = sweep_spec.split(':').collect();
-    if parts.len() != 2 {
-        eprintln!("Invalid sweep format. Use 'param:v1,v2,v3' (e.g., 'robots:5,10,15')");
-        std::process::exit(1);
-    }
-
-    let param = parts[0];
-    let values: Vec<&str> = parts[1].split(',').collect();
-
-    // Create sweep generator from base config
-    let mut generator = SweepGenerator::from_config(base_config);
-
-    match param {
-        "robots" => {
-            let vals: Vec<u32> = values.iter().filter_map(|v| v.parse().ok()).collect();
-            generator = generator.sweep_robot_count(&vals);
-        }
-        "order_rate" => {
-            let vals: Vec<f64> = values.iter().filter_map(|v| v.parse().ok()).collect();
-            generator = generator.sweep_order_rate(&vals);
-        }
-        "stations" => {
-            let vals: Vec<u32> = values.iter().filter_map(|v| v.parse().ok()).collect();
-            generator = generator.sweep_station_count(&vals);
-        }
-        _ => {
-            eprintln!(
-                "Unknown sweep parameter: {}. Supported: robots, order_rate, stations",
-                param
-            );
+    // Sweep specification (e.g., "robots:5,10,15,20" or "order_rate:30,60,90")
+    let mut generator = match SweepGenerator::from_config(base_config).with_spec(sweep_spec) {
+        Ok(generator) => generator,
+        Err(e) => {
+            eprintln!("{}", e);
             std::process::exit(1);
         }
-    }
+    };
 
     // Add seed replications
     generator = generator.sweep_seeds(replications);
@@ -1807,12 +1814,18 @@ fn run_analyze(
 // v6: Interactive Web UI
 // =============================================================================
 
-fn run_ui(port: u16, _open: bool) {
+fn run_ui(port: u16, _open: bool, scenario_dir: Option<PathBuf>) {
     let config = waremax_api::ApiConfig {
         session_timeout_secs: 30 * 60,
         max_sessions: 100,
         cors_origins: vec![],
         request_id_header: true,
+        scenario_dir,
+        job_dir: None,
+        job_workers: 2,
+        data_dir: None,
+        api_keys: Vec::new(),
+        audit_log: None,
     };
 
     let rt = tokio::runtime::Runtime::new().expect("Failed to create Tokio runtime");